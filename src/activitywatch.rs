@@ -0,0 +1,206 @@
+//! ActivityWatch連携モジュール - バケット形式（JSON）のエクスポート/インポート
+
+use crate::database::{format_timestamp, CaptureRecord};
+use crate::error::ImportError;
+use crate::export::TimeEntry;
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// ウィンドウ監視バケットの種別
+const CURRENTWINDOW_TYPE: &str = "currentwindow";
+
+/// エクスポート時のクライアント名
+const CLIENT_NAME: &str = "habit-tracker";
+
+/// ActivityWatchのエクスポートファイル（`/api/0/export` と同じ構造）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AwExport {
+    pub buckets: BTreeMap<String, AwBucket>,
+}
+
+/// バケット
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AwBucket {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub bucket_type: String,
+    #[serde(default)]
+    pub client: String,
+    #[serde(default)]
+    pub hostname: String,
+    #[serde(default)]
+    pub created: String,
+    #[serde(default)]
+    pub events: Vec<AwEvent>,
+}
+
+/// イベント（`duration` は秒）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AwEvent {
+    pub timestamp: String,
+    pub duration: f64,
+    #[serde(default)]
+    pub data: AwWindowData,
+}
+
+/// currentwindowイベントのデータ
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AwWindowData {
+    #[serde(default)]
+    pub app: String,
+    #[serde(default)]
+    pub title: String,
+}
+
+/// 時間エントリをActivityWatchのバケット形式で書き出す
+pub fn write_activitywatch_json<W: Write>(
+    writer: &mut W,
+    entries: &[TimeEntry],
+    hostname: &str,
+) -> Result<(), serde_json::Error> {
+    let bucket_id = format!("aw-watcher-window_{}", hostname);
+    let bucket = AwBucket {
+        id: bucket_id.clone(),
+        bucket_type: CURRENTWINDOW_TYPE.to_string(),
+        client: CLIENT_NAME.to_string(),
+        hostname: hostname.to_string(),
+        created: Utc::now().to_rfc3339(),
+        events: entries
+            .iter()
+            .map(|entry| AwEvent {
+                timestamp: entry.start.with_timezone(&Utc).to_rfc3339(),
+                duration: entry.duration_seconds as f64,
+                data: AwWindowData {
+                    app: entry.app.clone(),
+                    title: entry.description.clone(),
+                },
+            })
+            .collect(),
+    };
+
+    let export = AwExport {
+        buckets: BTreeMap::from([(bucket_id, bucket)]),
+    };
+    serde_json::to_writer_pretty(writer, &export)
+}
+
+/// ActivityWatchのエクスポートファイルを読み込みキャプチャ列に変換
+///
+/// currentwindowバケットのイベントのみを対象とする。画像のないキャプチャとして
+/// インターバルごとに展開するため、既存のレポートでそのまま集計できる
+pub fn read_activitywatch_json<R: Read>(
+    reader: R,
+    interval_seconds: u64,
+) -> Result<Vec<CaptureRecord>, ImportError> {
+    let export: AwExport = serde_json::from_reader(reader)?;
+    let interval = interval_seconds.max(1);
+    let mut records = Vec::new();
+
+    for bucket in export
+        .buckets
+        .values()
+        .filter(|b| b.bucket_type == CURRENTWINDOW_TYPE)
+    {
+        for event in &bucket.events {
+            let start = DateTime::parse_from_rfc3339(&event.timestamp)
+                .map_err(|_| ImportError::InvalidTimestamp(event.timestamp.clone()))?
+                .with_timezone(&Local);
+
+            // 端数はインターバル1回分に切り上げる
+            let count = (event.duration.max(0.0) / interval as f64).ceil().max(1.0) as i64;
+            for i in 0..count {
+                let captured_at = start + Duration::seconds(i * interval as i64);
+                records.push(CaptureRecord {
+                    id: None,
+                    captured_at: format_timestamp(&captured_at),
+                    image_path: None,
+                    active_app: event.data.app.clone(),
+                    window_title: event.data.title.clone(),
+                    is_paused: false,
+                    is_private: false,
+                    ocr_text: None,
+                    interval_seconds: None,
+                    hostname: (!bucket.hostname.is_empty()).then(|| bucket.hostname.clone()),
+                    tracker_version: None,
+                    bundle_id: None,
+                    activity_type: None,
+                    working_directory: None,
+                });
+            }
+        }
+    }
+
+    records.sort_by(|a, b| a.captured_at.cmp(&b.captured_at));
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_export_then_import_round_trip() {
+        let entries = vec![TimeEntry {
+            start: DateTime::parse_from_rfc3339("2025-01-10T10:00:00+09:00").unwrap(),
+            duration_seconds: 180,
+            app: "VS Code".to_string(),
+            description: "main.rs".to_string(),
+        }];
+
+        let mut output = Vec::new();
+        write_activitywatch_json(&mut output, &entries, "mac").unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let event = &json["buckets"]["aw-watcher-window_mac"]["events"][0];
+        assert_eq!(event["timestamp"], "2025-01-10T01:00:00+00:00");
+        assert_eq!(event["duration"], 180.0);
+        assert_eq!(event["data"]["app"], "VS Code");
+
+        let records = read_activitywatch_json(output.as_slice(), 60).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].window_title, "main.rs");
+        assert_eq!(records[0].hostname.as_deref(), Some("mac"));
+        let second = DateTime::parse_from_rfc3339(&records[1].captured_at).unwrap();
+        assert_eq!(
+            second
+                .with_timezone(&FixedOffset::east_opt(9 * 3600).unwrap())
+                .to_rfc3339(),
+            "2025-01-10T10:01:00+09:00"
+        );
+    }
+
+    #[test]
+    fn test_import_skips_non_window_buckets() {
+        let json = r#"{
+            "buckets": {
+                "aw-watcher-afk_mac": {
+                    "id": "aw-watcher-afk_mac",
+                    "type": "afkstatus",
+                    "events": [{"timestamp": "2025-01-10T01:00:00Z", "duration": 60, "data": {"status": "afk"}}]
+                },
+                "aw-watcher-window_mac": {
+                    "id": "aw-watcher-window_mac",
+                    "type": "currentwindow",
+                    "events": [{"timestamp": "2025-01-10T01:00:00.123Z", "duration": 10.5, "data": {"app": "Slack", "title": "general"}}]
+                }
+            }
+        }"#;
+
+        let records = read_activitywatch_json(json.as_bytes(), 60).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].active_app, "Slack");
+    }
+
+    #[test]
+    fn test_import_invalid_timestamp() {
+        let json = r#"{"buckets": {"w": {"id": "w", "type": "currentwindow",
+            "events": [{"timestamp": "yesterday", "duration": 60, "data": {}}]}}}"#;
+        assert!(matches!(
+            read_activitywatch_json(json.as_bytes(), 60),
+            Err(ImportError::InvalidTimestamp(_))
+        ));
+    }
+}
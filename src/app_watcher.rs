@@ -0,0 +1,146 @@
+//! アプリ切り替え監視モジュール - NSWorkspaceの通知をイベントとして記録
+
+use crate::database::{format_timestamp, AppEvent};
+use crate::db_writer::DatabaseWriter;
+use crate::pause_control::PauseControl;
+use chrono::Local;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use tracing::{debug, info, warn};
+
+/// didActivateApplication通知を購読し、切り替えのたびに
+/// `アプリ名\tバンドルID` を1行出力し続けるJXAスクリプト
+///
+/// osascriptの標準出力はバッファされるため、NSFileHandleで直接書き込む
+const WATCH_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+const out = $.NSFileHandle.fileHandleWithStandardOutput;
+const clean = (value) => (value || '').replace(/[\t\n]/g, ' ');
+const emit = (app) => {
+  if (!app || app.isNil()) return;
+  const line = clean(app.localizedName.js) + '\t' + clean(app.bundleIdentifier.js) + '\n';
+  out.writeData($(line).dataUsingEncoding($.NSUTF8StringEncoding));
+};
+ObjC.registerSubclass({
+  name: 'HabitTrackerAppObserver',
+  methods: {
+    'appActivated:': {
+      types: ['void', ['id']],
+      implementation: (notification) => {
+        emit(notification.userInfo.objectForKey($.NSWorkspaceApplicationKey));
+      },
+    },
+  },
+});
+const observer = $.HabitTrackerAppObserver.alloc.init;
+$.NSWorkspace.sharedWorkspace.notificationCenter.addObserverSelectorNameObject(
+  observer, 'appActivated:', $.NSWorkspaceDidActivateApplicationNotification, $());
+emit($.NSWorkspace.sharedWorkspace.frontmostApplication);
+$.NSRunLoop.currentRunLoop.run;
+"#;
+
+/// アプリ切り替え監視
+///
+/// 監視用のosascriptを子プロセスとして起動し、読み取りスレッドから
+/// DB書き込みスレッド経由でapp_eventsテーブルに書き込む。ドロップ時に子プロセスを終了する。
+/// プライベートなウィンドウかはこの時点では判定できないため、アプリ名はログに出さない
+pub struct AppWatcher {
+    child: Child,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl AppWatcher {
+    /// 監視を開始
+    ///
+    /// 一時停止中の切り替えは記録しない
+    pub fn spawn(writer: DatabaseWriter, pause_file: PathBuf) -> io::Result<Self> {
+        let mut child = Command::new("osascript")
+            .arg("-l")
+            .arg("JavaScript")
+            .arg("-e")
+            .arg(WATCH_SCRIPT)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("標準出力を取得できません"))?;
+
+        let reader = thread::spawn(move || {
+            let pause_control = PauseControl::new(pause_file);
+
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let Some((app_name, bundle_id)) = parse_event_line(&line) else {
+                    continue;
+                };
+                if pause_control.is_paused() {
+                    continue;
+                }
+
+                debug!("アプリ切り替えを記録します");
+                let event = AppEvent {
+                    id: None,
+                    occurred_at: format_timestamp(&Local::now()),
+                    app_name,
+                    bundle_id,
+                };
+                if let Err(e) = writer.execute(move |db| db.insert_app_event(&event)) {
+                    warn!("アプリ切り替えイベントの記録失敗: {}", e);
+                }
+            }
+        });
+
+        info!("アプリ切り替えの監視を開始しました");
+        Ok(Self {
+            child,
+            reader: Some(reader),
+        })
+    }
+}
+
+impl Drop for AppWatcher {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        // 子プロセスの終了で標準出力が閉じ、読み取りスレッドも終わる
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// `アプリ名\tバンドルID` 形式の行を解析
+fn parse_event_line(line: &str) -> Option<(String, String)> {
+    let (app_name, bundle_id) = line.split_once('\t').unwrap_or((line, ""));
+    let app_name = app_name.trim();
+    if app_name.is_empty() {
+        return None;
+    }
+    Some((app_name.to_string(), bundle_id.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_line() {
+        assert_eq!(
+            parse_event_line("Slack\tcom.tinyspeck.slackmacgap"),
+            Some(("Slack".to_string(), "com.tinyspeck.slackmacgap".to_string()))
+        );
+        assert_eq!(
+            parse_event_line("Finder"),
+            Some(("Finder".to_string(), String::new()))
+        );
+        assert_eq!(parse_event_line("\tcom.example"), None);
+        assert_eq!(parse_event_line(""), None);
+    }
+}
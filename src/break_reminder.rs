@@ -0,0 +1,171 @@
+//! 休憩リマインダーモジュール - 連続作業時間の計測と休憩を促すタイミングの判定
+
+use crate::config::BreakReminderConfig;
+use chrono::{DateTime, Local, Timelike};
+
+/// キャプチャの間隔がこれより空いたら、その間は休憩していたとみなす（秒）
+pub const BREAK_GAP_SECONDS: i64 = 300;
+
+/// 連続して作業している区間
+#[derive(Debug)]
+struct Streak {
+    /// カテゴリごとに数える場合のカテゴリ（PC操作全般なら `None`）
+    category: Option<String>,
+    started_at: DateTime<Local>,
+    last_seen: DateTime<Local>,
+}
+
+/// 休憩リマインダー
+///
+/// キャプチャのたびに呼び出し、同じカテゴリ（またはPC操作全般）が
+/// 設定の時間だけ続いたら通知するメッセージを返す。通知した時点から数え直す
+#[derive(Debug)]
+pub struct BreakReminder {
+    config: BreakReminderConfig,
+    streak: Option<Streak>,
+}
+
+impl BreakReminder {
+    /// 新しいBreakReminderを作成
+    pub fn new(config: BreakReminderConfig) -> Self {
+        Self {
+            config,
+            streak: None,
+        }
+    }
+
+    /// 一時停止・スリープなどで作業が途切れたことを記録
+    pub fn reset(&mut self) {
+        self.streak = None;
+    }
+
+    /// キャプチャを記録し、休憩を促すならそのメッセージを返す
+    pub fn observe(&mut self, now: DateTime<Local>, category: &str) -> Option<String> {
+        let category = self.config.per_category.then(|| category.to_string());
+        let continued = self.streak.as_ref().is_some_and(|streak| {
+            streak.category == category
+                && (now - streak.last_seen).num_seconds() <= BREAK_GAP_SECONDS
+        });
+        if !continued {
+            self.streak = Some(Streak {
+                category,
+                started_at: now,
+                last_seen: now,
+            });
+            return None;
+        }
+
+        let streak = self.streak.as_mut()?;
+        streak.last_seen = now;
+        let minutes = (now - streak.started_at).num_minutes();
+        if minutes < self.config.minutes as i64 || self.config.is_quiet(now.hour()) {
+            return None;
+        }
+
+        streak.started_at = now;
+        let message = match streak.category {
+            Some(ref category) => format!(
+                "「{}」が{}分続いています。休憩しませんか？",
+                category, minutes
+            ),
+            None => format!("PC操作が{}分続いています。休憩しませんか？", minutes),
+        };
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn config(per_category: bool) -> BreakReminderConfig {
+        BreakReminderConfig {
+            minutes: 30,
+            per_category,
+            quiet_start_hour: None,
+            quiet_end_hour: None,
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 10, hour, minute, 0)
+            .unwrap()
+    }
+
+    /// 1分ごとに同じカテゴリでキャプチャし、通知した時刻（分）を返す
+    fn notified_minutes(reminder: &mut BreakReminder, minutes: u32) -> Vec<u32> {
+        (0..=minutes)
+            .filter(|&m| {
+                reminder
+                    .observe(at(10, 0) + Duration::minutes(m as i64), "開発")
+                    .is_some()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_remind_after_threshold() {
+        let mut reminder = BreakReminder::new(config(false));
+        // 通知した時点から数え直す
+        assert_eq!(notified_minutes(&mut reminder, 65), vec![30, 60]);
+    }
+
+    #[test]
+    fn test_reset_on_gap_and_pause() {
+        let mut reminder = BreakReminder::new(config(false));
+        assert!(reminder.observe(at(10, 0), "開発").is_none());
+        // 5分を超えて空いたら休憩していたとみなし、10:25から数え直す
+        for minute in (25..55).step_by(5) {
+            assert!(reminder.observe(at(10, minute), "開発").is_none());
+        }
+        assert!(reminder.observe(at(10, 55), "開発").is_some());
+
+        reminder.reset();
+        assert!(reminder.observe(at(11, 0), "開発").is_none());
+        assert!(reminder.observe(at(11, 4), "開発").is_none());
+    }
+
+    #[test]
+    fn test_per_category() {
+        let mut reminder = BreakReminder::new(config(true));
+        for minute in 0..20 {
+            assert!(reminder.observe(at(10, minute), "開発").is_none());
+        }
+        // カテゴリが変わったら数え直す
+        for minute in 20..50 {
+            assert!(reminder.observe(at(10, minute), "ブラウザ").is_none());
+        }
+        assert_eq!(
+            reminder.observe(at(10, 50), "ブラウザ").as_deref(),
+            Some("「ブラウザ」が30分続いています。休憩しませんか？")
+        );
+
+        // PC操作全般ならカテゴリが変わっても続けて数える
+        let mut reminder = BreakReminder::new(config(false));
+        for minute in 0..30 {
+            let category = if minute % 2 == 0 {
+                "開発"
+            } else {
+                "ブラウザ"
+            };
+            assert!(reminder.observe(at(10, minute), category).is_none());
+        }
+        assert_eq!(
+            reminder.observe(at(10, 30), "ブラウザ").as_deref(),
+            Some("PC操作が30分続いています。休憩しませんか？")
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let mut reminder = BreakReminder::new(BreakReminderConfig {
+            quiet_start_hour: Some(10),
+            quiet_end_hour: Some(11),
+            ..config(false)
+        });
+        // 静音時間帯は通知せず、明けてから通知する
+        assert_eq!(notified_minutes(&mut reminder, 65), vec![60]);
+    }
+}
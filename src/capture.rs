@@ -1,152 +1,1387 @@
 //! キャプチャループモジュール
 
-use crate::config::Config;
-use crate::database::{CaptureRecord, Database};
-use crate::error::CaptureError;
-use crate::image_store::ImageStore;
-use crate::metadata::Metadata;
-use crate::ocr;
-use crate::pause_control::PauseControl;
-
-use chrono::Local;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
-use tracing::{error, info, warn};
+use crate::app_watcher::AppWatcher;
+use crate::break_reminder::BreakReminder;
+use crate::config::{CliArgs, Config};
+use crate::database::{
+    format_timestamp, Alert, AlertKind, CaptureRecord, Database, EndReason, InputMetrics,
+    PerfMetrics, SessionKind, SessionRecord, WalCheckpointer, ACTIVITY_TYPE_MEDIA,
+};
+use crate::db_writer::DatabaseWriter;
+use crate::distraction::DistractionMonitor;
+use crate::error::{CaptureError, ConfigError, ObsidianError};
+use crate::error_reporter::ErrorReporter;
+use crate::highlight;
+use crate::hooks::Hook;
+use crate::idle::{IdleMonitor, IDLE_EXTRA_KEY};
+use crate::image_store::{self, CapturedImage, ImageStore};
+use crate::input_monitor::InputMonitor;
+use crate::instance_lock::InstanceLock;
+use crate::metadata::{Metadata, SCREEN_RECORDING_SETTINGS};
+use crate::metadata_provider::{
+    self, MetadataCollector, KEY_ACTIVE_APP, KEY_BUNDLE_ID, KEY_WINDOW_TITLE, KEY_WORKING_DIRECTORY,
+};
+use crate::notification::{notify, NOTIFICATION_TITLE};
+use crate::obsidian::DailyNote;
+use crate::ocr::{self, OcrBackend, OcrResult};
+use crate::ocr_worker::{OcrRequest, OcrWorker, OCR_QUEUE_CAPACITY};
+use crate::pause_control::{PauseControl, PauseMode};
+use crate::pomodoro::{Pomodoro, PomodoroControl, PomodoroPhase, PomodoroStatus};
+use crate::process_monitor::ProcessMonitor;
+use crate::redact::{TitleRedactor, REDACTED};
+use crate::report::{logical_date, Report};
+use crate::runtime::Control;
+use crate::watchdog::{Watchdog, WatchdogAction};
+
+use chrono::{DateTime, Local, NaiveDateTime, Offset, TimeZone};
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError, Sender};
+use tokio::task::{self, JoinHandle};
+use tracing::{debug, error, info, info_span, warn};
 
 /// キャプチャループ
 pub struct CaptureLoop {
     config: Config,
+    /// 設定の再読み込み時にも優先するCLI引数
+    cli_args: CliArgs,
+    /// 読み取り用の接続（書き込みは `writer` に集約する）
     db: Database,
+    /// DB書き込み専用スレッド（OCRワーカー・アプリ切り替え監視と共有する）
+    writer: DatabaseWriter,
     image_store: ImageStore,
     pause_control: PauseControl,
-    running: Arc<AtomicBool>,
+    /// 停止・設定の再読み込み・即時キャプチャの要求（シグナル処理のタスクと共有する）
+    control: Control,
+    /// 実行中のOCRワーカーへの依頼の送信側（`None` ならOCRはキャプチャ中に行う）
+    ocr_queue: Option<Sender<OcrRequest>>,
+    /// 入力監視（入力活動量の記録も放置の監視も無効か、開始できなければ `None`）
+    input_monitor: Option<InputMonitor>,
+    /// 前回サイクルの入力イベント累積数
+    last_input_counters: Cell<Option<InputMetrics>>,
+    /// プロセス監視（無効なら `None`）
+    process_monitor: Option<RefCell<ProcessMonitor>>,
+    /// OCRバックエンド（無効なら `None`、OCRワーカーと共有する）
+    ocr_backend: Option<Arc<dyn OcrBackend>>,
+    /// 現在のキャプチャ間隔（秒、アダプティブ時に変化する）
+    current_interval: Cell<u64>,
+    /// 前回キャプチャのアプリ名とウィンドウタイトル
+    last_screen: RefCell<Option<(String, String)>>,
+    /// キャプチャ中に行うOCR（OCRワーカーがなければ使う）
+    ocr_worker: RefCell<OcrWorker>,
+    /// 記録するマシンのホスト名（起動時に1回だけ取得）
+    hostname: String,
+    /// 休憩リマインダー（無効なら `None`）
+    break_reminder: Option<RefCell<BreakReminder>>,
+    /// 脱線の監視（無効なら `None`）
+    distraction_monitor: Option<RefCell<DistractionMonitor>>,
+    /// 放置の監視（無効なら `None`）
+    idle_monitor: Option<RefCell<IdleMonitor>>,
+    /// 保存前のウィンドウタイトルのマスク
+    title_redactor: TitleRedactor,
+    /// 実行中のポモドーロの状態ファイル
+    pomodoro_control: PomodoroControl,
+    /// 前回確認したポモドーロ・時刻・状態
+    last_pomodoro: RefCell<Option<(Pomodoro, DateTime<Local>, PomodoroStatus)>>,
+    /// WALの自動チェックポイント
+    wal_checkpointer: RefCell<WalCheckpointer>,
+    /// 繰り返し発生するエラーの集約（OCRワーカーと共有する）
+    error_reporter: Arc<Mutex<ErrorReporter>>,
+    /// 設定で有効にしたメタデータプロバイダ
+    metadata_collector: MetadataCollector,
 }
 
 impl CaptureLoop {
     /// 新しいCaptureLoopを作成
     pub fn new(config: Config) -> Result<Self, CaptureError> {
         let db = Database::open(&config.db_path)?;
-        let image_store = ImageStore::new(config.images_dir.clone(), config.jpeg_quality);
+        let writer = DatabaseWriter::spawn(&config.db_path)?;
+        let image_store = image_store(&config);
         let pause_control = PauseControl::new(config.pause_file.clone());
-        let running = Arc::new(AtomicBool::new(true));
+        let pomodoro_control = PomodoroControl::new(config.pomodoro_file.clone());
+        let process_monitor = (config.process_snapshot_count > 0)
+            .then(|| RefCell::new(ProcessMonitor::new(config.process_snapshot_count)));
+        let ocr_backend = config.ocr_backend.create().map(Arc::from);
+        let ocr_worker = OcrWorker::new(&db)?;
+        let initial_interval = initial_interval(&config);
+        let break_reminder = break_reminder(&config);
+        let distraction_monitor = distraction_monitor(&config);
+        let idle_monitor = idle_monitor(&config);
+        let input_monitor = input_monitor(&config);
+        let title_redactor = title_redactor(&config)?;
+        let wal_checkpointer = wal_checkpointer(&config);
+        let error_reporter = ErrorReporter::new(config.error_notify_threshold);
+        let metadata_collector = metadata_collector(&config);
 
         Ok(Self {
             config,
+            cli_args: CliArgs::default(),
             db,
+            writer,
             image_store,
             pause_control,
-            running,
+            control: Control::default(),
+            ocr_queue: None,
+            input_monitor,
+            last_input_counters: Cell::new(None),
+            process_monitor,
+            ocr_backend,
+            current_interval: Cell::new(initial_interval),
+            last_screen: RefCell::new(None),
+            ocr_worker: RefCell::new(ocr_worker),
+            hostname: Metadata::hostname(),
+            break_reminder,
+            distraction_monitor,
+            idle_monitor,
+            title_redactor,
+            pomodoro_control,
+            last_pomodoro: RefCell::new(None),
+            wal_checkpointer: RefCell::new(wal_checkpointer),
+            error_reporter: Arc::new(Mutex::new(error_reporter)),
+            metadata_collector,
         })
     }
 
-    /// シグナルハンドラーをセットアップ
-    pub fn setup_signal_handler(&self) -> Result<(), CaptureError> {
-        let running = Arc::clone(&self.running);
+    /// 起動時に指定したCLI引数を設定（設定の再読み込み時にもファイルより優先する）
+    pub fn with_cli_args(mut self, cli_args: CliArgs) -> Self {
+        self.cli_args = cli_args;
+        self
+    }
 
-        ctrlc::set_handler(move || {
-            info!("シャットダウンシグナルを受信しました");
-            running.store(false, Ordering::SeqCst);
-        })
-        .map_err(|e| CaptureError::SignalHandlerError(e.to_string()))?;
+    /// 停止・設定の再読み込み・即時キャプチャの要求を受け取る制御フラグを設定
+    pub fn with_control(mut self, control: Control) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// キャプチャループを実行
+    ///
+    /// 待機は非同期に行い、停止・再読み込み・即時キャプチャの要求ですぐに再開する。
+    /// スクリーンショットやosascriptなどを同期的に待つ処理は `block_in_place` で実行し、
+    /// 同じランタイムのシグナル処理やOCRワーカーのタスクを止めないようにする
+    pub async fn run(&mut self) -> Result<(), CaptureError> {
+        // 多重起動していれば、セッションの記録などに触れる前に終了する
+        let _lock = InstanceLock::acquire(&self.config.pid_file)?;
+        // 権限がないとscreencaptureは壁紙だけの画像を保存して成功するため、先に確認する
+        ensure_screen_recording()?;
+
+        if self.config.adaptive_interval {
+            info!(
+                "キャプチャループを開始します（間隔: {}-{}秒で自動調整）",
+                self.config.min_interval_seconds, self.config.max_interval_seconds
+            );
+        } else {
+            info!(
+                "キャプチャループを開始します（間隔: {}秒）",
+                self.config.interval_seconds
+            );
+        }
+
+        let session_id = task::block_in_place(|| self.begin_session())?;
+        // 前回の実行で一時フォルダに残った画像があれば保存先に移す
+        task::block_in_place(|| self.flush_images());
+        let ocr_worker = self.start_ocr_worker()?;
+        let now = Local::now();
+        let mut state = LoopState {
+            // ドロップ時に監視プロセスを終了する
+            app_watcher: self.start_app_watcher(),
+            watchdog: Watchdog::new(self.config.watchdog_threshold),
+            current_day: logical_date(&now, self.config.day_boundary_hour),
+            current_hour: highlight::hour_start(&now),
+        };
 
+        while self.control.is_running() {
+            match task::block_in_place(|| self.step(&mut state)) {
+                NextWait::Interval => {
+                    // 設定の再読み込みを要求されたら反映してから待ち直す
+                    while self.wait_interval().await {
+                        task::block_in_place(|| {
+                            self.reload_config(&mut state.app_watcher, &mut state.watchdog)
+                        });
+                    }
+                }
+                NextWait::Cooldown => self.cooldown().await,
+            }
+        }
+
+        // 途中で終了した時間帯の代表画像も選んでおく
+        task::block_in_place(|| self.update_highlight(state.current_hour));
+        self.stop_ocr_worker(ocr_worker).await;
+        task::block_in_place(|| {
+            self.flush_images();
+            let ended_at = format_timestamp(&Local::now());
+            self.writer
+                .execute(move |db| db.end_session(session_id, &ended_at, EndReason::Stopped))
+        })?;
+        info!("キャプチャループを終了します");
         Ok(())
     }
 
-    /// キャプチャループを実行
-    pub fn run(&self) -> Result<(), CaptureError> {
-        info!(
-            "キャプチャループを開始します（間隔: {}秒）",
-            self.config.interval_seconds
-        );
+    /// ループの1回分（日付・時間帯の切り替え、一時停止の記録かキャプチャ、失敗の監視）を実行し、
+    /// 次に何を待つかを返す
+    fn step(&self, state: &mut LoopState) -> NextWait {
+        // 日付が変わったら前日のサマリーをデイリーノートに書き込む
+        let today = logical_date(&Local::now(), self.config.day_boundary_hour);
+        if today != state.current_day {
+            self.write_daily_note(&state.current_day);
+            state.current_day = today;
+        }
+        // 時間帯が変わったら前の時間帯の代表画像を選ぶ
+        let hour = highlight::hour_start(&Local::now());
+        if hour != state.current_hour {
+            self.update_highlight(state.current_hour);
+            state.current_hour = hour;
+        }
 
-        while self.running.load(Ordering::SeqCst) {
-            // 一時停止チェック
-            if self.pause_control.is_paused() {
+        // 一時停止チェック（集中モード連動による停止を含む）
+        let paused = self.pause_control.is_paused();
+        if paused || self.paused_by_focus() {
+            if paused {
                 info!("一時停止中...");
-                thread::sleep(Duration::from_secs(self.config.interval_seconds));
-                continue;
             }
+            self.record_paused();
+            // 一時停止中の入力は次のキャプチャに含めない（即時キャプチャの要求も記録しない）
+            self.last_input_counters.set(None);
+            self.reset_reminders();
+            return NextWait::Interval;
+        }
 
-            // キャプチャサイクルを実行
-            if let Err(e) = self.capture_cycle() {
+        // キャプチャサイクルを実行
+        let failures = match self.capture_cycle() {
+            Ok(failures) => failures,
+            Err(e) => {
                 error!("キャプチャサイクルでエラー: {}", e);
                 // エラーが発生してもループは継続
+                vec![e.to_string()]
             }
+        };
 
-            // インターバル待機
-            thread::sleep(Duration::from_secs(self.config.interval_seconds));
+        // 連続失敗を監視し、必要なら復旧・クールダウンする
+        if failures.is_empty() {
+            if state.watchdog.record_success() {
+                info!("キャプチャが復旧しました");
+            }
+        } else {
+            match state.watchdog.record_failure() {
+                WatchdogAction::None => {}
+                WatchdogAction::Recover => {
+                    warn!(
+                        "キャプチャが{}回連続で失敗したため再初期化します",
+                        self.config.watchdog_threshold
+                    );
+                    // 監視プロセスを止めてから起動し直す
+                    drop(state.app_watcher.take());
+                    self.reinitialize();
+                    state.app_watcher = self.start_app_watcher();
+                    self.notify_failure(&failures);
+                }
+                WatchdogAction::Cooldown => {
+                    error!(
+                        "再初期化後もキャプチャが失敗するため{}秒休止します",
+                        self.config.watchdog_cooldown_seconds
+                    );
+                    self.alert(
+                        AlertKind::CaptureFailure,
+                        Local::now(),
+                        &format!(
+                            "キャプチャの失敗が続いているため{}秒休止します",
+                            self.config.watchdog_cooldown_seconds
+                        ),
+                    );
+                    return NextWait::Cooldown;
+                }
+            }
         }
 
-        info!("キャプチャループを終了します");
-        Ok(())
+        self.checkpoint_wal();
+        self.flush_errors();
+        NextWait::Interval
     }
 
-    /// 単一のキャプチャサイクル
-    fn capture_cycle(&self) -> Result<(), CaptureError> {
-        let timestamp = Local::now();
+    /// 常駐せずに1回だけキャプチャする（一時停止中かどうかは呼び出し側で確認する）
+    ///
+    /// 記録は続けられたが失敗した処理があれば、その内容を返す
+    pub fn capture_once(&self) -> Result<Vec<String>, CaptureError> {
+        let result = self.capture_cycle();
+        self.flush_images();
+        result
+    }
 
-        // メタデータを収集
-        let active_app = match Metadata::get_active_app() {
-            Ok(app) => app,
+    /// 集中モード（Focus）との連動で記録を止めるべきか
+    ///
+    /// 集中モードの状態を取得できなければ連動せずに記録を続ける
+    pub fn paused_by_focus(&self) -> bool {
+        if !self.config.focus.is_enabled() {
+            return false;
+        }
+        let focus = match Metadata::get_focus_mode() {
+            Ok(focus) => focus,
             Err(e) => {
-                warn!("アクティブアプリ取得失敗: {}", e);
-                "Unknown".to_string()
+                self.report_error("集中モードの取得失敗（連動せずに記録します）", &e);
+                return false;
             }
         };
-        let window_title = Metadata::get_window_title();
 
-        // スクリーンショットをキャプチャ
-        let image_path = match self.image_store.capture(&timestamp) {
-            Ok(path) => Some(path),
+        let paused = self.config.focus.should_pause(focus.as_deref());
+        if paused {
+            info!(
+                "集中モード（{}）のため記録を止めています",
+                focus.as_deref().unwrap_or("オフ")
+            );
+        }
+        paused
+    }
+
+    /// 設定ファイルを読み直して反映する
+    ///
+    /// DB・一時停止ファイル・PIDファイルのパスは他のプロセスと共有しているため、
+    /// 変更されていても再起動まで反映しない。読み込みに失敗したら現在の設定で続ける
+    fn reload_config(&mut self, app_watcher: &mut Option<AppWatcher>, watchdog: &mut Watchdog) {
+        let config = match Config::load(&self.cli_args) {
+            Ok(config) => config,
             Err(e) => {
-                warn!("スクリーンショットキャプチャ失敗: {}", e);
+                warn!("設定の再読み込み失敗（現在の設定で続行します）: {}", e);
+                return;
+            }
+        };
+
+        let fixed = [
+            ("db_path", config.db_path != self.config.db_path),
+            ("pause_file", config.pause_file != self.config.pause_file),
+            (
+                "pomodoro_file",
+                config.pomodoro_file != self.config.pomodoro_file,
+            ),
+            ("pid_file", config.pid_file != self.config.pid_file),
+        ];
+        for (name, changed) in fixed {
+            if changed {
+                warn!("{}の変更は再起動するまで反映されません", name);
+            }
+        }
+        let config = Config {
+            db_path: self.config.db_path.clone(),
+            pause_file: self.config.pause_file.clone(),
+            pomodoro_file: self.config.pomodoro_file.clone(),
+            pid_file: self.config.pid_file.clone(),
+            ..config
+        };
+
+        // ためている画像は前の設定の保存先に移してから切り替える
+        self.flush_images();
+        self.image_store = image_store(&config);
+        self.process_monitor = (config.process_snapshot_count > 0)
+            .then(|| RefCell::new(ProcessMonitor::new(config.process_snapshot_count)));
+        self.ocr_backend = config.ocr_backend.create().map(Arc::from);
+        self.current_interval.set(initial_interval(&config));
+        self.last_screen.replace(None);
+        self.break_reminder = break_reminder(&config);
+        self.distraction_monitor = distraction_monitor(&config);
+        self.idle_monitor = idle_monitor(&config);
+        if needs_input_monitor(&config) != self.input_monitor.is_some() {
+            self.input_monitor = input_monitor(&config);
+            self.last_input_counters.set(None);
+        }
+        match title_redactor(&config) {
+            Ok(redactor) => self.title_redactor = redactor,
+            Err(e) => warn!("{}（タイトルのマスクは以前の設定で続行します）", e),
+        }
+        *watchdog = Watchdog::new(config.watchdog_threshold);
+        self.wal_checkpointer.replace(wal_checkpointer(&config));
+        *self
+            .error_reporter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) =
+            ErrorReporter::new(config.error_notify_threshold);
+        self.metadata_collector = metadata_collector(&config);
+        let restart_watcher = config.record_app_events != self.config.record_app_events;
+        self.config = config;
+
+        if restart_watcher {
+            drop(app_watcher.take());
+            *app_watcher = self.start_app_watcher();
+        }
+        info!(
+            "設定を再読み込みしました（間隔: {}秒）",
+            self.current_interval.get()
+        );
+    }
+
+    /// キャプチャに使うバックエンドの状態を初期化し直す
+    fn reinitialize(&self) {
+        self.last_input_counters.set(None);
+        self.last_screen.replace(None);
+        if let Some(ref monitor) = self.process_monitor {
+            monitor.replace(ProcessMonitor::new(self.config.process_snapshot_count));
+        }
+    }
+
+    /// 権限を確認し、失敗の内容を通知する
+    fn notify_failure(&self, failures: &[String]) {
+        let mut message = format!("キャプチャに失敗しています: {}", failures.join(" / "));
+        match Metadata::check_permissions() {
+            Ok(permissions) => {
+                let missing = permissions.missing();
+                if !missing.is_empty() {
+                    warn!("権限が不足しています: {}", missing.join("、"));
+                    message = format!(
+                        "{}の権限を許可してください（システム設定 > プライバシーとセキュリティ）",
+                        missing.join("・")
+                    );
+                }
+            }
+            Err(e) => warn!("権限の確認失敗: {}", e),
+        }
+        self.alert(AlertKind::CaptureFailure, Local::now(), &message);
+    }
+
+    /// クールダウン時間だけ待機する（停止シグナルで中断する）
+    async fn cooldown(&mut self) {
+        let deadline = Instant::now() + Duration::from_secs(self.config.watchdog_cooldown_seconds);
+        while self.control.is_running() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            self.control.wait(remaining).await;
+        }
+        // 休止中の入力は次のキャプチャに含めない
+        self.last_input_counters.set(None);
+        self.reset_reminders();
+    }
+
+    /// OCRワーカーを別のタスクとして起動（起動するまではOCRはキャプチャ中に行う）
+    ///
+    /// OCRワーカーの書き込みもキャプチャループと同じDB書き込みスレッドで行う
+    fn start_ocr_worker(&mut self) -> Result<JoinHandle<()>, CaptureError> {
+        let worker = OcrWorker::new(&self.db)?;
+        let (sender, receiver) = mpsc::channel(OCR_QUEUE_CAPACITY);
+        let writer = self.writer.clone();
+        let error_reporter = Arc::clone(&self.error_reporter);
+        self.ocr_queue = Some(sender);
+        Ok(tokio::spawn(worker.run(writer, receiver, error_reporter)))
+    }
+
+    /// OCRワーカーへの依頼を締め切り、待ち行列に残った分を処理し終えるまで待つ
+    async fn stop_ocr_worker(&mut self, worker: JoinHandle<()>) {
+        self.ocr_queue = None;
+        if let Err(e) = worker.await {
+            error!("OCRワーカーが異常終了しました: {}", e);
+        }
+    }
+
+    /// アプリ切り替えの監視を開始（無効または起動失敗時は `None`）
+    fn start_app_watcher(&self) -> Option<AppWatcher> {
+        if !self.config.record_app_events {
+            return None;
+        }
+        match AppWatcher::spawn(self.writer.clone(), self.config.pause_file.clone()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                warn!("アプリ切り替え監視の起動失敗: {}", e);
                 None
             }
+        }
+    }
+
+    /// トラッカーセッションの開始を記録
+    ///
+    /// 前回のセッションが閉じられていなければクラッシュとして記録する
+    fn begin_session(&self) -> Result<i64, CaptureError> {
+        let crashed = self.writer.execute(|db| db.close_abandoned_sessions())?;
+        if crashed > 0 {
+            warn!(
+                "前回のセッションは正常に終了していませんでした（{}件）",
+                crashed
+            );
+        }
+
+        let session = SessionRecord {
+            id: None,
+            kind: SessionKind::Tracker,
+            started_at: format_timestamp(&Local::now()),
+            ended_at: None,
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            hostname: Some(self.hostname.clone()),
+            end_reason: None,
         };
+        let session_id = self.writer.execute(move |db| db.insert_session(&session))?;
+        Ok(session_id)
+    }
 
-        // OCRでテキストを抽出
-        let ocr_text = if let Some(ref path) = image_path {
-            match ocr::recognize_text(path) {
-                Ok(text) => {
-                    if text.is_empty() {
-                        None
-                    } else {
-                        Some(text)
-                    }
+    /// 壁時計に整列した次の時刻まで待機し、その間のシステムスリープを記録
+    ///
+    /// 処理時間に関係なく毎回同じ時刻（60秒間隔なら毎分0秒）に実行されるため、
+    /// 時間あたりのキャプチャ数が一定になる。スリープ復帰後は取りこぼした回を
+    /// 埋め合わせず、すぐに1回実行してから再び整列する。
+    /// 即時キャプチャを要求されたら待機を打ち切り、
+    /// 設定の再読み込みを要求されたら待機を中断して `true` を返す
+    async fn wait_interval(&mut self) -> bool {
+        let wall_before = Local::now();
+        let mono_before = Instant::now();
+        let mut target = next_tick(&wall_before, self.current_interval.get());
+        // ポモドーロの段階が切り替わる時刻にはキャプチャして通知する
+        if let Some(boundary) = self
+            .pomodoro_control
+            .current()
+            .and_then(|pomodoro| pomodoro.next_boundary(&wall_before))
+        {
+            target = target.min(boundary);
+        }
+
+        // 停止などの要求にはすぐに起きる。システムスリープ中は単調時計が止まるため、
+        // 復帰に気付けるよう壁時計を一定の間隔で確かめ直す
+        while self.control.is_running()
+            && !self.control.reload_requested()
+            && !self.control.take_capture_request()
+        {
+            match (target - Local::now()).to_std() {
+                Ok(remaining) if !remaining.is_zero() => {
+                    self.control.wait(remaining.min(WAIT_STEP)).await;
                 }
+                _ => break,
+            }
+        }
+
+        if let Some((started_at, ended_at)) =
+            detect_sleep(wall_before, Local::now(), mono_before.elapsed())
+        {
+            task::block_in_place(|| self.record_sleep(&started_at, &ended_at));
+        }
+
+        self.control.is_running() && self.control.take_reload_request()
+    }
+
+    /// 一時停止していたことを記録（レポートで一時停止の時間を集計するため）
+    ///
+    /// タイトル・画像・OCRなどの内容は残さず、時刻と間隔だけを記録する。
+    /// `pause_mode = "metadata_only"` ならアプリ名も記録する
+    fn record_paused(&self) {
+        let (active_app, bundle_id) = match self.config.pause_mode {
+            PauseMode::Stop => (String::new(), None),
+            PauseMode::MetadataOnly => match Metadata::get_active_app() {
+                Ok(app) => (app, Metadata::get_active_bundle_id()),
                 Err(e) => {
-                    warn!("OCR失敗: {}", e);
-                    None
+                    self.report_error("アクティブアプリ取得失敗", &e);
+                    (String::new(), None)
                 }
+            },
+        };
+        let record = CaptureRecord {
+            id: None,
+            captured_at: format_timestamp(&Local::now()),
+            image_path: None,
+            active_app,
+            window_title: String::new(),
+            is_paused: true,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(self.current_interval.get()),
+            hostname: Some(self.hostname.clone()),
+            tracker_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            bundle_id,
+            activity_type: None,
+            working_directory: None,
+        };
+        if let Err(e) = self.writer.execute(move |db| db.insert_capture(&record)) {
+            self.report_error("一時停止の記録失敗", &e);
+        }
+    }
+
+    /// 検出したシステムスリープの区間を記録
+    fn record_sleep(&self, started_at: &DateTime<Local>, ended_at: &DateTime<Local>) {
+        info!(
+            "スリープを検出しました: {} - {}",
+            format_timestamp(started_at),
+            format_timestamp(ended_at)
+        );
+
+        let session = SessionRecord {
+            id: None,
+            kind: SessionKind::Sleep,
+            started_at: format_timestamp(started_at),
+            ended_at: Some(format_timestamp(ended_at)),
+            version: None,
+            hostname: None,
+            end_reason: None,
+        };
+        if let Err(e) = self.writer.execute(move |db| db.insert_session(&session)) {
+            warn!("スリープ区間の記録失敗: {}", e);
+        }
+        // スリープ中の入力は次のキャプチャに含めない
+        self.last_input_counters.set(None);
+        self.reset_reminders();
+    }
+
+    /// 作業が途切れたため、休憩リマインダー・脱線と放置の監視の連続時間を数え直す
+    fn reset_reminders(&self) {
+        if let Some(ref reminder) = self.break_reminder {
+            reminder.borrow_mut().reset();
+        }
+        if let Some(ref monitor) = self.distraction_monitor {
+            monitor.borrow_mut().reset();
+        }
+        if let Some(ref monitor) = self.idle_monitor {
+            monitor.borrow_mut().reset();
+        }
+    }
+
+    /// 実行中のポモドーロを確認し、休憩中なら `true` を返す
+    ///
+    /// 前回の確認以降に完了したポモドーロを記録し（初回は開始時刻から数え、
+    /// 再起動前に記録済みのものはDBで除く）、段階が切り替わっていれば通知する
+    fn observe_pomodoro(&self, now: DateTime<Local>) -> bool {
+        let Some(pomodoro) = self.pomodoro_control.current() else {
+            self.last_pomodoro.replace(None);
+            return false;
+        };
+        let Some(status) = pomodoro.status(&now) else {
+            return false;
+        };
+        let previous = self
+            .last_pomodoro
+            .replace(Some((pomodoro.clone(), now, status)))
+            .filter(|(last, _, _)| *last == pomodoro);
+
+        let from = previous.as_ref().map(|(_, checked_at, _)| checked_at);
+        for completed_at in pomodoro.completions_between(from, &now) {
+            let (completed_at, work_minutes) =
+                (format_timestamp(&completed_at), pomodoro.work_minutes);
+            if let Err(e) = self
+                .writer
+                .execute(move |db| db.insert_pomodoro(&completed_at, work_minutes))
+            {
+                warn!("ポモドーロの記録失敗: {}", e);
+            }
+        }
+
+        if let Some((_, _, last)) = previous {
+            if (last.phase, last.cycle) != (status.phase, status.cycle) {
+                let message = match status.phase {
+                    PomodoroPhase::Work => format!(
+                        "休憩終了です。{}分の作業を始めましょう（{}回目）",
+                        pomodoro.work_minutes, status.cycle
+                    ),
+                    PomodoroPhase::Break => format!(
+                        "ポモドーロ{}回目が完了しました。{}分休憩しましょう",
+                        status.completed, pomodoro.break_minutes
+                    ),
+                };
+                info!("{}", message);
+                self.alert(AlertKind::Pomodoro, now, &message);
+            }
+        }
+        status.phase == PomodoroPhase::Break
+    }
+
+    /// アラートを記録して通知する（記録に失敗しても通知は出す）
+    fn alert(&self, kind: AlertKind, timestamp: DateTime<Local>, message: &str) {
+        alert(&self.writer, kind, timestamp, message);
+    }
+
+    /// エラーを種類ごとに集約して記録し、多発していれば通知する
+    fn report_error(&self, kind: &str, error: &dyn Display) {
+        report_error(&self.error_reporter, &self.writer, kind, error);
+    }
+
+    /// 集約期間が過ぎたエラーの回数を記録する
+    fn flush_errors(&self) {
+        let messages = self
+            .error_reporter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .flush(Local::now());
+        for message in messages {
+            warn!("{}", message);
+        }
+    }
+
+    /// 同じカテゴリ（またはPC操作全般）が続いていれば休憩を促す通知を出す
+    fn remind_break(&self, timestamp: DateTime<Local>, record: &CaptureRecord) {
+        let Some(ref reminder) = self.break_reminder else {
+            return;
+        };
+        let category = self
+            .config
+            .categories
+            .category(&record.active_app, record.bundle_id.as_deref());
+        if let Some(message) = reminder.borrow_mut().observe(timestamp, category) {
+            info!("{}", message);
+            self.alert(AlertKind::Break, timestamp, &message);
+        }
+    }
+
+    /// 作業時間帯に脱線アプリを使い続けていれば警告の通知を出す
+    ///
+    /// 脱線ルールはマスク前のタイトル `window_title` で判定する（通知にタイトルは含めない）
+    fn warn_distraction(
+        &self,
+        timestamp: DateTime<Local>,
+        record: &CaptureRecord,
+        window_title: &str,
+    ) {
+        let Some(ref monitor) = self.distraction_monitor else {
+            return;
+        };
+        let warning = monitor.borrow_mut().observe(
+            timestamp,
+            &record.active_app,
+            record.bundle_id.as_deref(),
+            window_title,
+        );
+        if let Some(message) = warning {
+            info!("{}", message);
+            self.alert(AlertKind::Distraction, timestamp, &message);
+        }
+    }
+
+    /// 同じウィンドウのまま入力のない状態が続いていれば放置中か確認する通知を出し、
+    /// 区間のキャプチャに放置の疑いを記録する（動画視聴は除く）
+    ///
+    /// 通知にタイトルを表示するため、マスク後の保存するタイトルで判定する
+    fn warn_idle(
+        &self,
+        timestamp: DateTime<Local>,
+        record: &CaptureRecord,
+        input: Option<InputMetrics>,
+    ) {
+        let (Some(ref monitor), Some(id)) = (&self.idle_monitor, record.id) else {
+            return;
+        };
+        let input = input.filter(|_| record.activity_type.as_deref() != Some(ACTIVITY_TYPE_MEDIA));
+        let Some(detection) = monitor.borrow_mut().observe(
+            timestamp,
+            id,
+            &record.active_app,
+            &record.window_title,
+            input.as_ref(),
+        ) else {
+            return;
+        };
+        if let Some(ref message) = detection.message {
+            info!("{}", message);
+            self.alert(AlertKind::Idle, timestamp, message);
+        }
+        let result = self.writer.execute(move |db| {
+            for id in detection.capture_ids {
+                db.set_extra_value(id, IDLE_EXTRA_KEY, &serde_json::Value::Bool(true))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("放置の疑いの記録失敗", &e);
+        }
+    }
+
+    /// Obsidianのデイリーノートにサマリーを書き込む（未設定なら何もしない）
+    fn write_daily_note(&self, date: &str) {
+        let Some(ref obsidian) = self.config.obsidian else {
+            return;
+        };
+
+        let result = Database::open(&self.config.db_path)
+            .map_err(ObsidianError::from)
+            .and_then(|db| {
+                let report = Report::new(db, self.config.interval_seconds)
+                    .with_day_boundary(self.config.day_boundary_hour)
+                    .with_aliases(self.config.aliases.clone())
+                    .with_non_work(self.config.non_work.clone());
+                DailyNote::new(obsidian).write_summary(&report, date)
+            });
+        if let Err(e) = result {
+            warn!("デイリーノートへの書き込み失敗: {}", e);
+        }
+    }
+
+    /// 必要ならWALをチェックポイントする（失敗してもログに残すだけ）
+    fn checkpoint_wal(&self) {
+        let mut checkpointer = self.wal_checkpointer.borrow_mut();
+        let wal_bytes = checkpointer.wal_bytes();
+        match checkpointer.maybe_checkpoint(&self.db, Instant::now()) {
+            Ok(Some(true)) => debug!("WALをチェックポイントしました（{}バイト）", wal_bytes),
+            Ok(Some(false)) => debug!("読み取り中の接続があるためWALを書き戻せませんでした"),
+            Ok(None) => {}
+            Err(e) => self.report_error("WALのチェックポイント失敗", &e),
+        }
+    }
+
+    /// 時間帯の代表画像を選んで記録する（失敗してもログに残すだけ）
+    fn update_highlight(&self, hour: NaiveDateTime) {
+        match self
+            .writer
+            .execute(move |db| highlight::update_hour(db, hour))
+        {
+            Ok(Some(capture_id)) => debug!("代表画像を選びました: {} -> {}", hour, capture_id),
+            Ok(None) => {}
+            Err(e) => self.report_error("代表画像の選定失敗", &e),
+        }
+    }
+
+    /// 前回サイクルからの入力活動量を返し、設定で有効ならキャプチャに紐づけて保存
+    ///
+    /// 初回（および一時停止明け）は基準値の取得のみ行い `None` を返す
+    fn record_input_metrics(&self, capture_id: i64) -> Result<Option<InputMetrics>, CaptureError> {
+        let Some(ref monitor) = self.input_monitor else {
+            return Ok(None);
+        };
+        let counters = monitor.counters();
+        let Some(previous) = self.last_input_counters.replace(Some(counters)) else {
+            return Ok(None);
+        };
+        let delta = counters.delta_since(&previous);
+        if self.config.record_input_metrics {
+            self.writer
+                .execute(move |db| db.insert_input_metrics(capture_id, &delta))?;
+        }
+        Ok(Some(delta))
+    }
+
+    /// 今回の画面を前回と比べ、次のキャプチャまでの間隔を決める
+    ///
+    /// アダプティブが無効なら設定のインターバルをそのまま返す
+    fn update_interval(&self, active_app: &str, window_title: &str) -> u64 {
+        let current = self.current_interval.get();
+        if !self.config.adaptive_interval {
+            return current;
+        }
+
+        let screen = (active_app.to_string(), window_title.to_string());
+        let Some(previous) = self.last_screen.replace(Some(screen.clone())) else {
+            return current;
+        };
+        let interval = adapt_interval(
+            current,
+            previous != screen,
+            self.config.min_interval_seconds,
+            self.config.max_interval_seconds,
+        );
+        if interval != current {
+            debug!("キャプチャ間隔を変更: {}秒 -> {}秒", current, interval);
+            self.current_interval.set(interval);
+        }
+        interval
+    }
+
+    /// 単一のキャプチャサイクル
+    ///
+    /// 記録は続けられたがメタデータ取得やスクリーンショットに失敗した場合、
+    /// その内容を返す（ウォッチドッグの判定に使う）
+    fn capture_cycle(&self) -> Result<Vec<String>, CaptureError> {
+        // OpenTelemetryを有効にしていれば、各段階のスパンがトレースとして送られる
+        let _span = info_span!("capture_cycle").entered();
+        if let Some(ref command) = self.config.pre_capture_hook {
+            if let Err(e) = Hook::new(command.as_str()).run_pre_capture() {
+                self.report_error("キャプチャ前フック失敗", &e);
+            }
+        }
+        let timestamp = Local::now();
+        let cycle_started = Instant::now();
+        let mut failures = Vec::new();
+
+        // メタデータを収集（記録の列にないものは追加のkey-valueとして保存する）
+        let collected = self.metadata_collector.collect();
+        for (provider, e) in &collected.failures {
+            if provider == KEY_ACTIVE_APP {
+                self.report_error("アクティブアプリ取得失敗", e);
+                failures.push(format!("アクティブアプリ取得失敗: {}", e));
+            } else {
+                self.report_error(&format!("メタデータ取得失敗（{}）", provider), e);
+            }
+        }
+        let mut extra = collected.values;
+        let active_app = extra
+            .remove(KEY_ACTIVE_APP)
+            .unwrap_or_else(|| "Unknown".to_string());
+        let window_title = extra.remove(KEY_WINDOW_TITLE).unwrap_or_default();
+        let bundle_id = extra.remove(KEY_BUNDLE_ID);
+        // ターミナル・VS Codeの作業はディレクトリでプロジェクトを判定する
+        let working_directory = extra.remove(KEY_WORKING_DIRECTORY);
+        // 動画系のアプリ・タブをフルスクリーンで見ていれば作業時間と分けて集計する
+        let activity_type =
+            (self
+                .config
+                .media
+                .matches(&active_app, bundle_id.as_deref(), &window_title)
+                && Metadata::is_fullscreen())
+            .then(|| ACTIVITY_TYPE_MEDIA.to_string());
+        let interval_seconds = self.update_interval(&active_app, &window_title);
+        // プライベートブラウジング中・ポモドーロの休憩中は画像・OCR・タイトルを残さない
+        let on_break = self.observe_pomodoro(timestamp);
+        let is_private = on_break || self.config.private_windows.matches(&window_title);
+
+        // スクリーンショットをキャプチャ
+        let screenshot_started = Instant::now();
+        let image = if is_private {
+            if on_break {
+                debug!("ポモドーロの休憩中のため画像を保存しません");
+            } else {
+                debug!("プライベートブラウジング中のため画像を保存しません");
             }
-        } else {
             None
+        } else {
+            let result = info_span!("screenshot")
+                .in_scope(|| self.image_store.capture(&timestamp, &active_app));
+            match result {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    self.report_error("スクリーンショットキャプチャ失敗", &e);
+                    failures.push(format!("スクリーンショットキャプチャ失敗: {}", e));
+                    None
+                }
+            }
+        };
+        let screenshot_ms = elapsed_ms(screenshot_started);
+        let image = image.filter(|image| !self.discard_if_blank(image));
+
+        // 画像のファイルサイズと解像度（容量の推移の集計に使う）
+        let image_info =
+            image
+                .as_ref()
+                .and_then(|image| match image_store::image_info(&image.file) {
+                    Ok(info) => Some(info),
+                    Err(e) => {
+                        self.report_error("画像サイズ取得失敗", &e);
+                        None
+                    }
+                });
+
+        // 画像のハッシュ（同じ画面が続く間は既存のOCR結果を流用する）
+        let image_hash = image
+            .as_ref()
+            .and_then(|image| match ocr::image_hash(&image.file) {
+                Ok(hash) => Some(hash),
+                Err(e) => {
+                    self.report_error("画像ハッシュ計算失敗", &e);
+                    None
+                }
+            });
+        let cached_ocr = match image_hash {
+            Some(ref hash) => self
+                .db
+                .find_ocr_by_image_hash(hash)?
+                .map(|(text, details)| OcrResult::from_cached(text, details.as_deref())),
+            None => None,
         };
+        if cached_ocr.is_some() {
+            debug!("同じ画像のOCR結果を流用します");
+        }
+
+        // プロセスのCPU/メモリ使用状況
+        let processes = self
+            .process_monitor
+            .as_ref()
+            .map(|monitor| monitor.borrow_mut().snapshot(&active_app));
 
-        // データベースに記録
+        // データベースに記録（流用したテキストは正規化済み。新しく認識するテキストは記録後に書き込む）
         let record = CaptureRecord {
             id: None,
-            captured_at: timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
-            image_path: image_path.map(|p| p.to_string_lossy().to_string()),
+            captured_at: format_timestamp(&timestamp),
+            image_path: image
+                .as_ref()
+                .map(|image| image.path.to_string_lossy().to_string()),
             active_app,
-            window_title,
+            // ルール・脱線の判定には元のタイトルを使い、保存するタイトル（放置の通知にも使う）だけをマスクする
+            window_title: if is_private {
+                REDACTED.to_string()
+            } else {
+                self.title_redactor.redact(&window_title).into_owned()
+            },
             is_paused: false,
-            is_private: false,
-            ocr_text,
+            is_private,
+            ocr_text: cached_ocr
+                .as_ref()
+                .map(|result| result.text.clone())
+                .filter(|text| !text.is_empty()),
+            interval_seconds: Some(interval_seconds),
+            hostname: Some(self.hostname.clone()),
+            tracker_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            bundle_id,
+            activity_type,
+            working_directory,
         };
 
-        self.db.insert_capture(&record)?;
+        let db_span = info_span!("db_write").entered();
+        let db_started = Instant::now();
+        let details = cached_ocr
+            .as_ref()
+            .filter(|_| self.config.ocr_save_details)
+            .map(|result| result.details_json());
+        // プライベートなウィンドウのURLなどは残さない
+        let extra = (!is_private && !extra.is_empty()).then(|| metadata_provider::to_json(&extra));
+        let saved = record.clone();
+        let id = self.writer.execute(move |db| {
+            let id = db.insert_capture(&saved)?;
+            if let Some(ref hash) = image_hash {
+                db.update_image_hash(id, hash)?;
+            }
+            if let Some(ref info) = image_info {
+                db.update_image_info(id, info)?;
+            }
+            if let Some(ref details) = details {
+                db.update_ocr_details(id, details)?;
+            }
+            if let Some(ref processes) = processes {
+                db.insert_process_snapshots(id, processes)?;
+            }
+            if let Some(ref extra) = extra {
+                db.update_extra(id, extra)?;
+            }
+            Ok(id)
+        })?;
+        let db_ms = elapsed_ms(db_started);
+        drop(db_span);
+        // 放置の監視にも使うため、記録しない設定でも監視が有効なら取得する
+        let input = self.record_input_metrics(id)?;
+        let mut record = CaptureRecord {
+            id: Some(id),
+            ..record
+        };
+
+        // OCRでテキストを抽出（流用した場合・バックエンドが無効なら行わない）
+        let ocr_request = match (&image, &self.ocr_backend) {
+            (Some(image), Some(backend)) if cached_ocr.is_none() => Some(OcrRequest {
+                capture_id: id,
+                image_path: image.file.clone(),
+                timestamp,
+                record: record.clone(),
+                backend: Arc::clone(backend),
+                save_details: self.config.ocr_save_details,
+                post_capture_hook: self.config.post_capture_hook.clone(),
+            }),
+            _ => None,
+        };
+        // OCRワーカーがあれば依頼し、テキストの書き込みとキャプチャ後フックはワーカーに任せる
+        let (ocr_request, ocr_ms) = match ocr_request {
+            Some(request) if self.ocr_queue.is_some() => (Some(request), None),
+            Some(request) => {
+                let outcome = self
+                    .ocr_worker
+                    .borrow_mut()
+                    .process(&self.writer, &request)?;
+                if let Some(ref e) = outcome.error {
+                    self.report_error("OCR失敗", e);
+                }
+                record.ocr_text = outcome.text;
+                (None, Some(outcome.ocr_ms))
+            }
+            None => (None, None),
+        };
+
+        let perf = PerfMetrics {
+            screenshot_ms,
+            ocr_ms,
+            db_ms,
+            total_ms: elapsed_ms(cycle_started),
+        };
+        self.writer
+            .execute(move |db| db.insert_perf_metrics(id, &perf))?;
+        debug!(
+            "処理時間: スクリーンショット {}ms / OCR {:?}ms / DB {}ms / 合計 {}ms",
+            perf.screenshot_ms, perf.ocr_ms, perf.db_ms, perf.total_ms
+        );
         info!("キャプチャ完了: {}", record.captured_at);
 
-        Ok(())
+        let hook_queued = match ocr_request {
+            Some(request) => self.queue_ocr(request),
+            None => false,
+        };
+        if !hook_queued {
+            if let Some(ref command) = self.config.post_capture_hook {
+                if let Err(e) = Hook::new(command.as_str()).run_post_capture(&record) {
+                    self.report_error("キャプチャ後フック失敗", &e);
+                }
+            }
+        }
+        self.remind_break(timestamp, &record);
+        self.warn_distraction(timestamp, &record, &window_title);
+        self.warn_idle(timestamp, &record, input);
+        if self.image_store.is_buffer_full() {
+            self.flush_images();
+        }
+        Ok(failures)
+    }
+
+    /// OCRワーカーに依頼する（依頼できればキャプチャ後フックもワーカーが実行するため `true` を返す）
+    ///
+    /// 待ち行列が溢れていればOCRせずに記録を残し、`tracker ocr` で後から処理できるようにする
+    fn queue_ocr(&self, request: OcrRequest) -> bool {
+        let Some(ref queue) = self.ocr_queue else {
+            return false;
+        };
+        let reason = match queue.try_send(request) {
+            Ok(()) => return true,
+            Err(TrySendError::Full(_)) => "OCRの待ち行列が満杯",
+            Err(TrySendError::Closed(_)) => "OCRワーカーが停止",
+        };
+        self.report_error(
+            reason,
+            &"OCRせずに記録しました（`tracker ocr` で後から処理できます）",
+        );
+        false
+    }
+
+    /// 一時フォルダにためた画像を保存先に移す（失敗しても残りは次の機会に移す）
+    fn flush_images(&self) {
+        match self.image_store.flush() {
+            Ok(0) => {}
+            Ok(count) => debug!("ためていた画像を{}枚保存しました", count),
+            Err(e) => self.report_error("画像の書き出し失敗", &e),
+        }
+    }
+
+    /// 情報量のない画像（ロック画面の真っ黒など）なら削除し、`true` を返す
+    ///
+    /// 削除した場合はOCRもせず、メタデータだけを記録する
+    fn discard_if_blank(&self, image: &CapturedImage) -> bool {
+        let max_stddev = self.config.blank_image_max_stddev;
+        if max_stddev <= 0.0 {
+            return false;
+        }
+
+        let luminance = match image_store::luminance(&image.file) {
+            Ok(Some(luminance)) => luminance,
+            Ok(None) => return false,
+            Err(e) => {
+                self.report_error("画像の輝度計算失敗", &e);
+                return false;
+            }
+        };
+        if !luminance.is_blank(max_stddev) {
+            return false;
+        }
+
+        info!(
+            "ほぼ単色の画像のため保存をスキップします（平均輝度 {:.0}、標準偏差 {:.1}）",
+            luminance.mean, luminance.stddev
+        );
+        if let Err(e) = self.image_store.discard(image) {
+            self.report_error("画像の削除失敗", &e);
+        }
+        true
+    }
+}
+
+/// アラートを記録して通知する（記録に失敗しても通知は出す）
+fn alert(writer: &DatabaseWriter, kind: AlertKind, timestamp: DateTime<Local>, message: &str) {
+    let alert = Alert {
+        id: None,
+        occurred_at: format_timestamp(&timestamp),
+        kind,
+        message: message.to_string(),
+    };
+    if let Err(e) = writer.execute(move |db| db.insert_alert(&alert)) {
+        warn!("アラートの記録失敗: {}", e);
     }
+    notify_or_warn(message);
+}
+
+/// エラーを種類ごとに集約して記録し、多発していれば通知する（OCRワーカーからも使う）
+///
+/// 同じ種類のエラーは1時間に1回だけログに残し、残りは期間が過ぎてから回数をまとめて記録する
+pub(crate) fn report_error(
+    error_reporter: &Mutex<ErrorReporter>,
+    writer: &DatabaseWriter,
+    kind: &str,
+    error: &dyn Display,
+) {
+    let now = Local::now();
+    let report = error_reporter
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .report(now, kind, &error.to_string());
+    for message in report.logs {
+        warn!("{}", message);
+    }
+    if let Some(message) = report.notification {
+        alert(writer, AlertKind::RepeatedError, now, &message);
+    }
+}
+
+/// 画面収録の権限があるか確認する（権限がなければ設定画面を案内するエラー）
+///
+/// 権限の状態を取得できなかった場合はキャプチャを止めずに警告だけ残す
+pub fn ensure_screen_recording() -> Result<(), CaptureError> {
+    match Metadata::check_permissions() {
+        Ok(permissions) if !permissions.screen_recording => Err(
+            CaptureError::ScreenRecordingDenied(SCREEN_RECORDING_SETTINGS),
+        ),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            warn!("画面収録の権限の確認失敗: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// 設定から最初のキャプチャ間隔を決める（アダプティブなら範囲内に収める）
+fn initial_interval(config: &Config) -> u64 {
+    if config.adaptive_interval {
+        config
+            .interval_seconds
+            .clamp(config.min_interval_seconds, config.max_interval_seconds)
+    } else {
+        config.interval_seconds
+    }
+}
+
+/// 設定から休憩リマインダーを作成（無効なら `None`）
+fn break_reminder(config: &Config) -> Option<RefCell<BreakReminder>> {
+    config
+        .break_reminder
+        .clone()
+        .map(|reminder| RefCell::new(BreakReminder::new(reminder)))
+}
+
+/// 設定からメタデータプロバイダの集合を作成
+fn metadata_collector(config: &Config) -> MetadataCollector {
+    MetadataCollector::from_config(&config.metadata_providers, &config.metadata_commands)
+}
+
+/// 設定からWALの自動チェックポイントを作成
+fn wal_checkpointer(config: &Config) -> WalCheckpointer {
+    WalCheckpointer::new(
+        &config.db_path,
+        Duration::from_secs(config.wal_checkpoint_minutes * 60),
+        config.wal_checkpoint_max_mb * 1024 * 1024,
+    )
+}
+
+/// 設定から画像ストレージを作成
+fn image_store(config: &Config) -> ImageStore {
+    ImageStore::new(config.images_dir.clone(), config.jpeg_quality)
+        .with_filename_format(config.image_filename_format.clone())
+        .with_buffer(config.image_buffer_dir.clone(), config.image_buffer_count)
+}
+
+/// 設定から脱線の監視を作成（脱線アプリが未設定か警告しないなら `None`）
+fn distraction_monitor(config: &Config) -> Option<RefCell<DistractionMonitor>> {
+    (!config.distractions.is_empty() && config.distraction_warn_minutes > 0).then(|| {
+        RefCell::new(DistractionMonitor::new(
+            config.distractions.clone(),
+            config.distraction_warn_minutes,
+        ))
+    })
+}
+
+/// 設定から放置の監視を作成（確認しない設定なら `None`）
+fn idle_monitor(config: &Config) -> Option<RefCell<IdleMonitor>> {
+    (config.idle_warn_minutes > 0).then(|| RefCell::new(IdleMonitor::new(config.idle_warn_minutes)))
+}
+
+/// 入力監視が必要か（入力活動量の記録か放置の監視が有効）
+fn needs_input_monitor(config: &Config) -> bool {
+    config.record_input_metrics || config.idle_warn_minutes > 0
+}
+
+/// 設定から入力監視を開始（不要な設定か、開始できなければ `None`）
+fn input_monitor(config: &Config) -> Option<InputMonitor> {
+    if !needs_input_monitor(config) {
+        return None;
+    }
+    InputMonitor::spawn()
+        .inspect_err(|e| warn!("{}（入力活動量は記録しません）", e))
+        .ok()
+}
+
+/// 設定からウィンドウタイトルのマスクを作成
+fn title_redactor(config: &Config) -> Result<TitleRedactor, ConfigError> {
+    TitleRedactor::new(&config.title_redact_patterns).map_err(|e| ConfigError::Validation {
+        field: "title_redact_patterns".to_string(),
+        reason: e.to_string(),
+        line: None,
+    })
+}
+
+/// 計測開始からの経過時間（ミリ秒）
+fn elapsed_ms(started: Instant) -> u64 {
+    started.elapsed().as_millis() as u64
+}
+
+/// 通知を表示する（失敗してもログに残すだけ）
+fn notify_or_warn(message: &str) {
+    if let Err(e) = notify(NOTIFICATION_TITLE, message) {
+        warn!("通知の表示失敗: {}", e);
+    }
+}
+
+/// 待機中に壁時計を確認する間隔
+const WAIT_STEP: Duration = Duration::from_secs(1);
+
+/// キャプチャループの実行中に持ち回る状態
+struct LoopState {
+    /// アプリ切り替えの監視（無効または起動失敗時は `None`）
+    app_watcher: Option<AppWatcher>,
+    watchdog: Watchdog,
+    /// 現在の論理日付（日付が変わったらデイリーノートを書き込む）
+    current_day: String,
+    /// 現在の時間帯（変わったら代表画像を選ぶ）
+    current_hour: NaiveDateTime,
+}
+
+/// ループの1回分を終えたあとに待つもの
+enum NextWait {
+    /// 次のキャプチャ時刻まで
+    Interval,
+    /// 失敗が続いたためのクールダウン
+    Cooldown,
+}
+
+/// `now` より後で、インターバルの倍数に整列した最初の時刻を返す
+///
+/// 整列はローカル時刻基準（3600秒間隔なら毎時0分0秒）
+fn next_tick<Tz: TimeZone>(now: &DateTime<Tz>, interval_seconds: u64) -> DateTime<Tz> {
+    let interval = interval_seconds.max(1) as i64;
+    let offset = now.offset().fix().local_minus_utc() as i64;
+    let local = now.timestamp() + offset;
+    let next_local = (local.div_euclid(interval) + 1) * interval;
+    now.clone() - chrono::Duration::nanoseconds(now.timestamp_subsec_nanos() as i64)
+        + chrono::Duration::seconds(next_local - local)
+}
+
+/// 画面の変化に応じて次のキャプチャ間隔を求める
+///
+/// アプリやウィンドウが変わったら半分に、同じ画面が続いたら倍にして、
+/// 最小/最大間隔の範囲に収める
+fn adapt_interval(current: u64, changed: bool, min: u64, max: u64) -> u64 {
+    let next = if changed {
+        current / 2
+    } else {
+        current.saturating_mul(2)
+    };
+    next.clamp(min, max)
+}
+
+/// スリープとみなす壁時計とモノトニック時計のずれ
+const SLEEP_GAP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// 待機中のシステムスリープを検出
+///
+/// macOSのモノトニック時計（`Instant`）はスリープ中に進まないため、壁時計との
+/// 差をスリープ時間とみなす。スリープが待機中のどこで始まったかは分からないので、
+/// 待機開始時刻からの区間として近似する（誤差は最大でインターバル1回分）
+fn detect_sleep(
+    wall_before: DateTime<Local>,
+    wall_after: DateTime<Local>,
+    mono_elapsed: Duration,
+) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let wall_elapsed = (wall_after - wall_before).to_std().ok()?;
+    let gap = wall_elapsed.checked_sub(mono_elapsed)?;
+    if gap < SLEEP_GAP_THRESHOLD {
+        return None;
+    }
+    Some((
+        wall_before,
+        wall_before + chrono::Duration::from_std(gap).ok()?,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::FixedOffset;
+    use std::fs;
     use tempfile::TempDir;
 
     fn create_test_config() -> (Config, TempDir) {
@@ -157,6 +1392,8 @@ mod tests {
             db_path: temp_dir.path().join("test.db"),
             images_dir: temp_dir.path().join("images"),
             pause_file: temp_dir.path().join("pause"),
+            pid_file: temp_dir.path().join("tracker.pid"),
+            ..Default::default()
         };
         (config, temp_dir)
     }
@@ -172,7 +1409,7 @@ mod tests {
     fn test_running_flag_initial_state() {
         let (config, _temp_dir) = create_test_config();
         let loop_ = CaptureLoop::new(config).unwrap();
-        assert!(loop_.running.load(Ordering::SeqCst));
+        assert!(loop_.control.is_running());
     }
 
     #[test]
@@ -180,7 +1417,101 @@ mod tests {
         let (config, _temp_dir) = create_test_config();
         let loop_ = CaptureLoop::new(config).unwrap();
 
-        loop_.running.store(false, Ordering::SeqCst);
-        assert!(!loop_.running.load(Ordering::SeqCst));
+        loop_.control.stop();
+        assert!(!loop_.control.is_running());
+    }
+
+    #[test]
+    fn test_next_tick_aligns_to_clock() {
+        let jst = FixedOffset::east_opt(9 * 3600).unwrap();
+        let now = jst.with_ymd_and_hms(2025, 1, 10, 10, 0, 42).unwrap()
+            + chrono::Duration::milliseconds(350);
+
+        assert_eq!(
+            next_tick(&now, 60),
+            jst.with_ymd_and_hms(2025, 1, 10, 10, 1, 0).unwrap()
+        );
+        assert_eq!(
+            next_tick(&now, 300),
+            jst.with_ymd_and_hms(2025, 1, 10, 10, 5, 0).unwrap()
+        );
+
+        // ちょうど整列時刻なら次の回
+        let aligned = jst.with_ymd_and_hms(2025, 1, 10, 10, 1, 0).unwrap();
+        assert_eq!(
+            next_tick(&aligned, 60),
+            jst.with_ymd_and_hms(2025, 1, 10, 10, 2, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_next_tick_uses_local_time() {
+        // UTC+5:30でも毎時0分に整列する
+        let ist = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        let now = ist.with_ymd_and_hms(2025, 1, 10, 10, 20, 0).unwrap();
+        assert_eq!(
+            next_tick(&now, 3600),
+            ist.with_ymd_and_hms(2025, 1, 10, 11, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_adapt_interval() {
+        // 切り替えが続くと最小間隔まで短くなる
+        assert_eq!(adapt_interval(60, true, 15, 300), 30);
+        assert_eq!(adapt_interval(20, true, 15, 300), 15);
+
+        // 同じ画面が続くと最大間隔まで長くなる
+        assert_eq!(adapt_interval(60, false, 15, 300), 120);
+        assert_eq!(adapt_interval(240, false, 15, 300), 300);
+    }
+
+    #[test]
+    fn test_update_interval_tracks_screen_changes() {
+        let (mut config, _temp_dir) = create_test_config();
+        config.interval_seconds = 60;
+        config.adaptive_interval = true;
+        let loop_ = CaptureLoop::new(config).unwrap();
+
+        // 初回は比較対象がないので変えない
+        assert_eq!(loop_.update_interval("VS Code", "main.rs"), 60);
+        assert_eq!(loop_.update_interval("VS Code", "main.rs"), 120);
+        assert_eq!(loop_.update_interval("Slack", "general"), 60);
+        assert_eq!(loop_.current_interval.get(), 60);
+    }
+
+    #[test]
+    fn test_discard_if_blank_keeps_unreadable_image() {
+        let (config, temp_dir) = create_test_config();
+        let loop_ = CaptureLoop::new(config).unwrap();
+
+        // 輝度を計算できない画像は消さずに保存する
+        let path = temp_dir.path().join("broken.jpg");
+        fs::write(&path, b"not a jpeg").unwrap();
+        let image = CapturedImage {
+            path: path.clone(),
+            file: path.clone(),
+        };
+        assert!(!loop_.discard_if_blank(&image));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_detect_sleep() {
+        let before = Local::now();
+        let after = before + chrono::Duration::seconds(3660);
+
+        // 60秒待機中に1時間スリープ
+        let (start, end) = detect_sleep(before, after, Duration::from_secs(60)).unwrap();
+        assert_eq!(start, before);
+        assert_eq!((end - start).num_seconds(), 3600);
+
+        // 通常の待機ではスリープとみなさない
+        let after = before + chrono::Duration::seconds(61);
+        assert!(detect_sleep(before, after, Duration::from_secs(60)).is_none());
+
+        // 壁時計が戻った場合（時刻合わせ）も無視
+        let after = before - chrono::Duration::seconds(10);
+        assert!(detect_sleep(before, after, Duration::from_secs(60)).is_none());
     }
 }
@@ -0,0 +1,538 @@
+//! カテゴリモジュール - アプリ名・bundle IDによる作業カテゴリの分類、表示名の統一と動画視聴・会議・脱線・非作業の判定
+
+use std::collections::{BTreeMap, HashMap};
+
+/// どのカテゴリにも含まれないアプリのカテゴリ名
+pub const UNCATEGORIZED: &str = "その他";
+
+/// アプリのカテゴリ分類
+///
+/// 設定ファイルの `[categories]` テーブル（`カテゴリ名 = ["アプリ名", ...]`）から作る。
+/// アプリ名の代わりに `com.apple.Safari` のようなbundle IDも指定できる。
+/// 大文字小文字を区別せず完全一致で比較する
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Categories {
+    rules: Vec<(String, Vec<String>)>,
+}
+
+impl Categories {
+    /// カテゴリ名とアプリ名の一覧から作成
+    pub fn new(table: BTreeMap<String, Vec<String>>) -> Self {
+        let rules = table
+            .into_iter()
+            .map(|(category, apps)| {
+                let apps = apps
+                    .into_iter()
+                    .map(|app| app.trim().to_lowercase())
+                    .filter(|app| !app.is_empty())
+                    .collect();
+                (category, apps)
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// アプリのカテゴリ名（未分類なら `UNCATEGORIZED`）
+    ///
+    /// アプリ名とbundle IDのどちらかが一致すれば、そのカテゴリに分類する
+    pub fn category(&self, app: &str, bundle_id: Option<&str>) -> &str {
+        let app = app.to_lowercase();
+        let bundle_id = bundle_id.map(str::to_lowercase);
+        self.rules
+            .iter()
+            .find(|(_, apps)| {
+                apps.contains(&app) || bundle_id.as_ref().is_some_and(|id| apps.contains(id))
+            })
+            .map_or(UNCATEGORIZED, |(category, _)| category.as_str())
+    }
+}
+
+/// アプリの表示名の統一
+///
+/// 設定ファイルの `[aliases]` テーブル（`"アプリ名またはbundle ID" = "表示名"`）から作る。
+/// 言語設定やアップデートで変わる表示名を、bundle IDで1つの名前にまとめられる。
+/// キーは大文字小文字を区別せず完全一致で比較し、bundle IDの一致を優先する
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl AppAliases {
+    /// アプリ名またはbundle IDと表示名の対応から作成
+    pub fn new(table: BTreeMap<String, String>) -> Self {
+        let aliases = table
+            .into_iter()
+            .map(|(key, name)| (key.trim().to_lowercase(), name))
+            .filter(|(key, _)| !key.is_empty())
+            .collect();
+        Self { aliases }
+    }
+
+    /// 表示名（対応がなければ `app` のまま）
+    pub fn resolve<'a>(&'a self, app: &'a str, bundle_id: Option<&str>) -> &'a str {
+        bundle_id
+            .and_then(|id| self.aliases.get(&id.to_lowercase()))
+            .or_else(|| self.aliases.get(&app.to_lowercase()))
+            .map_or(app, String::as_str)
+    }
+}
+
+/// 動画視聴とみなすアプリ・ウィンドウタイトルの規則
+///
+/// アプリ名またはbundle IDが `apps` に一致するか、ウィンドウタイトルが `title_keywords` の
+/// いずれかを含む（ブラウザのYouTubeタブなど）場合に動画系とみなす。
+/// いずれも大文字小文字を区別しない。フルスクリーンかどうかは呼び出し側で確認する
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaRules {
+    apps: Vec<String>,
+    title_keywords: Vec<String>,
+}
+
+impl MediaRules {
+    /// 既定で動画系とみなすアプリ名・bundle ID
+    pub const DEFAULT_APPS: [&str; 10] = [
+        "IINA",
+        "VLC",
+        "QuickTime Player",
+        "TV",
+        "Netflix",
+        "com.colliderli.iina",
+        "org.videolan.vlc",
+        "com.apple.QuickTimePlayerX",
+        "com.apple.TV",
+        "com.netflix.Netflix",
+    ];
+
+    /// 既定で動画系とみなすウィンドウタイトルのキーワード
+    pub const DEFAULT_TITLE_KEYWORDS: [&str; 5] =
+        ["YouTube", "Netflix", "Prime Video", "Disney+", "ABEMA"];
+
+    /// 動画系とみなすアプリ名・bundle IDを設定
+    pub fn with_apps(mut self, apps: Vec<String>) -> Self {
+        self.apps = normalize(apps);
+        self
+    }
+
+    /// 動画系とみなすウィンドウタイトルのキーワードを設定
+    pub fn with_title_keywords(mut self, keywords: Vec<String>) -> Self {
+        self.title_keywords = normalize(keywords);
+        self
+    }
+
+    /// 動画系のアプリ・ウィンドウか
+    pub fn matches(&self, app: &str, bundle_id: Option<&str>, window_title: &str) -> bool {
+        let app = app.to_lowercase();
+        let bundle_id = bundle_id.map(str::to_lowercase);
+        let title = window_title.to_lowercase();
+        self.apps.contains(&app)
+            || bundle_id.is_some_and(|id| self.apps.contains(&id))
+            || self
+                .title_keywords
+                .iter()
+                .any(|keyword| title.contains(keyword.as_str()))
+    }
+}
+
+impl Default for MediaRules {
+    fn default() -> Self {
+        Self {
+            apps: normalize(Self::DEFAULT_APPS.map(String::from).into()),
+            title_keywords: normalize(Self::DEFAULT_TITLE_KEYWORDS.map(String::from).into()),
+        }
+    }
+}
+
+/// 会議とみなすアプリ・ウィンドウタイトルの規則
+///
+/// アプリ名またはbundle IDが `apps` に一致するか、ウィンドウタイトルが `title_keywords` の
+/// いずれかを含む（ブラウザのGoogle Meetタブなど）場合に会議とみなす。
+/// いずれも大文字小文字を区別しない
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeetingRules {
+    apps: Vec<String>,
+    title_keywords: Vec<String>,
+}
+
+impl MeetingRules {
+    /// 既定で会議とみなすアプリ名・bundle ID
+    pub const DEFAULT_APPS: [&str; 9] = [
+        "zoom.us",
+        "Microsoft Teams",
+        "Webex",
+        "FaceTime",
+        "us.zoom.xos",
+        "com.microsoft.teams",
+        "com.microsoft.teams2",
+        "com.cisco.webexmeetingsapp",
+        "com.apple.FaceTime",
+    ];
+
+    /// 既定で会議とみなすウィンドウタイトルのキーワード
+    pub const DEFAULT_TITLE_KEYWORDS: [&str; 3] = ["Google Meet", "Meet - ", "Zoom Meeting"];
+
+    /// 会議とみなすアプリ名・bundle IDを設定
+    pub fn with_apps(mut self, apps: Vec<String>) -> Self {
+        self.apps = normalize(apps);
+        self
+    }
+
+    /// 会議とみなすウィンドウタイトルのキーワードを設定
+    pub fn with_title_keywords(mut self, keywords: Vec<String>) -> Self {
+        self.title_keywords = normalize(keywords);
+        self
+    }
+
+    /// 会議のアプリ・ウィンドウか
+    pub fn matches(&self, app: &str, bundle_id: Option<&str>, window_title: &str) -> bool {
+        let app = app.to_lowercase();
+        let bundle_id = bundle_id.map(str::to_lowercase);
+        let title = window_title.to_lowercase();
+        self.apps.contains(&app)
+            || bundle_id.is_some_and(|id| self.apps.contains(&id))
+            || self
+                .title_keywords
+                .iter()
+                .any(|keyword| title.contains(keyword.as_str()))
+    }
+}
+
+impl Default for MeetingRules {
+    fn default() -> Self {
+        Self {
+            apps: normalize(Self::DEFAULT_APPS.map(String::from).into()),
+            title_keywords: normalize(Self::DEFAULT_TITLE_KEYWORDS.map(String::from).into()),
+        }
+    }
+}
+
+/// 非作業（ロック画面・スクリーンセーバー・デスクトップのみの表示）とみなすアプリの規則
+///
+/// アプリ名またはbundle IDが `apps` に一致するか、`desktop_apps` に一致してウィンドウがない
+/// （タイトルが空か「デスクトップ」）場合に非作業とみなす。いずれも大文字小文字を区別しない
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonWorkRules {
+    apps: Vec<String>,
+    desktop_apps: Vec<String>,
+}
+
+impl NonWorkRules {
+    /// 既定で非作業とみなすアプリ名・bundle ID
+    pub const DEFAULT_APPS: [&str; 4] = [
+        "loginwindow",
+        "ScreenSaverEngine",
+        "com.apple.loginwindow",
+        "com.apple.ScreenSaver.Engine",
+    ];
+
+    /// 既定でウィンドウがなければ非作業とみなすアプリ名・bundle ID
+    pub const DEFAULT_DESKTOP_APPS: [&str; 2] = ["Finder", "com.apple.finder"];
+
+    /// デスクトップだけを表示しているときのウィンドウタイトル（小文字）
+    const DESKTOP_TITLES: [&str; 3] = ["", "desktop", "デスクトップ"];
+
+    /// 非作業とみなすアプリ名・bundle IDを設定
+    pub fn with_apps(mut self, apps: Vec<String>) -> Self {
+        self.apps = normalize(apps);
+        self
+    }
+
+    /// ウィンドウがなければ非作業とみなすアプリ名・bundle IDを設定
+    pub fn with_desktop_apps(mut self, apps: Vec<String>) -> Self {
+        self.desktop_apps = normalize(apps);
+        self
+    }
+
+    /// 非作業のアプリ・ウィンドウか
+    pub fn matches(&self, app: &str, bundle_id: Option<&str>, window_title: &str) -> bool {
+        let app = app.to_lowercase();
+        let bundle_id = bundle_id.map(str::to_lowercase);
+        let is_listed = |apps: &[String]| {
+            apps.contains(&app) || bundle_id.as_ref().is_some_and(|id| apps.contains(id))
+        };
+        is_listed(&self.apps)
+            || (is_listed(&self.desktop_apps)
+                && Self::DESKTOP_TITLES.contains(&window_title.trim().to_lowercase().as_str()))
+    }
+}
+
+impl Default for NonWorkRules {
+    fn default() -> Self {
+        Self {
+            apps: normalize(Self::DEFAULT_APPS.map(String::from).into()),
+            desktop_apps: normalize(Self::DEFAULT_DESKTOP_APPS.map(String::from).into()),
+        }
+    }
+}
+
+/// プライベートブラウジングのウィンドウの判定（config: `private_title_keywords`）
+///
+/// ウィンドウタイトルがいずれかのキーワードを含めばプライベートとみなす。
+/// 大文字小文字を区別しない
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivateWindows {
+    title_keywords: Vec<String>,
+}
+
+impl PrivateWindows {
+    /// 既定でプライベートとみなすウィンドウタイトルのキーワード
+    pub const DEFAULT_TITLE_KEYWORDS: [&str; 6] = [
+        "シークレット",
+        "プライベートブラウズ",
+        "Incognito",
+        "Private Browsing",
+        "InPrivate",
+        "Private Window",
+    ];
+
+    /// プライベートとみなすウィンドウタイトルのキーワードから作成
+    pub fn new(title_keywords: Vec<String>) -> Self {
+        Self {
+            title_keywords: normalize(title_keywords),
+        }
+    }
+
+    /// プライベートブラウジングのウィンドウか
+    pub fn matches(&self, window_title: &str) -> bool {
+        let title = window_title.to_lowercase();
+        self.title_keywords
+            .iter()
+            .any(|keyword| title.contains(keyword.as_str()))
+    }
+}
+
+impl Default for PrivateWindows {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TITLE_KEYWORDS.map(String::from).into())
+    }
+}
+
+/// 作業時間帯に使うと脱線とみなすアプリ・サイト
+///
+/// アプリ名・bundle IDが一致するか、ウィンドウタイトルに含まれる（ブラウザの
+/// YouTubeタブなど）場合に脱線とみなす。大文字小文字を区別しない。
+/// 作業時間帯を設定しなければ終日を作業時間帯とする
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Distractions {
+    apps: Vec<String>,
+    work_hours: Option<(u32, u32)>,
+}
+
+impl Distractions {
+    /// 脱線とみなすアプリ名・bundle ID・サイト名から作成
+    pub fn new(apps: Vec<String>) -> Self {
+        Self {
+            apps: apps
+                .into_iter()
+                .map(|app| app.trim().to_string())
+                .filter(|app| !app.is_empty())
+                .collect(),
+            work_hours: None,
+        }
+    }
+
+    /// 作業時間帯（開始時刻から終了時刻の前まで、開始 > 終了なら日をまたぐ）を設定
+    pub fn with_work_hours(mut self, start_hour: u32, end_hour: u32) -> Self {
+        self.work_hours = Some((start_hour, end_hour));
+        self
+    }
+
+    /// 作業時間帯（未設定なら `None`）
+    pub fn work_hours(&self) -> Option<(u32, u32)> {
+        self.work_hours
+    }
+
+    /// 脱線とみなすアプリが設定されていないか
+    pub fn is_empty(&self) -> bool {
+        self.apps.is_empty()
+    }
+
+    /// `hour` 時台が作業時間帯か
+    pub fn is_work_hour(&self, hour: u32) -> bool {
+        match self.work_hours {
+            Some((start, end)) if start < end => (start..end).contains(&hour),
+            Some((start, end)) if start > end => hour >= start || hour < end,
+            _ => true,
+        }
+    }
+
+    /// 脱線とみなすアプリ・ウィンドウなら、一致した設定の名前を返す
+    pub fn find(&self, app: &str, bundle_id: Option<&str>, window_title: &str) -> Option<&str> {
+        let app = app.to_lowercase();
+        let bundle_id = bundle_id.map(str::to_lowercase);
+        let title = window_title.to_lowercase();
+        self.apps
+            .iter()
+            .find(|name| {
+                let name = name.to_lowercase();
+                app == name || bundle_id.as_ref() == Some(&name) || title.contains(&name)
+            })
+            .map(String::as_str)
+    }
+}
+
+/// 比較用に小文字にし、空の値を除く
+fn normalize(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distractions() {
+        let distractions = Distractions::new(vec![
+            "Twitter".to_string(),
+            "YouTube".to_string(),
+            " ".to_string(),
+        ])
+        .with_work_hours(9, 18);
+
+        assert_eq!(distractions.find("twitter", None, ""), Some("Twitter"));
+        assert_eq!(
+            distractions.find("Safari", Some("com.apple.Safari"), "猫 - YouTube"),
+            Some("YouTube")
+        );
+        assert_eq!(distractions.find("VS Code", None, "main.rs"), None);
+        assert!(Distractions::default().is_empty());
+
+        assert!(distractions.is_work_hour(9));
+        assert!(!distractions.is_work_hour(18));
+        let night = Distractions::new(vec![]).with_work_hours(22, 6);
+        assert!(night.is_work_hour(23) && night.is_work_hour(5));
+        assert!(!night.is_work_hour(12));
+        assert!(Distractions::default().is_work_hour(3));
+    }
+
+    #[test]
+    fn test_category() {
+        let categories = Categories::new(BTreeMap::from([
+            (
+                "開発".to_string(),
+                vec!["VS Code".to_string(), "Terminal".to_string()],
+            ),
+            ("コミュニケーション".to_string(), vec!["Slack".to_string()]),
+        ]));
+
+        assert_eq!(categories.category("vs code", None), "開発");
+        assert_eq!(categories.category("Slack", None), "コミュニケーション");
+        assert_eq!(categories.category("Finder", None), UNCATEGORIZED);
+        assert_eq!(Categories::default().category("Slack", None), UNCATEGORIZED);
+    }
+
+    #[test]
+    fn test_category_by_bundle_id() {
+        let categories = Categories::new(BTreeMap::from([(
+            "ブラウザ".to_string(),
+            vec!["com.apple.Safari".to_string()],
+        )]));
+
+        // 表示名が変わってもbundle IDで分類できる
+        assert_eq!(
+            categories.category("Safari", Some("com.apple.Safari")),
+            "ブラウザ"
+        );
+        assert_eq!(
+            categories.category("サファリ", Some("com.apple.safari")),
+            "ブラウザ"
+        );
+        assert_eq!(categories.category("Safari", None), UNCATEGORIZED);
+    }
+
+    #[test]
+    fn test_media_rules() {
+        let rules = MediaRules::default();
+        assert!(rules.matches("IINA", None, "movie.mp4"));
+        assert!(rules.matches("イナ", Some("com.colliderli.iina"), ""));
+        // ブラウザはタブのタイトルで判定する
+        assert!(rules.matches("Google Chrome", None, "Rust入門 - YouTube"));
+        assert!(!rules.matches("Google Chrome", None, "GitHub"));
+        assert!(!rules.matches("VS Code", None, "main.rs"));
+
+        let rules = MediaRules::default()
+            .with_apps(vec!["Plex".to_string()])
+            .with_title_keywords(vec![]);
+        assert!(rules.matches("plex", None, ""));
+        assert!(!rules.matches("IINA", None, ""));
+        assert!(!rules.matches("Safari", None, "YouTube"));
+    }
+
+    #[test]
+    fn test_non_work_rules() {
+        let rules = NonWorkRules::default();
+        assert!(rules.matches("loginwindow", None, ""));
+        assert!(rules.matches(
+            "スクリーンセーバー",
+            Some("com.apple.ScreenSaver.Engine"),
+            ""
+        ));
+        // Finderはウィンドウがないときだけ
+        assert!(rules.matches("Finder", None, ""));
+        assert!(rules.matches("Finder", None, "デスクトップ"));
+        assert!(!rules.matches("Finder", None, "Downloads"));
+        assert!(!rules.matches("VS Code", None, ""));
+
+        let rules = NonWorkRules::default()
+            .with_apps(vec!["Dock".to_string()])
+            .with_desktop_apps(vec![]);
+        assert!(rules.matches("dock", None, "Dock"));
+        assert!(!rules.matches("loginwindow", None, ""));
+        assert!(!rules.matches("Finder", None, ""));
+    }
+
+    #[test]
+    fn test_meeting_rules() {
+        let rules = MeetingRules::default();
+        assert!(rules.matches("zoom.us", None, "Zoom ミーティング"));
+        assert!(rules.matches("Teams", Some("com.microsoft.teams2"), ""));
+        assert!(rules.matches("Google Chrome", None, "Meet - abc-defg-hij"));
+        assert!(!rules.matches("Google Chrome", None, "GitHub"));
+
+        let rules = MeetingRules::default()
+            .with_apps(vec!["Around".to_string()])
+            .with_title_keywords(vec![]);
+        assert!(rules.matches("around", None, ""));
+        assert!(!rules.matches("zoom.us", None, ""));
+        assert!(!rules.matches("Google Chrome", None, "Google Meet"));
+    }
+
+    #[test]
+    fn test_private_windows() {
+        let private = PrivateWindows::default();
+        assert!(private.matches("新しいタブ - Google Chrome（シークレット）"));
+        assert!(private.matches("Mozilla Firefox Private Browsing"));
+        assert!(private.matches("Bing - [InPrivate] - Microsoft Edge"));
+        assert!(!private.matches("GitHub - Google Chrome"));
+
+        // 空のリストなら判定しない
+        assert!(!PrivateWindows::new(vec![]).matches("Incognito"));
+    }
+
+    #[test]
+    fn test_aliases() {
+        let aliases = AppAliases::new(BTreeMap::from([
+            ("com.microsoft.VSCode".to_string(), "VS Code".to_string()),
+            ("Code".to_string(), "VS Code (名前)".to_string()),
+            ("iTerm2".to_string(), "Terminal".to_string()),
+        ]));
+
+        // bundle IDの一致を優先する
+        assert_eq!(
+            aliases.resolve("Code", Some("com.microsoft.vscode")),
+            "VS Code"
+        );
+        assert_eq!(aliases.resolve("code", None), "VS Code (名前)");
+        assert_eq!(
+            aliases.resolve("iTerm2", Some("com.googlecode.iterm2")),
+            "Terminal"
+        );
+        assert_eq!(
+            aliases.resolve("Finder", Some("com.apple.finder")),
+            "Finder"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! グラフモジュール - アプリ別円グラフと時間帯別積み上げ棒グラフのPNG/SVG描画
+
+use crate::error::ChartError;
+use crate::report::AppHourly;
+use crate::wordcloud::{render_png, FONT_FAMILY};
+use plotters::prelude::*;
+use std::fs;
+use std::path::Path;
+
+/// 画像の幅（px）
+const WIDTH: u32 = 1200;
+
+/// 画像の高さ（px）
+const HEIGHT: u32 = 600;
+
+/// 円グラフを描く左側の領域の幅（px）
+const PIE_AREA_WIDTH: u32 = 480;
+
+/// 個別に描くアプリの最大数（残りは「その他」にまとめる）
+const MAX_APPS: usize = 7;
+
+/// 「その他」の表示名
+const OTHERS: &str = "その他";
+
+/// アプリの色（「その他」は灰色）
+const PALETTE: [RGBColor; MAX_APPS] = [
+    RGBColor(0x1f, 0x77, 0xb4),
+    RGBColor(0xff, 0x7f, 0x0e),
+    RGBColor(0x2c, 0xa0, 0x2c),
+    RGBColor(0xd6, 0x27, 0x28),
+    RGBColor(0x94, 0x67, 0xbd),
+    RGBColor(0x8c, 0x56, 0x4b),
+    RGBColor(0xe3, 0x77, 0xc2),
+];
+const OTHERS_COLOR: RGBColor = RGBColor(0xaa, 0xaa, 0xaa);
+
+/// 1日分のアプリ別・時間帯別のグラフ
+pub struct DailyChart {
+    date: String,
+    apps: Vec<AppHourly>,
+}
+
+impl DailyChart {
+    /// アプリ別・時間帯別の時間（`Report::hourly_by_app`、時間の降順）から作成
+    ///
+    /// 上位のアプリだけを個別に描き、残りは「その他」にまとめる
+    pub fn new(date: &str, mut apps: Vec<AppHourly>) -> Self {
+        apps.retain(|app| app.duration_seconds > 0);
+        if apps.len() > MAX_APPS {
+            let mut others = AppHourly {
+                app_name: OTHERS.to_string(),
+                duration_seconds: 0,
+                hourly: [0; 24],
+            };
+            for app in apps.drain(MAX_APPS - 1..) {
+                others.duration_seconds += app.duration_seconds;
+                for (total, seconds) in others.hourly.iter_mut().zip(app.hourly) {
+                    *total += seconds;
+                }
+            }
+            apps.push(others);
+        }
+        Self {
+            date: date.to_string(),
+            apps,
+        }
+    }
+
+    /// 描画するアプリ（「その他」を含む）
+    pub fn apps(&self) -> &[AppHourly] {
+        &self.apps
+    }
+
+    /// SVGとして描画
+    pub fn to_svg(&self) -> Result<String, ChartError> {
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (WIDTH, HEIGHT)).into_drawing_area();
+            self.draw(&root)
+                .map_err(|e| ChartError::RenderFailed(e.to_string()))?;
+            root.present()
+                .map_err(|e| ChartError::RenderFailed(e.to_string()))?;
+        }
+        Ok(svg)
+    }
+
+    /// 拡張子（.png / .svg）に応じた形式でファイルに書き出す
+    pub fn save(&self, path: &Path) -> Result<(), ChartError> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "png" => fs::write(
+                path,
+                render_png(&self.to_svg()?).map_err(ChartError::RenderFailed)?,
+            )?,
+            "svg" => fs::write(path, self.to_svg()?)?,
+            _ => return Err(ChartError::UnsupportedFormat(path.display().to_string())),
+        }
+        Ok(())
+    }
+
+    /// 左に円グラフ、右に積み上げ棒グラフを描く
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, plotters::coord::Shift>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        root.fill(&WHITE)?;
+        let root = root.titled(
+            &format!("{} のアプリ別時間", self.date),
+            (FONT_FAMILY, 24).into_font(),
+        )?;
+        let (left, right) = root.split_horizontally(PIE_AREA_WIDTH);
+        self.draw_pie(&left)?;
+        self.draw_hourly(&right)
+    }
+
+    /// アプリ別の円グラフ
+    fn draw_pie<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, plotters::coord::Shift>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        let (width, height) = area.dim_in_pixel();
+        let center = (width as i32 / 2, height as i32 / 2);
+        let radius = width.min(height) as f64 * 0.3;
+        let sizes: Vec<f64> = self
+            .apps
+            .iter()
+            .map(|app| app.duration_seconds as f64)
+            .collect();
+        let colors: Vec<RGBColor> = (0..self.apps.len()).map(|i| self.color(i)).collect();
+        let labels: Vec<&str> = self.apps.iter().map(|app| app.app_name.as_str()).collect();
+
+        let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+        pie.start_angle(-90.0);
+        pie.label_style((FONT_FAMILY, 14).into_font().color(&BLACK));
+        pie.percentages((FONT_FAMILY, 12).into_font().color(&WHITE));
+        area.draw(&pie)
+    }
+
+    /// 時間帯（0〜23時）別のアプリの積み上げ棒グラフ（分）
+    fn draw_hourly<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, plotters::coord::Shift>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        let max_minutes = (0..24)
+            .map(|hour| self.apps.iter().map(|app| app.hourly[hour]).sum::<u64>())
+            .max()
+            .unwrap_or(0) as f64
+            / 60.0;
+
+        // 凡例が棒に重ならないよう上に余白を取る
+        let y_max = max_minutes.max(60.0) * 4.0 / 3.0;
+        let mut chart = ChartBuilder::on(area)
+            .margin(20)
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(0f64..24f64, 0f64..y_max)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_labels(13)
+            .x_label_formatter(&|hour| format!("{}時", *hour as u32))
+            .y_desc("分")
+            .label_style((FONT_FAMILY, 12))
+            .axis_desc_style((FONT_FAMILY, 14))
+            .draw()?;
+
+        let mut bottoms = [0f64; 24];
+        for (i, app) in self.apps.iter().enumerate() {
+            let color = self.color(i);
+            let bars: Vec<_> = (0..24)
+                .filter(|&hour| app.hourly[hour] > 0)
+                .map(|hour| {
+                    let bottom = bottoms[hour];
+                    let top = bottom + app.hourly[hour] as f64 / 60.0;
+                    bottoms[hour] = top;
+                    let x = hour as f64;
+                    Rectangle::new([(x + 0.1, bottom), (x + 0.9, top)], color.filled())
+                })
+                .collect();
+            chart
+                .draw_series(bars)?
+                .label(app.app_name.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled())
+                });
+        }
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .label_font((FONT_FAMILY, 12))
+            .draw()
+    }
+
+    /// i番目のアプリの色
+    fn color(&self, i: usize) -> RGBColor {
+        if self.apps[i].app_name == OTHERS && i == self.apps.len() - 1 {
+            OTHERS_COLOR
+        } else {
+            PALETTE[i % PALETTE.len()]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn app(name: &str, hour: usize, seconds: u64) -> AppHourly {
+        let mut hourly = [0; 24];
+        hourly[hour] = seconds;
+        AppHourly {
+            app_name: name.to_string(),
+            duration_seconds: seconds,
+            hourly,
+        }
+    }
+
+    #[test]
+    fn test_group_minor_apps_into_others() {
+        let apps = (0..10)
+            .map(|i| app(&format!("App{}", i), 9 + i, 600 - i as u64 * 10))
+            .collect();
+        let chart = DailyChart::new("2025-01-10", apps);
+
+        assert_eq!(chart.apps().len(), MAX_APPS);
+        let others = &chart.apps()[MAX_APPS - 1];
+        assert_eq!(others.app_name, OTHERS);
+        assert_eq!(others.duration_seconds, 540 + 530 + 520 + 510);
+        assert_eq!(others.hourly[15], 540);
+        assert_eq!(chart.color(MAX_APPS - 1), OTHERS_COLOR);
+    }
+
+    #[test]
+    fn test_save_png_and_svg() {
+        let temp_dir = TempDir::new().unwrap();
+        let chart = DailyChart::new(
+            "2025-01-10",
+            vec![app("VS Code", 10, 2400), app("Slack & Mail", 14, 600)],
+        );
+
+        let svg_path = temp_dir.path().join("chart.svg");
+        chart.save(&svg_path).unwrap();
+        let svg = fs::read_to_string(&svg_path).unwrap();
+        assert!(svg.contains("2025-01-10 のアプリ別時間"));
+        assert!(svg.contains("Slack &amp; Mail"));
+
+        let png_path = temp_dir.path().join("chart.png");
+        chart.save(&png_path).unwrap();
+        assert!(fs::read(&png_path).unwrap().starts_with(b"\x89PNG"));
+
+        assert!(matches!(
+            chart.save(&temp_dir.path().join("chart.gif")),
+            Err(ChartError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -1,15 +1,57 @@
 //! CLIモジュール
 
-use crate::capture::CaptureLoop;
-use crate::config::{CliArgs, Config};
-use crate::database::Database;
-use crate::ocr;
+use crate::activitywatch;
+use crate::capture::{self, CaptureLoop};
+use crate::chart::DailyChart;
+use crate::config::{self, CliArgs, Config, PathOverrides, Paths};
+use crate::daemon;
+use crate::database::{
+    database_file_size, format_timestamp, Alert, AlertKind, CaptureRecord, Database, Highlight,
+    IntegrityReport, Note, OcrJob, OcrJobStatus, SessionKind, SessionRecord, OCR_MAX_ATTEMPTS,
+};
+use crate::export::{self, ExportFormat, ImportFormat};
+use crate::goals::{format_goal, GoalProgress};
+use crate::highlight;
+use crate::html_report;
+use crate::i18n::Message;
+use crate::image_store::{self, ImageStore};
+use crate::instance_lock;
+use crate::launchd;
+use crate::logging;
+use crate::markdown_report::{EmbedImages, MarkdownReport};
+use crate::mcp::McpServer;
+use crate::metadata::{Metadata, ACCESSIBILITY_SETTINGS, SCREEN_RECORDING_SETTINGS};
+use crate::obsidian::DailyNote;
+use crate::ocr::OcrBackendKind;
+use crate::ocr_batch::OcrBatch;
 use crate::pause_control::PauseControl;
-use crate::report::Report;
+use crate::pomodoro::{Pomodoro, PomodoroControl, PomodoroPhase, PomodoroStatus};
+use crate::progress::ProgressBar;
+use crate::report::{
+    format_bytes, format_duration, format_highlight, logical_date, parse_period, resolve_date,
+    validate_date, AppFilter, HourlyBreakdown, Report, MAX_PERIOD_DAYS,
+};
+use crate::rescuetime;
+use crate::runtime;
+use crate::server::ApiServer;
+use crate::shortcut::{render_report, ShortcutAction};
+use crate::standup::{previous_workday, render_standup};
+use crate::uninstall::PurgePlan;
+use crate::wordcloud::{Tokenizer, WordCloud};
+use crate::worklog::{self, TicketMatcher, WorklogClient};
 use anyhow::Result;
-use chrono::Local;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use dialoguer::{Confirm, Input, Select};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 
 /// Habit Tracker - macOS用作業トラッキングツール
@@ -17,13 +59,59 @@ use tracing::info;
 #[command(name = "tracker")]
 #[command(about = "macOS用作業トラッキングツール", long_about = None)]
 pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalOptions,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// 全サブコマンド共通のオプション
+#[derive(Args, Debug, Clone, Default)]
+pub struct GlobalOptions {
+    /// 使用するプロファイル（設定・DB・画像をプロファイルごとに分けて記録する）
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// 設定ファイルのパス
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// データベースのパス（設定ファイルの db_path より優先）
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db_path: Option<PathBuf>,
+}
+
+impl GlobalOptions {
+    /// 設定の読み込みに使うパスの上書き
+    pub fn path_overrides(&self) -> PathOverrides {
+        PathOverrides {
+            profile: self.profile.clone(),
+            config_file: self.config.clone(),
+            db_path: self.db_path.clone(),
+        }
+    }
+}
+
 /// サブコマンド
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// 初期セットアップ（設定ファイルの生成・権限の確認・テストキャプチャ・launchdへの登録）を対話的に行う
+    Init,
+    /// launchdの登録を解除してアンインストール（`--purge-data` でDB・画像・設定・ログも削除する）
+    Uninstall {
+        /// DB・画像・設定・ログも削除する
+        #[arg(long)]
+        purge_data: bool,
+
+        /// 何もせずに対象だけを表示
+        #[arg(long)]
+        dry_run: bool,
+
+        /// 確認せずに実行
+        #[arg(short, long)]
+        yes: bool,
+    },
     /// トラッキングを開始
     Start {
         /// キャプチャ間隔（秒）
@@ -33,20 +121,166 @@ pub enum Commands {
         /// JPEG品質（0-100）
         #[arg(short, long)]
         quality: Option<u8>,
+
+        /// 端末から切り離してバックグラウンドで起動（出力はログディレクトリのdaemon.logへ）
+        #[arg(short, long)]
+        daemon: bool,
+    },
+    /// 起動中のトラッカーを停止（処理中のキャプチャを終えてから終了する）
+    Stop {
+        /// 停止を待つ最大時間（秒）
+        #[arg(short, long, default_value_t = 60)]
+        timeout: u64,
+    },
+    /// 起動中のトラッカーに設定ファイルを再読み込みさせる（SIGHUP）
+    Reload,
+    /// 1回分のキャプチャ（スクリーンショット・メタデータ・OCR）を記録
+    Capture {
+        /// 自分で撮らず、起動中のトラッカーに即時キャプチャさせる（SIGUSR1）
+        #[arg(long)]
+        now: bool,
     },
     /// トラッキングを一時停止
-    Pause,
+    Pause {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
     /// トラッキングを再開
-    Resume,
+    Resume {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// 現在の状態を表示
+    ///
+    /// 終了コードは記録中なら0、一時停止中なら3、トラッカーが起動していなければ4
+    Status {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+
+        /// 何も出力せず、終了コードだけで状態を返す（シェルのプロンプト向け）
+        #[arg(short, long, conflicts_with = "json")]
+        quiet: bool,
+    },
+    /// トラッカーの稼働履歴（セッション）を表示
+    Sessions {
+        /// 表示件数
+        #[arg(short, long, default_value_t = 20)]
+        limit: i64,
+
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// ポモドーロ（作業/休憩サイクル）の開始・終了・状態表示
+    ///
+    /// 実行中は `tracker start` がサイクルの切り替わりで通知し、休憩中のキャプチャをプライベート扱いにする
+    Pomodoro {
+        #[command(subcommand)]
+        action: PomodoroAction,
+    },
+    /// 通知したアラート（休憩・脱線・キャプチャの失敗・ポモドーロ）の履歴を表示
+    Alerts {
+        /// 今日のアラートだけを表示
+        #[arg(short, long)]
+        today: bool,
+
+        /// 表示件数（新しいものから）
+        #[arg(short, long, default_value_t = 50)]
+        limit: i64,
+
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// 目標（config.tomlの [goals]）の達成状況と連続達成日数（ストリーク）を表示
+    Goals {
+        /// 対象日（YYYY-MM-DD / yesterday / -2d など、省略時は今日）
+        #[arg(allow_hyphen_values = true)]
+        when: Option<String>,
+
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
     /// 日次レポートを表示
+    ///
+    /// 日付を指定せずに端末で実行すると、キャプチャのある日付から選択できる
     Report {
-        /// レポート対象日（YYYY-MM-DD形式）
+        /// レポート対象日（YYYY-MM-DD / today / yesterday / -2d / -1w / last-friday）
+        #[arg(allow_hyphen_values = true, conflicts_with_all = ["date", "today"])]
+        when: Option<String>,
+
+        /// レポート対象日（相対指定も可）
         #[arg(short, long, conflicts_with = "today")]
         date: Option<String>,
 
         /// 今日のレポートを表示
         #[arg(short, long)]
         today: bool,
+
+        /// 指定アプリのみを対象にする（カンマ区切りで複数指定可）
+        #[arg(short, long, value_delimiter = ',')]
+        app: Vec<String>,
+
+        /// 集計から除外するアプリ（カンマ区切り）
+        #[arg(short, long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// サマリーをObsidianのデイリーノートにも書き込む
+        #[arg(long)]
+        obsidian: bool,
+
+        /// 指定キーワードがOCRテキストに出現した時間の推移を表示（カンマ区切り）
+        #[arg(short, long, value_delimiter = ',', conflicts_with = "obsidian")]
+        keywords: Vec<String>,
+
+        /// キーワード推移の集計日数（対象日までの日数、1〜366）
+        #[arg(
+            long,
+            default_value_t = 7,
+            requires = "keywords",
+            value_parser = clap::value_parser!(u32).range(1..=MAX_PERIOD_DAYS as i64)
+        )]
+        days: u32,
+
+        /// 対象日までの期間（例: 4w, 10d）の日別・カテゴリ別の作業時間の推移を表示
+        #[arg(long, value_parser = parse_period, conflicts_with_all = ["keywords", "obsidian"])]
+        trend: Option<u32>,
+
+        /// 1時間ごとの合計キャプチャ数と主要アプリ上位3つを表示
+        #[arg(long, conflicts_with_all = ["keywords", "trend"])]
+        hourly: bool,
+
+        /// HTMLレポートを一時ファイルに生成して既定のブラウザで開く
+        #[arg(long, conflicts_with_all = ["keywords", "trend", "hourly", "json"])]
+        open: bool,
+
+        /// タイムラインに各キャプチャのOCRテキストの抜粋（先頭100文字）を添える
+        #[arg(long, conflicts_with_all = ["keywords", "trend", "hourly"])]
+        with_ocr: bool,
+
+        /// 出力形式（md: 時間ごとの見出しに分けたMarkdown）
+        #[arg(long, value_enum, conflicts_with_all = ["keywords", "trend", "hourly", "open", "json"])]
+        format: Option<ReportFormat>,
+
+        /// Markdownに画像を埋め込む（thumbnail: 時間帯ごとの代表画像のサムネイル）
+        #[arg(long, value_enum, requires = "format")]
+        embed_images: Option<EmbedImages>,
+
+        /// Markdownの出力ファイル（省略時は標準出力、画像のリンクはこのファイルからの相対パス）
+        #[arg(short, long, requires = "format")]
+        output: Option<PathBuf>,
+
+        /// サムネイルを作ってコピーするフォルダ（省略時は元の画像にリンク）
+        #[arg(long, requires = "embed_images")]
+        image_dir: Option<PathBuf>,
+
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
     },
     /// 画像からOCRでテキストを抽出
     Ocr {
@@ -57,65 +291,812 @@ pub enum Commands {
         /// 未処理のキャプチャをOCR処理（件数指定）
         #[arg(short, long)]
         batch: Option<i64>,
+
+        /// 未処理のキャプチャをすべてOCR処理（中断しても次回は続きから再開する）
+        #[arg(long, conflicts_with_all = ["file", "batch", "redo"])]
+        all: bool,
+
+        /// バッチ処理の並列数
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+
+        /// 信頼度・座標も出力/保存する
+        #[arg(long)]
+        details: bool,
+
+        /// 処理済みのキャプチャもOCRし直す（--date/--whereで対象を指定）
+        #[arg(long)]
+        redo: bool,
+
+        /// 再処理の対象日（YYYY-MM-DD形式）
+        #[arg(long, requires = "redo")]
+        date: Option<String>,
+
+        /// 再処理の対象を絞り込むSQL条件式（例: "app = 'Safari'"）
+        #[arg(long = "where", requires = "redo")]
+        where_clause: Option<String>,
+
+        /// プライベートなキャプチャの既存OCRテキストを一括消去
+        #[arg(long, conflicts_with_all = ["file", "batch", "all", "redo"])]
+        scrub_private: bool,
+
+        /// OCRに失敗したキャプチャ（再試行待ち・打ち切り）の一覧を表示
+        #[arg(long, conflicts_with_all = ["file", "batch", "all", "redo", "scrub_private"])]
+        failed: bool,
+
+        /// OCRテキストの圧縮辞書を学習し、既存のテキストを圧縮し直す
+        #[arg(long, conflicts_with_all = ["file", "batch", "all", "redo", "scrub_private", "failed"])]
+        compress: bool,
+    },
+    /// 作業記録を外部サービス向け形式でエクスポート
+    Export {
+        /// 出力形式
+        #[arg(short, long, value_enum)]
+        format: ExportFormat,
+
+        /// 対象日（YYYY-MM-DD / today / yesterday / -2d など、省略時は今日）
+        #[arg(short, long)]
+        date: Option<String>,
+
+        /// 出力ファイル（省略時は標準出力）
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Toggl Trackのユーザーのメールアドレス
+        #[arg(long, default_value = "")]
+        email: String,
+
+        /// アプリ名をカテゴリ名に置き換え、ウィンドウタイトル・OCRテキスト・画像パスを含めない
+        #[arg(long)]
+        anonymize: bool,
+    },
+    /// 朝会用に前営業日のプロジェクト別ハイライトをMarkdownで出力
+    Standup {
+        /// 対象日（YYYY-MM-DD形式、省略時は前営業日）
+        #[arg(short, long)]
+        date: Option<String>,
+    },
+    /// 1日分のOCRテキストの頻出語からワードクラウドを生成
+    Wordcloud {
+        /// 対象日（YYYY-MM-DD形式、省略時は今日）
+        #[arg(short, long)]
+        date: Option<String>,
+
+        /// 出力ファイル（.svg または .png、省略時は wordcloud-<対象日>.svg）
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// 描画する語の最大数
+        #[arg(short, long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// 1日分のアプリ別円グラフと時間帯別積み上げ棒グラフを1枚の画像に描画
+    Chart {
+        /// 対象日（YYYY-MM-DD / today / yesterday / -2d など、省略時は今日）
+        #[arg(short, long, allow_hyphen_values = true)]
+        date: Option<String>,
+
+        /// 出力ファイル（.png または .svg、省略時は chart-<対象日>.png）
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// macOSのショートカット.appから操作する（一時停止・再開・メモの追加・レポートの取得）
+    ///
+    /// 操作は引数か標準入力で渡す。例: `tracker shortcut note 設計レビュー完了`、
+    /// `echo "tracker://report?date=yesterday" | tracker shortcut`
+    Shortcut {
+        /// 結果をJSONで出力（操作より前に指定する）
+        #[arg(long)]
+        json: bool,
+
+        /// 操作（pause / resume / note <内容> / report [対象日]、または tracker:// 形式のURL）
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+    /// Jira/Linearへの工数登録（要 `[worklog]` 設定）
+    Worklog {
+        #[command(subcommand)]
+        action: WorklogAction,
+    },
+    /// 1日分の時間帯ごとの代表画像（OCRテキストが最も多い画像）を選び直して表示
+    ///
+    /// トラッカーは時間帯が変わるたびに自動で選ぶ。機能の追加前の記録や、
+    /// OCRを後から行った日を選び直すときに使う
+    Highlights {
+        /// 対象日（YYYY-MM-DD / today / yesterday / -2d など、省略時は今日）
+        #[arg(short, long, allow_hyphen_values = true)]
+        date: Option<String>,
+
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// キャプチャを削除（画像ファイルも削除する）
+    Delete {
+        /// 削除するキャプチャのID（カンマ区切りで複数指定可）
+        #[arg(long, value_delimiter = ',', required_unless_present = "from", conflicts_with_all = ["from", "to"])]
+        id: Vec<i64>,
+
+        /// 削除する期間の開始（`YYYY-MM-DD` または `YYYY-MM-DDTHH:MM[:SS]`）
+        #[arg(long, requires = "to")]
+        from: Option<String>,
+
+        /// 削除する期間の終了（この日・時刻を含む）
+        #[arg(long, requires = "from")]
+        to: Option<String>,
+
+        /// 削除せずに対象だけを表示
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 外部サービスの記録をインポート
+    Import {
+        /// 入力形式
+        #[arg(short, long, value_enum)]
+        format: ImportFormat,
+
+        /// 入力ファイル
+        file: PathBuf,
+    },
+    /// MCPサーバーとして起動（stdio）
+    Mcp,
+    /// ローカルHTTP APIサーバーを起動
+    Serve {
+        /// 待ち受けポート
+        #[arg(short, long, default_value_t = 8686)]
+        port: u16,
+    },
+    /// 記録の統計を表示
+    Stats {
+        /// キャプチャ処理（スクリーンショット・OCR・DB書き込み）の所要時間のp50/p95を表示
+        #[arg(long, required_unless_present = "storage", conflicts_with = "storage")]
+        perf: bool,
+
+        /// 保存した画像の枚数と容量の日別の増加量を表示
+        #[arg(long)]
+        storage: bool,
+
+        /// 集計日数（今日までの日数、4w や 10d の形式）
+        #[arg(long, value_parser = parse_period, default_value = "7d")]
+        period: u32,
+
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// データベースの整合性チェックとVACUUM
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// ファイルに出力されたログを表示
+    Logs {
+        /// 表示する直近の行数
+        #[arg(short = 'n', long, default_value_t = 100)]
+        tail: usize,
+    },
+    /// 使用中の設定ファイル・DB・画像・ログなどのパスを表示
+    Paths {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// 設定・データ・画像・ログのフォルダをFinderで開く
+    ///
+    /// 例: tracker open images --date 2025-01-10
+    Open {
+        /// 開くフォルダ
+        #[arg(value_enum)]
+        target: OpenTarget,
+
+        /// 開く画像の日付（imagesのみ、YYYY-MM-DD / today / yesterday / -2d など）
+        #[arg(short, long, allow_hyphen_values = true)]
+        date: Option<String>,
+    },
+    /// シェル補完スクリプトを標準出力に出力
+    ///
+    /// 例: tracker completions zsh > ~/.zfunc/_tracker
+    Completions {
+        /// 対象のシェル
+        shell: Shell,
+    },
+    /// manページ（roff形式）を出力
+    Man {
+        /// 標準出力ではなく、サブコマンドごとのmanページをこのディレクトリに書き出す
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+/// `tracker pomodoro` のサブコマンド
+#[derive(Subcommand, Debug)]
+pub enum PomodoroAction {
+    /// ポモドーロを開始（実行中なら今から始め直す）
+    Start {
+        /// 作業の長さ（分）
+        #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u64).range(1..))]
+        work: u64,
+
+        /// 休憩の長さ（分）
+        #[arg(long = "break", default_value_t = 5)]
+        break_minutes: u64,
+    },
+    /// ポモドーロを終了
+    Stop,
+    /// 実行中のポモドーロの段階と残り時間を表示
+    Status {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// `tracker db` のサブコマンド
+#[derive(Subcommand, Debug)]
+pub enum DbAction {
+    /// PRAGMA integrity_check / foreign_key_check でDBの破損と参照切れを調べる
+    ///
+    /// 問題が見つかれば終了コード1で終了する
+    Check {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+    /// VACUUMとWALチェックポイントで空き領域を解放し、前後のファイルサイズを表示
+    Vacuum {
+        /// 結果をJSONで出力
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// `tracker worklog` のサブコマンド
+#[derive(Subcommand, Debug)]
+pub enum WorklogAction {
+    /// ウィンドウタイトルのチケットIDごとの作業時間を、Jira/Linearに工数として登録
+    ///
+    /// 登録済みの時間は記録しておき、同じ日に再実行したときは増えた分だけを登録する
+    Push {
+        /// 対象日（YYYY-MM-DD / today / yesterday / -2d など、省略時は今日）
+        #[arg(short, long, allow_hyphen_values = true)]
+        date: Option<String>,
+
+        /// 登録せずに、登録する工数を表示する
+        #[arg(long)]
+        dry_run: bool,
     },
 }
 
+/// `tracker open` で開くフォルダ
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenTarget {
+    /// 設定ファイルのあるフォルダ
+    Config,
+    /// DB・ログなどを置くフォルダ
+    Data,
+    /// 画像のフォルダ（`--date` でその日のフォルダ）
+    Images,
+    /// ログのフォルダ
+    Logs,
+}
+
+/// `tracker report` の出力形式
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// 端末向けのテキスト
+    Text,
+    /// Markdown（Obsidianでそのまま閲覧できる形式）
+    Md,
+}
+
+/// `tracker status` の終了コード: 一時停止中
+pub const EXIT_PAUSED: u8 = 3;
+
+/// `tracker status` の終了コード: トラッカーが起動していない
+pub const EXIT_STOPPED: u8 = 4;
+
 /// CLIエントリポイント
-pub fn run() -> Result<()> {
-    let cli = Cli::parse();
+///
+/// プロファイル・ログ・表示言語は呼び出し側で初期化しておく。
+/// 戻り値は終了コード（`status` 以外は成功なら0）
+pub fn run(cli: Cli) -> Result<ExitCode> {
+    let mut exit_code = ExitCode::SUCCESS;
 
     match cli.command {
-        Commands::Start { interval, quality } => {
+        Commands::Init => run_init()?,
+        Commands::Uninstall {
+            purge_data,
+            dry_run,
+            yes,
+        } => run_uninstall(purge_data, dry_run, yes)?,
+        Commands::Start {
+            interval,
+            quality,
+            daemon,
+        } => {
             let cli_args = CliArgs { interval, quality };
             let config = Config::load(&cli_args)?;
+            // バックグラウンドで起動する前に、権限がなければこの場で知らせる
+            check_screen_recording()?;
+
+            if daemon {
+                let daemon = daemon::spawn(&config, &cli_args)?;
+                println!(
+                    "{}",
+                    Message::DaemonStarted.format(&[&daemon.pid, &daemon.log_path.display()])
+                );
+                return Ok(ExitCode::SUCCESS);
+            }
 
             info!("トラッキングを開始します");
+            runtime::run(config, cli_args)?;
+        }
+        Commands::Stop { timeout } => {
+            let config = Config::load(&CliArgs::default())?;
+            match instance_lock::stop(&config.pid_file, Duration::from_secs(timeout))? {
+                Some(pid) => println!("{}", Message::TrackerStopped.format(&[&pid])),
+                None => println!("{}", Message::TrackerNotRunning.text()),
+            }
+        }
+        Commands::Reload => {
+            let config = Config::load(&CliArgs::default())?;
+            match instance_lock::signal(&config.pid_file, "HUP")? {
+                Some(pid) => println!("{}", Message::ReloadRequested.format(&[&pid])),
+                None => println!("{}", Message::TrackerNotRunning.text()),
+            }
+        }
+        Commands::Capture { now: true } => {
+            let config = Config::load(&CliArgs::default())?;
+            match instance_lock::signal(&config.pid_file, "USR1")? {
+                Some(pid) => println!("{}", Message::CaptureRequested.format(&[&pid])),
+                None => println!("{}", Message::TrackerNotRunning.text()),
+            }
+        }
+        Commands::Capture { now: false } => {
+            let config = Config::load(&CliArgs::default())?;
+            if PauseControl::new(config.pause_file.clone()).is_paused() {
+                println!("{}", Message::CaptureSkippedPaused.text());
+                return Ok(ExitCode::SUCCESS);
+            }
+
             let capture_loop = CaptureLoop::new(config)?;
-            capture_loop.setup_signal_handler()?;
-            capture_loop.run()?;
+            if capture_loop.paused_by_focus() {
+                println!("{}", Message::CaptureSkippedFocus.text());
+                return Ok(ExitCode::SUCCESS);
+            }
+            check_screen_recording()?;
+
+            let failures = capture_loop.capture_once()?;
+            if failures.is_empty() {
+                println!("{}", Message::Captured.text());
+            } else {
+                println!("{}", Message::CapturedWithFailures.text());
+                for failure in &failures {
+                    println!("  {}", failure);
+                }
+            }
         }
-        Commands::Pause => {
+        Commands::Pause { json } => {
             let config = Config::load(&CliArgs::default())?;
             let pause_control = PauseControl::new(config.pause_file);
             pause_control.pause()?;
-            println!("トラッキングを一時停止しました");
+            if json {
+                print_json(&PauseOutput { paused: true })?;
+            } else {
+                println!("{}", Message::TrackingPaused.text());
+            }
         }
-        Commands::Resume => {
+        Commands::Resume { json } => {
             let config = Config::load(&CliArgs::default())?;
             let pause_control = PauseControl::new(config.pause_file);
             pause_control.resume()?;
-            println!("トラッキングを再開しました");
+            if json {
+                print_json(&PauseOutput { paused: false })?;
+            } else {
+                println!("{}", Message::TrackingResumed.text());
+            }
+        }
+        Commands::Status { json, quiet } => {
+            let config = Config::load(&CliArgs::default())?;
+            let paused = PauseControl::new(config.pause_file.clone()).is_paused();
+            let pid = instance_lock::running_pid(&config.pid_file)?;
+            let state = TrackerState::new(pid.is_some(), paused);
+            exit_code = state.exit_code();
+            if quiet {
+                return Ok(exit_code);
+            }
+
+            let db = Database::open(&config.db_path)?;
+            let latest = db.get_latest_capture()?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone());
+
+            let date = logical_date(&Local::now(), config.day_boundary_hour);
+            let today = report.daily(&date)?;
+            let status = StatusOutput {
+                state,
+                running: pid.is_some(),
+                pid,
+                paused,
+                date,
+                today_seconds: today.total_seconds,
+                today_captures: today.timeline.len(),
+                last_capture_at: latest.as_ref().map(|c| c.captured_at.clone()),
+                last_app: latest.map(|c| c.active_app),
+            };
+
+            if json {
+                print_json(&status)?;
+            } else {
+                let state = match status.state {
+                    TrackerState::Recording => Message::StatusRecording,
+                    TrackerState::Paused => Message::StatusPaused,
+                    TrackerState::Stopped => Message::StatusStopped,
+                };
+                println!("{}", Message::Status.format(&[&state.text()]));
+                if let (Some(at), Some(app)) = (&status.last_capture_at, &status.last_app) {
+                    println!("{}", Message::LastCapture.format(&[at, app]));
+                }
+                println!(
+                    "{}",
+                    Message::DayRecord.format(&[
+                        &status.date,
+                        &format_duration(status.today_seconds),
+                        &Message::Captures.format(&[&status.today_captures]),
+                    ])
+                );
+            }
+        }
+        Commands::Sessions { limit, json } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let sessions = db.get_recent_sessions(SessionKind::Tracker, limit)?;
+
+            if json {
+                print_json(&SessionsOutput { sessions })?;
+            } else if sessions.is_empty() {
+                println!("{}", Message::NoSessions.text());
+            } else {
+                for session in &sessions {
+                    let ended = match (&session.ended_at, session.end_reason) {
+                        (Some(ended_at), Some(reason)) => {
+                            format!("{} ({})", ended_at, reason.as_str())
+                        }
+                        (Some(ended_at), None) => ended_at.clone(),
+                        (None, _) => Message::SessionRunning.text().to_string(),
+                    };
+                    println!(
+                        "{} - {} | v{} @ {}",
+                        session.started_at,
+                        ended,
+                        session.version.as_deref().unwrap_or("?"),
+                        session.hostname.as_deref().unwrap_or("?")
+                    );
+                }
+            }
+        }
+        Commands::Pomodoro { action } => {
+            let config = Config::load(&CliArgs::default())?;
+            let control = PomodoroControl::new(config.pomodoro_file);
+            match action {
+                PomodoroAction::Start {
+                    work,
+                    break_minutes,
+                } => {
+                    control.start(&Pomodoro::new(&Local::now(), work, break_minutes))?;
+                    println!(
+                        "{}",
+                        Message::PomodoroStarted.format(&[&work, &break_minutes])
+                    );
+                }
+                PomodoroAction::Stop => {
+                    control.stop()?;
+                    println!("{}", Message::PomodoroStopped.text());
+                }
+                PomodoroAction::Status { json } => {
+                    let pomodoro = control.current();
+                    let status = pomodoro
+                        .as_ref()
+                        .and_then(|pomodoro| pomodoro.status(&Local::now()));
+                    if json {
+                        print_json(&PomodoroOutput { pomodoro, status })?;
+                    } else if let Some(status) = status {
+                        let phase = match status.phase {
+                            PomodoroPhase::Work => Message::PomodoroWork,
+                            PomodoroPhase::Break => Message::PomodoroBreak,
+                        };
+                        println!(
+                            "{}",
+                            Message::PomodoroStatus.format(&[
+                                &phase.text(),
+                                &status.cycle,
+                                &format_duration(status.remaining_seconds),
+                                &status.completed,
+                            ])
+                        );
+                    } else {
+                        println!("{}", Message::PomodoroNotRunning.text());
+                    }
+                }
+            }
         }
-        Commands::Report { date, today } => {
+        Commands::Alerts { today, limit, json } => {
             let config = Config::load(&CliArgs::default())?;
             let db = Database::open(&config.db_path)?;
-            let report = Report::new(db, config.interval_seconds);
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour);
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let alerts = report.alerts(today.then_some(current_date.as_str()), limit)?;
 
-            let target_date = if today {
-                Local::now().format("%Y-%m-%d").to_string()
-            } else if let Some(d) = date {
-                d
+            if json {
+                print_json(&AlertsOutput { alerts })?;
+            } else if alerts.is_empty() {
+                println!("{}", Message::NoAlerts.text());
             } else {
-                Local::now().format("%Y-%m-%d").to_string()
+                for alert in &alerts {
+                    let kind = match alert.kind {
+                        AlertKind::Break => Message::AlertBreak,
+                        AlertKind::Distraction => Message::AlertDistraction,
+                        AlertKind::CaptureFailure => Message::AlertCaptureFailure,
+                        AlertKind::Pomodoro => Message::AlertPomodoro,
+                        AlertKind::RepeatedError => Message::AlertRepeatedError,
+                        AlertKind::Idle => Message::AlertIdle,
+                    };
+                    println!(
+                        "{} [{}] {}",
+                        alert.occurred_at.get(..16).unwrap_or(&alert.occurred_at),
+                        kind.text(),
+                        alert.message
+                    );
+                }
+            }
+        }
+        Commands::Goals { when, json } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_categories(config.categories.clone())
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone())
+                .with_goals(config.goals.clone());
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let date = match when {
+                Some(spec) => resolve_date(&spec, &current_date)?,
+                None => current_date,
+            };
+            let goals = report.goals(&date)?;
+
+            if json {
+                print_json(&GoalsOutput { date, goals })?;
+            } else if goals.is_empty() {
+                println!("{}", Message::NoGoals.text());
+            } else {
+                for progress in &goals {
+                    println!("{}", format_goal(progress));
+                }
+            }
+        }
+        Commands::Report {
+            when,
+            date,
+            today,
+            app,
+            exclude,
+            obsidian,
+            keywords,
+            days,
+            trend,
+            hourly,
+            open,
+            with_ocr,
+            format,
+            embed_images,
+            output,
+            image_dir,
+            json,
+        } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_app_filter(AppFilter::new(app, exclude))
+                .with_categories(config.categories.clone())
+                .with_aliases(config.aliases.clone())
+                .with_distractions(config.distractions.clone())
+                .with_meetings(config.meeting.clone())
+                .with_non_work(config.non_work.clone())
+                .with_focus_min_minutes(config.focus_min_minutes)
+                .with_goals(config.goals.clone())
+                .with_ocr_excerpts(with_ocr);
+
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let target_date = match when.or(date) {
+                Some(spec) => resolve_date(&spec, &current_date)?,
+                None if !today
+                    && !json
+                    && std::io::stdin().is_terminal()
+                    && std::io::stdout().is_terminal() =>
+                {
+                    match pick_date(&report, &current_date)? {
+                        Some(date) => date,
+                        None => return Ok(ExitCode::SUCCESS),
+                    }
+                }
+                None => current_date,
             };
 
-            report.print(&target_date)?;
+            if let Some(days) = trend {
+                if json {
+                    print_json(&report.trend(&target_date, days)?)?;
+                } else {
+                    report.print_trend(&target_date, days)?;
+                }
+            } else if !keywords.is_empty() {
+                if json {
+                    print_json(&report.keyword_trend(&keywords, &target_date, days)?)?;
+                } else {
+                    report.print_keyword_trend(&keywords, &target_date, days)?;
+                }
+            } else if hourly {
+                if json {
+                    print_json(&HourlyOutput {
+                        hours: report.hourly_breakdown(&target_date)?,
+                        date: target_date.clone(),
+                    })?;
+                } else {
+                    report.print_hourly(&target_date)?;
+                }
+            } else if json {
+                print_json(&report.daily(&target_date)?)?;
+            } else if open {
+                let path = html_report::open_in_browser(&report.daily(&target_date)?)?;
+                println!("{}", Message::HtmlReportOpened.format(&[&path.display()]));
+            } else if format == Some(ReportFormat::Md) {
+                // 画像のリンクは出力ファイルの置き場所（標準出力ならカレントディレクトリ）からの相対パス
+                let base_dir = output
+                    .as_deref()
+                    .and_then(Path::parent)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                let markdown = MarkdownReport::new(base_dir)
+                    .with_embed_images(embed_images.unwrap_or(EmbedImages::None))
+                    .with_image_dir(image_dir)
+                    .render(&report.daily(&target_date)?)?;
+                match output {
+                    Some(ref path) => {
+                        fs::write(path, markdown)?;
+                        eprintln!(
+                            "{}",
+                            Message::MarkdownReportWritten.format(&[&path.display()])
+                        );
+                    }
+                    None => print!("{}", markdown),
+                }
+            } else {
+                report.print(&target_date)?;
+            }
+
+            if obsidian {
+                let Some(ref obsidian_config) = config.obsidian else {
+                    anyhow::bail!(Message::ObsidianNotConfigured.text());
+                };
+                let path = DailyNote::new(obsidian_config).write_summary(&report, &target_date)?;
+                // JSON出力時は標準出力を汚さない
+                let message = Message::DailyNoteWritten.format(&[&path.display()]);
+                if json {
+                    eprintln!("{}", message);
+                } else {
+                    println!("\n{}", message);
+                }
+            }
         }
-        Commands::Ocr { file, batch } => {
-            if let Some(path) = file {
+        Commands::Ocr {
+            file,
+            batch,
+            all,
+            jobs,
+            details,
+            redo,
+            date,
+            where_clause,
+            scrub_private,
+            failed,
+            compress,
+        } => {
+            if compress {
+                let config = Config::load(&CliArgs::default())?;
+                let db = Database::open(&config.db_path)?;
+                match db.train_ocr_dictionary()? {
+                    Some(dict_id) => println!("{}", Message::DictionaryTrained.format(&[&dict_id])),
+                    None => println!("{}", Message::DictionarySkipped.text()),
+                }
+                let stats = db.recompress_ocr_text()?;
+                db.vacuum()?;
+                println!(
+                    "{}",
+                    Message::OcrCompressed.format(&[
+                        &stats.rows,
+                        &format_bytes(stats.bytes_before),
+                        &format_bytes(stats.bytes_after),
+                    ])
+                );
+            } else if failed {
+                let config = Config::load(&CliArgs::default())?;
+                let db = Database::open(&config.db_path)?;
+                print_ocr_failures(&db.get_ocr_failures(-1)?);
+            } else if scrub_private {
+                let config = Config::load(&CliArgs::default())?;
+                let db = Database::open(&config.db_path)?;
+                let count = db.scrub_private_ocr()?;
+                println!("{}", Message::PrivateOcrScrubbed.format(&[&count]));
+            } else if let Some(path) = file {
                 // 単一ファイルのOCR
-                match ocr::recognize_text(&path) {
-                    Ok(text) => {
-                        if text.is_empty() {
-                            println!("テキストは検出されませんでした");
-                        } else {
-                            println!("{}", text);
+                let config = Config::load(&CliArgs::default())?;
+                let Some(backend) = config.ocr_backend.create() else {
+                    println!("{}", Message::OcrDisabled.text());
+                    return Ok(ExitCode::SUCCESS);
+                };
+                if details {
+                    match backend.recognize(&path) {
+                        Ok(result) => println!("{}", result.details_json()),
+                        Err(e) => eprintln!("{}", Message::OcrError.format(&[&e])),
+                    }
+                } else {
+                    match backend.recognize(&path) {
+                        Ok(result) => {
+                            if result.text.is_empty() {
+                                println!("{}", Message::NoTextDetected.text());
+                            } else {
+                                println!("{}", result.text);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("{}", Message::OcrError.format(&[&e]));
                         }
                     }
-                    Err(e) => {
-                        eprintln!("OCRエラー: {}", e);
+                }
+            } else if redo {
+                // 再処理: 既存のocr_textを上書き
+                if date.is_none() && where_clause.is_none() {
+                    println!("{}", Message::RedoNeedsFilter.text());
+                } else {
+                    if let Some(ref d) = date {
+                        validate_date(d)?;
+                    }
+                    let config = Config::load(&CliArgs::default())?;
+                    let db = Database::open(&config.db_path)?;
+                    let captures = db.get_captures_for_ocr_redo(
+                        date.as_deref(),
+                        where_clause.as_deref(),
+                        batch.unwrap_or(-1),
+                    )?;
+
+                    if captures.is_empty() {
+                        println!("{}", Message::NoCapturesToRedo.text());
+                    } else {
+                        run_ocr_batch(
+                            &db,
+                            captures,
+                            jobs,
+                            details || config.ocr_save_details,
+                            config.ocr_backend,
+                            true,
+                        )?;
                     }
                 }
+            } else if all {
+                // 全件のバッチ処理: 進捗を記録しながら未OCRのキャプチャをすべて処理
+                let config = Config::load(&CliArgs::default())?;
+                let db = Database::open(&config.db_path)?;
+                run_ocr_all(
+                    &db,
+                    jobs,
+                    details || config.ocr_save_details,
+                    config.ocr_backend,
+                )?;
             } else if let Some(limit) = batch {
                 // バッチ処理: 未OCRのキャプチャを処理
                 let config = Config::load(&CliArgs::default())?;
@@ -123,111 +1104,2154 @@ pub fn run() -> Result<()> {
                 let captures = db.get_captures_without_ocr(limit)?;
 
                 if captures.is_empty() {
-                    println!("OCR未処理のキャプチャはありません");
+                    println!("{}", Message::NoCapturesWithoutOcr.text());
                 } else {
-                    println!("{}件のキャプチャをOCR処理します...", captures.len());
-                    for capture in captures {
-                        if let (Some(id), Some(ref path)) = (capture.id, &capture.image_path) {
-                            print!("{} ... ", path);
-                            match ocr::recognize_text(&PathBuf::from(path)) {
-                                Ok(text) => {
-                                    db.update_ocr_text(id, &text)?;
-                                    let preview = if text.len() > 50 {
-                                        format!("{}...", &text[..50])
-                                    } else {
-                                        text
-                                    };
-                                    println!("OK ({})", preview.replace('\n', " "));
-                                }
-                                Err(e) => {
-                                    println!("失敗: {}", e);
-                                }
-                            }
-                        }
-                    }
+                    run_ocr_batch(
+                        &db,
+                        captures,
+                        jobs,
+                        details || config.ocr_save_details,
+                        config.ocr_backend,
+                        false,
+                    )?;
                 }
             } else {
-                println!("--file または --batch オプションを指定してください");
+                println!("{}", Message::OcrNeedsTarget.text());
             }
         }
-    }
-
-    Ok(())
-}
+        Commands::Export {
+            format,
+            date,
+            output,
+            email,
+            anonymize,
+        } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let target_date = match date {
+                Some(spec) => resolve_date(&spec, &current_date)?,
+                None => current_date,
+            };
+            let mut captures = report.captures(&target_date)?;
+            if anonymize {
+                export::anonymize(&mut captures, &config.categories);
+            }
+            let hostname = if anonymize {
+                export::ANONYMOUS_HOSTNAME.to_string()
+            } else {
+                Metadata::hostname()
+            };
 
-    #[test]
-    fn test_start_command_no_args() {
-        let cli = Cli::try_parse_from(["tracker", "start"]);
-        assert!(cli.is_ok());
+            let mut writer: Box<dyn Write> = match output {
+                Some(ref path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            match format {
+                ExportFormat::TogglCsv => {
+                    let entries = export::time_entries(&captures, config.interval_seconds);
+                    export::write_toggl_csv(&mut writer, &entries, &email)?;
+                }
+                ExportFormat::Activitywatch => {
+                    let entries = export::time_entries(&captures, config.interval_seconds);
+                    activitywatch::write_activitywatch_json(&mut writer, &entries, &hostname)?;
+                    writeln!(writer)?;
+                }
+            }
+            writer.flush()?;
 
-        if let Commands::Start { interval, quality } = cli.unwrap().command {
-            assert_eq!(interval, None);
-            assert_eq!(quality, None);
-        } else {
-            panic!("Expected Start command");
+            if let Some(path) = output {
+                eprintln!("{}", Message::Exported.format(&[&path.display()]));
+            }
         }
-    }
+        Commands::Standup { date } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone());
+
+            let target_date = match date {
+                Some(date) => date,
+                None => {
+                    let today = logical_date(&Local::now(), config.day_boundary_hour);
+                    let today = NaiveDate::parse_from_str(&today, "%Y-%m-%d")?;
+                    previous_workday(today).format("%Y-%m-%d").to_string()
+                }
+            };
+            print!("{}", render_standup(&report, &target_date)?);
+        }
+        Commands::Highlights { date, json } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let target_date = match date {
+                Some(spec) => resolve_date(&spec, &current_date)?,
+                None => current_date,
+            };
+            let day = NaiveDate::parse_from_str(&target_date, "%Y-%m-%d")?;
+            let selected = highlight::update_day(&db, day, config.day_boundary_hour)?;
+
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone());
+            let highlights = report.highlights(&target_date)?;
+            if json {
+                print_json(&HighlightsOutput {
+                    date: target_date,
+                    highlights,
+                })?;
+            } else if selected == 0 {
+                println!("{}", Message::NoHighlights.format(&[&target_date]));
+            } else {
+                println!(
+                    "{}",
+                    Message::HighlightsSelected.format(&[&target_date, &selected])
+                );
+                for highlight in &highlights {
+                    println!("{}", format_highlight(highlight));
+                }
+            }
+        }
+        Commands::Wordcloud {
+            date,
+            output,
+            limit,
+        } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour);
+
+            let tokenizer = match config.wordcloud_dictionary {
+                Some(ref dictionary) => Tokenizer::load(dictionary)?,
+                None => {
+                    eprintln!("{}", Message::WordcloudNoDictionary.text());
+                    Tokenizer::CharacterClass
+                }
+            };
+
+            let target_date = date.unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
+            let captures = report.captures(&target_date)?;
+            let cloud = WordCloud::from_texts(
+                captures
+                    .iter()
+                    .filter(|c| !c.is_private)
+                    .filter_map(|c| c.ocr_text.as_deref()),
+                limit,
+                &tokenizer,
+            );
+            if cloud.words().is_empty() {
+                println!("{}", Message::NoOcrText.format(&[&target_date]));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let path =
+                output.unwrap_or_else(|| PathBuf::from(format!("wordcloud-{}.svg", target_date)));
+            cloud.save(&path)?;
+            println!(
+                "{}",
+                Message::WordcloudWritten.format(&[&path.display(), &cloud.words().len()])
+            );
+        }
+        Commands::Chart { date, output } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone());
+
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let target_date = match date {
+                Some(spec) => resolve_date(&spec, &current_date)?,
+                None => current_date,
+            };
+            let chart = DailyChart::new(&target_date, report.hourly_by_app(&target_date)?);
+            if chart.apps().is_empty() {
+                println!("{}", Message::NoCaptures.format(&[&target_date]));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let path =
+                output.unwrap_or_else(|| PathBuf::from(format!("chart-{}.png", target_date)));
+            chart.save(&path)?;
+            println!("{}", Message::ChartWritten.format(&[&path.display()]));
+        }
+        Commands::Shortcut { json, input } => {
+            let input = if !input.is_empty() {
+                input.join(" ")
+            } else if std::io::stdin().is_terminal() {
+                anyhow::bail!(Message::ShortcutInputRequired.text());
+            } else {
+                std::io::read_to_string(std::io::stdin())?
+            };
+            let action = ShortcutAction::parse(&input)?;
+            let config = Config::load(&CliArgs::default())?;
+
+            match action {
+                ShortcutAction::Pause | ShortcutAction::Resume => {
+                    let pause_control = PauseControl::new(config.pause_file);
+                    let paused = action == ShortcutAction::Pause;
+                    if paused {
+                        pause_control.pause()?;
+                    } else {
+                        pause_control.resume()?;
+                    }
+                    if json {
+                        print_json(&PauseOutput { paused })?;
+                    } else if paused {
+                        println!("{}", Message::TrackingPaused.text());
+                    } else {
+                        println!("{}", Message::TrackingResumed.text());
+                    }
+                }
+                ShortcutAction::Note(text) => {
+                    let db = Database::open(&config.db_path)?;
+                    let mut note = Note {
+                        id: None,
+                        created_at: format_timestamp(&Local::now()),
+                        text,
+                    };
+                    note.id = Some(db.insert_note(&note)?);
+                    if json {
+                        print_json(&note)?;
+                    } else {
+                        println!("{}", Message::NoteAdded.format(&[&note.text]));
+                    }
+                }
+                ShortcutAction::Report(date) => {
+                    let db = Database::open(&config.db_path)?;
+                    let report = Report::new(db, config.interval_seconds)
+                        .with_day_boundary(config.day_boundary_hour)
+                        .with_categories(config.categories.clone())
+                        .with_aliases(config.aliases.clone())
+                        .with_distractions(config.distractions.clone())
+                        .with_meetings(config.meeting.clone())
+                        .with_non_work(config.non_work.clone())
+                        .with_focus_min_minutes(config.focus_min_minutes);
+
+                    let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+                    let target_date = match date {
+                        Some(spec) => resolve_date(&spec, &current_date)?,
+                        None => current_date,
+                    };
+                    let daily = report.daily(&target_date)?;
+                    if json {
+                        print_json(&daily)?;
+                    } else {
+                        println!("{}", render_report(&daily));
+                    }
+                }
+            }
+        }
+        Commands::Worklog {
+            action: WorklogAction::Push { date, dry_run },
+        } => {
+            let config = Config::load(&CliArgs::default())?;
+            let Some(worklog_config) = config.worklog.clone() else {
+                anyhow::bail!(Message::WorklogNotConfigured.text());
+            };
+            let matcher = TicketMatcher::new(
+                &worklog_config.ticket_pattern,
+                worklog_config.mappings.clone(),
+            )?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour)
+                .with_aliases(config.aliases.clone())
+                .with_non_work(config.non_work.clone());
+
+            let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+            let target_date = match date {
+                Some(spec) => resolve_date(&spec, &current_date)?,
+                None => current_date,
+            };
+            let worklogs = worklog::collect(&report, &target_date, &matcher)?;
+            if worklogs.is_empty() {
+                println!("{}", Message::NoWorklogs.format(&[&target_date]));
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let client = WorklogClient::new(worklog_config);
+            let pushes =
+                worklog::push(report.database(), &client, &target_date, &worklogs, dry_run)?;
+            if pushes.is_empty() {
+                println!("{}", Message::WorklogUpToDate.format(&[&target_date]));
+                return Ok(ExitCode::SUCCESS);
+            }
+            let heading = if dry_run {
+                Message::WorklogDryRun
+            } else {
+                Message::WorklogPushed
+            };
+            println!("{}", heading.format(&[&target_date, &pushes.len()]));
+            for push in &pushes {
+                println!(
+                    "  {}  {}",
+                    push.ticket,
+                    Message::WorklogEntry.format(&[
+                        &format_duration(push.seconds),
+                        &format_duration(push.total_seconds)
+                    ])
+                );
+            }
+        }
+        Commands::Delete {
+            id,
+            from,
+            to,
+            dry_run,
+        } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+
+            let captures = match (from, to) {
+                (Some(from), Some(to)) => {
+                    let (start, end) = delete_range(&from, &to)?;
+                    db.get_captures_between(&start, &end)?
+                }
+                _ => {
+                    let mut captures = Vec::new();
+                    for id in id {
+                        match db.get_capture(id)? {
+                            Some(capture) => captures.push(capture),
+                            None => println!("{}", Message::CaptureNotFound.format(&[&id])),
+                        }
+                    }
+                    captures
+                }
+            };
+            if captures.is_empty() {
+                println!("{}", Message::NothingToDelete.text());
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            for capture in &captures {
+                println!(
+                    "{} {} | {} | {}",
+                    capture.id.unwrap_or_default(),
+                    capture.captured_at,
+                    capture.active_app,
+                    capture.window_title
+                );
+            }
+            let images: Vec<PathBuf> = captures
+                .iter()
+                .filter_map(|c| c.image_path.as_ref().map(PathBuf::from))
+                .collect();
+            if dry_run {
+                println!(
+                    "{}",
+                    Message::DeleteDryRun.format(&[&captures.len(), &images.len()])
+                );
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let ids: Vec<i64> = captures.iter().filter_map(|c| c.id).collect();
+            let deleted = db.delete_captures(&ids)?;
+            let mut removed = 0;
+            for image in &images {
+                // 遅延保存の一時フォルダにある画像は、後で保存先に移されて復活しないよう先に消す
+                let buffered = image_store::buffered_image_path(
+                    &config.images_dir,
+                    &config.image_buffer_dir,
+                    image,
+                );
+                let mut found = false;
+                for file in buffered.iter().chain([image]) {
+                    match fs::remove_file(file) {
+                        Ok(()) => found = true,
+                        Err(e) if e.kind() == ErrorKind::NotFound => {}
+                        Err(e) => println!(
+                            "{}",
+                            Message::ImageDeleteFailed.format(&[&file.display(), &e])
+                        ),
+                    }
+                }
+                if found {
+                    removed += 1;
+                }
+            }
+            println!("{}", Message::Deleted.format(&[&deleted, &removed]));
+        }
+        Commands::Import { format, file } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+
+            let records = match format {
+                ImportFormat::Activitywatch => activitywatch::read_activitywatch_json(
+                    BufReader::new(File::open(&file)?),
+                    config.interval_seconds,
+                )?,
+                ImportFormat::Rescuetime | ImportFormat::Timing => rescuetime::read_rescuetime_csv(
+                    BufReader::new(File::open(&file)?),
+                    config.interval_seconds,
+                )?,
+            };
+            let ids = db.import_captures(&records)?;
+            println!(
+                "{}",
+                Message::Imported.format(&[&ids.len(), &(records.len() - ids.len())])
+            );
+        }
+        Commands::Mcp => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let server = McpServer::new(
+                Report::new(db, config.interval_seconds)
+                    .with_day_boundary(config.day_boundary_hour)
+                    .with_aliases(config.aliases.clone())
+                    .with_distractions(config.distractions.clone())
+                    .with_meetings(config.meeting.clone())
+                    .with_non_work(config.non_work.clone())
+                    .with_focus_min_minutes(config.focus_min_minutes),
+            );
+            server.run()?;
+        }
+        Commands::Serve { port } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let server = ApiServer::new(
+                Report::new(db, config.interval_seconds)
+                    .with_day_boundary(config.day_boundary_hour)
+                    .with_aliases(config.aliases.clone())
+                    .with_distractions(config.distractions.clone())
+                    .with_meetings(config.meeting.clone())
+                    .with_non_work(config.non_work.clone())
+                    .with_focus_min_minutes(config.focus_min_minutes),
+            );
+            server.run(port)?;
+        }
+        Commands::Stats {
+            storage,
+            period,
+            json,
+            ..
+        } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            let report = Report::new(db, config.interval_seconds)
+                .with_day_boundary(config.day_boundary_hour);
+            let today = logical_date(&Local::now(), config.day_boundary_hour);
+            match (storage, json) {
+                (true, true) => print_json(&report.storage(&today, period)?)?,
+                (true, false) => report.print_storage(&today, period)?,
+                (false, true) => print_json(&report.perf(&today, period)?)?,
+                (false, false) => report.print_perf(&today, period)?,
+            }
+        }
+        Commands::Db { action } => {
+            let config = Config::load(&CliArgs::default())?;
+            let db = Database::open(&config.db_path)?;
+            match action {
+                DbAction::Check { json } => {
+                    let report = db.check_integrity()?;
+                    if !report.is_ok() {
+                        exit_code = ExitCode::FAILURE;
+                    }
+                    if json {
+                        print_json(&DbCheckOutput {
+                            ok: report.is_ok(),
+                            report,
+                        })?;
+                    } else if report.is_ok() {
+                        println!("{}", Message::DbCheckOk.text());
+                    } else {
+                        let problems =
+                            report.integrity_errors.len() + report.foreign_key_errors.len();
+                        println!("{}", Message::DbCheckFailed.format(&[&problems]));
+                        for problem in report
+                            .integrity_errors
+                            .iter()
+                            .chain(&report.foreign_key_errors)
+                        {
+                            println!("- {}", problem);
+                        }
+                    }
+                }
+                DbAction::Vacuum { json } => {
+                    let bytes_before = database_file_size(&config.db_path);
+                    let checkpointed = db.vacuum()?;
+                    let bytes_after = database_file_size(&config.db_path);
+                    if json {
+                        print_json(&DbVacuumOutput {
+                            bytes_before,
+                            bytes_after,
+                            checkpointed,
+                        })?;
+                    } else {
+                        println!(
+                            "{}",
+                            Message::DbVacuumed
+                                .format(&[&format_bytes(bytes_before), &format_bytes(bytes_after)])
+                        );
+                        if !checkpointed {
+                            println!("{}", Message::DbCheckpointBusy.text());
+                        }
+                    }
+                }
+            }
+        }
+        Commands::Logs { tail } => {
+            let config = Config::load(&CliArgs::default())?;
+            let lines = logging::tail(&config.log_dir, tail)?;
+            if lines.is_empty() {
+                if config.log_to_file {
+                    println!(
+                        "{}",
+                        Message::NoLogsYet.format(&[&config.log_dir.display()])
+                    );
+                } else {
+                    println!("{}", Message::FileLoggingDisabled.text());
+                }
+            }
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        Commands::Paths { json } => {
+            let config = Config::load(&CliArgs::default())?;
+            let paths = Paths::resolve();
+            let config_file = config.config_file_path();
+            let output = PathsOutput {
+                profile: config::profile().map(String::from),
+                config_file_exists: config_file.is_file(),
+                config_file,
+                config_dir: paths.config_dir,
+                data_dir: paths.data_dir,
+                db_path: config.db_path,
+                images_dir: config.images_dir,
+                log_dir: config.log_dir,
+                pid_file: config.pid_file,
+            };
+            if json {
+                print_json(&output)?;
+            } else {
+                print_paths(&output);
+            }
+        }
+        Commands::Open { target, date } => {
+            let config = Config::load(&CliArgs::default())?;
+            let path = match (target, date) {
+                (OpenTarget::Images, Some(spec)) => {
+                    let current_date = logical_date(&Local::now(), config.day_boundary_hour);
+                    let date = NaiveDate::parse_from_str(
+                        &resolve_date(&spec, &current_date)?,
+                        "%Y-%m-%d",
+                    )?;
+                    ImageStore::new(config.images_dir.clone(), config.jpeg_quality)
+                        .with_filename_format(config.image_filename_format.clone())
+                        .date_dir(date)
+                }
+                (_, Some(_)) => anyhow::bail!(Message::OpenDateImagesOnly.text()),
+                (OpenTarget::Config, None) => config
+                    .config_file_path()
+                    .parent()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| Paths::resolve().config_dir),
+                (OpenTarget::Data, None) => Paths::resolve().data_dir,
+                (OpenTarget::Images, None) => config.images_dir.clone(),
+                (OpenTarget::Logs, None) => config.log_dir.clone(),
+            };
+            if !path.exists() {
+                anyhow::bail!(Message::OpenPathNotFound.format(&[&path.display()]));
+            }
+            let status = Command::new("open").arg(&path).status()?;
+            if !status.success() {
+                anyhow::bail!(Message::OpenFailed.format(&[&status]));
+            }
+            println!("{}", Message::Opened.format(&[&path.display()]));
+        }
+        Commands::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        }
+        Commands::Man { out_dir } => match out_dir {
+            Some(dir) => {
+                fs::create_dir_all(&dir)?;
+                clap_mangen::generate_to(Cli::command(), &dir)?;
+                println!("{}", Message::ManPagesWritten.format(&[&dir.display()]));
+            }
+            None => clap_mangen::Man::new(Cli::command()).render(&mut std::io::stdout())?,
+        },
+    }
+
+    Ok(exit_code)
+}
+
+/// `--json` 出力のスキーマバージョン（互換性のない変更をしたら上げる）
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// `--json` 出力の共通形式
+#[derive(Serialize)]
+struct JsonOutput<'a, T: Serialize> {
+    schema_version: u32,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+/// pause/resumeの出力
+#[derive(Serialize)]
+struct PauseOutput {
+    paused: bool,
+}
+
+/// トラッカーの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum TrackerState {
+    /// 起動していて記録中
+    Recording,
+    /// 起動しているが一時停止中
+    Paused,
+    /// 起動していない（一時停止中かどうかによらない）
+    Stopped,
+}
+
+impl TrackerState {
+    fn new(running: bool, paused: bool) -> Self {
+        match (running, paused) {
+            (false, _) => TrackerState::Stopped,
+            (true, true) => TrackerState::Paused,
+            (true, false) => TrackerState::Recording,
+        }
+    }
+
+    /// `tracker status` の終了コード
+    fn exit_code(self) -> ExitCode {
+        match self {
+            TrackerState::Recording => ExitCode::SUCCESS,
+            TrackerState::Paused => ExitCode::from(EXIT_PAUSED),
+            TrackerState::Stopped => ExitCode::from(EXIT_STOPPED),
+        }
+    }
+}
+
+/// statusの出力
+#[derive(Serialize)]
+struct StatusOutput {
+    state: TrackerState,
+    running: bool,
+    pid: Option<u32>,
+    paused: bool,
+    date: String,
+    today_seconds: u64,
+    today_captures: usize,
+    last_capture_at: Option<String>,
+    last_app: Option<String>,
+}
+
+/// sessionsの出力
+#[derive(Serialize)]
+struct SessionsOutput {
+    sessions: Vec<SessionRecord>,
+}
+
+/// db checkの出力
+#[derive(Serialize)]
+struct DbCheckOutput {
+    ok: bool,
+    #[serde(flatten)]
+    report: IntegrityReport,
+}
+
+/// db vacuumの出力（WALを書き戻せなかったページがあれば `checkpointed` が `false`）
+#[derive(Serialize)]
+struct DbVacuumOutput {
+    bytes_before: u64,
+    bytes_after: u64,
+    checkpointed: bool,
+}
+
+/// pomodoro statusの出力（実行していなければどちらも `null`）
+#[derive(Serialize)]
+struct PomodoroOutput {
+    pomodoro: Option<Pomodoro>,
+    status: Option<PomodoroStatus>,
+}
+
+/// goalsの出力
+#[derive(Serialize)]
+struct GoalsOutput {
+    date: String,
+    goals: Vec<GoalProgress>,
+}
+
+/// alertsの出力
+#[derive(Serialize)]
+struct AlertsOutput {
+    alerts: Vec<Alert>,
+}
+
+/// highlightsの出力
+#[derive(Serialize)]
+struct HighlightsOutput {
+    date: String,
+    highlights: Vec<Highlight>,
+}
+
+/// report --hourlyの出力
+#[derive(Serialize)]
+struct HourlyOutput {
+    date: String,
+    hours: Vec<HourlyBreakdown>,
+}
+
+/// pathsの出力
+#[derive(Serialize)]
+struct PathsOutput {
+    profile: Option<String>,
+    config_file: PathBuf,
+    /// 設定ファイルがなければ既定値で動いている
+    config_file_exists: bool,
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    db_path: PathBuf,
+    images_dir: PathBuf,
+    log_dir: PathBuf,
+    pid_file: PathBuf,
+}
+
+/// スキーマバージョンを付けてJSONを1行で出力
+fn print_json<T: Serialize>(data: &T) -> Result<()> {
+    let output = JsonOutput {
+        schema_version: JSON_SCHEMA_VERSION,
+        data,
+    };
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
+
+/// 画面収録の権限を確認する
+///
+/// 権限がなければ、対話的に実行しているときはシステム設定の画面を開いてからエラーにする
+fn check_screen_recording() -> Result<()> {
+    let result = capture::ensure_screen_recording();
+    if result.is_err() && std::io::stdin().is_terminal() {
+        let _ = Command::new("open").arg(SCREEN_RECORDING_SETTINGS).status();
+    }
+    Ok(result?)
+}
+
+/// 初期セットアップを対話的に行う
+///
+/// 設定ファイルの生成、画面収録・アクセシビリティの権限の確認、テストキャプチャ、
+/// launchdへの登録を順に案内する。既存の設定ファイルやlaunchdの登録は確認してから作り直す
+fn run_init() -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(Message::InitNeedsTerminal.text());
+    }
+    println!("{}", Message::InitHeading.text());
+
+    // 1. 設定ファイル
+    println!("\n{}", Message::InitStepConfig.text());
+    let config_path = Config::default().config_file_path();
+    let write_config = if config_path.exists() {
+        println!(
+            "{}",
+            Message::ConfigExists.format(&[&config_path.display()])
+        );
+        Confirm::new()
+            .with_prompt(Message::ConfirmOverwriteConfig.text())
+            .default(false)
+            .interact()?
+    } else {
+        true
+    };
+    if write_config {
+        let defaults = Config::default();
+        let interval: u64 = Input::new()
+            .with_prompt(Message::PromptInterval.text())
+            .default(defaults.interval_seconds)
+            .validate_with(|value: &u64| match *value {
+                0 => Err(Message::PromptIntervalInvalid.text()),
+                _ => Ok(()),
+            })
+            .interact_text()?;
+        let quality: u8 = Input::new()
+            .with_prompt(Message::PromptQuality.text())
+            .default(defaults.jpeg_quality)
+            .validate_with(|value: &u8| match *value {
+                0..=100 => Ok(()),
+                _ => Err(Message::PromptQualityInvalid.text()),
+            })
+            .interact_text()?;
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            &config_path,
+            config::initial_file_content(interval, quality),
+        )?;
+        println!(
+            "{}",
+            Message::ConfigWritten.format(&[&config_path.display()])
+        );
+    } else {
+        println!("{}", Message::ConfigKept.text());
+    }
+    let config = Config::load(&CliArgs::default())?;
+
+    // 2. 権限
+    println!("\n{}", Message::InitStepPermissions.text());
+    match Metadata::check_permissions() {
+        Ok(permissions) => {
+            let checks = [
+                (
+                    Message::PermissionScreenRecording,
+                    permissions.screen_recording,
+                    SCREEN_RECORDING_SETTINGS,
+                ),
+                (
+                    Message::PermissionAccessibility,
+                    permissions.accessibility,
+                    ACCESSIBILITY_SETTINGS,
+                ),
+            ];
+            for (name, granted, _) in &checks {
+                let mark = if *granted { "✓" } else { "✗" };
+                println!("  {} {}", mark, name.text());
+            }
+            // 不足している最初の権限の設定画面を開く
+            if let Some((_, _, settings)) = checks.iter().find(|(_, granted, _)| !granted) {
+                println!("{}", Message::PermissionHint.text());
+                let open = Confirm::new()
+                    .with_prompt(Message::ConfirmOpenSettings.text())
+                    .default(true)
+                    .interact()?;
+                if open {
+                    Command::new("open").arg(settings).status()?;
+                }
+            }
+        }
+        Err(e) => println!("{}", Message::PermissionCheckFailed.format(&[&e])),
+    }
+
+    // 3. テストキャプチャ
+    println!("\n{}", Message::InitStepTestCapture.text());
+    let capture = Confirm::new()
+        .with_prompt(Message::ConfirmTestCapture.text())
+        .default(true)
+        .interact()?;
+    if capture {
+        let failures = CaptureLoop::new(config.clone())?.capture_once()?;
+        if failures.is_empty() {
+            println!("{}", Message::Captured.text());
+        } else {
+            println!("{}", Message::CapturedWithFailures.text());
+            for failure in &failures {
+                println!("  {}", failure);
+            }
+        }
+    }
+
+    // 4. launchd
+    println!("\n{}", Message::InitStepLaunchd.text());
+    let prompt = if launchd::is_installed() {
+        println!(
+            "{}",
+            Message::LaunchdAlreadyInstalled.format(&[&launchd::plist_path().display()])
+        );
+        Message::ConfirmReinstallLaunchd
+    } else {
+        Message::ConfirmLaunchd
+    };
+    let install = Confirm::new()
+        .with_prompt(prompt.text())
+        .default(false)
+        .interact()?;
+    if install {
+        let path = launchd::install(&config)?;
+        println!("{}", Message::LaunchdInstalled.format(&[&path.display()]));
+    }
+
+    println!("\n{}", Message::InitDone.text());
+    if !launchd::is_installed() {
+        println!("{}", Message::InitStartHint.text());
+    }
+    Ok(())
+}
+
+/// アンインストール時にトラッカーの停止を待つ最大時間（秒）
+const UNINSTALL_STOP_TIMEOUT_SECONDS: u64 = 60;
+
+/// launchdの登録を解除し、`purge_data` ならデータも削除する
+///
+/// 実行前に対象を表示して確認する（`yes` なら確認しない、`dry_run` なら表示だけ）
+fn run_uninstall(purge_data: bool, dry_run: bool, yes: bool) -> Result<()> {
+    let config = Config::load(&CliArgs::default())?;
+    let mut plan =
+        purge_data.then(|| PurgePlan::new(&config, &config.config_file_path(), &Paths::resolve()));
+
+    if launchd::is_installed() {
+        println!(
+            "{}",
+            Message::UninstallLaunchd.format(&[&launchd::plist_path().display()])
+        );
+    } else {
+        println!("{}", Message::LaunchdNotInstalled.text());
+    }
+    match plan {
+        Some(ref plan) if plan.is_empty() => println!("{}", Message::NoDataToPurge.text()),
+        Some(ref plan) => {
+            if !plan.targets.is_empty() {
+                println!("{}", Message::UninstallPurgeTargets.text());
+                for target in &plan.targets {
+                    println!("  {}", target.display());
+                }
+            }
+            if !plan.outside.is_empty() {
+                println!("{}", Message::UninstallOutsideTargets.text());
+                for target in &plan.outside {
+                    println!("  {}", target.display());
+                }
+            }
+        }
+        None => println!("{}", Message::UninstallKeepData.text()),
+    }
+    if dry_run {
+        println!("{}", Message::UninstallDryRun.text());
+        return Ok(());
+    }
+
+    let prompt = if purge_data {
+        Message::ConfirmUninstallPurge
+    } else {
+        Message::ConfirmUninstall
+    };
+    if !yes
+        && !Confirm::new()
+            .with_prompt(prompt.text())
+            .default(false)
+            .interact()?
+    {
+        println!("{}", Message::UninstallCancelled.text());
+        return Ok(());
+    }
+    // 置き場所の外のディレクトリは --yes でも消さず、その場で個別に確認する
+    if let Some(ref mut plan) = plan {
+        if !plan.outside.is_empty() {
+            if !yes
+                && Confirm::new()
+                    .with_prompt(Message::ConfirmPurgeOutside.text())
+                    .default(false)
+                    .interact()?
+            {
+                plan.confirm_outside();
+            } else {
+                println!("{}", Message::PurgeOutsideSkipped.text());
+            }
+        }
+    }
+
+    // 登録を外してから停止しないと、launchdに再起動されることがある
+    if let Some(path) = launchd::uninstall()? {
+        println!("{}", Message::LaunchdUninstalled.format(&[&path.display()]));
+    }
+    let timeout = Duration::from_secs(UNINSTALL_STOP_TIMEOUT_SECONDS);
+    if let Some(pid) = instance_lock::stop(&config.pid_file, timeout)? {
+        println!("{}", Message::TrackerStopped.format(&[&pid]));
+    }
+    if let Some(plan) = plan {
+        plan.execute()?;
+        println!("{}", Message::DataPurged.format(&[&plan.targets.len()]));
+    }
+    println!("{}", Message::Uninstalled.text());
+    Ok(())
+}
+
+/// 日付選択に表示する日付の最大数
+const DATE_PICKER_LIMIT: usize = 60;
+
+/// キャプチャのある日付を矢印キーで選択（キャンセル時は `None`）
+///
+/// キャプチャが1件もなければ今日の日付を返す
+fn pick_date(report: &Report, today: &str) -> Result<Option<String>> {
+    const WEEKDAYS: [Message; 7] = [
+        Message::Monday,
+        Message::Tuesday,
+        Message::Wednesday,
+        Message::Thursday,
+        Message::Friday,
+        Message::Saturday,
+        Message::Sunday,
+    ];
+
+    let dates = report.capture_dates(DATE_PICKER_LIMIT)?;
+    if dates.is_empty() {
+        return Ok(Some(today.to_string()));
+    }
+
+    let labels: Vec<String> = dates
+        .iter()
+        .map(|date| match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(day) => format!(
+                "{} ({})",
+                date,
+                WEEKDAYS[day.weekday().num_days_from_monday() as usize].text()
+            ),
+            Err(_) => date.clone(),
+        })
+        .collect();
+    let selected = Select::new()
+        .with_prompt(Message::PickReportDate.text())
+        .items(&labels)
+        .default(0)
+        .max_length(15)
+        .interact_opt()?;
+
+    Ok(selected.map(|index| dates[index].clone()))
+}
+
+/// 削除する期間の指定を `get_captures_between` の境界 `[start, end)` に変換
+///
+/// 日付だけの終了はその日の終わりまで、時刻付きの終了はその秒までを含める
+fn delete_range(from: &str, to: &str) -> Result<(String, String)> {
+    let parse = |value: &str, end: bool| -> Result<NaiveDateTime> {
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            let date = if end {
+                date.succ_opt().unwrap_or(date)
+            } else {
+                date
+            };
+            return Ok(date.and_time(NaiveTime::MIN));
+        }
+        let datetime = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+            .map_err(|_| anyhow::anyhow!(Message::InvalidDateTime.format(&[&value])))?;
+        Ok(if end {
+            datetime + chrono::Duration::seconds(1)
+        } else {
+            datetime
+        })
+    };
+
+    let start = parse(from, false)?;
+    let end = parse(to, true)?;
+    if start >= end {
+        anyhow::bail!(Message::DeleteRangeOrder.text());
+    }
+    let format = "%Y-%m-%dT%H:%M:%S";
+    Ok((
+        start.format(format).to_string(),
+        end.format(format).to_string(),
+    ))
+}
+
+/// 使用中のパスを表示
+fn print_paths(output: &PathsOutput) {
+    let config_file = if output.config_file_exists {
+        output.config_file.display().to_string()
+    } else {
+        Message::PathsConfigMissing.format(&[&output.config_file.display()])
+    };
+    let lines = [
+        (
+            Message::PathsProfile,
+            output.profile.as_deref().unwrap_or("-").to_string(),
+        ),
+        (Message::PathsConfigFile, config_file),
+        (
+            Message::PathsConfigDir,
+            output.config_dir.display().to_string(),
+        ),
+        (Message::PathsDataDir, output.data_dir.display().to_string()),
+        (Message::PathsDb, output.db_path.display().to_string()),
+        (
+            Message::PathsImages,
+            output.images_dir.display().to_string(),
+        ),
+        (Message::PathsLogs, output.log_dir.display().to_string()),
+        (Message::PathsPidFile, output.pid_file.display().to_string()),
+    ];
+    for (label, path) in lines {
+        println!("{}: {}", label.text(), path);
+    }
+}
+
+/// OCRに失敗したキャプチャの一覧を表示
+fn print_ocr_failures(jobs: &[OcrJob]) {
+    if jobs.is_empty() {
+        println!("{}", Message::NoOcrFailures.text());
+        return;
+    }
+
+    for job in jobs {
+        let state = match (job.status, &job.next_attempt_at) {
+            (OcrJobStatus::Retrying, Some(next)) => Message::OcrRetryWaiting.format(&[next]),
+            (OcrJobStatus::Failed, _) => Message::OcrGaveUp.text().to_string(),
+            _ => job.status.as_str().to_string(),
+        };
+        println!(
+            "{}  {}  {}  {}",
+            job.captured_at,
+            Message::OcrAttempts.format(&[&job.attempts]),
+            state,
+            job.image_path.as_deref().unwrap_or("-")
+        );
+        if let Some(ref error) = job.last_error {
+            println!("    {}", error);
+        }
+    }
+    println!(
+        "{}",
+        Message::OcrFailureSummary.format(&[&jobs.len(), &OCR_MAX_ATTEMPTS])
+    );
+}
+
+/// OCRバッチを実行し、進捗と結果を表示（`redo` なら保存済みの結果を流用せずに認識し直す）
+fn run_ocr_batch(
+    db: &Database,
+    captures: Vec<CaptureRecord>,
+    jobs: usize,
+    save_details: bool,
+    backend: OcrBackendKind,
+    redo: bool,
+) -> Result<()> {
+    let Some(backend) = backend.create() else {
+        println!("{}", Message::OcrDisabled.text());
+        return Ok(());
+    };
+    println!(
+        "{}",
+        Message::OcrBatchStarted.format(&[&captures.len(), &jobs.max(1)])
+    );
+
+    let cancel = cancel_on_ctrlc()?;
+    let mut progress = ProgressBar::new(captures.len());
+    let summary = OcrBatch::new(db, jobs, &cancel)
+        .with_details(save_details)
+        .with_redo(redo)
+        .run(
+            captures,
+            |path| backend.recognize(path),
+            |outcome| match &outcome.result {
+                Ok(_) => progress.inc(),
+                Err(e) => {
+                    progress.println(
+                        &Message::OcrBatchFailed.format(&[&outcome.image_path.display(), e]),
+                    );
+                    progress.inc_failed();
+                }
+            },
+        )?;
+    progress.finish();
+
+    println!(
+        "{}",
+        Message::OcrBatchDone.format(&[&summary.succeeded, &summary.cached, &summary.failed])
+    );
+    if summary.cancelled {
+        println!("{}", Message::OcrBatchCancelled.text());
+    }
+
+    Ok(())
+}
+
+/// `tracker ocr --all` で1回に読み込んで処理するキャプチャの数
+const OCR_ALL_CHUNK_SIZE: i64 = 500;
+
+/// 未処理のキャプチャをすべてOCR処理し、進捗（処理速度・残り時間）と結果を表示
+///
+/// 中断した場合は次回の `--all` で続きから処理する
+fn run_ocr_all(
+    db: &Database,
+    jobs: usize,
+    save_details: bool,
+    backend: OcrBackendKind,
+) -> Result<()> {
+    let Some(backend) = backend.create() else {
+        println!("{}", Message::OcrDisabled.text());
+        return Ok(());
+    };
+    let resumed = db.get_ocr_batch_progress()?;
+    let after_id = resumed.as_ref().map_or(0, |p| p.last_capture_id);
+    let total = db.count_captures_without_ocr_after(after_id)? as usize;
+    if total == 0 {
+        db.clear_ocr_batch_progress()?;
+        println!("{}", Message::NoCapturesWithoutOcr.text());
+        return Ok(());
+    }
+    if let Some(ref progress) = resumed {
+        println!(
+            "{}",
+            Message::OcrAllResumed.format(&[
+                &progress.started_at,
+                &progress.processed,
+                &progress.last_capture_id
+            ])
+        );
+    }
+    println!(
+        "{}",
+        Message::OcrBatchStarted.format(&[&total, &jobs.max(1)])
+    );
+
+    let cancel = cancel_on_ctrlc()?;
+    let mut progress = ProgressBar::new(total).with_rate();
+    let summary = OcrBatch::new(db, jobs, &cancel)
+        .with_details(save_details)
+        .run_all(
+            OCR_ALL_CHUNK_SIZE,
+            |path| backend.recognize(path),
+            |outcome| match &outcome.result {
+                Ok(_) => progress.inc(),
+                Err(e) => {
+                    progress.println(
+                        &Message::OcrBatchFailed.format(&[&outcome.image_path.display(), e]),
+                    );
+                    progress.inc_failed();
+                }
+            },
+        )?;
+    progress.finish();
+
+    println!(
+        "{}",
+        Message::OcrBatchDone.format(&[&summary.succeeded, &summary.cached, &summary.failed])
+    );
+    if summary.cancelled {
+        println!("{}", Message::OcrAllCancelled.text());
+    }
+
+    Ok(())
+}
+
+/// Ctrl-Cで立つ中断フラグを登録（バッチは処理中の画像を書き込んでから中断する）
+fn cancel_on_ctrlc() -> Result<Arc<AtomicBool>> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cancel_flag = Arc::clone(&cancel);
+    ctrlc::set_handler(move || {
+        cancel_flag.store(true, Ordering::SeqCst);
+    })?;
+    Ok(cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_command_no_args() {
+        let cli = Cli::try_parse_from(["tracker", "start"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Start {
+            interval, quality, ..
+        } = cli.unwrap().command
+        {
+            assert_eq!(interval, None);
+            assert_eq!(quality, None);
+        } else {
+            panic!("Expected Start command");
+        }
+    }
+
+    #[test]
+    fn test_start_command_with_args() {
+        let cli = Cli::try_parse_from(["tracker", "start", "--interval", "30", "--quality", "80"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Start {
+            interval, quality, ..
+        } = cli.unwrap().command
+        {
+            assert_eq!(interval, Some(30));
+            assert_eq!(quality, Some(80));
+        } else {
+            panic!("Expected Start command");
+        }
+    }
+
+    #[test]
+    fn test_start_daemon() {
+        let cli = Cli::try_parse_from(["tracker", "start", "--daemon", "-i", "30"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Start {
+                interval: Some(30),
+                daemon: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_pause_command() {
+        let cli = Cli::try_parse_from(["tracker", "pause"]);
+        assert!(cli.is_ok());
+        assert!(matches!(
+            cli.unwrap().command,
+            Commands::Pause { json: false }
+        ));
+    }
+
+    #[test]
+    fn test_resume_command() {
+        let cli = Cli::try_parse_from(["tracker", "resume"]);
+        assert!(cli.is_ok());
+        assert!(matches!(
+            cli.unwrap().command,
+            Commands::Resume { json: false }
+        ));
+    }
+
+    #[test]
+    fn test_report_with_date() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--date", "2024-12-30"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Report { date, today, .. } = cli.unwrap().command {
+            assert_eq!(date, Some("2024-12-30".to_string()));
+            assert!(!today);
+        } else {
+            panic!("Expected Report command");
+        }
+    }
+
+    #[test]
+    fn test_report_with_relative_date() {
+        let cli = Cli::try_parse_from(["tracker", "report", "last-friday"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Report { when, date, .. } = cli.unwrap().command {
+            assert_eq!(when, Some("last-friday".to_string()));
+            assert_eq!(date, None);
+        } else {
+            panic!("Expected Report command");
+        }
+
+        // 負の相対指定も位置引数として受け付ける
+        assert!(Cli::try_parse_from(["tracker", "report", "-2d"]).is_ok());
+        assert!(Cli::try_parse_from(["tracker", "report", "yesterday", "--today"]).is_err());
+        // 既存のフラグは位置引数として扱わない
+        let cli = Cli::try_parse_from(["tracker", "report", "-t"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Report {
+                today: true,
+                when: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_report_with_today() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--today"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Report { date, today, .. } = cli.unwrap().command {
+            assert_eq!(date, None);
+            assert!(today);
+        } else {
+            panic!("Expected Report command");
+        }
+    }
+
+    #[test]
+    fn test_report_date_and_today_conflicts() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--date", "2024-12-30", "--today"]);
+        assert!(cli.is_err());
+    }
+
+    #[test]
+    fn test_report_app_filters() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "report",
+            "--app",
+            "VS Code",
+            "--exclude",
+            "Finder,loginwindow",
+        ]);
+        assert!(cli.is_ok());
+
+        if let Commands::Report { app, exclude, .. } = cli.unwrap().command {
+            assert_eq!(app, vec!["VS Code"]);
+            assert_eq!(exclude, vec!["Finder", "loginwindow"]);
+        } else {
+            panic!("Expected Report command");
+        }
+    }
+
+    #[test]
+    fn test_sessions_command() {
+        let cli = Cli::try_parse_from(["tracker", "sessions", "--limit", "5"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Sessions { limit, json } = cli.unwrap().command {
+            assert_eq!(limit, 5);
+            assert!(!json);
+        } else {
+            panic!("Expected Sessions command");
+        }
+    }
 
     #[test]
-    fn test_start_command_with_args() {
-        let cli = Cli::try_parse_from(["tracker", "start", "--interval", "30", "--quality", "80"]);
+    fn test_json_flag() {
+        for command in ["pause", "resume", "status", "sessions", "report"] {
+            let cli = Cli::try_parse_from(["tracker", command, "--json"]);
+            assert!(cli.is_ok(), "{} --json", command);
+
+            let json = match cli.unwrap().command {
+                Commands::Pause { json }
+                | Commands::Resume { json }
+                | Commands::Status { json, .. }
+                | Commands::Sessions { json, .. }
+                | Commands::Report { json, .. } => json,
+                _ => panic!("Unexpected command"),
+            };
+            assert!(json);
+        }
+    }
+
+    #[test]
+    fn test_tracker_state() {
+        assert_eq!(TrackerState::new(true, false), TrackerState::Recording);
+        assert_eq!(TrackerState::new(true, true), TrackerState::Paused);
+        assert_eq!(TrackerState::new(false, true), TrackerState::Stopped);
+        assert_eq!(TrackerState::Recording.exit_code(), ExitCode::SUCCESS);
+        assert_eq!(TrackerState::Paused.exit_code(), ExitCode::from(3));
+        assert_eq!(TrackerState::Stopped.exit_code(), ExitCode::from(4));
+        assert_eq!(
+            serde_json::to_string(&TrackerState::Paused).unwrap(),
+            r#""paused""#
+        );
+
+        assert!(Cli::try_parse_from(["tracker", "status", "-q"]).is_ok());
+        assert!(Cli::try_parse_from(["tracker", "status", "-q", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_json_output_schema() {
+        let output = JsonOutput {
+            schema_version: JSON_SCHEMA_VERSION,
+            data: &PauseOutput { paused: true },
+        };
+        assert_eq!(
+            serde_json::to_string(&output).unwrap(),
+            r#"{"schema_version":1,"paused":true}"#
+        );
+    }
+
+    #[test]
+    fn test_report_obsidian() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--today", "--obsidian"]);
         assert!(cli.is_ok());
 
-        if let Commands::Start { interval, quality } = cli.unwrap().command {
-            assert_eq!(interval, Some(30));
-            assert_eq!(quality, Some(80));
+        if let Commands::Report {
+            today, obsidian, ..
+        } = cli.unwrap().command
+        {
+            assert!(today);
+            assert!(obsidian);
         } else {
-            panic!("Expected Start command");
+            panic!("Expected Report command");
         }
     }
 
     #[test]
-    fn test_pause_command() {
-        let cli = Cli::try_parse_from(["tracker", "pause"]);
+    fn test_report_keywords() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "report",
+            "--keywords",
+            "PR,review,設計",
+            "--days",
+            "14",
+        ]);
         assert!(cli.is_ok());
-        assert!(matches!(cli.unwrap().command, Commands::Pause));
+
+        if let Commands::Report { keywords, days, .. } = cli.unwrap().command {
+            assert_eq!(keywords, vec!["PR", "review", "設計"]);
+            assert_eq!(days, 14);
+        } else {
+            panic!("Expected Report command");
+        }
+
+        // --daysはキーワード指定時のみ
+        assert!(Cli::try_parse_from(["tracker", "report", "--days", "14"]).is_err());
+
+        // 集計日数は1〜366日
+        for days in ["0", "367", "4294967295"] {
+            assert!(
+                Cli::try_parse_from(["tracker", "report", "--keywords", "PR", "--days", days])
+                    .is_err()
+            );
+        }
+        assert!(
+            Cli::try_parse_from(["tracker", "report", "--keywords", "PR", "--days", "366"]).is_ok()
+        );
     }
 
     #[test]
-    fn test_resume_command() {
-        let cli = Cli::try_parse_from(["tracker", "resume"]);
+    fn test_report_open() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--today", "--open"]);
         assert!(cli.is_ok());
-        assert!(matches!(cli.unwrap().command, Commands::Resume));
+
+        if let Commands::Report { open, .. } = cli.unwrap().command {
+            assert!(open);
+        } else {
+            panic!("Expected Report command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "report", "--open", "--json"]).is_err());
     }
 
     #[test]
-    fn test_report_with_date() {
-        let cli = Cli::try_parse_from(["tracker", "report", "--date", "2024-12-30"]);
+    fn test_report_with_ocr() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--today", "--with-ocr", "--json"]);
         assert!(cli.is_ok());
 
-        if let Commands::Report { date, today } = cli.unwrap().command {
-            assert_eq!(date, Some("2024-12-30".to_string()));
-            assert!(!today);
+        if let Commands::Report { with_ocr, .. } = cli.unwrap().command {
+            assert!(with_ocr);
         } else {
             panic!("Expected Report command");
         }
+
+        assert!(Cli::try_parse_from(["tracker", "report", "--with-ocr", "--hourly"]).is_err());
     }
 
     #[test]
-    fn test_report_with_today() {
-        let cli = Cli::try_parse_from(["tracker", "report", "--today"]);
+    fn test_report_markdown() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "report",
+            "--today",
+            "--format",
+            "md",
+            "--embed-images",
+            "thumbnail",
+            "--output",
+            "vault/2025-01-10.md",
+            "--image-dir",
+            "vault/attachments",
+        ]);
         assert!(cli.is_ok());
 
-        if let Commands::Report { date, today } = cli.unwrap().command {
-            assert_eq!(date, None);
-            assert!(today);
+        if let Commands::Report {
+            format,
+            embed_images,
+            output,
+            image_dir,
+            ..
+        } = cli.unwrap().command
+        {
+            assert_eq!(format, Some(ReportFormat::Md));
+            assert_eq!(embed_images, Some(EmbedImages::Thumbnail));
+            assert_eq!(output, Some(PathBuf::from("vault/2025-01-10.md")));
+            assert_eq!(image_dir, Some(PathBuf::from("vault/attachments")));
+        } else {
+            panic!("Expected Report command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "report", "--embed-images", "thumbnail"]).is_err());
+        assert!(Cli::try_parse_from([
+            "tracker",
+            "report",
+            "--format",
+            "md",
+            "--image-dir",
+            "attachments"
+        ])
+        .is_err());
+        assert!(Cli::try_parse_from(["tracker", "report", "--format", "md", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_report_trend() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--trend", "4w"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Report { trend, .. } = cli.unwrap().command {
+            assert_eq!(trend, Some(28));
         } else {
             panic!("Expected Report command");
         }
+
+        assert!(Cli::try_parse_from(["tracker", "report", "--trend", "4m"]).is_err());
+        assert!(
+            Cli::try_parse_from(["tracker", "report", "--trend", "4w", "--keywords", "PR"])
+                .is_err()
+        );
     }
 
     #[test]
-    fn test_report_date_and_today_conflicts() {
-        let cli = Cli::try_parse_from(["tracker", "report", "--date", "2024-12-30", "--today"]);
+    fn test_report_hourly() {
+        let cli = Cli::try_parse_from(["tracker", "report", "--today", "--hourly", "--json"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Report { hourly, json, .. } = cli.unwrap().command {
+            assert!(hourly && json);
+        } else {
+            panic!("Expected Report command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "report", "--hourly", "--trend", "4w"]).is_err());
+    }
+
+    #[test]
+    fn test_export_toggl_csv() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "export",
+            "--format",
+            "toggl-csv",
+            "--date",
+            "2025-01-10",
+            "--anonymize",
+        ]);
+        assert!(cli.is_ok());
+
+        if let Commands::Export {
+            format,
+            date,
+            anonymize,
+            ..
+        } = cli.unwrap().command
+        {
+            assert_eq!(format, ExportFormat::TogglCsv);
+            assert_eq!(date, Some("2025-01-10".to_string()));
+            assert!(anonymize);
+        } else {
+            panic!("Expected Export command");
+        }
+    }
+
+    #[test]
+    fn test_highlights_command() {
+        let cli =
+            Cli::try_parse_from(["tracker", "highlights", "--date", "-1d", "--json"]).unwrap();
+        if let Commands::Highlights { date, json } = cli.command {
+            assert_eq!(date, Some("-1d".to_string()));
+            assert!(json);
+        } else {
+            panic!("Expected Highlights command");
+        }
+    }
+
+    #[test]
+    fn test_standup_command() {
+        let cli = Cli::try_parse_from(["tracker", "standup"]).unwrap();
+        assert!(matches!(cli.command, Commands::Standup { date: None }));
+
+        let cli = Cli::try_parse_from(["tracker", "standup", "--date", "2025-01-10"]).unwrap();
+        if let Commands::Standup { date } = cli.command {
+            assert_eq!(date, Some("2025-01-10".to_string()));
+        } else {
+            panic!("Expected Standup command");
+        }
+    }
+
+    #[test]
+    fn test_chart_command() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "chart",
+            "--date",
+            "2025-01-10",
+            "--output",
+            "chart.png",
+        ])
+        .unwrap();
+        if let Commands::Chart { date, output } = cli.command {
+            assert_eq!(date, Some("2025-01-10".to_string()));
+            assert_eq!(output, Some(PathBuf::from("chart.png")));
+        } else {
+            panic!("Expected Chart command");
+        }
+    }
+
+    #[test]
+    fn test_pomodoro_command() {
+        let cli = Cli::try_parse_from([
+            "tracker", "pomodoro", "start", "--work", "50", "--break", "10",
+        ])
+        .unwrap();
+        if let Commands::Pomodoro {
+            action:
+                PomodoroAction::Start {
+                    work,
+                    break_minutes,
+                },
+        } = cli.command
+        {
+            assert_eq!((work, break_minutes), (50, 10));
+        } else {
+            panic!("Expected Pomodoro start command");
+        }
+
+        let cli = Cli::try_parse_from(["tracker", "pomodoro", "start"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Pomodoro {
+                action: PomodoroAction::Start {
+                    work: 25,
+                    break_minutes: 5
+                }
+            }
+        ));
+        assert!(Cli::try_parse_from(["tracker", "pomodoro", "start", "--work", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_goals_command() {
+        let cli = Cli::try_parse_from(["tracker", "goals", "-1d", "--json"]).unwrap();
+        if let Commands::Goals { when, json } = cli.command {
+            assert_eq!(when.as_deref(), Some("-1d"));
+            assert!(json);
+        } else {
+            panic!("Expected Goals command");
+        }
+    }
+
+    #[test]
+    fn test_alerts_command() {
+        let cli = Cli::try_parse_from(["tracker", "alerts", "--today", "--json"]).unwrap();
+        if let Commands::Alerts { today, limit, json } = cli.command {
+            assert!(today);
+            assert_eq!(limit, 50);
+            assert!(json);
+        } else {
+            panic!("Expected Alerts command");
+        }
+    }
+
+    #[test]
+    fn test_shortcut_command() {
+        let cli = Cli::try_parse_from(["tracker", "shortcut", "--json", "report", "-1d"]).unwrap();
+        if let Commands::Shortcut { json, input } = cli.command {
+            assert!(json);
+            assert_eq!(input, vec!["report", "-1d"]);
+        } else {
+            panic!("Expected Shortcut command");
+        }
+
+        let cli = Cli::try_parse_from(["tracker", "shortcut"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Shortcut { json: false, ref input } if input.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_worklog_push_command() {
+        let cli = Cli::try_parse_from(["tracker", "worklog", "push", "--date", "-1d", "--dry-run"])
+            .unwrap();
+        if let Commands::Worklog {
+            action: WorklogAction::Push { date, dry_run },
+        } = cli.command
+        {
+            assert_eq!(date, Some("-1d".to_string()));
+            assert!(dry_run);
+        } else {
+            panic!("Expected Worklog push command");
+        }
+        assert!(Cli::try_parse_from(["tracker", "worklog"]).is_err());
+    }
+
+    #[test]
+    fn test_wordcloud_command() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "wordcloud",
+            "--date",
+            "2025-01-10",
+            "-o",
+            "cloud.png",
+        ]);
+        assert!(cli.is_ok());
+
+        if let Commands::Wordcloud {
+            date,
+            output,
+            limit,
+        } = cli.unwrap().command
+        {
+            assert_eq!(date, Some("2025-01-10".to_string()));
+            assert_eq!(output, Some(PathBuf::from("cloud.png")));
+            assert_eq!(limit, 100);
+        } else {
+            panic!("Expected Wordcloud command");
+        }
+    }
+
+    #[test]
+    fn test_import_activitywatch() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "import",
+            "--format",
+            "activitywatch",
+            "aw-export.json",
+        ]);
+        assert!(cli.is_ok());
+
+        if let Commands::Import { format, file } = cli.unwrap().command {
+            assert_eq!(format, ImportFormat::Activitywatch);
+            assert_eq!(file, PathBuf::from("aw-export.json"));
+        } else {
+            panic!("Expected Import command");
+        }
+
+        let cli = Cli::try_parse_from(["tracker", "import", "--format", "rescuetime", "data.csv"]);
+        if let Commands::Import { format, .. } = cli.unwrap().command {
+            assert_eq!(format, ImportFormat::Rescuetime);
+        } else {
+            panic!("Expected Import command");
+        }
+    }
+
+    #[test]
+    fn test_mcp_command() {
+        let cli = Cli::try_parse_from(["tracker", "mcp"]);
+        assert!(cli.is_ok());
+        assert!(matches!(cli.unwrap().command, Commands::Mcp));
+    }
+
+    #[test]
+    fn test_serve_command_default_port() {
+        let cli = Cli::try_parse_from(["tracker", "serve"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Serve { port } = cli.unwrap().command {
+            assert_eq!(port, 8686);
+        } else {
+            panic!("Expected Serve command");
+        }
+    }
+
+    #[test]
+    fn test_stats_perf() {
+        let cli = Cli::try_parse_from(["tracker", "stats", "--perf", "--period", "2w"]).unwrap();
+        if let Commands::Stats { perf, period, .. } = cli.command {
+            assert!(perf);
+            assert_eq!(period, 14);
+        } else {
+            panic!("Expected Stats command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "stats"]).is_err());
+    }
+
+    #[test]
+    fn test_stats_storage() {
+        let cli = Cli::try_parse_from(["tracker", "stats", "--storage"]).unwrap();
+        if let Commands::Stats {
+            perf,
+            storage,
+            period,
+            ..
+        } = cli.command
+        {
+            assert!(!perf);
+            assert!(storage);
+            assert_eq!(period, 7);
+        } else {
+            panic!("Expected Stats command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "stats", "--perf", "--storage"]).is_err());
+    }
+
+    #[test]
+    fn test_db_command() {
+        let cli = Cli::try_parse_from(["tracker", "db", "check", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Db {
+                action: DbAction::Check { json: true }
+            }
+        ));
+        let cli = Cli::try_parse_from(["tracker", "db", "vacuum"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Db {
+                action: DbAction::Vacuum { json: false }
+            }
+        ));
+        assert!(Cli::try_parse_from(["tracker", "db"]).is_err());
+    }
+
+    #[test]
+    fn test_stop() {
+        let cli = Cli::try_parse_from(["tracker", "stop"]).unwrap();
+        assert!(matches!(cli.command, Commands::Stop { timeout: 60 }));
+
+        let cli = Cli::try_parse_from(["tracker", "stop", "--timeout", "5"]).unwrap();
+        assert!(matches!(cli.command, Commands::Stop { timeout: 5 }));
+    }
+
+    #[test]
+    fn test_reload() {
+        let cli = Cli::try_parse_from(["tracker", "reload"]).unwrap();
+        assert!(matches!(cli.command, Commands::Reload));
+    }
+
+    #[test]
+    fn test_capture_now() {
+        let cli = Cli::try_parse_from(["tracker", "capture", "--now"]).unwrap();
+        assert!(matches!(cli.command, Commands::Capture { now: true }));
+
+        let cli = Cli::try_parse_from(["tracker", "capture"]).unwrap();
+        assert!(matches!(cli.command, Commands::Capture { now: false }));
+    }
+
+    #[test]
+    fn test_uninstall_args() {
+        let cli =
+            Cli::try_parse_from(["tracker", "uninstall", "--purge-data", "--dry-run"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Uninstall {
+                purge_data: true,
+                dry_run: true,
+                yes: false,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_delete_args() {
+        let cli = Cli::try_parse_from(["tracker", "delete", "--id", "1,2", "--dry-run"]).unwrap();
+        if let Commands::Delete { id, dry_run, .. } = cli.command {
+            assert_eq!(id, vec![1, 2]);
+            assert!(dry_run);
+        } else {
+            panic!("Expected Delete command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "delete"]).is_err());
+        assert!(Cli::try_parse_from(["tracker", "delete", "--from", "2025-01-10"]).is_err());
+        assert!(Cli::try_parse_from([
+            "tracker",
+            "delete",
+            "--id",
+            "1",
+            "--from",
+            "2025-01-10",
+            "--to",
+            "2025-01-10"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_delete_range() {
+        assert_eq!(
+            delete_range("2025-01-10", "2025-01-10").unwrap(),
+            (
+                "2025-01-10T00:00:00".to_string(),
+                "2025-01-11T00:00:00".to_string()
+            )
+        );
+        assert_eq!(
+            delete_range("2025-01-10T09:30", "2025-01-10T10:00:00").unwrap(),
+            (
+                "2025-01-10T09:30:00".to_string(),
+                "2025-01-10T10:00:01".to_string()
+            )
+        );
+        assert!(delete_range("2025-01-11", "2025-01-10").is_err());
+        assert!(delete_range("yesterday", "2025-01-10").is_err());
+    }
+
+    #[test]
+    fn test_profile_option() {
+        let cli = Cli::try_parse_from(["tracker", "--profile", "work", "start"]).unwrap();
+        assert_eq!(cli.global.profile.as_deref(), Some("work"));
+
+        // サブコマンドの後にも書ける
+        let cli = Cli::try_parse_from(["tracker", "report", "--profile", "personal"]).unwrap();
+        assert_eq!(cli.global.profile.as_deref(), Some("personal"));
+
+        let cli = Cli::try_parse_from(["tracker", "status"]).unwrap();
+        assert!(cli.global.profile.is_none());
+    }
+
+    #[test]
+    fn test_config_and_db_path_options() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "--config",
+            "/tmp/config.toml",
+            "report",
+            "--db-path",
+            "/tmp/other.db",
+        ])
+        .unwrap();
+        let overrides = cli.global.path_overrides();
+        assert_eq!(
+            overrides.config_file,
+            Some(PathBuf::from("/tmp/config.toml"))
+        );
+        assert_eq!(overrides.db_path, Some(PathBuf::from("/tmp/other.db")));
+        assert!(overrides.profile.is_none());
+    }
+
+    #[test]
+    fn test_completions_and_man() {
+        let cli = Cli::try_parse_from(["tracker", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Completions { shell: Shell::Zsh }
+        ));
+        assert!(Cli::try_parse_from(["tracker", "completions", "cmd"]).is_err());
+
+        let mut completion = Vec::new();
+        clap_complete::generate(Shell::Zsh, &mut Cli::command(), "tracker", &mut completion);
+        let completion = String::from_utf8(completion).unwrap();
+        assert!(completion.contains("#compdef tracker"));
+        assert!(completion.contains("--db-path"));
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        clap_mangen::generate_to(Cli::command(), temp_dir.path()).unwrap();
+        assert!(temp_dir.path().join("tracker.1").exists());
+        assert!(temp_dir.path().join("tracker-report.1").exists());
+    }
+
+    #[test]
+    fn test_init() {
+        let cli = Cli::try_parse_from(["tracker", "init"]).unwrap();
+        assert!(matches!(cli.command, Commands::Init));
+    }
+
+    #[test]
+    fn test_logs_tail() {
+        let cli = Cli::try_parse_from(["tracker", "logs", "--tail", "20"]).unwrap();
+        assert!(matches!(cli.command, Commands::Logs { tail: 20 }));
+
+        let cli = Cli::try_parse_from(["tracker", "logs"]).unwrap();
+        assert!(matches!(cli.command, Commands::Logs { tail: 100 }));
+    }
+
+    #[test]
+    fn test_paths_and_open_args() {
+        let cli = Cli::try_parse_from(["tracker", "paths", "--json"]).unwrap();
+        assert!(matches!(cli.command, Commands::Paths { json: true }));
+
+        let cli = Cli::try_parse_from(["tracker", "open", "images", "--date", "-1d"]).unwrap();
+        if let Commands::Open { target, date } = cli.command {
+            assert_eq!(target, OpenTarget::Images);
+            assert_eq!(date.as_deref(), Some("-1d"));
+        } else {
+            panic!("Expected Open command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "open"]).is_err());
+        assert!(Cli::try_parse_from(["tracker", "open", "desktop"]).is_err());
+    }
+
+    #[test]
+    fn test_ocr_batch_with_jobs() {
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--batch", "500", "--jobs", "4"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Ocr { batch, jobs, .. } = cli.unwrap().command {
+            assert_eq!(batch, Some(500));
+            assert_eq!(jobs, 4);
+        } else {
+            panic!("Expected Ocr command");
+        }
+    }
+
+    #[test]
+    fn test_ocr_all() {
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--all", "--jobs", "4"]);
+        assert!(cli.is_ok());
+
+        if let Commands::Ocr { all, jobs, .. } = cli.unwrap().command {
+            assert!(all);
+            assert_eq!(jobs, 4);
+        } else {
+            panic!("Expected Ocr command");
+        }
+
+        assert!(Cli::try_parse_from(["tracker", "ocr", "--all", "--batch", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_ocr_redo_with_date_and_where() {
+        let cli = Cli::try_parse_from([
+            "tracker",
+            "ocr",
+            "--redo",
+            "--date",
+            "2025-01-10",
+            "--where",
+            "app = 'Safari'",
+        ]);
+        assert!(cli.is_ok());
+
+        if let Commands::Ocr {
+            redo,
+            date,
+            where_clause,
+            ..
+        } = cli.unwrap().command
+        {
+            assert!(redo);
+            assert_eq!(date, Some("2025-01-10".to_string()));
+            assert_eq!(where_clause, Some("app = 'Safari'".to_string()));
+        } else {
+            panic!("Expected Ocr command");
+        }
+    }
+
+    #[test]
+    fn test_ocr_date_requires_redo() {
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--date", "2025-01-10"]);
+        assert!(cli.is_err());
+    }
+
+    #[test]
+    fn test_ocr_failed() {
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--failed"]);
+        assert!(matches!(
+            cli.unwrap().command,
+            Commands::Ocr { failed: true, .. }
+        ));
+
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--failed", "--batch", "10"]);
+        assert!(cli.is_err());
+    }
+
+    #[test]
+    fn test_ocr_compress() {
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--compress"]);
+        assert!(matches!(
+            cli.unwrap().command,
+            Commands::Ocr { compress: true, .. }
+        ));
+
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--compress", "--failed"]);
+        assert!(cli.is_err());
+    }
+
+    #[test]
+    fn test_ocr_scrub_private() {
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--scrub-private"]);
+        assert!(cli.is_ok());
+        assert!(matches!(
+            cli.unwrap().command,
+            Commands::Ocr {
+                scrub_private: true,
+                ..
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["tracker", "ocr", "--scrub-private", "--batch", "10"]);
         assert!(cli.is_err());
     }
 }
@@ -1,36 +1,444 @@
 //! 設定モジュール
 
+use crate::category::{
+    AppAliases, Categories, Distractions, MediaRules, MeetingRules, NonWorkRules, PrivateWindows,
+};
 use crate::error::ConfigError;
+use crate::goals::Goals;
+use crate::i18n::Language;
+use crate::image_store;
+use crate::logging::LogFormat;
+use crate::metadata_provider::MetadataProviderKind;
+use crate::ocr::OcrBackendKind;
+use crate::pause_control::PauseMode;
+use crate::redact::TitleRedactor;
+use crate::worklog::{TicketMatcher, WorklogService};
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 設定・データの置き場所を一括で指定する環境変数
+pub const HOME_ENV: &str = "HABIT_TRACKER_HOME";
+
+/// 置き場所の解決に使う環境変数（launchdなど環境を引き継がない起動元に渡す）
+pub const PATH_ENVS: [&str; 3] = [HOME_ENV, "XDG_CONFIG_HOME", "XDG_DATA_HOME"];
+
+/// XDGベースディレクトリ内のディレクトリ名
+const XDG_DIR_NAME: &str = "habit-tracker";
+
+/// プロファイルごとの設定・データを置くディレクトリ名
+const PROFILES_DIR_NAME: &str = "profiles";
+
+/// プロセス全体で使うパスの上書き（未設定なら上書きなし）
+static OVERRIDES: OnceLock<PathOverrides> = OnceLock::new();
+
+/// 全サブコマンド共通のオプションによるパスの上書き
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathOverrides {
+    /// プロファイル名（`--profile`）
+    pub profile: Option<String>,
+    /// 設定ファイル（`--config`）
+    pub config_file: Option<PathBuf>,
+    /// データベース（`--db-path`、設定ファイルの `db_path` より優先）
+    pub db_path: Option<PathBuf>,
+}
+
+impl PathOverrides {
+    /// 同じ上書きで子プロセスの `tracker` を起動するための引数
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(ref profile) = self.profile {
+            args.extend(["--profile".to_string(), profile.clone()]);
+        }
+        if let Some(ref path) = self.config_file {
+            args.extend(["--config".to_string(), path.display().to_string()]);
+        }
+        if let Some(ref path) = self.db_path {
+            args.extend(["--db-path".to_string(), path.display().to_string()]);
+        }
+        args
+    }
+}
+
+/// パスの上書きを設定（`--profile` / `--config` / `--db-path`）
+///
+/// プロセスで最初の1回だけ有効。プロファイル名は英数字・`-`・`_` のみ。
+/// パスはバックグラウンド起動やlaunchdでも同じ場所を指すよう絶対パスにする
+pub fn init_overrides(overrides: PathOverrides) -> Result<(), ConfigError> {
+    if let Some(ref name) = overrides.profile {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(ConfigError::InvalidProfile(name.clone()));
+        }
+    }
+    let overrides = PathOverrides {
+        config_file: overrides.config_file.map(std::path::absolute).transpose()?,
+        db_path: overrides.db_path.map(std::path::absolute).transpose()?,
+        ..overrides
+    };
+    let _ = OVERRIDES.set(overrides);
+    Ok(())
+}
+
+/// 使用中のパスの上書き
+pub fn overrides() -> PathOverrides {
+    OVERRIDES.get().cloned().unwrap_or_default()
+}
+
+/// 使用中のプロファイル
+pub fn profile() -> Option<&'static str> {
+    OVERRIDES
+        .get()
+        .and_then(|overrides| overrides.profile.as_deref())
+}
 
 /// アプリケーション設定
 #[derive(Debug, Clone)]
 pub struct Config {
     /// キャプチャ間隔（秒）
     pub interval_seconds: u64,
+    /// 画面の変化に応じてキャプチャ間隔を調整するか
+    pub adaptive_interval: bool,
+    /// アダプティブ時の最小キャプチャ間隔（秒）
+    pub min_interval_seconds: u64,
+    /// アダプティブ時の最大キャプチャ間隔（秒）
+    pub max_interval_seconds: u64,
     /// JPEG品質（0-100）
     pub jpeg_quality: u8,
+    /// 輝度の標準偏差がこれ未満の画像（真っ黒・真っ白など）は保存もOCRもしない（0で無効）
+    pub blank_image_max_stddev: f64,
     /// データベースファイルパス
     pub db_path: PathBuf,
     /// スクリーンショット保存ディレクトリ
     pub images_dir: PathBuf,
+    /// スクリーンショットのファイル名・ディレクトリ構成のテンプレート（`images_dir` からの相対パス）
+    pub image_filename_format: String,
+    /// 画像をこの枚数だけ一時フォルダにためてから保存先に移す（0で都度保存）
+    pub image_buffer_count: usize,
+    /// 遅延保存で画像をためる一時フォルダ
+    pub image_buffer_dir: PathBuf,
     /// 一時停止フラグファイルパス
     pub pause_file: PathBuf,
+    /// 一時停止中に何を記録するか
+    pub pause_mode: PauseMode,
+    /// 実行中のポモドーロの状態ファイルパス
+    pub pomodoro_file: PathBuf,
+    /// 多重起動防止のPIDファイルパス
+    pub pid_file: PathBuf,
+    /// OCRの信頼度・座標をocr_detailsに保存するか
+    pub ocr_save_details: bool,
+    /// OCRバックエンド
+    pub ocr_backend: OcrBackendKind,
+    /// 1日の境界時刻（0-23時、この時刻までは前日として集計）
+    pub day_boundary_hour: u32,
+    /// キーボード・マウスの活動量（イベント数のみ）を記録するか（既定では記録しない）
+    pub record_input_metrics: bool,
+    /// アプリ切り替えの瞬間をイベントとして記録するか
+    pub record_app_events: bool,
+    /// キャプチャごとに記録するプロセス数（CPU使用率の上位、0で無効）
+    pub process_snapshot_count: usize,
+    /// キャプチャが連続で失敗したら復旧を試みる回数（0で無効）
+    pub watchdog_threshold: u32,
+    /// 復旧後も失敗が続いた場合に休止する時間（秒）
+    pub watchdog_cooldown_seconds: u64,
+    /// 同じ種類のエラーが1時間にこの回数発生したら通知する（0で通知しない）
+    pub error_notify_threshold: u32,
+    /// キャプチャの前に `sh -c` で実行するコマンド（未設定なら実行しない）
+    pub pre_capture_hook: Option<String>,
+    /// キャプチャの記録後に、標準入力にキャプチャのJSONを渡して実行するコマンド（未設定なら実行しない）
+    pub post_capture_hook: Option<String>,
+    /// 追加で有効にするメタデータプロバイダ（アプリ名・ウィンドウタイトルは常に有効）
+    pub metadata_providers: Vec<MetadataProviderKind>,
+    /// メタデータを出力する外部コマンド（名前 → `sh -c` で実行するコマンド）
+    pub metadata_commands: BTreeMap<String, String>,
+    /// WALを定期的にチェックポイントする間隔（分、0で定期実行しない）
+    pub wal_checkpoint_minutes: u64,
+    /// WALファイルがこのサイズを超えたらチェックポイントする（MB、0でサイズを見ない）
+    pub wal_checkpoint_max_mb: u64,
+    /// ログをファイルにも出力するか
+    pub log_to_file: bool,
+    /// ログファイルの保存ディレクトリ
+    pub log_dir: PathBuf,
+    /// 残すログファイルの世代数（日数）
+    pub log_max_files: usize,
+    /// ログの出力形式（環境変数 `RUST_LOG_FORMAT` が優先）
+    pub log_format: LogFormat,
+    /// スパンをOpenTelemetryのトレースとして送るOTLP/HTTPのエンドポイント（未設定なら送らない）
+    pub otlp_endpoint: Option<String>,
+    /// アプリのカテゴリ分類
+    pub categories: Categories,
+    /// 1日の作業時間の目標（カテゴリ名または合計 → 分）
+    pub goals: Goals,
+    /// アプリの表示名の統一（アプリ名またはbundle IDから表示名へ）
+    pub aliases: AppAliases,
+    /// フルスクリーン時に動画視聴とみなすアプリ・ウィンドウタイトル
+    pub media: MediaRules,
+    /// 会議とみなすアプリ・ウィンドウタイトル
+    pub meeting: MeetingRules,
+    /// 作業時間に含めない非作業（ロック画面・デスクトップのみなど）のアプリ
+    pub non_work: NonWorkRules,
+    /// 同じアプリをこの時間以上続けて使った区間をフォーカス時間とする（分）
+    pub focus_min_minutes: u64,
+    /// 作業時間帯に使うと脱線とみなすアプリ・サイト
+    pub distractions: Distractions,
+    /// 脱線アプリをこの時間だけ使い続けたら警告する（分、0で警告しない）
+    pub distraction_warn_minutes: u64,
+    /// 同じウィンドウのまま入力のない状態がこの時間続いたら放置中か確認する（分、0で確認しない）
+    pub idle_warn_minutes: u64,
+    /// 保存前にウィンドウタイトルから `***` に置き換える部分の正規表現
+    pub title_redact_patterns: Vec<String>,
+    /// 画像・OCR・タイトルを残さないプライベートブラウジングのウィンドウ
+    pub private_windows: PrivateWindows,
+    /// Obsidianデイリーノート連携（未設定なら無効）
+    pub obsidian: Option<ObsidianConfig>,
+    /// 休憩リマインダー（未設定なら無効）
+    pub break_reminder: Option<BreakReminderConfig>,
+    /// Jira/Linearへの工数登録（未設定なら無効）
+    pub worklog: Option<WorklogConfig>,
+    /// macOSの集中モード（Focus）との連動
+    pub focus: FocusConfig,
+    /// ワードクラウドで日本語の形態素解析に使うビルド済み辞書（未設定なら文字種で分割）
+    pub wordcloud_dictionary: Option<PathBuf>,
+    /// CLIメッセージ・レポートの表示言語（未設定なら環境変数から判定）
+    pub language: Option<Language>,
+}
+
+/// Obsidianデイリーノート連携の設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObsidianConfig {
+    /// Vaultのパス
+    pub vault_path: PathBuf,
+    /// Vault内のデイリーノートのパス（strftime形式）
+    pub daily_note: String,
+    /// サマリーを書き込む見出し行
+    pub section: String,
+    /// サマリーのテンプレート（`{date}` `{total}` `{apps}` を置換）
+    pub template: String,
+}
+
+impl ObsidianConfig {
+    /// デフォルトのデイリーノートのパス
+    pub const DEFAULT_DAILY_NOTE: &'static str = "%Y-%m-%d.md";
+    /// デフォルトの見出し
+    pub const DEFAULT_SECTION: &'static str = "## 作業ログ";
+    /// デフォルトのテンプレート
+    pub const DEFAULT_TEMPLATE: &'static str = "合計: {total}\n\n{apps}";
+}
+
+/// Jira/Linearへの工数登録の設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorklogConfig {
+    /// 登録先のサービス
+    pub service: WorklogService,
+    /// APIのURL（JiraはサイトのURL、Linearは省略時 `https://api.linear.app/graphql`）
+    pub url: Option<String>,
+    /// Jiraのアカウントのメールアドレス（Basic認証に使う）
+    pub email: Option<String>,
+    /// APIトークン（Linearは個人APIキー）
+    pub api_token: String,
+    /// ウィンドウタイトルからチケットIDを抜き出す正規表現
+    pub ticket_pattern: String,
+    /// タイトルにチケットIDがないときのプロジェクト（ワークスペース名・アプリ名）→チケットID
+    pub mappings: BTreeMap<String, String>,
+}
+
+impl WorklogConfig {
+    /// デフォルトのチケットIDの正規表現（`PROJ-123` 形式）
+    pub const DEFAULT_TICKET_PATTERN: &'static str = r"\b[A-Z][A-Z0-9]+-[0-9]+\b";
+}
+
+/// 休憩リマインダーの設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakReminderConfig {
+    /// 休憩を促すまでの連続作業時間（分）
+    pub minutes: u64,
+    /// 同じカテゴリの連続で数えるか（`false` ならPC操作全般）
+    pub per_category: bool,
+    /// 通知しない時間帯の開始時刻（0-23時）
+    pub quiet_start_hour: Option<u32>,
+    /// 通知しない時間帯の終了時刻（0-23時、この時刻から通知を再開）
+    pub quiet_end_hour: Option<u32>,
+}
+
+impl BreakReminderConfig {
+    /// `hour` 時台が通知しない時間帯か（開始 > 終了なら日をまたぐ）
+    pub fn is_quiet(&self, hour: u32) -> bool {
+        match (self.quiet_start_hour, self.quiet_end_hour) {
+            (Some(start), Some(end)) if start <= end => (start..end).contains(&hour),
+            (Some(start), Some(end)) => hour >= start || hour < end,
+            _ => false,
+        }
+    }
+}
+
+/// macOSの集中モード（Focus）との連動設定
+///
+/// モード名は大文字小文字を区別せず比較する
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusConfig {
+    /// これらの集中モードがオンの間だけ記録する（空なら制限しない）
+    pub record_only: Vec<String>,
+    /// これらの集中モードがオンの間は一時停止する
+    pub pause: Vec<String>,
+}
+
+impl FocusConfig {
+    /// 連動する設定があるか
+    pub fn is_enabled(&self) -> bool {
+        !self.record_only.is_empty() || !self.pause.is_empty()
+    }
+
+    /// オンになっている集中モード（オフなら `None`）から、記録を止めるべきかを判定
+    pub fn should_pause(&self, focus: Option<&str>) -> bool {
+        let contains = |modes: &[String], focus: &str| {
+            modes.iter().any(|mode| mode.eq_ignore_ascii_case(focus))
+        };
+        match focus {
+            Some(focus) => {
+                contains(&self.pause, focus)
+                    || (!self.record_only.is_empty() && !contains(&self.record_only, focus))
+            }
+            None => !self.record_only.is_empty(),
+        }
+    }
+}
+
+/// 設定ファイルとデータ（DB・画像・ログなど）の置き場所
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    /// `config.toml` を置くディレクトリ
+    pub config_dir: PathBuf,
+    /// DB・画像・ログ・PIDファイルなどを置くディレクトリ
+    pub data_dir: PathBuf,
+}
+
+impl Paths {
+    /// 環境変数から置き場所を決める
+    ///
+    /// 1. `HABIT_TRACKER_HOME` があれば、設定もデータもその下
+    /// 2. 従来の `~/.habit-tracker` があれば、引き続きその下
+    /// 3. `XDG_CONFIG_HOME` / `XDG_DATA_HOME` があれば、それぞれの `habit-tracker` の下
+    /// 4. どれもなければ `~/.habit-tracker` の下
+    ///
+    /// プロファイルを使う場合はさらにその下の `profiles/<名前>`
+    pub fn resolve() -> Self {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let legacy_exists = home.join(".habit-tracker").is_dir();
+        let paths = Self::from_env(|name| env::var_os(name), &home, legacy_exists);
+        match profile() {
+            Some(name) => paths.for_profile(name),
+            None => paths,
+        }
+    }
+
+    /// プロファイル用の置き場所
+    fn for_profile(self, name: &str) -> Self {
+        Self {
+            config_dir: self.config_dir.join(PROFILES_DIR_NAME).join(name),
+            data_dir: self.data_dir.join(PROFILES_DIR_NAME).join(name),
+        }
+    }
+
+    fn from_env<F>(var: F, home: &Path, legacy_exists: bool) -> Self
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        // 空の値や相対パスは未設定とみなす（XDG Base Directory仕様と同じ扱い）
+        let dir = |name: &str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        if let Some(dir) = dir(HOME_ENV) {
+            return Self {
+                config_dir: dir.clone(),
+                data_dir: dir,
+            };
+        }
+
+        let legacy = home.join(".habit-tracker");
+        if legacy_exists {
+            return Self {
+                config_dir: legacy.clone(),
+                data_dir: legacy,
+            };
+        }
+        Self {
+            config_dir: dir("XDG_CONFIG_HOME")
+                .map(|dir| dir.join(XDG_DIR_NAME))
+                .unwrap_or_else(|| legacy.clone()),
+            data_dir: dir("XDG_DATA_HOME")
+                .map(|dir| dir.join(XDG_DIR_NAME))
+                .unwrap_or(legacy),
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        let base_dir = home.join(".habit-tracker");
+        let base_dir = Paths::resolve().data_dir;
 
         Self {
             interval_seconds: 60,
+            adaptive_interval: false,
+            min_interval_seconds: 15,
+            max_interval_seconds: 300,
             jpeg_quality: 60,
+            blank_image_max_stddev: 4.0,
             db_path: base_dir.join("tracker.db"),
             images_dir: base_dir.join("images"),
+            image_filename_format: image_store::DEFAULT_FILENAME_FORMAT.to_string(),
+            image_buffer_count: 0,
+            image_buffer_dir: base_dir.join("image_buffer"),
             pause_file: base_dir.join("pause"),
+            pause_mode: PauseMode::default(),
+            pomodoro_file: base_dir.join("pomodoro.json"),
+            pid_file: base_dir.join("tracker.pid"),
+            ocr_save_details: false,
+            ocr_backend: OcrBackendKind::default(),
+            day_boundary_hour: 0,
+            record_input_metrics: false,
+            record_app_events: true,
+            process_snapshot_count: 5,
+            watchdog_threshold: 5,
+            watchdog_cooldown_seconds: 600,
+            error_notify_threshold: 10,
+            pre_capture_hook: None,
+            post_capture_hook: None,
+            metadata_providers: Vec::new(),
+            metadata_commands: BTreeMap::new(),
+            wal_checkpoint_minutes: 60,
+            wal_checkpoint_max_mb: 64,
+            log_to_file: false,
+            log_dir: base_dir.join("logs"),
+            log_max_files: 7,
+            log_format: LogFormat::default(),
+            otlp_endpoint: None,
+            categories: Categories::default(),
+            goals: Goals::default(),
+            aliases: AppAliases::default(),
+            media: MediaRules::default(),
+            meeting: MeetingRules::default(),
+            non_work: NonWorkRules::default(),
+            focus_min_minutes: 25,
+            distractions: Distractions::default(),
+            distraction_warn_minutes: 10,
+            idle_warn_minutes: 120,
+            title_redact_patterns: Vec::new(),
+            private_windows: PrivateWindows::default(),
+            obsidian: None,
+            break_reminder: None,
+            worklog: None,
+            focus: FocusConfig::default(),
+            wordcloud_dictionary: None,
+            language: None,
         }
     }
 }
@@ -39,14 +447,122 @@ impl Default for Config {
 #[derive(Debug, Deserialize, Default)]
 struct FileConfig {
     interval_seconds: Option<u64>,
+    adaptive_interval: Option<bool>,
+    min_interval_seconds: Option<u64>,
+    max_interval_seconds: Option<u64>,
     jpeg_quality: Option<u8>,
+    blank_image_max_stddev: Option<f64>,
     db_path: Option<String>,
     images_dir: Option<String>,
+    image_filename_format: Option<String>,
+    image_buffer_count: Option<usize>,
+    image_buffer_dir: Option<String>,
     pause_file: Option<String>,
+    pause_mode: Option<PauseMode>,
+    pid_file: Option<String>,
+    ocr_save_details: Option<bool>,
+    ocr_backend: Option<OcrBackendKind>,
+    day_boundary_hour: Option<u32>,
+    record_input_metrics: Option<bool>,
+    record_app_events: Option<bool>,
+    process_snapshot_count: Option<usize>,
+    watchdog_threshold: Option<u32>,
+    watchdog_cooldown_seconds: Option<u64>,
+    error_notify_threshold: Option<u32>,
+    pre_capture_hook: Option<String>,
+    post_capture_hook: Option<String>,
+    metadata_providers: Option<Vec<MetadataProviderKind>>,
+    metadata_commands: Option<BTreeMap<String, String>>,
+    wal_checkpoint_minutes: Option<u64>,
+    wal_checkpoint_max_mb: Option<u64>,
+    log_to_file: Option<bool>,
+    log_dir: Option<String>,
+    log_max_files: Option<usize>,
+    log_format: Option<LogFormat>,
+    otlp_endpoint: Option<String>,
+    categories: Option<BTreeMap<String, Vec<String>>>,
+    goals: Option<BTreeMap<String, u64>>,
+    aliases: Option<BTreeMap<String, String>>,
+    media_apps: Option<Vec<String>>,
+    media_title_keywords: Option<Vec<String>>,
+    meeting_apps: Option<Vec<String>>,
+    meeting_title_keywords: Option<Vec<String>>,
+    non_work_apps: Option<Vec<String>>,
+    non_work_desktop_apps: Option<Vec<String>>,
+    focus_min_minutes: Option<u64>,
+    distraction_apps: Option<Vec<String>>,
+    distraction_warn_minutes: Option<u64>,
+    idle_warn_minutes: Option<u64>,
+    title_redact_patterns: Option<Vec<String>>,
+    private_title_keywords: Option<Vec<String>>,
+    work_start_hour: Option<u32>,
+    work_end_hour: Option<u32>,
+    obsidian: Option<ObsidianFileConfig>,
+    break_reminder: Option<BreakReminderFileConfig>,
+    worklog: Option<WorklogFileConfig>,
+    focus: Option<FocusFileConfig>,
+    wordcloud_dictionary: Option<String>,
+    language: Option<Language>,
+}
+
+impl FileConfig {
+    /// 他の設定と組にして指定する値の指定漏れを検出
+    fn validate(&self) -> Result<(), ConfigError> {
+        // 作業時間帯は開始と終了の両方がそろって初めて有効になる
+        let missing = match (self.work_start_hour, self.work_end_hour) {
+            (Some(_), None) => Some(("work_start_hour", "work_end_hour")),
+            (None, Some(_)) => Some(("work_end_hour", "work_start_hour")),
+            _ => None,
+        };
+        match missing {
+            Some((field, other)) => Err(ConfigError::Validation {
+                field: field.to_string(),
+                reason: format!("{}も指定してください", other),
+                line: None,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// TOML設定ファイルの `[obsidian]` テーブル
+#[derive(Debug, Deserialize, Default)]
+struct ObsidianFileConfig {
+    vault_path: Option<String>,
+    daily_note: Option<String>,
+    section: Option<String>,
+    template: Option<String>,
+}
+
+/// TOML設定ファイルの `[break_reminder]` テーブル
+#[derive(Debug, Deserialize, Default)]
+struct BreakReminderFileConfig {
+    minutes: Option<u64>,
+    per_category: Option<bool>,
+    quiet_start_hour: Option<u32>,
+    quiet_end_hour: Option<u32>,
+}
+
+/// TOML設定ファイルの `[worklog]` テーブル
+#[derive(Debug, Deserialize)]
+struct WorklogFileConfig {
+    service: WorklogService,
+    url: Option<String>,
+    email: Option<String>,
+    api_token: Option<String>,
+    ticket_pattern: Option<String>,
+    mappings: Option<BTreeMap<String, String>>,
+}
+
+/// TOML設定ファイルの `[focus]` テーブル
+#[derive(Debug, Deserialize, Default)]
+struct FocusFileConfig {
+    record_only: Option<Vec<String>>,
+    pause: Option<Vec<String>>,
 }
 
 /// CLI引数
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct CliArgs {
     pub interval: Option<u64>,
     pub quality: Option<u8>,
@@ -59,19 +575,34 @@ impl Config {
     pub fn load(cli_args: &CliArgs) -> Result<Self, ConfigError> {
         let mut config = Config::default();
 
-        // 設定ファイルを読み込む
+        // 設定ファイルを読み込む（`--config` で指定したファイルは必須）
+        let overrides = overrides();
         let config_path = config.config_file_path();
+        if overrides.config_file.is_some() && !config_path.exists() {
+            return Err(ConfigError::ConfigFileNotFound(config_path));
+        }
         if config_path.exists() {
             let content = fs::read_to_string(&config_path)?;
             let file_config: FileConfig = toml::from_str(&content)?;
+            file_config
+                .validate()
+                .map_err(|err| with_line_hints(err, &content))?;
             config.merge_file_config(&file_config);
         }
 
         // CLI引数で上書き
         config.merge_cli_args(cli_args);
+        if let Some(path) = overrides.db_path {
+            config.db_path = path;
+        }
 
-        // バリデーション
-        config.validate()?;
+        // バリデーション（設定ファイル由来の値なら行番号を添える）
+        config
+            .validate()
+            .map_err(|err| match fs::read_to_string(&config_path) {
+                Ok(content) => with_line_hints(err, &content),
+                Err(_) => err,
+            })?;
 
         // ディレクトリを作成
         config.ensure_directories()?;
@@ -80,9 +611,11 @@ impl Config {
     }
 
     /// 設定ファイルのパスを取得
-    fn config_file_path(&self) -> PathBuf {
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        home.join(".habit-tracker").join("config.toml")
+    pub fn config_file_path(&self) -> PathBuf {
+        match OVERRIDES.get().and_then(|o| o.config_file.clone()) {
+            Some(path) => path,
+            None => Paths::resolve().config_dir.join("config.toml"),
+        }
     }
 
     /// ファイル設定をマージ
@@ -90,18 +623,213 @@ impl Config {
         if let Some(interval) = file_config.interval_seconds {
             self.interval_seconds = interval;
         }
+        if let Some(adaptive) = file_config.adaptive_interval {
+            self.adaptive_interval = adaptive;
+        }
+        if let Some(min) = file_config.min_interval_seconds {
+            self.min_interval_seconds = min;
+        }
+        if let Some(max) = file_config.max_interval_seconds {
+            self.max_interval_seconds = max;
+        }
         if let Some(quality) = file_config.jpeg_quality {
             self.jpeg_quality = quality;
         }
+        if let Some(stddev) = file_config.blank_image_max_stddev {
+            self.blank_image_max_stddev = stddev;
+        }
         if let Some(ref path) = file_config.db_path {
             self.db_path = PathBuf::from(path);
         }
         if let Some(ref path) = file_config.images_dir {
             self.images_dir = PathBuf::from(path);
         }
+        if let Some(ref format) = file_config.image_filename_format {
+            self.image_filename_format = format.clone();
+        }
+        if let Some(count) = file_config.image_buffer_count {
+            self.image_buffer_count = count;
+        }
+        if let Some(ref path) = file_config.image_buffer_dir {
+            self.image_buffer_dir = PathBuf::from(path);
+        }
         if let Some(ref path) = file_config.pause_file {
             self.pause_file = PathBuf::from(path);
         }
+        if let Some(mode) = file_config.pause_mode {
+            self.pause_mode = mode;
+        }
+        if let Some(ref path) = file_config.pid_file {
+            self.pid_file = PathBuf::from(path);
+        }
+        if let Some(save) = file_config.ocr_save_details {
+            self.ocr_save_details = save;
+        }
+        if let Some(backend) = file_config.ocr_backend {
+            self.ocr_backend = backend;
+        }
+        if let Some(hour) = file_config.day_boundary_hour {
+            self.day_boundary_hour = hour;
+        }
+        if let Some(record) = file_config.record_input_metrics {
+            self.record_input_metrics = record;
+        }
+        if let Some(record) = file_config.record_app_events {
+            self.record_app_events = record;
+        }
+        if let Some(count) = file_config.process_snapshot_count {
+            self.process_snapshot_count = count;
+        }
+        if let Some(threshold) = file_config.watchdog_threshold {
+            self.watchdog_threshold = threshold;
+        }
+        if let Some(cooldown) = file_config.watchdog_cooldown_seconds {
+            self.watchdog_cooldown_seconds = cooldown;
+        }
+        if let Some(threshold) = file_config.error_notify_threshold {
+            self.error_notify_threshold = threshold;
+        }
+        if let Some(ref command) = file_config.pre_capture_hook {
+            self.pre_capture_hook = Some(command.clone()).filter(|c| !c.trim().is_empty());
+        }
+        if let Some(ref command) = file_config.post_capture_hook {
+            self.post_capture_hook = Some(command.clone()).filter(|c| !c.trim().is_empty());
+        }
+        if let Some(ref providers) = file_config.metadata_providers {
+            self.metadata_providers = providers.clone();
+        }
+        if let Some(ref commands) = file_config.metadata_commands {
+            self.metadata_commands = commands
+                .iter()
+                .filter(|(name, command)| !name.trim().is_empty() && !command.trim().is_empty())
+                .map(|(name, command)| (name.trim().to_string(), command.clone()))
+                .collect();
+        }
+        if let Some(minutes) = file_config.wal_checkpoint_minutes {
+            self.wal_checkpoint_minutes = minutes;
+        }
+        if let Some(mb) = file_config.wal_checkpoint_max_mb {
+            self.wal_checkpoint_max_mb = mb;
+        }
+        if let Some(enabled) = file_config.log_to_file {
+            self.log_to_file = enabled;
+        }
+        if let Some(ref path) = file_config.log_dir {
+            self.log_dir = PathBuf::from(path);
+        }
+        if let Some(count) = file_config.log_max_files {
+            self.log_max_files = count;
+        }
+        if let Some(format) = file_config.log_format {
+            self.log_format = format;
+        }
+        if let Some(ref endpoint) = file_config.otlp_endpoint {
+            self.otlp_endpoint = Some(endpoint.clone()).filter(|e| !e.trim().is_empty());
+        }
+        if let Some(ref categories) = file_config.categories {
+            self.categories = Categories::new(categories.clone());
+        }
+        if let Some(ref goals) = file_config.goals {
+            self.goals = Goals::new(goals.clone());
+        }
+        if let Some(ref aliases) = file_config.aliases {
+            self.aliases = AppAliases::new(aliases.clone());
+        }
+        if let Some(ref apps) = file_config.media_apps {
+            self.media = self.media.clone().with_apps(apps.clone());
+        }
+        if let Some(ref keywords) = file_config.media_title_keywords {
+            self.media = self.media.clone().with_title_keywords(keywords.clone());
+        }
+        if let Some(ref apps) = file_config.meeting_apps {
+            self.meeting = self.meeting.clone().with_apps(apps.clone());
+        }
+        if let Some(ref keywords) = file_config.meeting_title_keywords {
+            self.meeting = self.meeting.clone().with_title_keywords(keywords.clone());
+        }
+        if let Some(ref apps) = file_config.non_work_apps {
+            self.non_work = self.non_work.clone().with_apps(apps.clone());
+        }
+        if let Some(ref apps) = file_config.non_work_desktop_apps {
+            self.non_work = self.non_work.clone().with_desktop_apps(apps.clone());
+        }
+        if let Some(minutes) = file_config.focus_min_minutes {
+            self.focus_min_minutes = minutes;
+        }
+        if let Some(ref apps) = file_config.distraction_apps {
+            self.distractions = Distractions::new(apps.clone());
+        }
+        if let (Some(start), Some(end)) = (file_config.work_start_hour, file_config.work_end_hour) {
+            self.distractions = self.distractions.clone().with_work_hours(start, end);
+        }
+        if let Some(minutes) = file_config.distraction_warn_minutes {
+            self.distraction_warn_minutes = minutes;
+        }
+        if let Some(minutes) = file_config.idle_warn_minutes {
+            self.idle_warn_minutes = minutes;
+        }
+        if let Some(ref patterns) = file_config.title_redact_patterns {
+            self.title_redact_patterns = patterns.clone();
+        }
+        if let Some(ref keywords) = file_config.private_title_keywords {
+            self.private_windows = PrivateWindows::new(keywords.clone());
+        }
+        if let Some(ref obsidian) = file_config.obsidian {
+            // vault_pathがなければ連携は無効
+            self.obsidian = obsidian.vault_path.as_ref().map(|vault| ObsidianConfig {
+                vault_path: PathBuf::from(vault),
+                daily_note: obsidian
+                    .daily_note
+                    .clone()
+                    .unwrap_or_else(|| ObsidianConfig::DEFAULT_DAILY_NOTE.to_string()),
+                section: obsidian
+                    .section
+                    .clone()
+                    .unwrap_or_else(|| ObsidianConfig::DEFAULT_SECTION.to_string()),
+                template: obsidian
+                    .template
+                    .clone()
+                    .unwrap_or_else(|| ObsidianConfig::DEFAULT_TEMPLATE.to_string()),
+            });
+        }
+        if let Some(ref reminder) = file_config.break_reminder {
+            // minutesがなければ（または0なら）リマインダーは無効
+            self.break_reminder = reminder
+                .minutes
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| BreakReminderConfig {
+                    minutes,
+                    per_category: reminder.per_category.unwrap_or(false),
+                    quiet_start_hour: reminder.quiet_start_hour,
+                    quiet_end_hour: reminder.quiet_end_hour,
+                });
+        }
+        if let Some(ref worklog) = file_config.worklog {
+            // api_tokenがなければ連携は無効
+            self.worklog = worklog.api_token.as_ref().map(|token| WorklogConfig {
+                service: worklog.service,
+                url: worklog.url.clone(),
+                email: worklog.email.clone(),
+                api_token: token.clone(),
+                ticket_pattern: worklog
+                    .ticket_pattern
+                    .clone()
+                    .unwrap_or_else(|| WorklogConfig::DEFAULT_TICKET_PATTERN.to_string()),
+                mappings: worklog.mappings.clone().unwrap_or_default(),
+            });
+        }
+        if let Some(ref path) = file_config.wordcloud_dictionary {
+            self.wordcloud_dictionary = Some(PathBuf::from(path));
+        }
+        if let Some(language) = file_config.language {
+            self.language = Some(language);
+        }
+        if let Some(ref focus) = file_config.focus {
+            self.focus = FocusConfig {
+                record_only: focus.record_only.clone().unwrap_or_default(),
+                pause: focus.pause.clone().unwrap_or_default(),
+            };
+        }
     }
 
     /// CLI引数をマージ
@@ -116,42 +844,134 @@ impl Config {
 
     /// 設定値をバリデート
     fn validate(&self) -> Result<(), ConfigError> {
-        if self.interval_seconds == 0 {
-            return Err(ConfigError::DirectoryCreationError(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "interval_seconds must be greater than 0",
-            )));
-        }
-        if self.jpeg_quality > 100 {
-            return Err(ConfigError::DirectoryCreationError(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "jpeg_quality must be between 0 and 100",
-            )));
+        let mut errors = Vec::new();
+        let mut check = |valid: bool, field: &str, reason: &str| {
+            if !valid {
+                errors.push(ConfigError::Validation {
+                    field: field.to_string(),
+                    reason: reason.to_string(),
+                    line: None,
+                });
+            }
+        };
+        let is_hour = |hour: u32| hour <= 23;
+
+        check(
+            self.interval_seconds > 0,
+            "interval_seconds",
+            "1以上を指定してください",
+        );
+        if self.adaptive_interval {
+            check(
+                self.min_interval_seconds > 0,
+                "min_interval_seconds",
+                "1以上を指定してください",
+            );
+            check(
+                self.min_interval_seconds <= self.max_interval_seconds,
+                "max_interval_seconds",
+                "min_interval_seconds以上を指定してください",
+            );
+        }
+        check(
+            self.jpeg_quality <= 100,
+            "jpeg_quality",
+            "0〜100で指定してください",
+        );
+        if let Err(reason) = image_store::validate_filename_format(&self.image_filename_format) {
+            check(false, "image_filename_format", &reason);
+        }
+        check(
+            self.focus_min_minutes > 0,
+            "focus_min_minutes",
+            "1以上を指定してください",
+        );
+        check(
+            self.goals.iter().all(|(_, minutes)| minutes > 0),
+            "goals",
+            "目標時間は1分以上を指定してください",
+        );
+        check(
+            is_hour(self.day_boundary_hour),
+            "day_boundary_hour",
+            "0〜23で指定してください",
+        );
+        if let Some((start, end)) = self.distractions.work_hours() {
+            check(is_hour(start), "work_start_hour", "0〜23で指定してください");
+            check(is_hour(end), "work_end_hour", "0〜23で指定してください");
+        }
+        if let Some(ref reminder) = self.break_reminder {
+            if let Some(hour) = reminder.quiet_start_hour {
+                check(
+                    is_hour(hour),
+                    "break_reminder.quiet_start_hour",
+                    "0〜23で指定してください",
+                );
+            }
+            if let Some(hour) = reminder.quiet_end_hour {
+                check(
+                    is_hour(hour),
+                    "break_reminder.quiet_end_hour",
+                    "0〜23で指定してください",
+                );
+            }
+        }
+        for pattern in &self.title_redact_patterns {
+            if let Err(e) = TitleRedactor::new(std::slice::from_ref(pattern)) {
+                check(
+                    false,
+                    "title_redact_patterns",
+                    &format!("正規表現 {} が不正です（{}）", pattern, e),
+                );
+            }
+        }
+
+        if let Some(ref worklog) = self.worklog {
+            if let Err(e) = TicketMatcher::new(&worklog.ticket_pattern, BTreeMap::new()) {
+                check(
+                    false,
+                    "worklog.ticket_pattern",
+                    &format!("正規表現 {} が不正です（{}）", worklog.ticket_pattern, e),
+                );
+            }
+            if worklog.service == WorklogService::Jira {
+                check(
+                    worklog.url.is_some(),
+                    "worklog.url",
+                    "JiraのサイトのURLを指定してください",
+                );
+                check(
+                    worklog.email.is_some(),
+                    "worklog.email",
+                    "Jiraのアカウントのメールアドレスを指定してください",
+                );
+            }
+        }
+
+        match ConfigError::from_validation(errors) {
+            Some(err) => Err(err),
+            None => Ok(()),
         }
-        Ok(())
     }
 
     /// 必要なディレクトリを作成
     fn ensure_directories(&self) -> Result<(), ConfigError> {
         // images_dirを作成
         if !self.images_dir.exists() {
-            fs::create_dir_all(&self.images_dir)
-                .map_err(ConfigError::DirectoryCreationError)?;
+            fs::create_dir_all(&self.images_dir).map_err(ConfigError::DirectoryCreationError)?;
         }
 
         // db_pathの親ディレクトリを作成
         if let Some(parent) = self.db_path.parent() {
             if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(ConfigError::DirectoryCreationError)?;
+                fs::create_dir_all(parent).map_err(ConfigError::DirectoryCreationError)?;
             }
         }
 
         // pause_fileの親ディレクトリを作成
         if let Some(parent) = self.pause_file.parent() {
             if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(ConfigError::DirectoryCreationError)?;
+                fs::create_dir_all(parent).map_err(ConfigError::DirectoryCreationError)?;
             }
         }
 
@@ -159,7 +979,91 @@ impl Config {
     }
 }
 
+/// `tracker init` で生成する設定ファイルの内容
+///
+/// 主な設定をデフォルト値（間隔と画質は指定値）で書き出し、その他はコメントで例示する
+pub fn initial_file_content(interval_seconds: u64, jpeg_quality: u8) -> String {
+    let config = Config::default();
+    format!(
+        r#"# Habit Trackerの設定（詳細はREADMEの「設定」を参照）
+
+# CLIメッセージ・レポートの表示言語（"ja" | "en"、省略時はLANGから判定）
+# language = "ja"
+
+# キャプチャ間隔（秒）とJPEG品質（0-100）
+interval_seconds = {interval_seconds}
+jpeg_quality = {jpeg_quality}
+
+# 1日の境界時刻（4なら午前4時までは前日として集計）
+day_boundary_hour = {day_boundary_hour}
+
+# OCRバックエンド（"vision" | "tesseract" | "none"）
+ocr_backend = "vision"
+
+# ログをファイルにも出力（日次ローテーション）
+log_to_file = {log_to_file}
+
+# カテゴリ分類（カテゴリ名 = [アプリ名またはbundle ID, ...]）
+# [categories]
+# "開発" = ["VS Code", "Terminal"]
+# "コミュニケーション" = ["Slack", "Mail"]
+
+# 1日の作業時間の目標（カテゴリ名または "total" = 分）と連続達成日数
+# [goals]
+# "開発" = 180
+# total = 360
+"#,
+        day_boundary_hour = config.day_boundary_hour,
+        log_to_file = config.log_to_file,
+    )
+}
+
+/// バリデーションエラーに、該当する設定がconfig.tomlの何行目にあるかを添える
+fn with_line_hints(err: ConfigError, content: &str) -> ConfigError {
+    match err {
+        ConfigError::Validation { field, reason, .. } => {
+            let line = find_key_line(content, &field);
+            ConfigError::Validation {
+                field,
+                reason,
+                line,
+            }
+        }
+        ConfigError::ValidationErrors(errors) => ConfigError::ValidationErrors(
+            errors
+                .into_iter()
+                .map(|err| with_line_hints(err, content))
+                .collect(),
+        ),
+        err => err,
+    }
+}
+
+/// `table.key` 形式のキーが書かれた行番号（1始まり）を探す
+///
+/// テーブルのないキーはファイル先頭（最初のテーブル見出しより前）から探す
+fn find_key_line(content: &str, field: &str) -> Option<usize> {
+    let (table, key) = field.rsplit_once('.').unwrap_or(("", field));
+    let mut current_table = "";
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current_table = header.trim();
+            continue;
+        }
+        let Some((name, _)) = line.split_once('=') else {
+            continue;
+        };
+        if current_table == table && name.trim() == key {
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
+// 既定値から一部の項目だけを変えて検証するテストは、代入で書いたほうが差分が読みやすい
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
@@ -191,15 +1095,128 @@ mod tests {
         let mut config = Config::default();
         let file_config = FileConfig {
             interval_seconds: Some(120),
+            adaptive_interval: Some(true),
+            min_interval_seconds: Some(10),
+            max_interval_seconds: Some(600),
             jpeg_quality: Some(90),
+            blank_image_max_stddev: Some(0.0),
             db_path: Some("/tmp/test.db".to_string()),
             images_dir: Some("/tmp/images".to_string()),
+            image_filename_format: Some("%Y%m%d_%H%M%S_%app%.jpg".to_string()),
+            image_buffer_count: Some(20),
+            image_buffer_dir: Some("/tmp/image_buffer".to_string()),
             pause_file: Some("/tmp/pause".to_string()),
+            pause_mode: Some(PauseMode::MetadataOnly),
+            pid_file: Some("/tmp/tracker.pid".to_string()),
+            ocr_save_details: Some(true),
+            ocr_backend: Some(OcrBackendKind::Tesseract),
+            day_boundary_hour: Some(4),
+            record_input_metrics: Some(true),
+            record_app_events: Some(false),
+            process_snapshot_count: Some(10),
+            watchdog_threshold: Some(3),
+            watchdog_cooldown_seconds: Some(300),
+            error_notify_threshold: Some(0),
+            pre_capture_hook: Some(" ".to_string()),
+            post_capture_hook: Some("~/bin/send.sh".to_string()),
+            metadata_providers: Some(vec![MetadataProviderKind::Git]),
+            metadata_commands: Some(BTreeMap::from([
+                ("jira".to_string(), "~/bin/ticket.sh".to_string()),
+                ("empty".to_string(), " ".to_string()),
+            ])),
+            wal_checkpoint_minutes: Some(30),
+            wal_checkpoint_max_mb: Some(0),
+            log_to_file: Some(true),
+            log_dir: Some("/tmp/logs".to_string()),
+            log_max_files: Some(3),
+            log_format: Some(LogFormat::Json),
+            otlp_endpoint: Some("http://localhost:4318".to_string()),
+            categories: None,
+            goals: None,
+            aliases: None,
+            media_apps: Some(vec!["Plex".to_string()]),
+            media_title_keywords: Some(vec![]),
+            meeting_apps: Some(vec!["Around".to_string()]),
+            meeting_title_keywords: None,
+            non_work_apps: Some(vec!["Dock".to_string()]),
+            non_work_desktop_apps: None,
+            focus_min_minutes: Some(50),
+            distraction_apps: Some(vec!["Twitter".to_string()]),
+            distraction_warn_minutes: Some(0),
+            idle_warn_minutes: Some(180),
+            title_redact_patterns: Some(vec!["(?i)password".to_string()]),
+            private_title_keywords: Some(vec!["秘密".to_string()]),
+            work_start_hour: Some(9),
+            work_end_hour: Some(18),
+            obsidian: None,
+            break_reminder: None,
+            worklog: None,
+            focus: None,
+            wordcloud_dictionary: Some("/tmp/ipadic".to_string()),
+            language: Some(Language::En),
         };
         config.merge_file_config(&file_config);
         assert_eq!(config.interval_seconds, 120);
+        assert!(config.adaptive_interval);
+        assert_eq!(config.min_interval_seconds, 10);
+        assert_eq!(config.max_interval_seconds, 600);
         assert_eq!(config.jpeg_quality, 90);
+        assert_eq!(config.blank_image_max_stddev, 0.0);
         assert_eq!(config.db_path, PathBuf::from("/tmp/test.db"));
+        assert_eq!(config.image_filename_format, "%Y%m%d_%H%M%S_%app%.jpg");
+        assert_eq!(config.image_buffer_count, 20);
+        assert_eq!(config.image_buffer_dir, PathBuf::from("/tmp/image_buffer"));
+        assert_eq!(config.pause_mode, PauseMode::MetadataOnly);
+        assert_eq!(config.pid_file, PathBuf::from("/tmp/tracker.pid"));
+        assert!(config.ocr_save_details);
+        assert_eq!(config.ocr_backend, OcrBackendKind::Tesseract);
+        assert_eq!(config.day_boundary_hour, 4);
+        assert!(config.record_input_metrics);
+        assert!(!config.record_app_events);
+        assert_eq!(config.process_snapshot_count, 10);
+        assert_eq!(config.watchdog_threshold, 3);
+        assert_eq!(config.watchdog_cooldown_seconds, 300);
+        assert_eq!(config.error_notify_threshold, 0);
+        assert_eq!(config.pre_capture_hook, None);
+        assert_eq!(config.post_capture_hook.as_deref(), Some("~/bin/send.sh"));
+        assert_eq!(config.metadata_providers, vec![MetadataProviderKind::Git]);
+        assert_eq!(
+            config.metadata_commands,
+            BTreeMap::from([("jira".to_string(), "~/bin/ticket.sh".to_string())])
+        );
+        assert_eq!(config.wal_checkpoint_minutes, 30);
+        assert_eq!(config.wal_checkpoint_max_mb, 0);
+        assert!(config.log_to_file);
+        assert_eq!(config.log_dir, PathBuf::from("/tmp/logs"));
+        assert_eq!(config.log_max_files, 3);
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(
+            config.otlp_endpoint.as_deref(),
+            Some("http://localhost:4318")
+        );
+        assert!(config.media.matches("Plex", None, ""));
+        assert!(!config.media.matches("Safari", None, "YouTube"));
+        assert!(config.meeting.matches("Around", None, ""));
+        assert!(config.meeting.matches("Safari", None, "Google Meet"));
+        assert!(config.non_work.matches("Dock", None, "Dock"));
+        assert!(!config.non_work.matches("loginwindow", None, ""));
+        assert!(config.non_work.matches("Finder", None, ""));
+        assert_eq!(config.focus_min_minutes, 50);
+        assert_eq!(
+            config.distractions.find("Twitter", None, ""),
+            Some("Twitter")
+        );
+        assert_eq!(config.distractions.work_hours(), Some((9, 18)));
+        assert_eq!(config.distraction_warn_minutes, 0);
+        assert_eq!(config.idle_warn_minutes, 180);
+        assert_eq!(config.title_redact_patterns, vec!["(?i)password"]);
+        assert!(config.private_windows.matches("秘密のタブ"));
+        assert!(!config.private_windows.matches("Incognito"));
+        assert_eq!(
+            config.wordcloud_dictionary,
+            Some(PathBuf::from("/tmp/ipadic"))
+        );
+        assert_eq!(config.language, Some(Language::En));
     }
 
     #[test]
@@ -224,6 +1241,211 @@ mod tests {
         assert_eq!(config.jpeg_quality, 90);
     }
 
+    #[test]
+    fn test_obsidian_file_config() {
+        let mut config = Config::default();
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [obsidian]
+            vault_path = "/tmp/vault"
+            daily_note = "Daily/%Y-%m-%d.md"
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        let obsidian = config.obsidian.unwrap();
+        assert_eq!(obsidian.vault_path, PathBuf::from("/tmp/vault"));
+        assert_eq!(obsidian.daily_note, "Daily/%Y-%m-%d.md");
+        assert_eq!(obsidian.section, ObsidianConfig::DEFAULT_SECTION);
+    }
+
+    #[test]
+    fn test_worklog_file_config() {
+        let mut config = Config::default();
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [worklog]
+            service = "linear"
+            api_token = "lin_api_xxx"
+
+            [worklog.mappings]
+            "habit-tracker" = "HT-1"
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        let worklog = config.worklog.clone().unwrap();
+        assert_eq!(worklog.service, WorklogService::Linear);
+        assert_eq!(
+            worklog.ticket_pattern,
+            WorklogConfig::DEFAULT_TICKET_PATTERN
+        );
+        assert_eq!(worklog.mappings["habit-tracker"], "HT-1");
+        assert!(config.validate().is_ok());
+
+        // JiraはサイトのURLとメールアドレスが必要
+        config.worklog = Some(WorklogConfig {
+            service: WorklogService::Jira,
+            ..worklog
+        });
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("worklog.url"));
+        assert!(err.to_string().contains("worklog.email"));
+    }
+
+    #[test]
+    fn test_break_reminder_file_config() {
+        let mut config = Config::default();
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [break_reminder]
+            minutes = 50
+            quiet_start_hour = 22
+            quiet_end_hour = 7
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        let reminder = config.break_reminder.clone().unwrap();
+        assert_eq!(reminder.minutes, 50);
+        assert!(!reminder.per_category);
+        assert!(reminder.is_quiet(23));
+        assert!(reminder.is_quiet(6));
+        assert!(!reminder.is_quiet(7));
+        assert!(!reminder.is_quiet(12));
+
+        config.break_reminder = Some(BreakReminderConfig {
+            quiet_end_hour: Some(24),
+            ..reminder
+        });
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_focus_file_config() {
+        let mut config = Config::default();
+        assert!(!config.focus.is_enabled());
+        assert!(!config.focus.should_pause(None));
+
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [focus]
+            record_only = ["仕事", "Coding"]
+            pause = ["おやすみモード"]
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        assert!(config.focus.is_enabled());
+        assert!(!config.focus.should_pause(Some("仕事")));
+        assert!(!config.focus.should_pause(Some("coding")));
+        assert!(config.focus.should_pause(Some("パーソナル")));
+        assert!(config.focus.should_pause(None));
+
+        let pause_only = FocusConfig {
+            record_only: vec![],
+            pause: vec!["おやすみモード".to_string()],
+        };
+        assert!(pause_only.should_pause(Some("おやすみモード")));
+        assert!(!pause_only.should_pause(Some("仕事")));
+        assert!(!pause_only.should_pause(None));
+    }
+
+    #[test]
+    fn test_categories_file_config() {
+        let mut config = Config::default();
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [categories]
+            "開発" = ["VS Code", "Terminal"]
+            "コミュニケーション" = ["Slack"]
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        assert_eq!(config.categories.category("Terminal", None), "開発");
+        assert_eq!(
+            config.categories.category("Slack", None),
+            "コミュニケーション"
+        );
+    }
+
+    #[test]
+    fn test_goals_file_config() {
+        let mut config = Config::default();
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [goals]
+            "開発" = 180
+            total = 0
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        assert_eq!(
+            config.goals.iter().collect::<Vec<_>>(),
+            vec![("total", 0), ("開発", 180)]
+        );
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Validation { ref field, .. }) if field == "goals"
+        ));
+    }
+
+    #[test]
+    fn test_aliases_file_config() {
+        let mut config = Config::default();
+        let file_config: FileConfig = toml::from_str(
+            r#"
+            [aliases]
+            "com.apple.Safari" = "Safari"
+            "#,
+        )
+        .unwrap();
+        config.merge_file_config(&file_config);
+
+        assert_eq!(
+            config.aliases.resolve("サファリ", Some("com.apple.Safari")),
+            "Safari"
+        );
+    }
+
+    #[test]
+    fn test_ocr_backend_file_config() {
+        let file_config: FileConfig = toml::from_str(r#"ocr_backend = "none""#).unwrap();
+        assert_eq!(file_config.ocr_backend, Some(OcrBackendKind::None));
+
+        assert!(toml::from_str::<FileConfig>(r#"ocr_backend = "easyocr""#).is_err());
+    }
+
+    #[test]
+    fn test_metadata_providers_file_config() {
+        let file_config: FileConfig = toml::from_str(
+            r#"
+metadata_providers = ["browser_url", "git"]
+
+[metadata_commands]
+jira = "~/bin/ticket.sh"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            file_config.metadata_providers,
+            Some(vec![
+                MetadataProviderKind::BrowserUrl,
+                MetadataProviderKind::Git
+            ])
+        );
+
+        assert!(toml::from_str::<FileConfig>(r#"metadata_providers = ["weather"]"#).is_err());
+    }
+
     #[test]
     fn test_validate_interval_zero() {
         let mut config = Config::default();
@@ -231,6 +1453,24 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_adaptive_interval_range() {
+        let config = Config {
+            adaptive_interval: true,
+            min_interval_seconds: 600,
+            max_interval_seconds: 300,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        // アダプティブが無効なら範囲は検査しない
+        let config = Config {
+            adaptive_interval: false,
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_validate_quality_over_100() {
         let mut config = Config::default();
@@ -238,6 +1478,202 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_day_boundary_hour() {
+        let config = Config {
+            day_boundary_hour: 24,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_work_hours() {
+        let config = Config {
+            distractions: Distractions::new(vec![]).with_work_hours(9, 24),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_one_sided_work_hours() {
+        let content = "work_start_hour = 9\n";
+        let file_config: FileConfig = toml::from_str(content).unwrap();
+        let err = with_line_hints(file_config.validate().unwrap_err(), content);
+        assert!(matches!(
+            err,
+            ConfigError::Validation { ref field, ref reason, line: Some(1) }
+                if field == "work_start_hour" && reason.contains("work_end_hour")
+        ));
+
+        let file_config: FileConfig = toml::from_str("work_end_hour = 18").unwrap();
+        assert!(matches!(
+            file_config.validate(),
+            Err(ConfigError::Validation { ref field, .. }) if field == "work_end_hour"
+        ));
+
+        let file_config: FileConfig =
+            toml::from_str("work_start_hour = 9\nwork_end_hour = 18").unwrap();
+        assert!(file_config.validate().is_ok());
+        assert!(FileConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_image_filename_format() {
+        let config = Config {
+            image_filename_format: "/tmp/%H%M%S.jpg".to_string(),
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Validation { ref field, .. } if field == "image_filename_format"
+        ));
+    }
+
+    #[test]
+    fn test_validate_title_redact_patterns() {
+        let config = Config {
+            title_redact_patterns: vec![r"\d{4}-\d{4}".to_string(), "(unclosed".to_string()],
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Validation { ref field, .. } if field == "title_redact_patterns"
+        ));
+    }
+
+    #[test]
+    fn test_validate_reports_all_errors_with_lines() {
+        let config = Config {
+            interval_seconds: 0,
+            jpeg_quality: 101,
+            break_reminder: Some(BreakReminderConfig {
+                minutes: 50,
+                per_category: false,
+                quiet_start_hour: Some(25),
+                quiet_end_hour: None,
+            }),
+            ..Default::default()
+        };
+        let content = "# 設定\ninterval_seconds = 0\njpeg_quality=101\n\n[break_reminder]\nminutes = 50\nquiet_start_hour = 25\n";
+        let err = with_line_hints(config.validate().unwrap_err(), content);
+
+        let ConfigError::ValidationErrors(errors) = err else {
+            panic!("複数のエラーがまとめられていない: {}", err);
+        };
+        let found: Vec<_> = errors
+            .iter()
+            .map(|err| match err {
+                ConfigError::Validation { field, line, .. } => (field.as_str(), *line),
+                err => panic!("想定外のエラー: {}", err),
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("interval_seconds", Some(2)),
+                ("jpeg_quality", Some(3)),
+                ("break_reminder.quiet_start_hour", Some(7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_initial_file_content() {
+        let content = initial_file_content(30, 80);
+        let file_config: FileConfig = toml::from_str(&content).unwrap();
+        let mut config = Config::default();
+        config.merge_file_config(&file_config);
+
+        assert_eq!(config.interval_seconds, 30);
+        assert_eq!(config.jpeg_quality, 80);
+        assert_eq!(config.ocr_backend, OcrBackendKind::Vision);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_paths_from_env() {
+        let home = PathBuf::from("/Users/me");
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        let legacy = home.join(".habit-tracker");
+        let xdg: &'static [_] = &[
+            ("XDG_CONFIG_HOME", "/Users/me/dotfiles/config"),
+            ("XDG_DATA_HOME", "/Users/me/.local/share"),
+        ];
+
+        // 何もなければ従来どおり
+        let paths = Paths::from_env(env(&[]), &home, false);
+        assert_eq!(paths.config_dir, legacy);
+        assert_eq!(paths.data_dir, legacy);
+
+        let paths = Paths::from_env(env(xdg), &home, false);
+        assert_eq!(
+            paths.config_dir,
+            PathBuf::from("/Users/me/dotfiles/config/habit-tracker")
+        );
+        assert_eq!(
+            paths.data_dir,
+            PathBuf::from("/Users/me/.local/share/habit-tracker")
+        );
+
+        // 従来のディレクトリがあれば、移行するまでそちらを使う
+        let paths = Paths::from_env(env(xdg), &home, true);
+        assert_eq!(paths.config_dir, legacy);
+
+        // 一括指定が最優先、相対パスは無視する
+        let paths = Paths::from_env(env(&[("HABIT_TRACKER_HOME", "/tmp/ht")]), &home, true);
+        assert_eq!(paths.config_dir, PathBuf::from("/tmp/ht"));
+        assert_eq!(paths.data_dir, PathBuf::from("/tmp/ht"));
+        let paths = Paths::from_env(env(&[("XDG_DATA_HOME", "share")]), &home, false);
+        assert_eq!(paths.data_dir, legacy);
+
+        // プロファイルは設定もデータも分ける
+        let paths = Paths::from_env(env(xdg), &home, false).for_profile("work");
+        assert_eq!(
+            paths.config_dir,
+            PathBuf::from("/Users/me/dotfiles/config/habit-tracker/profiles/work")
+        );
+        assert_eq!(
+            paths.data_dir,
+            PathBuf::from("/Users/me/.local/share/habit-tracker/profiles/work")
+        );
+    }
+
+    #[test]
+    fn test_init_overrides_rejects_invalid_profile() {
+        for name in ["../work", ""] {
+            let overrides = PathOverrides {
+                profile: Some(name.to_string()),
+                ..Default::default()
+            };
+            assert!(init_overrides(overrides).is_err());
+        }
+    }
+
+    #[test]
+    fn test_path_overrides_to_args() {
+        assert!(PathOverrides::default().to_args().is_empty());
+
+        let overrides = PathOverrides {
+            profile: Some("work".to_string()),
+            config_file: None,
+            db_path: Some(PathBuf::from("/tmp/other.db")),
+        };
+        assert_eq!(
+            overrides.to_args(),
+            vec!["--profile", "work", "--db-path", "/tmp/other.db"]
+        );
+    }
+
     #[test]
     fn test_ensure_directories() {
         let temp_dir = TempDir::new().unwrap();
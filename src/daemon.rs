@@ -0,0 +1,69 @@
+//! デーモンモジュール - トラッカーのバックグラウンド起動
+
+use crate::config::{self, CliArgs, Config};
+use crate::error::{DaemonError, LockError};
+use crate::instance_lock;
+use std::fs::{self, OpenOptions};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// バックグラウンドプロセスの標準出力・標準エラーの出力先ファイル名（`log_dir` 内）
+const DAEMON_LOG_FILE: &str = "daemon.log";
+
+/// 起動直後に異常終了していないか確認するまでの待ち時間
+const STARTUP_CHECK_DELAY: Duration = Duration::from_secs(1);
+
+/// バックグラウンドで起動したトラッカー
+#[derive(Debug)]
+pub struct Daemon {
+    pub pid: u32,
+    /// 標準出力・標準エラーの出力先
+    pub log_path: PathBuf,
+}
+
+/// `tracker start` を端末から切り離したバックグラウンドプロセスとして起動する
+///
+/// 子プロセスは新しいプロセスグループに入れるため、端末のCtrl+Cや
+/// 端末を閉じたときのシグナルは届かない。標準入力は閉じ、
+/// 標準出力・標準エラーは `log_dir/daemon.log` に追記する
+pub fn spawn(config: &Config, cli_args: &CliArgs) -> Result<Daemon, DaemonError> {
+    if let Some(pid) = instance_lock::running_pid(&config.pid_file)? {
+        return Err(LockError::AlreadyRunning(Some(pid)).into());
+    }
+
+    fs::create_dir_all(&config.log_dir)?;
+    let log_path = config.log_dir.join(DAEMON_LOG_FILE);
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command.args(config::overrides().to_args()).arg("start");
+    if let Some(interval) = cli_args.interval {
+        command.arg("--interval").arg(interval.to_string());
+    }
+    if let Some(quality) = cli_args.quality {
+        command.arg("--quality").arg(quality.to_string());
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .process_group(0)
+        .spawn()?;
+
+    // 設定の誤りや多重起動はすぐに終了するので、少し待って確認する
+    thread::sleep(STARTUP_CHECK_DELAY);
+    if let Some(status) = child.try_wait()? {
+        return Err(DaemonError::ExitedEarly { status, log_path });
+    }
+
+    Ok(Daemon {
+        pid: child.id(),
+        log_path,
+    })
+}
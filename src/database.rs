@@ -1,11 +1,48 @@
 //! データベースモジュール
 
 use crate::error::DatabaseError;
-use rusqlite::{params, Connection};
-use std::path::Path;
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, SecondsFormat, TimeZone};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+/// スキーマバージョン（PRAGMA user_version）
+///
+/// - 1: captured_atをオフセット付きRFC3339に移行
+const SCHEMA_VERSION: i32 = 1;
+
+/// ロック競合時の待機時間
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 旧形式（オフセットなしローカル時刻）のタイムスタンプ書式
+const LEGACY_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// OCRテキストの圧縮レベル
+const OCR_COMPRESSION_LEVEL: i32 = 3;
+
+/// 辞書がないときに圧縮するOCRテキストの最小バイト数（短いと縮まない）
+const OCR_COMPRESS_MIN_BYTES: usize = 256;
+
+/// 学習するzstd辞書の最大サイズ
+const OCR_DICTIONARY_SIZE: usize = 16 * 1024;
+
+/// 辞書の学習に使うOCRテキストの最大件数
+const OCR_DICTIONARY_SAMPLES: i64 = 2000;
+
+/// 辞書の学習に必要なOCRテキストの最小件数
+const OCR_DICTIONARY_MIN_SAMPLES: usize = 100;
 
 /// キャプチャレコードDTO
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CaptureRecord {
     pub id: Option<i64>,
     pub captured_at: String,
@@ -15,11 +52,453 @@ pub struct CaptureRecord {
     pub is_paused: bool,
     pub is_private: bool,
     pub ocr_text: Option<String>,
+    /// このキャプチャが代表する時間（秒、`None` なら設定のインターバル）
+    pub interval_seconds: Option<u64>,
+    /// 記録したマシンのホスト名（カラム追加前の記録・インポートでは `None`）
+    pub hostname: Option<String>,
+    /// 記録したトラッカーのバージョン（カラム追加前の記録・インポートでは `None`）
+    pub tracker_version: Option<String>,
+    /// アプリのbundle ID（`com.apple.Safari` など、取得できなければ `None`）
+    pub bundle_id: Option<String>,
+    /// 自動付与した活動の種類（フルスクリーンの動画視聴なら `ACTIVITY_TYPE_MEDIA`）
+    pub activity_type: Option<String>,
+    /// ターミナルのカレントディレクトリ・VS Codeのワークスペースのフォルダ（それ以外・取得できなければ `None`）
+    pub working_directory: Option<String>,
+}
+
+/// フルスクリーンで動画を視聴していたキャプチャの活動の種類
+pub const ACTIVITY_TYPE_MEDIA: &str = "media";
+
+/// キーボード・マウスの活動量（キー内容は含まない）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct InputMetrics {
+    pub key_presses: u64,
+    pub clicks: u64,
+    pub scrolls: u64,
+}
+
+impl InputMetrics {
+    /// 累積カウンタの差分を計算（カウンタがリセットされた場合は0）
+    pub fn delta_since(&self, earlier: &InputMetrics) -> InputMetrics {
+        InputMetrics {
+            key_presses: self.key_presses.saturating_sub(earlier.key_presses),
+            clicks: self.clicks.saturating_sub(earlier.clicks),
+            scrolls: self.scrolls.saturating_sub(earlier.scrolls),
+        }
+    }
+}
+
+/// キャプチャ処理の各段階の所要時間（ミリ秒）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PerfMetrics {
+    /// スクリーンショット取得
+    pub screenshot_ms: u64,
+    /// OCR（既存結果の流用やバックエンド無効で行わなかった場合は `None`）
+    pub ocr_ms: Option<u64>,
+    /// DB書き込み
+    pub db_ms: u64,
+    /// サイクル全体
+    pub total_ms: u64,
+}
+
+/// プロセスのCPU/メモリ使用状況
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub name: String,
+    /// CPU使用率（1コア=100%）
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// アクティブアプリのプロセスか
+    pub is_active_app: bool,
+}
+
+/// アプリ切り替えイベントDTO
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppEvent {
+    pub id: Option<i64>,
+    pub occurred_at: String,
+    pub app_name: String,
+    pub bundle_id: String,
+}
+
+/// メモDTO（ショートカットなどから追加した作業メモ）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Note {
+    pub id: Option<i64>,
+    pub created_at: String,
+    pub text: String,
+}
+
+/// アラートの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// 休憩リマインダー
+    Break,
+    /// 脱線アプリの連続利用の警告
+    Distraction,
+    /// キャプチャの失敗・休止
+    CaptureFailure,
+    /// ポモドーロの作業・休憩の切り替え
+    Pomodoro,
+    /// 同じ種類のエラーの多発
+    RepeatedError,
+    /// 同じウィンドウのまま入力のない状態の継続（放置の疑い）
+    Idle,
+}
+
+impl AlertKind {
+    /// DBに保存する文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::Break => "break",
+            AlertKind::Distraction => "distraction",
+            AlertKind::CaptureFailure => "capture_failure",
+            AlertKind::Pomodoro => "pomodoro",
+            AlertKind::RepeatedError => "repeated_error",
+            AlertKind::Idle => "idle",
+        }
+    }
+
+    /// DBの文字列表現から変換
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "break" => Some(AlertKind::Break),
+            "distraction" => Some(AlertKind::Distraction),
+            "capture_failure" => Some(AlertKind::CaptureFailure),
+            "pomodoro" => Some(AlertKind::Pomodoro),
+            "repeated_error" => Some(AlertKind::RepeatedError),
+            "idle" => Some(AlertKind::Idle),
+            _ => None,
+        }
+    }
+}
+
+/// アラートDTO（通知したイベントの履歴）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub id: Option<i64>,
+    pub occurred_at: String,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+/// 目標の日別の達成状況DTO
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoalResult {
+    pub date: String,
+    pub goal: String,
+    /// 目標の対象にした作業時間（秒）
+    pub seconds: u64,
+    pub achieved: bool,
+}
+
+/// OCRジョブの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OcrJobStatus {
+    /// 失敗し、次回の試行を待っている
+    Retrying,
+    /// 試行回数の上限に達し、再試行を打ち切った
+    Failed,
+    /// 成功した
+    Done,
+}
+
+impl OcrJobStatus {
+    /// DBに保存する文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OcrJobStatus::Retrying => "retrying",
+            OcrJobStatus::Failed => "failed",
+            OcrJobStatus::Done => "done",
+        }
+    }
+
+    /// DBの文字列表現から変換
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "retrying" => Some(OcrJobStatus::Retrying),
+            "failed" => Some(OcrJobStatus::Failed),
+            "done" => Some(OcrJobStatus::Done),
+            _ => None,
+        }
+    }
+}
+
+/// OCRジョブDTO（キャプチャ1件ごとの試行状況）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OcrJob {
+    pub capture_id: i64,
+    pub captured_at: String,
+    pub image_path: Option<String>,
+    pub status: OcrJobStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    /// 次回の試行予定時刻（再試行待ちのときのみ）
+    pub next_attempt_at: Option<String>,
+}
+
+/// `tracker ocr --all` の進捗（中断しても次回は `last_capture_id` の次から再開する）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OcrBatchProgress {
+    /// 処理を終えたキャプチャのID（これ以下は処理済みか再試行待ち）
+    pub last_capture_id: i64,
+    /// これまでに処理した件数
+    pub processed: u64,
+    /// 最初に開始した日時
+    pub started_at: String,
+}
+
+/// セッションの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKind {
+    /// システムのスリープ区間
+    Sleep,
+    /// トラッカー自体の稼働区間
+    Tracker,
+}
+
+impl SessionKind {
+    /// DBに保存する文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionKind::Sleep => "sleep",
+            SessionKind::Tracker => "tracker",
+        }
+    }
+
+    /// DBの文字列表現から変換
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sleep" => Some(SessionKind::Sleep),
+            "tracker" => Some(SessionKind::Tracker),
+            _ => None,
+        }
+    }
+}
+
+/// トラッカーセッションの終了理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndReason {
+    /// シグナル等による通常終了
+    Stopped,
+    /// 終了が記録されないまま次の起動を迎えた（クラッシュ・強制終了）
+    Crashed,
+}
+
+impl EndReason {
+    /// DBに保存する文字列表現
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndReason::Stopped => "stopped",
+            EndReason::Crashed => "crashed",
+        }
+    }
+
+    /// DBの文字列表現から変換
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "stopped" => Some(EndReason::Stopped),
+            "crashed" => Some(EndReason::Crashed),
+            _ => None,
+        }
+    }
+}
+
+/// セッションレコードDTO（開始・終了時刻はキャプチャと同じ形式）
+///
+/// `version` `hostname` `end_reason` はトラッカーセッションでのみ使う
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionRecord {
+    pub id: Option<i64>,
+    pub kind: SessionKind,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub version: Option<String>,
+    pub hostname: Option<String>,
+    pub end_reason: Option<EndReason>,
+}
+
+/// 保存した画像のファイルサイズと解像度（解像度は読み取れなければ `None`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImageInfo {
+    pub bytes: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// 画像のあるキャプチャのサイズ（サイズ記録前のキャプチャでは `bytes` が `None`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredImage {
+    pub captured_at: String,
+    pub image_path: String,
+    pub bytes: Option<u64>,
+}
+
+/// 時間帯の代表画像（highlightsテーブル）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Highlight {
+    /// 時間帯（YYYY-MM-DDTHH、壁時計時刻）
+    pub hour: String,
+    pub capture_id: i64,
+    pub captured_at: String,
+    pub image_path: String,
+    pub active_app: String,
+    pub window_title: String,
+    /// 選んだときの情報量（OCRテキストの空白以外の文字数）
+    pub score: u64,
+    #[serde(skip)]
+    pub image_hash: Option<String>,
+}
+
+/// 整合性チェック（`PRAGMA integrity_check` / `foreign_key_check`）の結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IntegrityReport {
+    /// integrity_checkが見つけた問題（問題がなければ空）
+    pub integrity_errors: Vec<String>,
+    /// 参照先のない外部キー（`テーブル rowid=... -> 参照先テーブル`）
+    pub foreign_key_errors: Vec<String>,
+}
+
+impl IntegrityReport {
+    /// 問題が見つからなかったか
+    pub fn is_ok(&self) -> bool {
+        self.integrity_errors.is_empty() && self.foreign_key_errors.is_empty()
+    }
+}
+
+/// 代表画像の候補（画像のあるプライベートでないキャプチャ）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightCandidate {
+    pub capture_id: i64,
+    pub captured_at: String,
+    pub image_hash: Option<String>,
+    pub ocr_text: Option<String>,
+}
+
+/// 日付ごとのキャプチャ件数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DateCount {
+    /// 記録時点のローカル日付（YYYY-MM-DD）
+    pub date: String,
+    pub count: u64,
 }
 
+/// ページ単位で取得したキャプチャ（新しい順）
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturePage {
+    pub captures: Vec<CaptureRecord>,
+    /// 全キャプチャの件数
+    pub total: u64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl CapturePage {
+    /// このページより後（古い側）にキャプチャが残っているか
+    pub fn has_more(&self) -> bool {
+        (self.offset.max(0) as u64).saturating_add(self.captures.len() as u64) < self.total
+    }
+}
+
+/// OCRテキストの再圧縮結果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    pub rows: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// OCRの試行回数の上限（これに達すると再試行を打ち切る）
+pub const OCR_MAX_ATTEMPTS: u32 = 5;
+
+/// OCR再試行の初回待ち時間（試行のたびに倍になる）
+const OCR_RETRY_BASE_MINUTES: i64 = 5;
+
+/// 代表画像の取得SQL（`map_highlight_row` のカラム順）
+const SELECT_HIGHLIGHT_SQL: &str = r#"
+    SELECT h.hour, h.capture_id, c.captured_at, c.image_path, c.active_app, c.window_title, h.score, c.image_hash
+    FROM highlights h
+    JOIN captures c ON c.id = h.capture_id
+"#;
+
+/// キャプチャ挿入SQL
+const INSERT_CAPTURE_SQL: &str = r#"
+    INSERT INTO captures (captured_at, image_path, active_app, window_title, is_paused, is_private, ocr_text, interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+"#;
+
 /// データベース管理
 pub struct Database {
     conn: Connection,
+    /// OCRテキストの圧縮辞書（SQL関数 `decompress_text` と共有）
+    codec: Arc<RwLock<TextCodec>>,
+}
+
+/// OCRテキストの圧縮・展開（zstd辞書圧縮）
+///
+/// ocr_textカラムには平文（TEXT）と圧縮済み（BLOB）が混在する。zstdのフレームは
+/// 使用した辞書のIDを含むため、辞書を学習し直しても古い行はそのまま展開できる。
+/// SQLからは `decompress_text(ocr_text)` で平文として読めるので、OCRテキストの検索は
+/// 展開した値への `LIKE` で行う（索引は使えず、全行を展開して照合する）
+#[derive(Default)]
+struct TextCodec {
+    /// 新しく圧縮するときに使う辞書（最後に学習したもの）
+    encoder: Option<EncoderDictionary<'static>>,
+    /// 辞書IDごとの展開用辞書
+    decoders: HashMap<u32, DecoderDictionary<'static>>,
+}
+
+impl TextCodec {
+    /// 辞書を登録し、以降の圧縮に使う
+    fn add_dictionary(&mut self, dict_id: u32, data: &[u8]) {
+        self.encoder = Some(EncoderDictionary::copy(data, OCR_COMPRESSION_LEVEL));
+        self.decoders.insert(dict_id, DecoderDictionary::copy(data));
+    }
+
+    /// テキストを保存用の値に変換（縮まない場合は平文のまま）
+    fn encode(&self, text: &str) -> io::Result<Value> {
+        let compressed = match self.encoder {
+            Some(ref dict) => {
+                zstd::bulk::Compressor::with_prepared_dictionary(dict)?.compress(text.as_bytes())?
+            }
+            None if text.len() >= OCR_COMPRESS_MIN_BYTES => {
+                zstd::bulk::compress(text.as_bytes(), OCR_COMPRESSION_LEVEL)?
+            }
+            None => return Ok(Value::Text(text.to_string())),
+        };
+
+        if compressed.len() < text.len() {
+            Ok(Value::Blob(compressed))
+        } else {
+            Ok(Value::Text(text.to_string()))
+        }
+    }
+
+    /// 圧縮済みのBLOBを展開
+    fn decode(&self, blob: &[u8]) -> io::Result<String> {
+        let mut text = String::new();
+        match zstd::zstd_safe::get_dict_id_from_frame(blob) {
+            Some(dict_id) => {
+                let dict = self.decoders.get(&dict_id.get()).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("zstd辞書が見つかりません: {}", dict_id),
+                    )
+                })?;
+                zstd::stream::read::Decoder::with_prepared_dictionary(blob, dict)?
+                    .read_to_string(&mut text)?;
+            }
+            None => {
+                zstd::stream::read::Decoder::new(blob)?.read_to_string(&mut text)?;
+            }
+        }
+        Ok(text)
+    }
 }
 
 impl Database {
@@ -29,13 +508,153 @@ impl Database {
 
         // WALモードを有効化
         conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        // 別スレッド・別プロセスの書き込みと競合した場合は待機する
+        conn.busy_timeout(BUSY_TIMEOUT)?;
 
-        let db = Database { conn };
+        let db = Database {
+            conn,
+            codec: Arc::default(),
+        };
         db.initialize_schema()?;
+        db.load_ocr_dictionaries()?;
+        db.register_functions()?;
 
         Ok(db)
     }
 
+    /// SQL関数を登録
+    ///
+    /// `decompress_text(x)`: 圧縮済みのOCRテキストを展開する（平文・NULLはそのまま）
+    fn register_functions(&self) -> Result<(), DatabaseError> {
+        let codec = Arc::clone(&self.codec);
+        self.conn.create_scalar_function(
+            "decompress_text",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            move |ctx| match ctx.get_raw(0) {
+                ValueRef::Blob(blob) => codec
+                    .read()
+                    .expect("圧縮辞書のロック取得失敗")
+                    .decode(blob)
+                    .map(Value::Text)
+                    .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e))),
+                value => Ok(Value::from(value)),
+            },
+        )?;
+        Ok(())
+    }
+
+    /// 保存済みのzstd辞書を読み込む
+    fn load_ocr_dictionaries(&self) -> Result<(), DatabaseError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT dict_id, data FROM ocr_dictionaries ORDER BY created_at")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, u32>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })?;
+
+        let mut codec = self.codec.write().expect("圧縮辞書のロック取得失敗");
+        for row in rows {
+            let (dict_id, data) = row?;
+            codec.add_dictionary(dict_id, &data);
+        }
+        Ok(())
+    }
+
+    /// OCRテキストを保存用の値に変換
+    fn encode_ocr_text(&self, text: Option<&str>) -> Result<Option<Value>, DatabaseError> {
+        let Some(text) = text else {
+            return Ok(None);
+        };
+        let value = self
+            .codec
+            .read()
+            .expect("圧縮辞書のロック取得失敗")
+            .encode(text)?;
+        Ok(Some(value))
+    }
+
+    /// 既存のOCRテキストからzstd辞書を学習し、以降の圧縮に使う
+    ///
+    /// サンプルが足りない場合は学習せず `None` を返す
+    pub fn train_ocr_dictionary(&self) -> Result<Option<u32>, DatabaseError> {
+        let samples: Vec<String> = {
+            let mut stmt = self.conn.prepare(
+                r#"
+                SELECT decompress_text(ocr_text) FROM captures
+                WHERE ocr_text IS NOT NULL AND is_private = 0
+                ORDER BY id DESC
+                LIMIT ?1
+                "#,
+            )?;
+            let rows = stmt.query_map(params![OCR_DICTIONARY_SAMPLES], |row| row.get(0))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        if samples.len() < OCR_DICTIONARY_MIN_SAMPLES {
+            return Ok(None);
+        }
+
+        let data = zstd::dict::from_samples(&samples, OCR_DICTIONARY_SIZE)?;
+        let dict_id = zstd::zstd_safe::get_dict_id_from_dict(&data)
+            .map(|id| id.get())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "辞書IDがありません"))?;
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO ocr_dictionaries (dict_id, data, created_at) VALUES (?1, ?2, ?3)",
+            params![dict_id, data, format_timestamp(&Local::now())],
+        )?;
+        self.codec
+            .write()
+            .expect("圧縮辞書のロック取得失敗")
+            .add_dictionary(dict_id, &data);
+
+        Ok(Some(dict_id))
+    }
+
+    /// すべてのOCRテキストを現在の辞書で圧縮し直す
+    ///
+    /// 平文で保存されていた行や古い辞書で圧縮された行も対象。
+    /// 解放された領域をファイルから取り除くには別途VACUUMが必要
+    pub fn recompress_ocr_text(&self) -> Result<CompressionStats, DatabaseError> {
+        self.with_transaction(|db| {
+            let rows: Vec<(i64, String, u64)> = {
+                let mut stmt = db.conn.prepare(
+                    r#"
+                    SELECT id, decompress_text(ocr_text), length(CAST(ocr_text AS BLOB))
+                    FROM captures
+                    WHERE ocr_text IS NOT NULL
+                    "#,
+                )?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+                rows.collect::<Result<_, _>>()?
+            };
+
+            let mut stats = CompressionStats::default();
+            let mut stmt = db
+                .conn
+                .prepare_cached("UPDATE captures SET ocr_text = ?1 WHERE id = ?2")?;
+            for (id, text, size) in rows {
+                let value = db.encode_ocr_text(Some(&text))?;
+                stats.bytes_after += match value {
+                    Some(Value::Blob(ref blob)) => blob.len() as u64,
+                    _ => text.len() as u64,
+                };
+                stats.bytes_before += size;
+                stats.rows += 1;
+                stmt.execute(params![value, id])?;
+            }
+            Ok(stats)
+        })
+    }
+
+    /// 未使用領域を解放してファイルサイズを縮め、WALをチェックポイントする
+    ///
+    /// WALを書き戻せなかったページがあれば `false`（`checkpoint` を参照）
+    pub fn vacuum(&self) -> Result<bool, DatabaseError> {
+        self.conn.execute_batch("VACUUM")?;
+        self.checkpoint()
+    }
+
     /// スキーマを初期化
     fn initialize_schema(&self) -> Result<(), DatabaseError> {
         self.conn.execute_batch(
@@ -48,11 +667,145 @@ impl Database {
                 window_title TEXT NOT NULL DEFAULT '',
                 is_paused INTEGER NOT NULL DEFAULT 0,
                 is_private INTEGER NOT NULL DEFAULT 0,
-                ocr_text TEXT
+                ocr_text TEXT,
+                interval_seconds INTEGER,
+                image_hash TEXT,
+                hostname TEXT,
+                tracker_version TEXT,
+                bundle_id TEXT,
+                image_bytes INTEGER,
+                image_width INTEGER,
+                image_height INTEGER,
+                activity_type TEXT,
+                working_directory TEXT,
+                extra TEXT
             );
 
             CREATE INDEX IF NOT EXISTS idx_captures_captured_at
             ON captures(captured_at);
+
+            CREATE TABLE IF NOT EXISTS input_metrics (
+                capture_id INTEGER PRIMARY KEY REFERENCES captures(id) ON DELETE CASCADE,
+                key_presses INTEGER NOT NULL DEFAULT 0,
+                clicks INTEGER NOT NULL DEFAULT 0,
+                scrolls INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS perf_metrics (
+                capture_id INTEGER PRIMARY KEY REFERENCES captures(id) ON DELETE CASCADE,
+                screenshot_ms INTEGER NOT NULL,
+                ocr_ms INTEGER,
+                db_ms INTEGER NOT NULL,
+                total_ms INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS process_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                capture_id INTEGER NOT NULL REFERENCES captures(id) ON DELETE CASCADE,
+                pid INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                cpu_percent REAL NOT NULL,
+                memory_bytes INTEGER NOT NULL,
+                is_active_app INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_process_snapshots_capture_id
+            ON process_snapshots(capture_id);
+
+            CREATE TABLE IF NOT EXISTS sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                version TEXT,
+                hostname TEXT,
+                end_reason TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_sessions_started_at
+            ON sessions(started_at);
+
+            CREATE TABLE IF NOT EXISTS app_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                occurred_at TEXT NOT NULL,
+                app_name TEXT NOT NULL,
+                bundle_id TEXT NOT NULL DEFAULT ''
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_app_events_occurred_at
+            ON app_events(occurred_at);
+
+            CREATE TABLE IF NOT EXISTS ocr_dictionaries (
+                dict_id INTEGER PRIMARY KEY,
+                data BLOB NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS ocr_jobs (
+                capture_id INTEGER PRIMARY KEY REFERENCES captures(id) ON DELETE CASCADE,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                next_attempt_at TEXT,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS ocr_boilerplate (
+                line TEXT PRIMARY KEY,
+                learned_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS highlights (
+                hour TEXT PRIMARY KEY,
+                capture_id INTEGER NOT NULL REFERENCES captures(id) ON DELETE CASCADE,
+                score INTEGER NOT NULL,
+                selected_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS notes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at TEXT NOT NULL,
+                text TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS alerts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                occurred_at TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                message TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_alerts_occurred_at
+            ON alerts(occurred_at);
+
+            CREATE TABLE IF NOT EXISTS pomodoros (
+                completed_at TEXT PRIMARY KEY,
+                work_minutes INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS goal_results (
+                date TEXT NOT NULL,
+                goal TEXT NOT NULL,
+                seconds INTEGER NOT NULL,
+                achieved INTEGER NOT NULL,
+                PRIMARY KEY (date, goal)
+            );
+
+            CREATE TABLE IF NOT EXISTS worklog_pushes (
+                date TEXT NOT NULL,
+                ticket TEXT NOT NULL,
+                seconds INTEGER NOT NULL,
+                pushed_at TEXT NOT NULL,
+                PRIMARY KEY (date, ticket)
+            );
+
+            CREATE TABLE IF NOT EXISTS ocr_batch_progress (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                last_capture_id INTEGER NOT NULL,
+                processed INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
             "#,
         )?;
 
@@ -61,63 +814,340 @@ impl Database {
             .conn
             .execute("ALTER TABLE captures ADD COLUMN ocr_text TEXT", []);
 
+        // マイグレーション: ocr_detailsカラムを追加（信頼度・座標のJSON）
+        let _ = self
+            .conn
+            .execute("ALTER TABLE captures ADD COLUMN ocr_details TEXT", []);
+
+        // マイグレーション: interval_secondsカラムを追加（可変インターバル）
+        let _ = self.conn.execute(
+            "ALTER TABLE captures ADD COLUMN interval_seconds INTEGER",
+            [],
+        );
+
+        // マイグレーション: image_hashカラムを追加（OCR結果の流用）
+        let _ = self
+            .conn
+            .execute("ALTER TABLE captures ADD COLUMN image_hash TEXT", []);
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_captures_image_hash ON captures(image_hash);",
+        )?;
+
+        // アプリ別の取得用
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_captures_active_app ON captures(active_app, captured_at);",
+        )?;
+
+        // マイグレーション: capturesに記録環境のカラムを追加（複数マシン・バージョンの切り分け）
+        for column in ["hostname", "tracker_version"] {
+            let _ = self.conn.execute(
+                &format!("ALTER TABLE captures ADD COLUMN {} TEXT", column),
+                [],
+            );
+        }
+
+        // マイグレーション: bundle_idカラムを追加（表示名が変わってもアプリを識別する）
+        let _ = self
+            .conn
+            .execute("ALTER TABLE captures ADD COLUMN bundle_id TEXT", []);
+
+        // マイグレーション: 画像のファイルサイズ・解像度のカラムを追加（容量の推移の集計）
+        for column in ["image_bytes", "image_width", "image_height"] {
+            let _ = self.conn.execute(
+                &format!("ALTER TABLE captures ADD COLUMN {} INTEGER", column),
+                [],
+            );
+        }
+
+        // マイグレーション: activity_typeカラムを追加（動画視聴などの自動タグ）
+        let _ = self
+            .conn
+            .execute("ALTER TABLE captures ADD COLUMN activity_type TEXT", []);
+
+        // マイグレーション: working_directoryカラムを追加（ターミナルのプロジェクト判定）
+        let _ = self
+            .conn
+            .execute("ALTER TABLE captures ADD COLUMN working_directory TEXT", []);
+
+        // マイグレーション: extraカラムを追加（メタデータプロバイダが集めたkey-valueのJSON）
+        let _ = self
+            .conn
+            .execute("ALTER TABLE captures ADD COLUMN extra TEXT", []);
+
+        // マイグレーション: sessionsにトラッカーセッション用のカラムを追加
+        for column in ["version", "hostname", "end_reason"] {
+            let _ = self.conn.execute(
+                &format!("ALTER TABLE sessions ADD COLUMN {} TEXT", column),
+                [],
+            );
+        }
+
+        self.migrate()?;
+
+        Ok(())
+    }
+
+    /// バージョン管理されたデータマイグレーションを実行
+    fn migrate(&self) -> Result<(), DatabaseError> {
+        let version: i32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        if version > SCHEMA_VERSION {
+            return Err(DatabaseError::MigrationError(format!(
+                "DBのスキーマバージョン({})がこのバージョンの対応範囲({})より新しいです",
+                version, SCHEMA_VERSION
+            )));
+        }
+
+        if version < 1 {
+            let migrated = self.with_transaction(|db| db.migrate_timestamps_to_rfc3339())?;
+            if migrated > 0 {
+                info!("{}件のタイムスタンプをRFC3339形式に移行しました", migrated);
+            }
+        }
+
+        self.conn
+            .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
         Ok(())
     }
 
+    /// オフセットなしのcaptured_atを現在のタイムゾーンのRFC3339に変換
+    fn migrate_timestamps_to_rfc3339(&self) -> Result<usize, DatabaseError> {
+        let legacy: Vec<(i64, String)> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id, captured_at FROM captures WHERE length(captured_at) = 19")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        let mut migrated = 0;
+        for (id, captured_at) in legacy {
+            match parse_timestamp(&captured_at) {
+                Some(timestamp) => {
+                    self.conn.execute(
+                        "UPDATE captures SET captured_at = ?1 WHERE id = ?2",
+                        params![timestamp.to_rfc3339_opts(SecondsFormat::Secs, false), id],
+                    )?;
+                    migrated += 1;
+                }
+                None => warn!(
+                    "タイムスタンプを変換できません (id={}): {}",
+                    id, captured_at
+                ),
+            }
+        }
+
+        Ok(migrated)
+    }
+
     /// キャプチャレコードを挿入
     pub fn insert_capture(&self, record: &CaptureRecord) -> Result<i64, DatabaseError> {
-        self.conn.execute(
-            r#"
-            INSERT INTO captures (captured_at, image_path, active_app, window_title, is_paused, is_private, ocr_text)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-            "#,
-            params![
-                record.captured_at,
-                record.image_path,
-                record.active_app,
-                record.window_title,
-                record.is_paused as i32,
-                record.is_private as i32,
-                record.ocr_text,
-            ],
-        )?;
+        let mut stmt = self.conn.prepare_cached(INSERT_CAPTURE_SQL)?;
+        stmt.execute(params![
+            record.captured_at,
+            record.image_path,
+            record.active_app,
+            record.window_title,
+            record.is_paused as i32,
+            record.is_private as i32,
+            self.encode_ocr_text(record.ocr_text.as_deref())?,
+            record.interval_seconds,
+            record.hostname,
+            record.tracker_version,
+            record.bundle_id,
+            record.activity_type,
+            record.working_directory,
+        ])?;
 
         Ok(self.conn.last_insert_rowid())
     }
 
+    /// 複数のキャプチャレコードを1トランザクションで挿入
+    ///
+    /// 途中で失敗した場合はすべてロールバックされる。挿入したIDを順に返す
+    pub fn insert_captures(&self, records: &[CaptureRecord]) -> Result<Vec<i64>, DatabaseError> {
+        self.with_transaction(|db| {
+            records
+                .iter()
+                .map(|record| db.insert_capture(record))
+                .collect()
+        })
+    }
+
+    /// インポートしたキャプチャレコードを1トランザクションで挿入
+    ///
+    /// 同じ `captured_at` と `active_app` のキャプチャがすでにあれば挿入しない
+    /// （同じファイルを再度インポートしても重複しない）。挿入したIDを順に返す
+    pub fn import_captures(&self, records: &[CaptureRecord]) -> Result<Vec<i64>, DatabaseError> {
+        self.with_transaction(|db| {
+            let mut ids = Vec::new();
+            for record in records {
+                let exists: bool = db.conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM captures WHERE active_app = ?1 AND captured_at = ?2)",
+                    params![record.active_app, record.captured_at],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    ids.push(db.insert_capture(record)?);
+                }
+            }
+            Ok(ids)
+        })
+    }
+
+    /// クロージャをトランザクション内で実行
+    ///
+    /// クロージャが `Ok` を返せばコミット、`Err` を返せばロールバックする。
+    /// SAVEPOINTで実装しているため、入れ子で呼び出しても内側の失敗は
+    /// 内側の変更だけを取り消す
+    pub fn with_transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Database) -> Result<T, E>,
+        E: From<DatabaseError>,
+    {
+        self.conn
+            .execute_batch("SAVEPOINT tracker_tx")
+            .map_err(DatabaseError::from)?;
+
+        let result = f(self).and_then(|value| {
+            self.conn
+                .execute_batch("RELEASE tracker_tx")
+                .map_err(DatabaseError::from)?;
+            Ok(value)
+        });
+        if result.is_err() {
+            // RELEASEの失敗（遅延制約の違反など）でもSAVEPOINTを残さない。
+            // ロールバック自体の失敗よりも元のエラーを優先して返す
+            let _ = self
+                .conn
+                .execute_batch("ROLLBACK TO tracker_tx; RELEASE tracker_tx");
+        }
+        result
+    }
+
     /// OCRテキストを更新
     pub fn update_ocr_text(&self, id: i64, ocr_text: &str) -> Result<(), DatabaseError> {
         self.conn.execute(
             "UPDATE captures SET ocr_text = ?1 WHERE id = ?2",
-            params![ocr_text, id],
+            params![self.encode_ocr_text(Some(ocr_text))?, id],
         )?;
         Ok(())
     }
 
-    /// OCRテキストが未設定のキャプチャを取得
-    pub fn get_captures_without_ocr(&self, limit: i64) -> Result<Vec<CaptureRecord>, DatabaseError> {
+    /// OCR詳細（信頼度・座標のJSON）を更新
+    pub fn update_ocr_details(&self, id: i64, details_json: &str) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE captures SET ocr_details = ?1 WHERE id = ?2",
+            params![details_json, id],
+        )?;
+        Ok(())
+    }
+
+    /// OCR詳細（信頼度・座標のJSON）を取得
+    pub fn get_ocr_details(&self, id: i64) -> Result<Option<String>, DatabaseError> {
+        let details = self
+            .conn
+            .query_row(
+                "SELECT ocr_details FROM captures WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(details.flatten())
+    }
+
+    /// メタデータプロバイダが集めた追加のkey-value（JSONオブジェクト）を保存
+    pub fn update_extra(&self, id: i64, extra_json: &str) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE captures SET extra = ?1 WHERE id = ?2",
+            params![extra_json, id],
+        )?;
+        Ok(())
+    }
+
+    /// 追加のkey-value（JSONオブジェクト）を取得
+    pub fn get_extra(&self, id: i64) -> Result<Option<String>, DatabaseError> {
+        let extra = self
+            .conn
+            .query_row(
+                "SELECT extra FROM captures WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(extra.flatten())
+    }
+
+    /// 追加のkey-valueの1つの値を設定（他のキーはそのまま残す）
+    pub fn set_extra_value(
+        &self,
+        id: i64,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE captures SET extra = json_set(COALESCE(extra, '{}'), ?1, json(?2)) WHERE id = ?3",
+            params![extra_path(key), value.to_string(), id],
+        )?;
+        Ok(())
+    }
+
+    /// 追加のkey-valueの1つの値を取得（キーがなければ `None`）
+    pub fn get_extra_value(
+        &self,
+        id: i64,
+        key: &str,
+    ) -> Result<Option<serde_json::Value>, DatabaseError> {
+        // `->` はJSONの文字列のまま返すので、型を保ったまま読み戻せる
+        let value: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT extra -> ?1 FROM captures WHERE id = ?2",
+                params![extra_path(key), id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        value
+            .flatten()
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(DatabaseError::from)
+    }
+
+    /// 追加のkey-valueの1つの値を削除
+    pub fn remove_extra_value(&self, id: i64, key: &str) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE captures SET extra = json_remove(extra, ?1) WHERE id = ?2 AND extra IS NOT NULL",
+            params![extra_path(key), id],
+        )?;
+        Ok(())
+    }
+
+    /// 期間 `[start, end)` で追加のkey-valueの値が一致するキャプチャを古い順に取得
+    pub fn find_captures_by_extra(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, ocr_text
+            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
             FROM captures
-            WHERE ocr_text IS NULL AND image_path IS NOT NULL
-            ORDER BY captured_at DESC
-            LIMIT ?1
+            WHERE captured_at >= ?1 AND captured_at < ?2
+              AND extra IS NOT NULL
+              AND json_extract(extra, ?3) = json_extract(?4, '$')
+            ORDER BY captured_at ASC
             "#,
         )?;
 
-        let rows = stmt.query_map(params![limit], |row| {
-            Ok(CaptureRecord {
-                id: Some(row.get(0)?),
-                captured_at: row.get(1)?,
-                image_path: row.get(2)?,
-                active_app: row.get(3)?,
-                window_title: row.get(4)?,
-                is_paused: row.get::<_, i32>(5)? != 0,
-                is_private: row.get::<_, i32>(6)? != 0,
-                ocr_text: row.get(7)?,
-            })
-        })?;
+        let rows = stmt.query_map(
+            params![start, end, extra_path(key), value.to_string()],
+            map_capture_row,
+        )?;
 
         let mut records = Vec::new();
         for row in rows {
@@ -127,192 +1157,2866 @@ impl Database {
         Ok(records)
     }
 
-    /// 日付でキャプチャを取得
-    pub fn get_captures_by_date(&self, date: &str) -> Result<Vec<CaptureRecord>, DatabaseError> {
-        let pattern = format!("{}%", date);
+    /// 期間 `[start, end)` の追加のkey-valueの値ごとのキャプチャ数を多い順に取得
+    ///
+    /// 値はJSONの文字列（`"main"`・`42` など）で返す。キーがないキャプチャは含めない
+    pub fn count_extra_values(
+        &self,
+        key: &str,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<(String, i64)>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT extra -> ?3 AS value, COUNT(*) AS count
+            FROM captures
+            WHERE captured_at >= ?1 AND captured_at < ?2
+              AND json_type(extra, ?3) IS NOT NULL
+            GROUP BY value
+            ORDER BY count DESC, value ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end, extra_path(key)], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+
+        let mut counts = Vec::new();
+        for row in rows {
+            counts.push(row?);
+        }
+
+        Ok(counts)
+    }
 
+    /// キャプチャ画像のハッシュを保存
+    pub fn update_image_hash(&self, id: i64, image_hash: &str) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE captures SET image_hash = ?1 WHERE id = ?2",
+            params![image_hash, id],
+        )?;
+        Ok(())
+    }
+
+    /// キャプチャ画像のファイルサイズと解像度を保存
+    pub fn update_image_info(&self, id: i64, info: &ImageInfo) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE captures SET image_bytes = ?1, image_width = ?2, image_height = ?3 WHERE id = ?4",
+            params![info.bytes as i64, info.width, info.height, id],
+        )?;
+        Ok(())
+    }
+
+    /// 期間 `[start, end)` の画像のあるキャプチャのサイズを取得（古い順）
+    ///
+    /// 境界の比較は `get_captures_between` と同じ
+    pub fn get_stored_images_between(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<StoredImage>, DatabaseError> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, ocr_text
+            SELECT captured_at, image_path, image_bytes
             FROM captures
-            WHERE captured_at LIKE ?1
+            WHERE captured_at >= ?1 AND captured_at < ?2 AND image_path IS NOT NULL
+            ORDER BY captured_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(StoredImage {
+                captured_at: row.get(0)?,
+                image_path: row.get(1)?,
+                bytes: row.get::<_, Option<i64>>(2)?.map(|bytes| bytes as u64),
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 期間 `[start, end)` の代表画像の候補を取得（古い順）
+    ///
+    /// 画像のないキャプチャとプライベートなキャプチャは含まない
+    pub fn get_highlight_candidates(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<HighlightCandidate>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, captured_at, image_hash, decompress_text(ocr_text)
+            FROM captures
+            WHERE captured_at >= ?1 AND captured_at < ?2
+              AND image_path IS NOT NULL AND is_private = 0
             ORDER BY captured_at ASC
             "#,
         )?;
 
-        let rows = stmt.query_map(params![pattern], |row| {
-            Ok(CaptureRecord {
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(HighlightCandidate {
+                capture_id: row.get(0)?,
+                captured_at: row.get(1)?,
+                image_hash: row.get(2)?,
+                ocr_text: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 時間帯の代表画像を記録（選び直した場合は置き換える）
+    pub fn set_highlight(
+        &self,
+        hour: &str,
+        capture_id: i64,
+        score: u64,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO highlights (hour, capture_id, score, selected_at) VALUES (?1, ?2, ?3, ?4)",
+            params![hour, capture_id, score as i64, format_timestamp(&Local::now())],
+        )?;
+        Ok(())
+    }
+
+    /// 時間帯の代表画像の記録を削除（候補がなくなった場合）
+    pub fn delete_highlight(&self, hour: &str) -> Result<(), DatabaseError> {
+        self.conn
+            .execute("DELETE FROM highlights WHERE hour = ?1", params![hour])?;
+        Ok(())
+    }
+
+    /// 時間帯の代表画像を取得
+    pub fn get_highlight(&self, hour: &str) -> Result<Option<Highlight>, DatabaseError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} WHERE h.hour = ?1", SELECT_HIGHLIGHT_SQL))?;
+        Ok(stmt
+            .query_row(params![hour], map_highlight_row)
+            .optional()?)
+    }
+
+    /// 期間 `[start, end)` にキャプチャした代表画像を取得（古い順）
+    ///
+    /// 境界の比較は `get_captures_between` と同じ
+    pub fn get_highlights_between(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<Highlight>, DatabaseError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE c.captured_at >= ?1 AND c.captured_at < ?2 ORDER BY h.hour ASC",
+            SELECT_HIGHLIGHT_SQL
+        ))?;
+        let rows = stmt.query_map(params![start, end], map_highlight_row)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// メモを挿入
+    pub fn insert_note(&self, note: &Note) -> Result<i64, DatabaseError> {
+        self.conn.execute(
+            "INSERT INTO notes (created_at, text) VALUES (?1, ?2)",
+            params![note.created_at, note.text],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 指定範囲 `[start, end)` のメモを取得（時刻順）
+    pub fn get_notes_between(&self, start: &str, end: &str) -> Result<Vec<Note>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, created_at, text
+            FROM notes
+            WHERE created_at >= ?1 AND created_at < ?2
+            ORDER BY created_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(Note {
                 id: Some(row.get(0)?),
+                created_at: row.get(1)?,
+                text: row.get(2)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// アラートを記録
+    pub fn insert_alert(&self, alert: &Alert) -> Result<i64, DatabaseError> {
+        self.conn.execute(
+            "INSERT INTO alerts (occurred_at, kind, message) VALUES (?1, ?2, ?3)",
+            params![alert.occurred_at, alert.kind.as_str(), alert.message],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 直近のアラートを最大 `limit` 件取得（時刻順）
+    ///
+    /// `range` を指定すればその範囲 `[start, end)` に絞る。種類が不明な行は除く
+    pub fn get_recent_alerts(
+        &self,
+        range: Option<(&str, &str)>,
+        limit: i64,
+    ) -> Result<Vec<Alert>, DatabaseError> {
+        let (start, end) = range.unzip();
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, occurred_at, kind, message
+            FROM alerts
+            WHERE (?1 IS NULL OR occurred_at >= ?1)
+              AND (?2 IS NULL OR occurred_at < ?2)
+            ORDER BY occurred_at DESC
+            LIMIT ?3
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end, limit], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut alerts = Vec::new();
+        for row in rows {
+            let (id, occurred_at, kind, message) = row?;
+            if let Some(kind) = AlertKind::parse(&kind) {
+                alerts.push(Alert {
+                    id: Some(id),
+                    occurred_at,
+                    kind,
+                    message,
+                });
+            }
+        }
+        alerts.reverse();
+        Ok(alerts)
+    }
+
+    /// 完了したポモドーロを記録（同じ時刻に完了したものは記録済みとして無視する）
+    pub fn insert_pomodoro(
+        &self,
+        completed_at: &str,
+        work_minutes: u64,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO pomodoros (completed_at, work_minutes) VALUES (?1, ?2)",
+            params![completed_at, work_minutes],
+        )?;
+        Ok(())
+    }
+
+    /// 指定範囲 `[start, end)` に完了したポモドーロの数
+    pub fn count_pomodoros_between(&self, start: &str, end: &str) -> Result<u64, DatabaseError> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pomodoros WHERE completed_at >= ?1 AND completed_at < ?2",
+            params![start, end],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    /// 目標の日別の達成状況を記録（同じ日付・目標の記録は置き換える）
+    pub fn upsert_goal_result(&self, result: &GoalResult) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO goal_results (date, goal, seconds, achieved) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                result.date,
+                result.goal,
+                result.seconds as i64,
+                result.achieved
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 目標の `date` までの日別の達成状況を古い順に取得
+    pub fn get_goal_results(
+        &self,
+        goal: &str,
+        date: &str,
+    ) -> Result<Vec<GoalResult>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT date, goal, seconds, achieved
+            FROM goal_results
+            WHERE goal = ?1 AND date <= ?2
+            ORDER BY date
+            "#,
+        )?;
+        let results = stmt
+            .query_map(params![goal, date], |row| {
+                Ok(GoalResult {
+                    date: row.get(0)?,
+                    goal: row.get(1)?,
+                    seconds: row.get::<_, i64>(2)? as u64,
+                    achieved: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(results)
+    }
+
+    /// 目標の達成状況を記録した最後の日付
+    pub fn get_last_goal_date(&self, goal: &str) -> Result<Option<String>, DatabaseError> {
+        let date = self.conn.query_row(
+            "SELECT MAX(date) FROM goal_results WHERE goal = ?1",
+            params![goal],
+            |row| row.get(0),
+        )?;
+        Ok(date)
+    }
+
+    /// 日付・チケットについてJira/Linearに登録済みの工数（秒、未登録なら0）を取得
+    pub fn get_pushed_worklog_seconds(
+        &self,
+        date: &str,
+        ticket: &str,
+    ) -> Result<u64, DatabaseError> {
+        let seconds: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT seconds FROM worklog_pushes WHERE date = ?1 AND ticket = ?2",
+                params![date, ticket],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(seconds.unwrap_or(0) as u64)
+    }
+
+    /// 日付・チケットについて登録済みの工数（秒）を記録
+    pub fn set_pushed_worklog_seconds(
+        &self,
+        date: &str,
+        ticket: &str,
+        seconds: u64,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO worklog_pushes (date, ticket, seconds, pushed_at) VALUES (?1, ?2, ?3, ?4)",
+            params![date, ticket, seconds as i64, format_timestamp(&Local::now())],
+        )?;
+        Ok(())
+    }
+
+    /// 同じハッシュの画像の既存OCR結果（テキストと詳細JSON）を取得
+    ///
+    /// プライベートなキャプチャの結果は流用しない
+    pub fn find_ocr_by_image_hash(
+        &self,
+        image_hash: &str,
+    ) -> Result<Option<(String, Option<String>)>, DatabaseError> {
+        let cached = self
+            .conn
+            .query_row(
+                r#"
+                SELECT decompress_text(ocr_text), ocr_details FROM captures
+                WHERE image_hash = ?1 AND ocr_text IS NOT NULL AND is_private = 0
+                ORDER BY ocr_details IS NULL, id DESC
+                LIMIT 1
+                "#,
+                params![image_hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(cached)
+    }
+
+    /// OCRの失敗を記録し、次回の試行を指数バックオフで予約する
+    ///
+    /// 試行回数が上限に達した場合は再試行を打ち切る。記録後の状態を返す
+    pub fn record_ocr_failure(
+        &self,
+        capture_id: i64,
+        error: &str,
+        failed_at: &DateTime<Local>,
+    ) -> Result<OcrJobStatus, DatabaseError> {
+        let previous: u32 = self
+            .conn
+            .query_row(
+                "SELECT attempts FROM ocr_jobs WHERE capture_id = ?1 AND status != 'done'",
+                params![capture_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or(0);
+        let attempts = previous + 1;
+
+        let (status, next_attempt_at) = if attempts >= OCR_MAX_ATTEMPTS {
+            (OcrJobStatus::Failed, None)
+        } else {
+            let delay = chrono::Duration::minutes(OCR_RETRY_BASE_MINUTES << (attempts - 1));
+            (
+                OcrJobStatus::Retrying,
+                Some(format_timestamp(&(*failed_at + delay))),
+            )
+        };
+
+        self.conn.execute(
+            r#"
+            INSERT INTO ocr_jobs (capture_id, status, attempts, last_error, next_attempt_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(capture_id) DO UPDATE SET
+                status = excluded.status,
+                attempts = excluded.attempts,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at
+            "#,
+            params![
+                capture_id,
+                status.as_str(),
+                attempts,
+                error,
+                next_attempt_at,
+                format_timestamp(failed_at)
+            ],
+        )?;
+        Ok(status)
+    }
+
+    /// OCRの成功を記録（失敗履歴のあるジョブのみ更新）
+    pub fn record_ocr_success(
+        &self,
+        capture_id: i64,
+        succeeded_at: &str,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            r#"
+            UPDATE ocr_jobs
+            SET status = 'done', last_error = NULL, next_attempt_at = NULL, updated_at = ?2
+            WHERE capture_id = ?1
+            "#,
+            params![capture_id, succeeded_at],
+        )?;
+        Ok(())
+    }
+
+    /// 失敗中（再試行待ち・打ち切り）のOCRジョブを新しい順に取得
+    pub fn get_ocr_failures(&self, limit: i64) -> Result<Vec<OcrJob>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT j.capture_id, c.captured_at, c.image_path, j.status, j.attempts, j.last_error, j.next_attempt_at
+            FROM ocr_jobs j
+            JOIN captures c ON c.id = j.capture_id
+            WHERE j.status != 'done'
+            ORDER BY j.updated_at DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![limit], |row| {
+            let status: String = row.get(3)?;
+            Ok(OcrJob {
+                capture_id: row.get(0)?,
                 captured_at: row.get(1)?,
                 image_path: row.get(2)?,
-                active_app: row.get(3)?,
-                window_title: row.get(4)?,
-                is_paused: row.get::<_, i32>(5)? != 0,
-                is_private: row.get::<_, i32>(6)? != 0,
-                ocr_text: row.get(7)?,
+                status: OcrJobStatus::parse(&status).ok_or_else(|| {
+                    rusqlite::Error::InvalidColumnType(3, status, rusqlite::types::Type::Text)
+                })?,
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                next_attempt_at: row.get(6)?,
             })
         })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
 
-        let mut records = Vec::new();
-        for row in rows {
-            records.push(row?);
+    /// キャプチャのある時間帯（YYYY-MM-DDTHH、壁時計時刻）を新しい順に取得
+    pub fn get_capture_hours(&self) -> Result<Vec<String>, DatabaseError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT substr(captured_at, 1, 13) FROM captures ORDER BY 1 DESC")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 学習した定型文（OCRテキストから除く行）を追加
+    pub fn add_ocr_boilerplate(&self, lines: &[String]) -> Result<(), DatabaseError> {
+        let learned_at = format_timestamp(&Local::now());
+        for line in lines {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO ocr_boilerplate (line, learned_at) VALUES (?1, ?2)",
+                params![line, learned_at],
+            )?;
         }
+        Ok(())
+    }
 
-        Ok(records)
+    /// 学習済みの定型文を取得
+    pub fn get_ocr_boilerplate(&self) -> Result<Vec<String>, DatabaseError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT line FROM ocr_boilerplate ORDER BY line")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<Result<_, _>>()?)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    /// キャプチャに紐づく入力活動量を保存
+    pub fn insert_input_metrics(
+        &self,
+        capture_id: i64,
+        metrics: &InputMetrics,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO input_metrics (capture_id, key_presses, clicks, scrolls)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![
+                capture_id,
+                metrics.key_presses as i64,
+                metrics.clicks as i64,
+                metrics.scrolls as i64
+            ],
+        )?;
+        Ok(())
+    }
 
-    fn create_test_db() -> (Database, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let db_path = temp_dir.path().join("test.db");
-        let db = Database::open(&db_path).unwrap();
-        (db, temp_dir)
+    /// キャプチャに紐づく入力活動量を取得
+    pub fn get_input_metrics(
+        &self,
+        capture_id: i64,
+    ) -> Result<Option<InputMetrics>, DatabaseError> {
+        let metrics = self
+            .conn
+            .query_row(
+                "SELECT key_presses, clicks, scrolls FROM input_metrics WHERE capture_id = ?1",
+                params![capture_id],
+                |row| {
+                    Ok(InputMetrics {
+                        key_presses: row.get::<_, i64>(0)? as u64,
+                        clicks: row.get::<_, i64>(1)? as u64,
+                        scrolls: row.get::<_, i64>(2)? as u64,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(metrics)
+    }
+
+    /// キャプチャ処理の所要時間を保存
+    pub fn insert_perf_metrics(
+        &self,
+        capture_id: i64,
+        metrics: &PerfMetrics,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO perf_metrics (capture_id, screenshot_ms, ocr_ms, db_ms, total_ms)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![
+                capture_id,
+                metrics.screenshot_ms as i64,
+                metrics.ocr_ms.map(|ms| ms as i64),
+                metrics.db_ms as i64,
+                metrics.total_ms as i64
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 記録済みの処理時間にOCRの所要時間を書き足す（OCRワーカーが後から認識した場合）
+    pub fn update_perf_ocr_ms(&self, capture_id: i64, ocr_ms: u64) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE perf_metrics SET ocr_ms = ?1 WHERE capture_id = ?2",
+            params![ocr_ms as i64, capture_id],
+        )?;
+        Ok(())
+    }
+
+    /// 期間 `[start, end)` のキャプチャ処理の所要時間を取得
+    ///
+    /// 境界の比較は `get_captures_between` と同じ
+    pub fn get_perf_metrics_between(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<PerfMetrics>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT p.screenshot_ms, p.ocr_ms, p.db_ms, p.total_ms
+            FROM perf_metrics p
+            JOIN captures c ON c.id = p.capture_id
+            WHERE c.captured_at >= ?1 AND c.captured_at < ?2
+            ORDER BY c.captured_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(PerfMetrics {
+                screenshot_ms: row.get::<_, i64>(0)? as u64,
+                ocr_ms: row.get::<_, Option<i64>>(1)?.map(|ms| ms as u64),
+                db_ms: row.get::<_, i64>(2)? as u64,
+                total_ms: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// キャプチャに紐づくプロセスのスナップショットを保存
+    pub fn insert_process_snapshots(
+        &self,
+        capture_id: i64,
+        snapshots: &[ProcessSnapshot],
+    ) -> Result<(), DatabaseError> {
+        self.with_transaction(|db| {
+            let mut stmt = db.conn.prepare_cached(
+                r#"
+                INSERT INTO process_snapshots (capture_id, pid, name, cpu_percent, memory_bytes, is_active_app)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+            )?;
+            for snapshot in snapshots {
+                stmt.execute(params![
+                    capture_id,
+                    snapshot.pid,
+                    snapshot.name,
+                    snapshot.cpu_percent as f64,
+                    snapshot.memory_bytes as i64,
+                    snapshot.is_active_app
+                ])?;
+            }
+            Ok(())
+        })
+    }
+
+    /// キャプチャに紐づくプロセスのスナップショットを取得（CPU使用率の降順）
+    pub fn get_process_snapshots(
+        &self,
+        capture_id: i64,
+    ) -> Result<Vec<ProcessSnapshot>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT pid, name, cpu_percent, memory_bytes, is_active_app
+            FROM process_snapshots
+            WHERE capture_id = ?1
+            ORDER BY cpu_percent DESC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![capture_id], |row| {
+            Ok(ProcessSnapshot {
+                pid: row.get(0)?,
+                name: row.get(1)?,
+                cpu_percent: row.get::<_, f64>(2)? as f32,
+                memory_bytes: row.get::<_, i64>(3)? as u64,
+                is_active_app: row.get(4)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// アプリ切り替えイベントを記録
+    pub fn insert_app_event(&self, event: &AppEvent) -> Result<i64, DatabaseError> {
+        self.conn.execute(
+            "INSERT INTO app_events (occurred_at, app_name, bundle_id) VALUES (?1, ?2, ?3)",
+            params![event.occurred_at, event.app_name, event.bundle_id],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 指定範囲 `[start, end)` のアプリ切り替えイベントを取得（時刻順）
+    pub fn get_app_events_between(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<AppEvent>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, occurred_at, app_name, bundle_id
+            FROM app_events
+            WHERE occurred_at >= ?1 AND occurred_at < ?2
+            ORDER BY occurred_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(AppEvent {
+                id: Some(row.get(0)?),
+                occurred_at: row.get(1)?,
+                app_name: row.get(2)?,
+                bundle_id: row.get(3)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// セッションを記録
+    pub fn insert_session(&self, session: &SessionRecord) -> Result<i64, DatabaseError> {
+        self.conn.execute(
+            r#"
+            INSERT INTO sessions (kind, started_at, ended_at, version, hostname, end_reason)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![
+                session.kind.as_str(),
+                session.started_at,
+                session.ended_at,
+                session.version,
+                session.hostname,
+                session.end_reason.map(|r| r.as_str())
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// セッションの終了を記録
+    pub fn end_session(
+        &self,
+        id: i64,
+        ended_at: &str,
+        reason: EndReason,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "UPDATE sessions SET ended_at = ?1, end_reason = ?2 WHERE id = ?3",
+            params![ended_at, reason.as_str(), id],
+        )?;
+        Ok(())
+    }
+
+    /// 終了が記録されていないトラッカーセッションをクラッシュとして閉じる
+    ///
+    /// 終了時刻はセッション中の最後のキャプチャ（なければ開始時刻）で近似する。
+    /// 閉じた件数を返す
+    pub fn close_abandoned_sessions(&self) -> Result<usize, DatabaseError> {
+        let count = self.conn.execute(
+            r#"
+            UPDATE sessions
+            SET ended_at = COALESCE(
+                    (SELECT MAX(captured_at) FROM captures WHERE captured_at >= sessions.started_at),
+                    started_at
+                ),
+                end_reason = ?2
+            WHERE kind = ?1 AND ended_at IS NULL
+            "#,
+            params![SessionKind::Tracker.as_str(), EndReason::Crashed.as_str()],
+        )?;
+        Ok(count)
+    }
+
+    /// 指定範囲 `[start, end)` と重なるセッションを取得（開始時刻順）
+    ///
+    /// 終了していないセッションは現在も継続中として扱う
+    pub fn get_sessions_between(
+        &self,
+        kind: SessionKind,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<SessionRecord>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, kind, started_at, ended_at, version, hostname, end_reason
+            FROM sessions
+            WHERE kind = ?1 AND started_at < ?3 AND (ended_at IS NULL OR ended_at >= ?2)
+            ORDER BY started_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![kind.as_str(), start, end], map_session_row)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 最近のセッションを取得（新しい順）
+    pub fn get_recent_sessions(
+        &self,
+        kind: SessionKind,
+        limit: i64,
+    ) -> Result<Vec<SessionRecord>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, kind, started_at, ended_at, version, hostname, end_reason
+            FROM sessions
+            WHERE kind = ?1
+            ORDER BY started_at DESC
+            LIMIT ?2
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![kind.as_str(), limit], map_session_row)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// プライベートなキャプチャのOCRテキスト・詳細を消去
+    ///
+    /// 消去した件数を返す
+    pub fn scrub_private_ocr(&self) -> Result<usize, DatabaseError> {
+        let count = self.conn.execute(
+            r#"
+            UPDATE captures SET ocr_text = NULL, ocr_details = NULL
+            WHERE is_private = 1 AND (ocr_text IS NOT NULL OR ocr_details IS NOT NULL)
+            "#,
+            [],
+        )?;
+        Ok(count)
+    }
+
+    /// キャプチャと、それに紐づく入力活動量・処理時間・プロセス・OCRジョブ・代表画像を削除
+    ///
+    /// 外部キー制約は有効にしていないため、関連テーブルも明示的に削除する。
+    /// 削除したキャプチャの件数を返す（画像ファイルは削除しない）
+    pub fn delete_captures(&self, ids: &[i64]) -> Result<usize, DatabaseError> {
+        const TABLES: [&str; 5] = [
+            "input_metrics",
+            "perf_metrics",
+            "process_snapshots",
+            "ocr_jobs",
+            "highlights",
+        ];
+
+        self.with_transaction(|db| {
+            let mut deleted = 0;
+            for id in ids {
+                for table in TABLES {
+                    db.conn.execute(
+                        &format!("DELETE FROM {} WHERE capture_id = ?1", table),
+                        params![id],
+                    )?;
+                }
+                deleted += db
+                    .conn
+                    .execute("DELETE FROM captures WHERE id = ?1", params![id])?;
+            }
+            Ok(deleted)
+        })
+    }
+
+    /// OCRテキストが未設定のキャプチャを取得
+    ///
+    /// 失敗して再試行を待っているもの・再試行を打ち切ったものは除く
+    pub fn get_captures_without_ocr(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT c.id, c.captured_at, c.image_path, c.active_app, c.window_title, c.is_paused, c.is_private, decompress_text(c.ocr_text), c.interval_seconds, c.hostname, c.tracker_version, c.bundle_id, c.activity_type, c.working_directory
+            FROM captures c
+            LEFT JOIN ocr_jobs j ON j.capture_id = c.id
+            WHERE c.ocr_text IS NULL AND c.image_path IS NOT NULL AND c.is_private = 0
+              AND (j.status IS NULL OR j.status = 'done'
+                   OR (j.status = 'retrying' AND j.next_attempt_at <= ?2))
+            ORDER BY c.captured_at DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let now = format_timestamp(&Local::now());
+        let rows = stmt.query_map(params![limit, now], map_capture_row)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+
+        Ok(records)
+    }
+
+    /// `after_id` より後のOCRテキストが未設定のキャプチャをIDの古い順に最大 `limit` 件取得
+    ///
+    /// 対象は `get_captures_without_ocr` と同じ（再試行待ち・打ち切りは除く）
+    pub fn get_captures_without_ocr_after(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT c.id, c.captured_at, c.image_path, c.active_app, c.window_title, c.is_paused, c.is_private, decompress_text(c.ocr_text), c.interval_seconds, c.hostname, c.tracker_version, c.bundle_id, c.activity_type, c.working_directory
+            FROM captures c
+            LEFT JOIN ocr_jobs j ON j.capture_id = c.id
+            WHERE c.id > ?1 AND c.ocr_text IS NULL AND c.image_path IS NOT NULL AND c.is_private = 0
+              AND (j.status IS NULL OR j.status = 'done'
+                   OR (j.status = 'retrying' AND j.next_attempt_at <= ?3))
+            ORDER BY c.id ASC
+            LIMIT ?2
+            "#,
+        )?;
+
+        let now = format_timestamp(&Local::now());
+        let rows = stmt.query_map(params![after_id, limit, now], map_capture_row)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+
+        Ok(records)
+    }
+
+    /// `after_id` より後のOCRテキストが未設定のキャプチャの件数
+    pub fn count_captures_without_ocr_after(&self, after_id: i64) -> Result<u64, DatabaseError> {
+        let count: i64 = self.conn.query_row(
+            r#"
+            SELECT COUNT(*)
+            FROM captures c
+            LEFT JOIN ocr_jobs j ON j.capture_id = c.id
+            WHERE c.id > ?1 AND c.ocr_text IS NULL AND c.image_path IS NOT NULL AND c.is_private = 0
+              AND (j.status IS NULL OR j.status = 'done'
+                   OR (j.status = 'retrying' AND j.next_attempt_at <= ?2))
+            "#,
+            params![after_id, format_timestamp(&Local::now())],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    /// `tracker ocr --all` の進捗を取得（処理中でなければ `None`）
+    pub fn get_ocr_batch_progress(&self) -> Result<Option<OcrBatchProgress>, DatabaseError> {
+        let progress = self
+            .conn
+            .query_row(
+                "SELECT last_capture_id, processed, started_at FROM ocr_batch_progress WHERE id = 1",
+                [],
+                |row| {
+                    Ok(OcrBatchProgress {
+                        last_capture_id: row.get(0)?,
+                        processed: row.get::<_, i64>(1)? as u64,
+                        started_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(progress)
+    }
+
+    /// `tracker ocr --all` の進捗を記録
+    pub fn save_ocr_batch_progress(
+        &self,
+        progress: &OcrBatchProgress,
+    ) -> Result<(), DatabaseError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO ocr_batch_progress (id, last_capture_id, processed, started_at, updated_at) VALUES (1, ?1, ?2, ?3, ?4)",
+            params![
+                progress.last_capture_id,
+                progress.processed as i64,
+                progress.started_at,
+                format_timestamp(&Local::now())
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// `tracker ocr --all` の進捗を消去（最後まで処理した）
+    pub fn clear_ocr_batch_progress(&self) -> Result<(), DatabaseError> {
+        self.conn.execute("DELETE FROM ocr_batch_progress", [])?;
+        Ok(())
+    }
+
+    /// OCR再処理の対象となるキャプチャを取得
+    ///
+    /// `where_clause` は任意のSQL条件式で、`captures` の全カラム（ocr_textは展開済み）に加えて
+    /// エイリアス `app`（active_app）と `date`（YYYY-MM-DD）を参照できる。
+    /// `limit` に負の値を指定すると件数を制限しない
+    pub fn get_captures_for_ocr_redo(
+        &self,
+        date: Option<&str>,
+        where_clause: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let sql = format!(
+            r#"
+            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, ocr_text, interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+            FROM (
+                SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private,
+                       decompress_text(ocr_text) AS ocr_text, ocr_details, interval_seconds, image_hash, hostname, tracker_version, bundle_id, activity_type, working_directory,
+                       active_app AS app, substr(captured_at, 1, 10) AS date
+                FROM captures
+            )
+            WHERE image_path IS NOT NULL
+              AND is_private = 0
+              AND (?1 IS NULL OR captured_at LIKE ?1 || '%')
+              AND ({})
+            ORDER BY captured_at ASC
+            LIMIT ?2
+            "#,
+            where_clause.unwrap_or("1")
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![date, limit], map_capture_row)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+
+        Ok(records)
+    }
+
+    /// 期間 `[start, end)` のキャプチャを取得
+    ///
+    /// 境界はオフセットなしの `YYYY-MM-DDTHH:MM:SS` で指定し、記録時点の
+    /// ローカル時刻（壁時計時刻）で比較する
+    pub fn get_captures_between(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let mut records = Vec::new();
+        self.for_each_capture_between(start, end, |record| {
+            records.push(record);
+            Ok::<_, DatabaseError>(())
+        })?;
+        Ok(records)
+    }
+
+    /// 期間 `[start, end)` のキャプチャを古い順に1件ずつコールバックに渡す
+    ///
+    /// `get_captures_between` と異なり全件をメモリに載せないため、月単位などの
+    /// 大量のキャプチャの集計に使う。コールバックが `Err` を返すとそこで打ち切る
+    pub fn for_each_capture_between<E, F>(&self, start: &str, end: &str, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(CaptureRecord) -> Result<(), E>,
+        E: From<DatabaseError>,
+    {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r#"
+                SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+                FROM captures
+                WHERE captured_at >= ?1 AND captured_at < ?2
+                ORDER BY captured_at ASC
+                "#,
+            )
+            .map_err(DatabaseError::from)?;
+
+        let rows = stmt
+            .query_map(params![start, end], map_capture_row)
+            .map_err(DatabaseError::from)?;
+        for row in rows {
+            f(row.map_err(DatabaseError::from)?)?;
+        }
+        Ok(())
+    }
+
+    /// 日付でキャプチャを取得
+    pub fn get_captures_by_date(&self, date: &str) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let pattern = format!("{}%", date);
+
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+            FROM captures
+            WHERE captured_at LIKE ?1
+            ORDER BY captured_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![pattern], map_capture_row)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+
+        Ok(records)
+    }
+
+    /// IDでキャプチャを取得
+    pub fn get_capture(&self, id: i64) -> Result<Option<CaptureRecord>, DatabaseError> {
+        let record = self
+            .conn
+            .query_row(
+                r#"
+                SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+                FROM captures
+                WHERE id = ?1
+                "#,
+                params![id],
+                map_capture_row,
+            )
+            .optional()?;
+
+        Ok(record)
+    }
+
+    /// 最新のキャプチャを取得（一時停止中の記録は除く）
+    pub fn get_latest_capture(&self) -> Result<Option<CaptureRecord>, DatabaseError> {
+        let record = self
+            .conn
+            .query_row(
+                r#"
+                SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+                FROM captures
+                WHERE is_paused = 0
+                ORDER BY id DESC
+                LIMIT 1
+                "#,
+                [],
+                map_capture_row,
+            )
+            .optional()?;
+
+        Ok(record)
+    }
+
+    /// 最新のキャプチャを `limit` 件取得（新しい順）
+    pub fn get_latest_captures(&self, limit: i64) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        Ok(self.get_captures_page(limit, 0)?.captures)
+    }
+
+    /// キャプチャを新しい順にページ単位で取得
+    ///
+    /// `offset` 件を読み飛ばしてから `limit` 件を返す。`total` は全件数
+    pub fn get_captures_page(&self, limit: i64, offset: i64) -> Result<CapturePage, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+            FROM captures
+            ORDER BY captured_at DESC, id DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![limit, offset], map_capture_row)?;
+        let captures = rows.collect::<Result<_, _>>()?;
+
+        Ok(CapturePage {
+            captures,
+            total: self.count_captures()?,
+            limit,
+            offset,
+        })
+    }
+
+    /// 全キャプチャの件数
+    pub fn count_captures(&self) -> Result<u64, DatabaseError> {
+        let count = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM captures", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    /// 期間 `[start, end)` の指定アプリのキャプチャを取得
+    ///
+    /// アプリ名は完全一致で比較する。境界は `get_captures_between` と同じ形式
+    pub fn get_captures_by_app(
+        &self,
+        app: &str,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+            FROM captures
+            WHERE active_app = ?1 AND captured_at >= ?2 AND captured_at < ?3
+            ORDER BY captured_at ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![app, start, end], map_capture_row)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// 日付 `[start, end)` のキャプチャ件数を日付ごとに取得（古い順）
+    ///
+    /// 日付は記録時点のローカル日付（`YYYY-MM-DD`）。キャプチャのない日は含まない
+    pub fn count_by_date(&self, start: &str, end: &str) -> Result<Vec<DateCount>, DatabaseError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT substr(captured_at, 1, 10) AS date, COUNT(*)
+            FROM captures
+            WHERE captured_at >= ?1 AND captured_at < ?2
+            GROUP BY date
+            ORDER BY date ASC
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![start, end], |row| {
+            Ok(DateCount {
+                date: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// OCRテキストを部分一致で検索（新しい順）
+    pub fn search_ocr_text(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<CaptureRecord>, DatabaseError> {
+        let pattern = format!("%{}%", escape_like(query));
+
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, captured_at, image_path, active_app, window_title, is_paused, is_private, decompress_text(ocr_text), interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory
+            FROM captures
+            WHERE decompress_text(ocr_text) LIKE ?1 ESCAPE '\' AND is_private = 0
+            ORDER BY captured_at DESC
+            LIMIT ?2
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![pattern, limit], map_capture_row)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+
+        Ok(records)
+    }
+
+    /// DBファイルの整合性と外部キーの参照をチェック
+    pub fn check_integrity(&self) -> Result<IntegrityReport, DatabaseError> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let integrity_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|message| message != "ok")
+            .collect();
+
+        let mut stmt = self.conn.prepare("PRAGMA foreign_key_check")?;
+        let foreign_key_errors = stmt
+            .query_map([], |row| {
+                Ok(format!(
+                    "{} rowid={} -> {}",
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?
+                        .map_or_else(|| "?".to_string(), |id| id.to_string()),
+                    row.get::<_, String>(2)?
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(IntegrityReport {
+            integrity_errors,
+            foreign_key_errors,
+        })
+    }
+
+    /// WALの内容をDB本体に書き戻し、WALファイルを空にする
+    ///
+    /// 読み取り中の接続があって書き戻せなかったページがあれば `false`
+    pub fn checkpoint(&self) -> Result<bool, DatabaseError> {
+        let busy: i64 = self
+            .conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        Ok(busy == 0)
+    }
+}
+
+/// DBファイルとWAL・共有メモリファイルの合計サイズ（バイト、存在しないファイルは0）
+pub fn database_file_size(db_path: &Path) -> u64 {
+    ["", "-wal", "-shm"]
+        .iter()
+        .map(|suffix| {
+            let mut path = db_path.as_os_str().to_os_string();
+            path.push(suffix);
+            fs::metadata(path).map_or(0, |m| m.len())
+        })
+        .sum()
+}
+
+/// WALの自動チェックポイント
+///
+/// 前回のチェックポイントから `interval` が経つか、WALファイルが `max_wal_bytes` を超えたら
+/// `PRAGMA wal_checkpoint(TRUNCATE)` を実行する（どちらも0なら判定しない）。
+/// 読み取り中の接続があって書き戻せなかった場合は、次の呼び出しで再試行する
+pub struct WalCheckpointer {
+    wal_path: PathBuf,
+    interval: Duration,
+    max_wal_bytes: u64,
+    last_checkpoint: Instant,
+}
+
+impl WalCheckpointer {
+    /// DBファイルのパスと、チェックポイントの間隔・WALファイルの上限から作成
+    pub fn new(db_path: &Path, interval: Duration, max_wal_bytes: u64) -> Self {
+        let mut wal_path = db_path.as_os_str().to_os_string();
+        wal_path.push("-wal");
+        Self {
+            wal_path: PathBuf::from(wal_path),
+            interval,
+            max_wal_bytes,
+            last_checkpoint: Instant::now(),
+        }
+    }
+
+    /// WALファイルのサイズ（バイト、なければ0）
+    pub fn wal_bytes(&self) -> u64 {
+        fs::metadata(&self.wal_path).map_or(0, |m| m.len())
+    }
+
+    /// チェックポイントが必要か
+    pub fn is_due(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_checkpoint);
+        (!self.interval.is_zero() && elapsed >= self.interval)
+            || (self.max_wal_bytes > 0 && self.wal_bytes() > self.max_wal_bytes)
+    }
+
+    /// 必要ならチェックポイントを実行（実行しなければ `None`、書き戻せたかを `Some` で返す）
+    pub fn maybe_checkpoint(
+        &mut self,
+        db: &Database,
+        now: Instant,
+    ) -> Result<Option<bool>, DatabaseError> {
+        if !self.is_due(now) {
+            return Ok(None);
+        }
+        let checkpointed = db.checkpoint()?;
+        if checkpointed {
+            self.last_checkpoint = now;
+        }
+        Ok(Some(checkpointed))
+    }
+}
+
+/// タイムスタンプを保存形式（オフセット付きRFC3339、秒精度）に変換
+pub fn format_timestamp(timestamp: &DateTime<Local>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// 保存されたタイムスタンプを解析
+///
+/// RFC3339形式に加え、オフセットのない旧形式も現在のタイムゾーンの
+/// ローカル時刻として受け付ける（夏時間の切り替えで存在しない時刻はNone）
+pub fn parse_timestamp(value: &str) -> Option<DateTime<FixedOffset>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp);
+    }
+    let naive = NaiveDateTime::parse_from_str(value, LEGACY_TIMESTAMP_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|timestamp| timestamp.fixed_offset())
+}
+
+/// 追加のkey-valueのキーをJSONパスにする（`git_branch` → `$."git_branch"`）
+///
+/// `.` や `[` を含むキーも1つのキーとして扱うよう、引用符で囲む
+fn extra_path(key: &str) -> String {
+    format!("$.\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 行をCaptureRecordに変換
+///
+/// SELECT句のカラム順は `id, captured_at, image_path, active_app, window_title,
+/// is_paused, is_private, ocr_text, interval_seconds, hostname, tracker_version, bundle_id, activity_type, working_directory` であること
+fn map_capture_row(row: &Row) -> rusqlite::Result<CaptureRecord> {
+    Ok(CaptureRecord {
+        id: Some(row.get(0)?),
+        captured_at: row.get(1)?,
+        image_path: row.get(2)?,
+        active_app: row.get(3)?,
+        window_title: row.get(4)?,
+        is_paused: row.get::<_, i32>(5)? != 0,
+        is_private: row.get::<_, i32>(6)? != 0,
+        ocr_text: row.get(7)?,
+        interval_seconds: row.get(8)?,
+        hostname: row.get(9)?,
+        tracker_version: row.get(10)?,
+        bundle_id: row.get(11)?,
+        activity_type: row.get(12)?,
+        working_directory: row.get(13)?,
+    })
+}
+
+/// 代表画像の行をHighlightに変換
+///
+/// SELECT句のカラム順は `SELECT_HIGHLIGHT_SQL` と同じであること
+fn map_highlight_row(row: &Row) -> rusqlite::Result<Highlight> {
+    Ok(Highlight {
+        hour: row.get(0)?,
+        capture_id: row.get(1)?,
+        captured_at: row.get(2)?,
+        image_path: row.get(3)?,
+        active_app: row.get(4)?,
+        window_title: row.get(5)?,
+        score: row.get::<_, i64>(6)? as u64,
+        image_hash: row.get(7)?,
+    })
+}
+
+/// セッション行をSessionRecordに変換
+///
+/// SELECT句のカラム順は `id, kind, started_at, ended_at, version, hostname, end_reason` であること
+fn map_session_row(row: &Row) -> rusqlite::Result<SessionRecord> {
+    let kind: String = row.get(1)?;
+    let end_reason: Option<String> = row.get(6)?;
+    Ok(SessionRecord {
+        id: Some(row.get(0)?),
+        kind: SessionKind::parse(&kind).ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(1, kind, rusqlite::types::Type::Text)
+        })?,
+        started_at: row.get(2)?,
+        ended_at: row.get(3)?,
+        version: row.get(4)?,
+        hostname: row.get(5)?,
+        end_reason: end_reason.as_deref().and_then(EndReason::parse),
+    })
+}
+
+/// LIKEパターン用に `%` `_` `\` をエスケープ
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_db() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::open(&db_path).unwrap();
+        (db, temp_dir)
+    }
+
+    #[test]
+    fn test_database_open_creates_schema() {
+        let (db, _temp_dir) = create_test_db();
+
+        // テーブルが存在することを確認
+        let count: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='captures'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_insert_capture() {
+        let (db, _temp_dir) = create_test_db();
+
+        let record = CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:00:00".to_string(),
+            image_path: Some("/path/to/image.jpg".to_string()),
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        };
+
+        let id = db.insert_capture(&record).unwrap();
+        assert!(id > 0);
+    }
+
+    #[test]
+    fn test_get_captures_by_date() {
+        let (db, _temp_dir) = create_test_db();
+
+        // テストデータを挿入
+        let records = vec![
+            CaptureRecord {
+                id: None,
+                captured_at: "2024-12-30T10:00:00".to_string(),
+                image_path: Some("/path/1.jpg".to_string()),
+                active_app: "VS Code".to_string(),
+                window_title: "file1.rs".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            },
+            CaptureRecord {
+                id: None,
+                captured_at: "2024-12-30T11:00:00".to_string(),
+                image_path: Some("/path/2.jpg".to_string()),
+                active_app: "Chrome".to_string(),
+                window_title: "Google".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            },
+            CaptureRecord {
+                id: None,
+                captured_at: "2024-12-31T10:00:00".to_string(),
+                image_path: Some("/path/3.jpg".to_string()),
+                active_app: "Terminal".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            },
+        ];
+
+        for record in &records {
+            db.insert_capture(record).unwrap();
+        }
+
+        // 2024-12-30のレコードを取得
+        let result = db.get_captures_by_date("2024-12-30").unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].active_app, "VS Code");
+        assert_eq!(result[1].active_app, "Chrome");
+    }
+
+    #[test]
+    fn test_get_captures_empty_date() {
+        let (db, _temp_dir) = create_test_db();
+
+        let result = db.get_captures_by_date("2099-01-01").unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_insert_with_null_image_path() {
+        let (db, _temp_dir) = create_test_db();
+
+        let record = CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:00:00".to_string(),
+            image_path: None,
+            active_app: "VS Code".to_string(),
+            window_title: "".to_string(),
+            is_paused: true,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        };
+
+        let id = db.insert_capture(&record).unwrap();
+        assert!(id > 0);
+
+        let result = db.get_captures_by_date("2024-12-30").unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].image_path.is_none());
+        assert!(result[0].is_paused);
+    }
+
+    #[test]
+    fn test_wal_mode_enabled() {
+        let (db, _temp_dir) = create_test_db();
+
+        let mode: String = db
+            .conn
+            .query_row("PRAGMA journal_mode;", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode.to_lowercase(), "wal");
+    }
+
+    #[test]
+    fn test_check_integrity_and_vacuum() {
+        let (db, temp_dir) = create_test_db();
+        let report = db.check_integrity().unwrap();
+        assert!(report.is_ok());
+
+        // 外部キー制約を無効にした接続（外部ツールなど）から参照先のない行を書き込む
+        db.conn
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO input_metrics (capture_id, key_presses) VALUES (999, 1);
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+        let report = db.check_integrity().unwrap();
+        assert!(report.integrity_errors.is_empty());
+        assert_eq!(
+            report.foreign_key_errors,
+            vec!["input_metrics rowid=999 -> captures"]
+        );
+
+        let db_path = temp_dir.path().join("test.db");
+        assert!(database_file_size(&db_path) > 0);
+        assert!(db.vacuum().unwrap());
+        let wal = temp_dir.path().join("test.db-wal");
+        assert_eq!(fs::metadata(wal).map_or(0, |m| m.len()), 0);
+    }
+
+    #[test]
+    fn test_wal_checkpointer() {
+        let (db, temp_dir) = create_test_db();
+        let db_path = temp_dir.path().join("test.db");
+        let started = Instant::now();
+
+        // 間隔で判定する
+        let mut checkpointer = WalCheckpointer::new(&db_path, Duration::from_secs(60), 0);
+        assert_eq!(checkpointer.maybe_checkpoint(&db, started).unwrap(), None);
+        let later = started + Duration::from_secs(61);
+        assert_eq!(
+            checkpointer.maybe_checkpoint(&db, later).unwrap(),
+            Some(true)
+        );
+        assert_eq!(checkpointer.maybe_checkpoint(&db, later).unwrap(), None);
+
+        // WALファイルのサイズで判定する
+        let mut checkpointer = WalCheckpointer::new(&db_path, Duration::ZERO, 1);
+        assert!(!checkpointer.is_due(started));
+        db.insert_note(&Note {
+            id: None,
+            created_at: "2024-12-30T09:00:00+09:00".to_string(),
+            text: "WALに書き込む".to_string(),
+        })
+        .unwrap();
+        assert!(checkpointer.wal_bytes() > 1);
+        assert_eq!(
+            checkpointer.maybe_checkpoint(&db, started).unwrap(),
+            Some(true)
+        );
+        assert_eq!(checkpointer.wal_bytes(), 0);
+    }
+
+    #[test]
+    fn test_index_exists() {
+        let (db, _temp_dir) = create_test_db();
+
+        let count: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='index' AND name='idx_captures_captured_at'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_get_capture() {
+        let (db, _temp_dir) = create_test_db();
+
+        let record = CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:00:00".to_string(),
+            image_path: Some("/path/to/image.jpg".to_string()),
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        };
+        let id = db.insert_capture(&record).unwrap();
+
+        let found = db.get_capture(id).unwrap().unwrap();
+        assert_eq!(found.id, Some(id));
+        assert_eq!(found.active_app, "VS Code");
+        assert!(db.get_capture(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_input_metrics_roundtrip() {
+        let (db, _temp_dir) = create_test_db();
+        let id = db
+            .insert_capture(&sample_record("2024-12-30T10:00:00"))
+            .unwrap();
+        assert!(db.get_input_metrics(id).unwrap().is_none());
+
+        let metrics = InputMetrics {
+            key_presses: 120,
+            clicks: 8,
+            scrolls: 30,
+        };
+        db.insert_input_metrics(id, &metrics).unwrap();
+        assert_eq!(db.get_input_metrics(id).unwrap(), Some(metrics));
+    }
+
+    #[test]
+    fn test_process_snapshots_roundtrip() {
+        let (db, _temp_dir) = create_test_db();
+        let id = db
+            .insert_capture(&sample_record("2024-12-30T10:00:00"))
+            .unwrap();
+
+        let snapshots = vec![
+            ProcessSnapshot {
+                pid: 100,
+                name: "Code".to_string(),
+                cpu_percent: 12.5,
+                memory_bytes: 512 * 1024 * 1024,
+                is_active_app: true,
+            },
+            ProcessSnapshot {
+                pid: 200,
+                name: "rustc".to_string(),
+                cpu_percent: 380.0,
+                memory_bytes: 2 * 1024 * 1024 * 1024,
+                is_active_app: false,
+            },
+        ];
+        db.insert_process_snapshots(id, &snapshots).unwrap();
+
+        let found = db.get_process_snapshots(id).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], snapshots[1]);
+        assert!(db.get_process_snapshots(id + 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sessions_between() {
+        let (db, _temp_dir) = create_test_db();
+        let session = |started_at: &str, ended_at: Option<&str>| SessionRecord {
+            id: None,
+            kind: SessionKind::Sleep,
+            started_at: started_at.to_string(),
+            ended_at: ended_at.map(str::to_string),
+            version: None,
+            hostname: None,
+            end_reason: None,
+        };
+        db.insert_session(&session(
+            "2024-12-29T23:00:00+09:00",
+            Some("2024-12-30T07:00:00+09:00"),
+        ))
+        .unwrap();
+        db.insert_session(&session(
+            "2024-12-30T12:00:00+09:00",
+            Some("2024-12-30T13:00:00+09:00"),
+        ))
+        .unwrap();
+        db.insert_session(&session("2024-12-31T09:00:00+09:00", None))
+            .unwrap();
+
+        let found = db
+            .get_sessions_between(
+                SessionKind::Sleep,
+                "2024-12-30T00:00:00",
+                "2024-12-31T00:00:00",
+            )
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].started_at, "2024-12-29T23:00:00+09:00");
+        assert_eq!(found[1].kind, SessionKind::Sleep);
+    }
+
+    #[test]
+    fn test_tracker_session_lifecycle() {
+        let (db, _temp_dir) = create_test_db();
+        let tracker_session = |started_at: &str| SessionRecord {
+            id: None,
+            kind: SessionKind::Tracker,
+            started_at: started_at.to_string(),
+            ended_at: None,
+            version: Some("0.1.0".to_string()),
+            hostname: Some("mac".to_string()),
+            end_reason: None,
+        };
+
+        // 終了が記録されなかったセッション
+        db.insert_session(&tracker_session("2024-12-30T09:00:00+09:00"))
+            .unwrap();
+        db.insert_capture(&sample_record("2024-12-30T09:30:00+09:00"))
+            .unwrap();
+        assert_eq!(db.close_abandoned_sessions().unwrap(), 1);
+
+        let id = db
+            .insert_session(&tracker_session("2024-12-30T10:00:00+09:00"))
+            .unwrap();
+        db.end_session(id, "2024-12-30T18:00:00+09:00", EndReason::Stopped)
+            .unwrap();
+
+        let sessions = db.get_recent_sessions(SessionKind::Tracker, 10).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].end_reason, Some(EndReason::Stopped));
+        assert_eq!(sessions[0].hostname.as_deref(), Some("mac"));
+        assert_eq!(sessions[1].end_reason, Some(EndReason::Crashed));
+        assert_eq!(
+            sessions[1].ended_at.as_deref(),
+            Some("2024-12-30T09:30:00+09:00")
+        );
+    }
+
+    #[test]
+    fn test_app_events_between() {
+        let (db, _temp_dir) = create_test_db();
+        let event = |occurred_at: &str, app_name: &str| AppEvent {
+            id: None,
+            occurred_at: occurred_at.to_string(),
+            app_name: app_name.to_string(),
+            bundle_id: String::new(),
+        };
+        db.insert_app_event(&event("2024-12-30T10:00:12+09:00", "Slack"))
+            .unwrap();
+        db.insert_app_event(&event("2024-12-30T10:00:41+09:00", "Code"))
+            .unwrap();
+        db.insert_app_event(&event("2024-12-31T09:00:00+09:00", "Safari"))
+            .unwrap();
+
+        let events = db
+            .get_app_events_between("2024-12-30T00:00:00", "2024-12-31T00:00:00")
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].app_name, "Slack");
+        assert_eq!(events[1].app_name, "Code");
+    }
+
+    #[test]
+    fn test_notes_between() {
+        let (db, _temp_dir) = create_test_db();
+        let note = |created_at: &str, text: &str| Note {
+            id: None,
+            created_at: created_at.to_string(),
+            text: text.to_string(),
+        };
+        db.insert_note(&note("2024-12-30T15:00:00+09:00", "設計レビュー完了"))
+            .unwrap();
+        let id = db
+            .insert_note(&note("2024-12-30T10:00:00+09:00", "朝会"))
+            .unwrap();
+        db.insert_note(&note("2024-12-31T09:00:00+09:00", "翌日"))
+            .unwrap();
+
+        let notes = db
+            .get_notes_between("2024-12-30T00:00:00", "2024-12-31T00:00:00")
+            .unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].id, Some(id));
+        assert_eq!(notes[0].text, "朝会");
+    }
+
+    #[test]
+    fn test_recent_alerts() {
+        let (db, _temp_dir) = create_test_db();
+        let alert = |occurred_at: &str, kind: AlertKind| Alert {
+            id: None,
+            occurred_at: occurred_at.to_string(),
+            kind,
+            message: format!("{} のアラート", occurred_at),
+        };
+        db.insert_alert(&alert("2024-12-30T10:00:00+09:00", AlertKind::Break))
+            .unwrap();
+        db.insert_alert(&alert("2024-12-30T15:00:00+09:00", AlertKind::Distraction))
+            .unwrap();
+        db.insert_alert(&alert(
+            "2024-12-31T09:00:00+09:00",
+            AlertKind::CaptureFailure,
+        ))
+        .unwrap();
+
+        // 新しいものから件数を絞り、時刻順に並べる
+        let alerts = db.get_recent_alerts(None, 2).unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].kind, AlertKind::Distraction);
+        assert_eq!(alerts[1].kind, AlertKind::CaptureFailure);
+
+        let alerts = db
+            .get_recent_alerts(Some(("2024-12-30T00:00:00", "2024-12-31T00:00:00")), 10)
+            .unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].occurred_at, "2024-12-30T10:00:00+09:00");
+    }
+
+    #[test]
+    fn test_pomodoros() {
+        let (db, _temp_dir) = create_test_db();
+        db.insert_pomodoro("2024-12-30T09:25:00+09:00", 25).unwrap();
+        // 再起動後に同じポモドーロを記録しても重複しない
+        db.insert_pomodoro("2024-12-30T09:25:00+09:00", 25).unwrap();
+        db.insert_pomodoro("2024-12-30T09:55:00+09:00", 25).unwrap();
+        db.insert_pomodoro("2024-12-31T09:25:00+09:00", 25).unwrap();
+
+        assert_eq!(
+            db.count_pomodoros_between("2024-12-30T00:00:00", "2024-12-31T00:00:00")
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_goal_results() {
+        let (db, _temp_dir) = create_test_db();
+        assert_eq!(db.get_last_goal_date("開発").unwrap(), None);
+
+        let result = |date: &str, seconds: u64| GoalResult {
+            date: date.to_string(),
+            goal: "開発".to_string(),
+            seconds,
+            achieved: seconds >= 3600,
+        };
+        db.upsert_goal_result(&result("2024-12-30", 4000)).unwrap();
+        db.upsert_goal_result(&result("2024-12-31", 100)).unwrap();
+        // 当日分は後から作業時間が増えれば置き換える
+        db.upsert_goal_result(&result("2024-12-31", 3600)).unwrap();
+        db.upsert_goal_result(&result("2025-01-01", 0)).unwrap();
+
+        let results = db.get_goal_results("開発", "2024-12-31").unwrap();
+        assert_eq!(
+            results,
+            vec![result("2024-12-30", 4000), result("2024-12-31", 3600)]
+        );
+        assert_eq!(
+            db.get_last_goal_date("開発").unwrap(),
+            Some("2025-01-01".to_string())
+        );
+        assert!(db
+            .get_goal_results("合計", "2025-01-01")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_input_metrics_delta() {
+        let earlier = InputMetrics {
+            key_presses: 100,
+            clicks: 10,
+            scrolls: 50,
+        };
+        let later = InputMetrics {
+            key_presses: 150,
+            clicks: 12,
+            scrolls: 5,
+        };
+        assert_eq!(
+            later.delta_since(&earlier),
+            InputMetrics {
+                key_presses: 50,
+                clicks: 2,
+                scrolls: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_latest_capture() {
+        let (db, _temp_dir) = create_test_db();
+        assert!(db.get_latest_capture().unwrap().is_none());
+
+        db.insert_capture(&sample_record("2024-12-30T10:00:00"))
+            .unwrap();
+        let id = db
+            .insert_capture(&sample_record("2024-12-30T10:01:00"))
+            .unwrap();
+
+        assert_eq!(db.get_latest_capture().unwrap().unwrap().id, Some(id));
+
+        db.insert_capture(&CaptureRecord {
+            is_paused: true,
+            ..sample_record("2024-12-30T10:02:00")
+        })
+        .unwrap();
+        assert_eq!(db.get_latest_capture().unwrap().unwrap().id, Some(id));
+    }
+
+    #[test]
+    fn test_ocr_details_roundtrip() {
+        let (db, _temp_dir) = create_test_db();
+
+        let id = db
+            .insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: "2024-12-30T10:00:00".to_string(),
+                image_path: Some("/path/1.jpg".to_string()),
+                active_app: "VS Code".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        assert_eq!(db.get_ocr_details(id).unwrap(), None);
+
+        db.update_ocr_details(id, r#"[{"text":"a"}]"#).unwrap();
+        assert_eq!(
+            db.get_ocr_details(id).unwrap().as_deref(),
+            Some(r#"[{"text":"a"}]"#)
+        );
+
+        assert_eq!(db.get_extra(id).unwrap(), None);
+        db.update_extra(id, r#"{"git_branch":"main"}"#).unwrap();
+        assert_eq!(
+            db.get_extra(id).unwrap().as_deref(),
+            Some(r#"{"git_branch":"main"}"#)
+        );
+    }
+
+    #[test]
+    fn test_get_captures_for_ocr_redo() {
+        let (db, _temp_dir) = create_test_db();
+
+        for (time, app, image) in [
+            ("2025-01-10T10:00:00", "Safari", Some("/path/1.jpg")),
+            ("2025-01-10T11:00:00", "VS Code", Some("/path/2.jpg")),
+            ("2025-01-10T12:00:00", "Safari", None),
+            ("2025-01-11T10:00:00", "Safari", Some("/path/3.jpg")),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: image.map(|p| p.to_string()),
+                active_app: app.to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: Some("old".to_string()),
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+
+        let by_date = db
+            .get_captures_for_ocr_redo(Some("2025-01-10"), None, -1)
+            .unwrap();
+        assert_eq!(by_date.len(), 2);
+
+        let by_where = db
+            .get_captures_for_ocr_redo(None, Some("app = 'Safari'"), -1)
+            .unwrap();
+        assert_eq!(by_where.len(), 2);
+
+        let both = db
+            .get_captures_for_ocr_redo(Some("2025-01-10"), Some("app = 'Safari'"), -1)
+            .unwrap();
+        assert_eq!(both.len(), 1);
+        assert_eq!(both[0].image_path.as_deref(), Some("/path/1.jpg"));
+
+        // 複数文の注入は拒否される
+        assert!(db
+            .get_captures_for_ocr_redo(None, Some("1); DELETE FROM captures; --"), -1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_private_captures_excluded_from_ocr() {
+        let (db, _temp_dir) = create_test_db();
+
+        for (time, is_private, text) in [
+            ("2024-12-30T10:00:00", true, None),
+            ("2024-12-30T10:01:00", false, None),
+            ("2024-12-30T10:02:00", true, Some("secret")),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: Some(format!("/path/{}.jpg", time)),
+                active_app: "Safari".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private,
+                ocr_text: text.map(|t| t.to_string()),
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+
+        let pending = db.get_captures_without_ocr(100).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert!(!pending[0].is_private);
+
+        let redo = db
+            .get_captures_for_ocr_redo(Some("2024-12-30"), None, -1)
+            .unwrap();
+        assert_eq!(redo.len(), 1);
+
+        assert!(db.search_ocr_text("secret", 10).unwrap().is_empty());
+
+        assert_eq!(db.scrub_private_ocr().unwrap(), 1);
+        assert_eq!(db.scrub_private_ocr().unwrap(), 0);
+        let scrubbed = db.get_captures_by_date("2024-12-30").unwrap();
+        assert!(scrubbed
+            .iter()
+            .all(|c| !c.is_private || c.ocr_text.is_none()));
+    }
+
+    /// それらしいOCRテキスト（行ごとに少しずつ異なる）
+    fn sample_ocr_text(i: usize) -> String {
+        format!(
+            "File Edit View Selection Go Run Terminal Help\nsrc/database.rs - habit-tracker\n\
+             fn insert_capture(&self, record: &CaptureRecord) -> Result<i64, DatabaseError> {{\n\
+             let mut stmt = self.conn.prepare_cached(INSERT_CAPTURE_SQL)?;\n\
+             行 {} 列 {}  UTF-8  Rust  {}件の問題",
+            i,
+            i % 80,
+            i % 7
+        )
     }
 
     #[test]
-    fn test_database_open_creates_schema() {
+    fn test_ocr_text_compression_is_transparent() {
         let (db, _temp_dir) = create_test_db();
+        let long_text = sample_ocr_text(1).repeat(3);
 
-        // テーブルが存在することを確認
-        let count: i64 = db
+        let mut record = sample_record("2024-12-30T10:00:00+09:00");
+        record.ocr_text = Some(long_text.clone());
+        let id = db.insert_capture(&record).unwrap();
+
+        // 長いテキストはBLOBとして保存される
+        let stored: String = db
             .conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='captures'",
-                [],
+                "SELECT typeof(ocr_text) FROM captures WHERE id = ?1",
+                [id],
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 1);
+        assert_eq!(stored, "blob");
+
+        // 読み出し・検索では平文として扱える
+        assert_eq!(
+            db.get_capture(id).unwrap().unwrap().ocr_text,
+            Some(long_text)
+        );
+        assert_eq!(db.search_ocr_text("prepare_cached", 10).unwrap().len(), 1);
+
+        // 短いテキストは平文のまま
+        db.update_ocr_text(id, "short").unwrap();
+        assert_eq!(
+            db.get_capture(id).unwrap().unwrap().ocr_text.as_deref(),
+            Some("short")
+        );
     }
 
     #[test]
-    fn test_insert_capture() {
-        let (db, _temp_dir) = create_test_db();
+    fn test_ocr_dictionary_training_and_recompress() {
+        let (db, temp_dir) = create_test_db();
+        assert_eq!(db.train_ocr_dictionary().unwrap(), None);
 
-        let record = CaptureRecord {
-            id: None,
-            captured_at: "2024-12-30T10:00:00".to_string(),
-            image_path: Some("/path/to/image.jpg".to_string()),
-            active_app: "VS Code".to_string(),
-            window_title: "main.rs".to_string(),
-            is_paused: false,
-            is_private: false,
-            ocr_text: None,
-        };
+        for i in 0..200 {
+            let mut record =
+                sample_record(&format!("2024-12-30T10:{:02}:{:02}+09:00", i / 60, i % 60));
+            record.ocr_text = Some(sample_ocr_text(i));
+            db.insert_capture(&record).unwrap();
+        }
+
+        let dict_id = db.train_ocr_dictionary().unwrap();
+        assert!(dict_id.is_some());
+
+        let stats = db.recompress_ocr_text().unwrap();
+        assert_eq!(stats.rows, 200);
+        assert!(stats.bytes_after * 2 < stats.bytes_before);
+
+        // 開き直しても辞書を読み込んで展開できる
+        drop(db);
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let captures = db
+            .get_captures_between("2024-12-30T10:00:00", "2024-12-30T11:00:00")
+            .unwrap();
+        assert_eq!(captures[5].ocr_text, Some(sample_ocr_text(5)));
+        assert_eq!(db.search_ocr_text("行 150 列", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_find_ocr_by_image_hash() {
+        let (db, _temp_dir) = create_test_db();
 
+        let mut record = sample_record("2024-12-30T10:00:00+09:00");
+        record.ocr_text = Some("fn main() {}".to_string());
         let id = db.insert_capture(&record).unwrap();
-        assert!(id > 0);
+        db.update_image_hash(id, "abc").unwrap();
+        db.update_ocr_details(id, "[]").unwrap();
+
+        // プライベートなキャプチャの結果は使わない
+        let mut private = sample_record("2024-12-30T10:01:00+09:00");
+        private.ocr_text = Some("secret".to_string());
+        private.is_private = true;
+        let private_id = db.insert_capture(&private).unwrap();
+        db.update_image_hash(private_id, "def").unwrap();
+
+        assert_eq!(
+            db.find_ocr_by_image_hash("abc").unwrap(),
+            Some(("fn main() {}".to_string(), Some("[]".to_string())))
+        );
+        assert_eq!(db.find_ocr_by_image_hash("def").unwrap(), None);
+        assert_eq!(db.find_ocr_by_image_hash("missing").unwrap(), None);
     }
 
     #[test]
-    fn test_get_captures_by_date() {
+    fn test_ocr_boilerplate_roundtrip() {
         let (db, _temp_dir) = create_test_db();
 
-        // テストデータを挿入
-        let records = vec![
-            CaptureRecord {
-                id: None,
-                captured_at: "2024-12-30T10:00:00".to_string(),
-                image_path: Some("/path/1.jpg".to_string()),
-                active_app: "VS Code".to_string(),
-                window_title: "file1.rs".to_string(),
-                is_paused: false,
-                is_private: false,
-                ocr_text: None,
-            },
-            CaptureRecord {
-                id: None,
-                captured_at: "2024-12-30T11:00:00".to_string(),
-                image_path: Some("/path/2.jpg".to_string()),
-                active_app: "Chrome".to_string(),
-                window_title: "Google".to_string(),
-                is_paused: false,
-                is_private: false,
-                ocr_text: None,
-            },
-            CaptureRecord {
-                id: None,
-                captured_at: "2024-12-31T10:00:00".to_string(),
-                image_path: Some("/path/3.jpg".to_string()),
-                active_app: "Terminal".to_string(),
-                window_title: "".to_string(),
-                is_paused: false,
-                is_private: false,
-                ocr_text: None,
-            },
-        ];
+        db.add_ocr_boilerplate(&["Wi-Fi".to_string(), "Finder".to_string()])
+            .unwrap();
+        // 既存の行は無視する
+        db.add_ocr_boilerplate(&["Finder".to_string()]).unwrap();
 
-        for record in &records {
-            db.insert_capture(record).unwrap();
+        assert_eq!(
+            db.get_ocr_boilerplate().unwrap(),
+            vec!["Finder".to_string(), "Wi-Fi".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_ocr_failure_backoff_and_give_up() {
+        let (db, _temp_dir) = create_test_db();
+        let mut record = sample_record("2024-12-30T10:00:00+09:00");
+        record.image_path = Some("/path/1.jpg".to_string());
+        let id = db.insert_capture(&record).unwrap();
+
+        // 再試行時刻まではバッチの対象にならない
+        let now = Local::now();
+        let status = db.record_ocr_failure(id, "timeout", &now).unwrap();
+        assert_eq!(status, OcrJobStatus::Retrying);
+        assert!(db.get_captures_without_ocr(10).unwrap().is_empty());
+
+        let failures = db.get_ocr_failures(10).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].attempts, 1);
+        assert_eq!(failures[0].last_error.as_deref(), Some("timeout"));
+        assert_eq!(
+            failures[0].next_attempt_at,
+            Some(format_timestamp(&(now + chrono::Duration::minutes(5))))
+        );
+
+        // 待ち時間を過ぎれば再び対象になり、待ち時間は倍になる
+        let past = now - chrono::Duration::days(1);
+        db.record_ocr_failure(id, "timeout", &past).unwrap();
+        assert_eq!(db.get_captures_without_ocr(10).unwrap().len(), 1);
+        assert_eq!(
+            db.get_ocr_failures(10).unwrap()[0].next_attempt_at,
+            Some(format_timestamp(&(past + chrono::Duration::minutes(10))))
+        );
+
+        // 上限に達すると打ち切り
+        for _ in 2..OCR_MAX_ATTEMPTS - 1 {
+            db.record_ocr_failure(id, "timeout", &past).unwrap();
         }
+        let status = db.record_ocr_failure(id, "broken", &past).unwrap();
+        assert_eq!(status, OcrJobStatus::Failed);
+        assert!(db.get_captures_without_ocr(10).unwrap().is_empty());
 
-        // 2024-12-30のレコードを取得
-        let result = db.get_captures_by_date("2024-12-30").unwrap();
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0].active_app, "VS Code");
-        assert_eq!(result[1].active_app, "Chrome");
+        // 成功すると失敗一覧から消える
+        db.record_ocr_success(id, &format_timestamp(&now)).unwrap();
+        assert!(db.get_ocr_failures(10).unwrap().is_empty());
     }
 
     #[test]
-    fn test_get_captures_empty_date() {
+    fn test_captures_without_ocr_after_and_progress() {
         let (db, _temp_dir) = create_test_db();
+        let mut ids = Vec::new();
+        for i in 0..4 {
+            let mut record = sample_record(&format!("2024-12-30T10:0{}:00+09:00", i));
+            record.image_path = Some(format!("/path/{}.jpg", i));
+            ids.push(db.insert_capture(&record).unwrap());
+        }
+        db.update_ocr_text(ids[2], "done").unwrap();
 
-        let result = db.get_captures_by_date("2099-01-01").unwrap();
-        assert!(result.is_empty());
+        // 処理済みを除き、IDの古い順に取得する
+        let captures = db.get_captures_without_ocr_after(ids[0], 10).unwrap();
+        let found: Vec<_> = captures.iter().filter_map(|c| c.id).collect();
+        assert_eq!(found, vec![ids[1], ids[3]]);
+        assert_eq!(db.count_captures_without_ocr_after(0).unwrap(), 3);
+        assert_eq!(db.get_captures_without_ocr_after(0, 1).unwrap().len(), 1);
+
+        assert_eq!(db.get_ocr_batch_progress().unwrap(), None);
+        let progress = OcrBatchProgress {
+            last_capture_id: ids[1],
+            processed: 2,
+            started_at: "2024-12-30T12:00:00+09:00".to_string(),
+        };
+        db.save_ocr_batch_progress(&progress).unwrap();
+        db.save_ocr_batch_progress(&OcrBatchProgress {
+            processed: 3,
+            ..progress.clone()
+        })
+        .unwrap();
+        assert_eq!(db.get_ocr_batch_progress().unwrap().unwrap().processed, 3);
+        db.clear_ocr_batch_progress().unwrap();
+        assert_eq!(db.get_ocr_batch_progress().unwrap(), None);
     }
 
     #[test]
-    fn test_insert_with_null_image_path() {
+    fn test_delete_captures() {
         let (db, _temp_dir) = create_test_db();
+        let id = db
+            .insert_capture(&sample_record("2024-12-30T10:00:00"))
+            .unwrap();
+        let kept = db
+            .insert_capture(&sample_record("2024-12-30T10:01:00"))
+            .unwrap();
+        db.insert_input_metrics(id, &InputMetrics::default())
+            .unwrap();
+        db.insert_perf_metrics(id, &PerfMetrics::default()).unwrap();
+        db.insert_perf_metrics(kept, &PerfMetrics::default())
+            .unwrap();
 
-        let record = CaptureRecord {
+        assert_eq!(db.delete_captures(&[id, id + 100]).unwrap(), 1);
+        assert!(db.get_capture(id).unwrap().is_none());
+        assert!(db.get_input_metrics(id).unwrap().is_none());
+        assert!(db.get_capture(kept).unwrap().is_some());
+        assert_eq!(
+            db.get_perf_metrics_between("2024-12-30T00:00:00", "2024-12-31T00:00:00")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    fn sample_record(captured_at: &str) -> CaptureRecord {
+        CaptureRecord {
             id: None,
-            captured_at: "2024-12-30T10:00:00".to_string(),
+            captured_at: captured_at.to_string(),
             image_path: None,
             active_app: "VS Code".to_string(),
             window_title: "".to_string(),
-            is_paused: true,
+            is_paused: false,
             is_private: false,
             ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        }
+    }
+
+    #[test]
+    fn test_insert_captures_batch() {
+        let (db, _temp_dir) = create_test_db();
+
+        let records: Vec<_> = (0..3)
+            .map(|i| sample_record(&format!("2024-12-30T10:0{}:00", i)))
+            .collect();
+        let ids = db.insert_captures(&records).unwrap();
+
+        assert_eq!(ids.len(), 3);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(db.get_captures_by_date("2024-12-30").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_import_captures_is_idempotent() {
+        let (db, _temp_dir) = create_test_db();
+
+        let records: Vec<_> = (0..3)
+            .map(|i| sample_record(&format!("2024-12-30T10:0{}:00", i)))
+            .collect();
+        assert_eq!(db.import_captures(&records).unwrap().len(), 3);
+
+        // 同じレコードを再度インポートしても増えない
+        assert!(db.import_captures(&records).unwrap().is_empty());
+        assert_eq!(db.get_captures_by_date("2024-12-30").unwrap().len(), 3);
+
+        // アプリが違えば同じ時刻でも取り込む
+        let other = CaptureRecord {
+            active_app: "Safari".to_string(),
+            ..sample_record("2024-12-30T10:00:00")
         };
+        assert_eq!(db.import_captures(&[other]).unwrap().len(), 1);
+    }
 
-        let id = db.insert_capture(&record).unwrap();
-        assert!(id > 0);
+    #[test]
+    fn test_with_transaction_rollback() {
+        let (db, _temp_dir) = create_test_db();
 
-        let result = db.get_captures_by_date("2024-12-30").unwrap();
-        assert_eq!(result.len(), 1);
-        assert!(result[0].image_path.is_none());
-        assert!(result[0].is_paused);
+        let result: Result<(), DatabaseError> = db.with_transaction(|db| {
+            db.insert_capture(&sample_record("2024-12-30T10:00:00"))?;
+            Err(DatabaseError::MigrationError("中断".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert!(db.get_captures_by_date("2024-12-30").unwrap().is_empty());
     }
 
     #[test]
-    fn test_wal_mode_enabled() {
+    fn test_with_transaction_rollback_on_release_failure() {
         let (db, _temp_dir) = create_test_db();
+        db.conn
+            .execute_batch(
+                "PRAGMA foreign_keys = ON;
+                 CREATE TEMP TABLE parent (id INTEGER PRIMARY KEY);
+                 CREATE TEMP TABLE child (
+                     parent_id INTEGER REFERENCES parent(id) DEFERRABLE INITIALLY DEFERRED
+                 );",
+            )
+            .unwrap();
 
-        let mode: String = db
+        // 遅延した外部キー制約の違反で最外のRELEASE（コミット）が失敗する
+        let result: Result<(), DatabaseError> = db.with_transaction(|db| {
+            db.insert_capture(&sample_record("2024-12-30T10:00:00"))?;
+            db.conn
+                .execute_batch("INSERT INTO child (parent_id) VALUES (1)")?;
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert!(db.conn.is_autocommit());
+        assert!(db.get_captures_by_date("2024-12-30").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_with_transaction_nested() {
+        let (db, _temp_dir) = create_test_db();
+
+        db.with_transaction(|db| {
+            db.insert_capture(&sample_record("2024-12-30T10:00:00"))?;
+            // 内側の失敗は内側の変更だけを取り消す
+            let inner: Result<(), DatabaseError> = db.with_transaction(|db| {
+                db.insert_capture(&sample_record("2024-12-30T10:01:00"))?;
+                Err(DatabaseError::MigrationError("内側".to_string()))
+            });
+            assert!(inner.is_err());
+            db.insert_captures(&[sample_record("2024-12-30T10:02:00")])?;
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap();
+
+        let captures = db.get_captures_by_date("2024-12-30").unwrap();
+        let times: Vec<_> = captures.iter().map(|c| c.captured_at.as_str()).collect();
+        assert_eq!(times, ["2024-12-30T10:00:00", "2024-12-30T10:02:00"]);
+    }
+
+    #[test]
+    fn test_legacy_timestamps_migrated_on_open() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        {
+            let db = Database::open(&db_path).unwrap();
+            db.insert_capture(&sample_record("2024-12-30T10:00:00"))
+                .unwrap();
+            db.insert_capture(&sample_record("2024-12-30T11:00:00+09:00"))
+                .unwrap();
+            // 旧バージョンのDBを再現
+            db.conn.execute_batch("PRAGMA user_version = 0").unwrap();
+        }
+
+        let db = Database::open(&db_path).unwrap();
+        let captures = db.get_captures_by_date("2024-12-30").unwrap();
+
+        let expected = format_timestamp(&Local.with_ymd_and_hms(2024, 12, 30, 10, 0, 0).unwrap());
+        assert_eq!(captures[0].captured_at, expected);
+        // 既にRFC3339の値は変更しない
+        assert_eq!(captures[1].captured_at, "2024-12-30T11:00:00+09:00");
+
+        let version: i32 = db
             .conn
-            .query_row("PRAGMA journal_mode;", [], |row| row.get(0))
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(mode.to_lowercase(), "wal");
+        assert_eq!(version, SCHEMA_VERSION);
     }
 
     #[test]
-    fn test_index_exists() {
+    fn test_newer_schema_version_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        {
+            let db = Database::open(&db_path).unwrap();
+            db.conn
+                .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1))
+                .unwrap();
+        }
+
+        assert!(matches!(
+            Database::open(&db_path),
+            Err(DatabaseError::MigrationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let parsed = parse_timestamp("2025-01-10T10:00:00+09:00").unwrap();
+        assert_eq!(parsed.offset().local_minus_utc(), 9 * 3600);
+        assert_eq!(parsed.format("%H:%M:%S").to_string(), "10:00:00");
+
+        let legacy = parse_timestamp("2025-01-10T10:00:00").unwrap();
+        assert_eq!(
+            legacy.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            "2025-01-10T10:00:00"
+        );
+
+        assert!(parse_timestamp("invalid").is_none());
+    }
+
+    #[test]
+    fn test_get_captures_between() {
         let (db, _temp_dir) = create_test_db();
+        for time in [
+            "2025-01-10T03:59:59+09:00",
+            "2025-01-10T04:00:00+09:00",
+            "2025-01-11T03:59:59",
+            "2025-01-11T04:00:00+09:00",
+        ] {
+            db.insert_capture(&sample_record(time)).unwrap();
+        }
 
-        let count: i64 = db
+        let result = db
+            .get_captures_between("2025-01-10T04:00:00", "2025-01-11T04:00:00")
+            .unwrap();
+        let times: Vec<_> = result.iter().map(|c| c.captured_at.as_str()).collect();
+        assert_eq!(times, ["2025-01-10T04:00:00+09:00", "2025-01-11T03:59:59"]);
+    }
+
+    #[test]
+    fn test_extra_values() {
+        let (db, _temp_dir) = create_test_db();
+        let ids: Vec<i64> = [
+            "2025-01-10T09:00:00+09:00",
+            "2025-01-10T09:01:00+09:00",
+            "2025-01-10T09:02:00+09:00",
+            "2025-01-11T09:00:00+09:00",
+        ]
+        .iter()
+        .map(|time| db.insert_capture(&sample_record(time)).unwrap())
+        .collect();
+
+        db.update_extra(ids[0], r#"{"git_branch":"main"}"#).unwrap();
+        db.set_extra_value(ids[0], "ticket.id", &serde_json::json!(42))
+            .unwrap();
+        db.set_extra_value(ids[1], "git_branch", &serde_json::json!("main"))
+            .unwrap();
+        db.set_extra_value(ids[2], "git_branch", &serde_json::json!("fix"))
+            .unwrap();
+        db.set_extra_value(ids[3], "git_branch", &serde_json::json!("main"))
+            .unwrap();
+
+        // 他のキーは残り、`.` を含むキーも1つのキーとして扱う
+        assert_eq!(
+            db.get_extra_value(ids[0], "git_branch").unwrap(),
+            Some(serde_json::json!("main"))
+        );
+        assert_eq!(
+            db.get_extra_value(ids[0], "ticket.id").unwrap(),
+            Some(serde_json::json!(42))
+        );
+        assert_eq!(db.get_extra_value(ids[1], "ticket.id").unwrap(), None);
+
+        let found = db
+            .find_captures_by_extra(
+                "git_branch",
+                &serde_json::json!("main"),
+                "2025-01-10T04:00:00",
+                "2025-01-11T04:00:00",
+            )
+            .unwrap();
+        let found_ids: Vec<_> = found.iter().filter_map(|c| c.id).collect();
+        assert_eq!(found_ids, vec![ids[0], ids[1]]);
+        assert_eq!(
+            db.find_captures_by_extra(
+                "ticket.id",
+                &serde_json::json!(42),
+                "2025-01-10T04:00:00",
+                "2025-01-11T04:00:00",
+            )
+            .unwrap()
+            .len(),
+            1
+        );
+
+        assert_eq!(
+            db.count_extra_values("git_branch", "2025-01-10T04:00:00", "2025-01-11T04:00:00")
+                .unwrap(),
+            vec![(r#""main""#.to_string(), 2), (r#""fix""#.to_string(), 1)]
+        );
+
+        db.remove_extra_value(ids[0], "git_branch").unwrap();
+        assert_eq!(db.get_extra_value(ids[0], "git_branch").unwrap(), None);
+        assert_eq!(
+            db.get_extra(ids[0]).unwrap().as_deref(),
+            Some(r#"{"ticket.id":42}"#)
+        );
+    }
+
+    #[test]
+    fn test_capture_environment_roundtrip() {
+        let (db, _temp_dir) = create_test_db();
+        let id = db
+            .insert_capture(&CaptureRecord {
+                hostname: Some("mac".to_string()),
+                tracker_version: Some("0.1.0".to_string()),
+                bundle_id: Some("com.microsoft.VSCode".to_string()),
+                activity_type: None,
+                working_directory: Some("/Users/me/src/habit-tracker".to_string()),
+                ..sample_record("2025-01-10T09:00:00+09:00")
+            })
+            .unwrap();
+        let legacy = db
+            .insert_capture(&sample_record("2025-01-10T09:01:00+09:00"))
+            .unwrap();
+
+        let record = db.get_capture(id).unwrap().unwrap();
+        assert_eq!(record.hostname.as_deref(), Some("mac"));
+        assert_eq!(record.tracker_version.as_deref(), Some("0.1.0"));
+        assert_eq!(record.bundle_id.as_deref(), Some("com.microsoft.VSCode"));
+        assert_eq!(
+            record.working_directory.as_deref(),
+            Some("/Users/me/src/habit-tracker")
+        );
+        let record = db.get_capture(legacy).unwrap().unwrap();
+        assert_eq!(record.hostname, None);
+        assert_eq!(record.tracker_version, None);
+        assert_eq!(record.bundle_id, None);
+        assert_eq!(record.working_directory, None);
+    }
+
+    #[test]
+    fn test_stored_images() {
+        let (db, _temp_dir) = create_test_db();
+        let id = db
+            .insert_capture(&CaptureRecord {
+                image_path: Some("/images/1.jpg".to_string()),
+                ..sample_record("2025-01-10T09:00:00+09:00")
+            })
+            .unwrap();
+        db.insert_capture(&CaptureRecord {
+            image_path: Some("/images/2.jpg".to_string()),
+            ..sample_record("2025-01-10T09:01:00+09:00")
+        })
+        .unwrap();
+        // 画像のないキャプチャは含まない
+        db.insert_capture(&sample_record("2025-01-10T09:02:00+09:00"))
+            .unwrap();
+
+        db.update_image_info(
+            id,
+            &ImageInfo {
+                bytes: 204_800,
+                width: Some(1920),
+                height: Some(1080),
+            },
+        )
+        .unwrap();
+
+        let images = db
+            .get_stored_images_between("2025-01-10", "2025-01-11")
+            .unwrap();
+        assert_eq!(
+            images,
+            vec![
+                StoredImage {
+                    captured_at: "2025-01-10T09:00:00+09:00".to_string(),
+                    image_path: "/images/1.jpg".to_string(),
+                    bytes: Some(204_800),
+                },
+                StoredImage {
+                    captured_at: "2025-01-10T09:01:00+09:00".to_string(),
+                    image_path: "/images/2.jpg".to_string(),
+                    bytes: None,
+                },
+            ]
+        );
+        let (width, height): (u32, u32) = db
             .conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='index' AND name='idx_captures_captured_at'",
-                [],
-                |row| row.get(0),
+                "SELECT image_width, image_height FROM captures WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
             .unwrap();
-        assert_eq!(count, 1);
+        assert_eq!((width, height), (1920, 1080));
+    }
+
+    #[test]
+    fn test_for_each_capture_between() {
+        let (db, _temp_dir) = create_test_db();
+        for minute in 0..5 {
+            db.insert_capture(&sample_record(&format!(
+                "2025-01-10T09:0{}:00+09:00",
+                minute
+            )))
+            .unwrap();
+        }
+
+        let mut times = Vec::new();
+        db.for_each_capture_between("2025-01-10T09:01:00", "2025-01-10T09:04:00", |c| {
+            times.push(c.captured_at);
+            Ok::<_, DatabaseError>(())
+        })
+        .unwrap();
+        assert_eq!(
+            times,
+            [
+                "2025-01-10T09:01:00+09:00",
+                "2025-01-10T09:02:00+09:00",
+                "2025-01-10T09:03:00+09:00"
+            ]
+        );
+
+        // コールバックのエラーで打ち切る
+        let mut visited = 0;
+        let result = db.for_each_capture_between("2025-01-10", "2025-01-11", |_| {
+            visited += 1;
+            if visited == 2 {
+                return Err(DatabaseError::MigrationError("stop".to_string()));
+            }
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(visited, 2);
+    }
+
+    #[test]
+    fn test_get_captures_by_app() {
+        let (db, _temp_dir) = create_test_db();
+        for (time, app) in [
+            ("2025-01-10T09:00:00+09:00", "VS Code"),
+            ("2025-01-10T09:01:00+09:00", "Slack"),
+            ("2025-01-10T09:02:00+09:00", "VS Code"),
+            ("2025-01-11T09:00:00+09:00", "VS Code"),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                active_app: app.to_string(),
+                ..sample_record(time)
+            })
+            .unwrap();
+        }
+
+        let result = db
+            .get_captures_by_app("VS Code", "2025-01-10", "2025-01-11")
+            .unwrap();
+        let times: Vec<_> = result.iter().map(|c| c.captured_at.as_str()).collect();
+        assert_eq!(
+            times,
+            ["2025-01-10T09:00:00+09:00", "2025-01-10T09:02:00+09:00"]
+        );
+        assert!(db
+            .get_captures_by_app("Xcode", "2025-01-10", "2025-01-12")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_count_by_date() {
+        let (db, _temp_dir) = create_test_db();
+        for time in [
+            "2025-01-09T23:59:59+09:00",
+            "2025-01-10T09:00:00+09:00",
+            "2025-01-10T18:00:00+09:00",
+            "2025-01-12T09:00:00+09:00",
+        ] {
+            db.insert_capture(&sample_record(time)).unwrap();
+        }
+
+        let counts = db.count_by_date("2025-01-10", "2025-01-13").unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                DateCount {
+                    date: "2025-01-10".to_string(),
+                    count: 2
+                },
+                DateCount {
+                    date: "2025-01-12".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(db.count_captures().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_captures_page() {
+        let (db, _temp_dir) = create_test_db();
+        for minute in 0..5 {
+            db.insert_capture(&sample_record(&format!(
+                "2025-01-10T09:0{}:00+09:00",
+                minute
+            )))
+            .unwrap();
+        }
+
+        let first = db.get_captures_page(2, 0).unwrap();
+        let times: Vec<_> = first
+            .captures
+            .iter()
+            .map(|c| c.captured_at.as_str())
+            .collect();
+        assert_eq!(
+            times,
+            ["2025-01-10T09:04:00+09:00", "2025-01-10T09:03:00+09:00"]
+        );
+        assert_eq!(first.total, 5);
+        assert!(first.has_more());
+
+        let last = db.get_captures_page(2, 4).unwrap();
+        assert_eq!(last.captures.len(), 1);
+        assert_eq!(last.captures[0].captured_at, "2025-01-10T09:00:00+09:00");
+        assert!(!last.has_more());
+
+        let latest = db.get_latest_captures(3).unwrap();
+        assert_eq!(latest.len(), 3);
+        assert_eq!(latest[0].captured_at, "2025-01-10T09:04:00+09:00");
+    }
+
+    #[test]
+    fn test_search_ocr_text() {
+        let (db, _temp_dir) = create_test_db();
+
+        for (time, text) in [
+            ("2024-12-30T10:00:00", Some("cargo build 100% done")),
+            ("2024-12-30T10:01:00", Some("Pull Request #42")),
+            ("2024-12-30T10:02:00", None),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: None,
+                active_app: "Terminal".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: text.map(|t| t.to_string()),
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+
+        let result = db.search_ocr_text("request", 10).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].captured_at, "2024-12-30T10:01:00");
+
+        // `%` はワイルドカードではなくリテラルとして扱う
+        let result = db.search_ocr_text("100%", 10).unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(db.search_ocr_text("%%", 10).unwrap().is_empty());
     }
 }
@@ -0,0 +1,111 @@
+//! DB書き込みモジュール - 書き込みを専用スレッドに集約し、チャネル経由でコマンドを受け付ける
+
+use crate::database::Database;
+use crate::error::DatabaseError;
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// 書き込み専用スレッドで実行するコマンド
+type WriteCommand = Box<dyn FnOnce(&Database) + Send>;
+
+/// DB書き込み専用スレッドへのハンドル
+///
+/// SQLiteの接続は1つのスレッドからしか使えないため、書き込みは専用スレッドが持つ接続に集約し、
+/// キャプチャループ・OCRワーカー・アプリ切り替え監視などからはチャネル経由でコマンドを送る。
+/// ハンドルは複製して共有でき、すべてドロップされると専用スレッドは受け取り済みのコマンドを
+/// 実行してから終了する。読み取りはそれぞれの接続で行ってよい（WALモード）
+#[derive(Clone)]
+pub struct DatabaseWriter {
+    sender: Sender<WriteCommand>,
+}
+
+impl DatabaseWriter {
+    /// DBを開き、書き込み専用スレッドを起動
+    pub fn spawn(db_path: &Path) -> Result<Self, DatabaseError> {
+        Ok(Self::from_database(Database::open(db_path)?))
+    }
+
+    /// 開いた接続を渡して書き込み専用スレッドを起動
+    pub fn from_database(db: Database) -> Self {
+        let (sender, receiver) = mpsc::channel::<WriteCommand>();
+        thread::spawn(move || {
+            for command in receiver {
+                command(&db);
+            }
+        });
+        Self { sender }
+    }
+
+    /// 書き込み専用スレッドの接続で `f` を実行し、結果を待つ
+    ///
+    /// 同じハンドルから送ったコマンドは送った順に実行される
+    pub fn execute<T, F>(&self, f: F) -> Result<T, DatabaseError>
+    where
+        F: FnOnce(&Database) -> Result<T, DatabaseError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (reply, result) = mpsc::sync_channel(1);
+        let command: WriteCommand = Box::new(move |db| {
+            // 呼び出し元が待つのをやめていれば結果は捨てる
+            let _ = reply.send(f(db));
+        });
+        self.sender
+            .send(command)
+            .map_err(|_| DatabaseError::WriterStopped)?;
+        result.recv().map_err(|_| DatabaseError::WriterStopped)?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::AppEvent;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_execute_from_threads() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let writer = DatabaseWriter::spawn(&db_path).unwrap();
+
+        // 別のスレッドからも同じ接続で書き込める
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let writer = writer.clone();
+                thread::spawn(move || {
+                    writer
+                        .execute(move |db| {
+                            db.insert_app_event(&AppEvent {
+                                id: None,
+                                occurred_at: format!("2025-01-10T10:00:0{}+09:00", i),
+                                app_name: "VS Code".to_string(),
+                                bundle_id: String::new(),
+                            })
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // 書き込みは結果を返した時点で反映済みで、別の接続から読める
+        let db = Database::open(&db_path).unwrap();
+        let events = db
+            .get_app_events_between("2025-01-10T00:00:00", "2025-01-11T00:00:00")
+            .unwrap();
+        assert_eq!(events.len(), 4);
+    }
+
+    #[test]
+    fn test_execute_returns_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let writer = DatabaseWriter::spawn(&temp_dir.path().join("test.db")).unwrap();
+        let result = writer.execute(|db| db.update_ocr_text(1, "text").map(|_| "done"));
+        assert_eq!(result.unwrap(), "done");
+        let result: Result<(), _> = writer.execute(|_| Err(DatabaseError::WriterStopped));
+        assert!(matches!(result, Err(DatabaseError::WriterStopped)));
+    }
+}
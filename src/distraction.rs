@@ -0,0 +1,145 @@
+//! 脱線検知モジュール - 作業時間帯の脱線アプリの連続利用の計測と警告の判定
+
+use crate::break_reminder::BREAK_GAP_SECONDS;
+use crate::category::Distractions;
+use chrono::{DateTime, Local, Timelike};
+
+/// 脱線アプリを使い続けている区間
+#[derive(Debug)]
+struct Streak {
+    started_at: DateTime<Local>,
+    last_seen: DateTime<Local>,
+}
+
+/// 脱線の監視
+///
+/// キャプチャのたびに呼び出し、作業時間帯に脱線アプリ（複数を行き来しても同じ）を
+/// 設定の時間だけ使い続けたら通知するメッセージを返す。警告した時点から数え直す
+#[derive(Debug)]
+pub struct DistractionMonitor {
+    distractions: Distractions,
+    warn_minutes: u64,
+    streak: Option<Streak>,
+}
+
+impl DistractionMonitor {
+    /// 新しいDistractionMonitorを作成
+    pub fn new(distractions: Distractions, warn_minutes: u64) -> Self {
+        Self {
+            distractions,
+            warn_minutes,
+            streak: None,
+        }
+    }
+
+    /// 一時停止・スリープなどで利用が途切れたことを記録
+    pub fn reset(&mut self) {
+        self.streak = None;
+    }
+
+    /// キャプチャを記録し、警告するならそのメッセージを返す
+    pub fn observe(
+        &mut self,
+        now: DateTime<Local>,
+        app: &str,
+        bundle_id: Option<&str>,
+        window_title: &str,
+    ) -> Option<String> {
+        let name = if self.distractions.is_work_hour(now.hour()) {
+            self.distractions.find(app, bundle_id, window_title)
+        } else {
+            None
+        };
+        let Some(name) = name else {
+            self.streak = None;
+            return None;
+        };
+
+        let streak = match self.streak {
+            Some(ref mut streak) if (now - streak.last_seen).num_seconds() <= BREAK_GAP_SECONDS => {
+                streak
+            }
+            _ => {
+                self.streak = Some(Streak {
+                    started_at: now,
+                    last_seen: now,
+                });
+                return None;
+            }
+        };
+        streak.last_seen = now;
+        let minutes = (now - streak.started_at).num_minutes();
+        if minutes < self.warn_minutes as i64 {
+            return None;
+        }
+
+        streak.started_at = now;
+        Some(format!(
+            "作業時間中に{}を{}分使っています。作業に戻りませんか？",
+            name, minutes
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn monitor() -> DistractionMonitor {
+        let distractions = Distractions::new(vec!["Twitter".to_string(), "YouTube".to_string()])
+            .with_work_hours(9, 18);
+        DistractionMonitor::new(distractions, 10)
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 10, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_warn_after_threshold() {
+        let mut monitor = monitor();
+        for minute in 0..10 {
+            // 脱線アプリ同士を行き来しても続けて数える
+            let (app, title) = if minute % 2 == 0 {
+                ("Twitter", "")
+            } else {
+                ("Safari", "猫 - YouTube")
+            };
+            assert!(monitor.observe(at(10, minute), app, None, title).is_none());
+        }
+        assert_eq!(
+            monitor.observe(at(10, 10), "Twitter", None, "").as_deref(),
+            Some("作業時間中にTwitterを10分使っています。作業に戻りませんか？")
+        );
+        // 警告した時点から数え直す
+        assert!(monitor.observe(at(10, 11), "Twitter", None, "").is_none());
+    }
+
+    #[test]
+    fn test_reset_when_back_to_work() {
+        let mut monitor = monitor();
+        for minute in 0..9 {
+            monitor.observe(at(10, minute), "Twitter", None, "");
+        }
+        assert!(monitor
+            .observe(at(10, 9), "VS Code", None, "main.rs")
+            .is_none());
+        assert!(monitor.observe(at(10, 10), "Twitter", None, "").is_none());
+
+        monitor.reset();
+        assert!(monitor.observe(at(10, 20), "Twitter", None, "").is_none());
+    }
+
+    #[test]
+    fn test_outside_work_hours() {
+        let mut monitor = monitor();
+        for minute in 0..=30 {
+            assert!(monitor
+                .observe(at(20, minute), "Twitter", None, "")
+                .is_none());
+        }
+    }
+}
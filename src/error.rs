@@ -15,6 +15,38 @@ pub enum ConfigError {
 
     #[error("ディレクトリ作成エラー: {0}")]
     DirectoryCreationError(io::Error),
+
+    #[error("設定値 {field} が不正です: {reason}{}", line_hint(.line))]
+    Validation {
+        field: String,
+        reason: String,
+        /// config.tomlでの行番号（1始まり、ファイルに書かれていれば）
+        line: Option<usize>,
+    },
+
+    #[error("プロファイル名が不正です: {0}（英数字・-・_ のみ使えます）")]
+    InvalidProfile(String),
+
+    #[error("設定ファイルがありません: {}", .0.display())]
+    ConfigFileNotFound(std::path::PathBuf),
+
+    #[error("設定に{}件の誤りがあります:{}", .0.len(), .0.iter().map(|e| format!("\n  - {}", e)).collect::<String>())]
+    ValidationErrors(Vec<ConfigError>),
+}
+
+impl ConfigError {
+    /// バリデーションエラーの一覧を1つのエラーにまとめる（なければ `None`）
+    pub fn from_validation(mut errors: Vec<ConfigError>) -> Option<Self> {
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(ConfigError::ValidationErrors(errors)),
+        }
+    }
+}
+
+fn line_hint(line: &Option<usize>) -> String {
+    line.map_or_else(String::new, |line| format!("（config.toml {}行目）", line))
 }
 
 /// データベースエラー
@@ -28,6 +60,12 @@ pub enum DatabaseError {
 
     #[error("マイグレーションエラー: {0}")]
     MigrationError(String),
+
+    #[error("JSON変換エラー: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("DB書き込みスレッドが停止しています")]
+    WriterStopped,
 }
 
 /// メタデータエラー
@@ -38,6 +76,15 @@ pub enum MetadataError {
 
     #[error("UTF-8変換エラー: {0}")]
     Utf8Error(#[from] FromUtf8Error),
+
+    #[error("外部コマンドの実行失敗: {0}")]
+    HookFailed(#[from] HookError),
+
+    #[error("出力を解釈できません: {0}")]
+    InvalidOutput(String),
+
+    #[error("入力監視の開始失敗: {0}")]
+    InputMonitorFailed(String),
 }
 
 /// 画像ストレージエラー
@@ -51,6 +98,15 @@ pub enum ImageStoreError {
 
     #[error("キャプチャコマンド失敗: {0}")]
     CaptureCommandFailed(String),
+
+    #[error("すべてのキャプチャ方法が失敗: {0}")]
+    AllBackendsFailed(String),
+
+    #[error("画像の読み込み失敗: {0}")]
+    DecodeFailed(String),
+
+    #[error("画像の書き出し失敗: {0}")]
+    FlushFailed(io::Error),
 }
 
 /// キャプチャエラー
@@ -62,11 +118,56 @@ pub enum CaptureError {
     #[error("設定エラー: {0}")]
     ConfigError(#[from] ConfigError),
 
+    #[allow(dead_code)]
     #[error("初期化エラー: {0}")]
     InitializationError(String),
 
     #[error("シグナルハンドラーエラー: {0}")]
     SignalHandlerError(String),
+
+    #[error("ランタイムエラー: {0}")]
+    RuntimeError(String),
+
+    #[error(transparent)]
+    LockError(#[from] LockError),
+
+    #[error("画面収録の権限がありません。システム設定 > プライバシーとセキュリティ > 画面収録 で、実行しているターミナル（launchdから起動した場合はtracker）を許可してください（open \"{0}\" で開けます）")]
+    ScreenRecordingDenied(&'static str),
+}
+
+/// 多重起動防止ロックエラー
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error("トラッカーは既に起動しています（PID: {}）。`tracker stop` で停止してから実行してください", .0.map_or("不明".to_string(), |pid| pid.to_string()))]
+    AlreadyRunning(Option<u32>),
+
+    #[error("PIDファイルの内容が不正です: {0}")]
+    InvalidPid(String),
+
+    #[error("PID {0} にシグナルを送れませんでした")]
+    SignalFailed(u32),
+
+    #[error("PID {0} が時間内に停止しませんでした")]
+    StopTimeout(u32),
+
+    #[error("PIDファイルを扱えません: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// デーモン起動エラー
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error(transparent)]
+    LockError(#[from] LockError),
+
+    #[error("バックグラウンドプロセスを起動できません: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("バックグラウンドプロセスが起動直後に終了しました（{status}）。ログを確認してください: {}", .log_path.display())]
+    ExitedEarly {
+        status: std::process::ExitStatus,
+        log_path: std::path::PathBuf,
+    },
 }
 
 /// レポートエラー
@@ -77,6 +178,12 @@ pub enum ReportError {
 
     #[error("無効な日付: {0}")]
     InvalidDate(String),
+
+    #[error("期間は 4w や 10d の形式で指定してください: {0}")]
+    InvalidPeriod(String),
+
+    #[error("期間は{0}日以内で指定してください")]
+    PeriodTooLong(u32),
 }
 
 /// OCRエラー
@@ -89,6 +196,139 @@ pub enum OcrError {
     ExecutionFailed(String),
 }
 
+/// MCPサーバーエラー
+#[derive(Error, Debug)]
+pub enum McpError {
+    #[error("IOエラー: {0}")]
+    IoError(#[from] io::Error),
+}
+
+/// HTTP APIサーバーエラー
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("ポートのバインドに失敗: {0}")]
+    BindFailed(String),
+}
+
+/// LaunchAgent登録エラー
+#[derive(Error, Debug)]
+pub enum LaunchdError {
+    #[error("IOエラー: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("launchctlの実行に失敗: {0}")]
+    CommandFailed(String),
+}
+
+/// インポートエラー
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("IOエラー: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("JSON解析エラー: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("無効なタイムスタンプ: {0}")]
+    InvalidTimestamp(String),
+
+    #[error("CSV解析エラー: {0}")]
+    InvalidCsv(String),
+}
+
+/// Obsidian連携エラー
+#[derive(Error, Debug)]
+pub enum ObsidianError {
+    #[error("IOエラー: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("データベースエラー: {0}")]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error("レポートエラー: {0}")]
+    ReportError(#[from] ReportError),
+
+    #[error("無効な日付: {0}")]
+    InvalidDate(String),
+
+    #[error("無効なデイリーノートのパス形式: {0}")]
+    InvalidNotePath(String),
+}
+
+/// ワードクラウド生成エラー
+#[derive(Error, Debug)]
+pub enum WordCloudError {
+    #[error("IOエラー: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("レポートエラー: {0}")]
+    ReportError(#[from] ReportError),
+
+    #[error("未対応の出力形式: {0}（.svg または .png を指定してください）")]
+    UnsupportedFormat(String),
+
+    #[error("画像の描画に失敗: {0}")]
+    RenderFailed(String),
+
+    #[error("形態素解析の辞書を読み込めません: {0}")]
+    DictionaryLoadFailed(String),
+}
+
+/// ショートカット入力の解釈エラー
+#[derive(Error, Debug)]
+pub enum ShortcutError {
+    #[error("不明な操作: {0}（pause / resume / note / report のいずれかを指定してください）")]
+    UnknownAction(String),
+
+    #[error("メモの内容が空です")]
+    EmptyNote,
+
+    #[error("URLを解釈できません: {0}")]
+    InvalidUrl(String),
+}
+
+/// 工数登録エラー
+#[derive(Error, Debug)]
+pub enum WorklogError {
+    #[error("データベースエラー: {0}")]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error("{ticket}の工数の登録に失敗: {reason}")]
+    RequestFailed { ticket: String, reason: String },
+}
+
+/// フックの実行エラー
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("コマンド実行失敗: {0}")]
+    CommandFailed(#[from] io::Error),
+
+    #[error("JSON変換エラー: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("異常終了しました（{0}）")]
+    Failed(String),
+
+    #[error("{0}秒以内に終了しなかったため強制終了しました")]
+    Timeout(u64),
+}
+
+/// グラフ描画エラー
+#[derive(Error, Debug)]
+pub enum ChartError {
+    #[error("IOエラー: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("レポートエラー: {0}")]
+    ReportError(#[from] ReportError),
+
+    #[error("未対応の出力形式: {0}（.png または .svg を指定してください）")]
+    UnsupportedFormat(String),
+
+    #[error("グラフの描画に失敗: {0}")]
+    RenderFailed(String),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,6 +340,26 @@ mod tests {
             "permission denied",
         ));
         assert!(err.to_string().contains("ディレクトリ作成エラー"));
+
+        let validation = |field: &str, line| ConfigError::Validation {
+            field: field.to_string(),
+            reason: "0〜23で指定してください".to_string(),
+            line,
+        };
+        assert_eq!(
+            validation("day_boundary_hour", Some(3)).to_string(),
+            "設定値 day_boundary_hour が不正です: 0〜23で指定してください（config.toml 3行目）"
+        );
+        let err = ConfigError::from_validation(vec![
+            validation("work_start_hour", None),
+            validation("work_end_hour", Some(5)),
+        ])
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "設定に2件の誤りがあります:\n  - 設定値 work_start_hour が不正です: 0〜23で指定してください\n  - 設定値 work_end_hour が不正です: 0〜23で指定してください（config.toml 5行目）"
+        );
+        assert!(ConfigError::from_validation(vec![]).is_none());
     }
 
     #[test]
@@ -127,6 +387,23 @@ mod tests {
     fn test_capture_error_display() {
         let err = CaptureError::InitializationError("初期化に失敗".to_string());
         assert!(err.to_string().contains("初期化エラー"));
+
+        let err = CaptureError::RuntimeError("起動に失敗".to_string());
+        assert!(err.to_string().contains("ランタイムエラー"));
+
+        let err = CaptureError::ScreenRecordingDenied("x-apple.systempreferences:test");
+        assert!(err.to_string().contains("画面収録"));
+        assert!(err
+            .to_string()
+            .contains("open \"x-apple.systempreferences:test\""));
+    }
+
+    #[test]
+    fn test_lock_error_display() {
+        let err = LockError::AlreadyRunning(Some(1234));
+        assert!(err.to_string().contains("既に起動しています（PID: 1234）"));
+        let err = LockError::AlreadyRunning(None);
+        assert!(err.to_string().contains("PID: 不明"));
     }
 
     #[test]
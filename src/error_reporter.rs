@@ -0,0 +1,190 @@
+//! エラー集約モジュール - 同じ種類のエラーの集約とレート制限付きの記録・通知の判定
+
+use chrono::{DateTime, Duration, Local};
+use std::collections::HashMap;
+
+/// エラーを集約する期間（分）
+const WINDOW_MINUTES: i64 = 60;
+
+/// 集約期間内に発生した同じ種類のエラー
+#[derive(Debug)]
+struct ErrorCount {
+    window_start: DateTime<Local>,
+    count: u32,
+    last_message: String,
+    notified: bool,
+}
+
+impl ErrorCount {
+    /// 集約期間内の発生回数をまとめた記録（1回だけなら `None`）
+    fn summary(&self, kind: &str) -> Option<String> {
+        (self.count > 1).then(|| {
+            format!(
+                "{}: 過去{}分に{}回発生しました（最後: {}）",
+                kind, WINDOW_MINUTES, self.count, self.last_message
+            )
+        })
+    }
+}
+
+/// エラーの記録・通知の判定結果
+#[derive(Debug, Default, PartialEq)]
+pub struct ErrorReport {
+    /// ログに記録するメッセージ（集約期間内の重複は記録しない）
+    pub logs: Vec<String>,
+    /// 通知するメッセージ
+    pub notification: Option<String>,
+}
+
+/// エラーの集約
+///
+/// エラーの種類ごとに、初回だけそのまま記録し、集約期間（1時間）内の2回目以降は数えるだけにする。
+/// 期間が過ぎたら「過去60分にN回」とまとめて記録し、期間内の回数が閾値に達したら1回だけ通知する
+#[derive(Debug)]
+pub struct ErrorReporter {
+    /// 通知する集約期間内の回数（0で通知しない）
+    notify_threshold: u32,
+    errors: HashMap<String, ErrorCount>,
+}
+
+impl ErrorReporter {
+    /// 新しいErrorReporterを作成
+    pub fn new(notify_threshold: u32) -> Self {
+        Self {
+            notify_threshold,
+            errors: HashMap::new(),
+        }
+    }
+
+    /// エラーの発生を記録し、ログ・通知するメッセージを返す
+    pub fn report(&mut self, now: DateTime<Local>, kind: &str, message: &str) -> ErrorReport {
+        let mut logs = Vec::new();
+        if let Some(summary) = self.expire(now, kind) {
+            logs.push(summary);
+        }
+
+        let error = self.errors.entry(kind.to_string()).or_insert_with(|| {
+            logs.push(format!("{}: {}", kind, message));
+            ErrorCount {
+                window_start: now,
+                count: 0,
+                last_message: String::new(),
+                notified: false,
+            }
+        });
+        error.count += 1;
+        error.last_message = message.to_string();
+
+        let notification =
+            (self.notify_threshold > 0 && error.count >= self.notify_threshold && !error.notified)
+                .then(|| {
+                    error.notified = true;
+                    format!(
+                        "{}が過去{}分に{}回発生しています: {}",
+                        kind, WINDOW_MINUTES, error.count, message
+                    )
+                });
+        ErrorReport { logs, notification }
+    }
+
+    /// 集約期間が過ぎたエラーをまとめた記録を返す（キャプチャのたびに呼び出す）
+    pub fn flush(&mut self, now: DateTime<Local>) -> Vec<String> {
+        let mut expired: Vec<String> = self
+            .errors
+            .iter()
+            .filter(|(_, error)| is_expired(error, now))
+            .map(|(kind, _)| kind.clone())
+            .collect();
+        expired.sort();
+        expired
+            .iter()
+            .filter_map(|kind| self.expire(now, kind))
+            .collect()
+    }
+
+    /// 集約期間が過ぎていれば取り除き、まとめた記録を返す
+    fn expire(&mut self, now: DateTime<Local>, kind: &str) -> Option<String> {
+        let error = self.errors.get(kind)?;
+        if !is_expired(error, now) {
+            return None;
+        }
+        self.errors.remove(kind)?.summary(kind)
+    }
+}
+
+fn is_expired(error: &ErrorCount, now: DateTime<Local>) -> bool {
+    now - error.window_start >= Duration::minutes(WINDOW_MINUTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 10, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rate_limit_and_notify() {
+        let mut reporter = ErrorReporter::new(3);
+        let report = reporter.report(at(10, 0), "OCR失敗", "timeout");
+        assert_eq!(report.logs, vec!["OCR失敗: timeout"]);
+        assert_eq!(report.notification, None);
+
+        // 期間内の重複は記録しない
+        assert_eq!(
+            reporter.report(at(10, 1), "OCR失敗", "timeout"),
+            ErrorReport::default()
+        );
+        // 種類が違えば別に数える
+        let report = reporter.report(at(10, 1), "画像ハッシュ計算失敗", "io");
+        assert_eq!(report.logs.len(), 1);
+
+        let report = reporter.report(at(10, 2), "OCR失敗", "crash");
+        assert!(report.logs.is_empty());
+        assert_eq!(
+            report.notification.as_deref(),
+            Some("OCR失敗が過去60分に3回発生しています: crash")
+        );
+        // 通知は期間内に1回だけ
+        assert_eq!(
+            reporter.report(at(10, 3), "OCR失敗", "crash").notification,
+            None
+        );
+
+        // 期間が過ぎたらまとめて記録し、次の発生は初回として記録する
+        let report = reporter.report(at(11, 0), "OCR失敗", "timeout");
+        assert_eq!(
+            report.logs,
+            vec![
+                "OCR失敗: 過去60分に4回発生しました（最後: crash）",
+                "OCR失敗: timeout"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut reporter = ErrorReporter::new(0);
+        reporter.report(at(10, 0), "OCR失敗", "timeout");
+        reporter.report(at(10, 5), "OCR失敗", "timeout");
+        reporter.report(at(10, 30), "WALのチェックポイント失敗", "busy");
+        assert!(reporter.flush(at(10, 59)).is_empty());
+
+        assert_eq!(
+            reporter.flush(at(11, 0)),
+            vec!["OCR失敗: 過去60分に2回発生しました（最後: timeout）"]
+        );
+        // 1回だけのエラーはまとめずに取り除く
+        assert!(reporter.flush(at(11, 30)).is_empty());
+        assert_eq!(
+            reporter
+                .report(at(11, 31), "WALのチェックポイント失敗", "busy")
+                .logs,
+            vec!["WALのチェックポイント失敗: busy"]
+        );
+    }
+}
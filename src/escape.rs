@@ -0,0 +1,26 @@
+//! エスケープモジュール - HTML・XML（SVG・plist）に埋め込む文字列の特殊文字の置換
+
+/// HTML・XMLの特殊文字（`& < > " '`）をエスケープ
+///
+/// 要素のテキストと属性値のどちらにも埋め込める
+pub fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_markup() {
+        assert_eq!(
+            escape_markup("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+        assert_eq!(escape_markup("設計レビュー"), "設計レビュー");
+    }
+}
@@ -0,0 +1,258 @@
+//! エクスポートモジュール - 外部サービス形式との変換
+
+use crate::category::Categories;
+use crate::database::{parse_timestamp, CaptureRecord};
+use crate::report::elapsed_seconds;
+use chrono::{DateTime, FixedOffset};
+use clap::ValueEnum;
+use std::io::{self, Write};
+
+/// エクスポート形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Toggl TrackのCSVインポート形式
+    TogglCsv,
+    /// ActivityWatchのバケット形式（JSON）
+    Activitywatch,
+}
+
+/// インポート形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// ActivityWatchのエクスポートファイル（JSON）
+    Activitywatch,
+    /// RescueTimeのエクスポートファイル（CSV）
+    Rescuetime,
+    /// Timingのエクスポートファイル（CSV）
+    Timing,
+}
+
+/// 匿名化したエクスポートでホスト名の代わりに使う名前
+pub const ANONYMOUS_HOSTNAME: &str = "anonymous";
+
+/// 時間エントリ（同一アプリが連続した区間）
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeEntry {
+    pub start: DateTime<FixedOffset>,
+    pub duration_seconds: u64,
+    pub app: String,
+    pub description: String,
+}
+
+/// キャプチャ列を時間エントリにまとめる
+///
+/// 同じアプリが連続するキャプチャを1エントリとし、1キャプチャを次のキャプチャまでの
+/// 時間として扱う（キャプチャの間隔が上限、`elapsed_seconds`）。説明にはエントリ先頭のウィンドウタイトルを使い、
+/// プライベートなキャプチャのタイトルは出力しない
+pub fn time_entries(captures: &[CaptureRecord], interval_seconds: u64) -> Vec<TimeEntry> {
+    let mut entries: Vec<TimeEntry> = Vec::new();
+
+    for (index, capture) in captures.iter().enumerate() {
+        if capture.is_paused {
+            continue;
+        }
+        let Some(start) = parse_timestamp(&capture.captured_at) else {
+            continue;
+        };
+        let duration = elapsed_seconds(capture, captures.get(index + 1), interval_seconds);
+
+        match entries.last_mut() {
+            Some(last) if last.app == capture.active_app => {
+                last.duration_seconds += duration;
+            }
+            _ => entries.push(TimeEntry {
+                start,
+                duration_seconds: duration,
+                app: capture.active_app.clone(),
+                description: if capture.is_private {
+                    String::new()
+                } else {
+                    capture.window_title.clone()
+                },
+            }),
+        }
+    }
+
+    entries
+}
+
+/// キャプチャを統計だけのデータに匿名化
+///
+/// アプリ名をカテゴリ名に置き換え、ウィンドウタイトル・OCRテキスト・画像パスと、
+/// 個人や環境を特定できるbundle ID・作業ディレクトリ・ホスト名を取り除く
+pub fn anonymize(captures: &mut [CaptureRecord], categories: &Categories) {
+    for capture in captures {
+        capture.active_app = categories
+            .category(&capture.active_app, capture.bundle_id.as_deref())
+            .to_string();
+        capture.window_title.clear();
+        capture.ocr_text = None;
+        capture.image_path = None;
+        capture.bundle_id = None;
+        capture.working_directory = None;
+        capture.hostname = None;
+    }
+}
+
+/// Toggl Track形式のCSVを書き出す
+///
+/// プロジェクトにアプリ名、説明にウィンドウタイトルを入れる
+pub fn write_toggl_csv<W: Write>(
+    writer: &mut W,
+    entries: &[TimeEntry],
+    email: &str,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "Email,Project,Description,Start date,Start time,Duration"
+    )?;
+
+    for entry in entries {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            csv_field(email),
+            csv_field(&entry.app),
+            csv_field(&entry.description),
+            entry.start.format("%Y-%m-%d"),
+            entry.start.format("%H:%M:%S"),
+            format_hms(entry.duration_seconds)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 秒を `HH:MM:SS` 形式に変換
+fn format_hms(seconds: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+/// CSVフィールドをエスケープ（カンマ・引用符・改行を含む場合は引用符で囲む）
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(time: &str, app: &str, title: &str) -> CaptureRecord {
+        CaptureRecord {
+            id: None,
+            captured_at: time.to_string(),
+            image_path: None,
+            active_app: app.to_string(),
+            window_title: title.to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        }
+    }
+
+    #[test]
+    fn test_time_entries_merge_consecutive_apps() {
+        let captures = vec![
+            capture("2025-01-10T10:00:00+09:00", "VS Code", "main.rs"),
+            capture("2025-01-10T10:01:00+09:00", "VS Code", "lib.rs"),
+            capture("2025-01-10T10:02:00+09:00", "Slack", "general"),
+            capture("2025-01-10T10:03:00+09:00", "VS Code", "main.rs"),
+        ];
+
+        let entries = time_entries(&captures, 60);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].app, "VS Code");
+        assert_eq!(entries[0].description, "main.rs");
+        assert_eq!(entries[0].duration_seconds, 120);
+        assert_eq!(entries[1].app, "Slack");
+        assert_eq!(entries[2].start.format("%H:%M").to_string(), "10:03");
+    }
+
+    #[test]
+    fn test_private_title_is_hidden() {
+        let mut private = capture("2025-01-10T10:00:00+09:00", "Safari", "bank");
+        private.is_private = true;
+
+        let entries = time_entries(&[private], 60);
+        assert_eq!(entries[0].description, "");
+    }
+
+    #[test]
+    fn test_anonymize() {
+        let mut captures = vec![
+            capture("2025-01-10T10:00:00+09:00", "VS Code", "secret.rs"),
+            capture("2025-01-10T10:01:00+09:00", "Terminal", "~/work/secret"),
+            capture("2025-01-10T10:02:00+09:00", "Slack", "DM"),
+        ];
+        captures[0].ocr_text = Some("password".to_string());
+        captures[0].image_path = Some("/Users/me/images/100000.jpg".to_string());
+        captures[1].working_directory = Some("/Users/me/work/secret".to_string());
+        captures[1].hostname = Some("my-mac".to_string());
+        let categories = Categories::new(std::collections::BTreeMap::from([(
+            "開発".to_string(),
+            vec!["VS Code".to_string(), "Terminal".to_string()],
+        )]));
+
+        anonymize(&mut captures, &categories);
+        assert!(captures.iter().all(|c| c.window_title.is_empty()
+            && c.ocr_text.is_none()
+            && c.image_path.is_none()
+            && c.working_directory.is_none()
+            && c.hostname.is_none()));
+
+        // 同じカテゴリが続く区間は1エントリにまとまる
+        let entries = time_entries(&captures, 60);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            (entries[0].app.as_str(), entries[0].duration_seconds),
+            ("開発", 120)
+        );
+        assert_eq!(entries[0].description, "");
+        assert_eq!(entries[1].app, crate::category::UNCATEGORIZED);
+    }
+
+    #[test]
+    fn test_write_toggl_csv() {
+        let captures = vec![capture(
+            "2025-01-10T10:00:00+09:00",
+            "VS Code",
+            "main.rs, \"draft\"",
+        )];
+        let entries = time_entries(&captures, 90);
+
+        let mut output = Vec::new();
+        write_toggl_csv(&mut output, &entries, "me@example.com").unwrap();
+        let csv = String::from_utf8(output).unwrap();
+
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Email,Project,Description,Start date,Start time,Duration"
+        );
+        assert_eq!(
+            lines[1],
+            "me@example.com,VS Code,\"main.rs, \"\"draft\"\"\",2025-01-10,10:00:00,00:01:30"
+        );
+    }
+
+    #[test]
+    fn test_format_hms() {
+        assert_eq!(format_hms(0), "00:00:00");
+        assert_eq!(format_hms(3725), "01:02:05");
+    }
+}
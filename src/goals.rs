@@ -0,0 +1,206 @@
+//! 目標モジュール - 1日の作業時間の目標と連続達成日数（ストリーク）の集計
+
+use crate::database::GoalResult;
+use crate::i18n::Message;
+use crate::report::format_duration;
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// カテゴリを問わない合計の作業時間を対象にする目標名
+pub const TOTAL_GOAL: &str = "total";
+
+/// 1日の作業時間の目標
+///
+/// 設定ファイルの `[goals]` テーブル（`カテゴリ名 = 分`）から作る。
+/// カテゴリ名の代わりに `total` を指定すると合計の作業時間を対象にする
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Goals {
+    targets: BTreeMap<String, u64>,
+}
+
+impl Goals {
+    /// 目標名と1日の目標時間（分）の対応から作成
+    pub fn new(targets: BTreeMap<String, u64>) -> Self {
+        let targets = targets
+            .into_iter()
+            .map(|(goal, minutes)| (goal.trim().to_string(), minutes))
+            .filter(|(goal, _)| !goal.is_empty())
+            .collect();
+        Self { targets }
+    }
+
+    /// 目標が1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// 目標名と1日の目標時間（分）
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.targets
+            .iter()
+            .map(|(goal, minutes)| (goal.as_str(), *minutes))
+    }
+}
+
+/// 目標の対象日の達成状況とストリーク
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoalProgress {
+    pub goal: String,
+    pub target_seconds: u64,
+    /// 対象日の作業時間（秒）
+    pub seconds: u64,
+    pub achieved: bool,
+    /// 対象日まで続いている連続達成日数
+    pub current_streak: u32,
+    /// これまでの最長の連続達成日数
+    pub longest_streak: u32,
+}
+
+impl GoalProgress {
+    /// 日別の達成状況（古い順、`date` まで）から対象日の状況を作成
+    pub fn new(goal: &str, minutes: u64, results: &[GoalResult], date: &str) -> Self {
+        let today = results.last().filter(|result| result.date == date);
+        let (current_streak, longest_streak) = streaks(results, date);
+        Self {
+            goal: goal.to_string(),
+            target_seconds: minutes * 60,
+            seconds: today.map_or(0, |result| result.seconds),
+            achieved: today.is_some_and(|result| result.achieved),
+            current_streak,
+            longest_streak,
+        }
+    }
+}
+
+/// 日別の達成状況（古い順）から現在と最長のストリーク（日数）を求める
+///
+/// 記録のない日はストリークを途切れさせる。
+/// 対象日がまだ未達成のときは、その日のうちに達成できるため前日までの連続日数を現在のストリークとする
+pub fn streaks(results: &[GoalResult], date: &str) -> (u32, u32) {
+    // 日ごとに、その日で終わる連続達成日数を数える
+    let mut runs: Vec<(NaiveDate, u32)> = Vec::new();
+    for result in results {
+        let Some(day) = parse_date(&result.date) else {
+            continue;
+        };
+        let run = match runs.last() {
+            _ if !result.achieved => 0,
+            Some(&(previous, run)) if previous.checked_add_days(Days::new(1)) == Some(day) => {
+                run + 1
+            }
+            _ => 1,
+        };
+        runs.push((day, run));
+    }
+
+    let run_on = |day: Option<NaiveDate>| {
+        runs.iter()
+            .rev()
+            .find(|(d, _)| Some(*d) == day)
+            .map_or(0, |&(_, run)| run)
+    };
+    let date = parse_date(date);
+    let current = match run_on(date) {
+        0 => run_on(date.and_then(|d| d.checked_sub_days(Days::new(1)))),
+        run => run,
+    };
+    let longest = runs.iter().map(|&(_, run)| run).max().unwrap_or(0);
+    (current, longest)
+}
+
+/// 目標の達成状況を1行で表示
+pub fn format_goal(progress: &GoalProgress) -> String {
+    let mark = if progress.achieved {
+        Message::GoalAchieved
+    } else {
+        Message::GoalNotAchieved
+    };
+    let goal = if progress.goal == TOTAL_GOAL {
+        Message::GoalTotal.text()
+    } else {
+        progress.goal.as_str()
+    };
+    format!(
+        "{} {}",
+        Message::GoalLine.format(&[
+            &goal,
+            &format_duration(progress.seconds),
+            &format_duration(progress.target_seconds),
+            &mark.text(),
+        ]),
+        Message::GoalStreak.format(&[&progress.current_streak, &progress.longest_streak])
+    )
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(days: &[(&str, bool)]) -> Vec<GoalResult> {
+        days.iter()
+            .map(|&(date, achieved)| GoalResult {
+                date: date.to_string(),
+                goal: "開発".to_string(),
+                seconds: if achieved { 3 * 3600 } else { 600 },
+                achieved,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_streaks() {
+        let days = results(&[
+            ("2025-01-01", true),
+            ("2025-01-02", true),
+            ("2025-01-03", true),
+            ("2025-01-04", false),
+            ("2025-01-05", true),
+            ("2025-01-06", true),
+        ]);
+        assert_eq!(streaks(&days, "2025-01-06"), (2, 3));
+        // 当日が未達成でも前日までのストリークは続いている
+        assert_eq!(streaks(&days, "2025-01-07"), (2, 3));
+        assert_eq!(streaks(&days, "2025-01-08"), (0, 3));
+        assert_eq!(streaks(&[], "2025-01-08"), (0, 0));
+    }
+
+    #[test]
+    fn test_streaks_break_on_missing_day() {
+        let days = results(&[
+            ("2025-01-01", true),
+            ("2025-01-02", true),
+            ("2025-01-04", true),
+            ("2025-01-05", false),
+        ]);
+        assert_eq!(streaks(&days, "2025-01-05"), (1, 2));
+    }
+
+    #[test]
+    fn test_progress() {
+        let days = results(&[("2025-01-01", true), ("2025-01-02", true)]);
+        let progress = GoalProgress::new("開発", 180, &days, "2025-01-02");
+        assert_eq!(progress.target_seconds, 3 * 3600);
+        assert_eq!(progress.seconds, 3 * 3600);
+        assert!(progress.achieved);
+        assert_eq!((progress.current_streak, progress.longest_streak), (2, 2));
+
+        let progress = GoalProgress::new("開発", 180, &days, "2025-01-03");
+        assert_eq!(progress.seconds, 0);
+        assert!(!progress.achieved);
+        assert_eq!(progress.current_streak, 2);
+    }
+
+    #[test]
+    fn test_goals_ignore_blank_names() {
+        let goals = Goals::new(BTreeMap::from([
+            (" 開発 ".to_string(), 180),
+            (" ".to_string(), 60),
+        ]));
+        assert_eq!(goals.iter().collect::<Vec<_>>(), vec![("開発", 180)]);
+    }
+}
@@ -0,0 +1,200 @@
+//! ハイライトモジュール - 時間帯ごとの代表画像の選定
+
+use crate::database::{Database, HighlightCandidate};
+use crate::error::DatabaseError;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// 時間帯のキー（YYYY-MM-DDTHH、壁時計時刻）の書式
+const HOUR_FORMAT: &str = "%Y-%m-%dT%H";
+
+/// 時刻を含む時間帯の開始時刻（壁時計時刻）
+pub fn hour_start(now: &DateTime<Local>) -> NaiveDateTime {
+    let time = now.naive_local();
+    time.date().and_hms_opt(time.hour(), 0, 0).unwrap_or(time)
+}
+
+/// 候補の情報量（OCRテキストの空白以外の文字数）
+pub fn information(candidate: &HighlightCandidate) -> u64 {
+    candidate.ocr_text.as_deref().map_or(0, |text| {
+        text.chars().filter(|c| !c.is_whitespace()).count() as u64
+    })
+}
+
+/// 候補から代表画像を選び、その情報量とともに返す
+///
+/// 情報量の最も多い画像を選ぶ。直前の時間帯の代表と同じ画像（ハッシュが一致）は
+/// ほかに候補がなければ選ぶ。情報量が同じなら、時間帯の中で同じ画像が多い
+/// （長く表示していた）もの、それも同じなら早いものを選ぶ
+pub fn select<'a>(
+    candidates: &'a [HighlightCandidate],
+    previous_hash: Option<&str>,
+) -> Option<(&'a HighlightCandidate, u64)> {
+    let repeated = |candidate: &HighlightCandidate| {
+        previous_hash.is_some() && candidate.image_hash.as_deref() == previous_hash
+    };
+    let same_screen = |candidate: &HighlightCandidate| {
+        candidate.image_hash.as_ref().map_or(1, |hash| {
+            candidates
+                .iter()
+                .filter(|other| other.image_hash.as_ref() == Some(hash))
+                .count()
+        })
+    };
+
+    let mut best: Option<(&HighlightCandidate, (bool, u64, usize))> = None;
+    for candidate in candidates {
+        let key = (
+            !repeated(candidate),
+            information(candidate),
+            same_screen(candidate),
+        );
+        if best.as_ref().is_none_or(|(_, best_key)| key > *best_key) {
+            best = Some((candidate, key));
+        }
+    }
+    best.map(|(candidate, (_, information, _))| (candidate, information))
+}
+
+/// 時間帯の代表画像を選び直して記録し、選んだキャプチャのIDを返す
+///
+/// 候補がなければ記録を削除して `None` を返す
+pub fn update_hour(db: &Database, hour: NaiveDateTime) -> Result<Option<i64>, DatabaseError> {
+    let key = hour.format(HOUR_FORMAT).to_string();
+    let start = hour.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let end = (hour + Duration::hours(1))
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string();
+    let previous_key = (hour - Duration::hours(1)).format(HOUR_FORMAT).to_string();
+
+    let candidates = db.get_highlight_candidates(&start, &end)?;
+    let previous = db.get_highlight(&previous_key)?;
+    let previous_hash = previous.as_ref().and_then(|h| h.image_hash.as_deref());
+    match select(&candidates, previous_hash) {
+        Some((candidate, score)) => {
+            db.set_highlight(&key, candidate.capture_id, score)?;
+            Ok(Some(candidate.capture_id))
+        }
+        None => {
+            db.delete_highlight(&key)?;
+            Ok(None)
+        }
+    }
+}
+
+/// 1日分（境界時刻から24時間）の各時間帯の代表画像を選び直し、選べた時間帯の数を返す
+pub fn update_day(
+    db: &Database,
+    date: NaiveDate,
+    day_boundary_hour: u32,
+) -> Result<usize, DatabaseError> {
+    let start = date.and_time(NaiveTime::MIN) + Duration::hours(day_boundary_hour as i64);
+    let mut selected = 0;
+    for offset in 0..24 {
+        if update_hour(db, start + Duration::hours(offset))?.is_some() {
+            selected += 1;
+        }
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::CaptureRecord;
+    use tempfile::TempDir;
+
+    fn candidate(id: i64, hash: &str, text: &str) -> HighlightCandidate {
+        HighlightCandidate {
+            capture_id: id,
+            captured_at: format!("2025-01-10T10:{:02}:00+09:00", id),
+            image_hash: Some(hash.to_string()),
+            ocr_text: Some(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_select_most_information() {
+        let candidates = vec![
+            candidate(1, "a", "fn main"),
+            candidate(2, "b", "fn main() { println!(); }"),
+            candidate(3, "c", "ok"),
+        ];
+        let (selected, score) = select(&candidates, None).unwrap();
+        assert_eq!(selected.capture_id, 2);
+        assert_eq!(score, 21);
+        assert!(select(&[], None).is_none());
+    }
+
+    #[test]
+    fn test_select_avoids_previous_highlight() {
+        let candidates = vec![
+            candidate(1, "a", "long text here"),
+            candidate(2, "b", "short"),
+        ];
+        assert_eq!(select(&candidates, Some("a")).unwrap().0.capture_id, 2);
+        // ほかに候補がなければ同じ画像でも選ぶ
+        assert_eq!(select(&candidates[..1], Some("a")).unwrap().0.capture_id, 1);
+    }
+
+    #[test]
+    fn test_select_prefers_longest_screen_on_tie() {
+        let candidates = vec![
+            candidate(1, "a", "same"),
+            candidate(2, "b", "same"),
+            candidate(3, "b", "same"),
+        ];
+        assert_eq!(select(&candidates, None).unwrap().0.capture_id, 2);
+    }
+
+    #[test]
+    fn test_update_hour() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let record = |captured_at: &str, path: Option<&str>, text: &str| CaptureRecord {
+            id: None,
+            captured_at: captured_at.to_string(),
+            image_path: path.map(str::to_string),
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: Some(text.to_string()),
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        };
+        db.insert_capture(&record("2025-01-10T10:00:00+09:00", Some("/1.jpg"), "a"))
+            .unwrap();
+        let best = db
+            .insert_capture(&record("2025-01-10T10:30:00+09:00", Some("/2.jpg"), "abc"))
+            .unwrap();
+        // 画像のないキャプチャ・時間帯の外のキャプチャは選ばない
+        db.insert_capture(&record("2025-01-10T10:40:00+09:00", None, "abcdef"))
+            .unwrap();
+        db.insert_capture(&record(
+            "2025-01-10T11:00:00+09:00",
+            Some("/3.jpg"),
+            "abcdef",
+        ))
+        .unwrap();
+
+        let hour = NaiveDateTime::parse_from_str("2025-01-10T10:00", "%Y-%m-%dT%H:%M").unwrap();
+        assert_eq!(update_hour(&db, hour).unwrap(), Some(best));
+        let highlight = db.get_highlight("2025-01-10T10").unwrap().unwrap();
+        assert_eq!(highlight.image_path, "/2.jpg");
+        assert_eq!(highlight.score, 3);
+
+        let empty = hour - Duration::hours(1);
+        assert_eq!(update_hour(&db, empty).unwrap(), None);
+        assert_eq!(update_day(&db, hour.date(), 4).unwrap(), 2);
+        assert_eq!(
+            db.get_highlights_between("2025-01-10", "2025-01-11")
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
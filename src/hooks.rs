@@ -0,0 +1,197 @@
+//! フックモジュール - キャプチャの前後に実行するユーザー定義のコマンド
+
+use crate::database::CaptureRecord;
+use crate::error::HookError;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// フックの終了を待つ最大時間（秒）
+const HOOK_TIMEOUT_SECONDS: u64 = 10;
+
+/// フックの終了を確認する間隔
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// キャプチャの前後に `sh -c` で実行するコマンド
+///
+/// フックの標準出力は捨て、標準エラーはトラッカーのものをそのまま使う。
+/// 終わらないフックでキャプチャが止まらないよう、時間内に終わらなければ強制終了する。
+/// フックは独立したプロセスグループで起動し、`sh` から起動された子プロセスもまとめて終了させる
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    command: String,
+    timeout: Duration,
+}
+
+impl Hook {
+    /// 新しいHookを作成
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            timeout: Duration::from_secs(HOOK_TIMEOUT_SECONDS),
+        }
+    }
+
+    /// 終了を待つ最大時間を設定
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// キャプチャ前のフックを実行（標準入力には何も渡さない）
+    pub fn run_pre_capture(&self) -> Result<(), HookError> {
+        self.run(None, false).map(drop)
+    }
+
+    /// キャプチャ後のフックを実行（標準入力に記録したキャプチャのJSONを渡す）
+    pub fn run_post_capture(&self, record: &CaptureRecord) -> Result<(), HookError> {
+        let input = serde_json::to_vec(record)?;
+        self.run(Some(input), false).map(drop)
+    }
+
+    /// コマンドを実行し、標準出力を返す（メタデータプロバイダの外部コマンドに使う）
+    pub fn output(&self) -> Result<String, HookError> {
+        self.run(None, true)
+    }
+
+    fn run(&self, input: Option<Vec<u8>>, capture_stdout: bool) -> Result<String, HookError> {
+        let piped_or_null = |piped: bool| if piped { Stdio::piped() } else { Stdio::null() };
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(piped_or_null(input.is_some()))
+            .stdout(piped_or_null(capture_stdout))
+            .process_group(0)
+            .spawn()?;
+
+        // 標準入力を読まないフックでも待ち続けないよう、書き込みは別スレッドで行う
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            thread::spawn(move || {
+                if let Err(e) = stdin.write_all(&input) {
+                    if e.kind() != ErrorKind::BrokenPipe {
+                        debug!("フックへの入力の書き込み失敗: {}", e);
+                    }
+                }
+            });
+        }
+        // 出力がパイプの容量を超えても止まらないよう、読み取りも別スレッドで行う
+        let stdout = child.stdout.take().map(|mut stdout| {
+            thread::spawn(move || {
+                let mut output = Vec::new();
+                let _ = stdout.read_to_end(&mut output);
+                output
+            })
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return if status.success() {
+                    let output = stdout
+                        .and_then(|reader| reader.join().ok())
+                        .unwrap_or_default();
+                    Ok(String::from_utf8_lossy(&output).into_owned())
+                } else {
+                    Err(HookError::Failed(status.to_string()))
+                };
+            }
+            if Instant::now() >= deadline {
+                kill_process_group(&mut child);
+                let _ = child.wait();
+                return Err(HookError::Timeout(self.timeout.as_secs()));
+            }
+            thread::sleep(HOOK_POLL_INTERVAL);
+        }
+    }
+}
+
+/// フックのプロセスグループ（グループIDは `sh` のPID）をまとめて強制終了
+///
+/// グループに送れなければ `sh` だけでも終了させる
+fn kill_process_group(child: &mut Child) {
+    let killed = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !killed {
+        let _ = child.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record() -> CaptureRecord {
+        CaptureRecord {
+            id: Some(42),
+            captured_at: "2025-01-10T10:00:00+09:00".to_string(),
+            image_path: None,
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(60),
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        }
+    }
+
+    #[test]
+    fn test_post_capture_receives_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("record.json");
+        let hook = Hook::new(format!("cat > '{}'", output.display()));
+        hook.run_post_capture(&record()).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(json["id"], 42);
+        assert_eq!(json["active_app"], "VS Code");
+    }
+
+    #[test]
+    fn test_output() {
+        assert_eq!(
+            Hook::new("echo branch=main").output().unwrap(),
+            "branch=main\n"
+        );
+    }
+
+    #[test]
+    fn test_failure_and_timeout() {
+        assert!(Hook::new("true").run_pre_capture().is_ok());
+        assert!(matches!(
+            Hook::new("exit 3").run_pre_capture(),
+            Err(HookError::Failed(_))
+        ));
+        assert!(matches!(
+            Hook::new("sleep 5")
+                .with_timeout(Duration::from_millis(100))
+                .run_pre_capture(),
+            Err(HookError::Timeout(_))
+        ));
+    }
+
+    #[test]
+    fn test_timeout_kills_child_processes() {
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("marker");
+        // shが起動した子プロセスも時間切れでまとめて終了する
+        let hook = Hook::new(format!("(sleep 0.5; touch '{}') & wait", marker.display()))
+            .with_timeout(Duration::from_millis(100));
+        assert!(matches!(hook.run_pre_capture(), Err(HookError::Timeout(_))));
+
+        thread::sleep(Duration::from_millis(1000));
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,351 @@
+//! HTMLレポートモジュール - 日次レポートのHTML生成とブラウザ表示
+
+use crate::escape::escape_markup;
+use crate::goals::TOTAL_GOAL;
+use crate::i18n::{Language, Message};
+use crate::report::{format_duration, DailyReport, TimelineEntry};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// ページのスタイル
+const STYLE: &str = r#"
+body { font-family: -apple-system, 'Hiragino Sans', sans-serif; margin: 2em auto; max-width: 960px; color: #222; }
+h1 { font-size: 1.6em; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { text-align: left; padding: 4px 8px; border-bottom: 1px solid #eee; }
+td.num { text-align: right; white-space: nowrap; }
+.bar { background: #4c8bf5; height: 12px; border-radius: 2px; }
+.muted { color: #888; }
+.kpi { font-size: 1.1em; padding: 8px 12px; background: #f5f7fa; border-radius: 4px; }
+.highlights { display: grid; grid-template-columns: repeat(auto-fill, minmax(280px, 1fr)); gap: 12px; margin-bottom: 2em; }
+.highlights figure { margin: 0; }
+.highlights img { width: 100%; border: 1px solid #eee; border-radius: 4px; }
+.highlights figcaption { font-size: 0.85em; }
+"#;
+
+/// 日次レポートをHTMLにする
+///
+/// タイムラインは同じアプリ・ウィンドウタイトルが続く区間を1行にまとめる
+pub fn render_html(report: &DailyReport) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"{lang}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n<p class=\"kpi\">",
+        lang = Language::current().code(),
+        title = Message::ReportTitle.format(&[&escape_markup(&report.date)]),
+        style = STYLE,
+    );
+    let mut kpis = vec![
+        (Message::KpiWork, format_duration(report.kpi.work_seconds)),
+        (
+            Message::KpiMeeting,
+            format_duration(report.kpi.meeting_seconds),
+        ),
+        (Message::KpiFocus, format_duration(report.kpi.focus_seconds)),
+        (
+            Message::KpiDistraction,
+            format_duration(report.kpi.distraction_seconds),
+        ),
+        (
+            Message::KpiSwitches,
+            Message::Times.format(&[&report.kpi.context_switches]),
+        ),
+    ];
+    if report.kpi.pomodoros > 0 {
+        kpis.push((
+            Message::KpiPomodoro,
+            Message::Times.format(&[&report.kpi.pomodoros]),
+        ));
+    }
+    let kpis: Vec<String> = kpis
+        .into_iter()
+        .map(|(label, value)| format!("{} <strong>{}</strong>", label.text(), value))
+        .collect();
+    let _ = writeln!(html, "{}</p>", kpis.join(" / "));
+
+    if !report.goals.is_empty() {
+        let _ = writeln!(html, "<h2>{}</h2>\n<table>", Message::GoalsSection.text());
+        for progress in &report.goals {
+            let goal = if progress.goal == TOTAL_GOAL {
+                Message::GoalTotal.text()
+            } else {
+                progress.goal.as_str()
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{} / {}</td><td>{}</td>\
+                 <td class=\"num\">{}</td></tr>",
+                escape_markup(goal),
+                format_duration(progress.seconds),
+                format_duration(progress.target_seconds),
+                if progress.achieved {
+                    Message::GoalAchieved.text()
+                } else {
+                    Message::GoalNotAchieved.text()
+                },
+                Message::GoalStreak.format(&[&progress.current_streak, &progress.longest_streak])
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th></th><th>{}</th><th>{}</th></tr>",
+        Message::AppsSection.text(),
+        Message::AppColumn.text(),
+        Message::DurationColumn.text(),
+        Message::CapturesColumn.text()
+    );
+    let max = report
+        .apps
+        .iter()
+        .map(|a| a.duration_seconds)
+        .max()
+        .unwrap_or(0);
+    for app in &report.apps {
+        let width = (app.duration_seconds * 100).checked_div(max).unwrap_or(0);
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td style=\"width: 50%\"><div class=\"bar\" style=\"width: {}%\"></div></td>\
+             <td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape_markup(&app.app_name),
+            width,
+            format_duration(app.duration_seconds),
+            app.capture_count
+        );
+    }
+    html.push_str("</table>\n");
+
+    // 全画像ではなく時間帯ごとの代表画像だけを表示する
+    if !report.highlights.is_empty() {
+        let _ = writeln!(
+            html,
+            "<h2>{}</h2>\n<div class=\"highlights\">",
+            Message::HighlightsSection.text()
+        );
+        for highlight in &report.highlights {
+            let _ = writeln!(
+                html,
+                "<figure><a href=\"{src}\"><img src=\"{src}\" loading=\"lazy\" alt=\"\"></a>\
+                 <figcaption>{hour} {app} <span class=\"muted\">{title}</span></figcaption></figure>",
+                src = escape_markup(&file_url(&highlight.image_path)),
+                hour = Message::Hour.format(&[&highlight.hour.get(11..13).unwrap_or(&highlight.hour)]),
+                app = escape_markup(&highlight.active_app),
+                title = escape_markup(&highlight.window_title)
+            );
+        }
+        html.push_str("</div>\n");
+    }
+
+    if !report.notes.is_empty() {
+        let _ = writeln!(html, "<h2>{}</h2>\n<table>", Message::NotesSection.text());
+        for note in &report.notes {
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"num\">{}</td><td>{}</td></tr>",
+                note.created_at.get(11..16).unwrap_or(&note.created_at),
+                escape_markup(&note.text)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th></tr>",
+        Message::TimelineSection.text(),
+        Message::TimeColumn.text(),
+        Message::AppColumn.text(),
+        Message::WindowTitleColumn.text()
+    );
+    for (start, end) in group_timeline(&report.timeline) {
+        let time = if start.time == end.time {
+            start.time.clone()
+        } else {
+            Message::TimeRange.format(&[&start.time, &end.time])
+        };
+        // OCRテキストの抜粋は区間の最初のものを添える
+        let excerpt = start
+            .ocr_excerpt
+            .as_deref()
+            .map(|excerpt| format!("<br><small>{}</small>", escape_markup(excerpt)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"num\">{}</td><td>{}</td><td class=\"muted\">{}{}</td></tr>",
+            time,
+            escape_markup(&start.active_app),
+            escape_markup(&start.window_title),
+            excerpt
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+/// 同じアプリ・ウィンドウタイトルが続く区間の（最初, 最後）のエントリ
+pub(crate) fn group_timeline(timeline: &[TimelineEntry]) -> Vec<(&TimelineEntry, &TimelineEntry)> {
+    let mut groups: Vec<(&TimelineEntry, &TimelineEntry)> = Vec::new();
+    for entry in timeline {
+        match groups.last_mut() {
+            Some((start, end))
+                if start.active_app == entry.active_app
+                    && start.window_title == entry.window_title =>
+            {
+                *end = entry;
+            }
+            _ => groups.push((entry, entry)),
+        }
+    }
+    groups
+}
+
+/// ローカルファイルのパスを `file://` のURLにする（URLで意味を持つ文字はエスケープする）
+fn file_url(path: &str) -> String {
+    let mut url = String::from("file://");
+    for byte in path.bytes() {
+        match byte {
+            b'%' | b'#' | b'?' | b' ' | b'"' => {
+                let _ = write!(url, "%{:02X}", byte);
+            }
+            _ => url.push(byte as char),
+        }
+    }
+    url
+}
+
+/// 日次レポートのHTMLを一時ファイルに書き出し、既定のブラウザで開く
+pub fn open_in_browser(report: &DailyReport) -> io::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("habit-tracker-report-{}.html", report.date));
+    fs::write(&path, render_html(report))?;
+    open_path(&path)?;
+    Ok(path)
+}
+
+/// macOSの `open` コマンドでファイルを開く
+fn open_path(path: &Path) -> io::Result<()> {
+    let status = Command::new("open").arg(path).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "openコマンドが失敗しました: {}",
+            status
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Highlight, Note};
+    use crate::goals::GoalProgress;
+    use crate::report::{AppSummary, DailyKpi};
+
+    fn entry(time: &str, app: &str, title: &str) -> TimelineEntry {
+        TimelineEntry {
+            captured_at: format!("2025-01-10T{}", time),
+            time: time.to_string(),
+            active_app: app.to_string(),
+            window_title: title.to_string(),
+            activity_type: None,
+            ocr_excerpt: None,
+        }
+    }
+
+    #[test]
+    fn test_render_html() {
+        let report = DailyReport {
+            date: "2025-01-10".to_string(),
+            kpi: DailyKpi {
+                work_seconds: 180,
+                meeting_seconds: 0,
+                focus_seconds: 120,
+                distraction_seconds: 0,
+                context_switches: 1,
+                pomodoros: 2,
+                paused_seconds: 0,
+                private_seconds: 0,
+                idle_seconds: 0,
+            },
+            total_seconds: 180,
+            timeline: vec![
+                TimelineEntry {
+                    ocr_excerpt: Some("fn main() {".to_string()),
+                    ..entry("10:00:00", "VS Code", "main.rs")
+                },
+                entry("10:01:00", "VS Code", "main.rs"),
+                entry("10:02:00", "Safari", "<script>"),
+            ],
+            apps: vec![
+                AppSummary {
+                    app_name: "VS Code".to_string(),
+                    duration_seconds: 120,
+                    capture_count: 2,
+                },
+                AppSummary {
+                    app_name: "Safari".to_string(),
+                    duration_seconds: 60,
+                    capture_count: 1,
+                },
+            ],
+            media_seconds: 0,
+            media: Vec::new(),
+            non_work_seconds: 0,
+            non_work: Vec::new(),
+            paused: Vec::new(),
+            distraction_seconds: 0,
+            distractions: Vec::new(),
+            sleeps: Vec::new(),
+            highlights: vec![Highlight {
+                hour: "2025-01-10T10".to_string(),
+                capture_id: 1,
+                captured_at: "2025-01-10T10:00:00+09:00".to_string(),
+                image_path: "/Users/me/images/2025-01-10/100000 #1.jpg".to_string(),
+                active_app: "VS Code".to_string(),
+                window_title: "main.rs".to_string(),
+                score: 120,
+                image_hash: None,
+            }],
+            notes: vec![Note {
+                id: Some(1),
+                created_at: "2025-01-10T10:30:00+09:00".to_string(),
+                text: "設計レビュー & 修正".to_string(),
+            }],
+            goals: vec![GoalProgress {
+                goal: TOTAL_GOAL.to_string(),
+                target_seconds: 3600,
+                seconds: 120,
+                achieved: false,
+                current_streak: 12,
+                longest_streak: 30,
+            }],
+        };
+
+        let html = render_html(&report);
+        assert!(html.contains("<title>2025-01-10 の活動レポート</title>"));
+        assert!(html.contains("フォーカス <strong>2分</strong>"));
+        assert!(html.contains("コンテキストスイッチ <strong>1回</strong>"));
+        assert!(html.contains("ポモドーロ <strong>2回</strong>"));
+        assert!(html.contains("<td class=\"num\">10:00:00 〜 10:01:00</td>"));
+        assert!(html.contains("width: 50%\"></div>"));
+        assert!(html.contains("main.rs<br><small>fn main() {</small></td>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains(
+            "<img src=\"file:///Users/me/images/2025-01-10/100000%20%231.jpg\" loading=\"lazy\""
+        ));
+        assert!(html.contains("<figcaption>10時 VS Code"));
+        assert!(html.contains("<td class=\"num\">10:30</td><td>設計レビュー &amp; 修正</td>"));
+        assert!(
+            html.contains("<tr><td>合計</td><td class=\"num\">2分 / 1時間0分</td><td>未達成</td>")
+        );
+        assert!(html.contains("現在のストリーク: 12日 / 最長: 30日"));
+    }
+}
@@ -0,0 +1,455 @@
+//! 多言語化モジュール - CLIメッセージ・レポート見出しのメッセージカタログ
+
+use serde::Deserialize;
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// プロセス全体の表示言語（未設定なら日本語）
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// 表示言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// 日本語
+    #[default]
+    Ja,
+    /// 英語
+    En,
+}
+
+impl Language {
+    /// 現在の表示言語
+    pub fn current() -> Self {
+        LANGUAGE.get().copied().unwrap_or_default()
+    }
+
+    /// 環境変数（`LC_ALL` > `LC_MESSAGES` > `LANG`）のロケールから判定
+    pub fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty());
+        Self::from_locale(locale.as_deref())
+    }
+
+    /// HTMLの `lang` 属性に使う言語コード
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Ja => "ja",
+            Language::En => "en",
+        }
+    }
+
+    /// ロケール（`en_US.UTF-8` など）から判定（英語以外は日本語）
+    fn from_locale(locale: Option<&str>) -> Self {
+        match locale {
+            Some(locale) if locale.starts_with("en") => Language::En,
+            _ => Language::Ja,
+        }
+    }
+}
+
+/// 表示言語を設定（`None` なら環境変数から判定）
+///
+/// プロセスで最初の1回だけ有効
+pub fn init(language: Option<Language>) {
+    let _ = LANGUAGE.set(language.unwrap_or_else(Language::from_env));
+}
+
+/// メッセージカタログを定義する（`名前 => "日本語", "英語";`）
+macro_rules! messages {
+    ($($name:ident => $ja:literal, $en:literal;)*) => {
+        /// メッセージカタログのキー
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Message {
+            $($name,)*
+        }
+
+        impl Message {
+            /// カタログのすべてのキー
+            #[cfg(test)]
+            const ALL: &'static [Message] = &[$(Message::$name,)*];
+
+            /// 指定した言語のテンプレート
+            pub fn template(self, language: Language) -> &'static str {
+                match (self, language) {
+                    $(
+                        (Message::$name, Language::Ja) => $ja,
+                        (Message::$name, Language::En) => $en,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    // 共通
+    Captures => "{} キャプチャ", "{} captures";
+    DurationHours => "{}時間{}分", "{}h {}m";
+    DurationMinutes => "{}分", "{}m";
+    Times => "{}回", "{}";
+    TimeRange => "{} 〜 {}", "{} - {}";
+    WithDuration => "{}（{}）", "{} ({})";
+    Monday => "月", "Mon";
+    Tuesday => "火", "Tue";
+    Wednesday => "水", "Wed";
+    Thursday => "木", "Thu";
+    Friday => "金", "Fri";
+    Saturday => "土", "Sat";
+    Sunday => "日", "Sun";
+
+    // 初期セットアップ
+    InitNeedsTerminal => "tracker init は端末で実行してください", "Run tracker init in a terminal";
+    InitHeading => "=== Habit Tracker 初期セットアップ ===", "=== Habit Tracker setup ===";
+    InitStepConfig => "[1/4] 設定ファイル", "[1/4] Config file";
+    ConfigExists => "設定ファイルがあります: {}", "A config file already exists: {}";
+    ConfirmOverwriteConfig => "デフォルト値で作り直しますか？", "Recreate it with the default values?";
+    PromptInterval => "キャプチャ間隔（秒）", "Capture interval (seconds)";
+    PromptIntervalInvalid => "1以上を指定してください", "Enter 1 or more";
+    PromptQuality => "JPEG品質（0-100）", "JPEG quality (0-100)";
+    PromptQualityInvalid => "0〜100で指定してください", "Enter a value from 0 to 100";
+    ConfigWritten => "設定ファイルを作成しました: {}", "Created the config file: {}";
+    ConfigKept => "既存の設定ファイルを使います", "Keeping the existing config file";
+    InitStepPermissions => "[2/4] 権限の確認", "[2/4] Permissions";
+    PermissionScreenRecording => "画面収録", "Screen Recording";
+    PermissionAccessibility => "アクセシビリティ", "Accessibility";
+    PermissionHint => "システム設定 > プライバシーとセキュリティ で、このターミナル（またはtracker）に不足している権限を許可してください",
+        "Allow the missing permissions for this terminal (or tracker) in System Settings > Privacy & Security";
+    ConfirmOpenSettings => "システム設定を開きますか？", "Open System Settings?";
+    PermissionCheckFailed => "権限を確認できませんでした: {}", "Could not check the permissions: {}";
+    InitStepTestCapture => "[3/4] テストキャプチャ", "[3/4] Test capture";
+    ConfirmTestCapture => "テストキャプチャを1枚撮って記録しますか？", "Take and record one test capture?";
+    InitStepLaunchd => "[4/4] ログイン時の自動起動", "[4/4] Start at login";
+    LaunchdAlreadyInstalled => "launchdに登録済みです: {}", "Already registered with launchd: {}";
+    ConfirmLaunchd => "ログイン時にトラッカーを自動起動するよう launchd に登録しますか？",
+        "Register with launchd to start the tracker at login?";
+    ConfirmReinstallLaunchd => "登録し直しますか？", "Register again?";
+    LaunchdInstalled => "launchdに登録し、トラッカーを起動しました: {}",
+        "Registered with launchd and started the tracker: {}";
+    InitDone => "セットアップが完了しました。`tracker status` で記録の状態を、`tracker report --today` で今日のレポートを確認できます",
+        "Setup is complete. Check the recording status with `tracker status` and today's report with `tracker report --today`";
+    InitStartHint => "`tracker start --daemon` で記録を開始できます",
+        "Start recording with `tracker start --daemon`";
+
+    // アンインストール
+    UninstallLaunchd => "launchdの登録を解除します: {}", "Will unregister from launchd: {}";
+    LaunchdNotInstalled => "launchdには登録されていません", "Not registered with launchd";
+    UninstallPurgeTargets => "次のファイル・ディレクトリを削除します:",
+        "Will delete the following files and directories:";
+    NoDataToPurge => "削除するデータはありません", "No data to delete";
+    UninstallOutsideTargets => "次のディレクトリはデータの置き場所の外にあり、トラッカー以外のファイルも含むため、確認した場合だけ削除します:",
+        "The following directories are outside the data directory and contain other files, so they are deleted only if you confirm:";
+    UninstallKeepData => "DB・画像・設定・ログは残します（削除するには --purge-data を指定してください）",
+        "The database, images, config and logs are kept (use --purge-data to delete them)";
+    UninstallDryRun => "--dry-run のため何も変更していません", "Nothing was changed because of --dry-run";
+    ConfirmUninstall => "アンインストールしますか？", "Uninstall?";
+    ConfirmUninstallPurge => "アンインストールしてデータを削除しますか？（スクリーンショットを含め、元に戻せません）",
+        "Uninstall and delete the data? (This includes the screenshots and cannot be undone)";
+    ConfirmPurgeOutside => "置き場所の外のディレクトリも削除しますか？（中のファイルはすべて削除されます）",
+        "Also delete the directories outside the data directory? (Every file in them is deleted)";
+    PurgeOutsideSkipped => "置き場所の外のディレクトリは削除しません", "Directories outside the data directory are kept";
+    UninstallCancelled => "中止しました", "Cancelled";
+    LaunchdUninstalled => "launchdの登録を解除しました: {}", "Unregistered from launchd: {}";
+    DataPurged => "{}件のファイル・ディレクトリを削除しました", "Deleted {} files and directories";
+    Uninstalled => "アンインストールしました。tracker のバイナリは手動で削除してください",
+        "Uninstalled. Delete the tracker binary manually";
+
+    // トラッカーの操作
+    DaemonStarted => "バックグラウンドで起動しました（PID: {}、出力: {}）",
+        "Started in the background (PID: {}, output: {})";
+    TrackerStopped => "トラッカーを停止しました（PID: {}）", "Stopped the tracker (PID: {})";
+    TrackerNotRunning => "トラッカーは起動していません", "The tracker is not running";
+    ReloadRequested => "設定の再読み込みを要求しました（PID: {}）",
+        "Requested a config reload (PID: {})";
+    CaptureRequested => "即時キャプチャを要求しました（PID: {}）",
+        "Requested an immediate capture (PID: {})";
+    CaptureSkippedPaused => "一時停止中のためキャプチャしません", "Tracking is paused; not capturing";
+    CaptureSkippedFocus => "集中モードとの連動によりキャプチャしません",
+        "Not capturing because of the Focus mode settings";
+    Captured => "キャプチャしました", "Captured";
+    CapturedWithFailures => "キャプチャを記録しましたが、一部の処理に失敗しました:",
+        "Recorded the capture, but some steps failed:";
+    TrackingPaused => "トラッキングを一時停止しました", "Paused tracking";
+    TrackingResumed => "トラッキングを再開しました", "Resumed tracking";
+
+    // 状態・稼働履歴
+    Status => "状態: {}", "Status: {}";
+    StatusPaused => "一時停止中", "paused";
+    StatusRecording => "記録中", "recording";
+    StatusStopped => "停止中（トラッカーが起動していません）", "stopped (the tracker is not running)";
+    LastCapture => "最終キャプチャ: {} ({})", "Last capture: {} ({})";
+    DayRecord => "{}の記録: {} ({})", "Recorded on {}: {} ({})";
+    NoSessions => "セッションの記録はありません", "No sessions recorded";
+    NoAlerts => "アラートの記録はありません", "No alerts recorded";
+    AlertBreak => "休憩", "break";
+    AlertDistraction => "脱線", "distraction";
+    AlertCaptureFailure => "キャプチャ失敗", "capture failure";
+    AlertPomodoro => "ポモドーロ", "pomodoro";
+    AlertRepeatedError => "エラーの多発", "repeated error";
+    AlertIdle => "放置の疑い", "possibly away";
+    PomodoroStarted => "ポモドーロを開始しました（作業 {}分 / 休憩 {}分）",
+        "Started a pomodoro ({} min work / {} min break)";
+    PomodoroStopped => "ポモドーロを終了しました", "Stopped the pomodoro";
+    PomodoroNotRunning => "ポモドーロは実行していません", "No pomodoro is running";
+    PomodoroStatus => "{}（{}サイクル目、残り {}） / 完了 {}回", "{} (cycle {}, {} left) / {} completed";
+    PomodoroWork => "作業中", "Working";
+    PomodoroBreak => "休憩中", "On a break";
+    KpiPomodoros => " / ポモドーロ {}回", " / Pomodoros {}";
+    PausedPrivateSummary => "一時停止: {} / プライベート: {}", "Paused: {} / Private: {}";
+    IdleSummary => "放置の疑い: {}（同じウィンドウのまま入力がなかった時間、作業時間に含む）",
+        "Possibly away: {} (same window with no input, counted as work)";
+    SessionRunning => "稼働中", "running";
+
+    // レポート
+    HtmlReportOpened => "HTMLレポートを開きました: {}", "Opened the HTML report: {}";
+    MarkdownReportWritten => "Markdownレポートを書き出しました: {}", "Wrote the Markdown report: {}";
+    ObsidianNotConfigured => "config.tomlに [obsidian] の vault_path が設定されていません",
+        "vault_path under [obsidian] is not set in config.toml";
+    DailyNoteWritten => "デイリーノートに書き込みました: {}", "Wrote to the daily note: {}";
+    NoCaptures => "{}にキャプチャはありませんでした。", "No captures on {}.";
+    DailyHeading => "=== {} の活動レポート ===", "=== Activity report for {} ===";
+    KpiSummary => "総作業 {} / 会議 {} / フォーカス {} / 脱線 {} / コンテキストスイッチ {}回",
+        "Work {} / Meetings {} / Focus {} / Distractions {} / Context switches {}";
+    TimelineHeading => "--- タイムライン ---", "--- Timeline ---";
+    MediaMark => " [動画]", " [video]";
+    Sleep => "{} - {} | (スリープ {})", "{} - {} | (sleep {})";
+    SleepOngoing => "{} - | (スリープ)", "{} - | (sleep)";
+    AppsHeading => "--- アプリ別時間 ---", "--- Time by app ---";
+    MediaHeading => "--- 動画視聴（作業時間に含めない） ---",
+        "--- Video watching (not counted as work) ---";
+    NonWorkHeading => "--- 非作業（作業時間に含めない） ---",
+        "--- Non-work (not counted as work) ---";
+    PausedHeading => "--- 一時停止中（作業時間に含めない） ---",
+        "--- While paused (not counted as work) ---";
+    DistractionHeading => "--- 脱線時間（作業時間帯） {} ---",
+        "--- Distractions during work hours {} ---";
+    NotesHeading => "--- メモ ---", "--- Notes ---";
+    GoalsHeading => "--- 目標 ---", "--- Goals ---";
+    GoalLine => "{}: {} / {}（{}）", "{}: {} / {} ({})";
+    GoalStreak => "現在のストリーク: {}日 / 最長: {}日", "Current streak: {} days / Longest: {} days";
+    GoalAchieved => "達成", "achieved";
+    GoalNotAchieved => "未達成", "not yet";
+    GoalTotal => "合計", "Total";
+    NoGoals => "目標が設定されていません（config.tomlの [goals] で設定できます）",
+        "No goals configured (set them under [goals] in config.toml)";
+    HighlightsHeading => "--- 時間帯ごとの代表画像 ---", "--- Highlights by hour ---";
+    HighlightsSelected => "{} の{}時間帯の代表画像を選びました",
+        "Selected highlights on {} for {} hour(s)";
+    NoHighlights => "{}に代表画像にできる画像はありませんでした。", "No images to highlight on {}.";
+    TrendHeading => "=== 作業時間の推移 ({} 〜 {}) ===", "=== Work time trend ({} - {}) ===";
+    TrendDaily => "日別  {}  合計 {}（1日平均 {}）", "Daily  {}  total {} (avg {} per day)";
+    TrendMedia => "動画視聴（作業時間に含めない） {}", "Video watching (not counted as work) {}";
+    WeeklyHeading => "--- 週別 ---", "--- Weekly ---";
+    WeekStarting => "{}〜", "{}-";
+    CategoryHeading => "--- カテゴリ別 ---", "--- By category ---";
+    KeywordHeading => "=== キーワード推移 ({} 〜 {}) ===", "=== Keyword trend ({} - {}) ===";
+    KeywordTotal => "--- {} --- 合計 {} ({})", "--- {} --- total {} ({})";
+    KeywordDaily => "日別:", "Daily:";
+    KeywordHourly => "時間帯:", "By hour:";
+    Hour => "{}時", "{}:00";
+    HourlyHeading => "=== {} の時間帯別内訳 ===", "=== Hourly breakdown for {} ===";
+    PickReportDate => "レポートの日付を選択（Escでキャンセル）", "Pick a report date (Esc to cancel)";
+
+    // HTML・Markdownのレポートと朝会メモ
+    ReportTitle => "{} の活動レポート", "Activity report for {}";
+    KpiWork => "総作業", "Work";
+    KpiMeeting => "会議", "Meetings";
+    KpiFocus => "フォーカス", "Focus";
+    KpiDistraction => "脱線", "Distractions";
+    KpiSwitches => "コンテキストスイッチ", "Context switches";
+    KpiPomodoro => "ポモドーロ", "Pomodoros";
+    GoalsSection => "目標", "Goals";
+    GoalLineWithStreak => "{}: {} / {}（{}、{}）", "{}: {} / {} ({}, {})";
+    AppsSection => "アプリ別時間", "Time by app";
+    HighlightsSection => "時間帯ごとの代表画像", "Highlights by hour";
+    NotesSection => "メモ", "Notes";
+    TimelineSection => "タイムライン", "Timeline";
+    AppColumn => "アプリ", "App";
+    DurationColumn => "時間", "Duration";
+    CapturesColumn => "キャプチャ", "Captures";
+    TimeColumn => "時刻", "Time";
+    WindowTitleColumn => "ウィンドウタイトル", "Window title";
+    StandupHeading => "{} の作業（合計 {}）", "Work on {} ({} in total)";
+    StandupNoRecords => "記録なし", "No records";
+
+    // 統計
+    PerfHeading => "=== キャプチャ処理時間 ({} 〜 {}) ===",
+        "=== Capture processing time ({} - {}) ===";
+    PerfScreenshot => "スクリーンショット", "Screenshot";
+    PerfOcr => "OCR", "OCR";
+    PerfDb => "DB書き込み", "DB write";
+    PerfTotal => "合計", "Total";
+    NoPerfData => "計測データがありません", "No measurements";
+    PerfNoSamples => "計測なし", "no samples";
+    PerfStep => "p50 {}ms  p95 {}ms  最大 {}ms  ({}件)", "p50 {}ms  p95 {}ms  max {}ms  ({} samples)";
+    PerfRatio => "間隔 {}秒 に対する処理時間の割合: p50 {}% / p95 {}%",
+        "Processing time relative to the {}s interval: p50 {}% / p95 {}%";
+    StorageHeading => "=== 画像の保存量 ({} 〜 {}) ===", "=== Image storage ({} - {}) ===";
+    NoStoredImages => "保存した画像がありません", "No stored images";
+    StorageDaily => "日別  {}  合計 {}（{}枚、1日平均 {}）",
+        "Daily  {}  total {} ({} images, avg {} per day)";
+    StorageImages => "{}枚", "{} images";
+    DbCheckOk => "整合性チェック: 問題は見つかりませんでした", "Integrity check: no problems found";
+    DbCheckFailed => "整合性チェック: {}件の問題が見つかりました", "Integrity check: found {} problem(s)";
+    DbVacuumed => "VACUUMとWALチェックポイントを実行しました: {} → {}",
+        "Ran VACUUM and a WAL checkpoint: {} → {}";
+    DbCheckpointBusy => "読み取り中の接続があるため、WALの一部を書き戻せませんでした",
+        "Some WAL pages could not be written back because another connection is reading";
+
+    // OCR
+    DictionaryTrained => "圧縮辞書を学習しました（ID: {}）", "Trained a compression dictionary (ID: {})";
+    DictionarySkipped => "OCRテキストが少ないため、辞書なしで圧縮します",
+        "Not enough OCR text; compressing without a dictionary";
+    OcrCompressed => "{}件のOCRテキストを圧縮しました: {} → {}", "Compressed {} OCR texts: {} → {}";
+    PrivateOcrScrubbed => "プライベートなキャプチャ{}件のOCRテキストを消去しました",
+        "Erased OCR text from {} private captures";
+    OcrDisabled => "OCRは無効になっています（ocr_backend = \"none\"）",
+        "OCR is disabled (ocr_backend = \"none\")";
+    OcrError => "OCRエラー: {}", "OCR error: {}";
+    NoTextDetected => "テキストは検出されませんでした", "No text detected";
+    RedoNeedsFilter => "--redo には --date または --where を指定してください",
+        "Specify --date or --where with --redo";
+    NoCapturesToRedo => "再処理対象のキャプチャはありません", "No captures to reprocess";
+    NoCapturesWithoutOcr => "OCR未処理のキャプチャはありません", "No captures are waiting for OCR";
+    OcrNeedsTarget => "--file・--batch・--all のいずれかのオプションを指定してください",
+        "Specify --file, --batch, or --all";
+    NoOcrFailures => "OCRに失敗したキャプチャはありません", "No captures failed OCR";
+    OcrRetryWaiting => "再試行待ち（次回: {}）", "waiting to retry (next: {})";
+    OcrGaveUp => "打ち切り", "gave up";
+    OcrAttempts => "試行{}回", "{} attempts";
+    OcrFailureSummary => "{}件（{}回失敗すると再試行を打ち切ります）",
+        "{} captures (retries stop after {} failures)";
+    OcrBatchStarted => "{}件のキャプチャをOCR処理します（並列数: {}）...",
+        "Running OCR on {} captures ({} in parallel)...";
+    OcrBatchFailed => "失敗: {} ({})", "Failed: {} ({})";
+    OcrBatchDone => "完了: 成功 {}件（うち流用 {}件） / 失敗 {}件",
+        "Done: {} succeeded ({} reused) / {} failed";
+    OcrBatchCancelled => "中断しました（処理済みの結果は保存されています）",
+        "Cancelled (results processed so far have been saved)";
+    OcrAllResumed => "{}に開始した処理の続きから再開します（処理済み {}件、ID {}まで）",
+        "Resuming the run started at {} ({} processed, up to ID {})";
+    OcrAllCancelled => "中断しました（次回の tracker ocr --all で続きから処理します）",
+        "Cancelled (the next tracker ocr --all continues from here)";
+
+    // エクスポート・削除・インポートなど
+    Exported => "{} にエクスポートしました", "Exported to {}";
+    NoOcrText => "{}のOCRテキストがありません。", "No OCR text for {}.";
+    ShortcutInputRequired => "操作を引数か標準入力で指定してください（pause / resume / note <内容> / report [対象日]）",
+        "Specify an action as an argument or on stdin (pause / resume / note <text> / report [date])";
+    NoteAdded => "メモを追加しました: {}", "Added a note: {}";
+    WorklogNotConfigured => "config.tomlに [worklog] の api_token が設定されていません",
+        "api_token under [worklog] is not set in config.toml";
+    NoWorklogs => "{}にチケットを判定できた作業はありませんでした。", "No work matched a ticket on {}.";
+    WorklogUpToDate => "{}の工数はすべて登録済みです。", "All work on {} is already logged.";
+    WorklogPushed => "{}の工数を{}件登録しました:", "Logged work on {} to {} ticket(s):";
+    WorklogDryRun => "{}の工数を{}件登録します（--dry-run のため登録していません）:",
+        "Would log work on {} to {} ticket(s) (not logged because of --dry-run):";
+    WorklogEntry => "{}（この日の合計 {}）", "{} ({} in total for the day)";
+    ChartWritten => "グラフを書き出しました: {}", "Wrote the chart: {}";
+    WordcloudNoDictionary => "形態素解析の辞書（wordcloud_dictionary）が未設定のため、文字種で語を分割します", "No morphological dictionary (wordcloud_dictionary) is set; splitting words by character class";
+    WordcloudWritten => "ワードクラウドを書き出しました: {}（{}語）", "Wrote the word cloud: {} ({} words)";
+    CaptureNotFound => "ID {} のキャプチャはありません", "No capture with ID {}";
+    NothingToDelete => "削除するキャプチャはありません", "No captures to delete";
+    DeleteDryRun => "{}件のキャプチャ（画像{}件）が削除対象です（--dry-run のため削除していません）",
+        "{} captures ({} images) would be deleted (nothing was deleted because of --dry-run)";
+    InvalidDateTime => "日時の形式が不正です: {}", "Invalid date and time: {}";
+    DeleteRangeOrder => "--from は --to より前を指定してください", "--from must be earlier than --to";
+    ImageDeleteFailed => "画像の削除失敗: {} ({})", "Failed to delete the image: {} ({})";
+    Deleted => "{}件のキャプチャと画像{}件を削除しました", "Deleted {} captures and {} images";
+    ManPagesWritten => "manページを書き出しました: {}", "Wrote the man pages: {}";
+    Imported => "{}件のキャプチャをインポートしました（取り込み済みの{}件はスキップ）",
+        "Imported {} captures (skipped {} already imported)";
+    NoLogsYet => "ログはまだありません: {}", "No logs yet: {}";
+    FileLoggingDisabled => "ファイルへのログ出力は無効です（config.tomlで log_to_file = true を設定してください）",
+        "Logging to files is disabled (set log_to_file = true in config.toml)";
+    PathsProfile => "プロファイル", "Profile";
+    PathsConfigFile => "設定ファイル", "Config file";
+    PathsConfigMissing => "{}（ファイルがないため既定値を使用）", "{} (not found; using defaults)";
+    PathsConfigDir => "設定フォルダ", "Config directory";
+    PathsDataDir => "データフォルダ", "Data directory";
+    PathsDb => "データベース", "Database";
+    PathsImages => "画像", "Images";
+    PathsLogs => "ログ", "Logs";
+    PathsPidFile => "PIDファイル", "PID file";
+    OpenDateImagesOnly => "--date は images を開くときだけ指定できます",
+        "--date can only be used with images";
+    OpenPathNotFound => "フォルダがありません: {}", "No such directory: {}";
+    OpenFailed => "openコマンドが失敗しました: {}", "The open command failed: {}";
+    Opened => "開きました: {}", "Opened: {}";
+}
+
+impl Message {
+    /// 現在の表示言語の文言
+    pub fn text(self) -> &'static str {
+        self.template(Language::current())
+    }
+
+    /// 現在の表示言語のテンプレートの `{}` を、順に引数で置き換える
+    pub fn format(self, args: &[&dyn Display]) -> String {
+        fill(self.text(), args)
+    }
+}
+
+/// テンプレートの `{}` を順に引数で置き換える（余った `{}` はそのまま残す）
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find("{}") {
+        result.push_str(&rest[..index]);
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[index + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Language::from_locale(Some("en_US.UTF-8")), Language::En);
+        assert_eq!(Language::from_locale(Some("ja_JP.UTF-8")), Language::Ja);
+        assert_eq!(Language::from_locale(Some("C")), Language::Ja);
+        assert_eq!(Language::from_locale(None), Language::Ja);
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill("{}件のキャプチャと画像{}件", &[&3, &"2"]),
+            "3件のキャプチャと画像2件"
+        );
+        assert_eq!(fill("{} - {}", &[&"10:00"]), "10:00 - {}");
+    }
+
+    #[test]
+    fn test_catalog_placeholders_match() {
+        // どちらの言語でも同じ数の引数を埋め込む
+        for message in Message::ALL {
+            let ja = message.template(Language::Ja);
+            let en = message.template(Language::En);
+            assert_eq!(
+                ja.matches("{}").count(),
+                en.matches("{}").count(),
+                "{:?}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_language_is_japanese() {
+        // initしていないテストプロセスでは日本語
+        assert_eq!(Message::Captured.text(), "キャプチャしました");
+        assert_eq!(Message::Captures.format(&[&5]), "5 キャプチャ");
+    }
+}
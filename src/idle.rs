@@ -0,0 +1,199 @@
+//! 放置検知モジュール - 同じウィンドウのまま入力のない状態の継続の計測と警告の判定
+
+use crate::break_reminder::BREAK_GAP_SECONDS;
+use crate::database::InputMetrics;
+use chrono::{DateTime, Local};
+
+/// 放置の疑いがあるキャプチャに付ける追加のkey-valueのキー（値は `true`）
+pub const IDLE_EXTRA_KEY: &str = "idle_suspected";
+
+/// 同じウィンドウのまま入力がない区間
+#[derive(Debug)]
+struct Streak {
+    app: String,
+    window_title: String,
+    started_at: DateTime<Local>,
+    last_seen: DateTime<Local>,
+    /// まだ放置の疑いを記録していないキャプチャ
+    capture_ids: Vec<i64>,
+    warned: bool,
+}
+
+/// 放置の判定結果
+#[derive(Debug, PartialEq, Eq)]
+pub struct IdleDetection {
+    /// 通知するメッセージ（区間で最初に判定したときのみ）
+    pub message: Option<String>,
+    /// 放置の疑いを記録するキャプチャ
+    pub capture_ids: Vec<i64>,
+}
+
+/// 放置の監視
+///
+/// キャプチャのたびに呼び出し、同じアプリ・ウィンドウタイトルのまま入力のない状態が
+/// 設定の時間だけ続いたら、通知するメッセージと区間のキャプチャを返す。
+/// 以降は入力があるかウィンドウが変わるまで、キャプチャごとに記録対象として返す
+#[derive(Debug)]
+pub struct IdleMonitor {
+    warn_minutes: u64,
+    streak: Option<Streak>,
+}
+
+impl IdleMonitor {
+    /// 新しいIdleMonitorを作成
+    pub fn new(warn_minutes: u64) -> Self {
+        Self {
+            warn_minutes,
+            streak: None,
+        }
+    }
+
+    /// 一時停止・スリープなどで記録が途切れたことを記録
+    pub fn reset(&mut self) {
+        self.streak = None;
+    }
+
+    /// キャプチャを記録し、放置の疑いがあれば判定結果を返す
+    ///
+    /// `input` は前回のキャプチャからの入力活動量（取得できなければ `None` で、判定しない）
+    pub fn observe(
+        &mut self,
+        now: DateTime<Local>,
+        capture_id: i64,
+        app: &str,
+        window_title: &str,
+        input: Option<&InputMetrics>,
+    ) -> Option<IdleDetection> {
+        if input.is_none_or(|input| *input != InputMetrics::default()) {
+            self.streak = None;
+            return None;
+        }
+
+        let streak = match self.streak {
+            Some(ref mut streak)
+                if streak.app == app
+                    && streak.window_title == window_title
+                    && (now - streak.last_seen).num_seconds() <= BREAK_GAP_SECONDS =>
+            {
+                streak
+            }
+            _ => {
+                self.streak = Some(Streak {
+                    app: app.to_string(),
+                    window_title: window_title.to_string(),
+                    started_at: now,
+                    last_seen: now,
+                    capture_ids: vec![capture_id],
+                    warned: false,
+                });
+                return None;
+            }
+        };
+        streak.last_seen = now;
+        streak.capture_ids.push(capture_id);
+        if streak.warned {
+            return Some(IdleDetection {
+                message: None,
+                capture_ids: std::mem::take(&mut streak.capture_ids),
+            });
+        }
+        let minutes = (now - streak.started_at).num_minutes();
+        if minutes < self.warn_minutes as i64 {
+            return None;
+        }
+
+        streak.warned = true;
+        Some(IdleDetection {
+            message: Some(format!(
+                "{}（{}）のまま{}分入力がありません。放置中ですか？",
+                app, window_title, minutes
+            )),
+            capture_ids: std::mem::take(&mut streak.capture_ids),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const NO_INPUT: InputMetrics = InputMetrics {
+        key_presses: 0,
+        clicks: 0,
+        scrolls: 0,
+    };
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 10, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_warn_after_threshold_and_flag_streak() {
+        let mut monitor = IdleMonitor::new(30);
+        for minute in 0..30 {
+            let id = minute as i64 + 1;
+            assert!(monitor
+                .observe(
+                    at(10, minute),
+                    id,
+                    "Safari",
+                    "ドキュメント",
+                    Some(&NO_INPUT)
+                )
+                .is_none());
+        }
+        // 区間の最初からのキャプチャをまとめて記録する
+        let detection = monitor
+            .observe(at(10, 30), 31, "Safari", "ドキュメント", Some(&NO_INPUT))
+            .unwrap();
+        assert_eq!(
+            detection.message.as_deref(),
+            Some("Safari（ドキュメント）のまま30分入力がありません。放置中ですか？")
+        );
+        assert_eq!(detection.capture_ids, (1..=31).collect::<Vec<_>>());
+
+        // 以降は通知せず、キャプチャごとに記録する
+        assert_eq!(
+            monitor.observe(at(10, 31), 32, "Safari", "ドキュメント", Some(&NO_INPUT)),
+            Some(IdleDetection {
+                message: None,
+                capture_ids: vec![32],
+            })
+        );
+    }
+
+    #[test]
+    fn test_reset_on_input_or_window_change() {
+        let mut monitor = IdleMonitor::new(30);
+        let typed = InputMetrics {
+            key_presses: 3,
+            ..NO_INPUT
+        };
+        for minute in 0..29 {
+            monitor.observe(at(10, minute), 1, "Safari", "ドキュメント", Some(&NO_INPUT));
+        }
+        assert!(monitor
+            .observe(at(10, 29), 2, "Safari", "ドキュメント", Some(&typed))
+            .is_none());
+        assert!(monitor
+            .observe(at(10, 30), 3, "Safari", "ドキュメント", Some(&NO_INPUT))
+            .is_none());
+
+        // ウィンドウが変われば数え直す
+        assert!(monitor
+            .observe(at(10, 45), 4, "Safari", "別のページ", Some(&NO_INPUT))
+            .is_none());
+        // 入力活動量が取得できなければ判定しない
+        assert!(monitor
+            .observe(at(11, 30), 5, "Safari", "別のページ", None)
+            .is_none());
+
+        monitor.reset();
+        assert!(monitor
+            .observe(at(12, 0), 6, "Safari", "別のページ", Some(&NO_INPUT))
+            .is_none());
+    }
+}
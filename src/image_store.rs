@@ -1,64 +1,567 @@
 //! 画像ストレージモジュール
 
+use crate::database::ImageInfo;
 use crate::error::ImageStoreError;
-use chrono::{DateTime, Local};
-use std::fs;
-use std::path::PathBuf;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use jpeg_decoder::{Decoder, PixelFormat};
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use tracing::warn;
+
+/// CGDisplayCreateImageでメインディスプレイを撮ってJPEGで保存するJXAスクリプト
+///
+/// 引数は保存先とJPEG品質（0-100）
+const CORE_GRAPHICS_SCRIPT: &str = r#"
+ObjC.import('CoreGraphics');
+ObjC.import('ImageIO');
+function run(argv) {
+    var image = $.CGDisplayCreateImage($.CGMainDisplayID());
+    if (!image) throw new Error('CGDisplayCreateImage failed');
+    var url = $.NSURL.fileURLWithPath(argv[0]);
+    var dest = $.CGImageDestinationCreateWithURL(url, $('public.jpeg'), 1, $());
+    if (!dest) throw new Error('CGImageDestinationCreateWithURL failed');
+    var properties = $.NSDictionary.dictionaryWithObjectForKey(
+        $.NSNumber.numberWithDouble(parseInt(argv[1], 10) / 100),
+        $.kCGImageDestinationLossyCompressionQuality);
+    $.CGImageDestinationAddImage(dest, image, properties);
+    if (!$.CGImageDestinationFinalize(dest)) throw new Error('CGImageDestinationFinalize failed');
+}
+"#;
+
+/// スクリーンショットの撮影方法
+pub trait ScreenshotBackend: Send + Sync {
+    /// ログに表示する名前
+    fn name(&self) -> &'static str;
+
+    /// スクリーンショットを撮って `path` に保存
+    fn capture(&self, path: &Path) -> Result<(), ImageStoreError>;
+}
+
+/// screencaptureコマンドによる撮影
+pub struct ScreencaptureBackend;
+
+/// CoreGraphics（CGDisplayCreateImage）による撮影
+///
+/// screencaptureがOSアップデート直後などに失敗する場合の代替。
+/// osascriptのJXAからCoreGraphicsを呼ぶため、メインディスプレイのみを撮る
+pub struct CoreGraphicsBackend {
+    /// JPEG品質（0-100）
+    jpeg_quality: u8,
+}
+
+impl CoreGraphicsBackend {
+    /// 指定のJPEG品質で保存する撮影方法を作成
+    pub fn new(jpeg_quality: u8) -> Self {
+        Self { jpeg_quality }
+    }
+}
+
+impl ScreenshotBackend for ScreencaptureBackend {
+    fn name(&self) -> &'static str {
+        "screencapture"
+    }
+
+    fn capture(&self, path: &Path) -> Result<(), ImageStoreError> {
+        // Note: -q オプションは新しいmacOSでは非対応のため、-t jpg のみ使用
+        let output = Command::new("screencapture")
+            .arg("-x") // サイレント（シャッター音なし）
+            .arg("-t")
+            .arg("jpg")
+            .arg(path)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ImageStoreError::CaptureCommandFailed(format!(
+                "screencapture failed: {}",
+                stderr
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl ScreenshotBackend for CoreGraphicsBackend {
+    fn name(&self) -> &'static str {
+        "CoreGraphics"
+    }
+
+    fn capture(&self, path: &Path) -> Result<(), ImageStoreError> {
+        let output = Command::new("osascript")
+            .arg("-l")
+            .arg("JavaScript")
+            .arg("-e")
+            .arg(CORE_GRAPHICS_SCRIPT)
+            .arg(path)
+            .arg(self.jpeg_quality.to_string())
+            .output()?;
+
+        if !output.status.success() || !path.exists() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ImageStoreError::CaptureCommandFailed(format!(
+                "CGDisplayCreateImage failed: {}",
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 画像ファイル名のデフォルトのテンプレート（`images_dir` からの相対パス）
+pub const DEFAULT_FILENAME_FORMAT: &str = "%Y-%m-%d/%H%M%S.jpg";
+
+/// テンプレート中でアクティブアプリ名に置き換えるプレースホルダー
+const APP_PLACEHOLDER: &str = "%app%";
 
 /// 画像ストレージ
 pub struct ImageStore {
     images_dir: PathBuf,
-    jpeg_quality: u8,
+    filename_format: String,
+    /// 撮影方法（失敗したら次の方法で即時に撮り直す）
+    backends: Vec<Box<dyn ScreenshotBackend>>,
+    /// 遅延保存の一時フォルダ（無効なら `None`）
+    buffer: Option<ImageBuffer>,
+}
+
+/// 遅延保存の一時フォルダ
+///
+/// 画像は `images_dir` と同じ構成で一時フォルダに保存し、一定枚数たまったらまとめて移す
+struct ImageBuffer {
+    dir: PathBuf,
+    capacity: usize,
+    /// 一時フォルダにためている枚数
+    pending: Cell<usize>,
+}
+
+/// 撮影した画像の置き場所
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedImage {
+    /// 記録する保存先のパス
+    pub path: PathBuf,
+    /// 今ファイルがある場所（遅延保存中は一時フォルダ、それ以外は `path` と同じ）
+    pub file: PathBuf,
 }
 
 impl ImageStore {
     /// 新しいImageStoreを作成
+    ///
+    /// screencaptureで撮れなければCoreGraphicsで撮り直す。
+    /// screencaptureは品質を指定できないため、`jpeg_quality` はCoreGraphicsでの保存に使う
     pub fn new(images_dir: PathBuf, jpeg_quality: u8) -> Self {
         Self {
             images_dir,
-            jpeg_quality,
+            filename_format: DEFAULT_FILENAME_FORMAT.to_string(),
+            backends: vec![
+                Box::new(ScreencaptureBackend),
+                Box::new(CoreGraphicsBackend::new(jpeg_quality)),
+            ],
+            buffer: None,
         }
     }
 
+    /// ファイル名・ディレクトリ構成のテンプレートを設定（config: `image_filename_format`）
+    pub fn with_filename_format(mut self, format: impl Into<String>) -> Self {
+        self.filename_format = format.into();
+        self
+    }
+
+    /// 撮影方法を優先順に設定
+    pub fn with_backends(mut self, backends: Vec<Box<dyn ScreenshotBackend>>) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// 画像を `dir` に `capacity` 枚ためてからまとめて保存先に移す（config: `image_buffer_count`、0で無効）
+    ///
+    /// 保存先の外付けディスクを毎回起こさないよう、一時フォルダは内蔵ディスクに置く
+    pub fn with_buffer(mut self, dir: PathBuf, capacity: usize) -> Self {
+        self.buffer = (capacity > 0).then(|| ImageBuffer {
+            dir,
+            capacity,
+            pending: Cell::new(0),
+        });
+        self
+    }
+
     /// スクリーンショットをキャプチャし保存
-    pub fn capture(&self, timestamp: &DateTime<Local>) -> Result<PathBuf, ImageStoreError> {
-        let path = self.get_path(timestamp);
+    ///
+    /// 同じ名前のファイルが既にあれば（一時フォルダにためている分を含む）連番を付けて別のファイルに保存する
+    pub fn capture(
+        &self,
+        timestamp: &DateTime<Local>,
+        app: &str,
+    ) -> Result<CapturedImage, ImageStoreError> {
+        let path = unique_path(self.get_path(timestamp, app), |candidate| {
+            self.is_taken(candidate)
+        });
+        let file = self.buffered_path(&path).unwrap_or_else(|| path.clone());
 
         // 日付ディレクトリを作成
-        if let Some(parent) = path.parent() {
+        if let Some(parent) = file.parent() {
             if !parent.exists() {
                 fs::create_dir_all(parent).map_err(ImageStoreError::DirectoryCreationFailed)?;
             }
         }
 
-        // screencaptureコマンドを実行
-        // Note: -q オプションは新しいmacOSでは非対応のため、-t jpg のみ使用
-        let output = Command::new("screencapture")
-            .arg("-x") // サイレント（シャッター音なし）
-            .arg("-t")
-            .arg("jpg")
-            .arg(&path)
-            .output()?;
+        self.capture_with_fallback(&file)?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ImageStoreError::CaptureCommandFailed(format!(
-                "screencapture failed: {}",
-                stderr
-            )));
+        if let Some(ref buffer) = self.buffer {
+            buffer.pending.set(buffer.pending.get() + 1);
+        }
+        Ok(CapturedImage { path, file })
+    }
+
+    /// 保存しない画像を削除する
+    pub fn discard(&self, image: &CapturedImage) -> io::Result<()> {
+        fs::remove_file(&image.file)?;
+        if let (Some(buffer), true) = (&self.buffer, image.file != image.path) {
+            buffer.pending.set(buffer.pending.get().saturating_sub(1));
+        }
+        Ok(())
+    }
+
+    /// 一時フォルダにためた画像が設定の枚数に達したか
+    pub fn is_buffer_full(&self) -> bool {
+        self.buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.pending.get() >= buffer.capacity)
+    }
+
+    /// 一時フォルダにためた画像（前回の実行で残った分を含む）をすべて保存先に移し、移した枚数を返す
+    pub fn flush(&self) -> Result<usize, ImageStoreError> {
+        let Some(ref buffer) = self.buffer else {
+            return Ok(0);
+        };
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        collect_files(&buffer.dir, &mut files, &mut dirs).map_err(ImageStoreError::FlushFailed)?;
+        files.sort();
+
+        for (moved, file) in files.iter().enumerate() {
+            let Ok(relative) = file.strip_prefix(&buffer.dir) else {
+                continue;
+            };
+            let path = self.images_dir.join(relative);
+            let result = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| move_file(file, &path));
+            if let Err(e) = result {
+                buffer.pending.set(files.len() - moved);
+                return Err(ImageStoreError::FlushFailed(e));
+            }
+        }
+        // 空になったディレクトリは深い方から消す（残っていても次回に使うだけなので失敗は無視する）
+        for dir in dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+        buffer.pending.set(0);
+        Ok(files.len())
+    }
+
+    /// 保存先か一時フォルダに同じ名前のファイルがあるか
+    fn is_taken(&self, path: &Path) -> bool {
+        path.exists() || self.buffered_path(path).is_some_and(|file| file.exists())
+    }
+
+    /// 遅延保存が有効なら、保存先のパスに対応する一時フォルダのパス
+    fn buffered_path(&self, path: &Path) -> Option<PathBuf> {
+        let buffer = self.buffer.as_ref()?;
+        buffered_image_path(&self.images_dir, &buffer.dir, path)
+    }
+
+    /// 撮影方法を順に試し、最初に成功した方法で保存する
+    fn capture_with_fallback(&self, path: &Path) -> Result<(), ImageStoreError> {
+        let mut errors = Vec::new();
+        for backend in &self.backends {
+            match backend.capture(path) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!("{}でのキャプチャ失敗: {}", backend.name(), e);
+                    errors.push(format!("{}: {}", backend.name(), e));
+                }
+            }
         }
+        Err(ImageStoreError::AllBackendsFailed(errors.join(" / ")))
+    }
 
-        Ok(path)
+    /// タイムスタンプとアクティブアプリ名からファイルパスを生成
+    ///
+    /// テンプレートの `%app%` はアプリ名に、それ以外は `strftime` の書式として置き換える
+    /// （デフォルトの形式: YYYY-MM-DD/HHMMSS.jpg）
+    pub fn get_path(&self, timestamp: &DateTime<Local>, app: &str) -> PathBuf {
+        let app = sanitize_file_name(app);
+        let mut relative = String::new();
+        for (i, part) in self.filename_format.split(APP_PLACEHOLDER).enumerate() {
+            if i > 0 {
+                relative.push_str(&app);
+            }
+            // 不正な書式は設定の読み込み時に弾いているが、念のためそのまま使う
+            if write!(relative, "{}", timestamp.format(part)).is_err() {
+                relative.push_str(part);
+            }
+        }
+        self.images_dir.join(relative)
     }
 
-    /// タイムスタンプからファイルパスを生成
+    /// 指定日の画像を保存するディレクトリ
     ///
-    /// 形式: YYYY-MM-DD/HHMMSS.jpg
-    pub fn get_path(&self, timestamp: &DateTime<Local>) -> PathBuf {
-        let date_dir = timestamp.format("%Y-%m-%d").to_string();
-        let filename = timestamp.format("%H%M%S.jpg").to_string();
-        self.images_dir.join(date_dir).join(filename)
+    /// テンプレートのファイル名より前の部分を日付で置き換える。ディレクトリが日付で
+    /// 分かれていないか、アプリ名で分かれるテンプレートでは `images_dir` を返す
+    pub fn date_dir(&self, date: NaiveDate) -> PathBuf {
+        let Some((dir, _)) = self.filename_format.rsplit_once('/') else {
+            return self.images_dir.clone();
+        };
+        if dir.contains(APP_PLACEHOLDER) {
+            return self.images_dir.clone();
+        }
+        let mut relative = String::new();
+        // タイムゾーンの書式は日付だけでは決まらないため、使えなければ置き場所全体にする
+        if write!(relative, "{}", date.and_time(NaiveTime::MIN).format(dir)).is_err() {
+            return self.images_dir.clone();
+        }
+        self.images_dir.join(relative)
+    }
+}
+
+/// 保存先のパスに対応する遅延保存の一時フォルダのパス（保存先の外のパスなら `None`）
+pub fn buffered_image_path(images_dir: &Path, buffer_dir: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(images_dir).ok()?;
+    Some(buffer_dir.join(relative))
+}
+
+/// ファイル名のテンプレートを検証し、不正ならその理由を返す
+pub fn validate_filename_format(format: &str) -> Result<(), String> {
+    let path = Path::new(format);
+    if format.is_empty() || path.is_absolute() {
+        return Err("images_dirからの相対パスを指定してください".to_string());
+    }
+    if path
+        .components()
+        .any(|component| component == std::path::Component::ParentDir)
+    {
+        return Err("`..` は使えません".to_string());
+    }
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    if !matches!(extension.as_deref(), Some("jpg" | "jpeg")) {
+        return Err("拡張子は .jpg にしてください".to_string());
+    }
+    let invalid = format
+        .split(APP_PLACEHOLDER)
+        .any(|part| StrftimeItems::new(part).any(|item| matches!(item, Item::Error)));
+    if invalid {
+        return Err("日時の書式が不正です".to_string());
+    }
+    Ok(())
+}
+
+/// アプリ名をファイル名に使える文字列にする（パス区切りや制御文字は `_` に置き換える）
+fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        "_".to_string()
+    } else {
+        name
+    }
+}
+
+/// `dir` 以下のファイルとサブディレクトリを集める（`dir` がなければ何もしない）
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, dirs: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path.clone());
+            collect_files(&path, files, dirs)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// ファイルを移す（別のディスクへはコピーしてから元を消す）
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+/// 同じ名前のファイルがあれば、拡張子の前に `_1`, `_2`, ... を付けて重ならないパスにする
+///
+/// `is_taken` で使用済みかを判定する
+fn unique_path(path: PathBuf, is_taken: impl Fn(&Path) -> bool) -> PathBuf {
+    if !is_taken(&path) {
+        return path;
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+        .find(|candidate| !is_taken(candidate))
+        .unwrap_or(path)
+}
+
+/// 画像の輝度の統計（0〜255）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Luminance {
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Luminance {
+    /// 画素値の列から計算（RGBはBT.601の係数で輝度に変換する）
+    fn from_pixels(pixels: &[u8], format: PixelFormat) -> Option<Self> {
+        let luma: Vec<f64> = match format {
+            PixelFormat::L8 => pixels.iter().map(|&v| v as f64).collect(),
+            PixelFormat::L16 => pixels
+                .chunks_exact(2)
+                .map(|v| u16::from_be_bytes([v[0], v[1]]) as f64 / 257.0)
+                .collect(),
+            PixelFormat::RGB24 => pixels
+                .chunks_exact(3)
+                .map(|v| 0.299 * v[0] as f64 + 0.587 * v[1] as f64 + 0.114 * v[2] as f64)
+                .collect(),
+            PixelFormat::CMYK32 => return None,
+        };
+        if luma.is_empty() {
+            return None;
+        }
+
+        let count = luma.len() as f64;
+        let mean = luma.iter().sum::<f64>() / count;
+        let variance = luma.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+        Some(Self {
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+
+    /// ほぼ単色（ロック画面・プロジェクタ切り替え時の真っ黒や真っ白など）で情報量がないか
+    pub fn is_blank(&self, max_stddev: f64) -> bool {
+        self.stddev < max_stddev
+    }
+}
+
+/// JPEG画像の輝度の平均と標準偏差を計算
+///
+/// 縮小してデコードする（1/8まで）ため、全画素を展開するより速い。
+/// 輝度を求められない色空間（CMYK）では `None` を返す
+pub fn luminance(path: &Path) -> Result<Option<Luminance>, ImageStoreError> {
+    let file = File::open(path).map_err(|e| ImageStoreError::DecodeFailed(e.to_string()))?;
+    let mut decoder = Decoder::new(BufReader::new(file));
+    decoder
+        .read_info()
+        .map_err(|e| ImageStoreError::DecodeFailed(e.to_string()))?;
+    if let Some(info) = decoder.info() {
+        decoder
+            .scale(info.width.div_ceil(8), info.height.div_ceil(8))
+            .map_err(|e| ImageStoreError::DecodeFailed(e.to_string()))?;
+    }
+
+    let pixels = decoder
+        .decode()
+        .map_err(|e| ImageStoreError::DecodeFailed(e.to_string()))?;
+    let format = decoder
+        .info()
+        .map(|info| info.pixel_format)
+        .ok_or_else(|| ImageStoreError::DecodeFailed("画像情報がありません".to_string()))?;
+    Ok(Luminance::from_pixels(&pixels, format))
+}
+
+/// 保存した画像のファイルサイズと解像度を取得
+///
+/// 解像度はJPEGのヘッダーから読み、読み取れなければ `None` にする
+pub fn image_info(path: &Path) -> io::Result<ImageInfo> {
+    let bytes = fs::metadata(path)?.len();
+    let dimensions = match jpeg_dimensions(&mut BufReader::new(File::open(path)?)) {
+        Ok(dimensions) => dimensions,
+        // 途中で切れたファイルはサイズだけ記録する
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+        Err(e) => return Err(e),
+    };
+    Ok(ImageInfo {
+        bytes,
+        width: dimensions.map(|(width, _)| width),
+        height: dimensions.map(|(_, height)| height),
+    })
+}
+
+/// JPEGのフレームヘッダー（SOFセグメント）から幅と高さを読む
+///
+/// 画像データ全体は読まず、セグメントの長さを頼りに読み飛ばす
+fn jpeg_dimensions<R: Read + Seek>(reader: &mut R) -> io::Result<Option<(u32, u32)>> {
+    let mut byte = [0u8; 1];
+    let mut word = [0u8; 2];
+
+    reader.read_exact(&mut word)?;
+    if word != [0xFF, 0xD8] {
+        return Ok(None);
+    }
+
+    loop {
+        // マーカーの前の0xFF（詰め物を含む）を読み飛ばす
+        reader.read_exact(&mut byte)?;
+        if byte[0] != 0xFF {
+            return Ok(None);
+        }
+        while byte[0] == 0xFF {
+            reader.read_exact(&mut byte)?;
+        }
+        let marker = byte[0];
+
+        // 長さを持たないマーカー
+        if marker == 0x01 || (0xD0..=0xD8).contains(&marker) {
+            continue;
+        }
+        // フレームヘッダーより前に画像データが始まったら諦める
+        if marker == 0xD9 || marker == 0xDA {
+            return Ok(None);
+        }
+
+        reader.read_exact(&mut word)?;
+        let length = u16::from_be_bytes(word);
+        if length < 2 {
+            return Ok(None);
+        }
+
+        // SOF0〜SOF15（DHT・JPG・DACを除く）
+        if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+            let mut header = [0u8; 5];
+            reader.read_exact(&mut header)?;
+            let height = u16::from_be_bytes([header[1], header[2]]) as u32;
+            let width = u16::from_be_bytes([header[3], header[4]]) as u32;
+            return Ok(Some((width, height)));
+        }
+
+        reader.seek(SeekFrom::Current(length as i64 - 2))?;
     }
 }
 
@@ -74,43 +577,262 @@ mod tests {
         let store = ImageStore::new(temp_dir.path().to_path_buf(), 60);
 
         let timestamp = Local.with_ymd_and_hms(2024, 12, 30, 10, 30, 45).unwrap();
-        let path = store.get_path(&timestamp);
+        let path = store.get_path(&timestamp, "Safari");
 
         assert!(path.to_string_lossy().contains("2024-12-30"));
         assert!(path.to_string_lossy().contains("103045.jpg"));
     }
 
+    #[test]
+    fn test_date_dir() {
+        let images_dir = PathBuf::from("/images");
+        let date = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
+
+        let store = ImageStore::new(images_dir.clone(), 60);
+        assert_eq!(store.date_dir(date), images_dir.join("2025-01-10"));
+
+        let store = ImageStore::new(images_dir.clone(), 60)
+            .with_filename_format("%Y/%m/%d/%H%M%S_%app%.jpg");
+        assert_eq!(store.date_dir(date), images_dir.join("2025/01/10"));
+
+        // 日付やアプリ名でディレクトリを分けない形式では置き場所全体
+        for format in [
+            "%Y%m%d_%H%M%S.jpg",
+            "%app%/%Y-%m-%d/%H%M%S.jpg",
+            "%z/%H%M%S.jpg",
+        ] {
+            let store = ImageStore::new(images_dir.clone(), 60).with_filename_format(format);
+            assert_eq!(store.date_dir(date), images_dir);
+        }
+    }
+
     #[test]
     fn test_get_path_creates_date_directory() {
         let temp_dir = TempDir::new().unwrap();
         let store = ImageStore::new(temp_dir.path().to_path_buf(), 60);
 
         let timestamp = Local.with_ymd_and_hms(2024, 12, 30, 10, 30, 45).unwrap();
-        let path = store.get_path(&timestamp);
+        let path = store.get_path(&timestamp, "Safari");
 
         // パスの形式を確認
         let components: Vec<_> = path.components().collect();
         let last_two: Vec<_> = components.iter().rev().take(2).collect();
 
+        assert_eq!(last_two[0].as_os_str().to_string_lossy(), "103045.jpg");
+        assert_eq!(last_two[1].as_os_str().to_string_lossy(), "2024-12-30");
+    }
+
+    #[test]
+    fn test_new_image_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ImageStore::new(temp_dir.path().to_path_buf(), 80);
+
+        assert_eq!(store.images_dir, temp_dir.path());
+        let backends: Vec<_> = store.backends.iter().map(|b| b.name()).collect();
+        assert_eq!(backends, ["screencapture", "CoreGraphics"]);
+    }
+
+    #[test]
+    fn test_get_path_with_filename_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = ImageStore::new(temp_dir.path().to_path_buf(), 60)
+            .with_filename_format("%Y/%m/%Y%m%d_%H%M%S_%app%.jpg");
+
+        let timestamp = Local.with_ymd_and_hms(2024, 12, 30, 10, 30, 45).unwrap();
+        assert_eq!(
+            store.get_path(&timestamp, "Visual Studio Code"),
+            temp_dir
+                .path()
+                .join("2024/12/20241230_103045_Visual Studio Code.jpg")
+        );
+        // パス区切りはアプリ名に含めない
+        assert_eq!(
+            store.get_path(&timestamp, "a/b:c"),
+            temp_dir.path().join("2024/12/20241230_103045_a_b_c.jpg")
+        );
+        assert!(store
+            .get_path(&timestamp, "..")
+            .ends_with("20241230_103045__.jpg"));
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("103045.jpg");
+        assert_eq!(unique_path(path.clone(), Path::exists), path);
+
+        fs::write(&path, b"").unwrap();
+        fs::write(temp_dir.path().join("103045_1.jpg"), b"").unwrap();
         assert_eq!(
-            last_two[0].as_os_str().to_string_lossy(),
-            "103045.jpg"
+            unique_path(path, Path::exists),
+            temp_dir.path().join("103045_2.jpg")
         );
+    }
+
+    #[test]
+    fn test_flush_buffered_images() {
+        let temp_dir = TempDir::new().unwrap();
+        let images_dir = temp_dir.path().join("images");
+        let buffer_dir = temp_dir.path().join("buffer");
+        let store = ImageStore::new(images_dir.clone(), 60).with_buffer(buffer_dir.clone(), 2);
+
+        // 一時フォルダには保存先と同じ構成で置く
+        let timestamp = Local.with_ymd_and_hms(2024, 12, 30, 10, 30, 45).unwrap();
+        let path = store.get_path(&timestamp, "Safari");
+        let file = store.buffered_path(&path).unwrap();
+        assert_eq!(file, buffer_dir.join("2024-12-30/103045.jpg"));
         assert_eq!(
-            last_two[1].as_os_str().to_string_lossy(),
-            "2024-12-30"
+            buffered_image_path(&images_dir, &buffer_dir, Path::new("/elsewhere/1.jpg")),
+            None
         );
+
+        // 一時フォルダにある名前も使用済みとして扱う
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, b"first").unwrap();
+        let second = unique_path(path.clone(), |candidate| store.is_taken(candidate));
+        assert_eq!(second, images_dir.join("2024-12-30/103045_1.jpg"));
+        fs::write(store.buffered_path(&second).unwrap(), b"second").unwrap();
+        store.buffer.as_ref().unwrap().pending.set(2);
+        assert!(store.is_buffer_full());
+
+        assert_eq!(store.flush().unwrap(), 2);
+        assert!(!store.is_buffer_full());
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        assert_eq!(fs::read(&second).unwrap(), b"second");
+        assert!(!buffer_dir.join("2024-12-30").exists());
+
+        // 一時フォルダが空（またはない）なら何もしない
+        assert_eq!(store.flush().unwrap(), 0);
+        let store = ImageStore::new(images_dir, 60);
+        assert!(!store.is_buffer_full());
+        assert_eq!(store.flush().unwrap(), 0);
     }
 
     #[test]
-    fn test_new_image_store() {
+    fn test_validate_filename_format() {
+        assert!(validate_filename_format(DEFAULT_FILENAME_FORMAT).is_ok());
+        assert!(validate_filename_format("%Y%m%d_%H%M%S_%app%.JPG").is_ok());
+        assert!(validate_filename_format("").is_err());
+        assert!(validate_filename_format("/tmp/%H%M%S.jpg").is_err());
+        assert!(validate_filename_format("../%H%M%S.jpg").is_err());
+        assert!(validate_filename_format("%H%M%S.png").is_err());
+        assert!(validate_filename_format("%Q%H.jpg").is_err());
+    }
+
+    /// APP0とSOF0だけを持つ最小のJPEGヘッダー
+    fn jpeg_header(width: u16, height: u16) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        // APP0（JFIF、長さ16）
+        data.extend([0xFF, 0xE0, 0x00, 0x10]);
+        data.extend(b"JFIF\0");
+        data.extend([0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00]);
+        // SOF0（長さ17、精度8、高さ、幅、3成分）
+        data.extend([0xFF, 0xC0, 0x00, 0x11, 0x08]);
+        data.extend(height.to_be_bytes());
+        data.extend(width.to_be_bytes());
+        data.extend([0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01]);
+        data
+    }
+
+    #[test]
+    fn test_jpeg_dimensions() {
+        let mut reader = io::Cursor::new(jpeg_header(2880, 1800));
+        assert_eq!(jpeg_dimensions(&mut reader).unwrap(), Some((2880, 1800)));
+
+        let mut reader = io::Cursor::new(b"\x89PNG\r\n".to_vec());
+        assert_eq!(jpeg_dimensions(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_image_info() {
         let temp_dir = TempDir::new().unwrap();
-        let store = ImageStore::new(temp_dir.path().to_path_buf(), 80);
+        let path = temp_dir.path().join("103045.jpg");
+        let mut data = jpeg_header(1920, 1080);
+        data.extend([0u8; 100]);
+        fs::write(&path, &data).unwrap();
 
-        assert_eq!(store.jpeg_quality, 80);
-        assert_eq!(store.images_dir, temp_dir.path());
+        let info = image_info(&path).unwrap();
+        assert_eq!(info.bytes, data.len() as u64);
+        assert_eq!(info.width, Some(1920));
+        assert_eq!(info.height, Some(1080));
+    }
+
+    #[test]
+    fn test_luminance() {
+        let black = Luminance::from_pixels(&[0, 0, 1, 0], PixelFormat::L8).unwrap();
+        assert!(black.mean < 1.0);
+        assert!(black.is_blank(4.0));
+
+        let white = Luminance::from_pixels(&[255; 12], PixelFormat::RGB24).unwrap();
+        assert!((white.mean - 255.0).abs() < 0.01);
+        assert!(white.is_blank(4.0));
+        // 0なら検出しない
+        assert!(!white.is_blank(0.0));
+
+        // 白地に黒い文字がある画面は空白ではない
+        let mut text = vec![255u8; 100];
+        text[..10].fill(0);
+        let text = Luminance::from_pixels(&text, PixelFormat::L8).unwrap();
+        assert!(!text.is_blank(4.0));
+
+        assert_eq!(Luminance::from_pixels(&[], PixelFormat::L8), None);
+        assert_eq!(Luminance::from_pixels(&[0; 4], PixelFormat::CMYK32), None);
+    }
+
+    #[test]
+    fn test_luminance_invalid_image() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.jpg");
+        fs::write(&path, jpeg_header(16, 16)).unwrap();
+
+        assert!(matches!(
+            luminance(&path),
+            Err(ImageStoreError::DecodeFailed(_))
+        ));
+    }
+
+    /// 決まった結果を返す撮影方法（成功なら空のファイルを書く）
+    struct FakeBackend(bool);
+
+    impl ScreenshotBackend for FakeBackend {
+        fn name(&self) -> &'static str {
+            if self.0 {
+                "ok"
+            } else {
+                "broken"
+            }
+        }
+
+        fn capture(&self, path: &Path) -> Result<(), ImageStoreError> {
+            if !self.0 {
+                return Err(ImageStoreError::CaptureCommandFailed("failed".to_string()));
+            }
+            fs::write(path, b"").map_err(ImageStoreError::CommandFailed)
+        }
+    }
+
+    #[test]
+    fn test_capture_falls_back_to_next_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        let timestamp = Local.with_ymd_and_hms(2024, 12, 30, 10, 30, 45).unwrap();
+
+        let store = ImageStore::new(temp_dir.path().to_path_buf(), 60).with_backends(vec![
+            Box::new(FakeBackend(false)),
+            Box::new(FakeBackend(true)),
+        ]);
+        let image = store.capture(&timestamp, "Safari").unwrap();
+        assert!(image.file.exists());
+
+        // すべて失敗すれば、それぞれの失敗をまとめて返す
+        let store = ImageStore::new(temp_dir.path().to_path_buf(), 60).with_backends(vec![
+            Box::new(FakeBackend(false)),
+            Box::new(FakeBackend(false)),
+        ]);
+        let err = store.capture(&timestamp, "Safari").unwrap_err();
+        assert!(matches!(err, ImageStoreError::AllBackendsFailed(_)));
+        assert_eq!(err.to_string().matches("broken: ").count(), 2);
     }
 
-    // 注: capture()のテストは実際にスクリーンショットを撮影するため
+    // 注: 実際の撮影方法のテストは実際にスクリーンショットを撮影するため
     // CI環境では実行できない。手動テストまたはE2Eテストで確認する。
 }
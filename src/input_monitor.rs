@@ -0,0 +1,196 @@
+//! 入力監視モジュール - CGEventTapで打鍵・クリック・スクロールの回数だけを数える
+
+use crate::database::InputMetrics;
+use crate::error::MetadataError;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// CGEventTypeの値（IOLLEvent.h）
+const KEY_DOWN: u32 = 10;
+const LEFT_MOUSE_DOWN: u32 = 1;
+const RIGHT_MOUSE_DOWN: u32 = 3;
+const OTHER_MOUSE_DOWN: u32 = 25;
+const SCROLL_WHEEL: u32 = 22;
+
+/// イベントの種類ごとの累積数
+#[derive(Debug, Default)]
+struct Counters {
+    key_presses: AtomicU64,
+    clicks: AtomicU64,
+    scrolls: AtomicU64,
+}
+
+impl Counters {
+    /// イベントの種類に応じて数える（対象外の種類は無視する）
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn record(&self, event_type: u32) {
+        let counter = match event_type {
+            KEY_DOWN => &self.key_presses,
+            LEFT_MOUSE_DOWN | RIGHT_MOUSE_DOWN | OTHER_MOUSE_DOWN => &self.clicks,
+            SCROLL_WHEEL => &self.scrolls,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> InputMetrics {
+        InputMetrics {
+            key_presses: self.key_presses.load(Ordering::Relaxed),
+            clicks: self.clicks.load(Ordering::Relaxed),
+            scrolls: self.scrolls.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// キーボード・マウスの入力監視
+///
+/// 聞くだけのイベントタップ（ListenOnly）を専用スレッドのRunLoopで動かし、
+/// 種類ごとの件数だけを累積する。キーの内容や座標は読まない。
+/// タップの作成には入力監視の権限が必要で、ドロップ時にスレッドを終了する
+#[derive(Debug)]
+pub struct InputMonitor {
+    counters: Arc<Counters>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl InputMonitor {
+    /// 監視を開始（タップを作成できなければエラー）
+    pub fn spawn() -> Result<Self, MetadataError> {
+        let counters = Arc::new(Counters::default());
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = spawn_tap(Arc::clone(&counters), Arc::clone(&running))?;
+        Ok(Self {
+            counters,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// 開始してからの累積数（間隔ごとの活動量は `InputMetrics::delta_since` で求める）
+    pub fn counters(&self) -> InputMetrics {
+        self.counters.snapshot()
+    }
+}
+
+impl Drop for InputMonitor {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// タップを作成してRunLoopを回すスレッドを起動
+///
+/// タップは作成したスレッドのRunLoopで動くため、作成もスレッド内で行い結果だけを受け取る。
+/// 停止の確認のためRunLoopは1秒ずつ回し、そのたびに時間切れなどで無効にされたタップを有効に戻す
+#[cfg(target_os = "macos")]
+fn spawn_tap(
+    counters: Arc<Counters>,
+    running: Arc<AtomicBool>,
+) -> Result<JoinHandle<()>, MetadataError> {
+    use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
+    use core_graphics::event::{
+        CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType,
+    };
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        let tap = CGEventTap::new(
+            CGEventTapLocation::Session,
+            CGEventTapPlacement::TailAppendEventTap,
+            CGEventTapOptions::ListenOnly,
+            vec![
+                CGEventType::KeyDown,
+                CGEventType::LeftMouseDown,
+                CGEventType::RightMouseDown,
+                CGEventType::OtherMouseDown,
+                CGEventType::ScrollWheel,
+            ],
+            move |_, event_type, _| {
+                counters.record(event_type as u32);
+                None
+            },
+        );
+        let Ok(tap) = tap else {
+            let _ = ready_tx.send(Err(
+                "イベントタップを作成できません（入力監視の権限を確認してください）",
+            ));
+            return;
+        };
+        let Ok(source) = tap.mach_port.create_runloop_source(0) else {
+            let _ = ready_tx.send(Err("RunLoopにイベントタップを登録できません"));
+            return;
+        };
+        // SAFETY: CoreFoundationが定義する定数の読み取りのみ
+        let mode = unsafe { kCFRunLoopDefaultMode };
+        CFRunLoop::get_current().add_source(&source, mode);
+        tap.enable();
+        let _ = ready_tx.send(Ok(()));
+
+        while running.load(Ordering::Relaxed) {
+            CFRunLoop::run_in_mode(mode, Duration::from_secs(1), false);
+            tap.enable();
+        }
+    });
+
+    match ready_rx.recv() {
+        Ok(Ok(())) => Ok(thread),
+        Ok(Err(reason)) => {
+            let _ = thread.join();
+            Err(MetadataError::InputMonitorFailed(reason.to_string()))
+        }
+        Err(_) => Err(MetadataError::InputMonitorFailed(
+            "入力監視のスレッドが終了しました".to_string(),
+        )),
+    }
+}
+
+/// macOS以外では入力を監視できない
+#[cfg(not(target_os = "macos"))]
+fn spawn_tap(
+    _counters: Arc<Counters>,
+    _running: Arc<AtomicBool>,
+) -> Result<JoinHandle<()>, MetadataError> {
+    Err(MetadataError::InputMonitorFailed(
+        "macOS以外では入力を監視できません".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_counts_only_input_events() {
+        let counters = Counters::default();
+        for event_type in [
+            KEY_DOWN,
+            KEY_DOWN,
+            LEFT_MOUSE_DOWN,
+            RIGHT_MOUSE_DOWN,
+            OTHER_MOUSE_DOWN,
+            SCROLL_WHEEL,
+            // mouseMoved・keyUp・タップ無効化の通知は数えない
+            5,
+            11,
+            0xFFFF_FFFE,
+        ] {
+            counters.record(event_type);
+        }
+        assert_eq!(
+            counters.snapshot(),
+            InputMetrics {
+                key_presses: 2,
+                clicks: 3,
+                scrolls: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! 多重起動防止モジュール - PIDファイルとファイルロックによる単一インスタンス化と停止
+
+use crate::error::LockError;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 停止を待つ間の確認間隔
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 単一インスタンスロック
+///
+/// PIDファイルに排他ロックをかけ、自プロセスのPIDを書き込む。
+/// ロックはプロセスが終了すると（異常終了でも）OSが解放するため、
+/// 古いPIDファイルが残っていても次の起動を妨げない。ドロップ時はPIDを消してロックを解放し、
+/// ファイル自体は残す（削除すると、消す前に開いたプロセスと後から作ったプロセスが
+/// 別々のファイルをロックして同時に起動できてしまうため）
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+}
+
+impl InstanceLock {
+    /// ロックを取得する（既に別プロセスが保持していれば `AlreadyRunning`）
+    pub fn acquire(path: &Path) -> Result<Self, LockError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut content = String::new();
+                file.read_to_string(&mut content)?;
+                return Err(LockError::AlreadyRunning(content.trim().parse().ok()));
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        // ロックを取得してから書き換える（前回のPIDが残っていれば上書き）
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        file.sync_all()?;
+
+        Ok(Self { file })
+    }
+}
+
+/// ロックを保持している（起動中の）プロセスのPID（起動していなければ `None`）
+pub fn running_pid(path: &Path) -> Result<Option<u32>, LockError> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    match file.try_lock_shared() {
+        // ロックできる = 保持しているプロセスはない（異常終了で残ったファイル）
+        Ok(()) => {
+            file.unlock()?;
+            Ok(None)
+        }
+        Err(TryLockError::WouldBlock) => {
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            let content = content.trim();
+            content
+                .parse()
+                .map(Some)
+                .map_err(|_| LockError::InvalidPid(content.to_string()))
+        }
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// 起動中のトラッカーにシグナル（`TERM` `HUP` など）を送る
+///
+/// 送り先のPIDを返す（起動していなければ `None`）
+pub fn signal(path: &Path, signal: &str) -> Result<Option<u32>, LockError> {
+    let Some(pid) = running_pid(path)? else {
+        return Ok(None);
+    };
+
+    let status = Command::new("kill")
+        .arg(format!("-{}", signal))
+        .arg(pid.to_string())
+        .status()?;
+    if !status.success() {
+        return Err(LockError::SignalFailed(pid));
+    }
+    Ok(Some(pid))
+}
+
+/// 起動中のトラッカーにSIGTERMを送り、ロックが解放されるまで待つ
+///
+/// トラッカーは処理中のキャプチャ（OCRを含む）を終えてから終了する。
+/// 停止したプロセスのPIDを返す（起動していなければ `None`）
+pub fn stop(path: &Path, timeout: Duration) -> Result<Option<u32>, LockError> {
+    let Some(pid) = signal(path, "TERM")? else {
+        return Ok(None);
+    };
+
+    let deadline = Instant::now() + timeout;
+    while running_pid(path)?.is_some() {
+        if Instant::now() >= deadline {
+            return Err(LockError::StopTimeout(pid));
+        }
+        thread::sleep(STOP_POLL_INTERVAL);
+    }
+    Ok(Some(pid))
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // ロックを保持している間に中身を消し、解放後に古いPIDが読まれないようにする
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tracker.pid");
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+
+        // 同じファイルへの2つ目のロックは失敗する（別のファイル記述なのでプロセス内でも競合する）
+        match InstanceLock::acquire(&path) {
+            Err(LockError::AlreadyRunning(pid)) => assert_eq!(pid, Some(std::process::id())),
+            other => panic!("Expected AlreadyRunning, got {:?}", other),
+        }
+
+        assert_eq!(running_pid(&path).unwrap(), Some(std::process::id()));
+
+        drop(lock);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        assert_eq!(running_pid(&path).unwrap(), None);
+        assert!(InstanceLock::acquire(&path).is_ok());
+    }
+
+    #[test]
+    fn test_stale_pid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tracker.pid");
+        // 異常終了で残ったPIDファイルはロックされていないので取得できる
+        fs::write(&path, "999999\n").unwrap();
+        assert_eq!(running_pid(&path).unwrap(), None);
+        assert_eq!(stop(&path, Duration::ZERO).unwrap(), None);
+
+        let _lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+    }
+}
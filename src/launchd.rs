@@ -0,0 +1,221 @@
+//! launchdモジュール - ログイン時に `tracker start` を起動するLaunchAgentの登録
+
+use crate::config::{self, Config, PATH_ENVS};
+use crate::error::LaunchdError;
+use crate::escape::escape_markup;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// LaunchAgentのラベル（plistのファイル名にも使う）
+pub const LABEL: &str = "com.habit-tracker.tracker";
+
+/// 使用中のプロファイルのLaunchAgentのラベル（プロファイルごとに別に登録できる）
+pub fn label() -> String {
+    match config::profile() {
+        Some(profile) => format!("{}.{}", LABEL, profile),
+        None => LABEL.to_string(),
+    }
+}
+
+/// launchdから起動したトラッカーの標準出力・標準エラーの出力先ファイル名（`log_dir` 内）
+const LAUNCHD_LOG_FILE: &str = "launchd.log";
+
+/// 異常終了後に再起動するまでの最短間隔（秒）
+const THROTTLE_INTERVAL_SECONDS: u64 = 60;
+
+/// LaunchAgentのplistのパス（`~/Library/LaunchAgents/<ラベル>.plist`）
+pub fn plist_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", label()))
+}
+
+/// LaunchAgentが登録済みか（plistがあるか）
+pub fn is_installed() -> bool {
+    plist_path().exists()
+}
+
+/// 実行中の `tracker` をログイン時に起動するLaunchAgentとして登録する
+///
+/// plistを書き出して `launchctl load -w` で読み込む。登録済みなら読み込み直す。
+/// 異常終了した場合だけ再起動し、`tracker stop` で停止した場合は次のログインまで起動しない
+pub fn install(config: &Config) -> Result<PathBuf, LaunchdError> {
+    let path = plist_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::create_dir_all(&config.log_dir)?;
+
+    let program = std::env::current_exe()?;
+    let log_path = config.log_dir.join(LAUNCHD_LOG_FILE);
+    // launchdはシェルの環境変数を引き継がないため、置き場所の指定を書き込んでおく
+    let envs: Vec<(&str, String)> = PATH_ENVS
+        .iter()
+        .filter_map(|&name| {
+            let value = std::env::var(name).ok()?;
+            Some((name, value))
+        })
+        .collect();
+    let content = plist_content(
+        &label(),
+        &program,
+        &config::overrides().to_args(),
+        &log_path,
+        &envs,
+    );
+    fs::write(&path, content)?;
+
+    // 登録済みの古い定義が残っていると読み込めないため、先に外す（未登録なら失敗してよい）
+    let _ = Command::new("launchctl").arg("unload").arg(&path).output();
+    let output = Command::new("launchctl")
+        .arg("load")
+        .arg("-w")
+        .arg(&path)
+        .output()?;
+    if !output.status.success() {
+        return Err(LaunchdError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(path)
+}
+
+/// LaunchAgentの登録を解除する
+///
+/// `launchctl unload -w` で読み込みを外し（起動中のトラッカーも停止する）、plistを削除する。
+/// 削除したplistのパスを返す（登録されていなければ `None`）
+pub fn uninstall() -> Result<Option<PathBuf>, LaunchdError> {
+    let path = plist_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let output = Command::new("launchctl")
+        .arg("unload")
+        .arg("-w")
+        .arg(&path)
+        .output()?;
+    if !output.status.success() {
+        return Err(LaunchdError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    fs::remove_file(&path)?;
+    Ok(Some(path))
+}
+
+/// LaunchAgentのplistの内容
+///
+/// `global_args` は `start` の前に付ける共通オプション、`envs` は起動時に設定する環境変数
+fn plist_content(
+    label: &str,
+    program: &Path,
+    global_args: &[String],
+    log_path: &Path,
+    envs: &[(&str, String)],
+) -> String {
+    let mut arguments = vec![program.to_string_lossy().into_owned()];
+    arguments.extend(global_args.iter().cloned());
+    arguments.push("start".to_string());
+    let arguments: String = arguments
+        .iter()
+        .map(|argument| format!("        <string>{}</string>\n", escape_markup(argument)))
+        .collect();
+    let log_path = escape_markup(&log_path.to_string_lossy());
+    let environment = if envs.is_empty() {
+        String::new()
+    } else {
+        let entries: String = envs
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "        <key>{}</key>\n        <string>{}</string>\n",
+                    escape_markup(name),
+                    escape_markup(value)
+                )
+            })
+            .collect();
+        format!("    <key>EnvironmentVariables</key>\n    <dict>\n{entries}    </dict>\n")
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>ThrottleInterval</key>
+    <integer>{THROTTLE_INTERVAL_SECONDS}</integer>
+    <key>StandardOutPath</key>
+    <string>{log_path}</string>
+    <key>StandardErrorPath</key>
+    <string>{log_path}</string>
+{environment}</dict>
+</plist>
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plist_content() {
+        let content = plist_content(
+            LABEL,
+            Path::new("/Users/me/R&D/bin/tracker"),
+            &[],
+            Path::new("/Users/me/.habit-tracker/logs/launchd.log"),
+            &[],
+        );
+        assert!(content.contains("<string>com.habit-tracker.tracker</string>"));
+        assert!(content.contains(
+            "<string>/Users/me/R&amp;D/bin/tracker</string>\n        <string>start</string>"
+        ));
+        assert!(content.contains("<key>StandardErrorPath</key>\n    <string>/Users/me/.habit-tracker/logs/launchd.log</string>"));
+        assert!(!content.contains("EnvironmentVariables"));
+        assert!(plist_path().ends_with("Library/LaunchAgents/com.habit-tracker.tracker.plist"));
+    }
+
+    #[test]
+    fn test_plist_environment() {
+        let content = plist_content(
+            LABEL,
+            Path::new("/usr/local/bin/tracker"),
+            &[],
+            Path::new("/tmp/launchd.log"),
+            &[("HABIT_TRACKER_HOME", "/Users/me/tracker".to_string())],
+        );
+        assert!(content.contains(
+            "    <key>EnvironmentVariables</key>\n    <dict>\n        <key>HABIT_TRACKER_HOME</key>\n        <string>/Users/me/tracker</string>\n    </dict>\n</dict>"
+        ));
+    }
+
+    #[test]
+    fn test_plist_profile() {
+        let content = plist_content(
+            "com.habit-tracker.tracker.work",
+            Path::new("/usr/local/bin/tracker"),
+            &["--profile".to_string(), "work".to_string()],
+            Path::new("/tmp/launchd.log"),
+            &[],
+        );
+        assert!(content.contains("<string>com.habit-tracker.tracker.work</string>"));
+        assert!(content.contains(
+            "<array>\n        <string>/usr/local/bin/tracker</string>\n        <string>--profile</string>\n        <string>work</string>\n        <string>start</string>\n    </array>"
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+//! Habit Tracker - macOS向け個人作業トラッキングツール
+//!
+//! `tracker` コマンドの実装をライブラリとしても公開している。
+//! 記録済みのデータベースを読み、レポートを組み立てる例:
+//!
+//! ```no_run
+//! use habit_tracker::config::{CliArgs, Config};
+//! use habit_tracker::database::Database;
+//! use habit_tracker::report::Report;
+//!
+//! let config = Config::load(&CliArgs::default())?;
+//! let db = Database::open(&config.db_path)?;
+//! let report = Report::new(db, config.interval_seconds)
+//!     .with_day_boundary(config.day_boundary_hour);
+//! let daily = report.daily("2025-01-10")?;
+//! println!("{}秒", daily.total_seconds);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod activitywatch;
+mod app_watcher;
+mod break_reminder;
+pub mod capture;
+pub mod category;
+pub mod chart;
+pub mod cli;
+pub mod config;
+mod daemon;
+pub mod database;
+pub mod db_writer;
+mod distraction;
+pub mod error;
+mod error_reporter;
+mod escape;
+pub mod export;
+pub mod goals;
+pub mod highlight;
+pub mod hooks;
+pub mod html_report;
+pub mod i18n;
+mod idle;
+pub mod image_store;
+mod input_monitor;
+pub mod instance_lock;
+mod launchd;
+pub mod logging;
+pub mod markdown_report;
+mod mcp;
+pub mod metadata;
+pub mod metadata_provider;
+mod notification;
+pub mod obsidian;
+pub mod ocr;
+mod ocr_batch;
+pub mod ocr_normalize;
+mod ocr_worker;
+pub mod pause_control;
+pub mod pomodoro;
+mod process_monitor;
+mod progress;
+pub mod redact;
+pub mod report;
+pub mod rescuetime;
+pub mod runtime;
+mod server;
+pub mod shortcut;
+pub mod standup;
+mod uninstall;
+mod watchdog;
+pub mod wordcloud;
+pub mod worklog;
@@ -1,6 +1,61 @@
 //! ログインフラモジュール
 
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use crate::config::Config;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+/// ログファイル名の接頭辞（`tracker.YYYY-MM-DD.log`）
+const LOG_FILE_PREFIX: &str = "tracker";
+
+/// ログファイルの拡張子
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// トレースに付けるサービス名
+const SERVICE_NAME: &str = "habit-tracker";
+
+/// OTLP/HTTPのトレースのパス
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+/// ログ形式を上書きする環境変数（`text` / `json`）
+const LOG_FORMAT_ENV: &str = "RUST_LOG_FORMAT";
+
+/// ログの出力形式（config: `log_format`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 人が読むテキスト形式
+    #[default]
+    Text,
+    /// 1行1イベントのJSON（Vectorなどで収集する場合）
+    Json,
+}
+
+impl LogFormat {
+    /// 環境変数の値があれば設定より優先する（解釈できない値は無視する）
+    fn resolve(configured: LogFormat, env_value: Option<&str>) -> LogFormat {
+        match env_value
+            .map(|value| value.trim().to_lowercase())
+            .as_deref()
+        {
+            Some("json") => LogFormat::Json,
+            Some("text") => LogFormat::Text,
+            _ => configured,
+        }
+    }
+}
 
 /// ログシステムを初期化
 ///
@@ -10,21 +65,227 @@ use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, Env
 /// - info: 情報以上（デフォルト）
 /// - debug: デバッグ情報以上
 /// - trace: すべて
-pub fn init() {
+///
+/// `log_to_file` が有効なら `log_dir` にも日次ローテーションで出力し、
+/// `log_max_files` 日分を超えた古いファイルは削除する。
+/// `log_format = "json"`（または環境変数 `RUST_LOG_FORMAT=json`）で標準エラー・ファイルとも
+/// 1行1イベントのJSONで出力する。
+/// `otlp_endpoint` を設定すると、スパン（キャプチャサイクル・スクリーンショット・OCR・DB書き込み）を
+/// OpenTelemetryのトレースとしてOTLP/HTTPで送信する。
+/// 返り値のガードはプロセス終了まで保持すること（ドロップ時に未書き込みのログ・スパンを書き出す）
+pub fn init(config: &Config) -> LogGuard {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let format = LogFormat::resolve(config.log_format, env::var(LOG_FORMAT_ENV).ok().as_deref());
+
+    let (file_layer, guard) = match config.log_to_file.then(|| file_appender(config)) {
+        Some(Ok(appender)) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(format_layer(format, writer, false)), Some(guard))
+        }
+        Some(Err(e)) => {
+            // サブスクライバの初期化前なので標準エラーに直接出す
+            eprintln!(
+                "ログファイルを開けません（{}）: {}",
+                config.log_dir.display(),
+                e
+            );
+            (None, None)
+        }
+        None => (None, None),
+    };
+
+    let tracer_provider = match config.otlp_endpoint.as_deref().map(tracer_provider) {
+        Some(Ok(provider)) => Some(provider),
+        Some(Err(e)) => {
+            eprintln!("OpenTelemetryのエクスポーターを作成できません: {}", e);
+            None
+        }
+        None => None,
+    };
+    let otel_layer = tracer_provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
 
     tracing_subscriber::registry()
         .with(filter)
-        .with(fmt::layer().with_target(true).with_writer(std::io::stderr))
+        // バックグラウンド起動でファイルに振り向けた場合は色を付けない
+        .with(format_layer(
+            format,
+            std::io::stderr,
+            std::io::stderr().is_terminal(),
+        ))
+        .with(file_layer)
+        .with(otel_layer)
         .init();
+
+    LogGuard {
+        _file_guard: guard,
+        tracer_provider,
+    }
+}
+
+/// ログの後始末のためのガード
+///
+/// ドロップ時にファイルへの未書き込みのログを書き出し、送信待ちのスパンを送る
+pub struct LogGuard {
+    _file_guard: Option<WorkerGuard>,
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("スパンの送信に失敗しました: {}", e);
+            }
+        }
+    }
+}
+
+/// OTLP/HTTPでスパンを送るトレーサープロバイダーを作成
+fn tracer_provider(endpoint: &str) -> Result<SdkTracerProvider, String> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint(endpoint))
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build())
+}
+
+/// コレクターのURL（`http://localhost:4318`）にトレースのパスを補う
+fn traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.ends_with(OTLP_TRACES_PATH) {
+        endpoint.to_string()
+    } else {
+        format!("{}{}", endpoint, OTLP_TRACES_PATH)
+    }
+}
+
+/// 出力形式に応じたフォーマットレイヤーを作成（JSONには色を付けない）
+fn format_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_target(true).with_writer(writer);
+    match format {
+        LogFormat::Text => layer.with_ansi(ansi).boxed(),
+        // メッセージとフィールドをトップレベルに置き、収集側でパースしやすくする
+        LogFormat::Json => layer.json().flatten_event(true).boxed(),
+    }
+}
+
+/// 日次ローテーションのファイル出力を作成
+fn file_appender(config: &Config) -> Result<RollingFileAppender, String> {
+    // 古いファイルの削除でディレクトリを読むため、先に作っておく
+    fs::create_dir_all(&config.log_dir).map_err(|e| e.to_string())?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(config.log_max_files.max(1))
+        .build(&config.log_dir)
+        .map_err(|e| e.to_string())
+}
+
+/// ログディレクトリから直近 `count` 行を古い順に読む
+///
+/// 最新のファイルで足りなければ前日以前のファイルもさかのぼる
+pub fn tail(dir: &Path, count: usize) -> io::Result<Vec<String>> {
+    let mut files = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(is_log_file)
+            })
+            .collect::<Vec<_>>(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    // ファイル名の日付順に並ぶ
+    files.sort();
+
+    let mut lines = Vec::new();
+    for path in files.iter().rev() {
+        if lines.len() >= count {
+            break;
+        }
+        let content = fs::read_to_string(path)?;
+        let mut file_lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let keep = (count - lines.len()).min(file_lines.len());
+        file_lines.drain(..file_lines.len() - keep);
+        file_lines.append(&mut lines);
+        lines = file_lines;
+    }
+    Ok(lines)
+}
+
+/// `tracker.YYYY-MM-DD.log` 形式のファイル名か
+fn is_log_file(name: &str) -> bool {
+    name.starts_with(&format!("{}.", LOG_FILE_PREFIX))
+        && name.ends_with(&format!(".{}", LOG_FILE_SUFFIX))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
     // ログ初期化は1回しか呼べないため、テストは最小限に
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_logging_module_exists() {
         // モジュールが正しくコンパイルされることを確認
         assert!(true);
     }
+
+    #[test]
+    fn test_log_format_resolve() {
+        assert_eq!(LogFormat::resolve(LogFormat::Text, None), LogFormat::Text);
+        assert_eq!(
+            LogFormat::resolve(LogFormat::Text, Some("JSON")),
+            LogFormat::Json
+        );
+        assert_eq!(
+            LogFormat::resolve(LogFormat::Json, Some("text")),
+            LogFormat::Text
+        );
+        // 解釈できない値は設定のまま
+        assert_eq!(
+            LogFormat::resolve(LogFormat::Json, Some("yaml")),
+            LogFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_traces_endpoint() {
+        assert_eq!(
+            traces_endpoint("http://localhost:4318"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://collector:4318/v1/traces/"),
+            "http://collector:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn test_tail() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("tracker.2025-01-09.log"), "a\nb\nc\n").unwrap();
+        fs::write(dir.join("tracker.2025-01-10.log"), "d\ne\n").unwrap();
+        fs::write(dir.join("other.txt"), "x\n").unwrap();
+
+        assert_eq!(tail(dir, 1).unwrap(), vec!["e"]);
+        assert_eq!(tail(dir, 3).unwrap(), vec!["c", "d", "e"]);
+        assert_eq!(tail(dir, 10).unwrap(), vec!["a", "b", "c", "d", "e"]);
+        assert!(tail(&dir.join("missing"), 10).unwrap().is_empty());
+    }
 }
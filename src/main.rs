@@ -1,20 +1,19 @@
 //! Habit Tracker - macOS向け個人作業トラッキングツール
 
-mod capture;
-mod cli;
-mod config;
-mod database;
-mod error;
-mod image_store;
-mod logging;
-mod metadata;
-mod ocr;
-mod pause_control;
-mod report;
-
 use anyhow::Result;
+use clap::Parser;
+use habit_tracker::cli::{self, Cli};
+use habit_tracker::config::{self, CliArgs, Config};
+use habit_tracker::{i18n, logging};
+use std::process::ExitCode;
+
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    config::init_overrides(cli.global.path_overrides())?;
 
-fn main() -> Result<()> {
-    logging::init();
-    cli::run()
+    // 設定の誤りは各コマンドで報告されるため、ここではデフォルトで続ける
+    let config = Config::load(&CliArgs::default()).unwrap_or_default();
+    let _log_guard = logging::init(&config);
+    i18n::init(config.language);
+    cli::run(cli)
 }
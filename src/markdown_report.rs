@@ -0,0 +1,445 @@
+//! Markdownレポートモジュール - 日次レポートのMarkdown生成と代表画像のサムネイルの埋め込み
+
+use crate::goals::TOTAL_GOAL;
+use crate::html_report::group_timeline;
+use crate::i18n::Message;
+use crate::report::{format_duration, DailyReport, TimelineEntry};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+/// サムネイルの長辺のピクセル数（Obsidianでの表示幅にも使う）
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+/// Markdownへの画像の埋め込み方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmbedImages {
+    /// 画像を埋め込まない
+    None,
+    /// 時間帯ごとの代表画像をサムネイルとして埋め込む
+    Thumbnail,
+}
+
+/// 日次レポートのMarkdownの生成
+///
+/// 画像は相対リンクで埋め込むため、Markdownを置くディレクトリを基準にする。
+/// サムネイルのコピー先を指定すると、縮小した画像をそこに作ってリンクする
+/// （Obsidianのvault外の画像は表示できないため、vault内のフォルダを指定する）
+pub struct MarkdownReport {
+    /// 相対リンクの基準にするディレクトリ
+    base_dir: PathBuf,
+    embed_images: EmbedImages,
+    /// サムネイルのコピー先（`None` なら元の画像にリンクする）
+    image_dir: Option<PathBuf>,
+}
+
+impl MarkdownReport {
+    /// Markdownを置くディレクトリを指定してMarkdownReportを作成
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            embed_images: EmbedImages::None,
+            image_dir: None,
+        }
+    }
+
+    /// 画像の埋め込み方法を設定
+    pub fn with_embed_images(mut self, embed_images: EmbedImages) -> Self {
+        self.embed_images = embed_images;
+        self
+    }
+
+    /// サムネイルのコピー先を設定
+    pub fn with_image_dir(mut self, image_dir: Option<PathBuf>) -> Self {
+        self.image_dir = image_dir;
+        self
+    }
+
+    /// 日次レポートをMarkdownにする（コピー先が指定されていればサムネイルも作る）
+    pub fn render(&self, report: &DailyReport) -> io::Result<String> {
+        let thumbnails = match self.embed_images {
+            EmbedImages::None => HashMap::new(),
+            EmbedImages::Thumbnail => self.thumbnails(report)?,
+        };
+        Ok(render_markdown(report, &thumbnails))
+    }
+
+    /// 時間帯（YYYY-MM-DDTHH）ごとの代表画像への相対リンク
+    ///
+    /// 画像が削除済みの時間帯と、サムネイルを作れなかった時間帯は埋め込まない
+    fn thumbnails<'a>(&self, report: &'a DailyReport) -> io::Result<HashMap<&'a str, String>> {
+        let mut links = HashMap::new();
+        for highlight in &report.highlights {
+            let image = Path::new(&highlight.image_path);
+            if !image.exists() {
+                warn!("代表画像が見つかりません: {}", image.display());
+                continue;
+            }
+            let target = match self.image_dir {
+                Some(ref dir) => {
+                    fs::create_dir_all(dir)?;
+                    let extension = image.extension().and_then(|e| e.to_str()).unwrap_or("jpg");
+                    let name = format!("{}.{}", highlight.hour.replace('T', "-"), extension);
+                    let thumbnail = dir.join(name);
+                    if let Err(e) = make_thumbnail(image, &thumbnail) {
+                        warn!(
+                            "サムネイルを作れないため代表画像を埋め込みません: {}: {}",
+                            image.display(),
+                            e
+                        );
+                        continue;
+                    }
+                    thumbnail
+                }
+                None => image.to_path_buf(),
+            };
+            links.insert(
+                highlight.hour.as_str(),
+                relative_path(&self.base_dir, &target)?,
+            );
+        }
+        Ok(links)
+    }
+}
+
+/// 日次レポートをMarkdownにする
+///
+/// タイムラインは1時間ごとの見出しに分け、同じアプリ・ウィンドウタイトルが続く区間を1行にまとめる。
+/// `thumbnails` に時間帯の画像へのリンクがあれば見出しの直後に埋め込む
+pub fn render_markdown(report: &DailyReport, thumbnails: &HashMap<&str, String>) -> String {
+    let mut markdown = String::new();
+    let _ = writeln!(
+        markdown,
+        "# {}\n",
+        Message::ReportTitle.format(&[&report.date])
+    );
+    let mut kpis = vec![
+        (Message::KpiWork, format_duration(report.kpi.work_seconds)),
+        (
+            Message::KpiMeeting,
+            format_duration(report.kpi.meeting_seconds),
+        ),
+        (Message::KpiFocus, format_duration(report.kpi.focus_seconds)),
+        (
+            Message::KpiDistraction,
+            format_duration(report.kpi.distraction_seconds),
+        ),
+        (
+            Message::KpiSwitches,
+            Message::Times.format(&[&report.kpi.context_switches]),
+        ),
+    ];
+    if report.kpi.pomodoros > 0 {
+        kpis.push((
+            Message::KpiPomodoro,
+            Message::Times.format(&[&report.kpi.pomodoros]),
+        ));
+    }
+    let kpis: Vec<String> = kpis
+        .into_iter()
+        .map(|(label, value)| format!("{} **{}**", label.text(), value))
+        .collect();
+    let _ = writeln!(markdown, "{}\n", kpis.join(" / "));
+
+    if !report.goals.is_empty() {
+        let _ = writeln!(markdown, "## {}\n", Message::GoalsSection.text());
+        for progress in &report.goals {
+            let goal = if progress.goal == TOTAL_GOAL {
+                Message::GoalTotal.text()
+            } else {
+                progress.goal.as_str()
+            };
+            let status = if progress.achieved {
+                Message::GoalAchieved
+            } else {
+                Message::GoalNotAchieved
+            };
+            let _ = writeln!(
+                markdown,
+                "- {}",
+                Message::GoalLineWithStreak.format(&[
+                    &escape_markdown(goal),
+                    &format_duration(progress.seconds),
+                    &format_duration(progress.target_seconds),
+                    &status.text(),
+                    &Message::GoalStreak
+                        .format(&[&progress.current_streak, &progress.longest_streak]),
+                ])
+            );
+        }
+        markdown.push('\n');
+    }
+
+    let _ = writeln!(
+        markdown,
+        "## {}\n\n| {} | {} | {} |\n| --- | ---: | ---: |",
+        Message::AppsSection.text(),
+        Message::AppColumn.text(),
+        Message::DurationColumn.text(),
+        Message::CapturesColumn.text()
+    );
+    for app in &report.apps {
+        let _ = writeln!(
+            markdown,
+            "| {} | {} | {} |",
+            escape_markdown(&app.app_name),
+            format_duration(app.duration_seconds),
+            app.capture_count
+        );
+    }
+    markdown.push('\n');
+
+    if !report.notes.is_empty() {
+        let _ = writeln!(markdown, "## {}\n", Message::NotesSection.text());
+        for note in &report.notes {
+            let _ = writeln!(
+                markdown,
+                "- {} {}",
+                note.created_at.get(11..16).unwrap_or(&note.created_at),
+                escape_markdown(&note.text)
+            );
+        }
+        markdown.push('\n');
+    }
+
+    let _ = writeln!(markdown, "## {}", Message::TimelineSection.text());
+    for block in hour_blocks(&report.timeline) {
+        let hour = block[0]
+            .captured_at
+            .get(..13)
+            .unwrap_or(&block[0].captured_at);
+        let _ = writeln!(
+            markdown,
+            "\n### {}\n",
+            Message::Hour.format(&[&hour.get(11..13).unwrap_or(hour)])
+        );
+        let highlight = report.highlights.iter().find(|h| h.hour == hour);
+        if let (Some(highlight), Some(link)) = (highlight, thumbnails.get(hour)) {
+            let _ = writeln!(
+                markdown,
+                "![{}|{}](<{}>)\n",
+                escape_markdown(&highlight.active_app),
+                THUMBNAIL_SIZE,
+                link
+            );
+        }
+        for (start, end) in group_timeline(block) {
+            let time = if start.time == end.time {
+                start.time.clone()
+            } else {
+                Message::TimeRange.format(&[&start.time, &end.time])
+            };
+            let _ = writeln!(
+                markdown,
+                "- {} **{}** {}",
+                time,
+                escape_markdown(&start.active_app),
+                escape_markdown(&start.window_title)
+            );
+            if let Some(ref excerpt) = start.ocr_excerpt {
+                let _ = writeln!(markdown, "  > {}", escape_markdown(excerpt));
+            }
+        }
+    }
+
+    markdown
+}
+
+/// タイムラインを壁時計の1時間ごとに分ける
+fn hour_blocks(timeline: &[TimelineEntry]) -> Vec<&[TimelineEntry]> {
+    timeline
+        .chunk_by(|a, b| a.captured_at.get(..13) == b.captured_at.get(..13))
+        .collect()
+}
+
+/// macOSの `sips` コマンドで画像を縮小して保存する
+///
+/// 元の画像をそのままコピーするとvaultが大きくなるため、縮小できなければエラーにする
+fn make_thumbnail(image: &Path, thumbnail: &Path) -> io::Result<()> {
+    let output = Command::new("sips")
+        .arg("-Z")
+        .arg(THUMBNAIL_SIZE.to_string())
+        .arg(image)
+        .arg("--out")
+        .arg(thumbnail)
+        .output()?;
+    if !output.status.success() {
+        // 書きかけのファイルは残さない
+        let _ = fs::remove_file(thumbnail);
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// `base_dir` から `target` への相対パス（区切りは `/`）
+fn relative_path(base_dir: &Path, target: &Path) -> io::Result<String> {
+    let base = std::path::absolute(base_dir)?;
+    let target = std::path::absolute(target)?;
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let mut parts = vec![".."; base.len() - common];
+    let rest: Vec<String> = target[common..]
+        .iter()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    parts.extend(rest.iter().map(String::as_str));
+    Ok(parts.join("/"))
+}
+
+/// Markdownで意味を持つ文字をエスケープ
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Highlight, Note};
+    use crate::report::{AppSummary, DailyKpi};
+    use tempfile::TempDir;
+
+    fn entry(time: &str, app: &str, title: &str) -> TimelineEntry {
+        TimelineEntry {
+            captured_at: format!("2025-01-10T{}+09:00", time),
+            time: time.to_string(),
+            active_app: app.to_string(),
+            window_title: title.to_string(),
+            activity_type: None,
+            ocr_excerpt: None,
+        }
+    }
+
+    fn report(image_path: &Path) -> DailyReport {
+        DailyReport {
+            date: "2025-01-10".to_string(),
+            kpi: DailyKpi {
+                work_seconds: 180,
+                meeting_seconds: 0,
+                focus_seconds: 120,
+                distraction_seconds: 0,
+                context_switches: 1,
+                pomodoros: 0,
+                paused_seconds: 0,
+                private_seconds: 0,
+                idle_seconds: 0,
+            },
+            total_seconds: 180,
+            timeline: vec![
+                TimelineEntry {
+                    ocr_excerpt: Some("fn main() {".to_string()),
+                    ..entry("10:58:00", "VS Code", "main.rs")
+                },
+                entry("10:59:00", "VS Code", "main.rs"),
+                entry("11:00:00", "VS Code", "main.rs"),
+                entry("11:01:00", "Safari", "[PR] *fix*"),
+            ],
+            apps: vec![AppSummary {
+                app_name: "VS Code".to_string(),
+                duration_seconds: 180,
+                capture_count: 3,
+            }],
+            media_seconds: 0,
+            media: Vec::new(),
+            non_work_seconds: 0,
+            non_work: Vec::new(),
+            paused: Vec::new(),
+            distraction_seconds: 0,
+            distractions: Vec::new(),
+            sleeps: Vec::new(),
+            highlights: vec![Highlight {
+                hour: "2025-01-10T11".to_string(),
+                capture_id: 3,
+                captured_at: "2025-01-10T11:00:00+09:00".to_string(),
+                image_path: image_path.to_string_lossy().into_owned(),
+                active_app: "VS Code".to_string(),
+                window_title: "main.rs".to_string(),
+                score: 120,
+                image_hash: None,
+            }],
+            notes: vec![Note {
+                id: Some(1),
+                created_at: "2025-01-10T10:30:00+09:00".to_string(),
+                text: "設計レビュー".to_string(),
+            }],
+            goals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_markdown_by_hour() {
+        let report = report(Path::new("/images/2025-01-10/110000.jpg"));
+        let thumbnails = HashMap::from([("2025-01-10T11", "images/110000.jpg".to_string())]);
+        let markdown = render_markdown(&report, &thumbnails);
+
+        assert!(markdown.starts_with("# 2025-01-10 の活動レポート\n"));
+        assert!(markdown.contains("フォーカス **2分**"));
+        assert!(markdown.contains("| VS Code | 3分 | 3 |"));
+        assert!(markdown.contains("- 10:30 設計レビュー\n"));
+        // 時間の見出しをまたぐ区間は分ける
+        assert!(markdown
+            .contains("### 10時\n\n- 10:58:00 〜 10:59:00 **VS Code** main.rs\n  > fn main() {\n"));
+        assert!(markdown.contains(
+            "### 11時\n\n![VS Code|320](<images/110000.jpg>)\n\n- 11:00:00 **VS Code** main.rs\n"
+        ));
+        assert!(markdown.contains("**Safari** \\[PR\\] \\*fix\\*"));
+    }
+
+    #[test]
+    fn test_render_with_thumbnails() {
+        let temp_dir = TempDir::new().unwrap();
+        let image = temp_dir.path().join("images/2025-01-10/110000.jpg");
+        fs::create_dir_all(image.parent().unwrap()).unwrap();
+        fs::write(&image, b"jpeg").unwrap();
+        let notes = temp_dir.path().join("vault/daily");
+        let report = report(&image);
+
+        // コピー先を指定しなければ元の画像に相対リンクする
+        let markdown = MarkdownReport::new(&notes)
+            .with_embed_images(EmbedImages::Thumbnail)
+            .render(&report)
+            .unwrap();
+        assert!(markdown.contains("(<../../images/2025-01-10/110000.jpg>)"));
+
+        // コピー先を指定した場合、サムネイルを作れない画像（sipsがない・中身がJPEGでない）は
+        // 元の画像をコピーせずに埋め込みを省く
+        let markdown = MarkdownReport::new(&notes)
+            .with_embed_images(EmbedImages::Thumbnail)
+            .with_image_dir(Some(temp_dir.path().join("vault/attachments")))
+            .render(&report)
+            .unwrap();
+        assert!(!markdown.contains("!["));
+        assert!(!temp_dir
+            .path()
+            .join("vault/attachments/2025-01-10-11.jpg")
+            .exists());
+
+        // 埋め込まない場合・画像が削除済みの場合はリンクしない
+        let markdown = MarkdownReport::new(&notes).render(&report).unwrap();
+        assert!(!markdown.contains("!["));
+        fs::remove_file(&image).unwrap();
+        let markdown = MarkdownReport::new(&notes)
+            .with_embed_images(EmbedImages::Thumbnail)
+            .render(&report)
+            .unwrap();
+        assert!(!markdown.contains("!["));
+    }
+}
@@ -0,0 +1,322 @@
+//! MCPサーバーモジュール - Model Context Protocol（stdio）で作業ログを公開
+
+use crate::error::McpError;
+use crate::report::Report;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use tracing::{debug, info, warn};
+
+/// サポートするプロトコルバージョン
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// 検索結果のデフォルト件数
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+/// JSON-RPCエラーコード
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+
+/// MCPサーバー
+pub struct McpServer {
+    report: Report,
+}
+
+impl McpServer {
+    /// 新しいMcpServerを作成
+    pub fn new(report: Report) -> Self {
+        Self { report }
+    }
+
+    /// 標準入出力でサーバーを実行
+    ///
+    /// メッセージは改行区切りのJSON-RPC 2.0。標準出力はプロトコル専用のため、
+    /// ログは標準エラー出力にのみ書き出す
+    pub fn run(&self) -> Result<(), McpError> {
+        info!("MCPサーバーを開始します（stdio）");
+
+        let stdin = std::io::stdin();
+        let mut stdout = std::io::stdout();
+
+        for line in stdin.lock().lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => self.handle_message(&request),
+                Err(e) => Some(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("Parse error: {}", e),
+                )),
+            };
+
+            if let Some(response) = response {
+                writeln!(stdout, "{}", response)?;
+                stdout.flush()?;
+            }
+        }
+
+        info!("MCPサーバーを終了します");
+        Ok(())
+    }
+
+    /// 1件のメッセージを処理
+    ///
+    /// 通知（idなし）の場合は応答しないため `None` を返す
+    pub fn handle_message(&self, message: &Value) -> Option<Value> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        debug!("MCPリクエスト: {}", method);
+
+        // 通知には応答しない
+        let id = id?;
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "habit-tracker",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    /// ツールを呼び出す
+    ///
+    /// ツール実行時のエラーはプロトコルエラーではなく `isError` 付きの結果として返す
+    fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let args = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        let output = match name {
+            "get_timeline" => {
+                let date = required_str(&args, "date")?;
+                self.report
+                    .timeline(date)
+                    .map(|entries| json!({ "date": date, "timeline": entries }))
+            }
+            "search_ocr" => {
+                let query = required_str(&args, "query")?;
+                let limit = args
+                    .get("limit")
+                    .and_then(Value::as_i64)
+                    .unwrap_or(DEFAULT_SEARCH_LIMIT);
+                self.report
+                    .search(query, limit)
+                    .map(|hits| json!({ "query": query, "results": hits }))
+            }
+            "get_app_summary" => {
+                let date = required_str(&args, "date")?;
+                self.report
+                    .time_by_app(date)
+                    .map(|apps| json!({ "date": date, "apps": apps }))
+            }
+            _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        };
+
+        Ok(match output {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": value.to_string() }],
+                "isError": false,
+            }),
+            Err(e) => {
+                warn!("MCPツール実行エラー ({}): {}", name, e);
+                json!({
+                    "content": [{ "type": "text", "text": e.to_string() }],
+                    "isError": true,
+                })
+            }
+        })
+    }
+}
+
+/// 公開するツールの定義
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "get_timeline",
+            "description": "指定日のキャプチャのタイムライン（時刻・アプリ・ウィンドウタイトル）を取得する",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "date": { "type": "string", "description": "対象日（YYYY-MM-DD）" }
+                },
+                "required": ["date"]
+            }
+        },
+        {
+            "name": "search_ocr",
+            "description": "スクリーンショットのOCRテキストを部分一致で全文検索する（新しい順）",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "検索語" },
+                    "limit": { "type": "integer", "description": "最大件数（デフォルト20）" }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_app_summary",
+            "description": "指定日のアプリ別作業時間を集計する",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "date": { "type": "string", "description": "対象日（YYYY-MM-DD）" }
+                },
+                "required": ["date"]
+            }
+        }
+    ])
+}
+
+/// 必須の文字列引数を取得
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, (i64, String)> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, format!("Missing argument: {}", key)))
+}
+
+/// JSON-RPCエラー応答を生成
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{CaptureRecord, Database};
+    use tempfile::TempDir;
+
+    fn create_test_server() -> (McpServer, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:00:00".to_string(),
+            image_path: None,
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: Some("fn main() {}".to_string()),
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        (McpServer::new(Report::new(db, 60)), temp_dir)
+    }
+
+    fn tool_text(response: &Value) -> Value {
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_initialize() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }))
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn test_notification_has_no_response() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn test_tools_list() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }))
+            .unwrap();
+        assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_call_get_timeline() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": { "name": "get_timeline", "arguments": { "date": "2024-12-30" } }
+            }))
+            .unwrap();
+        assert_eq!(response["result"]["isError"], false);
+        let body = tool_text(&response);
+        assert_eq!(body["timeline"][0]["active_app"], "VS Code");
+    }
+
+    #[test]
+    fn test_call_search_ocr() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0",
+                "id": 4,
+                "method": "tools/call",
+                "params": { "name": "search_ocr", "arguments": { "query": "main" } }
+            }))
+            .unwrap();
+        let body = tool_text(&response);
+        assert_eq!(body["results"][0]["snippet"], "fn main() {}");
+    }
+
+    #[test]
+    fn test_call_with_invalid_date_is_tool_error() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0",
+                "id": 5,
+                "method": "tools/call",
+                "params": { "name": "get_app_summary", "arguments": { "date": "yesterday" } }
+            }))
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[test]
+    fn test_unknown_method() {
+        let (server, _temp_dir) = create_test_server();
+        let response = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "id": 6, "method": "unknown" }))
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
@@ -1,8 +1,20 @@
 //! メタデータ収集モジュール
 
 use crate::error::MetadataError;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tracing::warn;
+use tracing::{debug, warn};
+use url::Url;
+
+/// 画面収録の権限を設定するシステム設定の画面
+pub const SCREEN_RECORDING_SETTINGS: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture";
+
+/// アクセシビリティの権限を設定するシステム設定の画面
+pub const ACCESSIBILITY_SETTINGS: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
 
 /// メタデータ収集
 pub struct Metadata;
@@ -17,8 +29,7 @@ impl Metadata {
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(MetadataError::CommandFailed(std::io::Error::new(
-                std::io::ErrorKind::Other,
+            return Err(MetadataError::CommandFailed(std::io::Error::other(
                 format!("osascript failed: {}", stderr),
             )));
         }
@@ -55,6 +66,451 @@ impl Metadata {
         let title = String::from_utf8(output.stdout)?;
         Ok(title.trim().to_string())
     }
+
+    /// 最前面のウィンドウがフルスクリーンか
+    ///
+    /// 取得できない場合（ウィンドウのないアプリなど）は `false` を返す
+    pub fn is_fullscreen() -> bool {
+        Command::new("osascript")
+            .arg("-e")
+            .arg(r#"tell application "System Events" to get value of attribute "AXFullScreen" of front window of first process whose frontmost is true"#)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .is_some_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+    }
+
+    /// 最前面のアプリケーションのbundle IDを取得（`com.apple.Safari` など）
+    ///
+    /// 取得できない場合やbundle IDを持たないプロセスでは `None` を返す
+    pub fn get_active_bundle_id() -> Option<String> {
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(r#"tell application "System Events" to get bundle identifier of first process whose frontmost is true"#)
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let bundle_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!bundle_id.is_empty() && bundle_id != "missing value").then_some(bundle_id)
+    }
+
+    /// 最前面のアプリの作業ディレクトリを取得
+    ///
+    /// ターミナルはカレントディレクトリ、VS Codeはワークスペースのフォルダを返す。
+    /// それ以外のアプリや取得できない場合は `None` を返す
+    pub fn get_working_directory(
+        app: &str,
+        bundle_id: Option<&str>,
+        window_title: &str,
+    ) -> Option<String> {
+        if is_vscode(app, bundle_id) {
+            return Self::get_vscode_workspace(window_title);
+        }
+        Self::get_terminal_cwd(app, bundle_id)
+    }
+
+    /// 最前面のVS Codeのワークスペースのフォルダを取得
+    ///
+    /// VS Codeのプロセスの引数で開いたフォルダのうち、ウィンドウタイトルの
+    /// ワークスペース名と名前が一致するものを返す（見つからなければ `None`）
+    pub fn get_vscode_workspace(window_title: &str) -> Option<String> {
+        let workspace = parse_vscode_title(window_title)?.workspace;
+        let processes = command_stdout(Command::new("ps").args(["-axo", "args="]))?;
+        parse_vscode_folders(&processes).into_iter().find(|folder| {
+            Path::new(folder)
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy() == workspace)
+        })
+    }
+
+    /// 最前面のターミナルのカレントディレクトリを取得
+    ///
+    /// Terminal・iTerm2は選択中のタブのTTYで動いているフォアグラウンドのプロセス、
+    /// WezTermはフォーカスのあるペインのディレクトリを返す。
+    /// ターミナル以外のアプリや取得できない場合は `None` を返す
+    pub fn get_terminal_cwd(app: &str, bundle_id: Option<&str>) -> Option<String> {
+        let cwd = match TerminalApp::detect(app, bundle_id)? {
+            TerminalApp::Terminal => Self::tty_cwd(TERMINAL_TTY_SCRIPT),
+            TerminalApp::ITerm2 => Self::tty_cwd(ITERM2_TTY_SCRIPT),
+            TerminalApp::WezTerm => Self::wezterm_cwd(),
+        };
+        if cwd.is_none() {
+            debug!(
+                "ターミナルのカレントディレクトリを取得できませんでした: {}",
+                app
+            );
+        }
+        cwd
+    }
+
+    /// AppleScriptで取得したTTYのフォアグラウンドのプロセスのカレントディレクトリ
+    fn tty_cwd(script: &str) -> Option<String> {
+        let tty = command_stdout(Command::new("osascript").arg("-e").arg(script))?;
+        let tty = tty.trim().trim_start_matches("/dev/");
+        if tty.is_empty() {
+            return None;
+        }
+        let processes = command_stdout(Command::new("ps").args(["-o", "pid=,stat=", "-t", tty]))?;
+        let pid = parse_foreground_pid(&processes)?;
+        let files = command_stdout(Command::new("lsof").args([
+            "-a",
+            "-p",
+            &pid.to_string(),
+            "-d",
+            "cwd",
+            "-Fn",
+        ]))?;
+        parse_lsof_cwd(&files)
+    }
+
+    /// WezTermのフォーカスのあるペインのカレントディレクトリ
+    fn wezterm_cwd() -> Option<String> {
+        WEZTERM_COMMANDS.iter().find_map(|command| {
+            let clients = command_stdout(Command::new(command).args([
+                "cli",
+                "list-clients",
+                "--format",
+                "json",
+            ]))?;
+            let panes =
+                command_stdout(Command::new(command).args(["cli", "list", "--format", "json"]))?;
+            parse_wezterm_cwd(&clients, &panes)
+        })
+    }
+
+    /// ホスト名を取得（取得できなければ `unknown`）
+    pub fn hostname() -> String {
+        Command::new("hostname")
+            .arg("-s")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// オンになっているmacOSの集中モード（Focus）の名前を取得（オフなら `None`）
+    ///
+    /// `~/Library/DoNotDisturb/DB` の状態ファイルを読むため、フルディスクアクセスの権限が必要
+    pub fn get_focus_mode() -> Result<Option<String>, MetadataError> {
+        let dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Library/DoNotDisturb/DB");
+        let assertions = fs::read_to_string(dir.join("Assertions.json"))?;
+        let configurations = fs::read_to_string(dir.join("ModeConfigurations.json"))?;
+        Ok(parse_focus_mode(&assertions, &configurations))
+    }
+
+    /// 画面収録・アクセシビリティの権限があるか確認
+    ///
+    /// 権限はosascriptを起動した親プロセス（ターミナルやlaunchd）に対して判定される
+    pub fn check_permissions() -> Result<Permissions, MetadataError> {
+        let output = Command::new("osascript")
+            .arg("-l")
+            .arg("JavaScript")
+            .arg("-e")
+            .arg(PERMISSIONS_SCRIPT)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(MetadataError::CommandFailed(std::io::Error::other(
+                format!("osascript failed: {}", stderr),
+            )));
+        }
+
+        let stdout = String::from_utf8(output.stdout)?;
+        parse_permissions(&stdout).ok_or_else(|| {
+            MetadataError::CommandFailed(std::io::Error::other(format!(
+                "unexpected output: {}",
+                stdout.trim()
+            )))
+        })
+    }
+}
+
+/// カレントディレクトリを取得できるターミナル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TerminalApp {
+    Terminal,
+    ITerm2,
+    WezTerm,
+}
+
+impl TerminalApp {
+    /// アプリ名・bundle IDからターミナルを判定（ターミナル以外は `None`）
+    fn detect(app: &str, bundle_id: Option<&str>) -> Option<Self> {
+        match bundle_id {
+            Some("com.apple.Terminal") => return Some(Self::Terminal),
+            Some("com.googlecode.iterm2") => return Some(Self::ITerm2),
+            Some("com.github.wez.wezterm") => return Some(Self::WezTerm),
+            _ => {}
+        }
+        match app {
+            "Terminal" | "ターミナル" => Some(Self::Terminal),
+            "iTerm2" | "iTerm" => Some(Self::ITerm2),
+            "WezTerm" | "wezterm-gui" => Some(Self::WezTerm),
+            _ => None,
+        }
+    }
+}
+
+/// VS Code（派生エディタを含む）のアプリ名
+const VSCODE_APPS: [&str; 6] = [
+    "Code",
+    "Visual Studio Code",
+    "Code - Insiders",
+    "Visual Studio Code - Insiders",
+    "VSCodium",
+    "Cursor",
+];
+
+/// VS Code（派生エディタを含む）のbundle ID
+const VSCODE_BUNDLE_IDS: [&str; 4] = [
+    "com.microsoft.VSCode",
+    "com.microsoft.VSCodeInsiders",
+    "com.vscodium",
+    "com.todesktop.230313mzl4w4u92",
+];
+
+/// VS Codeのウィンドウタイトルの区切り（macOSの既定の `window.title` は ` — ` で区切る）
+const VSCODE_TITLE_SEPARATOR: &str = " — ";
+
+/// VS Codeのウィンドウタイトルの内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VsCodeTitle {
+    /// 開いているファイル（エディタを開いていなければ `None`）
+    pub file: Option<String>,
+    /// ワークスペース（フォルダ）名
+    pub workspace: String,
+}
+
+/// アプリがVS Code（派生エディタを含む）か
+pub fn is_vscode(app: &str, bundle_id: Option<&str>) -> bool {
+    bundle_id.is_some_and(|id| VSCODE_BUNDLE_IDS.contains(&id)) || VSCODE_APPS.contains(&app)
+}
+
+/// VS Codeのウィンドウタイトル（`● ファイル — ワークスペース — プロファイル — アプリ名`）を解析
+///
+/// 未保存の印・アプリ名と、リモート接続（`[SSH: host]` など）・`(Workspace)` の表記を除く。
+/// 区切りがなければタイトル全体をワークスペース名とする（エディタを開いていないウィンドウ）
+pub fn parse_vscode_title(title: &str) -> Option<VsCodeTitle> {
+    let title = title.trim().trim_start_matches('●').trim();
+    let mut parts: Vec<&str> = title
+        .split(VSCODE_TITLE_SEPARATOR)
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.len() > 1 && parts.last().is_some_and(|last| VSCODE_APPS.contains(last)) {
+        parts.pop();
+    }
+    let (file, workspace) = match parts.as_slice() {
+        [] => return None,
+        [workspace] => (None, *workspace),
+        [file, workspace, ..] => (Some(file.to_string()), *workspace),
+    };
+    let workspace = strip_workspace_suffix(workspace);
+    (!workspace.is_empty()).then(|| VsCodeTitle {
+        file,
+        workspace: workspace.to_string(),
+    })
+}
+
+/// ワークスペース名の末尾のリモート接続・マルチルートの表記を除く
+fn strip_workspace_suffix(workspace: &str) -> &str {
+    let mut workspace = workspace.trim();
+    loop {
+        let stripped = workspace
+            .strip_suffix("(Workspace)")
+            .or_else(|| {
+                workspace
+                    .ends_with(']')
+                    .then(|| workspace.rfind('[').map(|i| &workspace[..i]))
+                    .flatten()
+            })
+            .map(str::trim);
+        match stripped {
+            Some(rest) => workspace = rest,
+            None => return workspace,
+        }
+    }
+}
+
+/// `ps -axo args=` の出力から、VS Codeのプロセスの引数で開いたフォルダを取り出す
+///
+/// メインプロセス（Helper以外）とCLIの、`--folder-uri=file://...` と
+/// オプション以外の絶対パスの引数を対象にする（空白を含むパスは取り出せない）
+fn parse_vscode_folders(output: &str) -> Vec<String> {
+    let mut folders: Vec<String> = Vec::new();
+    for line in output.lines() {
+        let is_vscode = (line.contains("Visual Studio Code") || line.contains("/Electron"))
+            && !line.contains("Helper");
+        let mut args = line.split_whitespace();
+        let is_cli = args
+            .next()
+            .is_some_and(|command| command.ends_with("/code"));
+        if !is_vscode && !is_cli {
+            continue;
+        }
+        for arg in args {
+            let folder = match arg.strip_prefix("--folder-uri=") {
+                Some(uri) => Url::parse(uri)
+                    .ok()
+                    .and_then(|url| url.to_file_path().ok())
+                    .map(|path| path.to_string_lossy().to_string()),
+                None => (arg.starts_with('/') && !arg.starts_with("//")).then(|| arg.to_string()),
+            };
+            if let Some(folder) = folder {
+                let folder = folder.trim_end_matches('/').to_string();
+                if !folder.is_empty() && !folders.contains(&folder) {
+                    folders.push(folder);
+                }
+            }
+        }
+    }
+    folders
+}
+
+/// Terminalの選択中のタブのTTYを取得するAppleScript
+const TERMINAL_TTY_SCRIPT: &str =
+    r#"tell application "Terminal" to get tty of selected tab of front window"#;
+
+/// iTerm2の選択中のセッションのTTYを取得するAppleScript
+const ITERM2_TTY_SCRIPT: &str =
+    r#"tell application "iTerm2" to get tty of current session of current window"#;
+
+/// WezTermのCLI（PATHになければアプリに同梱のものを使う）
+const WEZTERM_COMMANDS: [&str; 2] = [
+    "wezterm",
+    "/Applications/WezTerm.app/Contents/MacOS/wezterm",
+];
+
+/// コマンドを実行し、成功すれば標準出力を返す
+fn command_stdout(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `ps -o pid=,stat=` の出力からフォアグラウンドのプロセスのPIDを取り出す
+///
+/// フォアグラウンドのプロセスグループ（状態に `+`）の最後のプロセス、なければシェル（先頭）を返す
+fn parse_foreground_pid(output: &str) -> Option<u32> {
+    let processes: Vec<(u32, &str)> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            Some((pid, fields.next().unwrap_or("")))
+        })
+        .collect();
+    processes
+        .iter()
+        .rev()
+        .find(|(_, stat)| stat.contains('+'))
+        .or_else(|| processes.first())
+        .map(|(pid, _)| *pid)
+}
+
+/// `lsof -d cwd -Fn` の出力からカレントディレクトリを取り出す
+fn parse_lsof_cwd(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+}
+
+/// `wezterm cli list-clients` と `wezterm cli list` のJSONから、
+/// フォーカスのあるペインのカレントディレクトリ（`file://ホスト/パス`）を取り出す
+fn parse_wezterm_cwd(clients: &str, panes: &str) -> Option<String> {
+    let clients: Value = serde_json::from_str(clients).ok()?;
+    let pane_id = clients
+        .as_array()?
+        .iter()
+        .find_map(|c| c["focused_pane_id"].as_u64())?;
+    let panes: Value = serde_json::from_str(panes).ok()?;
+    let cwd = panes
+        .as_array()?
+        .iter()
+        .find(|pane| pane["pane_id"].as_u64() == Some(pane_id))?["cwd"]
+        .as_str()?;
+    let url = Url::parse(cwd).ok()?;
+    // ホスト名が付いているとパスに変換できないため、パスだけのURLにする
+    let path = Url::parse(&format!("file://{}", url.path()))
+        .ok()?
+        .to_file_path()
+        .ok()?;
+    Some(path.to_string_lossy().to_string())
+}
+
+/// キャプチャに必要な権限の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    /// 画面収録（screencapture）
+    pub screen_recording: bool,
+    /// アクセシビリティ（System Events経由のアプリ・ウィンドウ取得）
+    pub accessibility: bool,
+}
+
+impl Permissions {
+    /// 不足している権限の名前
+    pub fn missing(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if !self.screen_recording {
+            missing.push("画面収録");
+        }
+        if !self.accessibility {
+            missing.push("アクセシビリティ");
+        }
+        missing
+    }
+}
+
+/// 権限の有無を `画面収録\tアクセシビリティ`（1/0）で出力するJXAスクリプト
+const PERMISSIONS_SCRIPT: &str = r#"
+ObjC.import('CoreGraphics');
+ObjC.import('ApplicationServices');
+[$.CGPreflightScreenCaptureAccess(), $.AXIsProcessTrusted()].map((v) => (v ? 1 : 0)).join('\t');
+"#;
+
+/// `画面収録\tアクセシビリティ` 形式の出力を解析
+fn parse_permissions(output: &str) -> Option<Permissions> {
+    match output.trim().split('\t').collect::<Vec<_>>().as_slice() {
+        [screen_recording, accessibility] => Some(Permissions {
+            screen_recording: *screen_recording == "1",
+            accessibility: *accessibility == "1",
+        }),
+        _ => None,
+    }
+}
+
+/// 集中モードの状態ファイルから、オンになっているモードの名前を取り出す
+///
+/// `Assertions.json` の有効なモードIDを `ModeConfigurations.json` で名前に変換する
+/// （名前が見つからなければモードIDを返す）
+fn parse_focus_mode(assertions: &str, configurations: &str) -> Option<String> {
+    let assertions: Value = serde_json::from_str(assertions).ok()?;
+    let mode_id = assertions["data"][0]["storeAssertionRecords"]
+        .as_array()?
+        .iter()
+        .find_map(|record| record["assertionDetails"]["assertionDetailsModeIdentifier"].as_str())?;
+
+    let name = serde_json::from_str::<Value>(configurations)
+        .ok()
+        .and_then(|configurations| {
+            configurations["data"][0]["modeConfigurations"][mode_id]["mode"]["name"]
+                .as_str()
+                .map(str::to_string)
+        });
+    Some(name.unwrap_or_else(|| mode_id.to_string()))
 }
 
 #[cfg(test)]
@@ -70,6 +526,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]
     fn test_get_window_title_returns_string() {
         // 常に文字列を返すことを確認（エラー時は空文字列）
         let result = Metadata::get_window_title();
@@ -77,6 +534,139 @@ mod tests {
         assert!(result.len() >= 0);
     }
 
+    #[test]
+    fn test_parse_permissions() {
+        let permissions = parse_permissions("1\t0\n").unwrap();
+        assert!(permissions.screen_recording);
+        assert!(!permissions.accessibility);
+        assert_eq!(permissions.missing(), vec!["アクセシビリティ"]);
+
+        assert!(parse_permissions("true").is_none());
+    }
+
+    #[test]
+    fn test_parse_focus_mode() {
+        let configurations = r#"{"data":[{"modeConfigurations":{
+            "com.apple.focus.work":{"mode":{"name":"仕事","modeIdentifier":"com.apple.focus.work"}}
+        }}]}"#;
+        let assertions = r#"{"data":[{"storeAssertionRecords":[
+            {"assertionDetails":{"assertionDetailsModeIdentifier":"com.apple.focus.work"}}
+        ]}]}"#;
+        assert_eq!(
+            parse_focus_mode(assertions, configurations),
+            Some("仕事".to_string())
+        );
+
+        // 名前が見つからなければモードIDを返す
+        let assertions = r#"{"data":[{"storeAssertionRecords":[
+            {"assertionDetails":{"assertionDetailsModeIdentifier":"com.apple.donotdisturb.mode.default"}}
+        ]}]}"#;
+        assert_eq!(
+            parse_focus_mode(assertions, configurations),
+            Some("com.apple.donotdisturb.mode.default".to_string())
+        );
+
+        // オフ（レコードなし）
+        assert_eq!(parse_focus_mode(r#"{"data":[{}]}"#, configurations), None);
+        assert_eq!(parse_focus_mode("", configurations), None);
+    }
+
+    #[test]
+    fn test_detect_terminal() {
+        assert_eq!(
+            TerminalApp::detect("ターミナル", Some("com.apple.Terminal")),
+            Some(TerminalApp::Terminal)
+        );
+        assert_eq!(
+            TerminalApp::detect("iTerm2", None),
+            Some(TerminalApp::ITerm2)
+        );
+        assert_eq!(
+            TerminalApp::detect("wezterm-gui", None),
+            Some(TerminalApp::WezTerm)
+        );
+        assert_eq!(
+            TerminalApp::detect("Safari", Some("com.apple.Safari")),
+            None
+        );
+        assert_eq!(Metadata::get_terminal_cwd("Safari", None), None);
+    }
+
+    #[test]
+    fn test_parse_foreground_pid() {
+        let output = " 4012 Ss    \n 4388 S+    \n 4390 R+    \n";
+        assert_eq!(parse_foreground_pid(output), Some(4390));
+        // フォアグラウンドのプロセスがなければシェル
+        assert_eq!(parse_foreground_pid(" 4012 Ss\n"), Some(4012));
+        assert_eq!(parse_foreground_pid(""), None);
+    }
+
+    #[test]
+    fn test_parse_lsof_cwd() {
+        let output = "p4390\nfcwd\nn/Users/me/src/habit-tracker\n";
+        assert_eq!(
+            parse_lsof_cwd(output),
+            Some("/Users/me/src/habit-tracker".to_string())
+        );
+        assert_eq!(parse_lsof_cwd("p4390\n"), None);
+    }
+
+    #[test]
+    fn test_parse_wezterm_cwd() {
+        let clients = r#"[{"hostname":"mac","focused_pane_id":3}]"#;
+        let panes = r#"[
+            {"pane_id":1,"cwd":"file://mac/Users/me"},
+            {"pane_id":3,"cwd":"file://mac/Users/me/src/my%20app"}
+        ]"#;
+        assert_eq!(
+            parse_wezterm_cwd(clients, panes),
+            Some("/Users/me/src/my app".to_string())
+        );
+        assert_eq!(parse_wezterm_cwd("[]", panes), None);
+    }
+
+    #[test]
+    fn test_parse_vscode_title() {
+        let title = |file: Option<&str>, workspace: &str| VsCodeTitle {
+            file: file.map(str::to_string),
+            workspace: workspace.to_string(),
+        };
+        assert_eq!(
+            parse_vscode_title("● report.rs — habit-tracker"),
+            Some(title(Some("report.rs"), "habit-tracker"))
+        );
+        // アプリ名・プロファイル名・リモート接続の表記を除く
+        assert_eq!(
+            parse_vscode_title("main.rs — api [SSH: dev-server] — Work — Visual Studio Code"),
+            Some(title(Some("main.rs"), "api"))
+        );
+        assert_eq!(
+            parse_vscode_title("monorepo (Workspace)"),
+            Some(title(None, "monorepo"))
+        );
+        assert_eq!(parse_vscode_title(""), None);
+        assert!(is_vscode("Code", None));
+        assert!(is_vscode("VS Code", Some("com.microsoft.VSCode")));
+        assert!(!is_vscode("Xcode", Some("com.apple.dt.Xcode")));
+    }
+
+    #[test]
+    fn test_parse_vscode_folders() {
+        let output = "\
+/Applications/Visual Studio Code.app/Contents/MacOS/Electron /Users/me/src/habit-tracker
+/Applications/Visual Studio Code.app/Contents/Frameworks/Code Helper.app/Contents/MacOS/Code Helper --type=renderer
+/usr/local/bin/code --folder-uri=file:///Users/me/src/my%20api/
+/bin/zsh /Users/me/script.sh
+";
+        assert_eq!(
+            parse_vscode_folders(output),
+            vec![
+                "/Users/me/src/habit-tracker".to_string(),
+                "/Users/me/src/my api".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_get_window_title_never_panics() {
         // パニックしないことを確認
@@ -0,0 +1,457 @@
+//! メタデータプロバイダモジュール - 設定で有効にしたプロバイダによるメタデータの収集
+
+use crate::error::MetadataError;
+use crate::hooks::Hook;
+use crate::metadata::Metadata;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+/// プロバイダが返すkey-value
+pub type MetadataValues = BTreeMap<String, String>;
+
+/// 最前面のアプリ名のキー
+pub const KEY_ACTIVE_APP: &str = "active_app";
+/// 最前面のアプリのbundle IDのキー
+pub const KEY_BUNDLE_ID: &str = "bundle_id";
+/// ウィンドウタイトルのキー
+pub const KEY_WINDOW_TITLE: &str = "window_title";
+/// ターミナル・VS Codeの作業ディレクトリのキー
+pub const KEY_WORKING_DIRECTORY: &str = "working_directory";
+/// ブラウザで表示中のページのURLのキー
+pub const KEY_BROWSER_URL: &str = "browser_url";
+/// Gitリポジトリ名のキー
+pub const KEY_GIT_REPOSITORY: &str = "git_repository";
+/// Gitのブランチ名のキー
+pub const KEY_GIT_BRANCH: &str = "git_branch";
+
+/// key-valueをDBに保存するJSONオブジェクトにする
+pub fn to_json(values: &MetadataValues) -> String {
+    let object: serde_json::Map<String, Value> = values
+        .iter()
+        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+        .collect();
+    Value::Object(object).to_string()
+}
+
+/// 最前面の画面についてのメタデータを集めるプロバイダ
+///
+/// 有効なプロバイダを順に呼び出し、それまでに集まった値（`collected`）を渡す。
+/// 返した値のうち、先に集まっているキーは上書きしない。
+/// キャプチャループのタスクとともにランタイムのスレッド間を移るため `Send` を要求する
+pub trait MetadataProvider: Send {
+    /// プロバイダ名（収集失敗のログに使う）
+    fn name(&self) -> &str;
+
+    /// メタデータを収集する
+    fn collect(&self, collected: &MetadataValues) -> Result<MetadataValues, MetadataError>;
+}
+
+/// 設定の `metadata_providers` で有効にできる組み込みのプロバイダ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataProviderKind {
+    /// 最前面のアプリ名とbundle ID（常に有効）
+    ActiveApp,
+    /// ウィンドウタイトル（常に有効）
+    WindowTitle,
+    /// Safari・Chrome系ブラウザで表示中のページのURL
+    BrowserUrl,
+    /// 作業ディレクトリのGitリポジトリ名とブランチ名
+    Git,
+}
+
+impl MetadataProviderKind {
+    /// プロバイダを作成
+    pub fn create(&self) -> Box<dyn MetadataProvider> {
+        match self {
+            Self::ActiveApp => Box::new(ActiveAppProvider),
+            Self::WindowTitle => Box::new(WindowTitleProvider),
+            Self::BrowserUrl => Box::new(BrowserUrlProvider),
+            Self::Git => Box::new(GitProvider),
+        }
+    }
+}
+
+/// 収集したメタデータと失敗したプロバイダ
+#[derive(Debug, Default)]
+pub struct CollectedMetadata {
+    pub values: MetadataValues,
+    pub failures: Vec<(String, MetadataError)>,
+}
+
+/// メタデータプロバイダの集合
+pub struct MetadataCollector {
+    providers: Vec<Box<dyn MetadataProvider>>,
+}
+
+impl MetadataCollector {
+    /// プロバイダを指定して作成（指定した順に呼び出す）
+    pub fn new(providers: Vec<Box<dyn MetadataProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// 設定から作成
+    ///
+    /// アプリ名・ウィンドウタイトル・作業ディレクトリは記録に欠かせないため常に先に収集し、
+    /// その後に有効にしたプロバイダ、外部コマンド（名前順）を呼び出す
+    pub fn from_config(
+        kinds: &[MetadataProviderKind],
+        commands: &BTreeMap<String, String>,
+    ) -> Self {
+        let mut providers: Vec<Box<dyn MetadataProvider>> = vec![
+            Box::new(ActiveAppProvider),
+            Box::new(WindowTitleProvider),
+            Box::new(WorkingDirectoryProvider),
+        ];
+        let mut optional: Vec<MetadataProviderKind> = Vec::new();
+        for kind in kinds {
+            let always = matches!(
+                kind,
+                MetadataProviderKind::ActiveApp | MetadataProviderKind::WindowTitle
+            );
+            if !always && !optional.contains(kind) {
+                optional.push(*kind);
+            }
+        }
+        providers.extend(optional.iter().map(MetadataProviderKind::create));
+        providers.extend(commands.iter().map(|(name, command)| {
+            Box::new(CommandProvider::new(name, command)) as Box<dyn MetadataProvider>
+        }));
+        Self::new(providers)
+    }
+
+    /// すべてのプロバイダからメタデータを収集する（失敗したプロバイダは飛ばす）
+    pub fn collect(&self) -> CollectedMetadata {
+        let mut collected = CollectedMetadata::default();
+        for provider in &self.providers {
+            match provider.collect(&collected.values) {
+                Ok(values) => {
+                    for (key, value) in values {
+                        collected.values.entry(key).or_insert(value);
+                    }
+                }
+                Err(e) => collected.failures.push((provider.name().to_string(), e)),
+            }
+        }
+        collected
+    }
+}
+
+/// 最前面のアプリ名とbundle ID
+pub struct ActiveAppProvider;
+
+impl MetadataProvider for ActiveAppProvider {
+    fn name(&self) -> &str {
+        KEY_ACTIVE_APP
+    }
+
+    fn collect(&self, _collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+        let mut values =
+            MetadataValues::from([(KEY_ACTIVE_APP.to_string(), Metadata::get_active_app()?)]);
+        if let Some(bundle_id) = Metadata::get_active_bundle_id() {
+            values.insert(KEY_BUNDLE_ID.to_string(), bundle_id);
+        }
+        Ok(values)
+    }
+}
+
+/// ウィンドウタイトル（取得できなければ空文字列）
+pub struct WindowTitleProvider;
+
+impl MetadataProvider for WindowTitleProvider {
+    fn name(&self) -> &str {
+        KEY_WINDOW_TITLE
+    }
+
+    fn collect(&self, _collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+        Ok(MetadataValues::from([(
+            KEY_WINDOW_TITLE.to_string(),
+            Metadata::get_window_title(),
+        )]))
+    }
+}
+
+/// ターミナルのカレントディレクトリ・VS Codeのワークスペースのフォルダ
+pub struct WorkingDirectoryProvider;
+
+impl MetadataProvider for WorkingDirectoryProvider {
+    fn name(&self) -> &str {
+        KEY_WORKING_DIRECTORY
+    }
+
+    fn collect(&self, collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+        let Some(app) = collected.get(KEY_ACTIVE_APP) else {
+            return Ok(MetadataValues::new());
+        };
+        let directory = Metadata::get_working_directory(
+            app,
+            collected.get(KEY_BUNDLE_ID).map(String::as_str),
+            collected.get(KEY_WINDOW_TITLE).map_or("", String::as_str),
+        );
+        Ok(directory
+            .map(|directory| (KEY_WORKING_DIRECTORY.to_string(), directory))
+            .into_iter()
+            .collect())
+    }
+}
+
+/// URLを取得できるブラウザ（bundle ID）と、表示中のタブのURLを返すAppleScript
+const BROWSER_URL_SCRIPTS: [(&str, &str); 7] = [
+    ("com.apple.Safari", "get URL of front document"),
+    (
+        "com.apple.SafariTechnologyPreview",
+        "get URL of front document",
+    ),
+    ("com.google.Chrome", "get URL of active tab of front window"),
+    ("com.brave.Browser", "get URL of active tab of front window"),
+    (
+        "com.microsoft.edgemac",
+        "get URL of active tab of front window",
+    ),
+    (
+        "company.thebrowser.Browser",
+        "get URL of active tab of front window",
+    ),
+    (
+        "com.vivaldi.Vivaldi",
+        "get URL of active tab of front window",
+    ),
+];
+
+/// Safari・Chrome系ブラウザで表示中のページのURL（ブラウザ以外では何も返さない）
+pub struct BrowserUrlProvider;
+
+impl MetadataProvider for BrowserUrlProvider {
+    fn name(&self) -> &str {
+        KEY_BROWSER_URL
+    }
+
+    fn collect(&self, collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+        let Some(bundle_id) = collected.get(KEY_BUNDLE_ID) else {
+            return Ok(MetadataValues::new());
+        };
+        let Some(&(_, script)) = BROWSER_URL_SCRIPTS.iter().find(|(id, _)| id == bundle_id) else {
+            return Ok(MetadataValues::new());
+        };
+
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                r#"tell application id "{}" to {}"#,
+                bundle_id, script
+            ))
+            .output()?;
+        if !output.status.success() {
+            // ウィンドウが開いていない場合など
+            return Ok(MetadataValues::new());
+        }
+        let url = String::from_utf8(output.stdout)?.trim().to_string();
+        Ok((!url.is_empty() && url != "missing value")
+            .then(|| (KEY_BROWSER_URL.to_string(), url))
+            .into_iter()
+            .collect())
+    }
+}
+
+/// 作業ディレクトリのGitリポジトリ名とブランチ名（リポジトリの外では何も返さない）
+pub struct GitProvider;
+
+impl MetadataProvider for GitProvider {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn collect(&self, collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+        let Some(directory) = collected.get(KEY_WORKING_DIRECTORY) else {
+            return Ok(MetadataValues::new());
+        };
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(directory)
+            .args(["rev-parse", "--show-toplevel", "--abbrev-ref", "HEAD"])
+            .output()?;
+        if !output.status.success() {
+            return Ok(MetadataValues::new());
+        }
+        Ok(parse_git_output(&String::from_utf8(output.stdout)?))
+    }
+}
+
+/// `git rev-parse --show-toplevel --abbrev-ref HEAD` の出力からリポジトリ名とブランチ名を取り出す
+fn parse_git_output(output: &str) -> MetadataValues {
+    let mut lines = output.lines().map(str::trim);
+    let mut values = MetadataValues::new();
+    if let Some(name) = lines
+        .next()
+        .and_then(|toplevel| Path::new(toplevel).file_name())
+    {
+        values.insert(
+            KEY_GIT_REPOSITORY.to_string(),
+            name.to_string_lossy().into_owned(),
+        );
+    }
+    if let Some(branch) = lines.next().filter(|branch| !branch.is_empty()) {
+        values.insert(KEY_GIT_BRANCH.to_string(), branch.to_string());
+    }
+    values
+}
+
+/// 設定の `[metadata_commands]` の外部コマンド
+///
+/// 標準出力のJSONオブジェクト、または `key=value` の行をメタデータとして取り込む
+pub struct CommandProvider {
+    name: String,
+    hook: Hook,
+}
+
+impl CommandProvider {
+    /// 新しいCommandProviderを作成
+    pub fn new(name: &str, command: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            hook: Hook::new(command),
+        }
+    }
+}
+
+impl MetadataProvider for CommandProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn collect(&self, _collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+        parse_command_output(&self.hook.output()?)
+    }
+}
+
+/// 外部コマンドの出力（JSONオブジェクトまたは `key=value` の行）を解釈する
+///
+/// JSONの文字列以外の値は文字列に変換し、`null` は取り込まない
+fn parse_command_output(output: &str) -> Result<MetadataValues, MetadataError> {
+    let output = output.trim();
+    if output.starts_with('{') {
+        let object: serde_json::Map<String, Value> = serde_json::from_str(output)
+            .map_err(|e| MetadataError::InvalidOutput(e.to_string()))?;
+        return Ok(object
+            .into_iter()
+            .filter_map(|(key, value)| match value {
+                Value::Null => None,
+                Value::String(text) => Some((key, text)),
+                value => Some((key, value.to_string())),
+            })
+            .collect());
+    }
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定の値を返す（またはそれまでの値を見て返す）テスト用のプロバイダ
+    struct FixedProvider(&'static str, Vec<(&'static str, &'static str)>);
+
+    impl MetadataProvider for FixedProvider {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn collect(&self, collected: &MetadataValues) -> Result<MetadataValues, MetadataError> {
+            if self.1.is_empty() {
+                return Err(MetadataError::InvalidOutput("empty".to_string()));
+            }
+            let mut values: MetadataValues = self
+                .1
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            if let Some(app) = collected.get(KEY_ACTIVE_APP) {
+                values.insert("seen_app".to_string(), app.clone());
+            }
+            Ok(values)
+        }
+    }
+
+    #[test]
+    fn test_collect_in_order() {
+        let collector = MetadataCollector::new(vec![
+            Box::new(FixedProvider("app", vec![(KEY_ACTIVE_APP, "Terminal")])),
+            Box::new(FixedProvider("broken", vec![])),
+            // 先に集まったキーは上書きしない
+            Box::new(FixedProvider(
+                "custom",
+                vec![(KEY_ACTIVE_APP, "Other"), ("ticket", "ABC-1")],
+            )),
+        ]);
+        let collected = collector.collect();
+        assert_eq!(collected.values[KEY_ACTIVE_APP], "Terminal");
+        assert_eq!(collected.values["ticket"], "ABC-1");
+        assert_eq!(collected.values["seen_app"], "Terminal");
+        assert_eq!(collected.failures.len(), 1);
+        assert_eq!(collected.failures[0].0, "broken");
+    }
+
+    #[test]
+    fn test_parse_command_output() {
+        let values =
+            parse_command_output(r#"{"ticket": "ABC-1", "points": 3, "note": null}"#).unwrap();
+        assert_eq!(
+            values,
+            MetadataValues::from([
+                ("points".to_string(), "3".to_string()),
+                ("ticket".to_string(), "ABC-1".to_string()),
+            ])
+        );
+
+        let values = parse_command_output("ticket = ABC-1\nno separator\n=empty key\n").unwrap();
+        assert_eq!(
+            values,
+            MetadataValues::from([("ticket".to_string(), "ABC-1".to_string())])
+        );
+        assert!(parse_command_output("{broken").is_err());
+    }
+
+    #[test]
+    fn test_parse_git_output() {
+        assert_eq!(
+            parse_git_output("/Users/me/src/habit-tracker\nfeature/hooks\n"),
+            MetadataValues::from([
+                (KEY_GIT_BRANCH.to_string(), "feature/hooks".to_string()),
+                (KEY_GIT_REPOSITORY.to_string(), "habit-tracker".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let collector = MetadataCollector::from_config(
+            &[
+                MetadataProviderKind::ActiveApp,
+                MetadataProviderKind::Git,
+                MetadataProviderKind::Git,
+            ],
+            &BTreeMap::from([("jira".to_string(), "echo ticket=ABC-1".to_string())]),
+        );
+        let names: Vec<&str> = collector.providers.iter().map(|p| p.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                KEY_ACTIVE_APP,
+                KEY_WINDOW_TITLE,
+                KEY_WORKING_DIRECTORY,
+                "git",
+                "jira"
+            ]
+        );
+    }
+}
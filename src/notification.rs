@@ -0,0 +1,31 @@
+//! 通知モジュール - macOSの通知センターへの通知
+
+use std::io;
+use std::process::Command;
+
+/// 通知のタイトル
+pub const NOTIFICATION_TITLE: &str = "habit-tracker";
+
+/// 通知センターに通知を表示
+///
+/// 文字列はスクリプトに埋め込まず引数で渡すため、エスケープは不要
+pub fn notify(title: &str, message: &str) -> io::Result<()> {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg("on run argv")
+        .arg("-e")
+        .arg("display notification (item 2 of argv) with title (item 1 of argv)")
+        .arg("-e")
+        .arg("end run")
+        .arg(title)
+        .arg(message)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "osascript failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
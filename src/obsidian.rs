@@ -0,0 +1,248 @@
+//! Obsidian連携モジュール - デイリーノートへの作業サマリー追記
+
+use crate::config::ObsidianConfig;
+use crate::error::ObsidianError;
+use crate::report::{format_duration, AppSummary, Report};
+use chrono::NaiveDate;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+/// サマリーブロックの開始マーカー
+const BLOCK_START: &str = "<!-- habit-tracker:start -->";
+
+/// サマリーブロックの終了マーカー
+const BLOCK_END: &str = "<!-- habit-tracker:end -->";
+
+/// デイリーノートへの書き込み
+pub struct DailyNote<'a> {
+    config: &'a ObsidianConfig,
+}
+
+impl<'a> DailyNote<'a> {
+    /// 新しいDailyNoteを作成
+    pub fn new(config: &'a ObsidianConfig) -> Self {
+        Self { config }
+    }
+
+    /// 指定日のデイリーノートのパス
+    pub fn note_path(&self, date: &str) -> Result<PathBuf, ObsidianError> {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| ObsidianError::InvalidDate(date.to_string()))?;
+
+        // 不正な書式指定子はフォーマット時にエラーになる
+        let mut relative = String::new();
+        write!(relative, "{}", day.format(&self.config.daily_note))
+            .map_err(|_| ObsidianError::InvalidNotePath(self.config.daily_note.clone()))?;
+        Ok(self.config.vault_path.join(relative))
+    }
+
+    /// 指定日のサマリーをデイリーノートに書き込む
+    ///
+    /// ノートやセクションがなければ作成する。サマリーはマーカーで囲んだブロックとして
+    /// 書き込み、同じ日に再実行した場合はブロックを置き換える
+    pub fn write_summary(&self, report: &Report, date: &str) -> Result<PathBuf, ObsidianError> {
+        let summaries = report.time_by_app(date)?;
+        let summary = render_summary(&self.config.template, date, &summaries);
+
+        let path = self.note_path(date)?;
+        let note = if path.exists() {
+            fs::read_to_string(&path)?
+        } else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            String::new()
+        };
+
+        fs::write(&path, upsert_block(&note, &self.config.section, &summary))?;
+        info!("デイリーノートに書き込みました: {}", path.display());
+
+        Ok(path)
+    }
+}
+
+/// テンプレートからサマリーMarkdownを生成
+///
+/// `{date}` は対象日、`{total}` は合計時間、`{apps}` はアプリ別時間の箇条書きに置換する
+pub fn render_summary(template: &str, date: &str, summaries: &[AppSummary]) -> String {
+    let total: u64 = summaries.iter().map(|s| s.duration_seconds).sum();
+    let apps = summaries
+        .iter()
+        .map(|s| format!("- {}: {}", s.app_name, format_duration(s.duration_seconds)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    template
+        .replace("{date}", date)
+        .replace("{total}", &format_duration(total))
+        .replace("{apps}", &apps)
+}
+
+/// ノート本文の指定セクションにサマリーブロックを挿入（既存なら置換）
+fn upsert_block(note: &str, section: &str, summary: &str) -> String {
+    let block = format!("{}\n{}\n{}", BLOCK_START, summary.trim_end(), BLOCK_END);
+
+    // 既存ブロックの置換
+    if let (Some(start), Some(end)) = (note.find(BLOCK_START), note.find(BLOCK_END)) {
+        if start < end {
+            return format!(
+                "{}{}{}",
+                &note[..start],
+                block,
+                &note[end + BLOCK_END.len()..]
+            );
+        }
+    }
+
+    let mut lines: Vec<&str> = note.lines().collect();
+    match lines.iter().position(|line| line.trim_end() == section) {
+        Some(heading) => {
+            // セクションの末尾（同じか上位レベルの次の見出しの手前）に挿入
+            let level = heading_level(section);
+            let mut end = lines[heading + 1..]
+                .iter()
+                .position(|line| heading_level(line).is_some_and(|l| Some(l) <= level))
+                .map(|offset| heading + 1 + offset)
+                .unwrap_or(lines.len());
+            while end > heading + 1 && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+
+            let mut inserted = vec![block.as_str()];
+            if end < lines.len() && !lines[end].trim().is_empty() {
+                inserted.push("");
+            }
+            lines.splice(end..end, inserted);
+            format!("{}\n", lines.join("\n"))
+        }
+        None => {
+            let mut result = note.trim_end().to_string();
+            if !result.is_empty() {
+                result.push_str("\n\n");
+            }
+            format!("{}{}\n{}\n", result, section, block)
+        }
+    }
+}
+
+/// Markdown見出しのレベル（見出しでなければ `None`）
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    (level > 0 && line[level..].starts_with(' ')).then_some(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{CaptureRecord, Database};
+    use tempfile::TempDir;
+
+    fn summaries() -> Vec<AppSummary> {
+        vec![
+            AppSummary {
+                app_name: "VS Code".to_string(),
+                duration_seconds: 5400,
+                capture_count: 90,
+            },
+            AppSummary {
+                app_name: "Slack".to_string(),
+                duration_seconds: 600,
+                capture_count: 10,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let summary = render_summary(ObsidianConfig::DEFAULT_TEMPLATE, "2025-01-10", &summaries());
+        assert_eq!(
+            summary,
+            "合計: 1時間40分\n\n- VS Code: 1時間30分\n- Slack: 10分"
+        );
+    }
+
+    #[test]
+    fn test_upsert_block_into_existing_section() {
+        let note = "# 2025-01-10\n\n## 作業ログ\nメモ\n\n## 日記\n今日は晴れ\n";
+        let result = upsert_block(note, "## 作業ログ", "合計: 1分");
+        assert_eq!(
+            result,
+            format!(
+                "# 2025-01-10\n\n## 作業ログ\nメモ\n{}\n合計: 1分\n{}\n\n## 日記\n今日は晴れ\n",
+                BLOCK_START, BLOCK_END
+            )
+        );
+
+        // 再実行時はブロックを置き換える
+        let rerun = upsert_block(&result, "## 作業ログ", "合計: 2分");
+        assert!(rerun.contains("合計: 2分"));
+        assert!(!rerun.contains("合計: 1分"));
+        assert_eq!(rerun.matches(BLOCK_START).count(), 1);
+    }
+
+    #[test]
+    fn test_upsert_block_appends_missing_section() {
+        let result = upsert_block("# 2025-01-10\n", "## 作業ログ", "合計: 1分");
+        assert_eq!(
+            result,
+            format!(
+                "# 2025-01-10\n\n## 作業ログ\n{}\n合計: 1分\n{}\n",
+                BLOCK_START, BLOCK_END
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_summary_creates_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2025-01-10T10:00:00".to_string(),
+            image_path: None,
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        let report = Report::new(db, 60);
+
+        let config = ObsidianConfig {
+            vault_path: temp_dir.path().join("vault"),
+            daily_note: "Daily/%Y-%m-%d.md".to_string(),
+            section: ObsidianConfig::DEFAULT_SECTION.to_string(),
+            template: ObsidianConfig::DEFAULT_TEMPLATE.to_string(),
+        };
+        let path = DailyNote::new(&config)
+            .write_summary(&report, "2025-01-10")
+            .unwrap();
+
+        assert_eq!(path, temp_dir.path().join("vault/Daily/2025-01-10.md"));
+        let note = fs::read_to_string(path).unwrap();
+        assert!(note.contains("- VS Code: 1分"));
+    }
+
+    #[test]
+    fn test_invalid_note_path_format() {
+        let config = ObsidianConfig {
+            vault_path: PathBuf::from("/tmp/vault"),
+            daily_note: "%Q.md".to_string(),
+            section: ObsidianConfig::DEFAULT_SECTION.to_string(),
+            template: ObsidianConfig::DEFAULT_TEMPLATE.to_string(),
+        };
+        assert!(matches!(
+            DailyNote::new(&config).note_path("2025-01-10"),
+            Err(ObsidianError::InvalidNotePath(_))
+        ));
+    }
+}
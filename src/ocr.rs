@@ -1,18 +1,148 @@
-//! OCRモジュール - Apple Vision APIを使用したテキスト認識
+//! OCRモジュール - Apple Vision API / Tesseractを使用したテキスト認識
 
 use crate::error::OcrError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::fs;
+use std::io;
 use std::path::Path;
 use std::process::Command;
 
-/// Apple Vision APIを使用してOCRを実行
-pub fn recognize_text(image_path: &Path) -> Result<String, OcrError> {
-    if !image_path.exists() {
-        return Err(OcrError::ImageNotFound(
+/// 認識されたテキスト1行分の観測結果
+///
+/// 座標はVisionの正規化座標（0.0〜1.0、原点は画像の左下）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrObservation {
+    pub text: String,
+    pub confidence: f32,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// OCR結果
+#[derive(Debug, Clone, Default)]
+pub struct OcrResult {
+    /// 改行区切りの全文
+    pub text: String,
+    /// 行ごとの信頼度と位置
+    pub observations: Vec<OcrObservation>,
+}
+
+impl OcrResult {
+    /// 観測結果をJSON文字列に変換（ocr_detailsカラム用）
+    pub fn details_json(&self) -> String {
+        serde_json::to_string(&self.observations).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// 保存済みのテキストと詳細JSONから復元（詳細が読めなければ観測結果は空）
+    pub fn from_cached(text: String, details_json: Option<&str>) -> Self {
+        let observations = details_json
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        OcrResult { text, observations }
+    }
+}
+
+/// 画像ファイルのSHA-256（16進文字列）
+///
+/// 同じ画面のキャプチャは同じハッシュになるため、OCR結果の流用に使う
+pub fn image_hash(image_path: &Path) -> io::Result<String> {
+    let digest = Sha256::digest(fs::read(image_path)?);
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest.iter() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    Ok(hex)
+}
+
+/// Tesseractの認識言語
+const TESSERACT_LANGUAGES: &str = "jpn+eng";
+
+/// OCRバックエンドの種類（config: `ocr_backend`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OcrBackendKind {
+    /// Apple Vision（macOS）
+    #[default]
+    Vision,
+    /// tesseractコマンド（Linux/CIなど）
+    Tesseract,
+    /// OCRを行わない
+    None,
+}
+
+impl OcrBackendKind {
+    /// バックエンドを生成（`None` ならOCRを行わない）
+    pub fn create(self) -> Option<Box<dyn OcrBackend>> {
+        match self {
+            OcrBackendKind::Vision => Some(Box::new(VisionBackend)),
+            OcrBackendKind::Tesseract => Some(Box::new(TesseractBackend)),
+            OcrBackendKind::None => None,
+        }
+    }
+}
+
+/// OCRバックエンド
+///
+/// 座標はバックエンドによらずVisionの正規化座標（原点は左下）で返す
+pub trait OcrBackend: Send + Sync {
+    /// 画像からテキストを認識し、信頼度と位置も取得
+    fn recognize(&self, image_path: &Path) -> Result<OcrResult, OcrError>;
+}
+
+/// Apple Vision APIによるOCR
+pub struct VisionBackend;
+
+/// tesseractコマンドによるOCR
+pub struct TesseractBackend;
+
+/// 画像ファイルの存在を確認
+fn ensure_exists(image_path: &Path) -> Result<(), OcrError> {
+    if image_path.exists() {
+        Ok(())
+    } else {
+        Err(OcrError::ImageNotFound(
             image_path.to_string_lossy().to_string(),
-        ));
+        ))
     }
+}
+
+impl OcrBackend for VisionBackend {
+    fn recognize(&self, image_path: &Path) -> Result<OcrResult, OcrError> {
+        ensure_exists(image_path)?;
+        recognize_with_vision(image_path)
+    }
+}
 
-    // osascript経由でSwiftのVision APIを呼び出す
+impl OcrBackend for TesseractBackend {
+    fn recognize(&self, image_path: &Path) -> Result<OcrResult, OcrError> {
+        ensure_exists(image_path)?;
+
+        let output = Command::new("tesseract")
+            .arg(image_path)
+            .arg("stdout")
+            .arg("-l")
+            .arg(TESSERACT_LANGUAGES)
+            .arg("tsv")
+            .output()
+            .map_err(|e| OcrError::ExecutionFailed(format!("tesseract: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OcrError::ExecutionFailed(stderr.to_string()));
+        }
+
+        Ok(parse_tesseract_tsv(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+}
+
+/// osascript経由でApple Vision APIを呼び出す
+fn recognize_with_vision(image_path: &Path) -> Result<OcrResult, OcrError> {
     let script = format!(
         r#"
 use framework "Vision"
@@ -45,7 +175,10 @@ set observations to textRequest's results()
 repeat with observation in observations
     set topCandidate to (observation's topCandidates:1)'s firstObject()
     if topCandidate is not missing value then
-        set end of recognizedTexts to (topCandidate's |string|() as text)
+        set bbox to observation's boundingBox()
+        set {{bx, by}} to item 1 of bbox
+        set {{bw, bh}} to item 2 of bbox
+        set end of recognizedTexts to ((topCandidate's confidence()) as text) & tab & (bx as text) & tab & (by as text) & tab & (bw as text) & tab & (bh as text) & tab & (topCandidate's |string|() as text)
     end if
 end repeat
 
@@ -68,13 +201,134 @@ return recognizedTexts as text
         return Err(OcrError::ExecutionFailed(stderr.to_string()));
     }
 
-    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
-    if text.starts_with("ERROR:") {
-        return Err(OcrError::ExecutionFailed(text));
+    if stdout.starts_with("ERROR:") {
+        return Err(OcrError::ExecutionFailed(stdout));
     }
 
-    Ok(text)
+    Ok(parse_observations(&stdout))
+}
+
+/// スクリプト出力（`信頼度\tx\ty\t幅\t高さ\tテキスト` の行）を解析
+fn parse_observations(output: &str) -> OcrResult {
+    let observations: Vec<OcrObservation> = output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(6, '\t').collect();
+            if fields.len() != 6 {
+                return None;
+            }
+            // ロケールによっては小数点がカンマになる
+            let num = |s: &str| s.trim().replace(',', ".").parse::<f64>().ok();
+            Some(OcrObservation {
+                text: fields[5].to_string(),
+                confidence: num(fields[0])? as f32,
+                x: num(fields[1])?,
+                y: num(fields[2])?,
+                width: num(fields[3])?,
+                height: num(fields[4])?,
+            })
+        })
+        .collect();
+
+    let text = observations
+        .iter()
+        .map(|o| o.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    OcrResult { text, observations }
+}
+
+/// tesseractのTSV出力を行単位の観測結果に変換
+///
+/// 単語（level 5）を行ごとにまとめ、信頼度は単語の平均、座標はページ（level 1）の
+/// サイズでVisionと同じ正規化座標（原点は左下）に変換する
+fn parse_tesseract_tsv(output: &str) -> OcrResult {
+    struct Line {
+        key: (u32, u32, u32, u32),
+        words: Vec<String>,
+        confidence_sum: f64,
+        left: f64,
+        top: f64,
+        right: f64,
+        bottom: f64,
+    }
+
+    let mut page = (1.0, 1.0);
+    let mut lines: Vec<Line> = Vec::new();
+
+    for row in output.lines().skip(1) {
+        let fields: Vec<&str> = row.splitn(12, '\t').collect();
+        if fields.len() < 11 {
+            continue;
+        }
+        let num = |i: usize| fields[i].trim().parse::<f64>().ok();
+        let id = |i: usize| fields[i].trim().parse::<u32>().ok();
+        let (Some(level), Some(left), Some(top), Some(width), Some(height)) =
+            (id(0), num(6), num(7), num(8), num(9))
+        else {
+            continue;
+        };
+
+        if level == 1 {
+            page = (width.max(1.0), height.max(1.0));
+            continue;
+        }
+
+        let text = fields.get(11).map(|t| t.trim()).unwrap_or("");
+        let confidence = num(10).unwrap_or(-1.0);
+        if level != 5 || text.is_empty() || confidence < 0.0 {
+            continue;
+        }
+        let (Some(page_num), Some(block), Some(par), Some(line_num)) = (id(1), id(2), id(3), id(4))
+        else {
+            continue;
+        };
+        let key = (page_num, block, par, line_num);
+
+        match lines.last_mut() {
+            Some(line) if line.key == key => {
+                line.words.push(text.to_string());
+                line.confidence_sum += confidence;
+                line.left = line.left.min(left);
+                line.top = line.top.min(top);
+                line.right = line.right.max(left + width);
+                line.bottom = line.bottom.max(top + height);
+            }
+            _ => lines.push(Line {
+                key,
+                words: vec![text.to_string()],
+                confidence_sum: confidence,
+                left,
+                top,
+                right: left + width,
+                bottom: top + height,
+            }),
+        }
+    }
+
+    let (page_width, page_height) = page;
+    let observations: Vec<OcrObservation> = lines
+        .into_iter()
+        .map(|line| OcrObservation {
+            confidence: (line.confidence_sum / line.words.len() as f64 / 100.0) as f32,
+            text: line.words.join(" "),
+            x: line.left / page_width,
+            y: 1.0 - line.bottom / page_height,
+            width: (line.right - line.left) / page_width,
+            height: (line.bottom - line.top) / page_height,
+        })
+        .collect();
+
+    let text = observations
+        .iter()
+        .map(|o| o.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    OcrResult { text, observations }
 }
 
 #[cfg(test)]
@@ -84,8 +338,78 @@ mod tests {
 
     #[test]
     fn test_recognize_text_missing_file() {
-        let result = recognize_text(&PathBuf::from("/nonexistent/image.jpg"));
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), OcrError::ImageNotFound(_)));
+        for backend in [OcrBackendKind::Vision, OcrBackendKind::Tesseract] {
+            let result = backend
+                .create()
+                .unwrap()
+                .recognize(&PathBuf::from("/nonexistent/image.jpg"));
+            assert!(result.is_err());
+            assert!(matches!(result.unwrap_err(), OcrError::ImageNotFound(_)));
+        }
+        assert!(OcrBackendKind::None.create().is_none());
+    }
+
+    #[test]
+    fn test_parse_tesseract_tsv() {
+        let output = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+            1\t1\t0\t0\t0\t0\t0\t0\t1000\t500\t-1\t\n\
+            4\t1\t1\t1\t1\t0\t100\t50\t300\t20\t-1\t\n\
+            5\t1\t1\t1\t1\t1\t100\t50\t100\t20\t90.5\tfn\n\
+            5\t1\t1\t1\t1\t2\t220\t48\t180\t25\t80.5\tmain()\n\
+            5\t1\t1\t1\t2\t1\t100\t100\t50\t20\t-1\t \n\
+            5\t1\t1\t1\t3\t1\t100\t200\t50\t20\t70\t設計\n";
+        let result = parse_tesseract_tsv(output);
+
+        assert_eq!(result.text, "fn main()\n設計");
+        let first = &result.observations[0];
+        assert!((first.confidence - 0.855).abs() < 1e-6);
+        assert_eq!(first.x, 0.1);
+        assert_eq!(first.width, 0.3);
+        // 行の範囲は単語の外接矩形（上端48・下端73）
+        assert!((first.y - (1.0 - 73.0 / 500.0)).abs() < 1e-9);
+        assert!((first.height - 25.0 / 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_observations() {
+        let output = "0.98\t0.1\t0.9\t0.3\t0.02\tfn main() {\n0,5\t0\t0\t1\t1\tA\tB\nbroken line";
+        let result = parse_observations(output);
+
+        assert_eq!(result.observations.len(), 2);
+        assert_eq!(result.text, "fn main() {\nA\tB");
+        assert!((result.observations[0].confidence - 0.98).abs() < f32::EPSILON);
+        assert_eq!(result.observations[0].y, 0.9);
+        assert_eq!(result.observations[1].confidence, 0.5);
+    }
+
+    #[test]
+    fn test_image_hash() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.jpg");
+        fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            image_hash(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(image_hash(&temp_dir.path().join("missing.jpg")).is_err());
+    }
+
+    #[test]
+    fn test_from_cached_restores_observations() {
+        let original = parse_observations("1\t0.1\t0.2\t0.3\t0.4\tHello");
+        let cached = OcrResult::from_cached("Hello".to_string(), Some(&original.details_json()));
+        assert_eq!(cached.observations, original.observations);
+
+        let text_only = OcrResult::from_cached("Hello".to_string(), None);
+        assert!(text_only.observations.is_empty());
+    }
+
+    #[test]
+    fn test_details_json() {
+        let result = parse_observations("1\t0.1\t0.2\t0.3\t0.4\tHello");
+        let json: serde_json::Value = serde_json::from_str(&result.details_json()).unwrap();
+        assert_eq!(json[0]["text"], "Hello");
+        assert_eq!(json[0]["width"], 0.3);
     }
 }
@@ -0,0 +1,538 @@
+//! OCRバッチ処理モジュール - 未処理キャプチャの並列OCR
+
+use crate::database::{format_timestamp, CaptureRecord, Database, OcrBatchProgress};
+use crate::error::{DatabaseError, OcrError};
+use crate::ocr::{self, OcrResult};
+use crate::ocr_normalize::OcrNormalizer;
+use chrono::Local;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+/// 1件分のOCR結果
+pub struct OcrOutcome {
+    pub id: i64,
+    pub image_path: PathBuf,
+    /// 画像のハッシュ（読めなかった場合は `None`）
+    pub image_hash: Option<String>,
+    /// 同じ画像の既存結果を流用したか
+    pub cached: bool,
+    pub result: Result<OcrResult, OcrError>,
+}
+
+/// バッチ処理の集計結果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    /// 成功のうち、既存のOCR結果を流用した件数
+    pub cached: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+}
+
+/// OCRバッチ処理
+///
+/// OCRはワーカースレッドで並列に実行し、DB書き込みは呼び出し元スレッドに集約する。
+/// 結果は1件ごとに即座に書き込むため、中断しても処理済みの分は失われず、
+/// 次回のバッチでは `ocr_text IS NULL` の残りだけが対象になる。
+/// 失敗はocr_jobsに記録し、指数バックオフで次回以降のバッチで再試行する。
+/// 同じハッシュの画像のOCR結果があればVisionを呼ばずに流用する
+pub struct OcrBatch<'a> {
+    db: &'a Database,
+    jobs: usize,
+    cancel: &'a AtomicBool,
+    save_details: bool,
+    /// DBに保存済みのOCR結果を流用せずに認識し直すか
+    redo: bool,
+}
+
+impl<'a> OcrBatch<'a> {
+    /// 新しいOcrBatchを作成
+    ///
+    /// `jobs` は並列数（0は1として扱う）。`cancel` が立つと新しい画像の処理を止め、
+    /// 処理中の画像の結果を書き込んでから終了する
+    pub fn new(db: &'a Database, jobs: usize, cancel: &'a AtomicBool) -> Self {
+        Self {
+            db,
+            jobs: jobs.max(1),
+            cancel,
+            save_details: false,
+            redo: false,
+        }
+    }
+
+    /// 信頼度・座標（ocr_details）も保存するか設定
+    pub fn with_details(mut self, save_details: bool) -> Self {
+        self.save_details = save_details;
+        self
+    }
+
+    /// 保存済みのOCR結果を流用せずに認識し直すか設定（`tracker ocr --redo`）
+    ///
+    /// 再処理するキャプチャ自身の結果も同じハッシュで見つかるため、流用すると上書きされない。
+    /// 同じバッチ内で新しく認識した結果は流用する
+    pub fn with_redo(mut self, redo: bool) -> Self {
+        self.redo = redo;
+        self
+    }
+
+    /// キャプチャ群をOCR処理
+    ///
+    /// `on_outcome` は1件処理するごとに呼び出し元スレッドで呼ばれる
+    pub fn run<F, C>(
+        &self,
+        captures: Vec<CaptureRecord>,
+        recognize: F,
+        mut on_outcome: C,
+    ) -> Result<BatchSummary, DatabaseError>
+    where
+        F: Fn(&Path) -> Result<OcrResult, OcrError> + Sync,
+        C: FnMut(&OcrOutcome),
+    {
+        // ハッシュの計算とDB上の既存結果の検索は呼び出し元スレッドで先に済ませる
+        let mut known: HashMap<String, OcrResult> = HashMap::new();
+        let mut apps: HashMap<i64, String> = HashMap::new();
+        let mut jobs = VecDeque::new();
+        for capture in captures {
+            let (Some(id), Some(image_path)) = (capture.id, capture.image_path) else {
+                continue;
+            };
+            apps.insert(id, capture.active_app);
+            let image_path = PathBuf::from(image_path);
+            let image_hash = ocr::image_hash(&image_path).ok();
+            if let (Some(ref hash), false) = (&image_hash, self.redo) {
+                if !known.contains_key(hash) {
+                    if let Some((text, details)) = self.db.find_ocr_by_image_hash(hash)? {
+                        known.insert(
+                            hash.clone(),
+                            OcrResult::from_cached(text, details.as_deref()),
+                        );
+                    }
+                }
+            }
+            jobs.push_back((id, image_path, image_hash));
+        }
+        let queue = Mutex::new(jobs);
+        let known = Mutex::new(known);
+        let (tx, rx) = mpsc::channel::<OcrOutcome>();
+        let recognize = &recognize;
+        let queue = &queue;
+        let known = &known;
+        let cancel = self.cancel;
+
+        thread::scope(|scope| {
+            for _ in 0..self.jobs {
+                let tx = tx.clone();
+                scope.spawn(move || loop {
+                    if cancel.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let job = queue.lock().expect("OCRキューのロック取得失敗").pop_front();
+                    let Some((id, image_path, image_hash)) = job else {
+                        break;
+                    };
+
+                    let cached = image_hash.as_ref().and_then(|hash| {
+                        known
+                            .lock()
+                            .expect("OCRキャッシュのロック取得失敗")
+                            .get(hash)
+                            .cloned()
+                    });
+                    let (result, cached) = match cached {
+                        Some(result) => (Ok(result), true),
+                        None => {
+                            let result = recognize(&image_path);
+                            if let (Ok(result), Some(hash)) = (&result, &image_hash) {
+                                known
+                                    .lock()
+                                    .expect("OCRキャッシュのロック取得失敗")
+                                    .insert(hash.clone(), result.clone());
+                            }
+                            (result, false)
+                        }
+                    };
+
+                    // 受信側が終了していれば（DBエラー時）ワーカーも終了
+                    if tx
+                        .send(OcrOutcome {
+                            id,
+                            image_path,
+                            image_hash,
+                            cached,
+                            result,
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                });
+            }
+            drop(tx);
+
+            let mut normalizer = OcrNormalizer::new(self.db.get_ocr_boilerplate()?);
+            let mut summary = BatchSummary::default();
+            for outcome in rx {
+                match &outcome.result {
+                    Ok(result) => {
+                        // 新しく認識したテキストだけを定型文の学習に使う
+                        let text = if outcome.cached {
+                            normalizer.normalize(&result.text)
+                        } else {
+                            let app = apps.get(&outcome.id).map_or("", String::as_str);
+                            let (text, learned) = normalizer.process(&result.text, app);
+                            self.db.add_ocr_boilerplate(&learned)?;
+                            text
+                        };
+
+                        // テキストと詳細は同時に更新する
+                        self.db.with_transaction(|db| {
+                            db.update_ocr_text(outcome.id, &text)?;
+                            if let Some(ref hash) = outcome.image_hash {
+                                db.update_image_hash(outcome.id, hash)?;
+                            }
+                            if self.save_details {
+                                db.update_ocr_details(outcome.id, &result.details_json())?;
+                            }
+                            db.record_ocr_success(outcome.id, &format_timestamp(&Local::now()))?;
+                            Ok::<_, DatabaseError>(())
+                        })?;
+                        summary.succeeded += 1;
+                        if outcome.cached {
+                            summary.cached += 1;
+                        }
+                    }
+                    Err(e) => {
+                        self.db
+                            .record_ocr_failure(outcome.id, &e.to_string(), &Local::now())?;
+                        summary.failed += 1;
+                    }
+                }
+                on_outcome(&outcome);
+            }
+            summary.cancelled = self.cancel.load(Ordering::SeqCst);
+
+            Ok(summary)
+        })
+    }
+
+    /// 未処理のキャプチャをすべて、IDの古い順に `chunk_size` 件ずつOCR処理
+    ///
+    /// 区切りごとに処理を終えたIDまでをDBに記録し、中断しても次回は続きから処理する。
+    /// 最後まで処理したら記録を消去する。再試行待ちになったキャプチャは通常のバッチで再試行する
+    pub fn run_all<F, C>(
+        &self,
+        chunk_size: i64,
+        recognize: F,
+        mut on_outcome: C,
+    ) -> Result<BatchSummary, DatabaseError>
+    where
+        F: Fn(&Path) -> Result<OcrResult, OcrError> + Sync,
+        C: FnMut(&OcrOutcome),
+    {
+        let mut progress = self
+            .db
+            .get_ocr_batch_progress()?
+            .unwrap_or_else(|| OcrBatchProgress {
+                last_capture_id: 0,
+                processed: 0,
+                started_at: format_timestamp(&Local::now()),
+            });
+        let mut summary = BatchSummary::default();
+        loop {
+            let captures = self
+                .db
+                .get_captures_without_ocr_after(progress.last_capture_id, chunk_size)?;
+            if captures.is_empty() {
+                self.db.clear_ocr_batch_progress()?;
+                break;
+            }
+
+            let ids: Vec<i64> = captures.iter().filter_map(|c| c.id).collect();
+            let mut done = HashSet::new();
+            let chunk = self.run(captures, &recognize, |outcome| {
+                done.insert(outcome.id);
+                on_outcome(outcome);
+            })?;
+            // 並列に処理するため、中断した場合は先頭から途切れずに処理できたIDまでを記録する
+            if let Some(&id) = ids.iter().take_while(|id| done.contains(*id)).last() {
+                progress.last_capture_id = id;
+            }
+            progress.processed += done.len() as u64;
+            self.db.save_ocr_batch_progress(&progress)?;
+
+            summary.succeeded += chunk.succeeded;
+            summary.cached += chunk.cached;
+            summary.failed += chunk.failed;
+            if chunk.cancelled {
+                summary.cancelled = true;
+                break;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_db(count: usize) -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        for i in 0..count {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: format!("2024-12-30T10:{:02}:00", i),
+                image_path: Some(format!("/path/{}.jpg", i)),
+                active_app: "VS Code".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        (db, temp_dir)
+    }
+
+    fn fake_recognize(path: &Path) -> Result<OcrResult, OcrError> {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        if name == "3" {
+            Err(OcrError::ExecutionFailed("broken".to_string()))
+        } else {
+            Ok(OcrResult {
+                text: format!("text-{}", name),
+                observations: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_parallel_batch_writes_results() {
+        let (db, _temp_dir) = create_test_db(10);
+        let cancel = AtomicBool::new(false);
+        let captures = db.get_captures_without_ocr(100).unwrap();
+
+        let mut seen = 0;
+        let summary = OcrBatch::new(&db, 4, &cancel)
+            .run(captures, fake_recognize, |_| seen += 1)
+            .unwrap();
+
+        assert_eq!(summary.succeeded, 9);
+        assert_eq!(summary.failed, 1);
+        assert!(!summary.cancelled);
+        assert_eq!(seen, 10);
+
+        // 失敗した1件は再試行待ちとして記録され、すぐには対象にならない
+        assert!(db.get_captures_without_ocr(100).unwrap().is_empty());
+        let failures = db.get_ocr_failures(100).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].image_path.as_deref(), Some("/path/3.jpg"));
+        assert_eq!(failures[0].attempts, 1);
+    }
+
+    #[test]
+    fn test_batch_with_details() {
+        let (db, _temp_dir) = create_test_db(1);
+        let cancel = AtomicBool::new(false);
+        let captures = db.get_captures_without_ocr(100).unwrap();
+
+        OcrBatch::new(&db, 1, &cancel)
+            .with_details(true)
+            .run(captures, fake_recognize, |_| {})
+            .unwrap();
+
+        assert_eq!(db.get_ocr_details(1).unwrap().as_deref(), Some("[]"));
+    }
+
+    #[test]
+    fn test_batch_reuses_results_for_identical_images() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let image = |name: &str, content: &str| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+
+        // 処理済みの画像と同じ内容の画像はDBの結果を流用する
+        let done = db
+            .insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: "2024-12-30T09:59:00".to_string(),
+                image_path: Some(image("done.jpg", "other")),
+                active_app: "VS Code".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: Some("previous".to_string()),
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        db.update_image_hash(
+            done,
+            &ocr::image_hash(&temp_dir.path().join("done.jpg")).unwrap(),
+        )
+        .unwrap();
+
+        for (i, content) in ["same", "same", "other"].into_iter().enumerate() {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: format!("2024-12-30T10:{:02}:00", i),
+                image_path: Some(image(&format!("{}.jpg", i), content)),
+                active_app: "VS Code".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let cancel = AtomicBool::new(false);
+        let captures = db.get_captures_without_ocr(100).unwrap();
+        let summary = OcrBatch::new(&db, 1, &cancel)
+            .run(
+                captures,
+                |path| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    fake_recognize(path)
+                },
+                |_| {},
+            )
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(summary.succeeded, 3);
+        assert_eq!(summary.cached, 2);
+        let texts: Vec<_> = db
+            .get_captures_between("2024-12-30T10:00:00", "2024-12-30T11:00:00")
+            .unwrap()
+            .into_iter()
+            .map(|c| c.ocr_text.unwrap())
+            .collect();
+        assert_eq!(texts[0], texts[1]);
+        assert_eq!(texts[2], "previous");
+    }
+
+    #[test]
+    fn test_redo_overwrites_existing_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let image = temp_dir.path().join("0.jpg");
+        std::fs::write(&image, "image").unwrap();
+        let id = db
+            .insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: "2024-12-30T10:00:00".to_string(),
+                image_path: Some(image.to_string_lossy().to_string()),
+                active_app: "VS Code".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: Some("old".to_string()),
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        db.update_image_hash(id, &ocr::image_hash(&image).unwrap())
+            .unwrap();
+
+        // 保存済みの自身の結果を流用せずに認識し直して上書きする
+        let cancel = AtomicBool::new(false);
+        let captures = db
+            .get_captures_for_ocr_redo(Some("2024-12-30"), None, -1)
+            .unwrap();
+        let summary = OcrBatch::new(&db, 1, &cancel)
+            .with_redo(true)
+            .run(captures, fake_recognize, |_| {})
+            .unwrap();
+
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.cached, 0);
+        let capture = db.get_capture(id).unwrap().unwrap();
+        assert_eq!(capture.ocr_text.as_deref(), Some("text-0"));
+    }
+
+    #[test]
+    fn test_run_all_in_chunks_and_resume() {
+        let (db, _temp_dir) = create_test_db(5);
+        let cancel = AtomicBool::new(false);
+
+        // 2件目を処理した時点で中断すると、次回は3件目から再開する
+        let mut seen = 0;
+        let summary = OcrBatch::new(&db, 1, &cancel)
+            .run_all(2, fake_recognize, |_| {
+                seen += 1;
+                if seen == 2 {
+                    cancel.store(true, Ordering::SeqCst);
+                }
+            })
+            .unwrap();
+        assert_eq!(summary.succeeded, 2);
+        assert!(summary.cancelled);
+        let progress = db.get_ocr_batch_progress().unwrap().unwrap();
+        assert_eq!(progress.last_capture_id, 2);
+        assert_eq!(progress.processed, 2);
+
+        cancel.store(false, Ordering::SeqCst);
+        let mut processed = Vec::new();
+        let summary = OcrBatch::new(&db, 1, &cancel)
+            .run_all(2, fake_recognize, |outcome| processed.push(outcome.id))
+            .unwrap();
+        assert_eq!(processed, vec![3, 4, 5]);
+        assert_eq!(summary.succeeded, 2);
+        assert_eq!(summary.failed, 1);
+        assert!(!summary.cancelled);
+        // 最後まで処理したら進捗は消える
+        assert_eq!(db.get_ocr_batch_progress().unwrap(), None);
+    }
+
+    #[test]
+    fn test_cancelled_batch_processes_nothing_new() {
+        let (db, _temp_dir) = create_test_db(5);
+        let cancel = AtomicBool::new(true);
+        let captures = db.get_captures_without_ocr(100).unwrap();
+
+        let summary = OcrBatch::new(&db, 2, &cancel)
+            .run(captures, fake_recognize, |_| {})
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            BatchSummary {
+                succeeded: 0,
+                cached: 0,
+                failed: 0,
+                cancelled: true
+            }
+        );
+        assert_eq!(db.get_captures_without_ocr(100).unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,169 @@
+//! OCRテキスト正規化モジュール - 保存前の空白・重複行・定型UI文字列の除去
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// 定型文の学習に使う直近テキストの件数
+const LEARN_WINDOW: usize = 200;
+
+/// 学習を行う間隔（テキスト件数）
+const LEARN_EVERY: usize = 50;
+
+/// 定型文とみなす出現割合（直近テキストのうち）
+const BOILERPLATE_MIN_RATIO: f64 = 0.5;
+
+/// 定型文とみなすのに必要な、その行が現れたアプリの種類数
+///
+/// メニューバーやDockはどのアプリを使っていても写るが、エディタで開きっぱなしの
+/// ファイルの内容は特定のアプリでしか写らないため、これで区別する
+const BOILERPLATE_MIN_APPS: usize = 3;
+
+/// 定型文とみなす行の最大文字数
+const BOILERPLATE_MAX_CHARS: usize = 60;
+
+/// OCRテキストの正規化
+///
+/// 1. 行内の連続する空白を1つにまとめ、前後の空白と空行を除く
+/// 2. 同じテキスト内で重複する行を除く（最初の1行を残す）
+/// 3. 学習済みの定型文（メニューバー・Dockなど毎回同じUI文字列）の行を除く
+///
+/// 正規化は冪等なので、正規化済みのテキストに再度かけても変わらない
+pub struct OcrNormalizer {
+    /// 学習済みの定型文
+    boilerplate: HashSet<String>,
+    /// 直近の生テキスト（アプリ名と行の集合）
+    recent: VecDeque<(String, HashSet<String>)>,
+    /// 前回の学習からの観測件数
+    observed: usize,
+}
+
+impl OcrNormalizer {
+    /// 学習済みの定型文から作成
+    pub fn new(boilerplate: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            boilerplate: boilerplate.into_iter().collect(),
+            recent: VecDeque::with_capacity(LEARN_WINDOW),
+            observed: 0,
+        }
+    }
+
+    /// テキストを正規化
+    pub fn normalize(&self, text: &str) -> String {
+        let mut seen = HashSet::new();
+        normalized_lines(text)
+            .filter(|line| !self.boilerplate.contains(line))
+            .filter(|line| seen.insert(line.clone()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 新しく認識した生テキストを学習用に記録し、正規化したテキストを返す
+    ///
+    /// 一定件数ごとに定型文を学習し直す。新しく学習した行を返すので、
+    /// 呼び出し元で永続化する
+    pub fn process(&mut self, text: &str, app: &str) -> (String, Vec<String>) {
+        if self.recent.len() == LEARN_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent
+            .push_back((app.to_string(), normalized_lines(text).collect()));
+
+        self.observed += 1;
+        let learned = if self.observed >= LEARN_EVERY {
+            self.observed = 0;
+            self.learn()
+        } else {
+            Vec::new()
+        };
+
+        (self.normalize(text), learned)
+    }
+
+    /// 直近のテキストから定型文を学習し、新しく加わった行を返す
+    fn learn(&mut self) -> Vec<String> {
+        let mut stats: HashMap<&str, (usize, HashSet<&str>)> = HashMap::new();
+        for (app, lines) in &self.recent {
+            for line in lines {
+                let entry = stats.entry(line.as_str()).or_default();
+                entry.0 += 1;
+                entry.1.insert(app.as_str());
+            }
+        }
+
+        let min_count = (self.recent.len() as f64 * BOILERPLATE_MIN_RATIO).ceil() as usize;
+        let mut learned: Vec<String> = stats
+            .into_iter()
+            .filter(|(line, (count, apps))| {
+                *count >= min_count
+                    && apps.len() >= BOILERPLATE_MIN_APPS
+                    && line.chars().count() <= BOILERPLATE_MAX_CHARS
+                    && !self.boilerplate.contains(*line)
+            })
+            .map(|(line, _)| line.to_string())
+            .collect();
+        learned.sort();
+
+        self.boilerplate.extend(learned.iter().cloned());
+        learned
+    }
+}
+
+/// 空白を整えた空でない行
+fn normalized_lines(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_whitespace_and_duplicates() {
+        let normalizer = OcrNormalizer::new(Vec::new());
+        let text = "  fn   main() {\n\n\tlet x = 1;\nfn main() {\n   \n}";
+        let normalized = normalizer.normalize(text);
+
+        assert_eq!(normalized, "fn main() {\nlet x = 1;\n}");
+        // 冪等
+        assert_eq!(normalizer.normalize(&normalized), normalized);
+    }
+
+    #[test]
+    fn test_normalize_removes_boilerplate() {
+        let normalizer = OcrNormalizer::new(vec!["Finder File Edit View".to_string()]);
+        assert_eq!(
+            normalizer.normalize("Finder  File Edit View\n設計レビュー"),
+            "設計レビュー"
+        );
+    }
+
+    #[test]
+    fn test_learns_lines_shared_across_apps() {
+        let mut normalizer = OcrNormalizer::new(Vec::new());
+
+        let mut learned = Vec::new();
+        for i in 0..LEARN_EVERY {
+            // 7割がVS Code。メニューバーはどのアプリでも写るが、
+            // エディタの内容は頻出してもVS Codeでしか写らない
+            let app = match i % 10 {
+                0..=6 => "VS Code",
+                7 | 8 => "Slack",
+                _ => "Safari",
+            };
+            let text = format!("Wi-Fi 100% 10:00\nfn main() {{}}\n{}枚目", i);
+            let text = if app == "VS Code" {
+                text
+            } else {
+                text.replace("fn main() {}", "")
+            };
+            learned.extend(normalizer.process(&text, app).1);
+        }
+
+        assert_eq!(learned, vec!["Wi-Fi 100% 10:00".to_string()]);
+        assert_eq!(
+            normalizer.normalize("Wi-Fi 100% 10:00\nfn main() {}"),
+            "fn main() {}"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! OCRワーカーモジュール - 記録済みのキャプチャの画像を認識し、結果をDBに書き込む
+
+use crate::capture::report_error;
+use crate::database::{CaptureRecord, Database};
+use crate::db_writer::DatabaseWriter;
+use crate::error::{DatabaseError, OcrError};
+use crate::error_reporter::ErrorReporter;
+use crate::hooks::Hook;
+use crate::ocr::OcrBackend;
+use crate::ocr_normalize::OcrNormalizer;
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc::Receiver;
+use tokio::task;
+use tracing::{error, info, info_span};
+
+/// OCRワーカーの待ち行列の長さ（溢れた分はOCRせずに記録し、`tracker ocr` で後から処理する）
+pub const OCR_QUEUE_CAPACITY: usize = 16;
+
+/// 記録済みのキャプチャ1件分のOCRの依頼
+///
+/// バックエンドと詳細の保存は依頼ごとに渡すため、設定を再読み込みすれば次の依頼から反映される
+pub struct OcrRequest {
+    pub capture_id: i64,
+    pub image_path: PathBuf,
+    pub timestamp: DateTime<Local>,
+    /// 記録したキャプチャ（認識したテキストを入れてキャプチャ後フックに渡す）
+    pub record: CaptureRecord,
+    pub backend: Arc<dyn OcrBackend>,
+    /// 信頼度・座標をocr_detailsに保存するか
+    pub save_details: bool,
+    /// 書き込み後に実行するキャプチャ後フック
+    pub post_capture_hook: Option<String>,
+}
+
+/// OCRの結果
+#[derive(Debug)]
+pub struct OcrOutcome {
+    /// 正規化したテキスト（空・失敗なら `None`）
+    pub text: Option<String>,
+    /// 認識にかかった時間（ミリ秒）
+    pub ocr_ms: u64,
+    /// 認識に失敗した場合のエラー（失敗はocr_jobsに記録済み）
+    pub error: Option<OcrError>,
+}
+
+/// OCRの実行と結果の書き込み
+///
+/// 新しく認識したテキストは正規化し、定型文の学習にも使う
+pub struct OcrWorker {
+    normalizer: OcrNormalizer,
+}
+
+impl OcrWorker {
+    /// 学習済みの定型文を読み込んでOcrWorkerを作成
+    pub fn new(db: &Database) -> Result<Self, DatabaseError> {
+        Ok(Self {
+            normalizer: OcrNormalizer::new(db.get_ocr_boilerplate()?),
+        })
+    }
+
+    /// 画像を認識し、テキスト・詳細（失敗なら再試行の予約）をDB書き込みスレッド経由で書き込む
+    pub fn process(
+        &mut self,
+        writer: &DatabaseWriter,
+        request: &OcrRequest,
+    ) -> Result<OcrOutcome, DatabaseError> {
+        let started = Instant::now();
+        let recognize =
+            |path: &Path| info_span!("ocr").in_scope(|| request.backend.recognize(path));
+        let mut result = recognize(&request.image_path);
+        // 遅延保存の画像は認識中に保存先へ移されることがあるため、移っていれば記録したパスで読み直す
+        if result.is_err() && !request.image_path.exists() {
+            if let Some(ref saved) = request.record.image_path {
+                result = recognize(Path::new(saved));
+            }
+        }
+        let ocr_ms = started.elapsed().as_millis() as u64;
+
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                // 失敗したOCRはバッチ処理で再試行する
+                let (capture_id, error, timestamp) =
+                    (request.capture_id, e.to_string(), request.timestamp);
+                writer.execute(move |db| db.record_ocr_failure(capture_id, &error, &timestamp))?;
+                return Ok(OcrOutcome {
+                    text: None,
+                    ocr_ms,
+                    error: Some(e),
+                });
+            }
+        };
+
+        let (text, learned) = self
+            .normalizer
+            .process(&result.text, &request.record.active_app);
+        if !learned.is_empty() {
+            info!("OCRの定型文を{}件学習しました", learned.len());
+        }
+        let capture_id = request.capture_id;
+        let saved_text = text.clone();
+        let details = request.save_details.then(|| result.details_json());
+        writer.execute(move |db| {
+            if !learned.is_empty() {
+                db.add_ocr_boilerplate(&learned)?;
+            }
+            if !saved_text.is_empty() {
+                db.update_ocr_text(capture_id, &saved_text)?;
+            }
+            if let Some(ref details) = details {
+                db.update_ocr_details(capture_id, details)?;
+            }
+            Ok(())
+        })?;
+        Ok(OcrOutcome {
+            text: Some(text).filter(|text| !text.is_empty()),
+            ocr_ms,
+            error: None,
+        })
+    }
+
+    /// 依頼を受け取るたびに処理するタスク（送信側がすべて閉じたら、残りの依頼を処理してから終了する）
+    ///
+    /// 依頼は非同期に待ち、外部コマンドを待つ認識とフックの実行だけをブロッキング用のスレッドで行う
+    pub async fn run(
+        mut self,
+        writer: DatabaseWriter,
+        mut receiver: Receiver<OcrRequest>,
+        error_reporter: Arc<Mutex<ErrorReporter>>,
+    ) {
+        while let Some(request) = receiver.recv().await {
+            let (writer, error_reporter) = (writer.clone(), Arc::clone(&error_reporter));
+            let handled = task::spawn_blocking(move || {
+                self.handle(&writer, request, &error_reporter);
+                self
+            });
+            self = match handled.await {
+                Ok(worker) => worker,
+                Err(e) => {
+                    error!("OCRの処理が異常終了しました: {}", e);
+                    return;
+                }
+            };
+        }
+    }
+
+    /// 依頼1件を処理し、所要時間を記録済みの処理時間に書き足してキャプチャ後フックを実行する
+    fn handle(
+        &mut self,
+        writer: &DatabaseWriter,
+        request: OcrRequest,
+        error_reporter: &Mutex<ErrorReporter>,
+    ) {
+        let outcome = match self.process(writer, &request) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                report_error(error_reporter, writer, "OCR結果の書き込み失敗", &e);
+                return;
+            }
+        };
+        if let Some(ref e) = outcome.error {
+            report_error(error_reporter, writer, "OCR失敗", e);
+        }
+        let (capture_id, ocr_ms) = (request.capture_id, outcome.ocr_ms);
+        if let Err(e) = writer.execute(move |db| db.update_perf_ocr_ms(capture_id, ocr_ms)) {
+            report_error(error_reporter, writer, "OCR時間の記録失敗", &e);
+        }
+
+        if let Some(ref command) = request.post_capture_hook {
+            let record = CaptureRecord {
+                ocr_text: outcome.text,
+                ..request.record
+            };
+            if let Err(e) = Hook::new(command.as_str()).run_post_capture(&record) {
+                report_error(error_reporter, writer, "キャプチャ後フック失敗", &e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::PerfMetrics;
+    use crate::ocr::OcrResult;
+    use std::path::Path;
+    use tempfile::TempDir;
+    use tokio::sync::mpsc;
+
+    /// 画像を読まずに決まったテキストを返すバックエンド
+    struct FixedBackend(Result<String, String>);
+
+    impl OcrBackend for FixedBackend {
+        fn recognize(&self, _image_path: &Path) -> Result<OcrResult, OcrError> {
+            match self.0 {
+                Ok(ref text) => Ok(OcrResult::from_cached(text.clone(), None)),
+                Err(ref e) => Err(OcrError::ExecutionFailed(e.clone())),
+            }
+        }
+    }
+
+    fn insert_capture(db: &Database) -> (CaptureRecord, i64) {
+        let record = CaptureRecord {
+            id: None,
+            captured_at: "2025-01-10T10:00:00+09:00".to_string(),
+            image_path: Some("/tmp/100000.jpg".to_string()),
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(60),
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        };
+        let id = db.insert_capture(&record).unwrap();
+        let perf = PerfMetrics {
+            screenshot_ms: 100,
+            ocr_ms: None,
+            db_ms: 5,
+            total_ms: 110,
+        };
+        db.insert_perf_metrics(id, &perf).unwrap();
+        (
+            CaptureRecord {
+                id: Some(id),
+                ..record
+            },
+            id,
+        )
+    }
+
+    fn request(record: CaptureRecord, backend: FixedBackend) -> OcrRequest {
+        OcrRequest {
+            capture_id: record.id.unwrap(),
+            image_path: PathBuf::from("/tmp/100000.jpg"),
+            timestamp: Local::now(),
+            record,
+            backend: Arc::new(backend),
+            save_details: false,
+            post_capture_hook: None,
+        }
+    }
+
+    #[test]
+    fn test_process_writes_text_or_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let writer = DatabaseWriter::spawn(&temp_dir.path().join("test.db")).unwrap();
+        let mut worker = OcrWorker::new(&db).unwrap();
+
+        let (record, id) = insert_capture(&db);
+        let outcome = worker
+            .process(
+                &writer,
+                &request(record, FixedBackend(Ok("fn  main()\n\nfn main()".into()))),
+            )
+            .unwrap();
+        assert_eq!(outcome.text.as_deref(), Some("fn main()"));
+        assert!(outcome.error.is_none());
+        let saved = db.get_capture(id).unwrap().unwrap();
+        assert_eq!(saved.ocr_text.as_deref(), Some("fn main()"));
+
+        // 失敗はocr_jobsに記録し、テキストは書き込まない
+        let (record, id) = insert_capture(&db);
+        let outcome = worker
+            .process(
+                &writer,
+                &request(record, FixedBackend(Err("timeout".into()))),
+            )
+            .unwrap();
+        assert!(outcome.text.is_none());
+        assert!(outcome.error.is_some());
+        let failures = db.get_ocr_failures(10).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].capture_id, id);
+    }
+
+    #[tokio::test]
+    async fn test_run_processes_queue_and_hook() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let worker = OcrWorker::new(&db).unwrap();
+        let output = temp_dir.path().join("record.json");
+
+        let (sender, receiver) = mpsc::channel(OCR_QUEUE_CAPACITY);
+        let (record, id) = insert_capture(&db);
+        let mut request = request(record, FixedBackend(Ok("hello".into())));
+        request.post_capture_hook = Some(format!("cat > '{}'", output.display()));
+        sender.try_send(request).unwrap();
+        // 送信側を閉じても、受け取り済みの依頼は処理してから終了する
+        drop(sender);
+        let reporter = Arc::new(Mutex::new(ErrorReporter::new(0)));
+        worker
+            .run(
+                DatabaseWriter::spawn(&temp_dir.path().join("test.db")).unwrap(),
+                receiver,
+                reporter,
+            )
+            .await;
+
+        let saved = db.get_capture(id).unwrap().unwrap();
+        assert_eq!(saved.ocr_text.as_deref(), Some("hello"));
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(json["ocr_text"], "hello");
+        assert_eq!(json["id"], id);
+        // 所要時間は記録済みの処理時間に書き足す
+        let perf = db
+            .get_perf_metrics_between("2025-01-10T00:00:00", "2025-01-11T00:00:00")
+            .unwrap();
+        assert!(perf[0].ocr_ms.is_some());
+    }
+}
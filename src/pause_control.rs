@@ -1,9 +1,21 @@
 //! 一時停止制御モジュール
 
+use serde::Deserialize;
 use std::fs::{self, File};
 use std::io;
 use std::path::PathBuf;
 
+/// 一時停止中に何を記録するか
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseMode {
+    /// 時刻と間隔だけを記録する
+    #[default]
+    Stop,
+    /// 画像・OCR・タイトルは残さず、アプリ名だけを記録する
+    MetadataOnly,
+}
+
 /// 一時停止制御
 pub struct PauseControl {
     pause_file: PathBuf,
@@ -0,0 +1,229 @@
+//! ポモドーロモジュール - 作業/休憩サイクルの状態と段階の判定
+
+use crate::database::{format_timestamp, parse_timestamp};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// ポモドーロの段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PomodoroPhase {
+    /// 作業
+    Work,
+    /// 休憩
+    Break,
+}
+
+/// 実行中のポモドーロ（作業と休憩を開始時刻から繰り返す）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pomodoro {
+    pub started_at: String,
+    pub work_minutes: u64,
+    pub break_minutes: u64,
+}
+
+/// ある時刻でのポモドーロの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    /// 何回目のサイクルか（1から）
+    pub cycle: u64,
+    /// 今の段階の残り時間（秒）
+    pub remaining_seconds: u64,
+    /// 完了した作業の回数
+    pub completed: u64,
+}
+
+impl Pomodoro {
+    /// 開始時刻と作業・休憩の長さ（分）から作成
+    pub fn new(started_at: &DateTime<Local>, work_minutes: u64, break_minutes: u64) -> Self {
+        Self {
+            started_at: format_timestamp(started_at),
+            work_minutes: work_minutes.max(1),
+            break_minutes,
+        }
+    }
+
+    /// 指定時刻の状態（開始前・開始時刻を解析できなければ `None`）
+    pub fn status(&self, now: &DateTime<Local>) -> Option<PomodoroStatus> {
+        let elapsed = self.elapsed_seconds(now)?;
+        let (work, cycle) = self.lengths();
+        let position = elapsed % cycle;
+        let (phase, remaining_seconds) = if position < work {
+            (PomodoroPhase::Work, work - position)
+        } else {
+            (PomodoroPhase::Break, cycle - position)
+        };
+        Some(PomodoroStatus {
+            phase,
+            cycle: elapsed / cycle + 1,
+            remaining_seconds,
+            completed: self.completed_at(elapsed),
+        })
+    }
+
+    /// `from` より後、`to` まで（`from < t <= to`）に作業が終わった時刻
+    ///
+    /// `from` が `None` なら開始時刻から数える
+    pub fn completions_between(
+        &self,
+        from: Option<&DateTime<Local>>,
+        to: &DateTime<Local>,
+    ) -> Vec<DateTime<Local>> {
+        let (Some(started), Some(to)) = (self.started(), self.elapsed_seconds(to)) else {
+            return Vec::new();
+        };
+        let (work, cycle) = self.lengths();
+        // 開始前の `from` は開始時刻と同じに扱う
+        let first = from
+            .and_then(|from| self.elapsed_seconds(from))
+            .map_or(0, |from| self.completed_at(from));
+        (first..self.completed_at(to))
+            .map(|i| started + Duration::seconds((i * cycle + work) as i64))
+            .collect()
+    }
+
+    /// 指定時刻の次に段階が切り替わる時刻
+    pub fn next_boundary(&self, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let status = self.status(now)?;
+        Some(*now + Duration::seconds(status.remaining_seconds as i64))
+    }
+
+    /// 開始時刻
+    fn started(&self) -> Option<DateTime<Local>> {
+        parse_timestamp(&self.started_at).map(|t| t.with_timezone(&Local))
+    }
+
+    /// 開始からの経過秒数（開始前なら `None`）
+    fn elapsed_seconds(&self, now: &DateTime<Local>) -> Option<u64> {
+        let seconds = (*now - self.started()?).num_seconds();
+        (seconds >= 0).then_some(seconds as u64)
+    }
+
+    /// 作業の長さとサイクル全体の長さ（秒）
+    fn lengths(&self) -> (u64, u64) {
+        let work = self.work_minutes.max(1) * 60;
+        (work, work + self.break_minutes * 60)
+    }
+
+    /// 経過秒数までに完了した作業の回数
+    fn completed_at(&self, elapsed: u64) -> u64 {
+        let (work, cycle) = self.lengths();
+        elapsed / cycle + u64::from(elapsed % cycle >= work)
+    }
+}
+
+/// 実行中のポモドーロの状態ファイル
+///
+/// `tracker pomodoro start` が書き込み、`tracker start` のキャプチャループが読む
+pub struct PomodoroControl {
+    pomodoro_file: PathBuf,
+}
+
+impl PomodoroControl {
+    /// 新しいPomodoroControlを作成
+    pub fn new(pomodoro_file: PathBuf) -> Self {
+        Self { pomodoro_file }
+    }
+
+    /// ポモドーロを開始（実行中なら置き換える）
+    pub fn start(&self, pomodoro: &Pomodoro) -> io::Result<()> {
+        if let Some(parent) = self.pomodoro_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(pomodoro).map_err(io::Error::other)?;
+        fs::write(&self.pomodoro_file, json)
+    }
+
+    /// ポモドーロを終了
+    pub fn stop(&self) -> io::Result<()> {
+        if self.pomodoro_file.exists() {
+            fs::remove_file(&self.pomodoro_file)?;
+        }
+        Ok(())
+    }
+
+    /// 実行中のポモドーロ（なければ、または読めなければ `None`）
+    pub fn current(&self) -> Option<Pomodoro> {
+        let json = fs::read_to_string(&self.pomodoro_file).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 10, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_status() {
+        let pomodoro = Pomodoro::new(&at(9, 0), 25, 5);
+        assert_eq!(pomodoro.status(&at(8, 59)), None);
+        assert_eq!(
+            pomodoro.status(&at(9, 10)),
+            Some(PomodoroStatus {
+                phase: PomodoroPhase::Work,
+                cycle: 1,
+                remaining_seconds: 15 * 60,
+                completed: 0,
+            })
+        );
+        assert_eq!(
+            pomodoro.status(&at(9, 25)),
+            Some(PomodoroStatus {
+                phase: PomodoroPhase::Break,
+                cycle: 1,
+                remaining_seconds: 5 * 60,
+                completed: 1,
+            })
+        );
+        let status = pomodoro.status(&at(10, 2)).unwrap();
+        assert_eq!(
+            (status.phase, status.cycle, status.completed),
+            (PomodoroPhase::Work, 3, 2)
+        );
+        assert_eq!(pomodoro.next_boundary(&at(10, 2)), Some(at(10, 25)));
+    }
+
+    #[test]
+    fn test_completions_between() {
+        let pomodoro = Pomodoro::new(&at(9, 0), 25, 5);
+        assert_eq!(
+            pomodoro.completions_between(None, &at(10, 0)),
+            vec![at(9, 25), at(9, 55)]
+        );
+        // 境界ちょうどは `to` 側に含める
+        assert_eq!(
+            pomodoro.completions_between(Some(&at(9, 25)), &at(9, 55)),
+            vec![at(9, 55)]
+        );
+        assert!(pomodoro
+            .completions_between(Some(&at(9, 56)), &at(10, 20))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_control() {
+        let temp_dir = TempDir::new().unwrap();
+        let control = PomodoroControl::new(temp_dir.path().join("sub").join("pomodoro.json"));
+        assert_eq!(control.current(), None);
+
+        let pomodoro = Pomodoro::new(&at(9, 0), 50, 10);
+        control.start(&pomodoro).unwrap();
+        assert_eq!(control.current(), Some(pomodoro));
+
+        control.stop().unwrap();
+        assert_eq!(control.current(), None);
+        control.stop().unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+//! プロセス監視モジュール - CPU/メモリ使用率のスナップショット
+
+use crate::database::ProcessSnapshot;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// プロセス監視
+///
+/// CPU使用率は前回の更新からの差分で計算されるため、インスタンスを保持して
+/// キャプチャサイクルごとに `snapshot` を呼ぶと、その間隔の平均使用率が得られる
+pub struct ProcessMonitor {
+    system: System,
+    top_n: usize,
+}
+
+impl ProcessMonitor {
+    /// 新しいProcessMonitorを作成（CPU使用率の基準値を取得する）
+    pub fn new(top_n: usize) -> Self {
+        let mut monitor = Self {
+            system: System::new(),
+            top_n,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// CPU使用率の上位N個とアクティブアプリのプロセスを取得
+    pub fn snapshot(&mut self, active_app: &str) -> Vec<ProcessSnapshot> {
+        self.refresh();
+
+        let processes = self
+            .system
+            .processes()
+            .values()
+            .map(|process| {
+                let name = process.name().to_string_lossy().to_string();
+                ProcessSnapshot {
+                    pid: process.pid().as_u32(),
+                    is_active_app: name == active_app,
+                    name,
+                    cpu_percent: process.cpu_usage(),
+                    memory_bytes: process.memory(),
+                }
+            })
+            .collect();
+
+        select_processes(processes, self.top_n)
+    }
+
+    /// プロセス一覧のCPU・メモリ情報を更新
+    fn refresh(&mut self) {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+    }
+}
+
+/// CPU使用率の降順で上位N個を選び、圏外のアクティブアプリのプロセスを追加
+fn select_processes(mut processes: Vec<ProcessSnapshot>, top_n: usize) -> Vec<ProcessSnapshot> {
+    processes.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+
+    let mut selected = Vec::new();
+    for (rank, process) in processes.into_iter().enumerate() {
+        if rank < top_n || process.is_active_app {
+            selected.push(process);
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(name: &str, cpu_percent: f32, is_active_app: bool) -> ProcessSnapshot {
+        ProcessSnapshot {
+            pid: 1,
+            name: name.to_string(),
+            cpu_percent,
+            memory_bytes: 1024,
+            is_active_app,
+        }
+    }
+
+    #[test]
+    fn test_select_top_processes_and_active_app() {
+        let processes = vec![
+            process("Safari", 1.0, true),
+            process("rustc", 350.0, false),
+            process("ffmpeg", 180.0, false),
+            process("Finder", 0.1, false),
+        ];
+
+        let selected = select_processes(processes, 2);
+        let names: Vec<_> = selected.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["rustc", "ffmpeg", "Safari"]);
+    }
+
+    #[test]
+    fn test_snapshot_lists_processes() {
+        let mut monitor = ProcessMonitor::new(3);
+        let snapshot = monitor.snapshot("");
+        assert!(snapshot.len() <= 3);
+    }
+}
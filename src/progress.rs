@@ -0,0 +1,169 @@
+//! 進捗表示モジュール - 標準エラー出力へのプログレスバー
+
+use crate::report::format_duration;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// バーの幅（文字数）
+const BAR_WIDTH: usize = 30;
+
+/// プログレスバー
+///
+/// 標準エラー出力が端末でない場合（リダイレクト時など）は描画しない
+pub struct ProgressBar {
+    total: usize,
+    current: usize,
+    failed: usize,
+    enabled: bool,
+    started: Instant,
+    /// 処理速度と残り時間も表示するか
+    show_rate: bool,
+}
+
+impl ProgressBar {
+    /// 新しいProgressBarを作成
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            current: 0,
+            failed: 0,
+            enabled: std::io::stderr().is_terminal(),
+            started: Instant::now(),
+            show_rate: false,
+        }
+    }
+
+    /// 処理速度と残り時間の見込みも表示する
+    pub fn with_rate(mut self) -> Self {
+        self.show_rate = true;
+        self
+    }
+
+    /// 1件の成功を記録
+    pub fn inc(&mut self) {
+        self.current += 1;
+        self.draw();
+    }
+
+    /// 1件の失敗を記録
+    pub fn inc_failed(&mut self) {
+        self.current += 1;
+        self.failed += 1;
+        self.draw();
+    }
+
+    /// バーを崩さずにメッセージを出力
+    pub fn println(&self, message: &str) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+        }
+        eprintln!("{}", message);
+        self.draw();
+    }
+
+    /// 描画を終了して改行
+    pub fn finish(&self) {
+        if self.enabled {
+            eprintln!();
+        }
+    }
+
+    /// 現在の状態を描画
+    fn draw(&self) {
+        if !self.enabled {
+            return;
+        }
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r{}", self.render());
+        let _ = stderr.flush();
+    }
+
+    /// バーの文字列を生成
+    fn render(&self) -> String {
+        self.render_at(self.started.elapsed())
+    }
+
+    /// 開始から `elapsed` が経過した時点のバーの文字列を生成
+    fn render_at(&self, elapsed: Duration) -> String {
+        let ratio = if self.total == 0 {
+            1.0
+        } else {
+            self.current as f64 / self.total as f64
+        };
+        let filled = ((ratio * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+
+        let mut line = format!(
+            "[{}{}] {}/{} ({:.0}%)",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.current,
+            self.total,
+            ratio * 100.0
+        );
+        if self.failed > 0 {
+            line.push_str(&format!(" 失敗: {}", self.failed));
+        }
+        let seconds = elapsed.as_secs_f64();
+        if self.show_rate && self.current > 0 && seconds > 0.0 {
+            let rate = self.current as f64 / seconds;
+            let remaining = self.total.saturating_sub(self.current) as f64 / rate;
+            line.push_str(&format!(
+                " {:.1}件/秒 残り {}",
+                rate,
+                format_duration(remaining as u64)
+            ));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn silent(total: usize) -> ProgressBar {
+        ProgressBar {
+            enabled: false,
+            ..ProgressBar::new(total)
+        }
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let bar = silent(10);
+        assert_eq!(bar.render(), format!("[{}] 0/10 (0%)", "-".repeat(30)));
+    }
+
+    #[test]
+    fn test_render_progress_with_failures() {
+        let mut bar = silent(4);
+        bar.inc();
+        bar.inc_failed();
+        assert_eq!(
+            bar.render(),
+            format!("[{}{}] 2/4 (50%) 失敗: 1", "#".repeat(15), "-".repeat(15))
+        );
+    }
+
+    #[test]
+    fn test_render_rate_and_eta() {
+        let mut bar = silent(7300).with_rate();
+        assert_eq!(
+            bar.render_at(Duration::from_secs(10)),
+            format!("[{}] 0/7300 (0%)", "-".repeat(30))
+        );
+        for _ in 0..100 {
+            bar.inc();
+        }
+        // 2件/秒で残り7200件なら1時間
+        assert!(bar
+            .render_at(Duration::from_secs(50))
+            .ends_with(" 2.0件/秒 残り 1時間0分"));
+    }
+
+    #[test]
+    fn test_render_zero_total() {
+        let bar = silent(0);
+        assert!(bar.render().starts_with(&format!("[{}]", "#".repeat(30))));
+    }
+}
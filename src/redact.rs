@@ -0,0 +1,72 @@
+//! マスクモジュール - ウィンドウタイトルに含まれる個人情報の保存前の置換
+
+use regex::Regex;
+use std::borrow::Cow;
+
+/// マッチした部分を置き換える文字列
+pub const REDACTED: &str = "***";
+
+/// ウィンドウタイトルのマスク（config: `title_redact_patterns`）
+///
+/// いずれかの正規表現にマッチした部分を `***` に置き換える
+#[derive(Debug, Clone, Default)]
+pub struct TitleRedactor {
+    patterns: Vec<Regex>,
+}
+
+impl TitleRedactor {
+    /// 正規表現の一覧から作成（不正なパターンがあればそのエラー）
+    pub fn new(patterns: &[String]) -> Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// マスクするパターンがないか
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// マッチした部分を `***` に置き換えたタイトル
+    pub fn redact<'a>(&self, title: &'a str) -> Cow<'a, str> {
+        let mut title = Cow::Borrowed(title);
+        for pattern in &self.patterns {
+            if let Cow::Owned(replaced) = pattern.replace_all(&title, REDACTED) {
+                title = Cow::Owned(replaced);
+            }
+        }
+        title
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let redactor = TitleRedactor::new(&[
+            r"\d{4}-\d{4}-\d{4}-\d{4}".to_string(),
+            "(?i)password".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            redactor.redact("口座 1234-5678-9012-3456 の明細 - Safari"),
+            "口座 *** の明細 - Safari"
+        );
+        assert_eq!(
+            redactor.redact("Reset PASSWORD and password"),
+            "Reset *** and ***"
+        );
+        assert!(matches!(redactor.redact("main.rs"), Cow::Borrowed(_)));
+        assert!(TitleRedactor::default().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(TitleRedactor::new(&["(unclosed".to_string()]).is_err());
+    }
+}
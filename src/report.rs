@@ -1,29 +1,288 @@
 //! レポートモジュール
 
-use crate::database::{CaptureRecord, Database};
+use crate::break_reminder::BREAK_GAP_SECONDS;
+use crate::category::{AppAliases, Categories, Distractions, MeetingRules, NonWorkRules};
+use crate::database::{
+    parse_timestamp, Alert, AppEvent, CaptureRecord, Database, GoalResult, Highlight, Note,
+    SessionKind, ACTIVITY_TYPE_MEDIA,
+};
 use crate::error::ReportError;
-use std::collections::HashMap;
+use crate::goals::{format_goal, GoalProgress, Goals, TOTAL_GOAL};
+use crate::i18n::Message;
+use crate::idle::IDLE_EXTRA_KEY;
+use chrono::{
+    DateTime, Datelike, Days, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Timelike,
+    Weekday,
+};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// 時間帯別の内訳に表示する主要アプリの数
+const HOURLY_TOP_APPS: usize = 3;
+
+/// 期間指定（`--trend` など）の上限の日数
+pub const MAX_PERIOD_DAYS: u32 = 366;
 
 /// タイムラインエントリ
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct TimelineEntry {
+    pub captured_at: String,
     pub time: String,
     pub active_app: String,
     pub window_title: String,
+    /// 自動付与した活動の種類（動画視聴なら `media`）
+    pub activity_type: Option<String>,
+    /// OCRテキストの抜粋（`with_ocr_excerpts` で有効にした場合のみ）
+    pub ocr_excerpt: Option<String>,
 }
 
 /// アプリ別サマリー
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct AppSummary {
     pub app_name: String,
     pub duration_seconds: u64,
     pub capture_count: u64,
 }
 
+/// アプリ別・時間帯別の時間
+#[derive(Debug, Serialize)]
+pub struct AppHourly {
+    pub app_name: String,
+    pub duration_seconds: u64,
+    /// 時間帯（0〜23時）別の時間（秒）
+    pub hourly: [u64; 24],
+}
+
+/// 時間帯別の内訳
+#[derive(Debug, Serialize)]
+pub struct HourlyBreakdown {
+    /// 時間帯（0〜23時）
+    pub hour: u32,
+    pub duration_seconds: u64,
+    pub capture_count: u64,
+    /// 時間の長い主要アプリ（最大3つ）
+    pub top_apps: Vec<AppSummary>,
+}
+
+/// スリープ区間
+#[derive(Debug, Serialize)]
+pub struct SleepInterval {
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_seconds: Option<u64>,
+}
+
+/// 日別の合計作業時間
+#[derive(Debug, Serialize)]
+pub struct TrendDay {
+    pub date: String,
+    pub total_seconds: u64,
+}
+
+/// カテゴリ別の作業時間の推移
+#[derive(Debug, Serialize)]
+pub struct CategoryTrend {
+    pub category: String,
+    pub total_seconds: u64,
+    /// 日別の作業時間（対象期間の全日、古い順）
+    pub daily: Vec<u64>,
+}
+
+/// 期間中の作業時間の推移
+#[derive(Debug, Serialize)]
+pub struct Trend {
+    pub start_date: String,
+    pub end_date: String,
+    pub total_seconds: u64,
+    /// 作業時間に含めない動画視聴の時間
+    pub media_seconds: u64,
+    pub days: Vec<TrendDay>,
+    pub categories: Vec<CategoryTrend>,
+}
+
+/// キャプチャ処理の1段階の所要時間の分布（ミリ秒）
+#[derive(Debug, Serialize)]
+pub struct PerfStep {
+    pub name: &'static str,
+    pub samples: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// 期間中のキャプチャ処理時間の統計
+#[derive(Debug, Serialize)]
+pub struct PerfStats {
+    pub start_date: String,
+    pub end_date: String,
+    pub interval_seconds: u64,
+    pub steps: Vec<PerfStep>,
+}
+
+/// 日別の画像の保存量
+#[derive(Debug, Serialize)]
+pub struct StorageDay {
+    pub date: String,
+    pub images: u64,
+    pub bytes: u64,
+}
+
+/// 期間中の画像ストレージの増加量
+#[derive(Debug, Serialize)]
+pub struct StorageStats {
+    pub start_date: String,
+    pub end_date: String,
+    pub total_images: u64,
+    pub total_bytes: u64,
+    /// 日別の保存量（対象期間の全日、古い順）
+    pub days: Vec<StorageDay>,
+}
+
+/// 1日のKPI（レポート冒頭のサマリー）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DailyKpi {
+    /// 総作業時間（動画視聴・非作業を除く）
+    pub work_seconds: u64,
+    /// 会議アプリ・ウィンドウの時間（作業時間の内数）
+    pub meeting_seconds: u64,
+    /// 同じアプリを続けて使った区間のうち、設定の時間以上続いたものの合計（作業時間の内数）
+    pub focus_seconds: u64,
+    /// 作業時間帯に脱線アプリを使っていた時間（作業時間の内数）
+    pub distraction_seconds: u64,
+    /// アプリを切り替えた回数
+    pub context_switches: u64,
+    /// 完了したポモドーロの数
+    pub pomodoros: u64,
+    /// 一時停止していた時間（作業時間に含めない）
+    pub paused_seconds: u64,
+    /// プライベート（プライベートブラウジング・ポモドーロの休憩）として記録した時間
+    pub private_seconds: u64,
+    /// 同じウィンドウのまま入力がなく、放置の疑いがある時間（作業時間の内数）
+    pub idle_seconds: u64,
+}
+
+/// 1日分のレポート
+///
+/// `total_seconds` と `apps` は作業時間で、動画視聴は `media`、非作業は `non_work` に分けて集計する
+#[derive(Debug, Serialize)]
+pub struct DailyReport {
+    pub date: String,
+    pub kpi: DailyKpi,
+    pub total_seconds: u64,
+    pub timeline: Vec<TimelineEntry>,
+    pub apps: Vec<AppSummary>,
+    pub media_seconds: u64,
+    pub media: Vec<AppSummary>,
+    /// ロック画面・デスクトップのみなどの非作業の時間（`total_seconds` に含めない）
+    pub non_work_seconds: u64,
+    pub non_work: Vec<AppSummary>,
+    /// `pause_mode = "metadata_only"` で一時停止中に記録したアプリ別時間（`total_seconds` に含めない）
+    pub paused: Vec<AppSummary>,
+    /// 作業時間帯に脱線アプリを使っていた時間（`total_seconds` の内数）
+    pub distraction_seconds: u64,
+    pub distractions: Vec<AppSummary>,
+    pub sleeps: Vec<SleepInterval>,
+    /// 時間帯ごとの代表画像（選定済みの時間帯のみ、古い順）
+    pub highlights: Vec<Highlight>,
+    /// 作業メモ（古い順）
+    pub notes: Vec<Note>,
+    /// 目標の達成状況とストリーク
+    pub goals: Vec<GoalProgress>,
+}
+
+/// OCR検索ヒット
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub captured_at: String,
+    pub active_app: String,
+    pub window_title: String,
+    pub snippet: String,
+}
+
+/// キーワードの日別出現時間
+#[derive(Debug, Serialize)]
+pub struct KeywordDay {
+    pub date: String,
+    pub duration_seconds: u64,
+}
+
+/// キーワードごとの出現集計
+///
+/// キーワードをOCRテキストに含むキャプチャの記録間隔を合計した、
+/// そのトピックに使った時間の近似
+#[derive(Debug, Serialize)]
+pub struct KeywordStats {
+    pub keyword: String,
+    pub total_seconds: u64,
+    pub capture_count: u64,
+    /// 日別推移（対象期間の全日、古い順）
+    pub daily: Vec<KeywordDay>,
+    /// 時間帯（0〜23時）別の出現時間
+    pub hourly: [u64; 24],
+}
+
+/// キーワード頻度トレンド
+#[derive(Debug, Serialize)]
+pub struct KeywordTrend {
+    pub start_date: String,
+    pub end_date: String,
+    pub keywords: Vec<KeywordStats>,
+}
+
+/// アプリ名によるキャプチャの絞り込み
+///
+/// アプリ名は大文字小文字を区別せず完全一致で比較する
+#[derive(Debug, Default, Clone)]
+pub struct AppFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl AppFilter {
+    /// 新しいAppFilterを作成
+    ///
+    /// `include` が空の場合はすべてのアプリを対象にする
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        let normalize = |apps: Vec<String>| {
+            apps.into_iter()
+                .map(|app| app.trim().to_lowercase())
+                .filter(|app| !app.is_empty())
+                .collect()
+        };
+        Self {
+            include: normalize(include),
+            exclude: normalize(exclude),
+        }
+    }
+
+    /// 絞り込みがない（すべてのアプリが対象）か
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// アプリが対象に含まれるか判定
+    pub fn matches(&self, app: &str) -> bool {
+        let app = app.to_lowercase();
+        (self.include.is_empty() || self.include.contains(&app)) && !self.exclude.contains(&app)
+    }
+}
+
 /// レポート生成
 pub struct Report {
     db: Database,
     interval_seconds: u64,
+    day_boundary_hour: u32,
+    app_filter: AppFilter,
+    categories: Categories,
+    aliases: AppAliases,
+    distractions: Distractions,
+    meetings: MeetingRules,
+    non_work: NonWorkRules,
+    focus_min_minutes: u64,
+    goals: Goals,
+    ocr_excerpts: bool,
 }
 
 impl Report {
@@ -32,21 +291,210 @@ impl Report {
         Self {
             db,
             interval_seconds,
+            day_boundary_hour: 0,
+            app_filter: AppFilter::default(),
+            categories: Categories::default(),
+            aliases: AppAliases::default(),
+            distractions: Distractions::default(),
+            meetings: MeetingRules::default(),
+            non_work: NonWorkRules::default(),
+            focus_min_minutes: 25,
+            goals: Goals::default(),
+            ocr_excerpts: false,
+        }
+    }
+
+    /// 1日の境界時刻を設定
+    ///
+    /// 例えば4を指定すると、指定日の4:00から翌日の4:00までを1日として集計する
+    pub fn with_day_boundary(mut self, hour: u32) -> Self {
+        self.day_boundary_hour = hour;
+        self
+    }
+
+    /// 記録間隔のないキャプチャの集計に使うキャプチャ間隔（秒）
+    pub fn interval_seconds(&self) -> u64 {
+        self.interval_seconds
+    }
+
+    /// 参照中のデータベース
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    /// アプリによる絞り込みを設定
+    pub fn with_app_filter(mut self, filter: AppFilter) -> Self {
+        self.app_filter = filter;
+        self
+    }
+
+    /// アプリのカテゴリ分類を設定
+    pub fn with_categories(mut self, categories: Categories) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// アプリの表示名の統一を設定
+    ///
+    /// 取得したキャプチャ・アプリ切り替えイベントのアプリ名を表示名に置き換えてから
+    /// 絞り込み・集計する
+    pub fn with_aliases(mut self, aliases: AppAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// 脱線とみなすアプリ・サイトと作業時間帯を設定（日次レポートの脱線時間の集計に使う）
+    pub fn with_distractions(mut self, distractions: Distractions) -> Self {
+        self.distractions = distractions;
+        self
+    }
+
+    /// 会議とみなすアプリ・ウィンドウを設定（KPIの会議時間の集計に使う）
+    pub fn with_meetings(mut self, meetings: MeetingRules) -> Self {
+        self.meetings = meetings;
+        self
+    }
+
+    /// 非作業とみなすアプリを設定（作業時間・KPI・推移の集計から除く）
+    pub fn with_non_work(mut self, non_work: NonWorkRules) -> Self {
+        self.non_work = non_work;
+        self
+    }
+
+    /// フォーカス時間とみなす、同じアプリを続けて使った最短の時間（分）を設定
+    pub fn with_focus_min_minutes(mut self, minutes: u64) -> Self {
+        self.focus_min_minutes = minutes;
+        self
+    }
+
+    /// 1日の作業時間の目標を設定
+    pub fn with_goals(mut self, goals: Goals) -> Self {
+        self.goals = goals;
+        self
+    }
+
+    /// タイムラインの各エントリにOCRテキストの抜粋を添えるかを設定
+    pub fn with_ocr_excerpts(mut self, enabled: bool) -> Self {
+        self.ocr_excerpts = enabled;
+        self
+    }
+
+    /// 指定日（境界時刻・アプリ絞り込みを考慮）のキャプチャを取得
+    pub fn captures(&self, date: &str) -> Result<Vec<CaptureRecord>, ReportError> {
+        let mut captures = Vec::new();
+        self.for_each_capture(date, date, |capture| captures.push(capture))?;
+        Ok(captures)
+    }
+
+    /// `first_date` から `last_date` まで（境界時刻・表示名・アプリ絞り込みを考慮）の
+    /// キャプチャを古い順に1件ずつ処理する
+    ///
+    /// 全件をメモリに載せないため、期間が長い集計でも使用メモリは件数によらない
+    pub fn for_each_capture<F>(
+        &self,
+        first_date: &str,
+        last_date: &str,
+        mut f: F,
+    ) -> Result<(), ReportError>
+    where
+        F: FnMut(CaptureRecord),
+    {
+        let (start, _) = day_range(first_date, self.day_boundary_hour)?;
+        let (_, end) = day_range(last_date, self.day_boundary_hour)?;
+        self.db.for_each_capture_between(&start, &end, |capture| {
+            if let Some(capture) = self.resolve_capture(capture) {
+                f(capture);
+            }
+            Ok::<_, ReportError>(())
+        })
+    }
+
+    /// `for_each_capture` と同じキャプチャを、記録時間（秒）とともに古い順に1件ずつ処理する
+    ///
+    /// 記録時間は次のキャプチャまでの実際の時間差で、そのキャプチャの間隔を上限とする
+    /// （`elapsed_seconds`）。次のキャプチャは絞り込みで除いたアプリのものも含めて判定する
+    pub fn for_each_timed_capture<F>(
+        &self,
+        first_date: &str,
+        last_date: &str,
+        mut f: F,
+    ) -> Result<(), ReportError>
+    where
+        F: FnMut(CaptureRecord, u64),
+    {
+        self.for_each_timed_record(first_date, last_date, |capture, seconds| {
+            if let Some(capture) = self.resolve_capture(capture) {
+                f(capture, seconds);
+            }
+        })
+    }
+
+    /// 一時停止中の記録を含むすべてのキャプチャを、表示名の置き換え・絞り込みをせずに
+    /// 記録時間（秒）とともに古い順に1件ずつ処理する
+    fn for_each_timed_record<F>(
+        &self,
+        first_date: &str,
+        last_date: &str,
+        mut f: F,
+    ) -> Result<(), ReportError>
+    where
+        F: FnMut(CaptureRecord, u64),
+    {
+        let (start, _) = day_range(first_date, self.day_boundary_hour)?;
+        let (_, end) = day_range(last_date, self.day_boundary_hour)?;
+        let mut emit = |capture: CaptureRecord, next: Option<&CaptureRecord>| {
+            let seconds = elapsed_seconds(&capture, next, self.interval_seconds);
+            f(capture, seconds);
+        };
+
+        // 次のキャプチャが届くまで1件だけ保留する
+        let mut pending: Option<CaptureRecord> = None;
+        self.db.for_each_capture_between(&start, &end, |capture| {
+            if let Some(previous) = pending.replace(capture) {
+                emit(previous, pending.as_ref());
+            }
+            Ok::<_, ReportError>(())
+        })?;
+        if let Some(last) = pending {
+            emit(last, None);
+        }
+        Ok(())
+    }
+
+    /// アプリ名を表示名に置き換え、一時停止中の記録か絞り込みに合わなければ `None` を返す
+    fn resolve_capture(&self, mut capture: CaptureRecord) -> Option<CaptureRecord> {
+        if capture.is_paused {
+            return None;
         }
+        let app = self
+            .aliases
+            .resolve(&capture.active_app, capture.bundle_id.as_deref());
+        if app != capture.active_app {
+            capture.active_app = app.to_string();
+        }
+        self.app_filter
+            .matches(&capture.active_app)
+            .then_some(capture)
     }
 
     /// タイムラインを生成
     pub fn timeline(&self, date: &str) -> Result<Vec<TimelineEntry>, ReportError> {
-        let captures = self.db.get_captures_by_date(date)?;
+        let captures = self.captures(date)?;
 
         let entries: Vec<TimelineEntry> = captures
             .into_iter()
             .map(|c| {
                 let time = extract_time(&c.captured_at);
                 TimelineEntry {
+                    captured_at: c.captured_at,
                     time,
                     active_app: c.active_app,
                     window_title: c.window_title,
+                    activity_type: c.activity_type,
+                    ocr_excerpt: c
+                        .ocr_text
+                        .filter(|_| self.ocr_excerpts)
+                        .and_then(|text| ocr_excerpt(&text)),
                 }
             })
             .collect();
@@ -54,93 +502,1458 @@ impl Report {
         Ok(entries)
     }
 
-    /// アプリ別時間を計算
+    /// アプリ別時間を計算（動画視聴・非作業は含めない）
     pub fn time_by_app(&self, date: &str) -> Result<Vec<AppSummary>, ReportError> {
-        let captures = self.db.get_captures_by_date(date)?;
+        self.summarize_apps(date, |capture| self.is_work(capture))
+    }
 
-        let mut app_counts: HashMap<String, u64> = HashMap::new();
-        for capture in &captures {
-            *app_counts.entry(capture.active_app.clone()).or_insert(0) += 1;
-        }
+    /// フルスクリーンでの動画視聴のアプリ別時間を計算
+    pub fn media_by_app(&self, date: &str) -> Result<Vec<AppSummary>, ReportError> {
+        self.summarize_apps(date, is_media)
+    }
+
+    /// ロック画面・デスクトップのみなどの非作業のアプリ別時間を計算
+    pub fn non_work_by_app(&self, date: &str) -> Result<Vec<AppSummary>, ReportError> {
+        self.summarize_apps(date, |capture| {
+            !is_media(capture) && self.is_non_work(capture)
+        })
+    }
 
-        let mut summaries: Vec<AppSummary> = app_counts
+    /// 一時停止中にアプリ名だけを記録したキャプチャのアプリ別時間を計算
+    ///
+    /// 一時停止中の記録は `resolve_capture` で除かれるので、表示名の置き換え・絞り込みをここで行う
+    pub fn paused_by_app(&self, date: &str) -> Result<Vec<AppSummary>, ReportError> {
+        let mut app_totals: HashMap<String, (u64, u64)> = HashMap::new();
+        self.for_each_timed_record(date, date, |capture, seconds| {
+            if !capture.is_paused || capture.active_app.is_empty() {
+                return;
+            }
+            let app = self
+                .aliases
+                .resolve(&capture.active_app, capture.bundle_id.as_deref());
+            if !self.app_filter.matches(app) {
+                return;
+            }
+            let total = app_totals.entry(app.to_string()).or_insert((0, 0));
+            total.0 += seconds;
+            total.1 += 1;
+        })?;
+        Ok(sorted_summaries(app_totals))
+    }
+
+    /// 作業時間帯に脱線アプリを使っていたアプリ別時間を計算（動画視聴・非作業は含めない）
+    pub fn distraction_by_app(&self, date: &str) -> Result<Vec<AppSummary>, ReportError> {
+        self.summarize_apps(date, |capture| {
+            self.is_work(capture) && self.is_distraction(capture)
+        })
+    }
+
+    /// アプリ別・時間帯別の時間を計算（動画視聴・非作業は含めない、時間の降順）
+    pub fn hourly_by_app(&self, date: &str) -> Result<Vec<AppHourly>, ReportError> {
+        let mut apps: HashMap<String, [u64; 24]> = HashMap::new();
+        self.for_each_timed_capture(date, date, |capture, seconds| {
+            if !self.is_work(&capture) {
+                return;
+            }
+            let hour: Option<usize> = capture.captured_at.get(11..13).and_then(|h| h.parse().ok());
+            let Some(hour) = hour.filter(|&hour| hour < 24) else {
+                return;
+            };
+            apps.entry(capture.active_app).or_insert([0; 24])[hour] += seconds;
+        })?;
+
+        let mut apps: Vec<AppHourly> = apps
             .into_iter()
-            .map(|(app_name, count)| AppSummary {
+            .map(|(app_name, hourly)| AppHourly {
                 app_name,
-                duration_seconds: count * self.interval_seconds,
-                capture_count: count,
+                duration_seconds: hourly.iter().sum(),
+                hourly,
+            })
+            .collect();
+        apps.sort_by(|a, b| {
+            b.duration_seconds
+                .cmp(&a.duration_seconds)
+                .then_with(|| a.app_name.cmp(&b.app_name))
+        });
+        Ok(apps)
+    }
+
+    /// キャプチャのある時間帯ごとの合計と主要アプリを古い順に計算（動画視聴も含める）
+    pub fn hourly_breakdown(&self, date: &str) -> Result<Vec<HourlyBreakdown>, ReportError> {
+        // 時間帯ごとの、アプリごとの（合計秒数, キャプチャ数）
+        let mut hours: HashMap<u32, HashMap<String, (u64, u64)>> = HashMap::new();
+        // キャプチャは古い順なので、時間帯が最初に現れた順が時刻の順になる
+        // （夏時間の切り替えで同じ時間帯が再び現れたら、最初の方にまとめる）
+        let mut order: Vec<u32> = Vec::new();
+        self.for_each_timed_capture(date, date, |capture, seconds| {
+            let Some(hour) = capture
+                .captured_at
+                .get(11..13)
+                .and_then(|h| h.parse::<u32>().ok())
+                .filter(|&hour| hour < 24)
+            else {
+                return;
+            };
+            let apps = hours.entry(hour).or_insert_with(|| {
+                order.push(hour);
+                HashMap::new()
+            });
+            let total = apps.entry(capture.active_app).or_insert((0, 0));
+            total.0 += seconds;
+            total.1 += 1;
+        })?;
+
+        Ok(order
+            .into_iter()
+            .map(|hour| {
+                let mut apps: Vec<AppSummary> = hours
+                    .remove(&hour)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(app_name, (duration_seconds, capture_count))| AppSummary {
+                        app_name,
+                        duration_seconds,
+                        capture_count,
+                    })
+                    .collect();
+                apps.sort_by(|a, b| {
+                    b.duration_seconds
+                        .cmp(&a.duration_seconds)
+                        .then_with(|| a.app_name.cmp(&b.app_name))
+                });
+                let duration_seconds = apps.iter().map(|a| a.duration_seconds).sum();
+                let capture_count = apps.iter().map(|a| a.capture_count).sum();
+                apps.truncate(HOURLY_TOP_APPS);
+                HourlyBreakdown {
+                    hour,
+                    duration_seconds,
+                    capture_count,
+                    top_apps: apps,
+                }
+            })
+            .collect())
+    }
+
+    /// 時間帯別の内訳を表示
+    pub fn print_hourly(&self, date: &str) -> Result<(), ReportError> {
+        let hours = self.hourly_breakdown(date)?;
+        if hours.is_empty() {
+            println!("{}", Message::NoCaptures.format(&[&date]));
+            return Ok(());
+        }
+
+        println!("{}\n", Message::HourlyHeading.format(&[&date]));
+        for hour in &hours {
+            println!("{}", format_hourly(hour));
+        }
+        Ok(())
+    }
+
+    /// 作業時間に数えるキャプチャか（動画視聴・非作業でない）
+    fn is_work(&self, capture: &CaptureRecord) -> bool {
+        !is_media(capture) && !self.is_non_work(capture)
+    }
+
+    /// ロック画面・デスクトップのみなどの非作業のキャプチャか
+    fn is_non_work(&self, capture: &CaptureRecord) -> bool {
+        self.non_work.matches(
+            &capture.active_app,
+            capture.bundle_id.as_deref(),
+            &capture.window_title,
+        )
+    }
+
+    /// 作業時間帯に脱線アプリを使っていたキャプチャか
+    fn is_distraction(&self, capture: &CaptureRecord) -> bool {
+        let hour = capture.captured_at.get(11..13).and_then(|h| h.parse().ok());
+        hour.is_some_and(|hour| self.distractions.is_work_hour(hour))
+            && self
+                .distractions
+                .find(
+                    &capture.active_app,
+                    capture.bundle_id.as_deref(),
+                    &capture.window_title,
+                )
+                .is_some()
+    }
+
+    /// 指定日のKPI（総作業・会議・フォーカス・脱線時間とコンテキストスイッチ）を計算
+    ///
+    /// コンテキストスイッチはアプリ切り替えイベントから数え、イベントの記録がなければ
+    /// キャプチャのアプリが前のキャプチャと変わった回数で代える
+    pub fn kpi(&self, date: &str) -> Result<DailyKpi, ReportError> {
+        let mut kpi = DailyKpi::default();
+        let mut focus = FocusCounter::new(self.focus_min_minutes * 60);
+        let mut previous_app: Option<String> = None;
+        let mut capture_switches = 0;
+        let (start, end) = day_range(date, self.day_boundary_hour)?;
+        let idle_ids: HashSet<i64> = self
+            .db
+            .find_captures_by_extra(IDLE_EXTRA_KEY, &serde_json::Value::Bool(true), &start, &end)?
+            .into_iter()
+            .filter_map(|capture| capture.id)
+            .collect();
+        self.for_each_timed_capture(date, date, |capture, seconds| {
+            if previous_app
+                .as_ref()
+                .is_some_and(|app| *app != capture.active_app)
+            {
+                capture_switches += 1;
+            }
+
+            if capture.is_private {
+                kpi.private_seconds += seconds;
+            }
+            let work = self.is_work(&capture);
+            let meeting = work
+                && self.meetings.matches(
+                    &capture.active_app,
+                    capture.bundle_id.as_deref(),
+                    &capture.window_title,
+                );
+            let distraction = work && self.is_distraction(&capture);
+            if work {
+                kpi.work_seconds += seconds;
+            }
+            if meeting {
+                kpi.meeting_seconds += seconds;
+            }
+            if distraction {
+                kpi.distraction_seconds += seconds;
+            }
+            if work && capture.id.is_some_and(|id| idle_ids.contains(&id)) {
+                kpi.idle_seconds += seconds;
+            }
+            // 会議・脱線・動画視聴・非作業はフォーカスを途切れさせる
+            match parse_timestamp(&capture.captured_at) {
+                Some(at) if work && !(meeting || distraction) => {
+                    focus.observe(&capture.active_app, at, seconds)
+                }
+                _ => focus.interrupt(),
+            }
+            previous_app = Some(capture.active_app);
+        })?;
+        kpi.focus_seconds = focus.finish();
+        // 一時停止中の記録はアプリを持たないため、絞り込みによらず数える
+        self.for_each_timed_record(date, date, |capture, seconds| {
+            if capture.is_paused {
+                kpi.paused_seconds += seconds;
+            }
+        })?;
+
+        let events = self.app_events(date)?;
+        kpi.context_switches = if events.is_empty() {
+            capture_switches
+        } else {
+            events
+                .windows(2)
+                .filter(|pair| pair[0].app_name != pair[1].app_name)
+                .count() as u64
+        };
+
+        kpi.pomodoros = self.db.count_pomodoros_between(&start, &end)?;
+        Ok(kpi)
+    }
+
+    /// 条件に合うキャプチャをアプリ別に集計
+    fn summarize_apps<F>(&self, date: &str, filter: F) -> Result<Vec<AppSummary>, ReportError>
+    where
+        F: Fn(&CaptureRecord) -> bool,
+    {
+        // アプリごとの（合計秒数, キャプチャ数）
+        let mut app_totals: HashMap<String, (u64, u64)> = HashMap::new();
+        self.for_each_timed_capture(date, date, |capture, seconds| {
+            if !filter(&capture) {
+                return;
+            }
+            let total = app_totals.entry(capture.active_app).or_insert((0, 0));
+            total.0 += seconds;
+            total.1 += 1;
+        })?;
+        Ok(sorted_summaries(app_totals))
+    }
+
+    /// タイムラインとアプリ別時間をまとめた1日分のレポートを生成
+    pub fn daily(&self, date: &str) -> Result<DailyReport, ReportError> {
+        let timeline = self.timeline(date)?;
+        let apps = self.time_by_app(date)?;
+        let media = self.media_by_app(date)?;
+        let non_work = self.non_work_by_app(date)?;
+        let distractions = self.distraction_by_app(date)?;
+        Ok(DailyReport {
+            date: date.to_string(),
+            kpi: self.kpi(date)?,
+            total_seconds: apps.iter().map(|a| a.duration_seconds).sum(),
+            timeline,
+            apps,
+            media_seconds: media.iter().map(|a| a.duration_seconds).sum(),
+            media,
+            non_work_seconds: non_work.iter().map(|a| a.duration_seconds).sum(),
+            non_work,
+            paused: self.paused_by_app(date)?,
+            distraction_seconds: distractions.iter().map(|a| a.duration_seconds).sum(),
+            distractions,
+            sleeps: self.sleeps(date)?,
+            highlights: self.highlights(date)?,
+            notes: self.notes(date)?,
+            goals: self.goals(date)?,
+        })
+    }
+
+    /// キャプチャのある日付（境界時刻を考慮）を新しい順に取得
+    pub fn capture_dates(&self, limit: usize) -> Result<Vec<String>, ReportError> {
+        let mut dates: Vec<String> = Vec::new();
+        for hour in self.db.get_capture_hours()? {
+            let Ok(hour) = NaiveDateTime::parse_from_str(&format!("{}:00", hour), "%Y-%m-%dT%H:%M")
+            else {
+                continue;
+            };
+            let date = (hour - Duration::hours(self.day_boundary_hour as i64))
+                .format("%Y-%m-%d")
+                .to_string();
+            if dates.last() != Some(&date) {
+                dates.push(date);
+            }
+            if dates.len() > limit {
+                dates.pop();
+                break;
+            }
+        }
+        Ok(dates)
+    }
+
+    /// 指定日のアプリ切り替えイベントを取得（アプリ絞り込みを考慮）
+    pub fn app_events(&self, date: &str) -> Result<Vec<AppEvent>, ReportError> {
+        let (start, end) = day_range(date, self.day_boundary_hour)?;
+        let mut events = self.db.get_app_events_between(&start, &end)?;
+        for event in &mut events {
+            let bundle_id = (!event.bundle_id.is_empty()).then_some(event.bundle_id.as_str());
+            event.app_name = self.aliases.resolve(&event.app_name, bundle_id).to_string();
+        }
+        events.retain(|e| self.app_filter.matches(&e.app_name));
+        Ok(events)
+    }
+
+    /// 指定日の作業メモを取得（境界時刻を考慮）
+    pub fn notes(&self, date: &str) -> Result<Vec<Note>, ReportError> {
+        let (start, end) = day_range(date, self.day_boundary_hour)?;
+        Ok(self.db.get_notes_between(&start, &end)?)
+    }
+
+    /// 直近のアラートを最大 `limit` 件取得（日付を指定すればその日に絞る、境界時刻を考慮）
+    pub fn alerts(&self, date: Option<&str>, limit: i64) -> Result<Vec<Alert>, ReportError> {
+        let range = date
+            .map(|date| day_range(date, self.day_boundary_hour))
+            .transpose()?;
+        let range = range
+            .as_ref()
+            .map(|(start, end)| (start.as_str(), end.as_str()));
+        Ok(self.db.get_recent_alerts(range, limit)?)
+    }
+
+    /// 指定日の目標の達成状況とストリーク
+    ///
+    /// 前回記録した日（その日は集計し直す）から指定日までの達成状況をDBに記録してから数えるため、
+    /// 古いキャプチャを削除してもストリークは途切れない。
+    /// アプリを絞り込んでいると作業時間が変わるため、目標は集計しない
+    pub fn goals(&self, date: &str) -> Result<Vec<GoalProgress>, ReportError> {
+        if self.goals.is_empty() || !self.app_filter.is_empty() {
+            return Ok(Vec::new());
+        }
+        let end = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| ReportError::InvalidDate(date.to_string()))?;
+
+        // 一度も記録していない目標があれば最初のキャプチャの日から集計する
+        let mut start = date.to_string();
+        for (goal, _) in self.goals.iter() {
+            let last = match self.db.get_last_goal_date(goal)? {
+                Some(last) => Some(last),
+                None => self.capture_dates(usize::MAX)?.pop(),
+            };
+            if let Some(last) = last.filter(|last| *last < start) {
+                start = last;
+            }
+        }
+        let start = NaiveDate::parse_from_str(&start, "%Y-%m-%d")
+            .map_err(|_| ReportError::InvalidDate(start.clone()))?;
+        let days = (end - start).num_days() + 1;
+
+        let trend = self.trend(date, days as u32)?;
+        for (index, day) in trend.days.iter().enumerate() {
+            for (goal, minutes) in self.goals.iter() {
+                let seconds = if goal == TOTAL_GOAL {
+                    day.total_seconds
+                } else {
+                    trend
+                        .categories
+                        .iter()
+                        .find(|c| c.category == goal)
+                        .map_or(0, |c| c.daily[index])
+                };
+                self.db.upsert_goal_result(&GoalResult {
+                    date: day.date.clone(),
+                    goal: goal.to_string(),
+                    seconds,
+                    achieved: seconds >= minutes * 60,
+                })?;
+            }
+        }
+
+        self.goals
+            .iter()
+            .map(|(goal, minutes)| {
+                let results = self.db.get_goal_results(goal, date)?;
+                Ok(GoalProgress::new(goal, minutes, &results, date))
+            })
+            .collect()
+    }
+
+    /// 指定日の時間帯ごとの代表画像を取得（アプリ絞り込みを考慮）
+    pub fn highlights(&self, date: &str) -> Result<Vec<Highlight>, ReportError> {
+        let (start, end) = day_range(date, self.day_boundary_hour)?;
+        let mut highlights = self.db.get_highlights_between(&start, &end)?;
+        for highlight in &mut highlights {
+            highlight.active_app = self
+                .aliases
+                .resolve(&highlight.active_app, None)
+                .to_string();
+        }
+        highlights.retain(|h| self.app_filter.matches(&h.active_app));
+        Ok(highlights)
+    }
+
+    /// 指定日と重なるスリープ区間を取得
+    pub fn sleeps(&self, date: &str) -> Result<Vec<SleepInterval>, ReportError> {
+        let (start, end) = day_range(date, self.day_boundary_hour)?;
+        let sessions = self
+            .db
+            .get_sessions_between(SessionKind::Sleep, &start, &end)?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| {
+                let duration_seconds = session.ended_at.as_deref().and_then(|ended_at| {
+                    let started = parse_timestamp(&session.started_at)?;
+                    let ended = parse_timestamp(ended_at)?;
+                    u64::try_from((ended - started).num_seconds()).ok()
+                });
+                SleepInterval {
+                    started_at: session.started_at,
+                    ended_at: session.ended_at,
+                    duration_seconds,
+                }
+            })
+            .collect())
+    }
+
+    /// キーワードがOCRテキストに出現した時間を日別・時間帯別に集計
+    ///
+    /// `end_date` までの `days` 日間を対象にする。キーワードは大文字小文字を区別しない
+    pub fn keyword_trend(
+        &self,
+        keywords: &[String],
+        end_date: &str,
+        days: u32,
+    ) -> Result<KeywordTrend, ReportError> {
+        let dates = period_dates(end_date, days)?;
+
+        let mut stats: Vec<KeywordStats> = keywords
+            .iter()
+            .map(|keyword| keyword.trim())
+            .filter(|keyword| !keyword.is_empty())
+            .map(|keyword| KeywordStats {
+                keyword: keyword.to_string(),
+                total_seconds: 0,
+                capture_count: 0,
+                daily: dates
+                    .iter()
+                    .map(|date| KeywordDay {
+                        date: date.clone(),
+                        duration_seconds: 0,
+                    })
+                    .collect(),
+                hourly: [0; 24],
             })
             .collect();
 
-        // 時間の降順でソート
-        summaries.sort_by(|a, b| b.duration_seconds.cmp(&a.duration_seconds));
+        let lowercase: Vec<String> = stats.iter().map(|k| k.keyword.to_lowercase()).collect();
+
+        self.for_each_timed_capture(&dates[0], end_date, |capture, seconds| {
+            if capture.is_private {
+                return;
+            }
+            let Some(text) = capture.ocr_text.as_deref() else {
+                return;
+            };
+            let Some(timestamp) = parse_timestamp(&capture.captured_at) else {
+                return;
+            };
+            let date = self.wall_clock_date(&timestamp);
+            let hour = timestamp.hour() as usize;
+            let text = text.to_lowercase();
+
+            for (keyword, lowercase) in stats.iter_mut().zip(&lowercase) {
+                if !text.contains(lowercase.as_str()) {
+                    continue;
+                }
+                keyword.total_seconds += seconds;
+                keyword.capture_count += 1;
+                keyword.hourly[hour] += seconds;
+                if let Some(day) = keyword.daily.iter_mut().find(|d| d.date == date) {
+                    day.duration_seconds += seconds;
+                }
+            }
+        })?;
+
+        Ok(KeywordTrend {
+            start_date: dates[0].clone(),
+            end_date: end_date.to_string(),
+            keywords: stats,
+        })
+    }
+
+    /// `end_date` までの `days` 日間の日別合計・カテゴリ別の作業時間を集計
+    pub fn trend(&self, end_date: &str, days: u32) -> Result<Trend, ReportError> {
+        let dates = period_dates(end_date, days)?;
+
+        let mut daily = vec![0; dates.len()];
+        let mut media_seconds = 0;
+        let mut categories: HashMap<String, Vec<u64>> = HashMap::new();
+        self.for_each_timed_capture(&dates[0], end_date, |capture, seconds| {
+            let Some(timestamp) = parse_timestamp(&capture.captured_at) else {
+                return;
+            };
+            let date = self.wall_clock_date(&timestamp);
+            let Some(index) = dates.iter().position(|d| *d == date) else {
+                return;
+            };
+            if is_media(&capture) {
+                media_seconds += seconds;
+                return;
+            }
+            if self.is_non_work(&capture) {
+                return;
+            }
+
+            daily[index] += seconds;
+            let category = self
+                .categories
+                .category(&capture.active_app, capture.bundle_id.as_deref());
+            categories
+                .entry(category.to_string())
+                .or_insert_with(|| vec![0; dates.len()])[index] += seconds;
+        })?;
+
+        let mut categories: Vec<CategoryTrend> = categories
+            .into_iter()
+            .map(|(category, daily)| CategoryTrend {
+                category,
+                total_seconds: daily.iter().sum(),
+                daily,
+            })
+            .collect();
+        categories.sort_by(|a, b| {
+            b.total_seconds
+                .cmp(&a.total_seconds)
+                .then_with(|| a.category.cmp(&b.category))
+        });
 
-        Ok(summaries)
+        Ok(Trend {
+            start_date: dates[0].clone(),
+            end_date: end_date.to_string(),
+            total_seconds: daily.iter().sum(),
+            media_seconds,
+            days: dates
+                .into_iter()
+                .zip(daily)
+                .map(|(date, total_seconds)| TrendDay {
+                    date,
+                    total_seconds,
+                })
+                .collect(),
+            categories,
+        })
+    }
+
+    /// 作業時間の推移をスパークラインと棒グラフで出力
+    pub fn print_trend(&self, end_date: &str, days: u32) -> Result<(), ReportError> {
+        let trend = self.trend(end_date, days)?;
+        let daily: Vec<u64> = trend.days.iter().map(|d| d.total_seconds).collect();
+
+        println!(
+            "{}\n",
+            Message::TrendHeading.format(&[&trend.start_date, &trend.end_date])
+        );
+        println!(
+            "{}",
+            Message::TrendDaily.format(&[
+                &sparkline(&daily),
+                &format_duration(trend.total_seconds),
+                &format_duration(trend.total_seconds / trend.days.len() as u64),
+            ])
+        );
+        if trend.media_seconds > 0 {
+            println!(
+                "{}",
+                Message::TrendMedia.format(&[&format_duration(trend.media_seconds)])
+            );
+        }
+
+        // 7日ごとの合計（古い方から）
+        println!("\n{}", Message::WeeklyHeading.text());
+        let weeks: Vec<(&str, u64)> = trend
+            .days
+            .chunks(7)
+            .map(|week| {
+                (
+                    week[0].date.as_str(),
+                    week.iter().map(|d| d.total_seconds).sum(),
+                )
+            })
+            .collect();
+        let max = weeks.iter().map(|(_, seconds)| *seconds).max().unwrap_or(0);
+        for (start, seconds) in &weeks {
+            println!(
+                "{} | {} {}",
+                Message::WeekStarting.format(&[start]),
+                format_bar(*seconds, max),
+                format_duration(*seconds)
+            );
+        }
+
+        if !trend.categories.is_empty() {
+            println!("\n{}", Message::CategoryHeading.text());
+            let width = trend
+                .categories
+                .iter()
+                .map(|c| display_width(&c.category))
+                .max()
+                .unwrap_or(0);
+            for category in &trend.categories {
+                let padding = " ".repeat(width - display_width(&category.category));
+                println!(
+                    "{}{}  {}  {}",
+                    category.category,
+                    padding,
+                    sparkline(&category.daily),
+                    format_duration(category.total_seconds)
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 対象日までの期間のキャプチャ処理時間のp50/p95を集計
+    pub fn perf(&self, end_date: &str, days: u32) -> Result<PerfStats, ReportError> {
+        let dates = period_dates(end_date, days)?;
+        let (start, _) = day_range(&dates[0], self.day_boundary_hour)?;
+        let (_, end) = day_range(&dates[dates.len() - 1], self.day_boundary_hour)?;
+        let metrics = self.db.get_perf_metrics_between(&start, &end)?;
+
+        let step = |name, values: Vec<u64>| {
+            let mut values = values;
+            values.sort_unstable();
+            PerfStep {
+                name,
+                samples: values.len(),
+                p50_ms: percentile(&values, 50),
+                p95_ms: percentile(&values, 95),
+                max_ms: values.last().copied().unwrap_or(0),
+            }
+        };
+        let steps = vec![
+            step(
+                Message::PerfScreenshot.text(),
+                metrics.iter().map(|m| m.screenshot_ms).collect(),
+            ),
+            step(
+                Message::PerfOcr.text(),
+                metrics.iter().filter_map(|m| m.ocr_ms).collect(),
+            ),
+            step(
+                Message::PerfDb.text(),
+                metrics.iter().map(|m| m.db_ms).collect(),
+            ),
+            step(
+                Message::PerfTotal.text(),
+                metrics.iter().map(|m| m.total_ms).collect(),
+            ),
+        ];
+
+        Ok(PerfStats {
+            start_date: dates[0].clone(),
+            end_date: end_date.to_string(),
+            interval_seconds: self.interval_seconds,
+            steps,
+        })
+    }
+
+    /// キャプチャ処理時間の統計を出力
+    pub fn print_perf(&self, end_date: &str, days: u32) -> Result<(), ReportError> {
+        let stats = self.perf(end_date, days)?;
+
+        println!(
+            "{}\n",
+            Message::PerfHeading.format(&[&stats.start_date, &stats.end_date])
+        );
+        let total = stats.steps.last().filter(|step| step.samples > 0);
+        let Some(total) = total else {
+            println!("{}", Message::NoPerfData.text());
+            return Ok(());
+        };
+
+        let width = stats
+            .steps
+            .iter()
+            .map(|step| display_width(step.name))
+            .max()
+            .unwrap_or(0);
+        for step in &stats.steps {
+            let padding = " ".repeat(width - display_width(step.name));
+            if step.samples == 0 {
+                println!(
+                    "{}{}  {}",
+                    step.name,
+                    padding,
+                    Message::PerfNoSamples.text()
+                );
+                continue;
+            }
+            println!(
+                "{}{}  {}",
+                step.name,
+                padding,
+                Message::PerfStep.format(&[
+                    &format!("{:>6}", step.p50_ms),
+                    &format!("{:>6}", step.p95_ms),
+                    &format!("{:>6}", step.max_ms),
+                    &step.samples,
+                ])
+            );
+        }
+
+        // 間隔に対して処理がどれだけの時間を占めているか
+        let interval_ms = stats.interval_seconds * 1000;
+        if interval_ms > 0 {
+            println!(
+                "\n{}",
+                Message::PerfRatio.format(&[
+                    &stats.interval_seconds,
+                    &format!("{:.1}", total.p50_ms as f64 * 100.0 / interval_ms as f64),
+                    &format!("{:.1}", total.p95_ms as f64 * 100.0 / interval_ms as f64),
+                ])
+            );
+        }
+
+        Ok(())
+    }
+
+    /// `end_date` までの `days` 日間に保存した画像の枚数と容量を日別に集計
+    ///
+    /// 記録したファイルサイズを使うため、後から削除した画像も保存した日の増加量に含む。
+    /// サイズを記録する前のキャプチャは、画像が残っていればファイルから求める
+    pub fn storage(&self, end_date: &str, days: u32) -> Result<StorageStats, ReportError> {
+        let dates = period_dates(end_date, days)?;
+        let (start, _) = day_range(&dates[0], self.day_boundary_hour)?;
+        let (_, end) = day_range(&dates[dates.len() - 1], self.day_boundary_hour)?;
+
+        let mut totals = vec![(0, 0); dates.len()];
+        for image in self.db.get_stored_images_between(&start, &end)? {
+            let bytes = image
+                .bytes
+                .or_else(|| fs::metadata(&image.image_path).ok().map(|m| m.len()));
+            let Some(bytes) = bytes else {
+                continue;
+            };
+            let Some(timestamp) = parse_timestamp(&image.captured_at) else {
+                continue;
+            };
+            let date = self.wall_clock_date(&timestamp);
+            let Some(index) = dates.iter().position(|d| *d == date) else {
+                continue;
+            };
+            totals[index].0 += 1;
+            totals[index].1 += bytes;
+        }
+
+        Ok(StorageStats {
+            start_date: dates[0].clone(),
+            end_date: end_date.to_string(),
+            total_images: totals.iter().map(|(images, _)| images).sum(),
+            total_bytes: totals.iter().map(|(_, bytes)| bytes).sum(),
+            days: dates
+                .into_iter()
+                .zip(totals)
+                .map(|(date, (images, bytes))| StorageDay {
+                    date,
+                    images,
+                    bytes,
+                })
+                .collect(),
+        })
+    }
+
+    /// 画像ストレージの日別の増加量を出力
+    pub fn print_storage(&self, end_date: &str, days: u32) -> Result<(), ReportError> {
+        let stats = self.storage(end_date, days)?;
+        let daily: Vec<u64> = stats.days.iter().map(|d| d.bytes).collect();
+
+        println!(
+            "{}\n",
+            Message::StorageHeading.format(&[&stats.start_date, &stats.end_date])
+        );
+        if stats.total_images == 0 {
+            println!("{}", Message::NoStoredImages.text());
+            return Ok(());
+        }
+        println!(
+            "{}\n",
+            Message::StorageDaily.format(&[
+                &sparkline(&daily),
+                &format_bytes(stats.total_bytes),
+                &stats.total_images,
+                &format_bytes(stats.total_bytes / stats.days.len() as u64),
+            ])
+        );
+
+        let max = daily.iter().copied().max().unwrap_or(0);
+        for day in &stats.days {
+            println!(
+                "{} | {:<20} {:>8} ({})",
+                day.date,
+                format_bar(day.bytes, max),
+                format_bytes(day.bytes),
+                Message::StorageImages.format(&[&day.images])
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 境界時刻を考慮した集計上の日付
+    ///
+    /// 対象範囲（`day_range`）と同じく記録時点の壁時計時刻で判定する
+    fn wall_clock_date(&self, timestamp: &DateTime<FixedOffset>) -> String {
+        (timestamp.naive_local() - Duration::hours(self.day_boundary_hour as i64))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    /// キーワード頻度トレンドを出力
+    pub fn print_keyword_trend(
+        &self,
+        keywords: &[String],
+        end_date: &str,
+        days: u32,
+    ) -> Result<(), ReportError> {
+        let trend = self.keyword_trend(keywords, end_date, days)?;
+
+        println!(
+            "{}",
+            Message::KeywordHeading.format(&[&trend.start_date, &trend.end_date])
+        );
+        for stats in &trend.keywords {
+            println!(
+                "\n{}",
+                Message::KeywordTotal.format(&[
+                    &stats.keyword,
+                    &format_duration(stats.total_seconds),
+                    &Message::Captures.format(&[&stats.capture_count]),
+                ])
+            );
+            if stats.capture_count == 0 {
+                continue;
+            }
+
+            println!("{}", Message::KeywordDaily.text());
+            let max = stats.daily.iter().map(|d| d.duration_seconds).max();
+            for day in &stats.daily {
+                println!(
+                    "  {} | {} {}",
+                    day.date,
+                    format_bar(day.duration_seconds, max.unwrap_or(0)),
+                    format_duration(day.duration_seconds)
+                );
+            }
+
+            println!("{}", Message::KeywordHourly.text());
+            let max = stats.hourly.iter().copied().max().unwrap_or(0);
+            for (hour, &seconds) in stats.hourly.iter().enumerate() {
+                if seconds > 0 {
+                    println!(
+                        "  {} | {} {}",
+                        Message::Hour.format(&[&format!("{:02}", hour)]),
+                        format_bar(seconds, max),
+                        format_duration(seconds)
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// OCRテキストを検索
+    pub fn search(&self, query: &str, limit: i64) -> Result<Vec<SearchHit>, ReportError> {
+        let captures = self.db.search_ocr_text(query, limit)?;
+
+        let hits = captures
+            .into_iter()
+            .map(|c| {
+                let snippet = c
+                    .ocr_text
+                    .as_deref()
+                    .map(|text| extract_snippet(text, query))
+                    .unwrap_or_default();
+                SearchHit {
+                    captured_at: c.captured_at,
+                    active_app: c.active_app,
+                    window_title: c.window_title,
+                    snippet,
+                }
+            })
+            .collect();
+
+        Ok(hits)
     }
 
     /// レポートを出力
     pub fn print(&self, date: &str) -> Result<(), ReportError> {
         let timeline = self.timeline(date)?;
         let summaries = self.time_by_app(date)?;
+        let media = self.media_by_app(date)?;
+        let non_work = self.non_work_by_app(date)?;
+        let paused = self.paused_by_app(date)?;
+        let distractions = self.distraction_by_app(date)?;
+        let sleeps = self.sleeps(date)?;
+        let highlights = self.highlights(date)?;
+        let notes = self.notes(date)?;
+        let goals = self.goals(date)?;
 
         if timeline.is_empty() {
-            println!("{}にキャプチャはありませんでした。", date);
+            println!("{}", Message::NoCaptures.format(&[&date]));
             return Ok(());
         }
 
-        println!("=== {} の活動レポート ===\n", date);
+        println!("{}", Message::DailyHeading.format(&[&date]));
+        let kpi = self.kpi(date)?;
+        println!("{}", format_kpi(&kpi));
+        if kpi.paused_seconds > 0 || kpi.private_seconds > 0 {
+            println!(
+                "{}",
+                Message::PausedPrivateSummary.format(&[
+                    &format_duration(kpi.paused_seconds),
+                    &format_duration(kpi.private_seconds),
+                ])
+            );
+        }
+        if kpi.idle_seconds > 0 {
+            println!(
+                "{}",
+                Message::IdleSummary.format(&[&format_duration(kpi.idle_seconds)])
+            );
+        }
+        println!();
+
+        if !goals.is_empty() {
+            println!("{}", Message::GoalsHeading.text());
+            for progress in &goals {
+                println!("{}", format_goal(progress));
+            }
+            println!();
+        }
 
         // タイムライン
-        println!("--- タイムライン ---");
+        // キャプチャのない区間のうちスリープだったものを明示する
+        println!("{}", Message::TimelineHeading.text());
+        let mut sleeps = sleeps.iter().peekable();
+        let mut last_excerpt = None;
         for entry in &timeline {
+            while let Some(sleep) = sleeps.next_if(|s| s.started_at < entry.captured_at) {
+                println!("{}", format_sleep(sleep));
+            }
             let title_display = if entry.window_title.is_empty() {
                 String::new()
             } else {
                 format!(" - {}", entry.window_title)
             };
-            println!("{} | {}{}", entry.time, entry.active_app, title_display);
+            let media_mark = if entry.activity_type.as_deref() == Some(ACTIVITY_TYPE_MEDIA) {
+                Message::MediaMark.text()
+            } else {
+                ""
+            };
+            println!(
+                "{} | {}{}{}",
+                entry.time, entry.active_app, title_display, media_mark
+            );
+            // 同じ画面が続く間は抜粋を繰り返さない
+            if let Some(ref excerpt) = entry.ocr_excerpt {
+                if last_excerpt != Some(excerpt) {
+                    println!("{:8} |   > {}", "", excerpt);
+                    last_excerpt = Some(excerpt);
+                }
+            }
+        }
+        for sleep in sleeps {
+            println!("{}", format_sleep(sleep));
         }
 
         println!();
 
-        // アプリ別時間
-        println!("--- アプリ別時間 ---");
-        for summary in &summaries {
-            let duration = format_duration(summary.duration_seconds);
-            println!(
-                "{}: {} ({} キャプチャ)",
-                summary.app_name, duration, summary.capture_count
-            );
-        }
+        // アプリ別時間
+        println!("{}", Message::AppsHeading.text());
+        for summary in &summaries {
+            println!("{}", format_app_summary(summary));
+        }
+
+        if !media.is_empty() {
+            println!("\n{}", Message::MediaHeading.text());
+            for summary in &media {
+                println!("{}", format_app_summary(summary));
+            }
+        }
+
+        if !non_work.is_empty() {
+            println!("\n{}", Message::NonWorkHeading.text());
+            for summary in &non_work {
+                println!("{}", format_app_summary(summary));
+            }
+        }
+
+        if !paused.is_empty() {
+            println!("\n{}", Message::PausedHeading.text());
+            for summary in &paused {
+                println!("{}", format_app_summary(summary));
+            }
+        }
+
+        if !distractions.is_empty() {
+            let total: u64 = distractions.iter().map(|s| s.duration_seconds).sum();
+            println!(
+                "\n{}",
+                Message::DistractionHeading.format(&[&format_duration(total)])
+            );
+            for summary in &distractions {
+                println!("{}", format_app_summary(summary));
+            }
+        }
+
+        if !highlights.is_empty() {
+            println!("\n{}", Message::HighlightsHeading.text());
+            for highlight in &highlights {
+                println!("{}", format_highlight(highlight));
+            }
+        }
+
+        if !notes.is_empty() {
+            println!("\n{}", Message::NotesHeading.text());
+            for note in &notes {
+                println!("{}", format_note(note));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 同じアプリを続けて使った区間のうち、最短の時間以上続いたものの合計
+struct FocusCounter {
+    min_seconds: u64,
+    /// 続けて使っている（アプリ, 合計秒数, 最後のキャプチャ時刻）
+    current: Option<(String, u64, DateTime<FixedOffset>)>,
+    total_seconds: u64,
+}
+
+impl FocusCounter {
+    fn new(min_seconds: u64) -> Self {
+        Self {
+            min_seconds,
+            current: None,
+            total_seconds: 0,
+        }
+    }
+
+    /// キャプチャを記録する（アプリが変わるか、間隔が空いたら区間を区切る）
+    fn observe(&mut self, app: &str, at: DateTime<FixedOffset>, seconds: u64) {
+        match self.current {
+            Some((ref current_app, ref mut total, ref mut last))
+                if current_app == app && (at - *last).num_seconds() <= BREAK_GAP_SECONDS =>
+            {
+                *total += seconds;
+                *last = at;
+            }
+            _ => {
+                self.interrupt();
+                self.current = Some((app.to_string(), seconds, at));
+            }
+        }
+    }
+
+    /// 区間を区切る
+    fn interrupt(&mut self) {
+        if let Some((_, seconds, _)) = self.current.take() {
+            if seconds >= self.min_seconds {
+                self.total_seconds += seconds;
+            }
+        }
+    }
+
+    /// 最後の区間を区切って合計を返す
+    fn finish(mut self) -> u64 {
+        self.interrupt();
+        self.total_seconds
+    }
+}
+
+/// キャプチャの記録時間（秒）
+///
+/// 次のキャプチャまでの実際の時間差を使い、キャプチャの間隔（記録がなければ
+/// `default_interval`）を上限とする。一時停止・スリープ・欠測で空いた分は含めず、
+/// 即時キャプチャなどで間隔より早く次が来た場合は重複して数えない。
+/// 次のキャプチャがない（期間の最後の）場合は間隔をそのまま使う
+pub fn elapsed_seconds(
+    capture: &CaptureRecord,
+    next: Option<&CaptureRecord>,
+    default_interval: u64,
+) -> u64 {
+    let interval = capture.interval_seconds.unwrap_or(default_interval);
+    let gap = next.and_then(|next| {
+        let start = parse_timestamp(&capture.captured_at)?;
+        let end = parse_timestamp(&next.captured_at)?;
+        Some((end - start).num_seconds().max(0) as u64)
+    });
+    gap.map_or(interval, |gap| gap.min(interval))
+}
+
+/// フルスクリーンでの動画視聴のキャプチャか
+fn is_media(capture: &CaptureRecord) -> bool {
+    capture.activity_type.as_deref() == Some(ACTIVITY_TYPE_MEDIA)
+}
+
+/// アプリごとの（合計秒数, キャプチャ数）を時間の降順に並べる
+fn sorted_summaries(app_totals: HashMap<String, (u64, u64)>) -> Vec<AppSummary> {
+    let mut summaries: Vec<AppSummary> = app_totals
+        .into_iter()
+        .map(|(app_name, (duration_seconds, count))| AppSummary {
+            app_name,
+            duration_seconds,
+            capture_count: count,
+        })
+        .collect();
+    summaries.sort_by_key(|s| Reverse(s.duration_seconds));
+    summaries
+}
+
+/// 日付文字列（YYYY-MM-DD）を検証
+pub fn validate_date(date: &str) -> Result<(), ReportError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| ReportError::InvalidDate(date.to_string()))
+}
+
+/// 日付指定を解決してYYYY-MM-DDを返す
+///
+/// `today` / `yesterday` / `-2d` / `-1w` / `last-friday`（今日より前の直近の金曜日）と
+/// YYYY-MM-DD形式を受け付ける。`today` は境界時刻を考慮した今日の日付
+pub fn resolve_date(spec: &str, today: &str) -> Result<String, ReportError> {
+    let invalid = || ReportError::InvalidDate(spec.to_string());
+    let spec = spec.trim().to_lowercase();
+    let today = NaiveDate::parse_from_str(today, "%Y-%m-%d")
+        .map_err(|_| ReportError::InvalidDate(today.to_string()))?;
+
+    let date = match spec.as_str() {
+        "today" => today,
+        "yesterday" => today.checked_sub_days(Days::new(1)).ok_or_else(invalid)?,
+        _ => {
+            if let Some(offset) = spec.strip_prefix('-') {
+                let days = if let Some(number) = offset.strip_suffix('d') {
+                    number.parse::<u64>().map_err(|_| invalid())?
+                } else if let Some(number) = offset.strip_suffix('w') {
+                    let number: u64 = number.parse().map_err(|_| invalid())?;
+                    number.checked_mul(7).ok_or_else(invalid)?
+                } else {
+                    return Err(invalid());
+                };
+                today
+                    .checked_sub_days(Days::new(days))
+                    .ok_or_else(invalid)?
+            } else if let Some(weekday) = spec.strip_prefix("last-") {
+                let weekday: Weekday = weekday.parse().map_err(|_| invalid())?;
+                let back = (today.weekday().num_days_from_monday() + 7
+                    - weekday.num_days_from_monday())
+                    % 7;
+                let back = if back == 0 { 7 } else { back };
+                today
+                    .checked_sub_days(Days::new(back as u64))
+                    .ok_or_else(invalid)?
+            } else {
+                NaiveDate::parse_from_str(&spec, "%Y-%m-%d").map_err(|_| invalid())?
+            }
+        }
+    };
+    Ok(date.format("%Y-%m-%d").to_string())
+}
+
+/// 境界時刻を考慮した集計上の日付（YYYY-MM-DD）を返す
+pub fn logical_date(timestamp: &DateTime<Local>, boundary_hour: u32) -> String {
+    (timestamp.naive_local() - Duration::hours(boundary_hour as i64))
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// `end_date` までの `days` 日間（1日以上）の日付を古い順に返す
+fn period_dates(end_date: &str, days: u32) -> Result<Vec<String>, ReportError> {
+    let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ReportError::InvalidDate(end_date.to_string()))?;
+    (0..days.max(1))
+        .rev()
+        .map(|offset| {
+            end.checked_sub_days(Days::new(offset as u64))
+                .map(|day| day.format("%Y-%m-%d").to_string())
+                .ok_or_else(|| ReportError::InvalidDate(end_date.to_string()))
+        })
+        .collect()
+}
+
+/// 昇順に並んだ値の `p` パーセンタイル（最近傍順位法、空なら0）
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * p).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// 期間指定（`4w` は4週間、`10d` は10日間）を日数に変換
+///
+/// 長すぎる期間は日付の範囲を作りきれないため、`MAX_PERIOD_DAYS` 日までに制限する
+pub fn parse_period(period: &str) -> Result<u32, ReportError> {
+    let period = period.trim();
+    let invalid = || ReportError::InvalidPeriod(period.to_string());
+    let (number, multiplier) = if let Some(number) = period.strip_suffix(['w', 'W']) {
+        (number, 7)
+    } else if let Some(number) = period.strip_suffix(['d', 'D']) {
+        (number, 1)
+    } else {
+        return Err(invalid());
+    };
+    let days = number
+        .parse::<u32>()
+        .ok()
+        .filter(|n| *n > 0)
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(invalid)?;
+    if days > MAX_PERIOD_DAYS {
+        return Err(ReportError::PeriodTooLong(MAX_PERIOD_DAYS));
+    }
+    Ok(days)
+}
+
+/// 値の推移を1文字1日のスパークラインにする（0は空白）
+fn sparkline(values: &[u64]) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| {
+            if value == 0 {
+                ' '
+            } else {
+                let level = (value * LEVELS.len() as u64).div_ceil(max) as usize;
+                LEVELS[level.clamp(1, LEVELS.len()) - 1]
+            }
+        })
+        .collect()
+}
+
+/// 端末上の表示幅（全角2、半角1）
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// 指定日の範囲 `[開始, 終了)` を壁時計時刻の文字列で返す
+///
+/// 保存形式の先頭19文字（YYYY-MM-DDTHH:MM:SS）と辞書順で比較できる
+fn day_range(date: &str, boundary_hour: u32) -> Result<(String, String), ReportError> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| ReportError::InvalidDate(date.to_string()))?;
+    let next = day
+        .checked_add_days(Days::new(1))
+        .ok_or_else(|| ReportError::InvalidDate(date.to_string()))?;
+
+    let format = |d: NaiveDate| format!("{}T{:02}:00:00", d.format("%Y-%m-%d"), boundary_hour);
+    Ok((format(day), format(next)))
+}
+
+/// 検索語を含む行を抜粋（前後を切り詰め）
+fn extract_snippet(text: &str, query: &str) -> String {
+    const MAX_CHARS: usize = 120;
+
+    let query_lower = query.to_lowercase();
+    let line = text
+        .lines()
+        .find(|line| line.to_lowercase().contains(&query_lower))
+        .unwrap_or_else(|| text.lines().next().unwrap_or(""));
+
+    if line.chars().count() > MAX_CHARS {
+        let truncated: String = line.chars().take(MAX_CHARS).collect();
+        format!("{}...", truncated)
+    } else {
+        line.to_string()
+    }
+}
+
+/// タイムラインに添えるOCRテキストの抜粋（空白・改行をまとめて先頭を切り出す、空なら `None`）
+fn ocr_excerpt(text: &str) -> Option<String> {
+    const MAX_CHARS: usize = 100;
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        None
+    } else if text.chars().count() > MAX_CHARS {
+        let truncated: String = text.chars().take(MAX_CHARS).collect();
+        Some(format!("{}...", truncated))
+    } else {
+        Some(text)
+    }
+}
+
+/// タイムスタンプから時刻部分を抽出
+///
+/// 記録時点のローカル時刻（オフセット適用後の壁時計時刻）を表示する
+fn extract_time(timestamp: &str) -> String {
+    if let Some(parsed) = parse_timestamp(timestamp) {
+        parsed.format("%H:%M:%S").to_string()
+    } else if let Some(time_part) = timestamp.split('T').nth(1) {
+        time_part.to_string()
+    } else {
+        timestamp.to_string()
+    }
+}
+
+/// 作業メモを1行にフォーマット
+pub fn format_note(note: &Note) -> String {
+    format!("{} | {}", extract_time(&note.created_at), note.text)
+}
+
+/// スリープ区間をタイムライン用の1行にフォーマット
+fn format_sleep(sleep: &SleepInterval) -> String {
+    let start = extract_time(&sleep.started_at);
+    match (&sleep.ended_at, sleep.duration_seconds) {
+        (Some(ended_at), Some(seconds)) => {
+            Message::Sleep.format(&[&start, &extract_time(ended_at), &format_duration(seconds)])
+        }
+        _ => Message::SleepOngoing.format(&[&start]),
+    }
+}
+
+/// KPIの1行（`総作業 6時間12分 / 会議 1時間30分 / ...`）
+pub fn format_kpi(kpi: &DailyKpi) -> String {
+    let mut summary = Message::KpiSummary.format(&[
+        &format_duration(kpi.work_seconds),
+        &format_duration(kpi.meeting_seconds),
+        &format_duration(kpi.focus_seconds),
+        &format_duration(kpi.distraction_seconds),
+        &kpi.context_switches,
+    ]);
+    if kpi.pomodoros > 0 {
+        summary.push_str(&Message::KpiPomodoros.format(&[&kpi.pomodoros]));
+    }
+    summary
+}
+
+/// 代表画像の1行（`N時 | アプリ - タイトル | 画像のパス`）
+pub fn format_highlight(highlight: &Highlight) -> String {
+    let hour = highlight
+        .hour
+        .get(11..13)
+        .and_then(|hour| hour.parse::<u32>().ok())
+        .map_or_else(
+            || highlight.hour.clone(),
+            |hour| Message::Hour.format(&[&hour]),
+        );
+    let title_display = if highlight.window_title.is_empty() {
+        String::new()
+    } else {
+        format!(" - {}", highlight.window_title)
+    };
+    format!(
+        "{} | {}{} | {}",
+        hour, highlight.active_app, title_display, highlight.image_path
+    )
+}
+
+/// 時間帯別の内訳の1行（`10時 | N キャプチャ | アプリ 時間, ...`）
+fn format_hourly(hourly: &HourlyBreakdown) -> String {
+    let apps: Vec<String> = hourly
+        .top_apps
+        .iter()
+        .map(|app| format!("{} {}", app.app_name, format_duration(app.duration_seconds)))
+        .collect();
+    format!(
+        "{} | {} | {}",
+        Message::Hour.format(&[&format!("{:02}", hourly.hour)]),
+        Message::Captures.format(&[&hourly.capture_count]),
+        apps.join(", ")
+    )
+}
+
+/// アプリ別時間の1行（`アプリ: 時間 (N キャプチャ)`）
+fn format_app_summary(summary: &AppSummary) -> String {
+    format!(
+        "{}: {} ({})",
+        summary.app_name,
+        format_duration(summary.duration_seconds),
+        Message::Captures.format(&[&summary.capture_count])
+    )
+}
+
+/// 最大値に対する割合を棒グラフにする（最大20文字）
+fn format_bar(value: u64, max: u64) -> String {
+    const WIDTH: u64 = 20;
 
-        Ok(())
+    if max == 0 {
+        return String::new();
     }
+    let len = (value * WIDTH).div_ceil(max);
+    "█".repeat(len as usize)
 }
 
-/// タイムスタンプから時刻部分を抽出
-fn extract_time(timestamp: &str) -> String {
-    if let Some(time_part) = timestamp.split('T').nth(1) {
-        time_part.to_string()
+/// バイト数をKB/MB/GB単位で表示
+pub fn format_bytes(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    const GB: u64 = 1024 * MB;
+
+    if bytes >= GB {
+        format!("{:.1}GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1}MB", bytes as f64 / MB as f64)
     } else {
-        timestamp.to_string()
+        format!("{:.1}KB", bytes as f64 / 1024.0)
     }
 }
 
 /// 秒を「○時間○分」形式にフォーマット
-fn format_duration(seconds: u64) -> String {
+pub fn format_duration(seconds: u64) -> String {
     let hours = seconds / 3600;
     let minutes = (seconds % 3600) / 60;
 
     if hours > 0 {
-        format!("{}時間{}分", hours, minutes)
+        Message::DurationHours.format(&[&hours, &minutes])
     } else {
-        format!("{}分", minutes)
+        Message::DurationMinutes.format(&[&minutes])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::category::UNCATEGORIZED;
+    use crate::database::{CaptureRecord, ImageInfo, PerfMetrics, SessionRecord};
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
     use tempfile::TempDir;
 
     fn create_test_db_with_data() -> (Database, TempDir) {
@@ -158,6 +1971,12 @@ mod tests {
                 is_paused: false,
                 is_private: false,
                 ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
             },
             CaptureRecord {
                 id: None,
@@ -168,6 +1987,12 @@ mod tests {
                 is_paused: false,
                 is_private: false,
                 ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
             },
             CaptureRecord {
                 id: None,
@@ -178,6 +2003,12 @@ mod tests {
                 is_paused: false,
                 is_private: false,
                 ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
             },
         ];
 
@@ -197,6 +2028,32 @@ mod tests {
         assert_eq!(timeline.len(), 3);
         assert_eq!(timeline[0].active_app, "VS Code");
         assert_eq!(timeline[0].time, "10:00:00");
+        assert_eq!(timeline[0].ocr_excerpt, None);
+    }
+
+    #[test]
+    fn test_timeline_ocr_excerpts() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.update_ocr_text(1, "Untitled\n\n  議事録   の下書き")
+            .unwrap();
+        db.update_ocr_text(2, &"あ".repeat(150)).unwrap();
+        db.update_ocr_text(3, " \n ").unwrap();
+
+        // 有効にしなければ抜粋は添えない
+        let report = Report::new(db, 60);
+        assert_eq!(report.timeline("2024-12-30").unwrap()[0].ocr_excerpt, None);
+
+        let report = report.with_ocr_excerpts(true);
+        let timeline = report.timeline("2024-12-30").unwrap();
+        assert_eq!(
+            timeline[0].ocr_excerpt.as_deref(),
+            Some("Untitled 議事録 の下書き")
+        );
+        assert_eq!(
+            timeline[1].ocr_excerpt,
+            Some(format!("{}...", "あ".repeat(100)))
+        );
+        assert_eq!(timeline[2].ocr_excerpt, None);
     }
 
     #[test]
@@ -219,6 +2076,553 @@ mod tests {
         assert_eq!(summaries[1].duration_seconds, 60);
     }
 
+    #[test]
+    fn test_hourly_by_app() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T11:00:00".to_string(),
+            image_path: None,
+            active_app: "Chrome".to_string(),
+            window_title: "Google".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        let report = Report::new(db, 60);
+
+        let apps = report.hourly_by_app("2024-12-30").unwrap();
+        assert_eq!(apps.len(), 2);
+        // 時間が同じならアプリ名順
+        assert_eq!(apps[0].app_name, "Chrome");
+        assert_eq!(apps[0].duration_seconds, 120);
+        assert_eq!((apps[0].hourly[10], apps[0].hourly[11]), (60, 60));
+        assert_eq!(apps[1].app_name, "VS Code");
+        assert_eq!(apps[1].hourly[10], 120);
+    }
+
+    #[test]
+    fn test_hourly_breakdown() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        for (time, app) in [
+            ("2024-12-30T11:00:00", "Chrome"),
+            ("2024-12-30T11:01:00", "Slack"),
+            ("2024-12-30T11:02:00", "Finder"),
+            ("2024-12-30T11:03:00", "Terminal"),
+            ("2024-12-30T11:04:00", "Terminal"),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: String::new(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60);
+
+        let hours = report.hourly_breakdown("2024-12-30").unwrap();
+        assert_eq!(hours.len(), 2);
+        assert_eq!(hours[0].hour, 10);
+        assert_eq!(hours[0].capture_count, 3);
+        assert_eq!(hours[1].hour, 11);
+        assert_eq!(hours[1].capture_count, 5);
+        assert_eq!(hours[1].duration_seconds, 300);
+        // 時間の長い順に上位3つ（同じならアプリ名順）
+        let apps: Vec<_> = hours[1]
+            .top_apps
+            .iter()
+            .map(|a| a.app_name.as_str())
+            .collect();
+        assert_eq!(apps, ["Terminal", "Chrome", "Finder"]);
+        assert_eq!(
+            format_hourly(&hours[1]),
+            format!(
+                "{} | {} | Terminal {}, Chrome {}, Finder {}",
+                Message::Hour.format(&[&"11"]),
+                Message::Captures.format(&[&5]),
+                format_duration(120),
+                format_duration(60),
+                format_duration(60)
+            )
+        );
+    }
+
+    #[test]
+    fn test_time_by_app_uses_recorded_interval() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:03:00".to_string(),
+            image_path: None,
+            active_app: "Chrome".to_string(),
+            window_title: "Google".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(240),
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        let report = Report::new(db, 60);
+
+        let summaries = report.time_by_app("2024-12-30").unwrap();
+        assert_eq!(summaries[0].app_name, "Chrome");
+        assert_eq!(summaries[0].capture_count, 2);
+        assert_eq!(summaries[0].duration_seconds, 300); // 60 + 240
+    }
+
+    #[test]
+    fn test_elapsed_seconds_clamped_to_interval() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        // 10:02のChromeの次は20秒後（即時キャプチャ）、その次は2時間後（スリープ明け）
+        for captured_at in ["2024-12-30T10:02:20", "2024-12-30T12:00:00"] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: captured_at.to_string(),
+                image_path: None,
+                active_app: "Slack".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        // 絞り込みで除いたアプリのキャプチャも区切りに使う
+        let report = Report::new(db, 60).with_app_filter(AppFilter::new(
+            vec!["Chrome".into(), "Slack".into()],
+            vec![],
+        ));
+
+        let summaries = report.time_by_app("2024-12-30").unwrap();
+        let seconds = |app: &str| {
+            summaries
+                .iter()
+                .find(|s| s.app_name == app)
+                .map(|s| s.duration_seconds)
+        };
+        assert_eq!(seconds("Chrome"), Some(20));
+        // 2時間の空白は間隔（60秒）で打ち切り、最後のキャプチャは間隔をそのまま使う
+        assert_eq!(seconds("Slack"), Some(120));
+    }
+
+    #[test]
+    fn test_daily_report() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        let report = Report::new(db, 60);
+
+        let daily = report.daily("2024-12-30").unwrap();
+        assert_eq!(daily.date, "2024-12-30");
+        assert_eq!(daily.total_seconds, 180);
+        assert_eq!(daily.timeline.len(), 3);
+        assert_eq!(daily.apps.len(), 2);
+    }
+
+    #[test]
+    fn test_sleeps() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_session(&SessionRecord {
+            id: None,
+            kind: SessionKind::Sleep,
+            started_at: "2024-12-30T12:00:00+09:00".to_string(),
+            ended_at: Some("2024-12-30T13:30:00+09:00".to_string()),
+            version: None,
+            hostname: None,
+            end_reason: None,
+        })
+        .unwrap();
+        let report = Report::new(db, 60);
+
+        let sleeps = report.sleeps("2024-12-30").unwrap();
+        assert_eq!(sleeps.len(), 1);
+        assert_eq!(sleeps[0].duration_seconds, Some(5400));
+        assert_eq!(
+            format_sleep(&sleeps[0]),
+            "12:00:00 - 13:30:00 | (スリープ 1時間30分)"
+        );
+        assert!(report.sleeps("2024-12-31").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_keyword_trend() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let captures = [
+            ("2025-01-09T10:00:00+09:00", "Review PR #12", false),
+            ("2025-01-10T10:00:00+09:00", "pr review", false),
+            ("2025-01-10T14:00:00+09:00", "設計ドキュメント", false),
+            ("2025-01-10T15:00:00+09:00", "PR 設計", true),
+        ];
+        for (captured_at, text, is_private) in captures {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: captured_at.to_string(),
+                image_path: None,
+                active_app: "Safari".to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private,
+                ocr_text: Some(text.to_string()),
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60);
+
+        let keywords = vec!["PR".to_string(), " 設計".to_string(), "".to_string()];
+        let trend = report.keyword_trend(&keywords, "2025-01-10", 3).unwrap();
+        assert_eq!(trend.start_date, "2025-01-08");
+        assert_eq!(trend.keywords.len(), 2);
+
+        // 大文字小文字を区別せず、プライベートなキャプチャは数えない
+        let pr = &trend.keywords[0];
+        assert_eq!(pr.keyword, "PR");
+        assert_eq!(pr.total_seconds, 120);
+        let daily: Vec<u64> = pr.daily.iter().map(|d| d.duration_seconds).collect();
+        assert_eq!(daily, vec![0, 60, 60]);
+        assert_eq!(pr.hourly[10], 120);
+
+        let design = &trend.keywords[1];
+        assert_eq!(design.keyword, "設計");
+        assert_eq!(design.capture_count, 1);
+        assert_eq!(design.hourly[14], 60);
+    }
+
+    #[test]
+    fn test_trend() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-28T09:00:00".to_string(),
+            image_path: None,
+            active_app: "Slack".to_string(),
+            window_title: "".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(300),
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        let categories = Categories::new(BTreeMap::from([(
+            "開発".to_string(),
+            vec!["VS Code".to_string()],
+        )]));
+        let report = Report::new(db, 60).with_categories(categories);
+
+        let trend = report.trend("2024-12-30", 3).unwrap();
+        assert_eq!(trend.start_date, "2024-12-28");
+        assert_eq!(trend.total_seconds, 480);
+        let daily: Vec<u64> = trend.days.iter().map(|d| d.total_seconds).collect();
+        assert_eq!(daily, vec![300, 0, 180]);
+
+        assert_eq!(trend.categories[0].category, UNCATEGORIZED);
+        assert_eq!(trend.categories[0].daily, vec![300, 0, 60]);
+        assert_eq!(trend.categories[1].category, "開発");
+        assert_eq!(trend.categories[1].daily, vec![0, 0, 120]);
+    }
+
+    #[test]
+    fn test_goals() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-28T09:00:00".to_string(),
+            image_path: None,
+            active_app: "Slack".to_string(),
+            window_title: "".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(300),
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        let categories = Categories::new(BTreeMap::from([(
+            "開発".to_string(),
+            vec!["VS Code".to_string()],
+        )]));
+        let goals = Goals::new(BTreeMap::from([
+            ("開発".to_string(), 2),
+            (TOTAL_GOAL.to_string(), 5),
+        ]));
+        let report = Report::new(db, 60)
+            .with_categories(categories)
+            .with_goals(goals);
+
+        // 最初のキャプチャの日（12/28）から記録して数える
+        let goals = report.goals("2024-12-30").unwrap();
+        assert_eq!(goals.len(), 2);
+        assert_eq!(goals[0].goal, TOTAL_GOAL);
+        assert_eq!((goals[0].seconds, goals[0].achieved), (180, false));
+        assert_eq!((goals[0].current_streak, goals[0].longest_streak), (0, 1));
+        assert_eq!(goals[1].goal, "開発");
+        assert_eq!((goals[1].seconds, goals[1].achieved), (120, true));
+        assert_eq!((goals[1].current_streak, goals[1].longest_streak), (1, 1));
+        assert_eq!(
+            report
+                .db
+                .get_goal_results("開発", "2024-12-30")
+                .unwrap()
+                .len(),
+            3
+        );
+
+        // アプリを絞り込むと作業時間が変わるため集計しない
+        let report = Report::new(report.db, 60)
+            .with_app_filter(AppFilter::new(vec!["VS Code".into()], vec![]))
+            .with_goals(Goals::new(BTreeMap::from([("開発".to_string(), 2)])));
+        assert!(report.goals("2024-12-30").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_date() {
+        // 2025-01-15は水曜日
+        let today = "2025-01-15";
+        assert_eq!(resolve_date("today", today).unwrap(), "2025-01-15");
+        assert_eq!(resolve_date("Yesterday", today).unwrap(), "2025-01-14");
+        assert_eq!(resolve_date("-2d", today).unwrap(), "2025-01-13");
+        assert_eq!(resolve_date("-1w", today).unwrap(), "2025-01-08");
+        assert_eq!(resolve_date("last-friday", today).unwrap(), "2025-01-10");
+        assert_eq!(resolve_date("last-mon", today).unwrap(), "2025-01-13");
+        // 同じ曜日なら1週間前
+        assert_eq!(resolve_date("last-wednesday", today).unwrap(), "2025-01-08");
+        assert_eq!(resolve_date("2024-12-30", today).unwrap(), "2024-12-30");
+
+        // 複数バイトの単位（`-2日` など）もパニックせずにエラーにする
+        for spec in [
+            "tomorrow",
+            "-2m",
+            "last-someday",
+            "2024-13-01",
+            "-d",
+            "-2日",
+            "-日",
+        ] {
+            assert!(matches!(
+                resolve_date(spec, today),
+                Err(ReportError::InvalidDate(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_capture_dates() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-31T02:00:00".to_string(),
+            image_path: None,
+            active_app: "Slack".to_string(),
+            window_title: "".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+
+        let report = Report::new(db, 60);
+        assert_eq!(
+            report.capture_dates(10).unwrap(),
+            vec!["2024-12-31", "2024-12-30"]
+        );
+        assert_eq!(report.capture_dates(1).unwrap(), vec!["2024-12-31"]);
+
+        // 境界時刻より前の深夜は前日に含める
+        let report = Report::new(report.db, 60).with_day_boundary(4);
+        assert_eq!(report.capture_dates(10).unwrap(), vec!["2024-12-30"]);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&values, 50), 50);
+        assert_eq!(percentile(&values, 95), 95);
+        assert_eq!(percentile(&[7], 95), 7);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+
+    #[test]
+    fn test_perf() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        let captures = db.get_captures_by_date("2024-12-30").unwrap();
+        for (i, capture) in captures.iter().enumerate() {
+            let ms = (i as u64 + 1) * 100;
+            db.insert_perf_metrics(
+                capture.id.unwrap(),
+                &PerfMetrics {
+                    screenshot_ms: ms,
+                    ocr_ms: (i == 0).then_some(2000),
+                    db_ms: 5,
+                    total_ms: ms + 10,
+                },
+            )
+            .unwrap();
+        }
+        let report = Report::new(db, 60);
+
+        let stats = report.perf("2024-12-30", 1).unwrap();
+        assert_eq!(stats.start_date, "2024-12-30");
+        let screenshot = &stats.steps[0];
+        assert_eq!(screenshot.samples, captures.len());
+        assert_eq!(screenshot.p50_ms, 200);
+        assert_eq!(screenshot.max_ms, captures.len() as u64 * 100);
+        // OCRを行わなかったキャプチャは含めない
+        assert_eq!(stats.steps[1].samples, 1);
+        assert_eq!(stats.steps[1].p95_ms, 2000);
+
+        let empty = report.perf("2024-12-20", 1).unwrap();
+        assert!(empty.steps.iter().all(|step| step.samples == 0));
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("4w").unwrap(), 28);
+        assert_eq!(parse_period("10d").unwrap(), 10);
+        assert!(parse_period("0w").is_err());
+        assert!(parse_period("4").is_err());
+        assert!(parse_period("w").is_err());
+        assert!(parse_period("").is_err());
+        // 複数バイトの単位でもパニックしない
+        for period in ["4週", "週", "10日"] {
+            assert!(matches!(
+                parse_period(period),
+                Err(ReportError::InvalidPeriod(_))
+            ));
+        }
+        assert_eq!(parse_period("366d").unwrap(), MAX_PERIOD_DAYS);
+        assert!(matches!(
+            parse_period("53w"),
+            Err(ReportError::PeriodTooLong(_))
+        ));
+        assert!(parse_period("4294967295d").is_err());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 1, 4, 8]), " ▁▄█");
+        assert_eq!(sparkline(&[0, 0]), "  ");
+        assert_eq!(display_width("開発a"), 5);
+    }
+
+    #[test]
+    fn test_format_bar() {
+        assert_eq!(format_bar(10, 10), "█".repeat(20));
+        assert_eq!(format_bar(1, 100), "█");
+        assert_eq!(format_bar(0, 10), "");
+        assert_eq!(format_bar(0, 0), "");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "0.5KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0MB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024 / 2), "2.5GB");
+    }
+
+    #[test]
+    fn test_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        // サイズ記録前のキャプチャの画像
+        let legacy_image = temp_dir.path().join("legacy.jpg");
+        fs::write(&legacy_image, [0u8; 300]).unwrap();
+
+        for (time, image_path, bytes) in [
+            ("2025-01-08T10:00:00", "/images/1.jpg", Some(1000)),
+            ("2025-01-10T03:00:00", "/images/2.jpg", Some(2000)),
+            ("2025-01-10T10:00:00", "/images/3.jpg", Some(4000)),
+            ("2025-01-10T11:00:00", legacy_image.to_str().unwrap(), None),
+            ("2025-01-10T12:00:00", "/missing.jpg", None),
+        ] {
+            let id = db
+                .insert_capture(&CaptureRecord {
+                    id: None,
+                    captured_at: time.to_string(),
+                    image_path: Some(image_path.to_string()),
+                    active_app: "VS Code".to_string(),
+                    window_title: "".to_string(),
+                    is_paused: false,
+                    is_private: false,
+                    ocr_text: None,
+                    interval_seconds: None,
+                    hostname: None,
+                    tracker_version: None,
+                    bundle_id: None,
+                    activity_type: None,
+                    working_directory: None,
+                })
+                .unwrap();
+            if let Some(bytes) = bytes {
+                db.update_image_info(
+                    id,
+                    &ImageInfo {
+                        bytes,
+                        width: Some(1920),
+                        height: Some(1080),
+                    },
+                )
+                .unwrap();
+            }
+        }
+
+        // 4時区切りでは1/10 3:00は1/9に数える
+        let report = Report::new(db, 60).with_day_boundary(4);
+        let stats = report.storage("2025-01-10", 3).unwrap();
+        assert_eq!(stats.start_date, "2025-01-08");
+        let days: Vec<_> = stats.days.iter().map(|d| (d.images, d.bytes)).collect();
+        assert_eq!(days, vec![(1, 1000), (1, 2000), (2, 4300)]);
+        assert_eq!(stats.total_images, 4);
+        assert_eq!(stats.total_bytes, 7300);
+    }
+
     #[test]
     fn test_empty_date() {
         let (db, _temp_dir) = create_test_db_with_data();
@@ -231,6 +2635,8 @@ mod tests {
     #[test]
     fn test_extract_time() {
         assert_eq!(extract_time("2024-12-30T10:30:45"), "10:30:45");
+        assert_eq!(extract_time("2024-12-30T10:30:45+09:00"), "10:30:45");
+        assert_eq!(extract_time("2024-12-30T10:30:45-05:00"), "10:30:45");
         assert_eq!(extract_time("invalid"), "invalid");
     }
 
@@ -241,4 +2647,450 @@ mod tests {
         assert_eq!(format_duration(3660), "1時間1分");
         assert_eq!(format_duration(7260), "2時間1分");
     }
+
+    #[test]
+    fn test_invalid_date() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        let report = Report::new(db, 60);
+
+        let result = report.timeline("2024-13-45");
+        assert!(matches!(result, Err(ReportError::InvalidDate(_))));
+    }
+
+    #[test]
+    fn test_day_boundary_hour() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        for (time, app) in [
+            ("2024-12-30T03:59:00+09:00", "Slack"),
+            ("2024-12-30T04:00:00+09:00", "VS Code"),
+            ("2024-12-31T02:30:00+09:00", "Terminal"),
+            ("2024-12-31T04:00:00+09:00", "Chrome"),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: "".to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+
+        let report = Report::new(db, 60).with_day_boundary(4);
+        let timeline = report.timeline("2024-12-30").unwrap();
+        let apps: Vec<_> = timeline.iter().map(|e| e.active_app.as_str()).collect();
+        assert_eq!(apps, ["VS Code", "Terminal"]);
+        assert_eq!(timeline[1].time, "02:30:00");
+    }
+
+    #[test]
+    fn test_app_filter() {
+        let filter = AppFilter::new(vec![], vec!["Finder".into(), " loginwindow ".into()]);
+        assert!(filter.matches("VS Code"));
+        assert!(!filter.matches("finder"));
+        assert!(!filter.matches("loginwindow"));
+
+        let filter = AppFilter::new(vec!["VS Code".into()], vec![]);
+        assert!(filter.matches("vs code"));
+        assert!(!filter.matches("Chrome"));
+    }
+
+    #[test]
+    fn test_report_with_app_filter() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        let report =
+            Report::new(db, 60).with_app_filter(AppFilter::new(vec![], vec!["Chrome".into()]));
+
+        let summaries = report.time_by_app("2024-12-30").unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].app_name, "VS Code");
+        assert_eq!(report.timeline("2024-12-30").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_report_with_aliases() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:03:00".to_string(),
+            image_path: None,
+            active_app: "Google Chrome".to_string(),
+            window_title: "".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: Some("com.google.Chrome".to_string()),
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+
+        // bundle IDで表示名を統一してから絞り込み・集計する
+        let report = Report::new(db, 60)
+            .with_aliases(AppAliases::new(BTreeMap::from([(
+                "com.google.Chrome".to_string(),
+                "Chrome".to_string(),
+            )])))
+            .with_app_filter(AppFilter::new(vec!["Chrome".into()], vec![]));
+
+        let summaries = report.time_by_app("2024-12-30").unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].app_name, "Chrome");
+        assert_eq!(summaries[0].capture_count, 2);
+    }
+
+    #[test]
+    fn test_distraction_by_app() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T19:00:00".to_string(),
+            image_path: None,
+            active_app: "Chrome".to_string(),
+            window_title: "YouTube".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+        let distractions = Distractions::new(vec!["Chrome".to_string()]).with_work_hours(9, 18);
+        let report = Report::new(db, 60).with_distractions(distractions);
+
+        // 作業時間帯外（19時）のキャプチャは脱線に含めない
+        let daily = report.daily("2024-12-30").unwrap();
+        assert_eq!(daily.total_seconds, 240);
+        assert_eq!(daily.distraction_seconds, 60);
+        assert_eq!(daily.distractions[0].app_name, "Chrome");
+        assert_eq!(daily.distractions[0].capture_count, 1);
+    }
+
+    #[test]
+    fn test_daily_kpi() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        for (time, app) in [
+            ("10:03:00", "zoom.us"),
+            ("10:04:00", "VS Code"),
+            ("10:20:00", "VS Code"),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: format!("2024-12-30T{}", time),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: String::new(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60).with_focus_min_minutes(2);
+
+        // 10:00〜10:01のVS Codeだけが2分続いている（会議とキャプチャの空きで区切る）
+        let kpi = report.kpi("2024-12-30").unwrap();
+        assert_eq!(
+            kpi,
+            DailyKpi {
+                work_seconds: 360,
+                meeting_seconds: 60,
+                focus_seconds: 120,
+                distraction_seconds: 0,
+                context_switches: 3,
+                pomodoros: 0,
+                paused_seconds: 0,
+                private_seconds: 0,
+                idle_seconds: 0,
+            }
+        );
+        assert_eq!(
+            format_kpi(&kpi),
+            "総作業 6分 / 会議 1分 / フォーカス 2分 / 脱線 0分 / コンテキストスイッチ 3回"
+        );
+
+        // アプリ切り替えイベントがあればそちらで数える
+        for (time, app) in [
+            ("10:00:00", "VS Code"),
+            ("10:00:30", "Slack"),
+            ("10:00:40", "VS Code"),
+        ] {
+            report
+                .database()
+                .insert_app_event(&AppEvent {
+                    id: None,
+                    occurred_at: format!("2024-12-30T{}", time),
+                    app_name: app.to_string(),
+                    bundle_id: String::new(),
+                })
+                .unwrap();
+        }
+        assert_eq!(report.kpi("2024-12-30").unwrap().context_switches, 2);
+
+        // 完了したポモドーロがあれば末尾に添える
+        report
+            .database()
+            .insert_pomodoro("2024-12-30T10:25:00", 25)
+            .unwrap();
+        let kpi = report.kpi("2024-12-30").unwrap();
+        assert_eq!(kpi.pomodoros, 1);
+        assert!(format_kpi(&kpi).ends_with("コンテキストスイッチ 2回 / ポモドーロ 1回"));
+
+        // 放置の疑いを記録したキャプチャは作業時間のまま別に数える
+        let captures = report
+            .database()
+            .get_captures_by_date("2024-12-30")
+            .unwrap();
+        let vs_code = captures
+            .iter()
+            .find(|c| c.captured_at == "2024-12-30T10:04:00")
+            .unwrap();
+        report
+            .database()
+            .set_extra_value(
+                vs_code.id.unwrap(),
+                IDLE_EXTRA_KEY,
+                &serde_json::Value::Bool(true),
+            )
+            .unwrap();
+        let kpi = report.kpi("2024-12-30").unwrap();
+        assert_eq!(kpi.idle_seconds, 60);
+        assert_eq!(kpi.work_seconds, 360);
+    }
+
+    #[test]
+    fn test_media_excluded_from_work_time() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:03:00".to_string(),
+            image_path: None,
+            active_app: "Safari".to_string(),
+            window_title: "YouTube".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: None,
+            interval_seconds: Some(300),
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: Some(ACTIVITY_TYPE_MEDIA.to_string()),
+            working_directory: None,
+        })
+        .unwrap();
+        let report = Report::new(db, 60);
+
+        // 動画視聴は作業時間から外し、別枠で集計する
+        let summaries = report.time_by_app("2024-12-30").unwrap();
+        assert!(summaries.iter().all(|s| s.app_name != "Safari"));
+        let media = report.media_by_app("2024-12-30").unwrap();
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].app_name, "Safari");
+        assert_eq!(media[0].duration_seconds, 300);
+
+        let daily = report.daily("2024-12-30").unwrap();
+        assert_eq!(daily.total_seconds, 180);
+        assert_eq!(daily.media_seconds, 300);
+
+        let trend = report.trend("2024-12-30", 1).unwrap();
+        assert_eq!(trend.total_seconds, 180);
+        assert_eq!(trend.media_seconds, 300);
+    }
+
+    #[test]
+    fn test_non_work_excluded_from_work_time() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        for (time, app, title) in [
+            ("2024-12-30T10:03:00", "loginwindow", ""),
+            ("2024-12-30T10:04:00", "Finder", ""),
+            ("2024-12-30T10:05:00", "Finder", "Downloads"),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: title.to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60);
+
+        // ロック画面とデスクトップのみのFinderは作業時間から外し、別枠で集計する
+        let non_work = report.non_work_by_app("2024-12-30").unwrap();
+        let apps: Vec<_> = non_work.iter().map(|s| s.app_name.as_str()).collect();
+        assert_eq!(apps.len(), 2);
+        assert!(apps.contains(&"loginwindow") && apps.contains(&"Finder"));
+
+        let daily = report.daily("2024-12-30").unwrap();
+        assert_eq!(daily.total_seconds, 240);
+        assert_eq!(daily.non_work_seconds, 120);
+        assert_eq!(report.kpi("2024-12-30").unwrap().work_seconds, 240);
+        assert_eq!(report.trend("2024-12-30", 1).unwrap().total_seconds, 240);
+
+        // 設定で上書きすれば作業時間に数える
+        let report = report.with_non_work(NonWorkRules::default().with_apps(vec![]));
+        assert_eq!(report.daily("2024-12-30").unwrap().total_seconds, 300);
+    }
+
+    #[test]
+    fn test_paused_and_private_time() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        for (time, app, is_paused, is_private) in [
+            ("2024-12-30T10:03:00", "Safari", false, true),
+            ("2024-12-30T10:04:00", "", true, false),
+            ("2024-12-30T10:05:00", "", true, false),
+            ("2024-12-30T10:06:00", "VS Code", false, false),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: String::new(),
+                is_paused,
+                is_private,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60);
+
+        // 一時停止中の記録はタイムライン・作業時間に含めない
+        let timeline = report.timeline("2024-12-30").unwrap();
+        assert!(timeline.iter().all(|entry| !entry.active_app.is_empty()));
+        assert_eq!(timeline.len(), 5);
+        let kpi = report.kpi("2024-12-30").unwrap();
+        assert_eq!(kpi.work_seconds, 300);
+        assert_eq!(kpi.paused_seconds, 120);
+        // プライベートは作業時間の内数
+        assert_eq!(kpi.private_seconds, 60);
+
+        // 一時停止はアプリで絞り込んでも数える
+        let report = report.with_app_filter(AppFilter::new(vec!["VS Code".to_string()], vec![]));
+        let kpi = report.kpi("2024-12-30").unwrap();
+        assert_eq!((kpi.paused_seconds, kpi.private_seconds), (120, 0));
+    }
+
+    #[test]
+    fn test_paused_by_app() {
+        let (db, _temp_dir) = create_test_db_with_data();
+        for (time, app, bundle_id) in [
+            ("2024-12-30T10:03:00", "Slack", None),
+            ("2024-12-30T10:04:00", "", None),
+            (
+                "2024-12-30T10:05:00",
+                "Google Chrome",
+                Some("com.google.Chrome"),
+            ),
+            ("2024-12-30T10:06:00", "Slack", None),
+        ] {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: time.to_string(),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: String::new(),
+                is_paused: true,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: bundle_id.map(str::to_string),
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60).with_aliases(AppAliases::new(BTreeMap::from([(
+            "com.google.Chrome".to_string(),
+            "Chrome".to_string(),
+        )])));
+
+        // アプリ名のない一時停止の記録は含めず、作業時間にも含めない
+        let paused = report.paused_by_app("2024-12-30").unwrap();
+        let apps: Vec<_> = paused
+            .iter()
+            .map(|s| (s.app_name.as_str(), s.duration_seconds))
+            .collect();
+        assert_eq!(apps, vec![("Slack", 120), ("Chrome", 60)]);
+        let daily = report.daily("2024-12-30").unwrap();
+        assert_eq!(daily.total_seconds, 180);
+        assert_eq!(daily.kpi.paused_seconds, 240);
+        assert_eq!(daily.paused.len(), 2);
+
+        let report = report.with_app_filter(AppFilter::new(vec!["Chrome".to_string()], vec![]));
+        let paused = report.paused_by_app("2024-12-30").unwrap();
+        assert_eq!(paused.len(), 1);
+        assert_eq!(paused[0].app_name, "Chrome");
+    }
+
+    #[test]
+    fn test_day_range() {
+        assert_eq!(
+            day_range("2024-12-31", 0).unwrap(),
+            (
+                "2024-12-31T00:00:00".to_string(),
+                "2025-01-01T00:00:00".to_string()
+            )
+        );
+        assert_eq!(day_range("2024-12-31", 4).unwrap().0, "2024-12-31T04:00:00");
+        assert!(day_range("2024-02-30", 0).is_err());
+    }
+
+    #[test]
+    fn test_logical_date() {
+        let late_night = Local.with_ymd_and_hms(2025, 1, 11, 2, 30, 0).unwrap();
+        assert_eq!(logical_date(&late_night, 0), "2025-01-11");
+        assert_eq!(logical_date(&late_night, 4), "2025-01-10");
+    }
+
+    #[test]
+    fn test_extract_snippet() {
+        let text = "Finder\nfn main() {\n    println!(\"Hello\");";
+        assert_eq!(extract_snippet(text, "MAIN"), "fn main() {");
+        assert_eq!(extract_snippet(text, "nothing"), "Finder");
+
+        let long = "a".repeat(200);
+        assert_eq!(extract_snippet(&long, "a").chars().count(), 123);
+    }
 }
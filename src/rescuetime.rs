@@ -0,0 +1,253 @@
+//! RescueTime・Timing連携モジュール - エクスポートCSVのインポート
+
+use crate::database::{format_timestamp, CaptureRecord};
+use crate::error::ImportError;
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+use std::io::Read;
+
+/// 開始日時の列名（RescueTime: `Date`、Timing: `Start Date`）
+const START_COLUMNS: &[&str] = &["date", "start date", "start"];
+
+/// 時間の列名（RescueTime: 秒、Timing: 秒または `h:mm:ss`）
+const DURATION_COLUMNS: &[&str] = &["time spent (seconds)", "duration"];
+
+/// アプリ・サイトの列名
+const APP_COLUMNS: &[&str] = &["activity", "application", "app"];
+
+/// ウィンドウタイトル・ドキュメントの列名（なければ空のタイトルにする）
+const TITLE_COLUMNS: &[&str] = &["document", "details", "title"];
+
+/// 1行の時間の上限（秒）
+const MAX_ROW_SECONDS: f64 = 24.0 * 60.0 * 60.0;
+
+/// 日時の列の書式（タイムゾーンのない日時はローカル時刻とみなす）
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+/// RescueTime・TimingのエクスポートCSVを読み込みキャプチャ列に変換
+///
+/// 列はヘッダー行の名前で判別する（大文字小文字は区別しない）。RescueTimeの時間帯ごとの集計のように
+/// 同じ開始日時の行が続く場合は、前の行の終わりから順に並べる。画像のないキャプチャとして
+/// インターバルごとに展開するため、既存のレポートでそのまま集計できる
+pub fn read_rescuetime_csv<R: Read>(
+    mut reader: R,
+    interval_seconds: u64,
+) -> Result<Vec<CaptureRecord>, ImportError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut rows = parse_csv(text.trim_start_matches('\u{feff}')).into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| ImportError::InvalidCsv("ヘッダー行がありません".to_string()))?;
+    let start_column = find_column(&header, START_COLUMNS)?;
+    let duration_column = find_column(&header, DURATION_COLUMNS)?;
+    let app_column = find_column(&header, APP_COLUMNS)?;
+    let title_column = find_column(&header, TITLE_COLUMNS).ok();
+
+    let mut activities = Vec::new();
+    for row in rows.filter(|row| row.iter().any(|field| !field.is_empty())) {
+        let field = |column: usize| row.get(column).map(String::as_str).unwrap_or("");
+        let start = parse_datetime(field(start_column))?;
+        let seconds = parse_duration(field(duration_column))?;
+        let title = title_column.map(field).unwrap_or("").to_string();
+        activities.push((start, seconds, field(app_column).to_string(), title));
+    }
+    activities.sort_by_key(|(start, ..)| *start);
+
+    let interval = interval_seconds.max(1) as i64;
+    let mut records = Vec::new();
+    let mut cursor: Option<DateTime<Local>> = None;
+    for (start, seconds, app, title) in activities {
+        let start = cursor.map_or(start, |cursor| start.max(cursor));
+        // 端数はインターバル1回分に切り上げる
+        let count = (seconds / interval as f64).ceil().max(1.0) as i64;
+        for i in 0..count {
+            records.push(CaptureRecord {
+                id: None,
+                captured_at: format_timestamp(&(start + Duration::seconds(i * interval))),
+                image_path: None,
+                active_app: app.clone(),
+                window_title: title.clone(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            });
+        }
+        cursor = Some(start + Duration::seconds(count * interval));
+    }
+    Ok(records)
+}
+
+/// ヘッダー行から候補の名前の列を探す
+fn find_column(header: &[String], names: &[&str]) -> Result<usize, ImportError> {
+    header
+        .iter()
+        .position(|column| names.contains(&column.trim().to_lowercase().as_str()))
+        .ok_or_else(|| ImportError::InvalidCsv(format!("{} の列がありません", names[0])))
+}
+
+/// 日時を解析（RFC 3339か、タイムゾーンのないローカル時刻）
+fn parse_datetime(value: &str) -> Result<DateTime<Local>, ImportError> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Local));
+    }
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|datetime| Local.from_local_datetime(&datetime).earliest())
+        .ok_or_else(|| ImportError::InvalidTimestamp(value.to_string()))
+}
+
+/// 時間を秒数に変換（秒数か `h:mm:ss`）
+///
+/// `inf`・`NaN` や負の値、1行で24時間を超える値はエラーにする（巨大な値で取り込みが終わらなくなるのを防ぐ）
+fn parse_duration(value: &str) -> Result<f64, ImportError> {
+    let value = value.trim();
+    let invalid = || ImportError::InvalidCsv(format!("時間を解析できません: {}", value));
+    let seconds = value
+        .split(':')
+        .try_fold(0.0, |total, part| -> Result<f64, ImportError> {
+            let part = part
+                .parse::<f64>()
+                .ok()
+                .filter(|part| part.is_finite() && *part >= 0.0)
+                .ok_or_else(invalid)?;
+            Ok(total * 60.0 + part)
+        })?;
+    if seconds > MAX_ROW_SECONDS {
+        return Err(ImportError::InvalidCsv(format!(
+            "1行の時間が24時間を超えています: {}",
+            value
+        )));
+    }
+    Ok(seconds)
+}
+
+/// CSVを行・列に分割（引用符で囲んだフィールドの区切り・改行と `""` のエスケープに対応）
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_rescuetime_csv() {
+        let csv =
+            "\u{feff}Date,Time Spent (seconds),Number of People,Activity,Category,Productivity\r\n\
+            2019-01-10T10:00:00,150,1,Visual Studio Code,Editing & IDEs,2\r\n\
+            2019-01-10T10:00:00,60,1,\"Slack, Inc.\",Communication,0\r\n\
+            \r\n";
+
+        let records = read_rescuetime_csv(csv.as_bytes(), 60).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].active_app, "Visual Studio Code");
+        assert_eq!(records[0].window_title, "");
+        assert!(records[0].image_path.is_none());
+        // 同じ時間帯の行は前の行の終わりから並べる
+        let times: Vec<_> = records
+            .iter()
+            .map(|r| {
+                DateTime::parse_from_rfc3339(&r.captured_at)
+                    .unwrap()
+                    .with_timezone(&Local)
+                    .format("%H:%M")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(times, ["10:00", "10:01", "10:02", "10:03"]);
+        assert_eq!(records[3].active_app, "Slack, Inc.");
+    }
+
+    #[test]
+    fn test_read_timing_csv() {
+        let csv = "Start Date,End Date,Duration,Application,Title\n\
+            2019-01-10 09:00:00,2019-01-10 09:02:00,0:02:00,Safari,\"He said \"\"hi\"\"\"\n";
+
+        let records = read_rescuetime_csv(csv.as_bytes(), 60).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].active_app, "Safari");
+        assert_eq!(records[0].window_title, "He said \"hi\"");
+    }
+
+    #[test]
+    fn test_read_invalid_csv() {
+        assert!(matches!(
+            read_rescuetime_csv("Date,Activity\n".as_bytes(), 60),
+            Err(ImportError::InvalidCsv(_))
+        ));
+        assert!(matches!(
+            read_rescuetime_csv(
+                "Date,Duration,Activity\nyesterday,60,Slack\n".as_bytes(),
+                60
+            ),
+            Err(ImportError::InvalidTimestamp(_))
+        ));
+        assert!(matches!(
+            read_rescuetime_csv(
+                "Date,Duration,Activity\n2019-01-10 10:00,-1,Slack\n".as_bytes(),
+                60
+            ),
+            Err(ImportError::InvalidCsv(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_non_finite_and_too_long() {
+        assert_eq!(parse_duration("90").unwrap(), 90.0);
+        assert_eq!(parse_duration("1:02:03").unwrap(), 3723.0);
+        assert_eq!(parse_duration("86400").unwrap(), 86400.0);
+        for value in ["inf", "-inf", "NaN", "1:inf:00", "-1", "1:-30:00"] {
+            assert!(
+                matches!(parse_duration(value), Err(ImportError::InvalidCsv(_))),
+                "{}",
+                value
+            );
+        }
+        for value in ["90000", "25:00:00", "1e300"] {
+            assert!(
+                matches!(parse_duration(value), Err(ImportError::InvalidCsv(_))),
+                "{}",
+                value
+            );
+        }
+    }
+}
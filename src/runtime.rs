@@ -0,0 +1,198 @@
+//! ランタイムモジュール - tokio上でキャプチャ・OCR・シグナル処理を独立したタスクとして動かす
+
+use crate::capture::CaptureLoop;
+use crate::config::{CliArgs, Config};
+use crate::error::CaptureError;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Builder;
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::Notify;
+use tokio::time;
+use tracing::info;
+
+/// キャプチャループとシグナル処理などのタスクで共有する制御フラグ
+#[derive(Debug, Clone)]
+pub struct Control {
+    running: Arc<AtomicBool>,
+    /// 設定の再読み込みが要求されたか（SIGHUP）
+    reload_requested: Arc<AtomicBool>,
+    /// 即時キャプチャが要求されたか（SIGUSR1）
+    capture_requested: Arc<AtomicBool>,
+    /// 要求があったことを待機中のタスクに知らせる
+    wake: Arc<Notify>,
+}
+
+impl Default for Control {
+    fn default() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(true)),
+            reload_requested: Arc::new(AtomicBool::new(false)),
+            capture_requested: Arc::new(AtomicBool::new(false)),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+}
+
+impl Control {
+    /// 実行を続けるか
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// 処理中のキャプチャを終えてから終了するよう要求する
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// 設定の再読み込みを要求する
+    pub fn request_reload(&self) {
+        self.reload_requested.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// 設定の再読み込みが要求されているか（要求は取り消さない）
+    pub fn reload_requested(&self) -> bool {
+        self.reload_requested.load(Ordering::SeqCst)
+    }
+
+    /// 設定の再読み込みの要求を取り出す
+    pub fn take_reload_request(&self) -> bool {
+        self.reload_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// 待機を打ち切って即時キャプチャするよう要求する
+    pub fn request_capture(&self) {
+        self.capture_requested.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// 指定した時間が経つか、停止・再読み込み・即時キャプチャを要求されるまで待つ
+    ///
+    /// 待ち始める前の要求も取りこぼさない（要求の内容は呼び出し側で確認する）
+    pub async fn wait(&self, duration: Duration) {
+        let _ = time::timeout(duration, self.wake.notified()).await;
+    }
+
+    /// 即時キャプチャの要求を取り出す
+    pub fn take_capture_request(&self) -> bool {
+        self.capture_requested.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// トラッカーをtokioのランタイム上で実行する
+///
+/// シグナル処理・キャプチャループ・OCRワーカーをそれぞれ独立したタスクとして起動し、
+/// キャプチャループ（OCRワーカーの終了待ちを含む）が終わるまで待つ
+pub fn run(config: Config, cli_args: CliArgs) -> Result<(), CaptureError> {
+    let runtime = Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("tracker-worker")
+        .build()
+        .map_err(|e| CaptureError::RuntimeError(e.to_string()))?;
+
+    runtime.block_on(async move {
+        let control = Control::default();
+        // キャプチャを始める前に登録し、起動直後のシグナルも取りこぼさない
+        let signals = SignalHandler::register()
+            .map_err(|e| CaptureError::SignalHandlerError(e.to_string()))?;
+        let signal_task = tokio::spawn(signals.run(control.clone()));
+
+        let capture = tokio::spawn(async move {
+            CaptureLoop::new(config)?
+                .with_cli_args(cli_args)
+                .with_control(control)
+                .run()
+                .await
+        });
+        let result = capture.await;
+        signal_task.abort();
+        result.map_err(|e| CaptureError::RuntimeError(format!("キャプチャループ: {}", e)))?
+    })
+}
+
+/// シグナルを受け取って制御フラグに反映するタスク
+///
+/// SIGINT・SIGTERMで処理中のキャプチャを終えてから終了し、
+/// SIGHUPで設定ファイルを再読み込み、SIGUSR1で待機を打ち切って即時キャプチャする
+struct SignalHandler {
+    interrupt: Signal,
+    terminate: Signal,
+    hangup: Signal,
+    user_defined1: Signal,
+}
+
+impl SignalHandler {
+    /// シグナルの受け取りを登録（ランタイム上で呼び出す）
+    fn register() -> io::Result<Self> {
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+            hangup: signal(SignalKind::hangup())?,
+            user_defined1: signal(SignalKind::user_defined1())?,
+        })
+    }
+
+    async fn run(mut self, control: Control) {
+        loop {
+            tokio::select! {
+                _ = self.hangup.recv() => {
+                    info!("設定の再読み込みを要求されました");
+                    control.request_reload();
+                }
+                _ = self.user_defined1.recv() => {
+                    info!("即時キャプチャを要求されました");
+                    control.request_capture();
+                }
+                _ = self.interrupt.recv() => {
+                    info!("シャットダウンシグナルを受信しました");
+                    control.stop();
+                }
+                _ = self.terminate.recv() => {
+                    info!("シャットダウンシグナルを受信しました");
+                    control.stop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_requests() {
+        let control = Control::default();
+        let shared = control.clone();
+        assert!(control.is_running());
+
+        shared.request_reload();
+        assert!(control.reload_requested());
+        assert!(control.take_reload_request());
+        assert!(!control.take_reload_request());
+
+        shared.request_capture();
+        assert!(control.take_capture_request());
+        assert!(!control.take_capture_request());
+
+        shared.stop();
+        assert!(!control.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_wait_wakes_on_request() {
+        let control = Control::default();
+        let shared = control.clone();
+        let waiter = tokio::spawn(async move {
+            control.wait(Duration::from_secs(3600)).await;
+            control.take_capture_request()
+        });
+        shared.request_capture();
+        let woken = time::timeout(Duration::from_secs(5), waiter).await;
+        assert!(woken.unwrap().unwrap());
+    }
+}
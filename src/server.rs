@@ -0,0 +1,499 @@
+//! HTTP APIサーバーモジュール - ローカル専用のREST API
+
+use crate::error::ServerError;
+use crate::report::Report;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use tiny_http::{Header, Method, Response, Server};
+use tracing::{info, warn};
+
+/// 検索結果のデフォルト件数
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+/// APIレスポンス
+#[derive(Debug)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl ApiResponse {
+    /// JSONレスポンスを生成
+    fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => Self {
+                status: 200,
+                content_type: "application/json; charset=utf-8",
+                body,
+            },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    /// エラーレスポンスを生成
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json; charset=utf-8",
+            body: json!({ "error": message }).to_string().into_bytes(),
+        }
+    }
+}
+
+/// HTTP APIサーバー
+///
+/// DNSリバインディング対策としてループバックアドレスにのみバインドし、
+/// Hostヘッダーがローカルホスト以外のリクエストは拒否する
+pub struct ApiServer {
+    report: Report,
+}
+
+impl ApiServer {
+    /// 新しいApiServerを作成
+    pub fn new(report: Report) -> Self {
+        Self { report }
+    }
+
+    /// 指定ポートでサーバーを実行
+    pub fn run(&self, port: u16) -> Result<(), ServerError> {
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| ServerError::BindFailed(e.to_string()))?;
+        info!("HTTP APIサーバーを開始します: http://127.0.0.1:{}", port);
+
+        for request in server.incoming_requests() {
+            let host_allowed = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Host"))
+                .map(|h| is_local_host(h.value.as_str()))
+                .unwrap_or(false);
+
+            let response = if !host_allowed {
+                ApiResponse::error(403, "Forbidden host")
+            } else if *request.method() != Method::Get {
+                ApiResponse::error(405, "Method not allowed")
+            } else {
+                self.handle(request.url())
+            };
+
+            info!(
+                "{} {} -> {}",
+                request.method(),
+                request.url(),
+                response.status
+            );
+
+            let header = Header::from_bytes("Content-Type", response.content_type)
+                .expect("静的なヘッダー値は常に有効");
+            let http_response = Response::from_data(response.body)
+                .with_status_code(response.status)
+                .with_header(header);
+            if let Err(e) = request.respond(http_response) {
+                warn!("レスポンス送信失敗: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// リクエストURLを処理
+    pub fn handle(&self, url: &str) -> ApiResponse {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let params = parse_query(query);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match segments.as_slice() {
+            ["captures"] => match params.get("date") {
+                Some(date) => self.captures(date),
+                None => ApiResponse::error(400, "Missing query parameter: date"),
+            },
+            ["report", date] => self.report(date),
+            ["events"] => match params.get("date") {
+                Some(date) => self.events(date),
+                None => ApiResponse::error(400, "Missing query parameter: date"),
+            },
+            ["search"] => match params.get("q") {
+                Some(q) => {
+                    let limit = params
+                        .get("limit")
+                        .and_then(|l| l.parse().ok())
+                        .unwrap_or(DEFAULT_SEARCH_LIMIT);
+                    self.search(q, limit)
+                }
+                None => ApiResponse::error(400, "Missing query parameter: q"),
+            },
+            ["images", id] => match id.parse::<i64>() {
+                Ok(id) => self.image(id),
+                Err(_) => ApiResponse::error(400, "Invalid image id"),
+            },
+            ["ocr", id] => match id.parse::<i64>() {
+                Ok(id) => self.ocr(id),
+                Err(_) => ApiResponse::error(400, "Invalid capture id"),
+            },
+            ["metrics", id] => match id.parse::<i64>() {
+                Ok(id) => self.metrics(id),
+                Err(_) => ApiResponse::error(400, "Invalid capture id"),
+            },
+            _ => ApiResponse::error(404, "Not found"),
+        }
+    }
+
+    /// GET /captures?date=YYYY-MM-DD
+    fn captures(&self, date: &str) -> ApiResponse {
+        match self.report.captures(date) {
+            Ok(captures) => ApiResponse::json(&json!({ "date": date, "captures": captures })),
+            Err(e) => ApiResponse::error(400, &e.to_string()),
+        }
+    }
+
+    /// GET /events?date=YYYY-MM-DD
+    fn events(&self, date: &str) -> ApiResponse {
+        match self.report.app_events(date) {
+            Ok(events) => ApiResponse::json(&json!({ "date": date, "events": events })),
+            Err(e) => ApiResponse::error(400, &e.to_string()),
+        }
+    }
+
+    /// GET /report/:date
+    fn report(&self, date: &str) -> ApiResponse {
+        match self.report.daily(date) {
+            Ok(report) => ApiResponse::json(&report),
+            Err(e) => ApiResponse::error(400, &e.to_string()),
+        }
+    }
+
+    /// GET /search?q=...&limit=N
+    fn search(&self, query: &str, limit: i64) -> ApiResponse {
+        match self.report.search(query, limit) {
+            Ok(results) => ApiResponse::json(&json!({ "query": query, "results": results })),
+            Err(e) => ApiResponse::error(500, &e.to_string()),
+        }
+    }
+
+    /// GET /ocr/:id
+    ///
+    /// プライベートなキャプチャは画像と同じく返さない
+    fn ocr(&self, id: i64) -> ApiResponse {
+        let db = self.report.database();
+        let capture = match db.get_capture(id) {
+            Ok(Some(capture)) => capture,
+            Ok(None) => return ApiResponse::error(404, "Capture not found"),
+            Err(e) => return ApiResponse::error(500, &e.to_string()),
+        };
+        if capture.is_private {
+            return ApiResponse::error(404, "OCR text not available");
+        }
+
+        let details = match db.get_ocr_details(id) {
+            Ok(details) => {
+                details.and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            }
+            Err(e) => return ApiResponse::error(500, &e.to_string()),
+        };
+
+        ApiResponse::json(&json!({
+            "id": id,
+            "text": capture.ocr_text,
+            "details": details,
+        }))
+    }
+
+    /// GET /metrics/:id
+    fn metrics(&self, id: i64) -> ApiResponse {
+        let db = self.report.database();
+        match db.get_capture(id) {
+            Ok(Some(_)) => {}
+            Ok(None) => return ApiResponse::error(404, "Capture not found"),
+            Err(e) => return ApiResponse::error(500, &e.to_string()),
+        }
+
+        let input = match db.get_input_metrics(id) {
+            Ok(input) => input,
+            Err(e) => return ApiResponse::error(500, &e.to_string()),
+        };
+        match db.get_process_snapshots(id) {
+            Ok(processes) => ApiResponse::json(&json!({
+                "id": id,
+                "input": input,
+                "processes": processes,
+            })),
+            Err(e) => ApiResponse::error(500, &e.to_string()),
+        }
+    }
+
+    /// GET /images/:id
+    fn image(&self, id: i64) -> ApiResponse {
+        let capture = match self.report.database().get_capture(id) {
+            Ok(Some(capture)) => capture,
+            Ok(None) => return ApiResponse::error(404, "Capture not found"),
+            Err(e) => return ApiResponse::error(500, &e.to_string()),
+        };
+
+        let Some(path) = capture.image_path.filter(|_| !capture.is_private) else {
+            return ApiResponse::error(404, "Image not available");
+        };
+
+        match fs::read(&path) {
+            Ok(body) => ApiResponse {
+                status: 200,
+                content_type: "image/jpeg",
+                body,
+            },
+            Err(e) => {
+                warn!("画像読み込み失敗 ({}): {}", path, e);
+                ApiResponse::error(404, "Image file not found")
+            }
+        }
+    }
+}
+
+/// Hostヘッダーがローカルホストかを判定
+fn is_local_host(host: &str) -> bool {
+    let name = host.rsplit_once(':').map(|(name, _)| name).unwrap_or(host);
+    matches!(name, "127.0.0.1" | "localhost")
+}
+
+/// クエリ文字列を解析
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// パーセントエンコーディングをデコード（`+` は空白として扱う）
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push(high << 4 | low);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// 16進数の1桁を数値に変換
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{AppEvent, CaptureRecord, Database, InputMetrics, ProcessSnapshot};
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    fn create_test_server() -> (ApiServer, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+
+        let image_path = temp_dir.path().join("100000.jpg");
+        fs::write(&image_path, b"jpeg-bytes").unwrap();
+
+        db.insert_capture(&CaptureRecord {
+            id: None,
+            captured_at: "2024-12-30T10:00:00".to_string(),
+            image_path: Some(image_path.to_string_lossy().to_string()),
+            active_app: "VS Code".to_string(),
+            window_title: "main.rs".to_string(),
+            is_paused: false,
+            is_private: false,
+            ocr_text: Some("設計レビュー".to_string()),
+            interval_seconds: None,
+            hostname: None,
+            tracker_version: None,
+            bundle_id: None,
+            activity_type: None,
+            working_directory: None,
+        })
+        .unwrap();
+
+        (ApiServer::new(Report::new(db, 60)), temp_dir)
+    }
+
+    fn body_json(response: &ApiResponse) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    #[test]
+    fn test_captures_endpoint() {
+        let (server, _temp_dir) = create_test_server();
+
+        let response = server.handle("/captures?date=2024-12-30");
+        assert_eq!(response.status, 200);
+        assert_eq!(body_json(&response)["captures"][0]["active_app"], "VS Code");
+
+        assert_eq!(server.handle("/captures").status, 400);
+        assert_eq!(server.handle("/captures?date=bad").status, 400);
+    }
+
+    #[test]
+    fn test_report_endpoint() {
+        let (server, _temp_dir) = create_test_server();
+
+        let response = server.handle("/report/2024-12-30");
+        assert_eq!(response.status, 200);
+        assert_eq!(body_json(&response)["apps"][0]["capture_count"], 1);
+    }
+
+    #[test]
+    fn test_search_endpoint_decodes_query() {
+        let (server, _temp_dir) = create_test_server();
+
+        // 「設計」をパーセントエンコード
+        let response = server.handle("/search?q=%E8%A8%AD%E8%A8%88");
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            body_json(&response)["results"][0]["snippet"],
+            "設計レビュー"
+        );
+    }
+
+    #[test]
+    fn test_images_endpoint() {
+        let (server, _temp_dir) = create_test_server();
+
+        let response = server.handle("/images/1");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "image/jpeg");
+        assert_eq!(response.body, b"jpeg-bytes");
+
+        assert_eq!(server.handle("/images/999").status, 404);
+        assert_eq!(server.handle("/images/abc").status, 400);
+    }
+
+    #[test]
+    fn test_ocr_endpoint() {
+        let (server, _temp_dir) = create_test_server();
+        server
+            .report
+            .database()
+            .update_ocr_details(1, r#"[{"text":"設計レビュー","confidence":0.9}]"#)
+            .unwrap();
+
+        let response = server.handle("/ocr/1");
+        assert_eq!(response.status, 200);
+        let body = body_json(&response);
+        assert_eq!(body["text"], "設計レビュー");
+        assert_eq!(body["details"][0]["confidence"], 0.9);
+
+        assert_eq!(server.handle("/ocr/999").status, 404);
+    }
+
+    #[test]
+    fn test_ocr_endpoint_hides_private_capture() {
+        let (server, _temp_dir) = create_test_server();
+        let db = server.report.database();
+        let mut capture = db.get_capture(1).unwrap().unwrap();
+        capture.id = None;
+        capture.is_private = true;
+        capture.ocr_text = Some("パスワード".to_string());
+        let id = db.insert_capture(&capture).unwrap();
+        db.update_ocr_details(id, r#"[{"text":"パスワード","confidence":0.9}]"#)
+            .unwrap();
+
+        let response = server.handle(&format!("/ocr/{}", id));
+        assert_eq!(response.status, 404);
+        assert!(!String::from_utf8_lossy(&response.body).contains("パスワード"));
+    }
+
+    #[test]
+    fn test_metrics_endpoint() {
+        let (server, _temp_dir) = create_test_server();
+        let db = server.report.database();
+        db.insert_input_metrics(
+            1,
+            &InputMetrics {
+                key_presses: 42,
+                clicks: 3,
+                scrolls: 7,
+            },
+        )
+        .unwrap();
+        db.insert_process_snapshots(
+            1,
+            &[ProcessSnapshot {
+                pid: 10,
+                name: "rustc".to_string(),
+                cpu_percent: 250.0,
+                memory_bytes: 1024,
+                is_active_app: false,
+            }],
+        )
+        .unwrap();
+
+        let response = server.handle("/metrics/1");
+        assert_eq!(response.status, 200);
+        let body = body_json(&response);
+        assert_eq!(body["input"]["key_presses"], 42);
+        assert_eq!(body["processes"][0]["name"], "rustc");
+
+        assert_eq!(server.handle("/metrics/999").status, 404);
+    }
+
+    #[test]
+    fn test_events_endpoint() {
+        let (server, _temp_dir) = create_test_server();
+        server
+            .report
+            .database()
+            .insert_app_event(&AppEvent {
+                id: None,
+                occurred_at: "2024-12-30T10:00:30+09:00".to_string(),
+                app_name: "Slack".to_string(),
+                bundle_id: "com.tinyspeck.slackmacgap".to_string(),
+            })
+            .unwrap();
+
+        let response = server.handle("/events?date=2024-12-30");
+        assert_eq!(response.status, 200);
+        assert_eq!(body_json(&response)["events"][0]["app_name"], "Slack");
+
+        assert_eq!(server.handle("/events").status, 400);
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let (server, _temp_dir) = create_test_server();
+        assert_eq!(server.handle("/unknown").status, 404);
+    }
+
+    #[test]
+    fn test_is_local_host() {
+        assert!(is_local_host("127.0.0.1:8686"));
+        assert!(is_local_host("localhost:8686"));
+        assert!(is_local_host("localhost"));
+        assert!(!is_local_host("evil.example.com:8686"));
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a+b%20c"), "a b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("%あ"), "%あ");
+    }
+}
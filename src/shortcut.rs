@@ -0,0 +1,141 @@
+//! ショートカットモジュール - macOSのショートカット.app・URLスキームから渡された操作の解釈
+
+use crate::error::ShortcutError;
+use crate::i18n::Message;
+use crate::report::{format_duration, format_kpi, DailyReport};
+use url::Url;
+
+/// URLスキーム（`tracker://pause` など）
+pub const URL_SCHEME: &str = "tracker";
+
+/// テキストのレポートに載せるアプリの数
+const REPORT_APPS: usize = 5;
+
+/// ショートカットから呼び出せる操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutAction {
+    /// トラッキングを一時停止
+    Pause,
+    /// トラッキングを再開
+    Resume,
+    /// 作業メモを追加
+    Note(String),
+    /// 日次レポートを取得（対象日の指定、省略時は今日）
+    Report(Option<String>),
+}
+
+impl ShortcutAction {
+    /// 入力を操作として解釈
+    ///
+    /// `pause` / `note 内容` / `report yesterday` のコマンド形式と、
+    /// `tracker://pause` / `tracker://note?text=内容` / `tracker://report?date=yesterday`
+    /// のURL形式を受け付ける
+    pub fn parse(input: &str) -> Result<Self, ShortcutError> {
+        let input = input.trim();
+        if input.starts_with(&format!("{}:", URL_SCHEME)) {
+            return Self::parse_url(input);
+        }
+        let (name, argument) = match input.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (input, ""),
+        };
+        Self::from_parts(name, (!argument.is_empty()).then(|| argument.to_string()))
+    }
+
+    /// `tracker://<操作>?text=...&date=...` 形式のURLを解釈
+    fn parse_url(input: &str) -> Result<Self, ShortcutError> {
+        let url = Url::parse(input).map_err(|e| ShortcutError::InvalidUrl(e.to_string()))?;
+        // `tracker://pause` は操作がホスト、`tracker:pause` はパスになる
+        let name = url
+            .host_str()
+            .unwrap_or_else(|| url.path())
+            .trim_matches('/')
+            .to_string();
+        let argument = url
+            .query_pairs()
+            .find(|(key, _)| key == "text" || key == "date")
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        Self::from_parts(&name, argument)
+    }
+
+    /// 操作名と引数から作成
+    fn from_parts(name: &str, argument: Option<String>) -> Result<Self, ShortcutError> {
+        match name.to_lowercase().as_str() {
+            "pause" => Ok(Self::Pause),
+            "resume" => Ok(Self::Resume),
+            "note" => argument.map(Self::Note).ok_or(ShortcutError::EmptyNote),
+            "report" => Ok(Self::Report(argument)),
+            _ => Err(ShortcutError::UnknownAction(name.to_string())),
+        }
+    }
+}
+
+/// ショートカットに返す短い日次レポート（KPIと上位のアプリ）
+pub fn render_report(report: &DailyReport) -> String {
+    if report.timeline.is_empty() {
+        return Message::NoCaptures.format(&[&report.date]);
+    }
+    let mut lines = vec![
+        Message::DailyHeading.format(&[&report.date]),
+        format_kpi(&report.kpi),
+    ];
+    for app in report.apps.iter().take(REPORT_APPS) {
+        lines.push(format!(
+            "- {}: {}",
+            app.app_name,
+            format_duration(app.duration_seconds)
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            ShortcutAction::parse("pause\n").unwrap(),
+            ShortcutAction::Pause
+        );
+        assert_eq!(
+            ShortcutAction::parse("note  設計レビュー完了 ").unwrap(),
+            ShortcutAction::Note("設計レビュー完了".to_string())
+        );
+        assert_eq!(
+            ShortcutAction::parse("Report yesterday").unwrap(),
+            ShortcutAction::Report(Some("yesterday".to_string()))
+        );
+        assert!(matches!(
+            ShortcutAction::parse("note"),
+            Err(ShortcutError::EmptyNote)
+        ));
+        assert!(matches!(
+            ShortcutAction::parse("delete"),
+            Err(ShortcutError::UnknownAction(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            ShortcutAction::parse("tracker://resume").unwrap(),
+            ShortcutAction::Resume
+        );
+        assert_eq!(
+            ShortcutAction::parse("tracker://note?text=%E4%BC%9A%E8%AD%B0+%E3%83%A1%E3%83%A2")
+                .unwrap(),
+            ShortcutAction::Note("会議 メモ".to_string())
+        );
+        assert_eq!(
+            ShortcutAction::parse("tracker://report/?date=-1d").unwrap(),
+            ShortcutAction::Report(Some("-1d".to_string()))
+        );
+        assert_eq!(
+            ShortcutAction::parse("tracker:pause").unwrap(),
+            ShortcutAction::Pause
+        );
+    }
+}
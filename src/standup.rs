@@ -0,0 +1,236 @@
+//! スタンドアップモジュール - 朝会用の前営業日の作業ハイライト
+
+use crate::error::ReportError;
+use crate::i18n::Message;
+use crate::metadata::{is_vscode, parse_vscode_title};
+use crate::report::{format_duration, Report};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// 表示するプロジェクトの最大数
+const MAX_PROJECTS: usize = 5;
+
+/// プロジェクトごとに表示するウィンドウタイトルの最大数
+const MAX_TITLES: usize = 3;
+
+/// ウィンドウタイトルの区切り（VS Code・Cursor・JetBrains系の `ファイル — ワークスペース`）
+const TITLE_SEPARATOR: &str = " — ";
+
+/// 指定日の前営業日（土日を除く）
+pub fn previous_workday(date: NaiveDate) -> NaiveDate {
+    let mut day = date;
+    loop {
+        day = day.checked_sub_days(Days::new(1)).unwrap_or(day);
+        if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            return day;
+        }
+    }
+}
+
+/// ウィンドウタイトルからプロジェクト名と作業対象を取り出す
+///
+/// ターミナル・VS Codeは作業ディレクトリ（`working_directory`）のプロジェクト、
+/// VS Codeで作業ディレクトリがなければタイトルから解析したワークスペース、
+/// ほかのエディタの `ファイル — ワークスペース` 形式のタイトルはワークスペースを
+/// プロジェクトとし、それ以外はアプリ名をプロジェクトとしてタイトル全体を作業対象とする
+pub fn split_project(app: &str, title: &str, working_directory: Option<&str>) -> (String, String) {
+    if is_vscode(app, None) {
+        if let Some(parsed) = parse_vscode_title(title) {
+            let project = working_directory
+                .and_then(directory_project)
+                .unwrap_or(parsed.workspace);
+            return (project, parsed.file.unwrap_or_default());
+        }
+    }
+    if let Some(project) = working_directory.and_then(directory_project) {
+        return (project, title.trim().to_string());
+    }
+    match title.rsplit_once(TITLE_SEPARATOR) {
+        Some((item, project)) if !item.trim().is_empty() && !project.trim().is_empty() => {
+            (project.trim().to_string(), item.trim().to_string())
+        }
+        _ => (app.to_string(), title.trim().to_string()),
+    }
+}
+
+/// ディレクトリのプロジェクト名
+///
+/// Gitリポジトリの中ならリポジトリのルート、それ以外（削除済みを含む）はディレクトリ自身の名前
+fn directory_project(directory: &str) -> Option<String> {
+    let path = Path::new(directory);
+    let root = path
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(path);
+    root.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// 指定日の作業ハイライトを箇条書きMarkdownで生成
+///
+/// プロジェクト別の時間と、その中で長く開いていたウィンドウタイトルを並べる。
+/// 時間はレポートと同じく次のキャプチャまでの実際の時間差で数える
+pub fn render_standup(report: &Report, date: &str) -> Result<String, ReportError> {
+    // プロジェクトごとの（合計秒数, 作業対象ごとの秒数）
+    let mut projects: HashMap<String, (u64, HashMap<String, u64>)> = HashMap::new();
+    let mut total = 0;
+    report.for_each_timed_capture(date, date, |capture, seconds| {
+        let (project, item) = split_project(
+            &capture.active_app,
+            &capture.window_title,
+            capture.working_directory.as_deref(),
+        );
+        let entry = projects.entry(project).or_default();
+        entry.0 += seconds;
+        if !item.is_empty() {
+            *entry.1.entry(item).or_default() += seconds;
+        }
+        total += seconds;
+    })?;
+
+    let mut markdown = String::new();
+    let _ = writeln!(
+        markdown,
+        "## {}\n",
+        Message::StandupHeading.format(&[&date, &format_duration(total)])
+    );
+    if projects.is_empty() {
+        let _ = writeln!(markdown, "- {}", Message::StandupNoRecords.text());
+        return Ok(markdown);
+    }
+
+    let mut projects: Vec<_> = projects.into_iter().collect();
+    projects.sort_by_key(|(project, (seconds, _))| (Reverse(*seconds), project.clone()));
+    for (project, (seconds, items)) in projects.into_iter().take(MAX_PROJECTS) {
+        let _ = writeln!(
+            markdown,
+            "- {}",
+            Message::WithDuration.format(&[&format!("**{}**", project), &format_duration(seconds)])
+        );
+
+        let mut items: Vec<_> = items.into_iter().collect();
+        items.sort_by_key(|(item, seconds)| (Reverse(*seconds), item.clone()));
+        for (item, seconds) in items.into_iter().take(MAX_TITLES) {
+            let _ = writeln!(
+                markdown,
+                "  - {}",
+                Message::WithDuration.format(&[&item, &format_duration(seconds)])
+            );
+        }
+    }
+
+    Ok(markdown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{CaptureRecord, Database};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_previous_workday() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        // 月曜日の前営業日は金曜日
+        assert_eq!(previous_workday(date("2025-01-13")), date("2025-01-10"));
+        assert_eq!(previous_workday(date("2025-01-14")), date("2025-01-13"));
+        assert_eq!(previous_workday(date("2025-01-12")), date("2025-01-10"));
+    }
+
+    #[test]
+    fn test_split_project() {
+        assert_eq!(
+            split_project("Code", "report.rs — habit-tracker", None),
+            ("habit-tracker".to_string(), "report.rs".to_string())
+        );
+        // VS Codeはアプリ名・リモート接続の表記を除いたワークスペース
+        assert_eq!(
+            split_project(
+                "Code",
+                "● main.rs — api [SSH: dev] — Visual Studio Code",
+                None
+            ),
+            ("api".to_string(), "main.rs".to_string())
+        );
+        assert_eq!(
+            split_project("Slack", "general - Slack", None),
+            ("Slack".to_string(), "general - Slack".to_string())
+        );
+        assert_eq!(
+            split_project("Finder", "", None),
+            ("Finder".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn test_split_project_by_working_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("habit-tracker");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+
+        // リポジトリの中ならルートの名前
+        let src = repo.join("src");
+        assert_eq!(
+            split_project("ターミナル", "zsh", src.to_str()),
+            ("habit-tracker".to_string(), "zsh".to_string())
+        );
+        // リポジトリの外はディレクトリの名前
+        assert_eq!(
+            split_project("iTerm2", "vim", Some("/tmp/scratch")),
+            ("scratch".to_string(), "vim".to_string())
+        );
+        assert_eq!(
+            split_project("iTerm2", "zsh", Some("/")),
+            ("iTerm2".to_string(), "zsh".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_standup() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let captures = [
+            ("10:00", "Code", "report.rs — habit-tracker"),
+            ("10:01", "Code", "report.rs — habit-tracker"),
+            ("10:02", "Code", "cli.rs — habit-tracker"),
+            ("10:03", "Slack", "general"),
+        ];
+        for (time, app, title) in captures {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: format!("2025-01-10T{}:00", time),
+                image_path: None,
+                active_app: app.to_string(),
+                window_title: title.to_string(),
+                is_paused: false,
+                is_private: false,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 600);
+
+        // 間隔より早く次のキャプチャが来た分は重複して数えない（最後のキャプチャは間隔のまま）
+        assert_eq!(
+            render_standup(&report, "2025-01-10").unwrap(),
+            "## 2025-01-10 の作業（合計 13分）\n\n\
+             - **Slack**（10分）\n  - general（10分）\n\
+             - **habit-tracker**（3分）\n  - report.rs（2分）\n  - cli.rs（1分）\n"
+        );
+        // 合計はレポートの合計と一致する
+        assert_eq!(report.daily("2025-01-10").unwrap().total_seconds, 13 * 60);
+        assert!(render_standup(&report, "2025-01-09")
+            .unwrap()
+            .contains("- 記録なし"));
+    }
+}
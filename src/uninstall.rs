@@ -0,0 +1,305 @@
+//! アンインストールモジュール - 削除するデータ（DB・画像・設定・ログ）の列挙と削除
+
+use crate::config::{Config, Paths};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// SQLiteがDB本体の横に作るファイルの接尾辞
+const DB_SIDE_FILE_SUFFIXES: [&str; 2] = ["-wal", "-shm"];
+
+/// 画像ディレクトリにトラッカーが作るファイルの拡張子
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg"];
+
+/// ログディレクトリにトラッカーが作るファイルの拡張子
+const LOG_EXTENSIONS: &[&str] = &["log", "pid"];
+
+/// トラッカー以外のファイルとみなさないファイル名（Finderが作る）
+const IGNORED_FILE_NAMES: &[&str] = &[".DS_Store"];
+
+/// `--purge-data` で削除するファイル・ディレクトリ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgePlan {
+    /// 削除する（存在する）ファイル・ディレクトリ
+    pub targets: Vec<PathBuf>,
+    /// 置き場所の外にあり、トラッカー以外のファイルも含むディレクトリ
+    ///
+    /// 明示的に確認されない限り削除しない（[`PurgePlan::confirm_outside`]）
+    pub outside: Vec<PathBuf>,
+    /// 空になっていれば最後に削除する置き場所のディレクトリ
+    base_dirs: Vec<PathBuf>,
+}
+
+impl PurgePlan {
+    /// 設定から削除対象を列挙する（存在しないものは含めない）
+    ///
+    /// 置き場所のディレクトリ自体は、他のプロファイルや無関係なファイルを消さないよう
+    /// 中身が空になった場合だけ削除する。設定で置き場所の外に指定した画像・ログの
+    /// ディレクトリは、トラッカーが作るファイルしかない場合だけ削除対象にする
+    pub fn new(config: &Config, config_file: &Path, paths: &Paths) -> Self {
+        let mut candidates = vec![
+            (config_file.to_path_buf(), None),
+            (config.db_path.clone(), None),
+        ];
+        candidates.extend(
+            DB_SIDE_FILE_SUFFIXES
+                .iter()
+                .map(|suffix| (with_suffix(&config.db_path, suffix), None)),
+        );
+        candidates.extend([
+            (config.images_dir.clone(), Some(IMAGE_EXTENSIONS)),
+            (config.image_buffer_dir.clone(), Some(IMAGE_EXTENSIONS)),
+            (config.log_dir.clone(), Some(LOG_EXTENSIONS)),
+            (config.pause_file.clone(), None),
+            (config.pomodoro_file.clone(), None),
+            (config.pid_file.clone(), None),
+        ]);
+
+        let mut found: Vec<(PathBuf, Option<&[&str]>)> = Vec::new();
+        for (path, extensions) in candidates {
+            if path.exists() && !found.iter().any(|(target, _)| path.starts_with(target)) {
+                // 先に列挙したものの親ディレクトリなら、そちらにまとめる
+                found.retain(|(target, _)| !target.starts_with(&path));
+                found.push((path, extensions));
+            }
+        }
+
+        let mut base_dirs = vec![paths.data_dir.clone()];
+        if paths.config_dir != paths.data_dir {
+            base_dirs.push(paths.config_dir.clone());
+        }
+        let (targets, outside) = found
+            .into_iter()
+            .partition::<Vec<_>, _>(|(path, extensions)| match extensions {
+                Some(extensions) if path.is_dir() => {
+                    base_dirs.iter().any(|dir| is_under(path, dir))
+                        || contains_only(path, extensions)
+                }
+                _ => true,
+            });
+        Self {
+            targets: targets.into_iter().map(|(path, _)| path).collect(),
+            outside: outside.into_iter().map(|(path, _)| path).collect(),
+            base_dirs,
+        }
+    }
+
+    /// 削除するものがないか
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty() && self.outside.is_empty()
+    }
+
+    /// 置き場所の外のディレクトリも削除対象にする（ユーザーが確認した場合だけ呼ぶ）
+    pub fn confirm_outside(&mut self) {
+        self.targets.append(&mut self.outside);
+    }
+
+    /// 削除対象をすべて削除し、空になった置き場所のディレクトリも削除する
+    pub fn execute(&self) -> io::Result<()> {
+        for target in &self.targets {
+            let result = if target.is_dir() {
+                fs::remove_dir_all(target)
+            } else {
+                fs::remove_file(target)
+            };
+            match result {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        for dir in &self.base_dirs {
+            // 他のファイルが残っていれば削除しない
+            let _ = fs::remove_dir(dir);
+        }
+        Ok(())
+    }
+}
+
+/// `path` が `dir` の中にあるか（シンボリックリンクや `..` を解決して比べる）
+///
+/// 解決できない（存在しない）パスは中にないとみなす
+fn is_under(path: &Path, dir: &Path) -> bool {
+    match (fs::canonicalize(path), fs::canonicalize(dir)) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// ディレクトリ内（サブディレクトリを含む）が指定した拡張子のファイルだけか
+///
+/// シンボリックリンクや読めないエントリがあればトラッカー以外のものとみなす
+fn contains_only(dir: &Path, extensions: &[&str]) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.into_iter().all(|entry| {
+        let Ok(entry) = entry else {
+            return false;
+        };
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => contains_only(&path, extensions),
+            Ok(file_type) if file_type.is_file() => {
+                let name = entry.file_name();
+                IGNORED_FILE_NAMES.iter().any(|ignored| name == *ignored)
+                    || path
+                        .extension()
+                        .map(|extension| extension.to_string_lossy().to_lowercase())
+                        .is_some_and(|extension| extensions.contains(&extension.as_str()))
+            }
+            _ => false,
+        }
+    })
+}
+
+/// パスの末尾に文字列を付ける（`tracker.db` → `tracker.db-wal`）
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_purge() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("habit-tracker");
+        let config = Config {
+            db_path: base.join("tracker.db"),
+            images_dir: base.join("images"),
+            image_buffer_dir: base.join("image_buffer"),
+            log_dir: base.join("logs"),
+            pause_file: base.join("pause"),
+            pomodoro_file: base.join("pomodoro.json"),
+            pid_file: base.join("logs").join("tracker.pid"),
+            ..Config::default()
+        };
+        let paths = Paths {
+            config_dir: base.clone(),
+            data_dir: base.clone(),
+        };
+        let config_file = base.join("config.toml");
+        fs::create_dir_all(base.join("images").join("2025-01-10")).unwrap();
+        fs::create_dir_all(base.join("image_buffer").join("2025-01-10")).unwrap();
+        fs::create_dir_all(base.join("logs")).unwrap();
+        for file in [
+            "config.toml",
+            "tracker.db",
+            "tracker.db-wal",
+            "images/2025-01-10/090000.jpg",
+            "image_buffer/2025-01-10/091000.jpg",
+            "logs/tracker.pid",
+        ] {
+            fs::write(base.join(file), "").unwrap();
+        }
+
+        let plan = PurgePlan::new(&config, &config_file, &paths);
+        // 存在しないファイルとログディレクトリ内のPIDファイルは含めない
+        assert_eq!(
+            plan.targets,
+            vec![
+                base.join("config.toml"),
+                base.join("tracker.db"),
+                base.join("tracker.db-wal"),
+                base.join("images"),
+                base.join("image_buffer"),
+                base.join("logs"),
+            ]
+        );
+        plan.execute().unwrap();
+        assert!(!base.exists());
+    }
+
+    #[test]
+    fn test_keep_unrelated_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().to_path_buf();
+        let config = Config {
+            db_path: base.join("tracker.db"),
+            images_dir: base.join("images"),
+            image_buffer_dir: base.join("image_buffer"),
+            log_dir: base.join("logs"),
+            pause_file: base.join("pause"),
+            pomodoro_file: base.join("pomodoro.json"),
+            pid_file: base.join("tracker.pid"),
+            ..Config::default()
+        };
+        let paths = Paths {
+            config_dir: base.clone(),
+            data_dir: base.clone(),
+        };
+        fs::write(base.join("tracker.db"), "").unwrap();
+        fs::create_dir_all(base.join("profiles").join("work")).unwrap();
+
+        let plan = PurgePlan::new(&config, &base.join("config.toml"), &paths);
+        assert_eq!(plan.targets, vec![base.join("tracker.db")]);
+        plan.execute().unwrap();
+        // 他のプロファイルが残っている置き場所は削除しない
+        assert!(base.join("profiles").join("work").exists());
+    }
+
+    #[test]
+    fn test_keep_directories_outside_data_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("habit-tracker");
+        let pictures = temp_dir.path().join("Pictures");
+        let logs = temp_dir.path().join("logs");
+        let config = Config {
+            db_path: base.join("tracker.db"),
+            images_dir: pictures.clone(),
+            image_buffer_dir: base.join("image_buffer"),
+            log_dir: logs.clone(),
+            pause_file: base.join("pause"),
+            pomodoro_file: base.join("pomodoro.json"),
+            pid_file: base.join("tracker.pid"),
+            ..Config::default()
+        };
+        let paths = Paths {
+            config_dir: base.clone(),
+            data_dir: base.clone(),
+        };
+        fs::create_dir_all(&base).unwrap();
+        fs::write(base.join("tracker.db"), "").unwrap();
+        // 画像ディレクトリにはユーザーの写真もある
+        fs::create_dir_all(pictures.join("2025-01-10")).unwrap();
+        fs::write(pictures.join("2025-01-10").join("090000.jpg"), "").unwrap();
+        fs::write(pictures.join("family.heic"), "").unwrap();
+        // ログディレクトリはトラッカーのファイルだけ
+        fs::create_dir_all(&logs).unwrap();
+        fs::write(logs.join("tracker.2025-01-10.log"), "").unwrap();
+        fs::write(logs.join(".DS_Store"), "").unwrap();
+
+        let mut plan = PurgePlan::new(&config, &base.join("config.toml"), &paths);
+        assert_eq!(plan.targets, vec![base.join("tracker.db"), logs.clone()]);
+        assert_eq!(plan.outside, vec![pictures.clone()]);
+        assert!(!plan.is_empty());
+
+        plan.clone().execute().unwrap();
+        assert!(!logs.exists());
+        assert!(pictures.join("family.heic").exists());
+
+        // 確認すれば置き場所の外のディレクトリも削除する
+        plan.confirm_outside();
+        assert!(plan.outside.is_empty());
+        plan.execute().unwrap();
+        assert!(!pictures.exists());
+    }
+
+    #[test]
+    fn test_is_under_resolves_parent_components() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("habit-tracker");
+        fs::create_dir_all(base.join("images")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("habit-tracker-other")).unwrap();
+        assert!(is_under(&base.join("images"), &base));
+        assert!(!is_under(
+            &base.join("..").join("habit-tracker-other"),
+            &base
+        ));
+        assert!(!is_under(&base.join("images").join("..").join(".."), &base));
+    }
+}
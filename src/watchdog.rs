@@ -0,0 +1,114 @@
+//! ウォッチドッグモジュール - キャプチャの連続失敗の検知と復旧手順の判定
+
+/// 連続失敗に対して取るべき対応
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// 何もしない（閾値未満）
+    None,
+    /// バックエンドを再初期化し、権限を確認して通知する
+    Recover,
+    /// 再初期化しても失敗が続くため、クールダウンしてから再試行する
+    Cooldown,
+}
+
+/// キャプチャループのウォッチドッグ
+///
+/// 失敗が閾値の回数だけ続いたら再初期化、再初期化後もさらに閾値の回数だけ
+/// 続いたらクールダウンを指示する。クールダウン後は再び再初期化からやり直す。
+/// 1回でも成功すれば状態をリセットする
+#[derive(Debug)]
+pub struct Watchdog {
+    threshold: u32,
+    failures: u32,
+    /// 直近の対応が再初期化だったか（次の閾値でクールダウンする）
+    recovered: bool,
+    /// 再初期化またはクールダウンを指示したか
+    alerted: bool,
+}
+
+impl Watchdog {
+    /// 新しいWatchdogを作成（`threshold` が0なら無効）
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            failures: 0,
+            recovered: false,
+            alerted: false,
+        }
+    }
+
+    /// 成功を記録し、再初期化やクールダウンが必要だった状態から復旧したかを返す
+    pub fn record_success(&mut self) -> bool {
+        self.failures = 0;
+        self.recovered = false;
+        std::mem::take(&mut self.alerted)
+    }
+
+    /// 失敗を記録し、取るべき対応を返す
+    pub fn record_failure(&mut self) -> WatchdogAction {
+        if self.threshold == 0 {
+            return WatchdogAction::None;
+        }
+
+        self.failures += 1;
+        if self.failures < self.threshold {
+            return WatchdogAction::None;
+        }
+
+        self.failures = 0;
+        self.alerted = true;
+        if self.recovered {
+            self.recovered = false;
+            WatchdogAction::Cooldown
+        } else {
+            self.recovered = true;
+            WatchdogAction::Recover
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_then_cooldown() {
+        let mut watchdog = Watchdog::new(3);
+        let actions: Vec<_> = (0..7).map(|_| watchdog.record_failure()).collect();
+        assert_eq!(
+            actions,
+            vec![
+                WatchdogAction::None,
+                WatchdogAction::None,
+                WatchdogAction::Recover,
+                WatchdogAction::None,
+                WatchdogAction::None,
+                WatchdogAction::Cooldown,
+                WatchdogAction::None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_success_resets() {
+        let mut watchdog = Watchdog::new(2);
+        assert!(!watchdog.record_success());
+
+        watchdog.record_failure();
+        assert_eq!(watchdog.record_failure(), WatchdogAction::Recover);
+        assert!(watchdog.record_success());
+
+        // リセット後は再び再初期化から
+        watchdog.record_failure();
+        assert_eq!(watchdog.record_failure(), WatchdogAction::Recover);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut watchdog = Watchdog::new(0);
+        for _ in 0..10 {
+            assert_eq!(watchdog.record_failure(), WatchdogAction::None);
+        }
+        assert!(!watchdog.record_success());
+    }
+}
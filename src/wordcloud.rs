@@ -0,0 +1,503 @@
+//! ワードクラウドモジュール - OCRテキストの頻出語をSVG/PNGで描画
+
+use crate::error::WordCloudError;
+use crate::escape::escape_markup;
+use lindera::dictionary::load_fs_dictionary;
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use resvg::{tiny_skia, usvg};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+/// 画像の幅（px）
+const WIDTH: f64 = 1200.0;
+
+/// 画像の高さ（px）
+const HEIGHT: f64 = 800.0;
+
+/// 最小・最大のフォントサイズ（px）
+const MIN_FONT_SIZE: f64 = 14.0;
+const MAX_FONT_SIZE: f64 = 96.0;
+
+/// 語同士の余白（px）
+const MARGIN: f64 = 4.0;
+
+/// 配置位置を探す螺旋の最大ステップ数
+const SPIRAL_STEPS: usize = 4000;
+
+/// 文字色
+const PALETTE: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+
+/// 描画に使うフォント（macOSの標準日本語フォントを優先）
+pub const FONT_FAMILY: &str =
+    "'Hiragino Sans', 'Hiragino Kaku Gothic ProN', 'Noto Sans CJK JP', sans-serif";
+
+/// 語として数えない名詞の細分類（IPADICの品詞細分類1）
+const EXCLUDED_NOUN_TYPES: &[&str] = &["非自立", "代名詞", "数", "接尾"];
+
+/// 英語の頻出語（ワードクラウドから除く）
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "are", "was", "you", "your", "not", "but",
+    "have", "has", "can", "will", "all", "com", "www", "http", "https", "html",
+];
+
+/// 語と出現回数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordCount {
+    pub word: String,
+    /// 語を含むテキスト（キャプチャ）の数
+    pub count: u64,
+}
+
+/// 配置済みの語
+#[derive(Debug, Clone)]
+struct PlacedWord {
+    text: String,
+    /// 中心座標
+    x: f64,
+    y: f64,
+    font_size: f64,
+    color: &'static str,
+}
+
+/// ワードクラウド
+///
+/// 同じ画面が続くと同じ語が何度も認識されるため、語の頻度は
+/// その語を含むテキストの数で数える
+pub struct WordCloud {
+    words: Vec<WordCount>,
+}
+
+/// テキストの語への分割方法
+pub enum Tokenizer {
+    /// 文字種（漢字・カタカナ・英数字）の連続を1語とみなす簡易的な分割（辞書が不要）
+    CharacterClass,
+    /// 辞書による形態素解析で名詞を取り出す
+    Morphological(Box<Segmenter>),
+}
+
+impl Tokenizer {
+    /// ビルド済みの辞書（IPADIC形式）のディレクトリを読み込み、形態素解析で分割する
+    pub fn load(dictionary: &Path) -> Result<Self, WordCloudError> {
+        let dictionary = load_fs_dictionary(dictionary)
+            .map_err(|e| WordCloudError::DictionaryLoadFailed(e.to_string()))?;
+        Ok(Self::Morphological(Box::new(Segmenter::new(
+            Mode::Normal,
+            dictionary,
+            None,
+        ))))
+    }
+
+    /// テキストを語に分割
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        match self {
+            Self::CharacterClass => tokenize(text),
+            Self::Morphological(segmenter) => match segment_nouns(segmenter, text) {
+                Ok(words) => words,
+                Err(e) => {
+                    debug!("形態素解析に失敗したため文字種で分割: {}", e);
+                    tokenize(text)
+                }
+            },
+        }
+    }
+}
+
+impl WordCloud {
+    /// テキスト群から頻出語の上位 `limit` 語を抽出
+    pub fn from_texts<'a>(
+        texts: impl IntoIterator<Item = &'a str>,
+        limit: usize,
+        tokenizer: &Tokenizer,
+    ) -> Self {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for text in texts {
+            let words: HashSet<String> = tokenizer.tokenize(text).into_iter().collect();
+            for word in words {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+
+        let mut words: Vec<WordCount> = counts
+            .into_iter()
+            .map(|(word, count)| WordCount { word, count })
+            .collect();
+        words.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+        words.truncate(limit);
+
+        Self { words }
+    }
+
+    /// 頻出語（出現回数の降順）
+    pub fn words(&self) -> &[WordCount] {
+        &self.words
+    }
+
+    /// SVGを生成
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = WIDTH,
+            h = HEIGHT
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        for word in layout(&self.words, WIDTH, HEIGHT) {
+            // 中心座標をベースライン位置に換算する
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-size="{:.1}" font-family="{}" fill="{}" text-anchor="middle">{}</text>"#,
+                word.x,
+                word.y + word.font_size * 0.35,
+                word.font_size,
+                FONT_FAMILY,
+                word.color,
+                escape_markup(&word.text)
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// 拡張子（.svg / .png）に応じた形式でファイルに書き出す
+    pub fn save(&self, path: &Path) -> Result<(), WordCloudError> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "svg" => fs::write(path, self.to_svg())?,
+            "png" => fs::write(
+                path,
+                render_png(&self.to_svg()).map_err(WordCloudError::RenderFailed)?,
+            )?,
+            _ => {
+                return Err(WordCloudError::UnsupportedFormat(
+                    path.display().to_string(),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// SVGをPNGに描画（システムフォントを使用、失敗したらその理由）
+///
+/// グラフ（`chart`）の描画にも使う
+pub fn render_png(svg: &str) -> Result<Vec<u8>, String> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| e.to_string())?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "画像サイズが不正です".to_string())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+/// 文字種
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Kanji,
+    Katakana,
+    Hiragana,
+    Alphanumeric,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    match c {
+        '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '々' => CharClass::Kanji,
+        // 中点（・）は語の区切りとして扱う
+        '・' => CharClass::Other,
+        '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' => CharClass::Katakana,
+        '\u{3040}'..='\u{309F}' => CharClass::Hiragana,
+        c if c.is_ascii_alphanumeric() || c == '_' => CharClass::Alphanumeric,
+        _ => CharClass::Other,
+    }
+}
+
+/// 文字種でテキストを語に分割
+///
+/// 日本語は文字種（漢字・カタカナ）の連続を1語とみなす簡易的な分割で、
+/// 助詞や送り仮名にあたるひらがなは除く。英数字は小文字にそろえる
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut current_class = CharClass::Other;
+
+    for c in text.chars() {
+        let class = char_class(c);
+        if class != current_class && !current.is_empty() {
+            tokens.push((current_class, std::mem::take(&mut current)));
+        }
+        current_class = class;
+        if class != CharClass::Other {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push((current_class, current));
+    }
+
+    tokens
+        .into_iter()
+        .filter_map(|(class, token)| match class {
+            CharClass::Kanji | CharClass::Katakana => Some(token),
+            CharClass::Alphanumeric => Some(token.to_lowercase()),
+            CharClass::Hiragana | CharClass::Other => None,
+        })
+        .filter(|token| is_word(token))
+        .collect()
+}
+
+/// 形態素解析でテキストから名詞を取り出す
+///
+/// 非自立語・代名詞・数詞・接尾辞と、記号を含む語は除く。英字は小文字にそろえる
+fn segment_nouns(segmenter: &Segmenter, text: &str) -> Result<Vec<String>, String> {
+    let tokens = segmenter
+        .segment(Cow::Borrowed(text))
+        .map_err(|e| e.to_string())?;
+    Ok(tokens
+        .into_iter()
+        .filter_map(|mut token| {
+            let details = token.details();
+            let noun = details.first() == Some(&"名詞")
+                && !details
+                    .get(1)
+                    .is_some_and(|kind| EXCLUDED_NOUN_TYPES.contains(kind));
+            let surface = token.surface.as_ref();
+            (noun && !surface.chars().any(|c| char_class(c) == CharClass::Other))
+                .then(|| surface.to_lowercase())
+        })
+        .filter(|token| is_word(token))
+        .collect())
+}
+
+/// ワードクラウドに載せる語か（1文字の語、数字だけの語、英語の頻出語は除く）
+fn is_word(token: &str) -> bool {
+    token.chars().count() >= 2
+        && !token.chars().all(|c| c.is_ascii_digit())
+        && !STOPWORDS.contains(&token)
+}
+
+/// 描画上の幅の概算（全角1em、半角0.6em）
+fn text_width(text: &str, font_size: f64) -> f64 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.6 } else { 1.0 })
+        .sum::<f64>()
+        * font_size
+}
+
+/// 頻度の高い語から順に、中心から螺旋状に重ならない位置を探して配置
+///
+/// 画像内に収まらない語は省く
+fn layout(words: &[WordCount], width: f64, height: f64) -> Vec<PlacedWord> {
+    let (Some(max), Some(min)) = (words.first(), words.last()) else {
+        return Vec::new();
+    };
+    let (max, min) = (max.count as f64, min.count as f64);
+
+    let mut placed: Vec<(PlacedWord, [f64; 4])> = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let ratio = if max > min {
+            (word.count as f64 - min) / (max - min)
+        } else {
+            1.0
+        };
+        let font_size = MIN_FONT_SIZE + (MAX_FONT_SIZE - MIN_FONT_SIZE) * ratio.sqrt();
+        let (w, h) = (text_width(&word.word, font_size), font_size);
+
+        for step in 0..SPIRAL_STEPS {
+            let t = step as f64 * 0.1;
+            let radius = 3.0 * t;
+            let x = width / 2.0 + radius * t.cos();
+            let y = height / 2.0 + radius * t.sin() * (height / width);
+            let rect = [x - w / 2.0, y - h / 2.0, x + w / 2.0, y + h / 2.0];
+
+            let inside = rect[0] >= 0.0 && rect[1] >= 0.0 && rect[2] <= width && rect[3] <= height;
+            if inside && !placed.iter().any(|(_, other)| overlaps(&rect, other)) {
+                placed.push((
+                    PlacedWord {
+                        text: word.word.clone(),
+                        x,
+                        y,
+                        font_size,
+                        color: PALETTE[i % PALETTE.len()],
+                    },
+                    rect,
+                ));
+                break;
+            }
+        }
+    }
+
+    placed.into_iter().map(|(word, _)| word).collect()
+}
+
+/// 矩形 `[左, 上, 右, 下]` が余白込みで重なるか判定
+fn overlaps(a: &[f64; 4], b: &[f64; 4]) -> bool {
+    a[0] < b[2] + MARGIN && b[0] < a[2] + MARGIN && a[1] < b[3] + MARGIN && b[1] < a[3] + MARGIN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tokenize_by_character_class() {
+        let tokens = Tokenizer::CharacterClass
+            .tokenize("設計レビューの資料をGitHubで確認した。PR #128 の Review");
+        assert_eq!(
+            tokens,
+            vec!["設計", "レビュー", "資料", "github", "確認", "pr", "review"]
+        );
+    }
+
+    /// 最小限のIPADIC形式の辞書をビルドする
+    fn build_dictionary(dir: &Path) -> std::path::PathBuf {
+        use lindera::dictionary::{DictionaryBuilder, Metadata};
+
+        let source = dir.join("source");
+        let output = dir.join("dictionary");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(
+            source.join("char.def"),
+            "DEFAULT 0 1 0\nSPACE 0 1 0\nKANJI 0 0 2\nKATAKANA 1 1 0\nHIRAGANA 0 1 0\nALPHA 1 1 0\nNUMERIC 1 1 0\n\
+             0x0020 SPACE\n0x0030..0x0039 NUMERIC\n0x0041..0x005A ALPHA\n0x0061..0x007A ALPHA\n\
+             0x3041..0x309F HIRAGANA\n0x30A1..0x30FF KATAKANA\n0x4E00..0x9FFF KANJI\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("unk.def"),
+            "DEFAULT,0,0,1000,記号,一般,*,*,*,*,*\nSPACE,0,0,1000,記号,空白,*,*,*,*,*\n\
+             KANJI,0,0,1000,名詞,一般,*,*,*,*,*\nKATAKANA,0,0,1000,名詞,一般,*,*,*,*,*\n\
+             HIRAGANA,0,0,1000,記号,一般,*,*,*,*,*\nALPHA,0,0,1000,名詞,固有名詞,組織,*,*,*,*\n\
+             NUMERIC,0,0,1000,名詞,数,*,*,*,*,*\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("lex.csv"),
+            "設計,0,0,100,名詞,サ変接続,*,*,*,*,設計,セッケイ,セッケイ\n\
+             資料,0,0,100,名詞,一般,*,*,*,*,資料,シリョウ,シリョー\n\
+             レビュー,0,0,100,名詞,サ変接続,*,*,*,*,レビュー,レビュー,レビュー\n\
+             確認,0,0,100,名詞,サ変接続,*,*,*,*,確認,カクニン,カクニン\n\
+             こと,0,0,100,名詞,非自立,一般,*,*,*,こと,コト,コト\n\
+             の,0,0,100,助詞,連体化,*,*,*,*,の,ノ,ノ\n\
+             を,0,0,100,助詞,格助詞,一般,*,*,*,を,ヲ,ヲ\n\
+             で,0,0,100,助詞,格助詞,一般,*,*,*,で,デ,デ\n\
+             し,0,0,100,動詞,自立,*,*,サ変・スル,連用形,する,シ,シ\n\
+             た,0,0,100,助動詞,*,*,*,特殊・タ,基本形,た,タ,タ\n",
+        )
+        .unwrap();
+        fs::write(source.join("matrix.def"), "1 1\n0 0 0\n").unwrap();
+
+        DictionaryBuilder::new(Metadata::default())
+            .build_dictionary(&source, &output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_tokenize_morphologically() {
+        let temp_dir = TempDir::new().unwrap();
+        let tokenizer = Tokenizer::load(&build_dictionary(temp_dir.path())).unwrap();
+
+        // 文字種では1語になる「設計資料」も語ごとに分け、非自立の名詞（こと）は除く
+        let tokens = tokenizer.tokenize("設計資料をGitHubでレビューしたこと");
+        assert_eq!(tokens, vec!["設計", "資料", "github", "レビュー"]);
+
+        assert!(matches!(
+            Tokenizer::load(&temp_dir.path().join("missing")),
+            Err(WordCloudError::DictionaryLoadFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_word_counts_per_text() {
+        let texts = ["設計 設計 設計", "設計 レビュー", "レビュー the 2025"];
+        let cloud = WordCloud::from_texts(texts, 10, &Tokenizer::CharacterClass);
+        assert_eq!(
+            cloud.words(),
+            &[
+                WordCount {
+                    word: "レビュー".to_string(),
+                    count: 2
+                },
+                WordCount {
+                    word: "設計".to_string(),
+                    count: 2
+                },
+            ]
+        );
+        assert_eq!(
+            WordCloud::from_texts(texts, 1, &Tokenizer::CharacterClass)
+                .words()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_layout_does_not_overlap() {
+        let words: Vec<WordCount> = (0..40)
+            .map(|i| WordCount {
+                word: format!("word{}", i),
+                count: 40 - i,
+            })
+            .collect();
+        let placed = layout(&words, WIDTH, HEIGHT);
+        assert!(!placed.is_empty());
+        assert_eq!(placed[0].font_size, MAX_FONT_SIZE);
+
+        let rects: Vec<[f64; 4]> = placed
+            .iter()
+            .map(|p| {
+                let w = text_width(&p.text, p.font_size);
+                [
+                    p.x - w / 2.0,
+                    p.y - p.font_size / 2.0,
+                    p.x + w / 2.0,
+                    p.y + p.font_size / 2.0,
+                ]
+            })
+            .collect();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a[0] >= 0.0 && a[2] <= WIDTH && a[1] >= 0.0 && a[3] <= HEIGHT);
+            for b in &rects[i + 1..] {
+                assert!(!overlaps(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_save_svg_and_png() {
+        let temp_dir = TempDir::new().unwrap();
+        let cloud = WordCloud::from_texts(["設計レビュー"], 10, &Tokenizer::CharacterClass);
+
+        let svg_path = temp_dir.path().join("cloud.svg");
+        cloud.save(&svg_path).unwrap();
+        let svg = fs::read_to_string(&svg_path).unwrap();
+        assert!(svg.contains(">設計</text>"));
+
+        let png_path = temp_dir.path().join("cloud.png");
+        cloud.save(&png_path).unwrap();
+        assert!(fs::read(&png_path).unwrap().starts_with(b"\x89PNG"));
+
+        assert!(matches!(
+            cloud.save(&temp_dir.path().join("cloud.gif")),
+            Err(WordCloudError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,453 @@
+//! 工数連携モジュール - チケットごとの作業時間の集計とJira/Linearへの登録
+
+use crate::config::WorklogConfig;
+use crate::database::{parse_timestamp, Database};
+use crate::error::{ReportError, WorklogError};
+use crate::report::{format_duration, Report};
+use crate::standup::split_project;
+use base64::Engine;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// LinearのGraphQL APIのURL
+pub const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+/// 登録する最小の時間（秒、Jiraは1分未満の工数を受け付けない）
+const MIN_SECONDS: u64 = 60;
+
+/// APIリクエストのタイムアウト
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 登録した工数に付けるコメント
+const COMMENT: &str = "habit-trackerで計測した作業時間";
+
+/// 工数の登録先（config: `[worklog] service`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorklogService {
+    /// JiraのWorklog API
+    Jira,
+    /// Linear（工数の項目がないため、作業時間をコメントとして記録する）
+    Linear,
+}
+
+/// チケットごとの1日分の作業時間
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Worklog {
+    pub ticket: String,
+    /// 作業時間（秒、分単位に切り捨て）
+    pub seconds: u64,
+    /// 最初に作業した時刻
+    pub started_at: String,
+}
+
+/// 工数の登録結果
+#[derive(Debug, Serialize)]
+pub struct WorklogPush {
+    pub ticket: String,
+    /// 今回登録した時間（秒、前回までに登録した分を除く）
+    pub seconds: u64,
+    /// その日に登録した時間の合計（秒）
+    pub total_seconds: u64,
+}
+
+/// キャプチャのチケットIDの判定
+///
+/// ウィンドウタイトルからチケットIDを抜き出し、なければプロジェクト
+/// （エディタのワークスペース名、それ以外はアプリ名）の対応表で決める
+#[derive(Debug, Clone)]
+pub struct TicketMatcher {
+    pattern: Regex,
+    mappings: BTreeMap<String, String>,
+}
+
+impl TicketMatcher {
+    /// 正規表現とプロジェクト→チケットIDの対応表から作成（不正なパターンならそのエラー）
+    pub fn new(pattern: &str, mappings: BTreeMap<String, String>) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Regex::new(pattern)?,
+            mappings,
+        })
+    }
+
+    /// キャプチャのチケットID（判定できなければ `None`）
+    pub fn find(&self, app: &str, title: &str, working_directory: Option<&str>) -> Option<String> {
+        if let Some(ticket) = self.pattern.find(title) {
+            return Some(ticket.as_str().to_string());
+        }
+        let (project, _) = split_project(app, title, working_directory);
+        self.mappings
+            .get(&project)
+            .or_else(|| self.mappings.get(app))
+            .cloned()
+    }
+}
+
+/// 指定日のチケットごとの作業時間を集計（最初に作業した順）
+///
+/// プライベートブラウジングのキャプチャとチケットを判定できないキャプチャは除き、
+/// 1分に満たないチケットは含めない
+pub fn collect(
+    report: &Report,
+    date: &str,
+    matcher: &TicketMatcher,
+) -> Result<Vec<Worklog>, ReportError> {
+    let mut worklogs: Vec<Worklog> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    report.for_each_timed_capture(date, date, |capture, seconds| {
+        if capture.is_private {
+            return;
+        }
+        let Some(ticket) = matcher.find(
+            &capture.active_app,
+            &capture.window_title,
+            capture.working_directory.as_deref(),
+        ) else {
+            return;
+        };
+        let i = *index.entry(ticket.clone()).or_insert_with(|| {
+            worklogs.push(Worklog {
+                ticket,
+                seconds: 0,
+                started_at: capture.captured_at.clone(),
+            });
+            worklogs.len() - 1
+        });
+        worklogs[i].seconds += seconds;
+    })?;
+
+    for worklog in &mut worklogs {
+        worklog.seconds -= worklog.seconds % 60;
+    }
+    worklogs.retain(|worklog| worklog.seconds >= MIN_SECONDS);
+    Ok(worklogs)
+}
+
+/// 前回までに登録した分を除いて工数を登録し、登録した結果を返す
+///
+/// 登録済みの時間はDBに記録するため、同じ日に何度実行しても二重には登録しない。
+/// `dry_run` ならAPIを呼ばず、DBにも記録しない
+pub fn push(
+    db: &Database,
+    client: &WorklogClient,
+    date: &str,
+    worklogs: &[Worklog],
+    dry_run: bool,
+) -> Result<Vec<WorklogPush>, WorklogError> {
+    let mut pushes = Vec::new();
+    for worklog in worklogs {
+        let pushed = db.get_pushed_worklog_seconds(date, &worklog.ticket)?;
+        let seconds = worklog.seconds.saturating_sub(pushed);
+        if seconds < MIN_SECONDS {
+            continue;
+        }
+        if !dry_run {
+            client.add(&worklog.ticket, seconds, &worklog.started_at)?;
+            db.set_pushed_worklog_seconds(date, &worklog.ticket, pushed + seconds)?;
+        }
+        pushes.push(WorklogPush {
+            ticket: worklog.ticket.clone(),
+            seconds,
+            total_seconds: pushed + seconds,
+        });
+    }
+    Ok(pushes)
+}
+
+/// Jira/LinearのAPIクライアント
+pub struct WorklogClient {
+    config: WorklogConfig,
+    agent: ureq::Agent,
+}
+
+impl WorklogClient {
+    /// 新しいWorklogClientを作成
+    pub fn new(config: WorklogConfig) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        Self { config, agent }
+    }
+
+    /// チケットに工数を登録
+    pub fn add(&self, ticket: &str, seconds: u64, started_at: &str) -> Result<(), WorklogError> {
+        let failed = |reason: String| WorklogError::RequestFailed {
+            ticket: ticket.to_string(),
+            reason,
+        };
+        match self.config.service {
+            WorklogService::Jira => self.add_jira(ticket, seconds, started_at),
+            WorklogService::Linear => self.add_linear(ticket, seconds),
+        }
+        .map_err(failed)
+    }
+
+    /// JiraのWorklog API（`POST /rest/api/3/issue/{チケット}/worklog`）で登録
+    fn add_jira(&self, ticket: &str, seconds: u64, started_at: &str) -> Result<(), String> {
+        let url = format!(
+            "{}/rest/api/3/issue/{}/worklog",
+            self.config
+                .url
+                .as_deref()
+                .unwrap_or_default()
+                .trim_end_matches('/'),
+            ticket
+        );
+        let credentials = format!(
+            "{}:{}",
+            self.config.email.as_deref().unwrap_or_default(),
+            self.config.api_token
+        );
+        let authorization = format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        );
+        // Jiraは `2025-01-10T09:00:00.000+0900` 形式の開始時刻を受け付ける
+        let started = parse_timestamp(started_at)
+            .map(|timestamp| timestamp.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string())
+            .ok_or_else(|| format!("開始時刻 {} を解釈できません", started_at))?;
+        let body = json!({
+            "timeSpentSeconds": seconds,
+            "started": started,
+            "comment": {
+                "type": "doc",
+                "version": 1,
+                "content": [{
+                    "type": "paragraph",
+                    "content": [{ "type": "text", "text": COMMENT }],
+                }],
+            },
+        });
+
+        self.agent
+            .post(&url)
+            .set("Authorization", &authorization)
+            .send_json(body)
+            .map_err(request_error)?;
+        Ok(())
+    }
+
+    /// LinearのGraphQL APIで、作業時間をチケットのコメントとして登録
+    fn add_linear(&self, ticket: &str, seconds: u64) -> Result<(), String> {
+        let issue = self.linear_query(
+            "query($id: String!) { issue(id: $id) { id } }",
+            json!({ "id": ticket }),
+        )?;
+        let issue_id = issue["issue"]["id"]
+            .as_str()
+            .ok_or_else(|| "チケットが見つかりません".to_string())?;
+        let body = format!("{}: {}", COMMENT, format_duration(seconds));
+        let comment = self.linear_query(
+            "mutation($issueId: String!, $body: String!) { commentCreate(input: { issueId: $issueId, body: $body }) { success } }",
+            json!({ "issueId": issue_id, "body": body }),
+        )?;
+        if comment["commentCreate"]["success"].as_bool() != Some(true) {
+            return Err("コメントを作成できませんでした".to_string());
+        }
+        Ok(())
+    }
+
+    /// GraphQLのクエリを実行し、`data` を返す
+    fn linear_query(&self, query: &str, variables: Value) -> Result<Value, String> {
+        let url = self.config.url.as_deref().unwrap_or(LINEAR_API_URL);
+        let response: Value = self
+            .agent
+            .post(url)
+            .set("Authorization", &self.config.api_token)
+            .send_json(json!({ "query": query, "variables": variables }))
+            .map_err(request_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+        // GraphQLのエラーはステータス200で返る
+        if let Some(message) = response["errors"][0]["message"].as_str() {
+            return Err(message.to_string());
+        }
+        Ok(response["data"].clone())
+    }
+}
+
+/// APIリクエストのエラーを、レスポンスの本文があればそれを含めて説明に変換
+fn request_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(status, response) => {
+            let body = response.into_string().unwrap_or_default();
+            format!("HTTP {}: {}", status, body.trim())
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::CaptureRecord;
+    use std::thread;
+    use tempfile::TempDir;
+    use tiny_http::{Response, Server};
+
+    fn matcher() -> TicketMatcher {
+        TicketMatcher::new(
+            WorklogConfig::DEFAULT_TICKET_PATTERN,
+            BTreeMap::from([
+                ("habit-tracker".to_string(), "HT-1".to_string()),
+                ("Slack".to_string(), "OPS-2".to_string()),
+            ]),
+        )
+        .unwrap()
+    }
+
+    fn config(service: WorklogService, url: String) -> WorklogConfig {
+        WorklogConfig {
+            service,
+            url: Some(url),
+            email: Some("me@example.com".to_string()),
+            api_token: "token".to_string(),
+            ticket_pattern: WorklogConfig::DEFAULT_TICKET_PATTERN.to_string(),
+            mappings: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_ticket() {
+        let matcher = matcher();
+        assert_eq!(
+            matcher.find(
+                "Google Chrome",
+                "[PROJ-123] ログイン画面の修正 - Jira",
+                None
+            ),
+            Some("PROJ-123".to_string())
+        );
+        assert_eq!(
+            matcher.find("Code", "report.rs — habit-tracker", None),
+            Some("HT-1".to_string())
+        );
+        assert_eq!(
+            matcher.find("Slack", "general - Slack", None),
+            Some("OPS-2".to_string())
+        );
+        // ターミナルはカレントディレクトリのプロジェクト
+        assert_eq!(
+            matcher.find("ターミナル", "zsh", Some("/tmp/habit-tracker")),
+            Some("HT-1".to_string())
+        );
+        assert_eq!(matcher.find("Finder", "Downloads", None), None);
+    }
+
+    #[test]
+    fn test_collect_and_push() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let titles = [
+            ("09:00", "PROJ-1 ログイン - Jira", false),
+            ("09:01", "report.rs — habit-tracker", false),
+            ("09:02", "PROJ-1 ログイン - Jira", false),
+            ("09:03", "PROJ-9 秘密", true),
+            ("09:04", "Downloads", false),
+        ];
+        for (time, title, is_private) in titles {
+            db.insert_capture(&CaptureRecord {
+                id: None,
+                captured_at: format!("2025-01-10T{}:00+09:00", time),
+                image_path: None,
+                active_app: "Code".to_string(),
+                window_title: title.to_string(),
+                is_paused: false,
+                is_private,
+                ocr_text: None,
+                interval_seconds: None,
+                hostname: None,
+                tracker_version: None,
+                bundle_id: None,
+                activity_type: None,
+                working_directory: None,
+            })
+            .unwrap();
+        }
+        let report = Report::new(db, 60);
+        let worklogs = collect(&report, "2025-01-10", &matcher()).unwrap();
+        assert_eq!(
+            worklogs,
+            vec![
+                Worklog {
+                    ticket: "PROJ-1".to_string(),
+                    seconds: 120,
+                    started_at: "2025-01-10T09:00:00+09:00".to_string(),
+                },
+                Worklog {
+                    ticket: "HT-1".to_string(),
+                    seconds: 60,
+                    started_at: "2025-01-10T09:01:00+09:00".to_string(),
+                },
+            ]
+        );
+
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                let mut request = server.recv().unwrap();
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let authorization = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Authorization"))
+                    .map(|h| h.value.to_string());
+                requests.push((request.url().to_string(), authorization, body));
+                request
+                    .respond(Response::from_string("{}").with_status_code(201))
+                    .unwrap();
+            }
+            requests
+        });
+
+        let client = WorklogClient::new(config(WorklogService::Jira, url));
+        let db = report.database();
+        // dry-runは登録しない
+        assert_eq!(
+            push(db, &client, "2025-01-10", &worklogs, true)
+                .unwrap()
+                .len(),
+            2
+        );
+        let pushes = push(db, &client, "2025-01-10", &worklogs, false).unwrap();
+        assert_eq!(pushes.len(), 2);
+        // 登録済みの分は二重に登録しない
+        assert!(push(db, &client, "2025-01-10", &worklogs, false)
+            .unwrap()
+            .is_empty());
+
+        let requests = handle.join().unwrap();
+        let (path, authorization, body) = &requests[0];
+        assert_eq!(path, "/rest/api/3/issue/PROJ-1/worklog");
+        assert_eq!(
+            authorization.as_deref(),
+            Some("Basic bWVAZXhhbXBsZS5jb206dG9rZW4=")
+        );
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["timeSpentSeconds"], 120);
+        assert_eq!(body["started"], "2025-01-10T09:00:00.000+0900");
+        assert_eq!(requests[1].0, "/rest/api/3/issue/HT-1/worklog");
+    }
+
+    #[test]
+    fn test_request_failed() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/graphql", server.server_addr().to_ip().unwrap());
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            let response = r#"{"errors":[{"message":"Entity not found"}]}"#;
+            request.respond(Response::from_string(response)).unwrap();
+        });
+
+        let client = WorklogClient::new(config(WorklogService::Linear, url));
+        let err = client
+            .add("ENG-1", 600, "2025-01-10T09:00:00+09:00")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "ENG-1の工数の登録に失敗: Entity not found");
+        handle.join().unwrap();
+    }
+}